@@ -0,0 +1,12 @@
+-- Daily snapshots of the published rota, used by GET /api/rota/diff
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/002_rota_snapshots.sql
+
+CREATE TABLE IF NOT EXISTS "RotaSnapshots" (
+    id BIGSERIAL PRIMARY KEY,
+    role_id INT4 NOT NULL REFERENCES "Roles" (id) ON DELETE CASCADE,
+    snapshot_date DATE NOT NULL,
+    taken_at TIMESTAMP(6) NOT NULL DEFAULT NOW(),
+    -- Published shifts dated on/after snapshot_date: [{uuid, date, label, start, end, user_profile_id}]
+    shifts JSONB NOT NULL DEFAULT '[]'::jsonb,
+    UNIQUE (role_id, snapshot_date)
+);
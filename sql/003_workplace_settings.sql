@@ -0,0 +1,14 @@
+-- Per-workplace settings (one row per workplace, defaults apply when no row exists)
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/003_workplace_settings.sql
+
+CREATE TABLE IF NOT EXISTS "WorkplaceSettings" (
+    workplace_id INT4 PRIMARY KEY REFERENCES "Workplaces" (id) ON DELETE CASCADE,
+    default_shift_start TIME,
+    default_shift_end TIME,
+    marketplace_enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    pin_length INT2 NOT NULL DEFAULT 5 CHECK (pin_length BETWEEN 4 AND 8),
+    updated_at TIMESTAMP(6) NOT NULL DEFAULT NOW()
+);
+
+-- auth_pin was varchar(5); widen it so PIN policies up to 8 digits fit
+ALTER TABLE "Users" ALTER COLUMN auth_pin TYPE VARCHAR(8);
@@ -0,0 +1,15 @@
+-- In-app notifications for staff (e.g. marketplace updates), read via GET /api/notifications
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/004_notifications.sql
+
+CREATE TABLE IF NOT EXISTS "Notifications" (
+    id BIGSERIAL PRIMARY KEY,
+    user_profile_id INT4 NOT NULL REFERENCES "Users" (user_profile_id) ON DELETE CASCADE,
+    kind VARCHAR(50) NOT NULL,
+    message TEXT NOT NULL,
+    -- Kind-specific context, e.g. {"shift_request_id": 12}
+    payload JSONB NOT NULL DEFAULT '{}'::jsonb,
+    created_at TIMESTAMP(6) NOT NULL DEFAULT NOW(),
+    read_at TIMESTAMP(6)
+);
+
+CREATE INDEX IF NOT EXISTS idx_notifications_user_created ON "Notifications" (user_profile_id, created_at DESC);
@@ -0,0 +1,19 @@
+-- Record the acting user on ShiftAudit rows, taken from the app.current_user session setting
+-- The API sets it per transaction (see db::begin_as_user); rows written outside the API keep acted_by NULL
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/005_audit_actor.sql
+
+ALTER TABLE "ShiftAudit" ADD COLUMN IF NOT EXISTS acted_by INT4;
+
+CREATE OR REPLACE FUNCTION shift_audit_set_actor() RETURNS trigger AS $$
+BEGIN
+    IF NEW.acted_by IS NULL THEN
+        NEW.acted_by := NULLIF(current_setting('app.current_user', true), '')::INT4;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_shift_audit_set_actor ON "ShiftAudit";
+CREATE TRIGGER trg_shift_audit_set_actor
+    BEFORE INSERT ON "ShiftAudit"
+    FOR EACH ROW EXECUTE FUNCTION shift_audit_set_actor();
@@ -0,0 +1,19 @@
+-- Log of reads of personal data (staff profiles, contact details, leave), queried via GET /api/audit/access-log
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/006_data_access_log.sql
+
+CREATE TABLE IF NOT EXISTS "DataAccessLog" (
+    id BIGSERIAL PRIMARY KEY,
+    -- NULL for unauthenticated reads
+    viewer_id INT4,
+    -- Profile whose data was returned; NULL for list endpoints covering many profiles
+    subject_id INT4,
+    method VARCHAR(10) NOT NULL,
+    route VARCHAR(200) NOT NULL,
+    path TEXT NOT NULL,
+    status INT2 NOT NULL,
+    created_at TIMESTAMP(6) NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_data_access_log_viewer ON "DataAccessLog" (viewer_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_data_access_log_subject ON "DataAccessLog" (subject_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_data_access_log_created ON "DataAccessLog" (created_at DESC);
@@ -0,0 +1,20 @@
+-- Reusable week patterns of shift templates, rolled across a month via POST /api/patterns/{id}/apply
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/007_rota_patterns.sql
+
+CREATE TABLE IF NOT EXISTS "RotaPatterns" (
+    id SERIAL PRIMARY KEY,
+    role_id INT4 NOT NULL REFERENCES "Roles" (id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    created_at TIMESTAMP(6) NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_rota_patterns_role_id ON "RotaPatterns" (role_id);
+
+CREATE TABLE IF NOT EXISTS "RotaPatternEntries" (
+    id SERIAL PRIMARY KEY,
+    pattern_id INT4 NOT NULL REFERENCES "RotaPatterns" (id) ON DELETE CASCADE,
+    -- ISO weekday: 1 = Monday ... 7 = Sunday
+    weekday INT2 NOT NULL CHECK (weekday BETWEEN 1 AND 7),
+    template_id INT4 NOT NULL REFERENCES "ShiftTemplates" (id) ON DELETE CASCADE,
+    UNIQUE (pattern_id, weekday, template_id)
+);
@@ -0,0 +1,8 @@
+-- Blind index for encrypted secondary emails (exact-match lookups without decrypting)
+-- tel and secondary_emails keep their TEXT[] type; encrypted elements are stored as "enc:v1:<base64>"
+-- After setting PII_ENCRYPTION_KEY, run `edrota4-axum rekey-pii` once to encrypt existing rows
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/008_pii_encryption.sql
+
+ALTER TABLE "Users" ADD COLUMN IF NOT EXISTS secondary_email_index TEXT[];
+
+CREATE INDEX IF NOT EXISTS idx_users_secondary_email_index ON "Users" USING GIN (secondary_email_index);
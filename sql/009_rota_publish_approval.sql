@@ -0,0 +1,24 @@
+-- Optional per-role sign-off before a month's shifts may be published
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/009_rota_publish_approval.sql
+
+ALTER TABLE "Roles" ADD COLUMN IF NOT EXISTS publish_requires_approval BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE "UserRoles" ADD COLUMN IF NOT EXISTS can_approve_rota BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE IF NOT EXISTS "RotaPublishApprovals" (
+    id SERIAL PRIMARY KEY,
+    role_id INT4 NOT NULL REFERENCES "Roles" (id) ON DELETE CASCADE,
+    year INT4 NOT NULL,
+    month INT4 NOT NULL CHECK (month BETWEEN 1 AND 12),
+    status VARCHAR(20) NOT NULL DEFAULT 'PENDING' CHECK (status IN ('PENDING', 'APPROVED', 'REJECTED')),
+    submitted_by INT4 NOT NULL REFERENCES "Users" (user_profile_id),
+    submitted_at TIMESTAMP(6) NOT NULL DEFAULT NOW(),
+    submit_comment TEXT,
+    decided_by INT4 REFERENCES "Users" (user_profile_id),
+    decided_at TIMESTAMP(6),
+    decision_comment TEXT
+);
+
+-- At most one open or approved request per role and month; rejected ones can be resubmitted
+CREATE UNIQUE INDEX IF NOT EXISTS idx_rota_publish_approvals_active
+    ON "RotaPublishApprovals" (role_id, year, month)
+    WHERE status IN ('PENDING', 'APPROVED');
@@ -0,0 +1,18 @@
+-- Dates locums advertise as available, consumed when an admin assigns them to a shift
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/010_locum_availability.sql
+
+CREATE TABLE IF NOT EXISTS "LocumAvailability" (
+    id SERIAL PRIMARY KEY,
+    user_profile_id INT4 NOT NULL REFERENCES "Users" (user_profile_id) ON DELETE CASCADE,
+    role_id INT4 NOT NULL REFERENCES "Roles" (id) ON DELETE CASCADE,
+    date DATE NOT NULL,
+    notes TEXT,
+    created_at TIMESTAMP(6) NOT NULL DEFAULT NOW(),
+    assigned_shift_id UUID REFERENCES "Shifts" (uuid) ON DELETE SET NULL,
+    assigned_at TIMESTAMP(6),
+    UNIQUE (user_profile_id, role_id, date)
+);
+
+CREATE INDEX IF NOT EXISTS idx_locum_availability_open
+    ON "LocumAvailability" (role_id, date)
+    WHERE assigned_shift_id IS NULL;
@@ -0,0 +1,34 @@
+-- Approvers delegate marketplace approval to a colleague while away; decisions record who they acted for
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/011_approval_delegations.sql
+
+CREATE TABLE IF NOT EXISTS "ApprovalDelegations" (
+    id SERIAL PRIMARY KEY,
+    delegator_id INT4 NOT NULL REFERENCES "Users" (user_profile_id) ON DELETE CASCADE,
+    delegate_id INT4 NOT NULL REFERENCES "Users" (user_profile_id) ON DELETE CASCADE,
+    start_date DATE NOT NULL,
+    end_date DATE NOT NULL,
+    reason TEXT,
+    created_at TIMESTAMP(6) NOT NULL DEFAULT NOW(),
+    CHECK (end_date >= start_date),
+    CHECK (delegator_id <> delegate_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_approval_delegations_delegate
+    ON "ApprovalDelegations" (delegate_id, start_date, end_date);
+
+ALTER TABLE "ShiftRequests" ADD COLUMN IF NOT EXISTS resolved_as_delegate_of INT4 REFERENCES "Users" (user_profile_id);
+
+-- Shift changes made by a delegate carry the delegator, from the app.acting_for session setting
+ALTER TABLE "ShiftAudit" ADD COLUMN IF NOT EXISTS acted_as_delegate_of INT4;
+
+CREATE OR REPLACE FUNCTION shift_audit_set_actor() RETURNS trigger AS $$
+BEGIN
+    IF NEW.acted_by IS NULL THEN
+        NEW.acted_by := NULLIF(current_setting('app.current_user', true), '')::INT4;
+    END IF;
+    IF NEW.acted_as_delegate_of IS NULL THEN
+        NEW.acted_as_delegate_of := NULLIF(current_setting('app.acting_for', true), '')::INT4;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
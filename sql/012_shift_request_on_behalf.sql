@@ -0,0 +1,4 @@
+-- Admins can raise marketplace requests for staff (e.g. someone off sick); record who actually created them
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/012_shift_request_on_behalf.sql
+
+ALTER TABLE "ShiftRequests" ADD COLUMN IF NOT EXISTS created_by_admin_id INT4 REFERENCES "Users" (user_profile_id);
@@ -0,0 +1,13 @@
+-- Optional TOTP second factor for super admins; high-risk endpoints require a fresh X-MFA-Code once enabled
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/013_super_admin_mfa.sql
+
+CREATE TABLE IF NOT EXISTS "SuperAdminMfa" (
+    user_profile_id INT4 PRIMARY KEY REFERENCES "Users" (user_profile_id) ON DELETE CASCADE,
+    -- Base32 shared secret, encrypted with PII_ENCRYPTION_KEY ("enc:v1:...")
+    secret TEXT NOT NULL,
+    -- NULL until the first code is confirmed
+    enabled_at TIMESTAMP(6),
+    -- Last accepted time step; a code is never accepted twice
+    last_used_step INT8,
+    created_at TIMESTAMP(6) NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,27 @@
+-- Announcements replace using role-wide diary entries as notices: targeted, scheduled, pinnable, read-tracked
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/014_announcements.sql
+
+CREATE TABLE IF NOT EXISTS "Announcements" (
+    id SERIAL PRIMARY KEY,
+    title TEXT NOT NULL,
+    body TEXT NOT NULL,
+    -- Both NULL = everyone; role_id wins over workplace_id when set
+    workplace_id INT4 REFERENCES "Workplaces" (id) ON DELETE CASCADE,
+    role_id INT4 REFERENCES "Roles" (id) ON DELETE CASCADE,
+    pinned BOOLEAN NOT NULL DEFAULT FALSE,
+    publish_at TIMESTAMP(6) NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMP(6),
+    created_by INT4 NOT NULL REFERENCES "Users" (user_profile_id),
+    created_at TIMESTAMP(6) NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP(6) NOT NULL DEFAULT NOW(),
+    CHECK (expires_at IS NULL OR expires_at > publish_at)
+);
+
+CREATE INDEX IF NOT EXISTS idx_announcements_live ON "Announcements" (publish_at, expires_at);
+
+CREATE TABLE IF NOT EXISTS "AnnouncementReads" (
+    announcement_id INT4 NOT NULL REFERENCES "Announcements" (id) ON DELETE CASCADE,
+    user_profile_id INT4 NOT NULL REFERENCES "Users" (user_profile_id) ON DELETE CASCADE,
+    read_at TIMESTAMP(6) NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (announcement_id, user_profile_id)
+);
@@ -0,0 +1,15 @@
+-- Speed up GET /api/audit on large ShiftAudit tables: the staff and time-off ids inside the old/new JSON
+-- become generated columns (joined without per-row casts) and filters/ordering get matching indexes.
+-- Adding STORED columns rewrites the table; run outside busy hours.
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/015_audit_query_performance.sql
+
+ALTER TABLE "ShiftAudit"
+    ADD COLUMN IF NOT EXISTS old_user_profile_id INT4 GENERATED ALWAYS AS ((old->>'user_profile_id')::int4) STORED,
+    ADD COLUMN IF NOT EXISTS new_user_profile_id INT4 GENERATED ALWAYS AS ((new->>'user_profile_id')::int4) STORED,
+    ADD COLUMN IF NOT EXISTS old_time_off INT4 GENERATED ALWAYS AS ((old->>'time_off')::int4) STORED,
+    ADD COLUMN IF NOT EXISTS new_time_off INT4 GENERATED ALWAYS AS ((new->>'time_off')::int4) STORED;
+
+-- Newest-first paging, optionally narrowed to a role and date range
+CREATE INDEX CONCURRENTLY IF NOT EXISTS idx_shift_audit_created_at ON "ShiftAudit" (created_at DESC);
+CREATE INDEX CONCURRENTLY IF NOT EXISTS idx_shift_audit_role_date_created
+    ON "ShiftAudit" (role_id, date, created_at DESC);
@@ -0,0 +1,8 @@
+-- Retention job support: audit rows past the retention period keep the change but lose who made it
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/016_data_retention.sql
+
+ALTER TABLE "ShiftAudit" ALTER COLUMN created_by DROP NOT NULL;
+ALTER TABLE "ShiftAudit" ADD COLUMN IF NOT EXISTS anonymised_at TIMESTAMP(6);
+
+CREATE INDEX IF NOT EXISTS idx_diary_deleted_date ON "Diary" (date) WHERE deleted;
+CREATE INDEX IF NOT EXISTS idx_shift_audit_not_anonymised ON "ShiftAudit" (created_at) WHERE anonymised_at IS NULL;
@@ -0,0 +1,8 @@
+-- Per-user shift reassignment history (GET /api/users/{id}/shift-changes): find audit rows by the
+-- staff member a shift moved from or to. Needs the generated columns from 015.
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/017_shift_audit_user_history.sql
+
+CREATE INDEX CONCURRENTLY IF NOT EXISTS idx_shift_audit_old_user_date
+    ON "ShiftAudit" (old_user_profile_id, date) WHERE old_user_profile_id IS NOT NULL;
+CREATE INDEX CONCURRENTLY IF NOT EXISTS idx_shift_audit_new_user_date
+    ON "ShiftAudit" (new_user_profile_id, date) WHERE new_user_profile_id IS NOT NULL;
@@ -0,0 +1,13 @@
+-- Cover board: shifts admins flag as needing cover, plus unassigned published shifts coming up soon
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/018_cover_board.sql
+
+ALTER TABLE "Shifts" ADD COLUMN IF NOT EXISTS needs_cover BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Days ahead in which unassigned published shifts appear on the board without a flag (0 = flagged only)
+ALTER TABLE "WorkplaceSettings" ADD COLUMN IF NOT EXISTS cover_window_days INT2 NOT NULL DEFAULT 7
+    CHECK (cover_window_days BETWEEN 0 AND 90);
+
+CREATE INDEX IF NOT EXISTS idx_shifts_cover_board
+    ON "Shifts" (role_id, date) WHERE needs_cover OR user_profile_id IS NULL;
+CREATE INDEX IF NOT EXISTS idx_shift_requests_pickup_pending
+    ON "ShiftRequests" (shift_id) WHERE type = 'PICKUP' AND status = 'PENDING_APPROVAL';
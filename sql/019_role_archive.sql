@@ -0,0 +1,4 @@
+-- Archived roles: kept with their history but hidden from role pickers (GET /api/roles?includeArchived=true shows them)
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/019_role_archive.sql
+
+ALTER TABLE "Roles" ADD COLUMN IF NOT EXISTS archived BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,12 @@
+-- PIN policy: per-workplace complexity and expiry, plus a must-change flag for admin-set PINs
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/020_pin_policy.sql
+
+ALTER TABLE "WorkplaceSettings" ADD COLUMN IF NOT EXISTS pin_require_complex BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE "WorkplaceSettings" ADD COLUMN IF NOT EXISTS pin_expiry_days INT2 NOT NULL DEFAULT 0
+    CHECK (pin_expiry_days BETWEEN 0 AND 365);
+
+ALTER TABLE "Users" ADD COLUMN IF NOT EXISTS pin_changed_at TIMESTAMPTZ;
+ALTER TABLE "Users" ADD COLUMN IF NOT EXISTS pin_must_change BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Existing PINs start their expiry period now rather than counting as already expired
+UPDATE "Users" SET pin_changed_at = NOW() WHERE auth_pin IS NOT NULL AND pin_changed_at IS NULL;
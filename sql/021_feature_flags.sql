@@ -0,0 +1,13 @@
+-- Feature flags: soft-launch features per workplace or user cohort without a redeploy
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/021_feature_flags.sql
+
+CREATE TABLE IF NOT EXISTS "FeatureFlags" (
+    key TEXT PRIMARY KEY CHECK (key ~ '^[a-z0-9_]{1,64}$'),
+    description TEXT,
+    enabled BOOLEAN NOT NULL DEFAULT FALSE,
+    workplace_ids INT4[] NOT NULL DEFAULT '{}',
+    user_profile_ids INT4[] NOT NULL DEFAULT '{}',
+    rollout_percent INT2 NOT NULL DEFAULT 0 CHECK (rollout_percent BETWEEN 0 AND 100),
+    updated_by INT4 REFERENCES "Users" (user_profile_id) ON DELETE SET NULL,
+    updated_at TIMESTAMP(6) NOT NULL DEFAULT NOW()
+);
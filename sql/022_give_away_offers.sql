@@ -0,0 +1,14 @@
+-- Give-away auto-matching: eligible staff are notified when a GIVE_AWAY is posted, and who was told is kept for audit
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/022_give_away_offers.sql
+
+-- Staff can stop receiving give-away offers (PUT /api/users/me); they can still browse /api/marketplace/open
+ALTER TABLE "Users" ADD COLUMN IF NOT EXISTS marketplace_offers_opt_out BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE IF NOT EXISTS "ShiftRequestOffers" (
+    shift_request_id INT4 NOT NULL REFERENCES "ShiftRequests" (id) ON DELETE CASCADE,
+    user_profile_id INT4 NOT NULL REFERENCES "Users" (user_profile_id) ON DELETE CASCADE,
+    notified_at TIMESTAMP(6) NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (shift_request_id, user_profile_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_shift_request_offers_user ON "ShiftRequestOffers" (user_profile_id);
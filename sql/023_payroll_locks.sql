@@ -0,0 +1,11 @@
+-- Payroll locks: once payroll has run for a role's month its shifts can no longer be changed or swapped
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/023_payroll_locks.sql
+
+CREATE TABLE IF NOT EXISTS "RotaLocks" (
+    role_id INT4 NOT NULL REFERENCES "Roles" (id) ON DELETE CASCADE,
+    year INT4 NOT NULL,
+    month INT4 NOT NULL CHECK (month BETWEEN 1 AND 12),
+    locked_by INT4 NOT NULL REFERENCES "Users" (user_profile_id),
+    locked_at TIMESTAMP(6) NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (role_id, year, month)
+);
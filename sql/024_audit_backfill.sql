@@ -0,0 +1,9 @@
+-- Audit backfill: entries reconstructed by POST /api/audit/backfill after the shift triggers were off
+-- (e.g. during maintenance) are flagged so the history shows they were inferred, not observed
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/024_audit_backfill.sql
+
+ALTER TABLE "ShiftAudit" ADD COLUMN IF NOT EXISTS backfilled BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Latest audit entry per shift, used to find the last recorded state
+CREATE INDEX CONCURRENTLY IF NOT EXISTS idx_shift_audit_shift_uuid_created
+    ON "ShiftAudit" ((COALESCE(new->>'uuid', old->>'uuid')), created_at DESC);
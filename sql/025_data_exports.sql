@@ -0,0 +1,19 @@
+-- Self-service data exports (subject access requests): the bundle is prepared in the background and
+-- downloaded with its token by the same user until it expires
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/025_data_exports.sql
+
+CREATE TABLE IF NOT EXISTS "DataExports" (
+    token UUID PRIMARY KEY,
+    user_profile_id INT4 NOT NULL REFERENCES "Users" (user_profile_id) ON DELETE CASCADE,
+    status VARCHAR(16) NOT NULL DEFAULT 'PENDING' CHECK (status IN ('PENDING', 'READY', 'FAILED')),
+    bundle JSONB,
+    error TEXT,
+    requested_at TIMESTAMP(6) NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMP(6),
+    expires_at TIMESTAMP(6) NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_data_exports_user ON "DataExports" (user_profile_id, requested_at DESC);
+
+-- One export in preparation per user
+CREATE UNIQUE INDEX IF NOT EXISTS idx_data_exports_pending ON "DataExports" (user_profile_id) WHERE status = 'PENDING';
@@ -0,0 +1,23 @@
+-- Shift request state history: one row per status change, with how long the request sat in its previous status.
+-- Feeds GET /api/marketplace/sla (approval wait percentiles) and the marketplace_status_wait_seconds metric
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/026_shift_request_transitions.sql
+
+CREATE TABLE IF NOT EXISTS "ShiftRequestTransitions" (
+    id BIGSERIAL PRIMARY KEY,
+    shift_request_id INT4 NOT NULL REFERENCES "ShiftRequests" (id) ON DELETE CASCADE,
+    from_status VARCHAR(20),  -- NULL when the request was created
+    to_status VARCHAR(20) NOT NULL,
+    changed_by INT4,
+    changed_at TIMESTAMP(6) NOT NULL DEFAULT NOW(),
+    -- Seconds since the previous transition (or since creation)
+    waited_seconds DOUBLE PRECISION NOT NULL DEFAULT 0
+);
+
+CREATE INDEX IF NOT EXISTS idx_shift_request_transitions_request ON "ShiftRequestTransitions" (shift_request_id, changed_at);
+CREATE INDEX IF NOT EXISTS idx_shift_request_transitions_from ON "ShiftRequestTransitions" (from_status, changed_at);
+
+-- Existing requests get their current status as a starting point, dated by their last update
+INSERT INTO "ShiftRequestTransitions" (shift_request_id, from_status, to_status, changed_at)
+SELECT sr.id, NULL, sr.status, sr.updated_at
+FROM "ShiftRequests" sr
+WHERE NOT EXISTS (SELECT 1 FROM "ShiftRequestTransitions" t WHERE t.shift_request_id = sr.id);
@@ -0,0 +1,9 @@
+-- Profiles created without a Clerk account keep a temp_ auth_id until they are linked. Admins can invite
+-- them to sign up (they are auto-linked by email on first sign-in) or deactivate stale ones.
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/027_unlinked_profiles.sql
+
+ALTER TABLE "Users" ADD COLUMN IF NOT EXISTS invited_at TIMESTAMP(6);
+ALTER TABLE "Users" ADD COLUMN IF NOT EXISTS deactivated_at TIMESTAMP(6);
+ALTER TABLE "Users" ADD COLUMN IF NOT EXISTS deactivated_by INT4 REFERENCES "Users" (user_profile_id) ON DELETE SET NULL;
+
+CREATE INDEX IF NOT EXISTS idx_users_unlinked ON "Users" (created_at) WHERE auth_id LIKE 'temp\_%';
@@ -0,0 +1,16 @@
+-- Pay enhancements per role: night, weekend and bank-holiday percentages on top of a shift's money_per_hour,
+-- used by GET /api/roles/{id}/costs. Roles without a row have no enhancements.
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/028_role_pay_rules.sql
+
+CREATE TABLE IF NOT EXISTS "RolePayRules" (
+    role_id INT4 PRIMARY KEY REFERENCES "Roles" (id) ON DELETE CASCADE,
+    night_percent REAL NOT NULL DEFAULT 0 CHECK (night_percent BETWEEN 0 AND 500),
+    weekend_percent REAL NOT NULL DEFAULT 0 CHECK (weekend_percent BETWEEN 0 AND 500),
+    bank_holiday_percent REAL NOT NULL DEFAULT 0 CHECK (bank_holiday_percent BETWEEN 0 AND 500),
+    -- Night hours run from night_start to night_end, across midnight when night_end is earlier
+    night_start TIME NOT NULL DEFAULT '20:00',
+    night_end TIME NOT NULL DEFAULT '07:00',
+    bank_holidays DATE[] NOT NULL DEFAULT '{}',
+    updated_by INT4 REFERENCES "Users" (user_profile_id) ON DELETE SET NULL,
+    updated_at TIMESTAMP(6) NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,21 @@
+-- Marketplace bumps: a requester can move an OPEN request back to the top of the open list once a day and
+-- re-notify everyone who could take it. Each bump is kept for audit with the optional note given.
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/029_shift_request_bumps.sql
+
+ALTER TABLE "ShiftRequests" ADD COLUMN IF NOT EXISTS bumped_at TIMESTAMP(6);
+
+CREATE TABLE IF NOT EXISTS "ShiftRequestBumps" (
+    id SERIAL PRIMARY KEY,
+    shift_request_id INT4 NOT NULL REFERENCES "ShiftRequests" (id) ON DELETE CASCADE,
+    bumped_by INT4 REFERENCES "Users" (user_profile_id) ON DELETE SET NULL,
+    note TEXT,
+    -- Staff notified again by this bump
+    notified INT4 NOT NULL DEFAULT 0,
+    bumped_at TIMESTAMP(6) NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_shift_request_bumps_request ON "ShiftRequestBumps" (shift_request_id, bumped_at);
+
+-- Default open list order (sort=created_at) is by last bump, falling back to creation
+CREATE INDEX IF NOT EXISTS idx_shift_requests_open_listed
+    ON "ShiftRequests" ((COALESCE(bumped_at, created_at)) DESC) WHERE status = 'OPEN';
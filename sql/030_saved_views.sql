@@ -0,0 +1,18 @@
+-- Saved views: named rota filter sets kept per user so they follow them across devices, optionally shared
+-- with colleagues through a token
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/030_saved_views.sql
+
+CREATE TABLE IF NOT EXISTS "SavedViews" (
+    id SERIAL PRIMARY KEY,
+    user_profile_id INT4 NOT NULL REFERENCES "Users" (user_profile_id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    view_type VARCHAR(10) NOT NULL CHECK (view_type IN ('DAY', 'WEEK', 'MONTH', 'LIST')),
+    -- NULL means all of the user's roles
+    role_id INT4 REFERENCES "Roles" (id) ON DELETE SET NULL,
+    -- Staff subset; empty means everyone in the role
+    staff_ids INT4[] NOT NULL DEFAULT '{}',
+    share_token UUID UNIQUE,
+    created_at TIMESTAMP(6) NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP(6) NOT NULL DEFAULT NOW(),
+    UNIQUE (user_profile_id, name)
+);
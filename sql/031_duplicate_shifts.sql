@@ -0,0 +1,9 @@
+-- Duplicate shift detection: a new shift matching an existing one on role, date, label, start, end and assignee
+-- is rejected, skipped or allowed (the default) per workplace (requests can override with on_duplicate)
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/031_duplicate_shifts.sql
+
+ALTER TABLE "WorkplaceSettings" ADD COLUMN IF NOT EXISTS duplicate_shifts VARCHAR(10) NOT NULL DEFAULT 'ALLOW'
+    CHECK (duplicate_shifts IN ('REJECT', 'SKIP', 'ALLOW'));
+
+-- The duplicate lookup narrows by role, date and label before comparing times and assignee
+CREATE INDEX IF NOT EXISTS idx_shifts_role_date_label ON "Shifts" (role_id, date, label);
@@ -0,0 +1,14 @@
+-- Swap fairness cap: a role may limit how many marketplace requests each member has approved per month.
+-- Requests that would take someone past the cap wait for an admin, who must approve them as an override.
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/032_swap_caps.sql
+
+ALTER TABLE "Roles" ADD COLUMN IF NOT EXISTS max_swaps_per_month INT2 NULL
+    CHECK (max_swaps_per_month IS NULL OR max_swaps_per_month > 0);
+
+ALTER TABLE "ShiftRequests" ADD COLUMN IF NOT EXISTS swap_cap_exceeded BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE "ShiftRequests" ADD COLUMN IF NOT EXISTS swap_cap_override_by INT4 NULL
+    REFERENCES "Users" (user_profile_id);
+
+-- Monthly counts look up each member's approved requests as requester or candidate
+CREATE INDEX IF NOT EXISTS idx_shift_requests_approved_requester ON "ShiftRequests" (requester_id) WHERE status = 'APPROVED';
+CREATE INDEX IF NOT EXISTS idx_shift_requests_approved_candidate ON "ShiftRequests" (candidate_id) WHERE status = 'APPROVED';
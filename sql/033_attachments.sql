@@ -0,0 +1,22 @@
+-- Attachments (PDF handover sheets, images) on diary entries and shifts. The files live in the attachment
+-- bucket under storage_key. Rows go with their diary entry or shift; their objects stay in the bucket
+-- (only DELETE .../attachments/{id} removes one), so give the bucket a lifecycle rule if that matters.
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/033_attachments.sql
+
+CREATE TABLE IF NOT EXISTS "Attachments" (
+    id SERIAL PRIMARY KEY,
+    diary_id INT4 REFERENCES "Diary" (id) ON DELETE CASCADE,
+    shift_id UUID REFERENCES "Shifts" (uuid) ON DELETE CASCADE,
+    storage_key TEXT NOT NULL UNIQUE,
+    filename VARCHAR(255) NOT NULL,
+    content_type VARCHAR(100) NOT NULL,
+    size_bytes INT4 NOT NULL CHECK (size_bytes > 0),
+    sha256 CHAR(64) NOT NULL,
+    scan_status VARCHAR(12) NOT NULL CHECK (scan_status IN ('CLEAN', 'NOT_SCANNED')),
+    uploaded_by INT4 NOT NULL REFERENCES "Users" (user_profile_id),
+    created_at TIMESTAMP(6) NOT NULL DEFAULT NOW(),
+    CHECK ((diary_id IS NULL) <> (shift_id IS NULL))
+);
+
+CREATE INDEX IF NOT EXISTS idx_attachments_diary ON "Attachments" (diary_id) WHERE diary_id IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_attachments_shift ON "Attachments" (shift_id) WHERE shift_id IS NOT NULL;
@@ -0,0 +1,20 @@
+-- Successful sign-ins with the device and address they came from, queried via GET /api/users/me/logins
+-- and GET /api/audit/logins. The auth extractor records one row per Clerk session, IP and user agent,
+-- so a session that moves to another address or browser shows up as a new row.
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/034_login_audit.sql
+
+CREATE TABLE IF NOT EXISTS "LoginAudit" (
+    id BIGSERIAL PRIMARY KEY,
+    user_profile_id INT4 NOT NULL REFERENCES "Users" (user_profile_id) ON DELETE CASCADE,
+    -- Clerk session id (sid claim); NULL for tokens without one
+    session_id VARCHAR(100),
+    -- Client address as reported by the proxy (X-Forwarded-For), else the socket peer
+    ip VARCHAR(45),
+    user_agent VARCHAR(500),
+    auth_method VARCHAR(10) NOT NULL CHECK (auth_method IN ('COOKIE', 'BEARER')),
+    created_at TIMESTAMP(6) NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_login_audit_user ON "LoginAudit" (user_profile_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_login_audit_ip ON "LoginAudit" (ip, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_login_audit_created ON "LoginAudit" (created_at DESC);
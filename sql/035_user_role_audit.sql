@@ -0,0 +1,21 @@
+-- History of bulk permission changes on role assignments: one row per assignment changed, with the flags that moved.
+-- Written by PATCH /api/user-roles/bulk, queried via GET /api/audit/user-roles
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/035_user_role_audit.sql
+
+CREATE TABLE IF NOT EXISTS "UserRoleAudit" (
+    id BIGSERIAL PRIMARY KEY,
+    -- No foreign keys: the history outlives deleted assignments
+    user_role_id INT4 NOT NULL,
+    role_id INT4 NOT NULL,
+    user_profile_id INT4 NOT NULL,
+    changed_by INT4,
+    -- Shared by every row of one bulk update
+    batch_id UUID NOT NULL,
+    -- [{"permission": "can_edit_rota", "from": false, "to": true}, ...]
+    changes JSONB NOT NULL,
+    created_at TIMESTAMP(6) NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_role_audit_role ON "UserRoleAudit" (role_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_user_role_audit_user ON "UserRoleAudit" (user_profile_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_user_role_audit_batch ON "UserRoleAudit" (batch_id);
@@ -0,0 +1,9 @@
+-- Shift series: the shifts of one multi-day block (e.g. a week of nights) share a series_id, set when
+-- POST /api/shifts/series or a pattern creates them. A marketplace request with a series_id covers every
+-- shift the requester holds in that series, not just the one it was raised on.
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/036_shift_series.sql
+
+ALTER TABLE "Shifts" ADD COLUMN IF NOT EXISTS series_id UUID NULL;
+ALTER TABLE "ShiftRequests" ADD COLUMN IF NOT EXISTS series_id UUID NULL;
+
+CREATE INDEX IF NOT EXISTS idx_shifts_series ON "Shifts" (series_id) WHERE series_id IS NOT NULL;
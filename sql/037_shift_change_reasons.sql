@@ -0,0 +1,22 @@
+-- Reasons for manual shift edits: PUT/DELETE /api/shifts/{uuid} take an optional reason, kept on the ShiftAudit
+-- row the change writes (from the app.change_reason session setting). Roles with edit_reason_required refuse
+-- to edit or delete a published shift without one.
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/037_shift_change_reasons.sql
+
+ALTER TABLE "Roles" ADD COLUMN IF NOT EXISTS edit_reason_required BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE "ShiftAudit" ADD COLUMN IF NOT EXISTS reason TEXT NULL;
+
+CREATE OR REPLACE FUNCTION shift_audit_set_actor() RETURNS trigger AS $$
+BEGIN
+    IF NEW.acted_by IS NULL THEN
+        NEW.acted_by := NULLIF(current_setting('app.current_user', true), '')::INT4;
+    END IF;
+    IF NEW.acted_as_delegate_of IS NULL THEN
+        NEW.acted_as_delegate_of := NULLIF(current_setting('app.acting_for', true), '')::INT4;
+    END IF;
+    IF NEW.reason IS NULL THEN
+        NEW.reason := NULLIF(current_setting('app.change_reason', true), '');
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
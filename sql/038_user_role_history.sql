@@ -0,0 +1,81 @@
+-- Role membership history: a trigger on "UserRoles" records every grant, permission change and revocation, so
+-- deleting an assignment no longer loses when it existed. GET /api/users/{id}/role-history rebuilds the periods.
+-- acted_by comes from the app.current_user session setting, like ShiftAudit. Existing assignments are seeded
+-- as granted at their created_at.
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/038_user_role_history.sql
+
+CREATE TABLE IF NOT EXISTS "UserRoleHistory" (
+    id BIGSERIAL PRIMARY KEY,
+    -- No foreign keys: the history outlives deleted assignments, roles and profiles
+    user_role_id INT4 NOT NULL,
+    role_id INT4 NOT NULL,
+    user_profile_id INT4 NOT NULL,
+    event VARCHAR(8) NOT NULL CHECK (event IN ('GRANTED', 'CHANGED', 'REVOKED')),
+    -- Flags in force from this event on (all false for REVOKED)
+    can_edit_rota BOOLEAN NOT NULL,
+    can_access_diary BOOLEAN NOT NULL,
+    can_work_shifts BOOLEAN NOT NULL,
+    can_edit_templates BOOLEAN NOT NULL,
+    can_edit_staff BOOLEAN NOT NULL,
+    can_view_staff_details BOOLEAN NOT NULL,
+    can_approve_rota BOOLEAN NOT NULL,
+    acted_by INT4,
+    created_at TIMESTAMP(6) NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_role_history_user ON "UserRoleHistory" (user_profile_id, user_role_id, created_at);
+
+CREATE OR REPLACE FUNCTION user_role_history_record() RETURNS trigger AS $$
+DECLARE
+    actor INT4 := NULLIF(current_setting('app.current_user', true), '')::INT4;
+BEGIN
+    IF TG_OP = 'UPDATE'
+       AND (NEW.role_id, NEW.user_profile_id, NEW.can_edit_rota, NEW.can_access_diary, NEW.can_work_shifts,
+            NEW.can_edit_templates, NEW.can_edit_staff, NEW.can_view_staff_details, NEW.can_approve_rota)
+           IS NOT DISTINCT FROM
+           (OLD.role_id, OLD.user_profile_id, OLD.can_edit_rota, OLD.can_access_diary, OLD.can_work_shifts,
+            OLD.can_edit_templates, OLD.can_edit_staff, OLD.can_view_staff_details, OLD.can_approve_rota) THEN
+        RETURN NEW;
+    END IF;
+
+    -- Deleting an assignment, or moving it to another role or profile, ends the old membership
+    IF TG_OP = 'DELETE' OR (TG_OP = 'UPDATE' AND (NEW.role_id, NEW.user_profile_id) IS DISTINCT FROM (OLD.role_id, OLD.user_profile_id)) THEN
+        INSERT INTO "UserRoleHistory" (
+            user_role_id, role_id, user_profile_id, event, can_edit_rota, can_access_diary, can_work_shifts,
+            can_edit_templates, can_edit_staff, can_view_staff_details, can_approve_rota, acted_by
+        )
+        VALUES (OLD.id, OLD.role_id, OLD.user_profile_id, 'REVOKED', false, false, false, false, false, false, false, actor);
+        IF TG_OP = 'DELETE' THEN
+            RETURN OLD;
+        END IF;
+    END IF;
+
+    INSERT INTO "UserRoleHistory" (
+        user_role_id, role_id, user_profile_id, event, can_edit_rota, can_access_diary, can_work_shifts,
+        can_edit_templates, can_edit_staff, can_view_staff_details, can_approve_rota, acted_by
+    )
+    VALUES (
+        NEW.id, NEW.role_id, NEW.user_profile_id,
+        CASE WHEN TG_OP = 'UPDATE' AND NEW.role_id = OLD.role_id AND NEW.user_profile_id = OLD.user_profile_id
+             THEN 'CHANGED' ELSE 'GRANTED' END,
+        NEW.can_edit_rota, NEW.can_access_diary, NEW.can_work_shifts, NEW.can_edit_templates,
+        NEW.can_edit_staff, NEW.can_view_staff_details, NEW.can_approve_rota, actor
+    );
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS trg_user_role_history ON "UserRoles";
+CREATE TRIGGER trg_user_role_history
+    AFTER INSERT OR UPDATE OR DELETE ON "UserRoles"
+    FOR EACH ROW EXECUTE FUNCTION user_role_history_record();
+
+INSERT INTO "UserRoleHistory" (
+    user_role_id, role_id, user_profile_id, event, can_edit_rota, can_access_diary, can_work_shifts,
+    can_edit_templates, can_edit_staff, can_view_staff_details, can_approve_rota, created_at
+)
+SELECT
+    ur.id, ur.role_id, ur.user_profile_id, 'GRANTED', ur.can_edit_rota, ur.can_access_diary, ur.can_work_shifts,
+    ur.can_edit_templates, ur.can_edit_staff, ur.can_view_staff_details, ur.can_approve_rota, ur.created_at
+FROM "UserRoles" ur
+WHERE NOT EXISTS (SELECT 1 FROM "UserRoleHistory" h WHERE h.user_role_id = ur.id);
@@ -0,0 +1,29 @@
+-- Shift attendance: the assigned user checks in and out of a shift (POST /api/shifts/{uuid}/check-in and
+-- /check-out), recording the actual start and end in local wall-clock time like Shifts.date + start.
+-- GET /api/roles/{id}/attendance compares them with the rostered times for payroll adjustments.
+-- Attendance is kept per shift and user, so a shift reassigned after someone checked in keeps that person's times
+-- and the new assignee can check in too.
+-- A workplace with geofence_radius_m > 0 only accepts check-ins and check-outs sent with a location within
+-- that many metres of geofence_latitude/geofence_longitude.
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/039_shift_attendance.sql
+
+ALTER TABLE "WorkplaceSettings" ADD COLUMN IF NOT EXISTS geofence_latitude DOUBLE PRECISION NULL;
+ALTER TABLE "WorkplaceSettings" ADD COLUMN IF NOT EXISTS geofence_longitude DOUBLE PRECISION NULL;
+ALTER TABLE "WorkplaceSettings" ADD COLUMN IF NOT EXISTS geofence_radius_m INT4 NOT NULL DEFAULT 0
+    CHECK (geofence_radius_m >= 0);
+
+CREATE TABLE IF NOT EXISTS "ShiftAttendance" (
+    shift_id UUID NOT NULL REFERENCES "Shifts" (uuid) ON DELETE CASCADE,
+    user_profile_id INT4 NOT NULL REFERENCES "Users" (user_profile_id) ON DELETE CASCADE,
+    actual_start TIMESTAMP NOT NULL,
+    actual_end TIMESTAMP NULL,
+    check_in_latitude DOUBLE PRECISION NULL,
+    check_in_longitude DOUBLE PRECISION NULL,
+    check_out_latitude DOUBLE PRECISION NULL,
+    check_out_longitude DOUBLE PRECISION NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (shift_id, user_profile_id),
+    CHECK (actual_end IS NULL OR actual_end >= actual_start)
+);
+
+CREATE INDEX IF NOT EXISTS idx_shift_attendance_user ON "ShiftAttendance" (user_profile_id, actual_start);
@@ -0,0 +1,37 @@
+-- Passkeys (WebAuthn) for super admins, as an alternative or supplement to TOTP on high-risk endpoints.
+-- POST /api/auth/passkeys/verify checks an assertion and returns a one-time step-up token, sent in
+-- X-MFA-Passkey where X-MFA-Code would otherwise be required.
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/040_passkeys.sql
+
+CREATE TABLE IF NOT EXISTS "PasskeyCredentials" (
+    id SERIAL PRIMARY KEY,
+    user_profile_id INT4 NOT NULL REFERENCES "Users" (user_profile_id) ON DELETE CASCADE,
+    -- base64url credential ID as the browser reports it
+    credential_id TEXT NOT NULL UNIQUE,
+    -- DER SubjectPublicKeyInfo and its COSE algorithm (-7 ES256, -8 EdDSA, -257 RS256)
+    public_key BYTEA NOT NULL,
+    algorithm INT4 NOT NULL,
+    -- Last signature counter seen; a counter that does not increase suggests a cloned authenticator
+    sign_count INT8 NOT NULL DEFAULT 0,
+    name TEXT NOT NULL,
+    created_at TIMESTAMP(6) NOT NULL DEFAULT NOW(),
+    last_used_at TIMESTAMP(6)
+);
+
+CREATE INDEX IF NOT EXISTS idx_passkey_credentials_user ON "PasskeyCredentials" (user_profile_id);
+
+-- Outstanding registration and verification challenges, each usable once
+CREATE TABLE IF NOT EXISTS "PasskeyChallenges" (
+    challenge TEXT PRIMARY KEY,
+    user_profile_id INT4 NOT NULL REFERENCES "Users" (user_profile_id) ON DELETE CASCADE,
+    purpose TEXT NOT NULL CHECK (purpose IN ('REGISTER', 'VERIFY')),
+    expires_at TIMESTAMP(6) NOT NULL
+);
+
+-- Step-up tokens from a verified assertion; only the SHA-256 of the token is stored
+CREATE TABLE IF NOT EXISTS "PasskeyStepUps" (
+    token_hash TEXT PRIMARY KEY,
+    user_profile_id INT4 NOT NULL REFERENCES "Users" (user_profile_id) ON DELETE CASCADE,
+    credential_id INT4 NOT NULL REFERENCES "PasskeyCredentials" (id) ON DELETE CASCADE,
+    expires_at TIMESTAMP(6) NOT NULL
+);
@@ -0,0 +1,8 @@
+-- Diary entry types: NOTE entries are only visible in roles where the caller has can_access_diary;
+-- ANNOUNCEMENT entries are also visible to every member of the entry's role.
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/041_diary_entry_types.sql
+
+ALTER TABLE "Diary" ADD COLUMN IF NOT EXISTS entry_type TEXT NOT NULL DEFAULT 'NOTE'
+    CHECK (entry_type IN ('NOTE', 'ANNOUNCEMENT'));
+
+CREATE INDEX IF NOT EXISTS idx_diary_announcements ON "Diary" (role_id, date) WHERE entry_type = 'ANNOUNCEMENT';
@@ -0,0 +1,11 @@
+-- Clerk accounts create-login made but could neither link to the profile nor delete again. They are listed by
+-- GET /api/users/clerk-orphans until the account is gone from Clerk.
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/042_clerk_rollback_failures.sql
+
+CREATE TABLE IF NOT EXISTS "ClerkRollbackFailures" (
+    clerk_user_id TEXT PRIMARY KEY,
+    user_profile_id INT4 REFERENCES "Users" (user_profile_id) ON DELETE SET NULL,
+    email TEXT NOT NULL,
+    error TEXT NOT NULL,
+    created_at TIMESTAMP(6) NOT NULL DEFAULT NOW()
+);
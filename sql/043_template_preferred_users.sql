@@ -0,0 +1,5 @@
+-- Preferred staff for a shift template, most preferred first (e.g. the usual Tuesday clinic consultant).
+-- Applying a pattern lists them against each shift it creates, and POST /api/templates/auto-fill assigns them.
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/043_template_preferred_users.sql
+
+ALTER TABLE "ShiftTemplates" ADD COLUMN IF NOT EXISTS preferred_user_ids INT4[] NOT NULL DEFAULT '{}';
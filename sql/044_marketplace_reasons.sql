@@ -0,0 +1,25 @@
+-- Reason codes for rejecting and cancelling marketplace requests, managed by super admins, so the SLA report
+-- can show why swaps fail. Codes are never deleted (requests keep pointing at them); set active = false instead.
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/044_marketplace_reasons.sql
+
+CREATE TABLE IF NOT EXISTS "MarketplaceReasons" (
+    code TEXT PRIMARY KEY CHECK (code ~ '^[A-Z][A-Z0-9_]{1,39}$'),
+    label TEXT NOT NULL,
+    applies_to TEXT NOT NULL DEFAULT 'ANY' CHECK (applies_to IN ('REJECT', 'CANCEL', 'ANY')),
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    sort_order INTEGER NOT NULL DEFAULT 0,
+    updated_by INTEGER,
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+INSERT INTO "MarketplaceReasons" (code, label, applies_to, sort_order) VALUES
+    ('SHORT_NOTICE', 'Too short notice', 'ANY', 10),
+    ('SKILL_MISMATCH', 'Skills or grade do not match the shift', 'REJECT', 20),
+    ('STAFFING_RULES', 'Breaks staffing rules (rest, hours or cover)', 'REJECT', 30),
+    ('NO_LONGER_NEEDED', 'No longer needed', 'CANCEL', 40),
+    ('OTHER', 'Other', 'ANY', 100)
+ON CONFLICT (code) DO NOTHING;
+
+ALTER TABLE "ShiftRequests" ADD COLUMN IF NOT EXISTS reason_code TEXT REFERENCES "MarketplaceReasons" (code);
+
+CREATE INDEX IF NOT EXISTS idx_shift_requests_reason_code ON "ShiftRequests" (reason_code) WHERE reason_code IS NOT NULL;
@@ -0,0 +1,9 @@
+-- Job plan templates scoped to a role, so POST /api/job-plans/from-template can instantiate the usual
+-- registrar plan for a new starter. Templates without a role stay available to every role of their workplace.
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/045_job_plan_template_roles.sql
+
+ALTER TABLE "JobPlanTemplates" ADD COLUMN IF NOT EXISTS role_id INT4 REFERENCES "Roles"(id) ON DELETE CASCADE;
+ALTER TABLE "JobPlanTemplates" ADD COLUMN IF NOT EXISTS sl_per_year REAL;
+ALTER TABLE "JobPlanTemplates" ADD COLUMN IF NOT EXISTS pl_per_year REAL;
+
+CREATE INDEX IF NOT EXISTS idx_job_plan_templates_role ON "JobPlanTemplates" (role_id) WHERE role_id IS NOT NULL;
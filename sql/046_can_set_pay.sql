@@ -0,0 +1,64 @@
+-- Locum pay approval: setting or changing money_per_hour on a locum shift needs can_set_pay in the shift's role
+-- (super admins always can). Each such change is kept in "ShiftPayAudit" with the old and new rate and who
+-- approved it. The role history trigger from 038 is redefined to carry the new flag.
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/046_can_set_pay.sql
+
+ALTER TABLE "UserRoles" ADD COLUMN IF NOT EXISTS can_set_pay BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE "UserRoleHistory" ADD COLUMN IF NOT EXISTS can_set_pay BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE TABLE IF NOT EXISTS "ShiftPayAudit" (
+    id BIGSERIAL PRIMARY KEY,
+    -- No foreign keys: the audit outlives deleted shifts
+    shift_uuid UUID NOT NULL,
+    role_id INT4 NOT NULL,
+    -- NULL old rate for new shifts; NULL new rate when the rate was cleared
+    old_money_per_hour REAL,
+    new_money_per_hour REAL,
+    approved_by INT4 NOT NULL,
+    created_at TIMESTAMP(6) NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_shift_pay_audit_shift ON "ShiftPayAudit" (shift_uuid, created_at);
+CREATE INDEX IF NOT EXISTS idx_shift_pay_audit_role ON "ShiftPayAudit" (role_id, created_at);
+
+CREATE OR REPLACE FUNCTION user_role_history_record() RETURNS trigger AS $$
+DECLARE
+    actor INT4 := NULLIF(current_setting('app.current_user', true), '')::INT4;
+BEGIN
+    IF TG_OP = 'UPDATE'
+       AND (NEW.role_id, NEW.user_profile_id, NEW.can_edit_rota, NEW.can_access_diary, NEW.can_work_shifts,
+            NEW.can_edit_templates, NEW.can_edit_staff, NEW.can_view_staff_details, NEW.can_approve_rota,
+            NEW.can_set_pay)
+           IS NOT DISTINCT FROM
+           (OLD.role_id, OLD.user_profile_id, OLD.can_edit_rota, OLD.can_access_diary, OLD.can_work_shifts,
+            OLD.can_edit_templates, OLD.can_edit_staff, OLD.can_view_staff_details, OLD.can_approve_rota,
+            OLD.can_set_pay) THEN
+        RETURN NEW;
+    END IF;
+
+    -- Deleting an assignment, or moving it to another role or profile, ends the old membership
+    IF TG_OP = 'DELETE' OR (TG_OP = 'UPDATE' AND (NEW.role_id, NEW.user_profile_id) IS DISTINCT FROM (OLD.role_id, OLD.user_profile_id)) THEN
+        INSERT INTO "UserRoleHistory" (
+            user_role_id, role_id, user_profile_id, event, can_edit_rota, can_access_diary, can_work_shifts,
+            can_edit_templates, can_edit_staff, can_view_staff_details, can_approve_rota, can_set_pay, acted_by
+        )
+        VALUES (OLD.id, OLD.role_id, OLD.user_profile_id, 'REVOKED', false, false, false, false, false, false, false, false, actor);
+        IF TG_OP = 'DELETE' THEN
+            RETURN OLD;
+        END IF;
+    END IF;
+
+    INSERT INTO "UserRoleHistory" (
+        user_role_id, role_id, user_profile_id, event, can_edit_rota, can_access_diary, can_work_shifts,
+        can_edit_templates, can_edit_staff, can_view_staff_details, can_approve_rota, can_set_pay, acted_by
+    )
+    VALUES (
+        NEW.id, NEW.role_id, NEW.user_profile_id,
+        CASE WHEN TG_OP = 'UPDATE' AND NEW.role_id = OLD.role_id AND NEW.user_profile_id = OLD.user_profile_id
+             THEN 'CHANGED' ELSE 'GRANTED' END,
+        NEW.can_edit_rota, NEW.can_access_diary, NEW.can_work_shifts, NEW.can_edit_templates,
+        NEW.can_edit_staff, NEW.can_view_staff_details, NEW.can_approve_rota, NEW.can_set_pay, actor
+    );
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
@@ -0,0 +1,50 @@
+-- Precomputed shift statistics: PA totals per user and month, and fill rates per role and month, as
+-- materialised views refreshed by a background job every 10 minutes (and on demand by super admins via
+-- POST /api/shift-stats/refresh). "ShiftStatsRefreshes" records when each view was last refreshed, so
+-- GET /api/shift-stats/users and /roles can say how stale their figures are. The unique indexes let the
+-- job refresh CONCURRENTLY without blocking readers.
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/047_shift_stats_views.sql
+
+CREATE MATERIALIZED VIEW IF NOT EXISTS "ShiftStatsUserMonth" AS
+SELECT
+    s.user_profile_id,
+    s.role_id,
+    date_trunc('month', s.date)::date AS month,
+    COUNT(*) FILTER (WHERE s.time_off_category_id IS NULL) AS shifts,
+    COUNT(*) FILTER (WHERE s.time_off_category_id IS NULL AND s.is_locum) AS locum_shifts,
+    COALESCE(SUM(s.pa_value) FILTER (WHERE s.time_off_category_id IS NULL), 0)::float8 AS total_pa,
+    COALESCE(SUM(s.pa_value) FILTER (WHERE s.time_off_category_id IS NULL AND s.is_dcc), 0)::float8 AS dcc_pa,
+    COALESCE(SUM(s.pa_value) FILTER (WHERE s.time_off_category_id IS NULL AND s.is_spa), 0)::float8 AS spa_pa,
+    COUNT(*) FILTER (WHERE s.time_off_category_id IS NOT NULL) AS time_off_entries
+FROM "Shifts" s
+WHERE s.user_profile_id IS NOT NULL
+GROUP BY s.user_profile_id, s.role_id, date_trunc('month', s.date);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_shift_stats_user_month ON "ShiftStatsUserMonth" (user_profile_id, role_id, month);
+
+CREATE MATERIALIZED VIEW IF NOT EXISTS "ShiftStatsRoleMonth" AS
+SELECT
+    s.role_id,
+    date_trunc('month', s.date)::date AS month,
+    COUNT(*) FILTER (WHERE s.time_off_category_id IS NULL) AS shift_count,
+    COUNT(*) FILTER (WHERE s.time_off_category_id IS NULL AND s.user_profile_id IS NOT NULL) AS filled,
+    COUNT(*) FILTER (WHERE s.time_off_category_id IS NULL AND s.user_profile_id IS NULL) AS unfilled,
+    COUNT(*) FILTER (WHERE s.time_off_category_id IS NULL AND s.is_locum) AS locum_shifts,
+    COALESCE(SUM(s.pa_value) FILTER (WHERE s.time_off_category_id IS NULL), 0)::float8 AS total_pa,
+    COUNT(DISTINCT s.user_profile_id) FILTER (WHERE s.time_off_category_id IS NULL) AS distinct_staff,
+    COUNT(*) FILTER (WHERE s.time_off_category_id IS NOT NULL) AS time_off_entries
+FROM "Shifts" s
+GROUP BY s.role_id, date_trunc('month', s.date);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_shift_stats_role_month ON "ShiftStatsRoleMonth" (role_id, month);
+
+CREATE TABLE IF NOT EXISTS "ShiftStatsRefreshes" (
+    view_name VARCHAR(64) PRIMARY KEY,
+    refreshed_at TIMESTAMP(6) NOT NULL,
+    duration_ms INT4 NOT NULL
+);
+
+-- The views were populated when created
+INSERT INTO "ShiftStatsRefreshes" (view_name, refreshed_at, duration_ms)
+VALUES ('ShiftStatsUserMonth', NOW(), 0), ('ShiftStatsRoleMonth', NOW(), 0)
+ON CONFLICT (view_name) DO NOTHING;
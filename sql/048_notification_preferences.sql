@@ -0,0 +1,11 @@
+-- Per-user opt-outs from in-app notification kinds, set via PUT /api/notifications/preferences. A kind
+-- without a row is delivered; notify() skips kinds a user has switched off.
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/048_notification_preferences.sql
+
+CREATE TABLE IF NOT EXISTS "NotificationPreferences" (
+    user_profile_id INT4 NOT NULL REFERENCES "Users" (user_profile_id) ON DELETE CASCADE,
+    kind VARCHAR(50) NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    updated_at TIMESTAMP(6) NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_profile_id, kind)
+);
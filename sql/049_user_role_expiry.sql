@@ -0,0 +1,11 @@
+-- Revoking a user role keeps access until the end of a day instead of cutting it mid-shift:
+-- DELETE /api/user-roles/{id} sets effective_until (today unless ?until= names a later date), permission
+-- checks ignore the assignment once that day is over, and an hourly job deletes it (the history trigger
+-- records the revocation then). ?immediate=true still deletes straight away.
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/049_user_role_expiry.sql
+
+ALTER TABLE "UserRoles" ADD COLUMN IF NOT EXISTS effective_until DATE;
+
+CREATE INDEX IF NOT EXISTS idx_user_roles_effective_until
+    ON "UserRoles" (effective_until)
+    WHERE effective_until IS NOT NULL;
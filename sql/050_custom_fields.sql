@@ -0,0 +1,25 @@
+-- Department-specific profile attributes (bleep number, smart card ID, ...): each workplace defines its own
+-- typed fields, and users hold at most one value per field. Values are shown on GET /api/users/{id}, set
+-- through PUT /api/users/profiles/{id} and exported as custom_fields.<key> CSV columns.
+-- Run against the Neon database manually: psql $DATABASE_URL -f sql/050_custom_fields.sql
+
+CREATE TABLE IF NOT EXISTS "CustomFieldDefinitions" (
+    id SERIAL PRIMARY KEY,
+    workplace_id INT4 NOT NULL REFERENCES "Workplaces" (id) ON DELETE CASCADE,
+    key VARCHAR(50) NOT NULL,
+    label VARCHAR(100) NOT NULL,
+    field_type VARCHAR(10) NOT NULL CHECK (field_type IN ('TEXT', 'NUMBER', 'BOOLEAN', 'DATE')),
+    created_at TIMESTAMP(6) NOT NULL DEFAULT NOW(),
+    UNIQUE (workplace_id, key)
+);
+
+CREATE TABLE IF NOT EXISTS "UserCustomFieldValues" (
+    user_profile_id INT4 NOT NULL REFERENCES "Users" (user_profile_id) ON DELETE CASCADE,
+    definition_id INT4 NOT NULL REFERENCES "CustomFieldDefinitions" (id) ON DELETE CASCADE,
+    value JSONB NOT NULL,
+    updated_at TIMESTAMP(6) NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_profile_id, definition_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_custom_field_values_definition
+    ON "UserCustomFieldValues" (definition_id);
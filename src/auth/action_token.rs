@@ -0,0 +1,85 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use subtle::ConstantTimeEq;
+
+use crate::{auth::create_hmac_signature, AppError};
+
+/// Signed with the payload; all three token kinds share pin_token_secret, so this keeps them apart
+const ACTION_SCOPE: &str = "action";
+
+/// Action links in notifications stop working after two days
+pub const ACTION_TOKEN_TTL_SECS: i64 = 48 * 60 * 60;
+
+/// Generate a deep-link token for one recipient to open one shift request.
+/// Token format: base64url(action:request_id:user_profile_id:expiry_timestamp:hmac_signature), safe in a URL path
+pub fn generate_action_token(request_id: i32, user_profile_id: i32, secret: &str) -> Result<(String, i64), AppError> {
+    let expiry_time = chrono::Utc::now().timestamp() + ACTION_TOKEN_TTL_SECS;
+
+    let payload = format!("{}:{}:{}:{}", ACTION_SCOPE, request_id, user_profile_id, expiry_time);
+    let signature = create_hmac_signature(&payload, secret)?;
+    let token = URL_SAFE_NO_PAD.encode(format!("{}:{}", payload, signature).as_bytes());
+
+    Ok((token, expiry_time))
+}
+
+/// What a valid action token names
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ActionToken {
+    pub request_id: i32,
+    pub user_profile_id: i32,
+    pub expires_at: i64,
+}
+
+/// Validate an action token and extract the request and recipient it was issued for
+pub fn validate_action_token(token: &str, secret: &str) -> Result<ActionToken, AppError> {
+    let invalid = || AppError::Unauthorized("Invalid action link".to_string());
+
+    let decoded = URL_SAFE_NO_PAD
+        .decode(token)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .ok_or_else(invalid)?;
+
+    // Parse token: action:request_id:user_profile_id:expiry_time:signature
+    let parts: Vec<&str> = decoded.split(':').collect();
+    if parts.len() != 5 || parts[0] != ACTION_SCOPE {
+        return Err(invalid());
+    }
+
+    let request_id: i32 = parts[1].parse().map_err(|_| invalid())?;
+    let user_profile_id: i32 = parts[2].parse().map_err(|_| invalid())?;
+    let expiry_time: i64 = parts[3].parse().map_err(|_| invalid())?;
+
+    let payload = format!("{}:{}:{}:{}", ACTION_SCOPE, request_id, user_profile_id, expiry_time);
+    let expected_signature = create_hmac_signature(&payload, secret)?;
+    if !bool::from(parts[4].as_bytes().ct_eq(expected_signature.as_bytes())) {
+        return Err(invalid());
+    }
+
+    if chrono::Utc::now().timestamp() > expiry_time {
+        return Err(AppError::Unauthorized("Action link has expired".to_string()));
+    }
+
+    Ok(ActionToken { request_id, user_profile_id, expires_at: expiry_time })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_and_validate_action_token() {
+        let secret = "test_secret_key_for_testing_purposes";
+
+        let (token, expires_at) = generate_action_token(42, 7, secret).unwrap();
+        assert!(!token.contains(['/', '+', '=']));
+        assert_eq!(
+            validate_action_token(&token, secret).unwrap(),
+            ActionToken { request_id: 42, user_profile_id: 7, expires_at }
+        );
+        assert!(validate_action_token(&token, "wrong_secret").is_err());
+
+        // Display tokens share the secret but not the scope
+        let (display_token, _) = crate::auth::generate_display_token(42, 1, secret).unwrap();
+        assert!(validate_action_token(&display_token, secret).is_err());
+    }
+}
@@ -7,6 +7,7 @@ pub struct ClerkClaims {
     pub iat: i64,     // Issued at timestamp
     pub iss: String,  // Issuer
     pub azp: Option<String>, // Authorized party
+    pub sid: Option<String>, // Clerk session ID
 
     // Custom claims (set in Clerk Dashboard session token)
     #[serde(rename = "primaryEmail")]
@@ -27,7 +27,7 @@ pub async fn check_email_in_clerk(email: &str, clerk_secret_key: &str) -> Result
     if !response.status().is_success() {
         let status = response.status();
         let body = response.text().await.unwrap_or_default();
-        tracing::error!(status = %status, body, email, "Clerk API returned error");
+        tracing::error!(status = %status, body = %crate::redaction::redact_body(&body), email, "Clerk API returned error");
         return Err(AppError::Internal(format!(
             "Clerk API error: {} - {}",
             status, body
@@ -45,10 +45,200 @@ pub async fn check_email_in_clerk(email: &str, clerk_secret_key: &str) -> Result
     Ok(exists)
 }
 
+/// Send a Clerk sign-up invitation; the new account is auto-linked to the profile by email on first sign-in
+pub async fn create_clerk_invitation(email: &str, clerk_secret_key: &str) -> Result<(), AppError> {
+    let client = reqwest::Client::new();
+
+    let response = client
+        .post("https://api.clerk.com/v1/invitations")
+        .header("Authorization", format!("Bearer {}", clerk_secret_key))
+        .header("Content-Type", "application/json")
+        .json(&serde_json::json!({
+            "email_address": email,
+            "notify": true,
+            "ignore_existing": true,
+        }))
+        .send()
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, email, "Failed to call Clerk API");
+            AppError::Internal(format!("Failed to create Clerk invitation: {}", e))
+        })?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        tracing::error!(status = %status, body = %crate::redaction::redact_body(&body), email, "Clerk API returned error");
+        return Err(AppError::Internal(format!(
+            "Clerk API error: {} - {}",
+            status, body
+        )));
+    }
+
+    Ok(())
+}
+
+/// Clerk's page size limit for GET /v1/users
+const USERS_PAGE_SIZE: usize = 500;
+
+/// Listing stops after this many pages so a runaway directory cannot hang the request
+const MAX_USER_PAGES: usize = 20;
+
+/// A Clerk account as returned by the Backend API
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClerkUser {
+    pub id: String,
+    /// Primary email address, or the first one when none is marked primary
+    pub email: Option<String>,
+    /// Set to the user_profile_id by create-login
+    pub external_id: Option<String>,
+    /// Unix milliseconds
+    pub created_at: Option<i64>,
+}
+
+fn parse_clerk_user(value: &Value) -> Option<ClerkUser> {
+    let id = value["id"].as_str()?.to_string();
+    let addresses = value["email_addresses"].as_array().map(Vec::as_slice).unwrap_or_default();
+    let primary = value["primary_email_address_id"].as_str();
+    let email = addresses
+        .iter()
+        .find(|a| primary.is_some() && a["id"].as_str() == primary)
+        .or_else(|| addresses.first())
+        .and_then(|a| a["email_address"].as_str())
+        .map(str::to_string);
+
+    Some(ClerkUser {
+        id,
+        email,
+        external_id: value["external_id"].as_str().map(str::to_string),
+        created_at: value["created_at"].as_i64(),
+    })
+}
+
+/// Send a Clerk Backend API request, turning transport and non-2xx failures into AppError::Internal
+async fn clerk_request(request: reqwest::RequestBuilder, clerk_secret_key: &str, action: &str) -> Result<reqwest::Response, AppError> {
+    let response = request
+        .header("Authorization", format!("Bearer {}", clerk_secret_key))
+        .header("Content-Type", "application/json")
+        .send()
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "❌ Failed to call Clerk API");
+            AppError::Internal(format!("Failed to {}: {}", action, e))
+        })?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        tracing::error!(status = %status, body = %crate::redaction::redact_body(&body), "❌ Clerk API returned error");
+        return Err(AppError::Internal(format!(
+            "Clerk API error: {} - {}",
+            status, body
+        )));
+    }
+
+    Ok(response)
+}
+
+/// Create a Clerk user, tagged with the profile it is for, and return its Clerk ID
+pub async fn create_clerk_user(
+    email: &str,
+    password: &str,
+    skip_password_requirement: bool,
+    user_profile_id: i32,
+    clerk_secret_key: &str,
+) -> Result<String, AppError> {
+    let request = reqwest::Client::new()
+        .post("https://api.clerk.com/v1/users")
+        .json(&serde_json::json!({
+            "email_address": [email],
+            "password": password,
+            "skip_password_requirement": skip_password_requirement,
+            "external_id": user_profile_id.to_string(),
+        }));
+    let clerk_user: Value = clerk_request(request, clerk_secret_key, "create Clerk user")
+        .await?
+        .json()
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "❌ Failed to parse Clerk response");
+            AppError::Internal(format!("Failed to parse Clerk response: {}", e))
+        })?;
+
+    clerk_user["id"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| AppError::Internal("Clerk response missing user id".to_string()))
+}
+
+/// Delete a Clerk user, e.g. to undo create_clerk_user when linking it failed
+pub async fn delete_clerk_user(clerk_user_id: &str, clerk_secret_key: &str) -> Result<(), AppError> {
+    let request = reqwest::Client::new().delete(format!("https://api.clerk.com/v1/users/{}", clerk_user_id));
+    clerk_request(request, clerk_secret_key, "delete Clerk user").await?;
+    Ok(())
+}
+
+/// Clerk users as listed by `list_clerk_users`
+#[derive(Debug, Clone, Default)]
+pub struct ClerkUserList {
+    pub users: Vec<ClerkUser>,
+    /// The listing stopped at MAX_USER_PAGES, so accounts are missing from `users`
+    pub truncated: bool,
+}
+
+/// Every Clerk user, oldest first (capped at MAX_USER_PAGES pages)
+pub async fn list_clerk_users(clerk_secret_key: &str) -> Result<ClerkUserList, AppError> {
+    let client = reqwest::Client::new();
+    let mut users = Vec::new();
+
+    for page in 0..MAX_USER_PAGES {
+        let request = client.get("https://api.clerk.com/v1/users").query(&[
+            ("limit", USERS_PAGE_SIZE.to_string()),
+            ("offset", (page * USERS_PAGE_SIZE).to_string()),
+            ("order_by", "+created_at".to_string()),
+        ]);
+        let batch: Vec<Value> = clerk_request(request, clerk_secret_key, "list Clerk users")
+            .await?
+            .json()
+            .await
+            .map_err(|e| AppError::Internal(format!("Failed to parse Clerk response: {}", e)))?;
+
+        let done = batch.len() < USERS_PAGE_SIZE;
+        users.extend(batch.iter().filter_map(parse_clerk_user));
+        if done {
+            return Ok(ClerkUserList { users, truncated: false });
+        }
+    }
+
+    tracing::warn!(users = users.len(), "⚠️ Clerk user listing stopped at the page cap");
+    Ok(ClerkUserList { users, truncated: true })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_clerk_user() {
+        let user = parse_clerk_user(&serde_json::json!({
+            "id": "user_2abc",
+            "external_id": "42",
+            "created_at": 1_760_000_000_000i64,
+            "primary_email_address_id": "idn_2",
+            "email_addresses": [
+                {"id": "idn_1", "email_address": "old@example.org"},
+                {"id": "idn_2", "email_address": "dr.a@example.org"}
+            ]
+        }))
+        .unwrap();
+        assert_eq!(user.email.as_deref(), Some("dr.a@example.org"));
+        assert_eq!(user.external_id.as_deref(), Some("42"));
+
+        let bare = parse_clerk_user(&serde_json::json!({"id": "user_2def", "email_addresses": []})).unwrap();
+        assert_eq!((bare.email, bare.external_id, bare.created_at), (None, None, None));
+        assert!(parse_clerk_user(&serde_json::json!({"email_addresses": []})).is_none());
+    }
+
     // Note: These tests require a valid Clerk API key and will make real API calls
     // In production, consider mocking the HTTP client
 
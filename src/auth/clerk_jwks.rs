@@ -1,22 +1,45 @@
 use jsonwebtoken::{jwk::JwkSet, DecodingKey};
 use moka::future::Cache;
-use std::sync::Arc;
-use std::time::Duration;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+/// How long a fetched key set is used before Clerk is asked again
+const JWKS_TTL: Duration = Duration::from_secs(3600);
+
+/// How often the background task refetches, well inside the TTL so requests rarely wait on Clerk
+const JWKS_REFRESH_INTERVAL: Duration = Duration::from_secs(900);
+
+/// A hung Clerk must not hold up every request waiting on the key set
+const JWKS_FETCH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// After a failed fetch, requests go straight to the stale copy for this long instead of each retrying
+const JWKS_FAILURE_BACKOFF: Duration = Duration::from_secs(30);
 
 pub struct JwksCache {
     cache: Cache<String, Arc<JwkSet>>,
     jwks_url: String,
+    /// Last key set fetched successfully and when, kept past the TTL in case Clerk is unreachable
+    last_good: RwLock<Option<(Arc<JwkSet>, Instant)>>,
+    /// How long past the TTL the last good set may still be served when a fetch fails
+    stale_window: Duration,
+    /// When the last fetch failed; cleared by the next successful one
+    last_failure: RwLock<Option<Instant>>,
+}
+
+/// Whether a key set fetched at `fetched_at` may still be served after a failed fetch
+fn stale_usable(fetched_at: Instant, now: Instant, stale_window: Duration) -> bool {
+    now.saturating_duration_since(fetched_at) < JWKS_TTL + stale_window
 }
 
 impl JwksCache {
-    pub fn new(clerk_domain: &str) -> Self {
+    pub fn new(clerk_domain: &str, stale_window: Duration) -> Self {
         let jwks_url = format!("https://{}/.well-known/jwks.json", clerk_domain);
 
         let cache = Cache::builder()
-            .time_to_live(Duration::from_secs(3600)) // 1 hour TTL
+            .time_to_live(JWKS_TTL)
             .build();
 
-        Self { cache, jwks_url }
+        Self { cache, jwks_url, last_good: RwLock::new(None), stale_window, last_failure: RwLock::new(None) }
     }
 
     pub async fn get_jwks(&self) -> Result<Arc<JwkSet>, String> {
@@ -25,8 +48,46 @@ impl JwksCache {
             return Ok(jwks);
         }
 
-        // Fetch from Clerk
-        let response = reqwest::get(&self.jwks_url)
+        let backing_off = self
+            .last_failure
+            .read()
+            .ok()
+            .and_then(|failed_at| *failed_at)
+            .is_some_and(|failed_at| failed_at.elapsed() < JWKS_FAILURE_BACKOFF);
+        if backing_off {
+            if let Some(jwks) = self.serve_stale() {
+                return Ok(jwks);
+            }
+        }
+
+        match self.refresh().await {
+            Ok(jwks) => Ok(jwks),
+            Err(e) => self.serve_stale().ok_or(e),
+        }
+    }
+
+    /// Fetch the key set from Clerk and cache it
+    pub async fn refresh(&self) -> Result<Arc<JwkSet>, String> {
+        let result = self.fetch().await;
+        crate::handlers::metrics::record_jwks_refresh(if result.is_ok() { "ok" } else { "error" });
+        if let Ok(mut last_failure) = self.last_failure.write() {
+            *last_failure = result.is_err().then(Instant::now);
+        }
+
+        let jwks = Arc::new(result?);
+        self.cache.insert(self.jwks_url.clone(), jwks.clone()).await;
+        if let Ok(mut last_good) = self.last_good.write() {
+            *last_good = Some((jwks.clone(), Instant::now()));
+        }
+
+        Ok(jwks)
+    }
+
+    async fn fetch(&self) -> Result<JwkSet, String> {
+        let response = reqwest::Client::new()
+            .get(&self.jwks_url)
+            .timeout(JWKS_FETCH_TIMEOUT)
+            .send()
             .await
             .map_err(|e| format!("Failed to fetch JWKS: {}", e))?;
 
@@ -34,15 +95,39 @@ impl JwksCache {
             return Err(format!("JWKS endpoint returned {}", response.status()));
         }
 
-        let jwks: JwkSet = response
+        response
             .json()
             .await
-            .map_err(|e| format!("Failed to parse JWKS: {}", e))?;
+            .map_err(|e| format!("Failed to parse JWKS: {}", e))
+    }
 
-        let jwks_arc = Arc::new(jwks);
-        self.cache.insert(self.jwks_url.clone(), jwks_arc.clone()).await;
+    /// The last good key set, if it is still inside the stale window
+    fn serve_stale(&self) -> Option<Arc<JwkSet>> {
+        let (jwks, fetched_at) = self.last_good.read().ok()?.clone()?;
+        if !stale_usable(fetched_at, Instant::now(), self.stale_window) {
+            tracing::error!(age_secs = fetched_at.elapsed().as_secs(), "❌ JWKS unreachable and last good keys too old to serve");
+            return None;
+        }
 
-        Ok(jwks_arc)
+        tracing::warn!(age_secs = fetched_at.elapsed().as_secs(), "⚠️ JWKS unreachable, serving stale keys");
+        crate::handlers::metrics::record_jwks_stale_served(fetched_at.elapsed().as_secs_f64());
+        Some(jwks)
+    }
+
+    /// Spawn the background task that refetches the key set before it expires. A failed refresh
+    /// leaves the cached set in place until the TTL, then requests fall back to the stale copy.
+    pub fn spawn_refresh_job(self: Arc<Self>) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(JWKS_REFRESH_INTERVAL);
+            loop {
+                interval.tick().await;
+
+                match self.refresh().await {
+                    Ok(jwks) => tracing::debug!(keys = jwks.keys.len(), "🔑 JWKS refreshed"),
+                    Err(e) => tracing::warn!(error = %e, "⚠️ JWKS background refresh failed"),
+                }
+            }
+        });
     }
 
     pub async fn get_decoding_key(&self, kid: &str) -> Result<DecodingKey, String> {
@@ -57,3 +142,19 @@ impl JwksCache {
         DecodingKey::from_jwk(jwk).map_err(|e| format!("Failed to create decoding key: {}", e))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stale_usable_within_ttl_plus_window() {
+        let fetched_at = Instant::now();
+        let window = Duration::from_secs(6 * 3600);
+
+        assert!(stale_usable(fetched_at, fetched_at + JWKS_TTL, window));
+        assert!(stale_usable(fetched_at, fetched_at + JWKS_TTL + window - Duration::from_secs(1), window));
+        assert!(!stale_usable(fetched_at, fetched_at + JWKS_TTL + window, window));
+        assert!(!stale_usable(fetched_at, fetched_at + JWKS_TTL, Duration::ZERO));
+    }
+}
@@ -0,0 +1,80 @@
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use subtle::ConstantTimeEq;
+
+use crate::{auth::create_hmac_signature, AppError};
+
+/// Prefix bound into the signature so PIN tokens and display tokens can't be swapped
+const DISPLAY_SCOPE: &str = "display";
+
+/// Generate a read-only rota display token for a role, valid for `valid_days`
+/// Token format: base64(display:role_id:expiry_timestamp:hmac_signature)
+pub fn generate_display_token(role_id: i32, valid_days: i64, secret: &str) -> Result<(String, i64), AppError> {
+    let expiry_time = chrono::Utc::now().timestamp() + valid_days * 24 * 60 * 60;
+
+    let payload = format!("{}:{}:{}", DISPLAY_SCOPE, role_id, expiry_time);
+    let signature = create_hmac_signature(&payload, secret)?;
+    let token = STANDARD.encode(format!("{}:{}", payload, signature).as_bytes());
+
+    Ok((token, expiry_time))
+}
+
+/// Validate a display token and extract the role_id it grants read access to
+pub fn validate_display_token(token: &str, secret: &str) -> Result<i32, AppError> {
+    let decoded_bytes = STANDARD
+        .decode(token)
+        .map_err(|_| AppError::Unauthorized("Invalid display token format".to_string()))?;
+
+    let decoded = String::from_utf8(decoded_bytes)
+        .map_err(|_| AppError::Unauthorized("Invalid display token encoding".to_string()))?;
+
+    // Parse token: display:role_id:expiry_time:signature
+    let parts: Vec<&str> = decoded.split(':').collect();
+
+    if parts.len() != 4 || parts[0] != DISPLAY_SCOPE {
+        return Err(AppError::Unauthorized("Invalid display token structure".to_string()));
+    }
+
+    let role_id: i32 = parts[1]
+        .parse()
+        .map_err(|_| AppError::Unauthorized("Invalid role ID in display token".to_string()))?;
+
+    let expiry_time: i64 = parts[2]
+        .parse()
+        .map_err(|_| AppError::Unauthorized("Invalid expiry time in display token".to_string()))?;
+
+    if chrono::Utc::now().timestamp() > expiry_time {
+        return Err(AppError::Unauthorized("Display token has expired".to_string()));
+    }
+
+    let payload = format!("{}:{}:{}", DISPLAY_SCOPE, role_id, expiry_time);
+    let expected_signature = create_hmac_signature(&payload, secret)?;
+
+    if !bool::from(parts[3].as_bytes().ct_eq(expected_signature.as_bytes())) {
+        return Err(AppError::Unauthorized("Invalid display token".to_string()));
+    }
+
+    Ok(role_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_and_validate_display_token() {
+        let secret = "test_secret_key_for_testing_purposes";
+
+        let (token, _) = generate_display_token(7, 30, secret).unwrap();
+
+        assert_eq!(validate_display_token(&token, secret).unwrap(), 7);
+        assert!(validate_display_token(&token, "wrong_secret").is_err());
+    }
+
+    #[test]
+    fn test_pin_token_is_not_a_display_token() {
+        let secret = "test_secret_key";
+        let pin_token = crate::auth::generate_pin_token(7, secret).unwrap();
+
+        assert!(validate_display_token(&pin_token, secret).is_err());
+    }
+}
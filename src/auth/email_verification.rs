@@ -0,0 +1,204 @@
+use moka::future::Cache;
+use once_cell::sync::Lazy;
+use rand::{Rng, SeedableRng};
+use serde_json::json;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use subtle::ConstantTimeEq;
+use uuid::Uuid;
+
+use crate::{config::AppConfig, AppError};
+
+/// How long an emailed code / magic link stays valid
+pub const EMAIL_CHALLENGE_TTL_SECS: u64 = 10 * 60;
+
+/// Wrong codes allowed before the challenge is discarded
+const MAX_CODE_ATTEMPTS: u8 = 5;
+
+/// Pending email challenge for a generic terminal
+#[derive(Clone)]
+struct EmailChallenge {
+    user_profile_id: i32,
+    requested_by: i32,
+    code: String,
+    /// Shared by every copy read from the cache, so concurrent guesses count against the same total
+    attempts: Arc<AtomicU8>,
+}
+
+// Pending challenges keyed by challenge ID, expired after EMAIL_CHALLENGE_TTL_SECS
+static CHALLENGES: Lazy<Cache<Uuid, EmailChallenge>> = Lazy::new(|| {
+    Cache::builder()
+        .max_capacity(10_000)
+        .time_to_live(Duration::from_secs(EMAIL_CHALLENGE_TTL_SECS))
+        .build()
+});
+
+/// Create a challenge for `user_profile_id`, requested from the terminal logged in as `requested_by`.
+/// Returns the challenge ID (given to the terminal) and the 6-digit code (sent by email only).
+pub async fn create_email_challenge(user_profile_id: i32, requested_by: i32) -> (Uuid, String) {
+    let mut rng = rand::rngs::StdRng::from_entropy();
+    let code = format!("{:06}", rng.gen_range(0..1_000_000));
+    let challenge_id = Uuid::new_v4();
+
+    CHALLENGES
+        .insert(
+            challenge_id,
+            EmailChallenge {
+                user_profile_id,
+                requested_by,
+                code: code.clone(),
+                attempts: Arc::new(AtomicU8::new(0)),
+            },
+        )
+        .await;
+
+    (challenge_id, code)
+}
+
+/// Check a code against a pending challenge and consume it on success.
+/// Returns the verified user_profile_id.
+pub async fn confirm_email_challenge(challenge_id: Uuid, code: &str, requested_by: i32) -> Result<i32, AppError> {
+    let expired = || AppError::BadRequest("Verification code has expired. Please start over.".to_string());
+    let challenge = CHALLENGES.get(&challenge_id).await.ok_or_else(expired)?;
+
+    // Only the terminal that asked for the code can redeem it
+    if challenge.requested_by != requested_by {
+        return Err(AppError::Forbidden("This verification was started from another account".to_string()));
+    }
+
+    // Count the attempt and read the new total in one step, before comparing, so parallel guesses cannot
+    // all pass the limit check
+    let attempt = challenge
+        .attempts
+        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| Some(n.saturating_add(1)))
+        .unwrap_or_default()
+        .saturating_add(1);
+    let too_many = || AppError::Unauthorized("Too many incorrect codes. Please start over.".to_string());
+    if attempt > MAX_CODE_ATTEMPTS {
+        CHALLENGES.invalidate(&challenge_id).await;
+        return Err(too_many());
+    }
+
+    if !bool::from(challenge.code.as_bytes().ct_eq(code.as_bytes())) {
+        if attempt >= MAX_CODE_ATTEMPTS {
+            CHALLENGES.invalidate(&challenge_id).await;
+            return Err(too_many());
+        }
+        return Err(AppError::Unauthorized("Incorrect verification code".to_string()));
+    }
+
+    // Consume the challenge; of two concurrent correct redemptions only one removes it
+    CHALLENGES.remove(&challenge_id).await.map(|c| c.user_profile_id).ok_or_else(expired)
+}
+
+/// Send the verification code (and magic link, if APP_BASE_URL is set) via the Resend API
+pub async fn send_verification_email(
+    config: &AppConfig,
+    to: &str,
+    challenge_id: Uuid,
+    code: &str,
+) -> Result<(), AppError> {
+    let (api_key, from) = match (&config.resend_api_key, &config.email_from) {
+        (Some(api_key), Some(from)) => (api_key, from),
+        _ => {
+            return Err(AppError::Internal(
+                "Email verification is not configured (RESEND_API_KEY / EMAIL_FROM)".to_string(),
+            ))
+        }
+    };
+
+    let minutes = EMAIL_CHALLENGE_TTL_SECS / 60;
+    let mut text = format!(
+        "Your EDRota verification code is {}.\n\nIt expires in {} minutes. If you did not request this, you can ignore this email.",
+        code, minutes
+    );
+    if let Some(base_url) = &config.app_base_url {
+        text.push_str(&format!(
+            "\n\nOr open this link on the ward terminal: {}/verify-identity?challenge={}&code={}",
+            base_url.trim_end_matches('/'),
+            challenge_id,
+            code
+        ));
+    }
+
+    let response = reqwest::Client::new()
+        .post("https://api.resend.com/emails")
+        .header("Authorization", format!("Bearer {}", api_key))
+        .json(&json!({
+            "from": from,
+            "to": [to],
+            "subject": "Your EDRota verification code",
+            "text": text,
+        }))
+        .send()
+        .await
+        .map_err(|e| {
+            tracing::error!(error = %e, "❌ Failed to call email API");
+            AppError::Internal(format!("Failed to send verification email: {}", e))
+        })?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        tracing::error!(status = %status, body = %crate::redaction::redact_body(&body), "❌ Email API returned error");
+        return Err(AppError::Internal(format!("Email API error: {} - {}", status, body)));
+    }
+
+    Ok(())
+}
+
+/// Mask an email address for display on a shared screen (j***@example.com)
+pub fn mask_email(email: &str) -> String {
+    match email.split_once('@') {
+        Some((local, domain)) => {
+            let first = local.chars().next().map(String::from).unwrap_or_default();
+            format!("{}***@{}", first, domain)
+        }
+        None => "***".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_challenge_round_trip_and_single_use() {
+        let (challenge_id, code) = create_email_challenge(42, 7).await;
+
+        assert_eq!(confirm_email_challenge(challenge_id, &code, 7).await.unwrap(), 42);
+        assert!(confirm_email_challenge(challenge_id, &code, 7).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_challenge_locks_after_max_attempts() {
+        let (challenge_id, code) = create_email_challenge(42, 7).await;
+
+        assert!(confirm_email_challenge(challenge_id, &code, 8).await.is_err());
+        for _ in 0..MAX_CODE_ATTEMPTS {
+            assert!(confirm_email_challenge(challenge_id, "not-a-code", 7).await.is_err());
+        }
+        assert!(confirm_email_challenge(challenge_id, &code, 7).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_parallel_guesses_share_the_attempt_limit() {
+        let (challenge_id, code) = create_email_challenge(42, 7).await;
+
+        let guesses = (0..20).map(|i| {
+            let guess = format!("{:06}", i);
+            tokio::spawn(async move { confirm_email_challenge(challenge_id, &guess, 7).await })
+        });
+        for guess in guesses.collect::<Vec<_>>() {
+            let _ = guess.await.unwrap();
+        }
+        assert!(confirm_email_challenge(challenge_id, &code, 7).await.is_err());
+    }
+
+    #[test]
+    fn test_mask_email() {
+        assert_eq!(mask_email("jane.doe@nhs.net"), "j***@nhs.net");
+        assert_eq!(mask_email("invalid"), "***");
+    }
+}
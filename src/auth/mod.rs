@@ -1,10 +1,25 @@
+pub mod action_token;
 pub mod claims;
 pub mod clerk_api;
 pub mod clerk_jwks;
+pub mod display_token;
+pub mod email_verification;
 pub mod jwt;
+pub mod password_policy;
 pub mod pin_token;
+pub mod signature;
+pub mod totp;
+pub mod webauthn;
 
-pub use clerk_api::check_email_in_clerk;
+pub use action_token::{generate_action_token, validate_action_token, ActionToken, ACTION_TOKEN_TTL_SECS};
+pub use clerk_api::{
+    check_email_in_clerk, create_clerk_invitation, create_clerk_user, delete_clerk_user, list_clerk_users, ClerkUser,
+    ClerkUserList,
+};
 pub use clerk_jwks::JwksCache;
+pub use display_token::{generate_display_token, validate_display_token};
+pub use email_verification::{confirm_email_challenge, create_email_challenge, mask_email, send_verification_email};
 pub use jwt::validate_jwt;
 pub use pin_token::{generate_pin_token, validate_pin_token};
+pub use signature::create_hmac_signature;
+pub use totp::{generate_totp_secret, totp_provisioning_uri, verify_totp};
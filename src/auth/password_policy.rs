@@ -0,0 +1,153 @@
+use serde_json::Value;
+use std::time::Duration;
+
+use crate::{config::PasswordPolicy, error::FieldError, AppError};
+
+/// Clerk hashes with bcrypt, which ignores anything past 72 bytes, and refuses longer passwords
+const MAX_PASSWORD_BYTES: usize = 72;
+
+/// Have I Been Pwned's k-anonymity range API: takes the first 5 hex characters of the SHA-1
+const PWNED_RANGE_URL: &str = "https://api.pwnedpasswords.com/range";
+const PWNED_TIMEOUT: Duration = Duration::from_secs(3);
+
+fn field_error(field: &str, code: &'static str, message: impl Into<String>) -> FieldError {
+    FieldError { field: field.to_string(), code, message: message.into() }
+}
+
+/// Policy failures for `new_password` that need no network call
+pub fn check_password(policy: &PasswordPolicy, new_password: &str, current_password: &str) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    if new_password.chars().count() < policy.min_length {
+        errors.push(field_error(
+            "new_password",
+            "TOO_SHORT",
+            format!("Password must be at least {} characters", policy.min_length),
+        ));
+    }
+    if new_password.len() > MAX_PASSWORD_BYTES {
+        errors.push(field_error(
+            "new_password",
+            "TOO_LONG",
+            format!("Password must be at most {} bytes", MAX_PASSWORD_BYTES),
+        ));
+    }
+    if !new_password.is_empty() && new_password == current_password {
+        errors.push(field_error("new_password", "UNCHANGED", "New password must differ from the current one"));
+    }
+    errors
+}
+
+/// The field error for a password found by `is_breached`
+pub fn breached() -> FieldError {
+    field_error("new_password", "BREACHED", "This password has appeared in a data breach; choose another")
+}
+
+/// Upper-case SHA-1 hex of the password, split into the prefix sent to the range API and the suffix matched locally
+fn pwned_hash_parts(password: &str) -> (String, String) {
+    let digest = ring::digest::digest(&ring::digest::SHA1_FOR_LEGACY_USE_ONLY, password.as_bytes());
+    let hash = hex::encode_upper(digest.as_ref());
+    let (prefix, suffix) = hash.split_at(5);
+    (prefix.to_string(), suffix.to_string())
+}
+
+/// Whether a range API response ("SUFFIX:COUNT" per line) lists the suffix; padding entries have a count of 0
+fn range_contains(body: &str, suffix: &str) -> bool {
+    body.lines().filter_map(|line| line.trim().split_once(':')).any(|(candidate, count)| {
+        candidate.eq_ignore_ascii_case(suffix) && count.trim().parse::<u64>().is_ok_and(|count| count > 0)
+    })
+}
+
+/// Whether the password appears in a known breach. The check is advisory: when the API cannot be reached the
+/// password is let through to Clerk, which runs its own breach check.
+pub async fn is_breached(password: &str) -> bool {
+    let (prefix, suffix) = pwned_hash_parts(password);
+    let response = reqwest::Client::new()
+        .get(format!("{}/{}", PWNED_RANGE_URL, prefix))
+        .header("Add-Padding", "true")
+        .timeout(PWNED_TIMEOUT)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status);
+
+    match response {
+        Ok(response) => match response.text().await {
+            Ok(body) => range_contains(&body, &suffix),
+            Err(e) => {
+                tracing::warn!(error = %e, "⚠️ Could not read the breached password response; skipping the check");
+                false
+            }
+        },
+        Err(e) => {
+            tracing::warn!(error = %e, "⚠️ Breached password check unavailable; skipping it");
+            false
+        }
+    }
+}
+
+/// Clerk's password error codes as field error codes
+fn clerk_code(code: &str) -> &'static str {
+    match code {
+        "form_password_pwned" => "BREACHED",
+        "form_password_length_too_short" => "TOO_SHORT",
+        "form_password_size_in_bytes_exceeded" => "TOO_LONG",
+        "form_password_not_strong_enough" | "form_password_validation_failed" => "TOO_WEAK",
+        _ => "INVALID",
+    }
+}
+
+/// Field errors from a Clerk 422 body ({"errors": [{"code", "message", "long_message", "meta": {"param_name"}}]}).
+/// Clerk's "password" is this API's `new_password`. Empty when the body holds no field errors.
+pub fn clerk_field_errors(body: &str) -> Vec<FieldError> {
+    let Ok(value) = serde_json::from_str::<Value>(body) else {
+        return Vec::new();
+    };
+    value["errors"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|error| {
+            let param = error["meta"]["param_name"].as_str()?;
+            let field = if param == "password" { "new_password" } else { param };
+            let message = error["long_message"].as_str().or(error["message"].as_str()).unwrap_or("Invalid value");
+            Some(field_error(field, clerk_code(error["code"].as_str().unwrap_or_default()), message))
+        })
+        .collect()
+}
+
+/// Fail with InvalidFields (422) when there are policy failures
+pub fn policy_error(errors: Vec<FieldError>) -> Result<(), AppError> {
+    if errors.is_empty() {
+        return Ok(());
+    }
+    Err(AppError::InvalidFields { message: "The new password does not meet the password policy".to_string(), errors })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_password_checks() {
+        let policy = PasswordPolicy { min_length: 10, check_breached: false };
+        let codes = |new: &str, current: &str| {
+            check_password(&policy, new, current).into_iter().map(|e| e.code).collect::<Vec<_>>()
+        };
+        assert!(codes("correct horse", "old password").is_empty());
+        assert_eq!(codes("short", "old password"), ["TOO_SHORT"]);
+        assert_eq!(codes(&"x".repeat(73), "old password"), ["TOO_LONG"]);
+        assert_eq!(codes("same password", "same password"), ["UNCHANGED"]);
+
+        // SHA-1 of "password" is 5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8
+        let (prefix, suffix) = pwned_hash_parts("password");
+        assert_eq!(prefix, "5BAA6");
+        assert!(range_contains("0018A45C4D1DEF81644B54AB7F969B88D65:1\r\n1E4C9B93F3F0682250B6CF8331B7EE68FD8:3861493", &suffix));
+        assert!(!range_contains("1E4C9B93F3F0682250B6CF8331B7EE68FD8:0", &suffix));
+
+        let body = r#"{"errors":[{"code":"form_password_pwned","message":"Password has been found in an online data breach.","meta":{"param_name":"password"}}]}"#;
+        let errors = clerk_field_errors(body);
+        assert_eq!(errors.len(), 1);
+        assert_eq!((errors[0].field.as_str(), errors[0].code), ("new_password", "BREACHED"));
+        assert!(clerk_field_errors("not json").is_empty());
+    }
+}
@@ -1,10 +1,6 @@
 use base64::{engine::general_purpose::STANDARD, Engine as _};
-use hmac::{Hmac, Mac};
-use sha2::Sha256;
 
-use crate::AppError;
-
-type HmacSha256 = Hmac<Sha256>;
+use crate::{auth::create_hmac_signature, AppError};
 
 /// Generate a PIN verification token valid for 5 minutes
 /// Token format: base64(user_profile_id:expiry_timestamp:hmac_signature)
@@ -75,19 +71,6 @@ pub fn validate_pin_token(token: &str, secret: &str) -> Result<i32, AppError> {
     Ok(user_profile_id)
 }
 
-/// Create HMAC-SHA256 signature for the given data
-fn create_hmac_signature(data: &str, secret: &str) -> Result<String, AppError> {
-    let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
-        .map_err(|e| AppError::Internal(format!("HMAC initialization error: {}", e)))?;
-
-    mac.update(data.as_bytes());
-
-    let result = mac.finalize();
-    let code_bytes = result.into_bytes();
-
-    Ok(hex::encode(code_bytes))
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
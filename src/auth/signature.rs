@@ -0,0 +1,16 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::AppError;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Hex HMAC-SHA256 signature of `data`, shared by the PIN, display and action tokens
+pub fn create_hmac_signature(data: &str, secret: &str) -> Result<String, AppError> {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
+        .map_err(|e| AppError::Internal(format!("HMAC initialization error: {}", e)))?;
+
+    mac.update(data.as_bytes());
+
+    Ok(hex::encode(mac.finalize().into_bytes()))
+}
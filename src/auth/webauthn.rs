@@ -0,0 +1,245 @@
+//! Server-side WebAuthn checks for passkeys. Registration takes the browser's `getPublicKey()` (SPKI) and
+//! `getAuthenticatorData()` instead of the CBOR attestation object, and accepts no attestation ("none"):
+//! passkeys here are a second factor for accounts that already signed in through Clerk.
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use ring::signature::{self, UnparsedPublicKey, VerificationAlgorithm};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+/// COSE algorithm identifiers offered to authenticators, most preferred first
+pub const COSE_ES256: i32 = -7;
+pub const COSE_EDDSA: i32 = -8;
+pub const COSE_RS256: i32 = -257;
+pub const SUPPORTED_ALGORITHMS: [i32; 3] = [COSE_ES256, COSE_EDDSA, COSE_RS256];
+
+const FLAG_USER_PRESENT: u8 = 0x01;
+const FLAG_USER_VERIFIED: u8 = 0x04;
+const FLAG_ATTESTED_CREDENTIAL: u8 = 0x40;
+
+/// rpIdHash (32) + flags (1) + signCount (4)
+const AUTH_DATA_MIN_LEN: usize = 37;
+/// aaguid (16) + credentialIdLength (2)
+const ATTESTED_HEADER_LEN: usize = 18;
+
+pub fn b64url_decode(field: &str, value: &str) -> Result<Vec<u8>, String> {
+    URL_SAFE_NO_PAD
+        .decode(value.trim_end_matches('='))
+        .map_err(|_| format!("{} is not base64url", field))
+}
+
+pub fn b64url_encode(bytes: &[u8]) -> String {
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+#[derive(Debug, Deserialize)]
+struct ClientData {
+    #[serde(rename = "type")]
+    kind: String,
+    challenge: String,
+    origin: String,
+    #[serde(rename = "crossOrigin", default)]
+    cross_origin: bool,
+}
+
+/// Check clientDataJSON is for this ceremony and origin, returning the challenge it signed so the caller
+/// can consume it
+pub fn verify_client_data(client_data_json: &[u8], expected_type: &str, origin: &str) -> Result<String, String> {
+    let client_data: ClientData =
+        serde_json::from_slice(client_data_json).map_err(|_| "clientDataJSON is not valid".to_string())?;
+
+    if client_data.kind != expected_type {
+        return Err(format!("Expected a {} response", expected_type));
+    }
+    if client_data.origin != origin || client_data.cross_origin {
+        return Err("Origin does not match".to_string());
+    }
+    Ok(client_data.challenge.trim_end_matches('=').to_string())
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct AuthenticatorData {
+    pub rp_id_hash: [u8; 32],
+    pub flags: u8,
+    pub sign_count: u32,
+    /// Present on registration
+    pub credential_id: Option<Vec<u8>>,
+}
+
+pub fn parse_authenticator_data(bytes: &[u8]) -> Result<AuthenticatorData, String> {
+    if bytes.len() < AUTH_DATA_MIN_LEN {
+        return Err("authenticatorData is too short".to_string());
+    }
+
+    let mut rp_id_hash = [0u8; 32];
+    rp_id_hash.copy_from_slice(&bytes[..32]);
+    let flags = bytes[32];
+    let sign_count = u32::from_be_bytes([bytes[33], bytes[34], bytes[35], bytes[36]]);
+
+    let credential_id = if flags & FLAG_ATTESTED_CREDENTIAL != 0 {
+        let attested = &bytes[AUTH_DATA_MIN_LEN..];
+        if attested.len() < ATTESTED_HEADER_LEN {
+            return Err("authenticatorData attested credential is truncated".to_string());
+        }
+        let id_len = usize::from(u16::from_be_bytes([attested[16], attested[17]]));
+        let id = attested
+            .get(ATTESTED_HEADER_LEN..ATTESTED_HEADER_LEN + id_len)
+            .ok_or_else(|| "authenticatorData credential id is truncated".to_string())?;
+        Some(id.to_vec())
+    } else {
+        None
+    };
+
+    Ok(AuthenticatorData { rp_id_hash, flags, sign_count, credential_id })
+}
+
+/// The authenticator must have been asked for this RP and must have verified the user (PIN or biometric)
+pub fn check_authenticator_data(auth_data: &AuthenticatorData, rp_id: &str) -> Result<(), String> {
+    if auth_data.rp_id_hash[..] != Sha256::digest(rp_id.as_bytes())[..] {
+        return Err("Relying party does not match".to_string());
+    }
+    if auth_data.flags & FLAG_USER_PRESENT == 0 || auth_data.flags & FLAG_USER_VERIFIED == 0 {
+        return Err("The authenticator did not verify the user".to_string());
+    }
+    Ok(())
+}
+
+/// Read a DER tag and length at `at`, returning the content range
+fn der_element(der: &[u8], at: usize, tag: u8) -> Result<(usize, usize), String> {
+    let invalid = || "publicKey is not a valid SubjectPublicKeyInfo".to_string();
+    if der.get(at) != Some(&tag) {
+        return Err(invalid());
+    }
+    let first = *der.get(at + 1).ok_or_else(invalid)?;
+    let (len, header) = if first < 0x80 {
+        (usize::from(first), 2)
+    } else {
+        let octets = usize::from(first & 0x7f);
+        if octets == 0 || octets > 2 {
+            return Err(invalid());
+        }
+        let len = der
+            .get(at + 2..at + 2 + octets)
+            .ok_or_else(invalid)?
+            .iter()
+            .fold(0usize, |len, b| (len << 8) | usize::from(*b));
+        (len, 2 + octets)
+    };
+    let start = at + header;
+    if start + len > der.len() {
+        return Err(invalid());
+    }
+    Ok((start, start + len))
+}
+
+/// The key bytes ring expects from a SubjectPublicKeyInfo: the EC point, the Ed25519 key or the PKCS#1
+/// RSAPublicKey, i.e. the BIT STRING after the algorithm identifier
+pub fn spki_public_key(spki: &[u8]) -> Result<&[u8], String> {
+    const SEQUENCE: u8 = 0x30;
+    const BIT_STRING: u8 = 0x03;
+
+    let (body, end) = der_element(spki, 0, SEQUENCE)?;
+    let (_, algorithm_end) = der_element(spki, body, SEQUENCE)?;
+    let (key, key_end) = der_element(spki, algorithm_end, BIT_STRING)?;
+    if key_end != end || spki.get(key) != Some(&0) {
+        return Err("publicKey is not a valid SubjectPublicKeyInfo".to_string());
+    }
+    Ok(&spki[key + 1..key_end])
+}
+
+fn verification_algorithm(algorithm: i32) -> Result<&'static dyn VerificationAlgorithm, String> {
+    match algorithm {
+        COSE_ES256 => Ok(&signature::ECDSA_P256_SHA256_ASN1),
+        COSE_EDDSA => Ok(&signature::ED25519),
+        COSE_RS256 => Ok(&signature::RSA_PKCS1_2048_8192_SHA256),
+        other => Err(format!("Unsupported public key algorithm {}", other)),
+    }
+}
+
+/// Check a stored key is usable before saving it
+pub fn check_public_key(algorithm: i32, spki: &[u8]) -> Result<(), String> {
+    verification_algorithm(algorithm)?;
+    spki_public_key(spki).map(|_| ())
+}
+
+/// Verify an assertion signature over authenticatorData || SHA-256(clientDataJSON)
+pub fn verify_assertion_signature(
+    algorithm: i32,
+    spki: &[u8],
+    authenticator_data: &[u8],
+    client_data_json: &[u8],
+    signature: &[u8],
+) -> Result<(), String> {
+    let key = UnparsedPublicKey::new(verification_algorithm(algorithm)?, spki_public_key(spki)?);
+    let mut signed = authenticator_data.to_vec();
+    signed.extend_from_slice(&Sha256::digest(client_data_json));
+    key.verify(&signed, signature).map_err(|_| "Signature does not verify".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::rand::SystemRandom;
+    use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_ASN1_SIGNING};
+
+    /// DER header of a P-256 SubjectPublicKeyInfo, followed by the 65-byte uncompressed point
+    const P256_SPKI_PREFIX: [u8; 26] = [
+        0x30, 0x59, 0x30, 0x13, 0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01, 0x06, 0x08, 0x2a, 0x86, 0x48,
+        0xce, 0x3d, 0x03, 0x01, 0x07, 0x03, 0x42, 0x00,
+    ];
+
+    fn auth_data(rp_id: &str, flags: u8, sign_count: u32, credential_id: Option<&[u8]>) -> Vec<u8> {
+        let mut data = Sha256::digest(rp_id.as_bytes()).to_vec();
+        data.push(flags);
+        data.extend_from_slice(&sign_count.to_be_bytes());
+        if let Some(id) = credential_id {
+            data.extend_from_slice(&[0u8; 16]);
+            data.extend_from_slice(&(id.len() as u16).to_be_bytes());
+            data.extend_from_slice(id);
+        }
+        data
+    }
+
+    #[test]
+    fn test_client_data_checks() {
+        let json = br#"{"type":"webauthn.get","challenge":"abc","origin":"https://rota.example.org"}"#;
+        assert_eq!(verify_client_data(json, "webauthn.get", "https://rota.example.org").unwrap(), "abc");
+        assert!(verify_client_data(json, "webauthn.create", "https://rota.example.org").is_err());
+        assert!(verify_client_data(json, "webauthn.get", "https://evil.example.org").is_err());
+        assert!(verify_client_data(b"{}", "webauthn.get", "https://rota.example.org").is_err());
+    }
+
+    #[test]
+    fn test_authenticator_data() {
+        let data = auth_data("rota.example.org", 0x45, 7, Some(b"cred-1"));
+        let parsed = parse_authenticator_data(&data).unwrap();
+        assert_eq!((parsed.sign_count, parsed.credential_id.as_deref()), (7, Some(&b"cred-1"[..])));
+        assert!(check_authenticator_data(&parsed, "rota.example.org").is_ok());
+        assert!(check_authenticator_data(&parsed, "example.org").is_err());
+
+        // Present but not verified
+        let parsed = parse_authenticator_data(&auth_data("rota.example.org", 0x01, 0, None)).unwrap();
+        assert!(check_authenticator_data(&parsed, "rota.example.org").is_err());
+        assert!(parse_authenticator_data(&[0u8; 10]).is_err());
+    }
+
+    #[test]
+    fn test_verify_es256_assertion() {
+        let rng = SystemRandom::new();
+        let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, &rng).unwrap();
+        let key_pair = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, pkcs8.as_ref(), &rng).unwrap();
+        let spki = [&P256_SPKI_PREFIX[..], key_pair.public_key().as_ref()].concat();
+        assert!(check_public_key(COSE_ES256, &spki).is_ok());
+        assert!(check_public_key(-35, &spki).is_err());
+
+        let data = auth_data("rota.example.org", 0x05, 1, None);
+        let client_data = br#"{"type":"webauthn.get","challenge":"abc","origin":"https://rota.example.org"}"#;
+        let signed = [&data[..], &Sha256::digest(client_data)[..]].concat();
+        let signature = key_pair.sign(&rng, &signed).unwrap();
+
+        assert!(verify_assertion_signature(COSE_ES256, &spki, &data, client_data, signature.as_ref()).is_ok());
+        let tampered = auth_data("rota.example.org", 0x05, 2, None);
+        assert!(verify_assertion_signature(COSE_ES256, &spki, &tampered, client_data, signature.as_ref()).is_err());
+        assert!(spki_public_key(&spki[..40]).is_err());
+    }
+}
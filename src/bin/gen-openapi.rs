@@ -0,0 +1,52 @@
+//! Writes the OpenAPI spec to disk so the frontend codegen can run without a server.
+//!
+//! `cargo run --bin gen-openapi [-- PATH]` writes to PATH (default `openapi.json`).
+//! `cargo run --bin gen-openapi -- --check [PATH]` exits non-zero when PATH is out of date.
+
+use edrota4_axum::openapi::render_spec;
+use std::process::ExitCode;
+
+const DEFAULT_PATH: &str = "openapi.json";
+
+fn main() -> ExitCode {
+    let mut check = false;
+    let mut path = DEFAULT_PATH.to_string();
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--check" => check = true,
+            _ => path = arg,
+        }
+    }
+
+    let json = match render_spec() {
+        Ok(json) => json,
+        Err(e) => {
+            eprintln!("❌ Failed to serialise OpenAPI spec: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    if check {
+        return match std::fs::read_to_string(&path) {
+            Ok(existing) if existing == json => {
+                println!("✅ {} is up to date", path);
+                ExitCode::SUCCESS
+            }
+            Ok(_) => {
+                eprintln!("❌ {} is out of date; run `cargo run --bin gen-openapi`", path);
+                ExitCode::FAILURE
+            }
+            Err(e) => {
+                eprintln!("❌ Failed to read {}: {}", path, e);
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    if let Err(e) = std::fs::write(&path, &json) {
+        eprintln!("❌ Failed to write {}: {}", path, e);
+        return ExitCode::FAILURE;
+    }
+    println!("📄 Wrote OpenAPI spec to {} ({} bytes)", path, json.len());
+    ExitCode::SUCCESS
+}
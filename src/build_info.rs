@@ -0,0 +1,52 @@
+//! What this binary is: crate version, git commit, build time and a hash of its OpenAPI spec.
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// Build details served by GET /api/version and logged at startup
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct BuildInfo {
+    /// Crate version (semver)
+    pub version: &'static str,
+    /// Commit the binary was built from ("unknown" outside a git checkout without GIT_SHA)
+    pub git_sha: &'static str,
+    pub built_at: DateTime<Utc>,
+    /// SHA-256 (hex) of the spec exactly as `gen-openapi` writes openapi.json; it changes with any API change
+    pub openapi_sha256: String,
+}
+
+static BUILD_INFO: Lazy<BuildInfo> = Lazy::new(|| BuildInfo {
+    version: env!("CARGO_PKG_VERSION"),
+    git_sha: env!("EDROTA_GIT_SHA"),
+    built_at: env!("EDROTA_BUILD_TIMESTAMP")
+        .parse()
+        .ok()
+        .and_then(|secs| DateTime::from_timestamp(secs, 0))
+        .unwrap_or_default(),
+    openapi_sha256: crate::openapi::render_spec().map(|spec| spec_hash(&spec)).unwrap_or_default(),
+});
+
+/// Computed once; the spec is rendered the first time it is asked for
+pub fn build_info() -> &'static BuildInfo {
+    &BUILD_INFO
+}
+
+fn spec_hash(spec: &str) -> String {
+    hex::encode(ring::digest::digest(&ring::digest::SHA256, spec.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_info() {
+        let info = build_info();
+        assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(info.openapi_sha256.len(), 64);
+        assert_eq!(info.openapi_sha256, spec_hash(&crate::openapi::render_spec().unwrap()));
+        assert!(info.built_at.timestamp() > 0);
+    }
+}
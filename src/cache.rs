@@ -0,0 +1,196 @@
+use axum::http::{header, HeaderName, HeaderValue};
+use moka::future::Cache;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::extractors::permissions::PermissionSnapshot;
+use crate::models::{DirectoryWorkplace, FeatureFlag, Role, TimeOffCategory, Workplace};
+
+/// Server-side TTL for cached reference lists; mutations invalidate explicitly
+const REFERENCE_TTL: Duration = Duration::from_secs(60);
+
+/// The staff directory changes with every role assignment, so it is only cached briefly
+const DIRECTORY_TTL: Duration = Duration::from_secs(30);
+
+/// Per-user role assignments behind every permission check; mutations invalidate explicitly
+const USER_ROLES_TTL: Duration = Duration::from_secs(30);
+const USER_ROLES_CAPACITY: u64 = 1_000;
+
+/// Resolved profiles behind every authenticated request; profile and role mutations invalidate explicitly
+const PROFILE_TTL: Duration = Duration::from_secs(60);
+const PROFILE_CAPACITY: u64 = 10_000;
+
+/// A sign-in is recorded once per session, IP and user agent within this window
+const LOGIN_AUDIT_TTL: Duration = Duration::from_secs(12 * 3600);
+const LOGIN_AUDIT_CAPACITY: u64 = 10_000;
+
+/// What the auth extractor needs from "Users" for a signed-in Clerk user
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedProfile {
+    pub profile_id: i32,
+    pub is_super_admin: bool,
+    pub email: String,
+}
+
+/// Single-entry cache for an unfiltered list endpoint (roles, workplaces, time-off categories)
+pub struct ListCache<T> {
+    inner: Cache<(), Vec<T>>,
+}
+
+impl<T: Clone + Send + Sync + 'static> ListCache<T> {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            inner: Cache::builder().time_to_live(ttl).build(),
+        }
+    }
+
+    pub async fn get(&self) -> Option<Vec<T>> {
+        self.inner.get(&()).await
+    }
+
+    pub async fn insert(&self, items: Vec<T>) {
+        self.inner.insert((), items).await;
+    }
+
+    pub async fn invalidate(&self) {
+        self.inner.invalidate(&()).await;
+    }
+}
+
+/// Every server-side cache, owned by AppState so each app (and each test) gets its own and any module
+/// can invalidate through the hooks below
+pub struct CacheRegistry {
+    /// clerk_user_id → resolved profile, read by the auth extractor
+    pub profiles: Cache<String, CachedProfile>,
+    /// UserRoles rows and the permissions they add up to per profile_id, read by the permission checks
+    pub user_roles: Cache<i32, Arc<PermissionSnapshot>>,
+    /// All roles (unfiltered), including their joined workplace and active staff counts
+    pub roles: ListCache<Role>,
+    /// Every role, archived included, with its workplace: the synthetic assignments shown for super admins
+    pub super_admin_roles: ListCache<Role>,
+    pub workplaces: ListCache<Workplace>,
+    /// Time-off categories (no mutation endpoints; TTL only)
+    pub time_off_categories: ListCache<TimeOffCategory>,
+    /// Staff directory with every member's contact details; redacted per caller on the way out
+    pub directory: ListCache<DirectoryWorkplace>,
+    /// Feature flags, read on every guarded request
+    pub feature_flags: ListCache<FeatureFlag>,
+    /// Sign-ins already written to "LoginAudit", keyed by profile, session, IP and user agent
+    pub recorded_logins: Cache<String, ()>,
+}
+
+impl Default for CacheRegistry {
+    fn default() -> Self {
+        Self {
+            profiles: Cache::builder()
+                .time_to_live(PROFILE_TTL)
+                .max_capacity(PROFILE_CAPACITY)
+                .support_invalidation_closures()
+                .build(),
+            user_roles: Cache::builder()
+                .time_to_live(USER_ROLES_TTL)
+                .max_capacity(USER_ROLES_CAPACITY)
+                .build(),
+            roles: ListCache::new(REFERENCE_TTL),
+            super_admin_roles: ListCache::new(REFERENCE_TTL),
+            workplaces: ListCache::new(REFERENCE_TTL),
+            time_off_categories: ListCache::new(REFERENCE_TTL),
+            directory: ListCache::new(DIRECTORY_TTL),
+            feature_flags: ListCache::new(REFERENCE_TTL),
+            recorded_logins: Cache::builder()
+                .time_to_live(LOGIN_AUDIT_TTL)
+                .max_capacity(LOGIN_AUDIT_CAPACITY)
+                .build(),
+        }
+    }
+}
+
+impl CacheRegistry {
+    /// A profile's email or admin flag may have changed; entries are keyed by Clerk id, so match on profile_id
+    pub fn invalidate_profile(&self, user_profile_id: i32) {
+        if let Err(e) = self.profiles.invalidate_entries_if(move |_, p| p.profile_id == user_profile_id) {
+            tracing::warn!(error = %e, user_profile_id, "Failed to invalidate cached profile");
+        }
+    }
+
+    /// A user's role assignments changed: their profile, permissions, role staff counts and the directory
+    pub async fn invalidate_user_roles(&self, user_profile_id: i32) {
+        self.invalidate_profile(user_profile_id);
+        self.user_roles.invalidate(&user_profile_id).await;
+        self.roles.invalidate().await;
+        self.directory.invalidate().await;
+    }
+
+    /// Roles were created, changed or deleted; deleting one drops every assignment to it
+    pub async fn invalidate_roles(&self) {
+        self.roles.invalidate().await;
+        self.super_admin_roles.invalidate().await;
+        self.directory.invalidate().await;
+        self.user_roles.invalidate_all();
+    }
+
+    /// Workplace mutations also change the workplace embedded in each role, and deleting one drops its roles
+    pub async fn invalidate_workplaces(&self) {
+        self.workplaces.invalidate().await;
+        self.invalidate_roles().await;
+    }
+}
+
+/// Cache-Control header for public reference data; a max-age of 0 disables browser caching
+pub fn cache_control(max_age_secs: u64) -> [(HeaderName, HeaderValue); 1] {
+    let value = if max_age_secs == 0 {
+        HeaderValue::from_static("no-cache")
+    } else {
+        HeaderValue::from_str(&format!("public, max-age={}", max_age_secs))
+            .unwrap_or_else(|_| HeaderValue::from_static("no-cache"))
+    };
+    [(header::CACHE_CONTROL, value)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::extractors::permissions::UserRoleRow;
+
+    #[tokio::test]
+    async fn test_registries_are_independent_and_hooks_invalidate() {
+        let role_row = |role_id| UserRoleRow {
+            id: role_id,
+            role_id,
+            user_profile_id: 7,
+            can_edit_rota: true,
+            can_access_diary: false,
+            can_work_shifts: true,
+            can_edit_templates: false,
+            can_edit_staff: false,
+            can_view_staff_details: false,
+            can_approve_rota: false,
+            can_set_pay: false,
+            effective_until: None,
+        };
+
+        let caches = CacheRegistry::default();
+        let other = CacheRegistry::default();
+        caches.user_roles.insert(7, Arc::new(PermissionSnapshot::new(vec![role_row(1)]))).await;
+        caches.user_roles.insert(8, Arc::default()).await;
+        caches.workplaces.insert(vec![]).await;
+        assert!(other.user_roles.get(&7).await.is_none());
+
+        let profile = |profile_id| CachedProfile { profile_id, is_super_admin: false, email: String::new() };
+        caches.profiles.insert("user_a".to_string(), profile(7)).await;
+        caches.profiles.insert("user_b".to_string(), profile(8)).await;
+
+        caches.invalidate_user_roles(7).await;
+        assert!(caches.user_roles.get(&7).await.is_none());
+        assert!(caches.user_roles.get(&8).await.is_some());
+        caches.profiles.run_pending_tasks().await;
+        assert!(caches.profiles.get("user_a").await.is_none());
+        assert_eq!(caches.profiles.get("user_b").await, Some(profile(8)));
+
+        caches.super_admin_roles.insert(vec![]).await;
+        caches.invalidate_workplaces().await;
+        assert!(caches.workplaces.get().await.is_none());
+        assert!(caches.super_admin_roles.get().await.is_none());
+        assert!(caches.user_roles.get(&8).await.is_none());
+    }
+}
@@ -1,4 +1,6 @@
 use std::env;
+use std::net::{IpAddr, SocketAddr};
+use std::path::PathBuf;
 
 #[derive(Clone, Debug)]
 pub struct AppConfig {
@@ -8,8 +10,108 @@ pub struct AppConfig {
     pub clerk_domain: String,
     pub pin_token_secret: String,
     pub debug_key: String,
+    pub resend_api_key: Option<String>,
+    pub email_from: Option<String>,
+    pub app_base_url: Option<String>,
+    pub reference_cache_max_age: u64,
+    /// How long past its 1-hour TTL the last good JWKS may be served while Clerk is unreachable
+    pub jwks_stale_window: std::time::Duration,
+    pub pii_encryption_key: Option<String>,
+    pub pii_encryption_key_previous: Option<String>,
+    pub metrics_latency_buckets: Vec<f64>,
+    pub metrics_global_labels: Vec<(String, String)>,
+    /// Cookies checked for the session JWT, in order
+    pub session_cookie_names: Vec<String>,
+    pub retention: RetentionPolicy,
+    pub password_policy: PasswordPolicy,
+    /// Attachment bucket; attachment endpoints answer 500 without it
+    pub attachments: Option<AttachmentStorage>,
+    /// Relying party for passkeys; passkey endpoints answer 500 without it
+    pub webauthn: Option<WebAuthnConfig>,
+    /// Where the server accepts connections
+    pub listen: ListenAddress,
+    /// Serve HTTPS directly with this certificate; plain HTTP without it (e.g. behind a reverse proxy)
+    pub tls: Option<TlsFiles>,
+    /// Reverse proxies in front of the API that append to X-Forwarded-For; 0 uses the socket peer as the client IP
+    pub trusted_proxy_hops: usize,
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ListenAddress {
+    Tcp(SocketAddr),
+    /// Unix domain socket for a reverse proxy on the same host; peers have no IP address
+    Unix(PathBuf),
+}
+
+/// PEM files for TLS termination, read once at startup
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TlsFiles {
+    /// Certificate chain, leaf first
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
+}
+
+/// Where passkeys are scoped: the browser origin of the frontend and its relying party ID
+#[derive(Clone, Debug)]
+pub struct WebAuthnConfig {
+    /// e.g. "rota.example.org"; a registrable suffix of the origin's host
+    pub rp_id: String,
+    /// e.g. "https://rota.example.org", compared exactly against clientDataJSON
+    pub origin: String,
+}
+
+/// S3-compatible bucket (AWS S3, Cloudflare R2, MinIO) for diary and shift attachments
+#[derive(Clone, Debug)]
+pub struct AttachmentStorage {
+    /// Bucket base URL, virtual-hosted ("https://bucket.s3.eu-west-2.amazonaws.com") or path-style
+    /// ("https://minio.internal:9000/bucket"); object keys are appended to its path
+    pub bucket_url: reqwest::Url,
+    pub region: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    /// Virus-scanning hook: uploads are POSTed here and must come back {"infected": false}
+    pub scan_url: Option<String>,
+}
+
+/// How long records are kept before the retention job purges or anonymises them.
+/// A period of 0 days disables that rule.
+#[derive(Clone, Debug)]
+pub struct RetentionPolicy {
+    /// Run the daily job; the dry-run report works either way
+    pub job_enabled: bool,
+    pub deleted_diary_days: u32,
+    pub audit_actor_days: u32,
+    pub access_log_days: u32,
+    pub read_notification_days: u32,
+    pub login_audit_days: u32,
+}
+
+/// Checks on a new password before it is sent to Clerk, which applies its own rules after these
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PasswordPolicy {
+    pub min_length: usize,
+    /// Look the password up in Have I Been Pwned's range API (only a 5-character hash prefix leaves the server)
+    pub check_breached: bool,
+}
+
+/// Clerk refuses shorter passwords, so the minimum cannot go below this
+const CLERK_MIN_PASSWORD_LENGTH: usize = 8;
+
+/// Keys stay valid for hours after Clerk rotates them, so a day of stale keys is a safe default
+const DEFAULT_JWKS_STALE_WINDOW_SECS: u64 = 86400;
+
+/// Cookie Clerk sets for the TanStack frontend
+const DEFAULT_SESSION_COOKIE: &str = "__session";
+
+/// More proxies than this in front of one API is a misconfiguration
+const MAX_TRUSTED_PROXY_HOPS: usize = 5;
+
+const DEFAULT_HOST: &str = "0.0.0.0";
+const DEFAULT_PORT: u16 = 8080;
+
+/// Default http_request_duration_seconds buckets (seconds)
+const DEFAULT_LATENCY_BUCKETS: &[f64] = &[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
 impl AppConfig {
     pub fn from_env() -> Result<Self, String> {
         let database_url = env::var("DATABASE_URL")
@@ -31,6 +133,100 @@ impl AppConfig {
         let debug_key = env::var("DEBUG_KEY")
             .map_err(|_| "DEBUG_KEY must be set".to_string())?;
 
+        // Optional: email verification for generic terminals is disabled without these
+        let resend_api_key = env::var("RESEND_API_KEY").ok();
+        let email_from = env::var("EMAIL_FROM").ok();
+        let app_base_url = env::var("APP_BASE_URL").ok();
+
+        // Browser cache lifetime for reference data (seconds, 0 disables)
+        let reference_cache_max_age = env::var("REFERENCE_CACHE_MAX_AGE")
+            .ok()
+            .map(|v| v.parse().map_err(|_| "REFERENCE_CACHE_MAX_AGE must be a number of seconds".to_string()))
+            .transpose()?
+            .unwrap_or(300);
+
+        // Serve-stale window for Clerk's JWKS (seconds past the 1-hour TTL, 0 disables)
+        let jwks_stale_window = env::var("JWKS_STALE_WINDOW_SECS")
+            .ok()
+            .map(|v| v.trim().parse().map_err(|_| "JWKS_STALE_WINDOW_SECS must be a number of seconds".to_string()))
+            .transpose()?
+            .map(std::time::Duration::from_secs)
+            .unwrap_or(std::time::Duration::from_secs(DEFAULT_JWKS_STALE_WINDOW_SECS));
+
+        // Optional: base64 AES-256 key for PII columns (tel, secondary_emails); plaintext without it.
+        // During rotation the old key goes in PII_ENCRYPTION_KEY_PREVIOUS until `rekey-pii` has run.
+        let pii_encryption_key = env::var("PII_ENCRYPTION_KEY").ok();
+        let pii_encryption_key_previous = env::var("PII_ENCRYPTION_KEY_PREVIOUS").ok();
+
+        // Optional: latency histogram buckets ("0.05,0.1,0.5") and constant labels on every
+        // series ("environment=prod,region=eu-west-2")
+        let metrics_latency_buckets = env::var("METRICS_LATENCY_BUCKETS")
+            .ok()
+            .map(|v| parse_buckets(&v))
+            .transpose()?
+            .unwrap_or_else(|| DEFAULT_LATENCY_BUCKETS.to_vec());
+        let metrics_global_labels = env::var("METRICS_GLOBAL_LABELS")
+            .ok()
+            .map(|v| parse_labels(&v))
+            .transpose()?
+            .unwrap_or_default();
+
+        // Optional: session cookie names, comma-separated and tried in order (default "__session")
+        let session_cookie_names = env::var("SESSION_COOKIE_NAMES")
+            .ok()
+            .map(|v| parse_cookie_names(&v))
+            .transpose()?
+            .unwrap_or_else(|| vec![DEFAULT_SESSION_COOKIE.to_string()]);
+
+        // Optional: retention periods in days (0 disables a rule). The job only runs with RETENTION_JOB_ENABLED=true.
+        let retention = RetentionPolicy {
+            job_enabled: env::var("RETENTION_JOB_ENABLED").is_ok_and(|v| v == "true"),
+            deleted_diary_days: parse_days("RETENTION_DELETED_DIARY_DAYS", 730)?,
+            audit_actor_days: parse_days("RETENTION_AUDIT_ACTOR_DAYS", 2190)?,
+            access_log_days: parse_days("RETENTION_ACCESS_LOG_DAYS", 2190)?,
+            read_notification_days: parse_days("RETENTION_READ_NOTIFICATION_DAYS", 365)?,
+            login_audit_days: parse_days("RETENTION_LOGIN_AUDIT_DAYS", 365)?,
+        };
+
+        // Optional: password policy for self-service changes (minimum length, at least Clerk's 8)
+        let password_policy = PasswordPolicy {
+            min_length: parse_password_min_length(env::var("PASSWORD_MIN_LENGTH").ok().as_deref())?,
+            check_breached: env::var("PASSWORD_BREACH_CHECK").is_ok_and(|v| v == "true"),
+        };
+
+        // Optional: attachment bucket (the access keys are required once ATTACHMENT_BUCKET_URL is set)
+        let attachments = env::var("ATTACHMENT_BUCKET_URL")
+            .ok()
+            .map(|url| -> Result<AttachmentStorage, String> {
+                Ok(AttachmentStorage {
+                    bucket_url: parse_bucket_url(&url)?,
+                    region: env::var("ATTACHMENT_BUCKET_REGION").unwrap_or_else(|_| "us-east-1".to_string()),
+                    access_key_id: env::var("ATTACHMENT_ACCESS_KEY_ID")
+                        .map_err(|_| "ATTACHMENT_ACCESS_KEY_ID must be set with ATTACHMENT_BUCKET_URL".to_string())?,
+                    secret_access_key: env::var("ATTACHMENT_SECRET_ACCESS_KEY")
+                        .map_err(|_| "ATTACHMENT_SECRET_ACCESS_KEY must be set with ATTACHMENT_BUCKET_URL".to_string())?,
+                    scan_url: env::var("ATTACHMENT_SCAN_URL").ok(),
+                })
+            })
+            .transpose()?;
+
+        // Optional: passkeys, defaulting to the frontend at APP_BASE_URL and its host as the relying party
+        let webauthn = env::var("WEBAUTHN_ORIGIN")
+            .ok()
+            .or_else(|| app_base_url.clone())
+            .map(|origin| parse_webauthn(&origin, env::var("WEBAUTHN_RP_ID").ok().as_deref()))
+            .transpose()?;
+
+        // Optional: HOST/PORT (default 0.0.0.0:8080), or UNIX_SOCKET_PATH instead of TCP. With TLS_CERT_PATH and
+        // TLS_KEY_PATH the server terminates HTTPS itself.
+        let listen = parse_listen(
+            env::var("HOST").ok().as_deref(),
+            env::var("PORT").ok().as_deref(),
+            env::var("UNIX_SOCKET_PATH").ok().as_deref(),
+        )?;
+        let tls = parse_tls(env::var("TLS_CERT_PATH").ok(), env::var("TLS_KEY_PATH").ok(), &listen)?;
+        let trusted_proxy_hops = parse_trusted_proxy_hops(env::var("TRUSTED_PROXY_HOPS").ok().as_deref())?;
+
         Ok(Self {
             database_url,
             clerk_secret_key,
@@ -38,8 +234,172 @@ impl AppConfig {
             clerk_domain,
             pin_token_secret,
             debug_key,
+            resend_api_key,
+            email_from,
+            app_base_url,
+            reference_cache_max_age,
+            jwks_stale_window,
+            pii_encryption_key,
+            pii_encryption_key_previous,
+            metrics_latency_buckets,
+            metrics_global_labels,
+            session_cookie_names,
+            retention,
+            password_policy,
+            attachments,
+            webauthn,
+            listen,
+            tls,
+            trusted_proxy_hops,
+        })
+    }
+}
+
+/// Parse comma-separated histogram bucket boundaries, which must be positive and ascending
+fn parse_buckets(value: &str) -> Result<Vec<f64>, String> {
+    let buckets = value
+        .split(',')
+        .map(|b| b.trim().parse::<f64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| "METRICS_LATENCY_BUCKETS must be comma-separated numbers".to_string())?;
+
+    if buckets.is_empty() || buckets[0] <= 0.0 || buckets.windows(2).any(|w| w[0] >= w[1]) {
+        return Err("METRICS_LATENCY_BUCKETS must be positive and strictly ascending".to_string());
+    }
+
+    Ok(buckets)
+}
+
+/// Parse comma-separated key=value label pairs
+fn parse_labels(value: &str) -> Result<Vec<(String, String)>, String> {
+    value
+        .split(',')
+        .filter(|pair| !pair.trim().is_empty())
+        .map(|pair| {
+            let (key, val) = pair
+                .split_once('=')
+                .ok_or_else(|| format!("METRICS_GLOBAL_LABELS entry '{}' must be key=value", pair.trim()))?;
+            let key = key.trim();
+            if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+                return Err(format!("METRICS_GLOBAL_LABELS key '{}' is not a valid label name", key));
+            }
+            Ok((key.to_string(), val.trim().to_string()))
         })
+        .collect()
+}
+
+/// Read a whole number of days from the environment, falling back to a default
+fn parse_days(name: &str, default: u32) -> Result<u32, String> {
+    env::var(name)
+        .ok()
+        .map(|v| v.trim().parse().map_err(|_| format!("{} must be a whole number of days", name)))
+        .transpose()
+        .map(|days| days.unwrap_or(default))
+}
+
+/// PASSWORD_MIN_LENGTH, defaulting to and no lower than Clerk's minimum
+fn parse_password_min_length(value: Option<&str>) -> Result<usize, String> {
+    let Some(value) = value else {
+        return Ok(CLERK_MIN_PASSWORD_LENGTH);
+    };
+    match value.trim().parse::<usize>() {
+        Ok(length) if (CLERK_MIN_PASSWORD_LENGTH..=72).contains(&length) => Ok(length),
+        _ => Err(format!("PASSWORD_MIN_LENGTH must be a number from {} to 72", CLERK_MIN_PASSWORD_LENGTH)),
+    }
+}
+
+/// TRUSTED_PROXY_HOPS, defaulting to 0 (no proxy: X-Forwarded-For is ignored)
+fn parse_trusted_proxy_hops(value: Option<&str>) -> Result<usize, String> {
+    let Some(value) = value else {
+        return Ok(0);
+    };
+    match value.trim().parse::<usize>() {
+        Ok(hops) if hops <= MAX_TRUSTED_PROXY_HOPS => Ok(hops),
+        _ => Err(format!("TRUSTED_PROXY_HOPS must be a number from 0 to {}", MAX_TRUSTED_PROXY_HOPS)),
+    }
+}
+
+/// Parse comma-separated cookie names; each must be a valid cookie-name token
+fn parse_cookie_names(value: &str) -> Result<Vec<String>, String> {
+    let names: Vec<String> = value
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(String::from)
+        .collect();
+
+    if names.is_empty() {
+        return Err("SESSION_COOKIE_NAMES must list at least one cookie name".to_string());
+    }
+    if let Some(bad) = names
+        .iter()
+        .find(|name| !name.chars().all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c)))
+    {
+        return Err(format!("SESSION_COOKIE_NAMES entry '{}' is not a valid cookie name", bad));
+    }
+
+    Ok(names)
+}
+
+/// Bucket URL must be http(s) with a host and no query, since presigned URLs add their own
+fn parse_bucket_url(value: &str) -> Result<reqwest::Url, String> {
+    let url = reqwest::Url::parse(value.trim()).map_err(|_| "ATTACHMENT_BUCKET_URL must be a URL".to_string())?;
+    if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() || url.query().is_some() {
+        return Err("ATTACHMENT_BUCKET_URL must be an http(s) URL with a host and no query".to_string());
+    }
+    Ok(url)
+}
+
+/// Origin must be http(s) with a host, and the RP ID (default: that host) must be the host or a parent domain
+fn parse_webauthn(origin: &str, rp_id: Option<&str>) -> Result<WebAuthnConfig, String> {
+    let url = reqwest::Url::parse(origin.trim()).map_err(|_| "WEBAUTHN_ORIGIN must be a URL".to_string())?;
+    let host = url
+        .host_str()
+        .filter(|_| matches!(url.scheme(), "http" | "https"))
+        .ok_or_else(|| "WEBAUTHN_ORIGIN must be an http(s) URL with a host".to_string())?;
+
+    let rp_id = rp_id.map(str::trim).unwrap_or(host).to_ascii_lowercase();
+    if rp_id.is_empty() || !(host == rp_id || host.ends_with(&format!(".{}", rp_id))) {
+        return Err("WEBAUTHN_RP_ID must be the origin's host or a parent domain of it".to_string());
+    }
+
+    Ok(WebAuthnConfig { rp_id, origin: url.origin().ascii_serialization() })
+}
+
+/// HOST must be an IP address (IPv6 without brackets); a non-empty socket path wins over HOST/PORT
+fn parse_listen(host: Option<&str>, port: Option<&str>, socket_path: Option<&str>) -> Result<ListenAddress, String> {
+    if let Some(path) = socket_path.map(str::trim).filter(|p| !p.is_empty()) {
+        return Ok(ListenAddress::Unix(PathBuf::from(path)));
+    }
+
+    let host: IpAddr = host
+        .map(str::trim)
+        .unwrap_or(DEFAULT_HOST)
+        .parse()
+        .map_err(|_| "HOST must be an IP address such as 0.0.0.0 or ::".to_string())?;
+    let port = port
+        .map(|p| p.trim().parse::<u16>().map_err(|_| "PORT must be a number from 0 to 65535".to_string()))
+        .transpose()?
+        .unwrap_or(DEFAULT_PORT);
+
+    Ok(ListenAddress::Tcp(SocketAddr::new(host, port)))
+}
+
+/// Certificate and key come as a pair, and only over TCP
+fn parse_tls(
+    cert_path: Option<String>,
+    key_path: Option<String>,
+    listen: &ListenAddress,
+) -> Result<Option<TlsFiles>, String> {
+    let tls = match (cert_path, key_path) {
+        (None, None) => return Ok(None),
+        (Some(cert_path), Some(key_path)) => TlsFiles { cert_path: cert_path.into(), key_path: key_path.into() },
+        _ => return Err("TLS_CERT_PATH and TLS_KEY_PATH must be set together".to_string()),
+    };
+    if matches!(listen, ListenAddress::Unix(_)) {
+        return Err("TLS_CERT_PATH cannot be used with UNIX_SOCKET_PATH".to_string());
     }
+    Ok(Some(tls))
 }
 
 fn extract_clerk_domain(publishable_key: &str) -> Result<String, String> {
@@ -67,3 +427,89 @@ fn base64_decode(input: &str) -> Result<Vec<u8>, String> {
     use base64::{Engine as _, engine::general_purpose::STANDARD};
     STANDARD.decode(input).map_err(|e| format!("Base64 decode error: {}", e))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_password_min_length() {
+        assert_eq!(parse_password_min_length(None), Ok(8));
+        assert_eq!(parse_password_min_length(Some(" 12 ")), Ok(12));
+        assert!(parse_password_min_length(Some("6")).is_err());
+        assert!(parse_password_min_length(Some("long")).is_err());
+    }
+
+    #[test]
+    fn test_parse_metrics_settings() {
+        assert_eq!(parse_buckets("0.1, 0.5,2").unwrap(), vec![0.1, 0.5, 2.0]);
+        assert!(parse_buckets("0.5,0.1").is_err());
+        assert!(parse_buckets("fast").is_err());
+
+        assert_eq!(
+            parse_labels("environment=prod, region=eu-west-2").unwrap(),
+            vec![
+                ("environment".to_string(), "prod".to_string()),
+                ("region".to_string(), "eu-west-2".to_string())
+            ]
+        );
+        assert!(parse_labels("region").is_err());
+        assert!(parse_labels("bad-key=x").is_err());
+    }
+
+    #[test]
+    fn test_parse_trusted_proxy_hops() {
+        assert_eq!(parse_trusted_proxy_hops(None).unwrap(), 0);
+        assert_eq!(parse_trusted_proxy_hops(Some(" 2 ")).unwrap(), 2);
+        assert!(parse_trusted_proxy_hops(Some("6")).is_err());
+        assert!(parse_trusted_proxy_hops(Some("-1")).is_err());
+    }
+
+    #[test]
+    fn test_parse_cookie_names() {
+        assert_eq!(parse_cookie_names("__session, __session_abc").unwrap(), vec!["__session", "__session_abc"]);
+        assert!(parse_cookie_names(" , ").is_err());
+        assert!(parse_cookie_names("bad name").is_err());
+        assert!(parse_cookie_names("a;b").is_err());
+    }
+
+    #[test]
+    fn test_parse_bucket_url() {
+        assert_eq!(parse_bucket_url(" https://minio.internal:9000/rota ").unwrap().path(), "/rota");
+        assert!(parse_bucket_url("bucket").is_err());
+        assert!(parse_bucket_url("ftp://files.example.com").is_err());
+        assert!(parse_bucket_url("https://bucket.example.com?x=1").is_err());
+    }
+
+    #[test]
+    fn test_parse_webauthn() {
+        let config = parse_webauthn("https://rota.example.org/app/", None).unwrap();
+        assert_eq!((config.rp_id.as_str(), config.origin.as_str()), ("rota.example.org", "https://rota.example.org"));
+        assert_eq!(parse_webauthn("https://rota.example.org", Some("example.org")).unwrap().rp_id, "example.org");
+        assert!(parse_webauthn("https://rota.example.org", Some("other.org")).is_err());
+        assert!(parse_webauthn("https://rota.example.org", Some("ample.org")).is_err());
+        assert!(parse_webauthn("rota.example.org", None).is_err());
+    }
+
+    #[test]
+    fn test_parse_listen_and_tls() {
+        assert_eq!(parse_listen(None, None, None).unwrap(), ListenAddress::Tcp("0.0.0.0:8080".parse().unwrap()));
+        assert_eq!(
+            parse_listen(Some("::1"), Some(" 3000"), None).unwrap(),
+            ListenAddress::Tcp("[::1]:3000".parse().unwrap())
+        );
+        assert_eq!(
+            parse_listen(Some("127.0.0.1"), None, Some("/run/edrota.sock")).unwrap(),
+            ListenAddress::Unix("/run/edrota.sock".into())
+        );
+        assert!(parse_listen(Some("localhost"), None, None).is_err());
+        assert!(parse_listen(None, Some("80800"), None).is_err());
+
+        let tcp = parse_listen(None, None, None).unwrap();
+        let pem = |p: &str| Some(p.to_string());
+        assert!(parse_tls(None, None, &tcp).unwrap().is_none());
+        assert!(parse_tls(pem("cert.pem"), pem("key.pem"), &tcp).unwrap().is_some());
+        assert!(parse_tls(pem("cert.pem"), None, &tcp).is_err());
+        assert!(parse_tls(pem("cert.pem"), pem("key.pem"), &ListenAddress::Unix("/run/edrota.sock".into())).is_err());
+    }
+}
@@ -0,0 +1,274 @@
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    Aes256Gcm, Nonce,
+};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use hmac::{Hmac, Mac};
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{
+    postgres::{PgArgumentBuffer, PgHasArrayType, PgTypeInfo, PgValueRef},
+    PgPool, Postgres,
+};
+
+/// Marks a column value as encrypted: "enc:v1:" + base64(nonce || ciphertext)
+const PREFIX: &str = "enc:v1:";
+const NONCE_LEN: usize = 12;
+
+struct PiiKey {
+    cipher: Aes256Gcm,
+    /// Key for blind indexes (HMAC of the normalised plaintext), derived from the AES key
+    index_key: Vec<u8>,
+}
+
+struct PiiKeys {
+    current: Option<PiiKey>,
+    previous: Option<PiiKey>,
+}
+
+static KEYS: OnceCell<PiiKeys> = OnceCell::new();
+
+fn parse_key(name: &str, encoded: &str) -> Result<PiiKey, String> {
+    let bytes = STANDARD
+        .decode(encoded.trim())
+        .map_err(|_| format!("{} must be base64", name))?;
+    if bytes.len() != 32 {
+        return Err(format!("{} must decode to 32 bytes (AES-256)", name));
+    }
+
+    let mut hasher = Sha256::new();
+    hasher.update(b"edrota-pii-index");
+    hasher.update(&bytes);
+
+    Ok(PiiKey {
+        cipher: Aes256Gcm::new_from_slice(&bytes).map_err(|e| format!("{}: {}", name, e))?,
+        index_key: hasher.finalize().to_vec(),
+    })
+}
+
+/// Load PII keys once at startup. Without a current key, values are written in plaintext
+/// and only legacy plaintext can be read.
+pub fn init_keys(current: Option<&str>, previous: Option<&str>) -> Result<(), String> {
+    let keys = PiiKeys {
+        current: current.map(|k| parse_key("PII_ENCRYPTION_KEY", k)).transpose()?,
+        previous: previous.map(|k| parse_key("PII_ENCRYPTION_KEY_PREVIOUS", k)).transpose()?,
+    };
+    KEYS.set(keys).map_err(|_| "PII keys already initialised".to_string())
+}
+
+fn keys() -> Option<&'static PiiKeys> {
+    KEYS.get()
+}
+
+pub fn encryption_enabled() -> bool {
+    keys().is_some_and(|k| k.current.is_some())
+}
+
+/// Encrypt with the current key, or pass through when encryption is not configured
+pub fn encrypt(plain: &str) -> Result<String, String> {
+    let Some(key) = keys().and_then(|k| k.current.as_ref()) else {
+        return Ok(plain.to_string());
+    };
+
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = key
+        .cipher
+        .encrypt(&nonce, plain.as_bytes())
+        .map_err(|_| "PII encryption failed".to_string())?;
+
+    let mut payload = nonce.to_vec();
+    payload.extend_from_slice(&ciphertext);
+    Ok(format!("{}{}", PREFIX, STANDARD.encode(payload)))
+}
+
+/// Decrypt with the current key, falling back to the previous one during key rotation.
+/// Values without the prefix are legacy plaintext and returned as-is.
+pub fn decrypt(stored: &str) -> Result<String, String> {
+    let Some(encoded) = stored.strip_prefix(PREFIX) else {
+        return Ok(stored.to_string());
+    };
+
+    let payload = STANDARD
+        .decode(encoded)
+        .map_err(|_| "Malformed encrypted value".to_string())?;
+    if payload.len() <= NONCE_LEN {
+        return Err("Malformed encrypted value".to_string());
+    }
+    let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+
+    let keys = keys().ok_or("Encrypted value found but PII_ENCRYPTION_KEY is not set")?;
+    [keys.current.as_ref(), keys.previous.as_ref()]
+        .into_iter()
+        .flatten()
+        .find_map(|key| key.cipher.decrypt(Nonce::from_slice(nonce), ciphertext).ok())
+        .ok_or_else(|| "Unable to decrypt value with the configured PII keys".to_string())
+        .and_then(|plain| String::from_utf8(plain).map_err(|_| "Decrypted value is not UTF-8".to_string()))
+}
+
+/// Blind index values for exact-match lookups on an encrypted column (current key first,
+/// then previous). Empty when encryption is not configured.
+pub fn blind_indexes(value: &str) -> Vec<String> {
+    let normalised = value.trim().to_lowercase();
+
+    keys()
+        .map(|k| [k.current.as_ref(), k.previous.as_ref()])
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|key| {
+            let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(&key.index_key).expect("HMAC accepts any key length");
+            mac.update(normalised.as_bytes());
+            hex::encode(mac.finalize().into_bytes())
+        })
+        .collect()
+}
+
+/// Blind index for storing alongside a value (current key only)
+pub fn blind_index(value: &str) -> Option<String> {
+    if encryption_enabled() {
+        blind_indexes(value).into_iter().next()
+    } else {
+        None
+    }
+}
+
+/// A text column holding PII: encrypted on write, decrypted on read, plain text in JSON
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct EncryptedString(pub String);
+
+impl From<String> for EncryptedString {
+    fn from(value: String) -> Self {
+        EncryptedString(value)
+    }
+}
+
+impl EncryptedString {
+    /// Wrap plaintext values for binding to an encrypted array column
+    pub fn wrap_all(values: &[String]) -> Vec<EncryptedString> {
+        values.iter().cloned().map(EncryptedString).collect()
+    }
+}
+
+impl sqlx::Type<Postgres> for EncryptedString {
+    fn type_info() -> PgTypeInfo {
+        <&str as sqlx::Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <&str as sqlx::Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl PgHasArrayType for EncryptedString {
+    fn array_type_info() -> PgTypeInfo {
+        <&str as PgHasArrayType>::array_type_info()
+    }
+
+    fn array_compatible(ty: &PgTypeInfo) -> bool {
+        <&str as PgHasArrayType>::array_compatible(ty)
+    }
+}
+
+impl<'q> sqlx::Encode<'q, Postgres> for EncryptedString {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> Result<sqlx::encode::IsNull, sqlx::error::BoxDynError> {
+        let stored = encrypt(&self.0)?;
+        <String as sqlx::Encode<Postgres>>::encode(stored, buf)
+    }
+}
+
+impl<'r> sqlx::Decode<'r, Postgres> for EncryptedString {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, sqlx::error::BoxDynError> {
+        let stored = <&str as sqlx::Decode<Postgres>>::decode(value)?;
+        Ok(EncryptedString(decrypt(stored)?))
+    }
+}
+
+/// Re-encrypt every PII column (and super admin TOTP secrets) with the current key and rebuild blind indexes.
+/// Run after rotating PII_ENCRYPTION_KEY (old key in PII_ENCRYPTION_KEY_PREVIOUS),
+/// or once after first enabling encryption to encrypt existing plaintext.
+pub async fn rekey_pii(db: &PgPool) -> Result<usize, sqlx::Error> {
+    #[derive(sqlx::FromRow)]
+    struct PiiRow {
+        user_profile_id: i32,
+        secondary_emails: Option<Vec<EncryptedString>>,
+        tel: Option<Vec<EncryptedString>>,
+    }
+
+    let rows = sqlx::query_as::<_, PiiRow>(
+        r#"
+        SELECT user_profile_id, secondary_emails, tel
+        FROM "Users"
+        WHERE secondary_emails IS NOT NULL OR tel IS NOT NULL
+        ORDER BY user_profile_id
+        "#,
+    )
+    .fetch_all(db)
+    .await?;
+
+    let mut tx = db.begin().await?;
+
+    for row in &rows {
+        let index = row.secondary_emails.as_ref().map(|emails| secondary_email_index(emails));
+
+        sqlx::query(
+            r#"UPDATE "Users" SET secondary_emails = $1, tel = $2, secondary_email_index = $3 WHERE user_profile_id = $4"#,
+        )
+        .bind(&row.secondary_emails)
+        .bind(&row.tel)
+        .bind(index)
+        .bind(row.user_profile_id)
+        .execute(&mut *tx)
+        .await?;
+    }
+
+    // Super admin TOTP secrets use the same key
+    let mfa_secrets = sqlx::query_as::<_, (i32, EncryptedString)>(r#"SELECT user_profile_id, secret FROM "SuperAdminMfa""#)
+        .fetch_all(&mut *tx)
+        .await?;
+
+    for (user_profile_id, secret) in &mfa_secrets {
+        sqlx::query(r#"UPDATE "SuperAdminMfa" SET secret = $1 WHERE user_profile_id = $2"#)
+            .bind(secret)
+            .bind(user_profile_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(rows.len() + mfa_secrets.len())
+}
+
+/// Blind index column value for a list of secondary emails
+pub fn secondary_email_index(emails: &[EncryptedString]) -> Vec<String> {
+    emails.iter().filter_map(|e| blind_index(&e.0)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip_and_plaintext_passthrough() {
+        let key = STANDARD.encode([7u8; 32]);
+        init_keys(Some(&key), None).unwrap();
+
+        let stored = encrypt("07700 900123").unwrap();
+        assert!(stored.starts_with(PREFIX));
+        assert_ne!(encrypt("07700 900123").unwrap(), stored); // random nonce
+        assert_eq!(decrypt(&stored).unwrap(), "07700 900123");
+
+        // Legacy rows written before encryption was enabled
+        assert_eq!(decrypt("legacy@nhs.net").unwrap(), "legacy@nhs.net");
+
+        assert_eq!(blind_indexes(" Jane@NHS.net"), blind_indexes("jane@nhs.net"));
+        assert!(decrypt(&format!("{}AAAA", PREFIX)).is_err());
+    }
+
+    #[test]
+    fn test_rejects_short_key() {
+        assert!(parse_key("PII_ENCRYPTION_KEY", &STANDARD.encode([1u8; 16])).is_err());
+    }
+}
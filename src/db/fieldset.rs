@@ -0,0 +1,147 @@
+use axum::{
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+
+use crate::{
+    models::shift::{CROSSES_MIDNIGHT_SQL, DURATION_MINUTES_SQL},
+    AppError, AppResult,
+};
+
+/// Fields a list endpoint can return: JSON key, the SQL select expression producing it, and the typed
+/// placeholder selected instead when a projection leaves the field out (so every row still decodes in full)
+pub type FieldColumns = &'static [(&'static str, &'static str, &'static str)];
+
+pub const SHIFT_FIELDS: FieldColumns = &[
+    ("uuid", "uuid", "'00000000-0000-0000-0000-000000000000'::uuid AS uuid"),
+    ("role", "role_id AS role", "0 AS role"),
+    ("label", "label", "''::text AS label"),
+    ("start", "to_char(start, 'HH24:MI:SS') AS start", "NULL::text AS start"),
+    ("end", r#"to_char("end", 'HH24:MI:SS') AS "end""#, r#"NULL::text AS "end""#),
+    ("money_per_hour", "money_per_hour", "NULL::real AS money_per_hour"),
+    ("pa_value", "pa_value", "0::real AS pa_value"),
+    ("font_color", "font_color", "''::text AS font_color"),
+    ("bk_color", "bk_color", "''::text AS bk_color"),
+    ("is_locum", "is_locum", "false AS is_locum"),
+    ("published", "published", "false AS published"),
+    ("date", "date", "'1970-01-01'::date AS date"),
+    ("created_at", "created_at", "'epoch'::timestamp AS created_at"),
+    ("is_dcc", "is_dcc", "false AS is_dcc"),
+    ("is_spa", "is_spa", "false AS is_spa"),
+    ("time_off", "time_off_category_id AS time_off", "NULL::int4 AS time_off"),
+    ("user_profile_id", "user_profile_id", "NULL::int4 AS user_profile_id"),
+    ("created_by", "created_by", "0 AS created_by"),
+    ("duration_minutes", DURATION_MINUTES_SQL, "NULL::int4 AS duration_minutes"),
+    ("crosses_midnight", CROSSES_MIDNIGHT_SQL, "false AS crosses_midnight"),
+    ("series_id", "series_id", "NULL::uuid AS series_id"),
+];
+
+pub const USER_FIELDS: FieldColumns = &[
+    ("user_profile_id", "u.user_profile_id", "0 AS user_profile_id"),
+    ("auth_id", "u.auth_id", "''::text AS auth_id"),
+    ("full_name", "u.full_name", "''::text AS full_name"),
+    ("short_name", "u.short_name", "''::text AS short_name"),
+    ("primary_email", "u.primary_email", "NULL::text AS primary_email"),
+    ("secondary_emails", "u.secondary_emails", "NULL::text[] AS secondary_emails"),
+    ("tel", "u.tel", "NULL::text[] AS tel"),
+    ("gmc", "u.gmc", "NULL::int4 AS gmc"),
+    ("auth_pin", "u.auth_pin", "NULL::text AS auth_pin"),
+    ("is_super_admin", "u.is_super_admin", "false AS is_super_admin"),
+    ("comment", "u.comment", "NULL::text AS comment"),
+    ("created_at", "u.created_at", "'epoch'::timestamp AS created_at"),
+    ("color", "u.color", "NULL::text AS color"),
+    ("is_generic_login", "u.is_generic_login", "false AS is_generic_login"),
+];
+
+/// Sparse fieldset from `?fields=a,b,c`; `None` means the full object
+pub struct FieldSet {
+    columns: FieldColumns,
+    selected: Option<Vec<&'static str>>,
+}
+
+impl FieldSet {
+    /// Parse a comma-separated field list, rejecting names the endpoint does not have
+    pub fn parse(raw: Option<&str>, columns: FieldColumns) -> AppResult<Self> {
+        let Some(raw) = raw.map(str::trim).filter(|r| !r.is_empty()) else {
+            return Ok(Self { columns, selected: None });
+        };
+
+        let mut selected = Vec::new();
+        for name in raw.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            let (key, _, _) = columns
+                .iter()
+                .find(|(key, _, _)| *key == name)
+                .ok_or_else(|| AppError::BadRequest(format!("Unknown field '{}'", name)))?;
+            if !selected.contains(key) {
+                selected.push(*key);
+            }
+        }
+
+        Ok(Self { columns, selected: Some(selected) })
+    }
+
+    /// SELECT list for the requested fields plus `always` (keys, ORDER BY/DISTINCT columns), with
+    /// placeholders for the rest
+    pub fn select_list(&self, always: &[&str]) -> String {
+        self.columns
+            .iter()
+            .map(|(key, expr, placeholder)| match &self.selected {
+                Some(selected) if !selected.contains(key) && !always.contains(key) => *placeholder,
+                _ => *expr,
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Also keep `keys` in a projected response, for values joined in outside the column list
+    pub fn keep(mut self, keys: &[&'static str]) -> Self {
+        if let Some(selected) = &mut self.selected {
+            selected.extend(keys.iter().filter(|key| !selected.contains(key)).collect::<Vec<_>>());
+        }
+        self
+    }
+
+    /// Serialise rows, keeping only the requested fields when a fieldset was given
+    pub fn respond<T: Serialize>(&self, rows: Vec<T>) -> AppResult<Response> {
+        let Some(selected) = &self.selected else {
+            return Ok(Json(rows).into_response());
+        };
+
+        let projected = rows
+            .into_iter()
+            .map(|row| {
+                let mut value = serde_json::to_value(row)
+                    .map_err(|e| AppError::Internal(format!("Failed to serialise row: {}", e)))?;
+                if let Some(object) = value.as_object_mut() {
+                    object.retain(|key, _| selected.contains(&key.as_str()));
+                }
+                Ok(value)
+            })
+            .collect::<AppResult<Vec<_>>>()?;
+
+        Ok(Json(projected).into_response())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fieldset_select_list() {
+        let all = FieldSet::parse(None, SHIFT_FIELDS).unwrap();
+        assert!(all.select_list(&[]).ends_with("AS crosses_midnight, series_id"));
+
+        let sparse = FieldSet::parse(Some("date, role,date"), SHIFT_FIELDS).unwrap();
+        let list = sparse.select_list(&["uuid"]);
+        assert!(list.starts_with("uuid, role_id AS role, ''::text AS label"));
+        assert!(list.contains("false AS published, date, 'epoch'::timestamp AS created_at"));
+
+        assert!(FieldSet::parse(Some("date,password"), SHIFT_FIELDS).is_err());
+
+        let kept = FieldSet::parse(Some("date"), SHIFT_FIELDS).unwrap().keep(&["date", "extra"]);
+        assert_eq!(kept.selected.as_deref(), Some(&["date", "extra"][..]));
+        assert!(kept.select_list(&[]).contains(", date, "));
+    }
+}
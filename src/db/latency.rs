@@ -0,0 +1,222 @@
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use tracing::{
+    field::{Field, Visit},
+    Event, Level, Subscriber,
+};
+use tracing_subscriber::{filter::Targets, layer::Context, Layer};
+
+/// Samples kept per route and measurement; percentiles cover this rolling window
+const WINDOW: usize = 512;
+
+/// Route label for DB work outside a request (background jobs, spawned tasks)
+const BACKGROUND_ROUTE: &str = "background";
+
+/// sqlx event targets carrying timings (pool acquire needs acquire_time_level, see db::create_pool)
+const QUERY_TARGET: &str = "sqlx::query";
+const ACQUIRE_TARGET: &str = "sqlx::pool::acquire";
+
+tokio::task_local! {
+    static CURRENT_ROUTE: String;
+}
+
+/// Run a request's future with its matched route attached, so DB timings are recorded against it
+pub async fn scope_route<F: std::future::Future>(route: String, f: F) -> F::Output {
+    CURRENT_ROUTE.scope(route, f).await
+}
+
+#[derive(Default)]
+struct Samples {
+    recent: VecDeque<f64>,
+    total: u64,
+}
+
+impl Samples {
+    fn push(&mut self, secs: f64) {
+        if self.recent.len() == WINDOW {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(secs);
+        self.total += 1;
+    }
+
+    fn summary(&self) -> LatencySummary {
+        let mut sorted: Vec<f64> = self.recent.iter().copied().collect();
+        sorted.sort_by(f64::total_cmp);
+        let ms = |p| percentile(&sorted, p) * 1000.0;
+        LatencySummary {
+            total: self.total,
+            window: sorted.len(),
+            p50_ms: ms(50.0),
+            p95_ms: ms(95.0),
+            p99_ms: ms(99.0),
+        }
+    }
+}
+
+/// Nearest-rank percentile of sorted values (0 when empty)
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+#[derive(Default)]
+struct RouteSamples {
+    acquire: Samples,
+    query: Samples,
+}
+
+/// Rolling DB acquire and query times per route. High acquire times with normal query times
+/// point at pool exhaustion; high query times at slow statements.
+#[derive(Default)]
+pub struct DbLatency {
+    routes: Mutex<HashMap<String, RouteSamples>>,
+}
+
+/// Percentiles over the most recent samples
+#[derive(Debug, Clone, Serialize)]
+pub struct LatencySummary {
+    /// Samples recorded since startup
+    pub total: u64,
+    /// Samples the percentiles are computed from
+    pub window: usize,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RouteDbLatency {
+    pub route: String,
+    pub acquire: LatencySummary,
+    pub query: LatencySummary,
+}
+
+impl DbLatency {
+    fn record(&self, target: &str, secs: f64) {
+        let route = CURRENT_ROUTE
+            .try_with(|route| route.clone())
+            .unwrap_or_else(|_| BACKGROUND_ROUTE.to_string());
+        let Ok(mut routes) = self.routes.lock() else { return };
+        let samples = routes.entry(route).or_default();
+        if target == ACQUIRE_TARGET {
+            samples.acquire.push(secs);
+        } else {
+            samples.query.push(secs);
+        }
+    }
+
+    /// Per-route percentiles, sorted by route
+    pub fn snapshot(&self) -> Vec<RouteDbLatency> {
+        let Ok(routes) = self.routes.lock() else { return Vec::new() };
+        let mut snapshot: Vec<RouteDbLatency> = routes
+            .iter()
+            .map(|(route, samples)| RouteDbLatency {
+                route: route.clone(),
+                acquire: samples.acquire.summary(),
+                query: samples.query.summary(),
+            })
+            .collect();
+        snapshot.sort_by(|a, b| a.route.cmp(&b.route));
+        snapshot
+    }
+}
+
+/// Tracing layer that reads timings from sqlx's own query and acquire events
+pub struct DbLatencyLayer {
+    latency: Arc<DbLatency>,
+}
+
+impl DbLatencyLayer {
+    pub fn new(latency: Arc<DbLatency>) -> Self {
+        Self { latency }
+    }
+
+    /// Per-layer filter: only the sqlx timing events, whatever RUST_LOG says about the console
+    pub fn targets() -> Targets {
+        Targets::new()
+            .with_target(QUERY_TARGET, Level::DEBUG)
+            .with_target(ACQUIRE_TARGET, Level::DEBUG)
+    }
+}
+
+/// Pulls the elapsed seconds out of a sqlx event
+#[derive(Default)]
+struct ElapsedVisitor(Option<f64>);
+
+impl Visit for ElapsedVisitor {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        // sqlx spells the acquire field "aquired_after_secs"
+        if matches!(field.name(), "elapsed_secs" | "aquired_after_secs" | "acquired_after_secs") {
+            self.0 = Some(value);
+        }
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+}
+
+impl<S: Subscriber> Layer<S> for DbLatencyLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let target = event.metadata().target();
+        if target != QUERY_TARGET && target != ACQUIRE_TARGET {
+            return;
+        }
+        let mut visitor = ElapsedVisitor::default();
+        event.record(&mut visitor);
+        if let Some(secs) = visitor.0 {
+            self.latency.record(target, secs);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentiles_over_rolling_window() {
+        let sorted: Vec<f64> = (1..=100).map(f64::from).collect();
+        assert_eq!(percentile(&sorted, 50.0), 50.0);
+        assert_eq!(percentile(&sorted, 99.0), 99.0);
+        assert_eq!(percentile(&[], 95.0), 0.0);
+
+        let mut samples = Samples::default();
+        for i in 0..(WINDOW + 10) {
+            samples.push(i as f64 / 1000.0);
+        }
+        let summary = samples.summary();
+        assert_eq!(summary.total, (WINDOW + 10) as u64);
+        assert_eq!(summary.window, WINDOW);
+        // The oldest ten samples (0-9 ms) have rolled out of the window
+        assert!(summary.p50_ms > 10.0 + WINDOW as f64 / 2.0 - 2.0);
+    }
+
+    #[tokio::test]
+    async fn test_layer_records_sqlx_events_per_route() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let latency = Arc::new(DbLatency::default());
+        let subscriber = tracing_subscriber::registry()
+            .with(DbLatencyLayer::new(latency.clone()).with_filter(DbLatencyLayer::targets()));
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        scope_route("/api/shifts".to_string(), async {
+            tracing::debug!(target: "sqlx::pool::acquire", aquired_after_secs = 0.25, "acquired connection");
+            tracing::debug!(target: "sqlx::query", elapsed_secs = 0.004, "SELECT");
+            tracing::debug!(target: "edrota4_axum", elapsed_secs = 9.0, "not a sqlx event");
+        })
+        .await;
+        tracing::debug!(target: "sqlx::query", elapsed_secs = 0.002, "SELECT");
+
+        let snapshot = latency.snapshot();
+        assert_eq!(snapshot.len(), 2);
+        assert_eq!(snapshot[0].route, "/api/shifts");
+        assert_eq!(snapshot[0].acquire.p99_ms, 250.0);
+        assert_eq!(snapshot[0].query.total, 1);
+        assert_eq!(snapshot[1].route, BACKGROUND_ROUTE);
+    }
+}
@@ -1,3 +1,11 @@
+pub mod encrypted;
+pub mod fieldset;
+pub mod latency;
 pub mod pool;
+pub mod query_tag;
+pub mod schema;
+pub mod transaction;
 
 pub use pool::create_pool;
+pub use query_tag::tag_sql;
+pub use transaction::{begin_as_user, set_acting_for, set_change_reason};
@@ -7,6 +7,8 @@ pub async fn create_pool(database_url: &str) -> Result<PgPool, sqlx::Error> {
         .min_connections(2)
         .acquire_timeout(Duration::from_secs(5))
         .idle_timeout(Duration::from_secs(600))
+        // Emit acquire times for db::latency; the console only shows them at debug for sqlx::pool
+        .acquire_time_level(log::LevelFilter::Debug)
         .connect(database_url)
         .await
 }
@@ -0,0 +1,17 @@
+use crate::middleware::request_id::current_request_id;
+
+/// Prefix SQL with `/* req:<request id> */` so Postgres slow-query logs can be matched to
+/// app traces. Outside a request the SQL is returned unchanged.
+///
+/// Run tagged SQL with `.persistent(false)`: the text differs per request, so caching it as a
+/// prepared statement would only churn the statement cache.
+pub fn tag_sql(sql: &str) -> String {
+    match current_request_id() {
+        Some(id) => {
+            // Request IDs are UUIDs; keep only safe characters so the comment can never close early
+            let id: String = id.chars().filter(|c| c.is_ascii_alphanumeric() || *c == '-').collect();
+            format!("/* req:{} */ {}", id, sql)
+        }
+        None => sql.to_string(),
+    }
+}
@@ -0,0 +1,235 @@
+use serde::Serialize;
+use sqlx::PgPool;
+use std::collections::HashSet;
+
+/// Migrations in sql/, embedded so a deployment can report which ones it has. Add new files here.
+pub const MIGRATIONS: &[(&str, &str)] = &[
+    ("001_add_indexes", include_str!("../../sql/001_add_indexes.sql")),
+    ("002_rota_snapshots", include_str!("../../sql/002_rota_snapshots.sql")),
+    ("003_workplace_settings", include_str!("../../sql/003_workplace_settings.sql")),
+    ("004_notifications", include_str!("../../sql/004_notifications.sql")),
+    ("005_audit_actor", include_str!("../../sql/005_audit_actor.sql")),
+    ("006_data_access_log", include_str!("../../sql/006_data_access_log.sql")),
+    ("007_rota_patterns", include_str!("../../sql/007_rota_patterns.sql")),
+    ("008_pii_encryption", include_str!("../../sql/008_pii_encryption.sql")),
+    ("009_rota_publish_approval", include_str!("../../sql/009_rota_publish_approval.sql")),
+    ("010_locum_availability", include_str!("../../sql/010_locum_availability.sql")),
+    ("011_approval_delegations", include_str!("../../sql/011_approval_delegations.sql")),
+    ("012_shift_request_on_behalf", include_str!("../../sql/012_shift_request_on_behalf.sql")),
+    ("013_super_admin_mfa", include_str!("../../sql/013_super_admin_mfa.sql")),
+    ("014_announcements", include_str!("../../sql/014_announcements.sql")),
+    ("015_audit_query_performance", include_str!("../../sql/015_audit_query_performance.sql")),
+    ("016_data_retention", include_str!("../../sql/016_data_retention.sql")),
+    ("017_shift_audit_user_history", include_str!("../../sql/017_shift_audit_user_history.sql")),
+    ("018_cover_board", include_str!("../../sql/018_cover_board.sql")),
+    ("019_role_archive", include_str!("../../sql/019_role_archive.sql")),
+    ("020_pin_policy", include_str!("../../sql/020_pin_policy.sql")),
+    ("021_feature_flags", include_str!("../../sql/021_feature_flags.sql")),
+    ("022_give_away_offers", include_str!("../../sql/022_give_away_offers.sql")),
+    ("023_payroll_locks", include_str!("../../sql/023_payroll_locks.sql")),
+    ("024_audit_backfill", include_str!("../../sql/024_audit_backfill.sql")),
+    ("025_data_exports", include_str!("../../sql/025_data_exports.sql")),
+    ("026_shift_request_transitions", include_str!("../../sql/026_shift_request_transitions.sql")),
+    ("027_unlinked_profiles", include_str!("../../sql/027_unlinked_profiles.sql")),
+    ("028_role_pay_rules", include_str!("../../sql/028_role_pay_rules.sql")),
+    ("029_shift_request_bumps", include_str!("../../sql/029_shift_request_bumps.sql")),
+    ("030_saved_views", include_str!("../../sql/030_saved_views.sql")),
+    ("031_duplicate_shifts", include_str!("../../sql/031_duplicate_shifts.sql")),
+    ("032_swap_caps", include_str!("../../sql/032_swap_caps.sql")),
+    ("033_attachments", include_str!("../../sql/033_attachments.sql")),
+    ("034_login_audit", include_str!("../../sql/034_login_audit.sql")),
+    ("035_user_role_audit", include_str!("../../sql/035_user_role_audit.sql")),
+    ("036_shift_series", include_str!("../../sql/036_shift_series.sql")),
+    ("037_shift_change_reasons", include_str!("../../sql/037_shift_change_reasons.sql")),
+    ("038_user_role_history", include_str!("../../sql/038_user_role_history.sql")),
+    ("039_shift_attendance", include_str!("../../sql/039_shift_attendance.sql")),
+    ("040_passkeys", include_str!("../../sql/040_passkeys.sql")),
+    ("041_diary_entry_types", include_str!("../../sql/041_diary_entry_types.sql")),
+    ("042_clerk_rollback_failures", include_str!("../../sql/042_clerk_rollback_failures.sql")),
+    ("043_template_preferred_users", include_str!("../../sql/043_template_preferred_users.sql")),
+    ("044_marketplace_reasons", include_str!("../../sql/044_marketplace_reasons.sql")),
+    ("045_job_plan_template_roles", include_str!("../../sql/045_job_plan_template_roles.sql")),
+    ("046_can_set_pay", include_str!("../../sql/046_can_set_pay.sql")),
+    ("047_shift_stats_views", include_str!("../../sql/047_shift_stats_views.sql")),
+    ("048_notification_preferences", include_str!("../../sql/048_notification_preferences.sql")),
+    ("049_user_role_expiry", include_str!("../../sql/049_user_role_expiry.sql")),
+    ("050_custom_fields", include_str!("../../sql/050_custom_fields.sql")),
+];
+
+/// Schema object a migration creates, as found in its SQL
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SchemaObject {
+    Table(String),
+    Column { table: String, column: String },
+    Index(String),
+    Trigger(String),
+}
+
+impl std::fmt::Display for SchemaObject {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchemaObject::Table(name) => write!(f, "table {}", name),
+            SchemaObject::Column { table, column } => write!(f, "column {}.{}", table, column),
+            SchemaObject::Index(name) => write!(f, "index {}", name),
+            SchemaObject::Trigger(name) => write!(f, "trigger {}", name),
+        }
+    }
+}
+
+/// Tables, added columns, indexes and triggers a migration creates. Migrations are idempotent
+/// (`IF NOT EXISTS`), so these are what tell whether one has been run.
+pub fn expected_objects(sql: &str) -> Vec<SchemaObject> {
+    let without_comments: String = sql
+        .lines()
+        .map(|line| line.split("--").next().unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join(" ");
+    let spaced = without_comments.replace(';', " ; ").replace(['(', ')', ','], " ");
+    let tokens: Vec<&str> = spaced.split_whitespace().collect();
+    let name = |i: usize| tokens.get(i).map(|t| t.trim_matches('"').to_string());
+    let upper = |i: usize| tokens.get(i).map(|t| t.to_ascii_uppercase()).unwrap_or_default();
+    let if_not_exists = |i: usize| upper(i) == "IF" && upper(i + 1) == "NOT" && upper(i + 2) == "EXISTS";
+
+    let mut objects = Vec::new();
+    let mut altering: Option<String> = None;
+    let mut i = 0;
+    while i < tokens.len() {
+        match (upper(i).as_str(), upper(i + 1).as_str()) {
+            (";", _) => altering = None,
+            ("CREATE", "TABLE") if if_not_exists(i + 2) => {
+                objects.extend(name(i + 5).map(SchemaObject::Table));
+            }
+            ("CREATE", "TRIGGER") => objects.extend(name(i + 2).map(SchemaObject::Trigger)),
+            ("CREATE", "INDEX" | "UNIQUE") => {
+                let mut j = i + 1;
+                while matches!(upper(j).as_str(), "UNIQUE" | "INDEX" | "CONCURRENTLY") {
+                    j += 1;
+                }
+                if if_not_exists(j) {
+                    objects.extend(name(j + 3).map(SchemaObject::Index));
+                }
+            }
+            ("ALTER", "TABLE") => altering = name(i + 2),
+            ("ADD", "COLUMN") if if_not_exists(i + 2) => {
+                if let (Some(table), Some(column)) = (altering.clone(), name(i + 5)) {
+                    objects.push(SchemaObject::Column { table, column });
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    objects
+}
+
+/// What the database currently has, to check migrations against
+#[derive(Debug, Default)]
+pub struct SchemaSnapshot {
+    pub tables: HashSet<String>,
+    pub columns: HashSet<(String, String)>,
+    pub indexes: HashSet<String>,
+    pub triggers: HashSet<String>,
+}
+
+impl SchemaSnapshot {
+    pub async fn load(db: &PgPool) -> Result<Self, sqlx::Error> {
+        let tables: Vec<String> = sqlx::query_scalar("SELECT tablename::text FROM pg_tables WHERE schemaname = 'public'")
+            .fetch_all(db)
+            .await?;
+        let columns: Vec<(String, String)> = sqlx::query_as(
+            "SELECT table_name::text, column_name::text FROM information_schema.columns WHERE table_schema = 'public'",
+        )
+        .fetch_all(db)
+        .await?;
+        let indexes: Vec<String> = sqlx::query_scalar("SELECT indexname::text FROM pg_indexes WHERE schemaname = 'public'")
+            .fetch_all(db)
+            .await?;
+        let triggers: Vec<String> = sqlx::query_scalar("SELECT tgname::text FROM pg_trigger WHERE NOT tgisinternal")
+            .fetch_all(db)
+            .await?;
+
+        Ok(Self {
+            tables: tables.into_iter().collect(),
+            columns: columns.into_iter().collect(),
+            indexes: indexes.into_iter().collect(),
+            triggers: triggers.into_iter().collect(),
+        })
+    }
+
+    pub fn contains(&self, object: &SchemaObject) -> bool {
+        match object {
+            SchemaObject::Table(name) => self.tables.contains(name),
+            SchemaObject::Column { table, column } => self.columns.contains(&(table.clone(), column.clone())),
+            SchemaObject::Index(name) => self.indexes.contains(name),
+            SchemaObject::Trigger(name) => self.triggers.contains(name),
+        }
+    }
+}
+
+/// APPLIED | PARTIAL | MISSING, or UNKNOWN for migrations that only change data or types
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationStatus {
+    pub version: &'static str,
+    pub status: &'static str,
+    /// Objects the migration creates that the database lacks
+    pub missing: Vec<String>,
+}
+
+pub fn migration_status(version: &'static str, sql: &str, snapshot: &SchemaSnapshot) -> MigrationStatus {
+    let expected = expected_objects(sql);
+    let missing: Vec<String> = expected.iter().filter(|o| !snapshot.contains(o)).map(ToString::to_string).collect();
+    let status = if expected.is_empty() {
+        "UNKNOWN"
+    } else if missing.is_empty() {
+        "APPLIED"
+    } else if missing.len() == expected.len() {
+        "MISSING"
+    } else {
+        "PARTIAL"
+    };
+    MigrationStatus { version, status, missing }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expected_objects() {
+        let sql = r#"
+            -- CREATE TABLE IF NOT EXISTS "Ignored" in a comment
+            CREATE TABLE IF NOT EXISTS "Locks" (id INT4);
+            ALTER TABLE "ShiftAudit"
+                ADD COLUMN IF NOT EXISTS old_time_off INT4 GENERATED ALWAYS AS ((old->>'time_off')::int4) STORED,
+                ADD COLUMN IF NOT EXISTS new_time_off INT4;
+            ALTER TABLE "Users" ALTER COLUMN auth_pin TYPE VARCHAR(8);
+            CREATE INDEX CONCURRENTLY IF NOT EXISTS idx_a ON "Locks" (id);
+            CREATE UNIQUE INDEX IF NOT EXISTS idx_b ON "Locks" (id) WHERE id > 0;
+            CREATE TRIGGER trg_x BEFORE INSERT ON "Locks" FOR EACH ROW EXECUTE FUNCTION f();
+        "#;
+        let column = |table: &str, column: &str| SchemaObject::Column { table: table.to_string(), column: column.to_string() };
+        assert_eq!(
+            expected_objects(sql),
+            vec![
+                SchemaObject::Table("Locks".to_string()),
+                column("ShiftAudit", "old_time_off"),
+                column("ShiftAudit", "new_time_off"),
+                SchemaObject::Index("idx_a".to_string()),
+                SchemaObject::Index("idx_b".to_string()),
+                SchemaObject::Trigger("trg_x".to_string()),
+            ]
+        );
+
+        let mut snapshot = SchemaSnapshot::default();
+        assert_eq!(migration_status("x", sql, &snapshot).status, "MISSING");
+        snapshot.tables.insert("Locks".to_string());
+        let partial = migration_status("x", sql, &snapshot);
+        assert_eq!((partial.status, partial.missing.len()), ("PARTIAL", 5));
+        assert_eq!(migration_status("x", "UPDATE \"Users\" SET a = 1;", &snapshot).status, "UNKNOWN");
+
+        // Every embedded migration creates something its status can be told from
+        for (version, sql) in MIGRATIONS {
+            assert!(!expected_objects(sql).is_empty(), "{} has no detectable objects", version);
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use sqlx::{PgPool, Postgres, Transaction};
+
+/// Begin a transaction with `app.current_user` set to the acting profile.
+///
+/// The ShiftAudit triggers read this setting, so changes made on someone's behalf
+/// (e.g. a marketplace swap approved by an admin) are attributed to the real actor
+/// rather than the database role. The setting is transaction-local (`SET LOCAL`).
+pub async fn begin_as_user(pool: &PgPool, profile_id: i32) -> Result<Transaction<'static, Postgres>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    // SET LOCAL does not accept bind parameters; set_config(..., true) is equivalent
+    sqlx::query("SELECT set_config('app.current_user', $1, true)")
+        .bind(profile_id.to_string())
+        .execute(&mut *tx)
+        .await?;
+
+    Ok(tx)
+}
+
+/// Mark the rest of a `begin_as_user` transaction as done on behalf of `delegator_id`
+/// (an approval delegation); ShiftAudit rows record it in `acted_as_delegate_of`.
+pub async fn set_acting_for(tx: &mut Transaction<'static, Postgres>, delegator_id: i32) -> Result<(), sqlx::Error> {
+    sqlx::query("SELECT set_config('app.acting_for', $1, true)")
+        .bind(delegator_id.to_string())
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
+
+/// Give the shift changes in the rest of a `begin_as_user` transaction a reason; ShiftAudit rows
+/// record it in `reason`.
+pub async fn set_change_reason(tx: &mut Transaction<'static, Postgres>, reason: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("SELECT set_config('app.change_reason', $1, true)")
+        .bind(reason)
+        .execute(&mut **tx)
+        .await?;
+
+    Ok(())
+}
@@ -3,7 +3,11 @@ use axum::{
     response::{IntoResponse, Response},
     Json,
 };
+use serde::Serialize;
 use serde_json::json;
+use utoipa::ToSchema;
+
+use crate::models::ShiftRequestStatus;
 
 #[derive(Debug, thiserror::Error)]
 pub enum AppError {
@@ -22,35 +26,335 @@ pub enum AppError {
     #[error("Conflict: {0}")]
     Conflict(String),
 
+    /// Conflict that carries the conflicting resource, rendered under "conflict"
+    #[error("Conflict: {message}")]
+    ConflictWith {
+        message: String,
+        conflict: serde_json::Value,
+    },
+
     #[error("{0}")]
     Internal(String),
 
+    /// Unique and foreign-key violations never land here, see From<sqlx::Error>
     #[error("{0}")]
-    Database(#[from] sqlx::Error),
+    Database(sqlx::Error),
 
     #[error("{0}")]
     Validation(String),
+
+    /// JSON body parsed but a field did not fit the input type (see extractors::Json)
+    #[error("{message}")]
+    InvalidBody {
+        message: String,
+        /// Path to the field, e.g. "entries[0].start"
+        field: String,
+        expected: Option<String>,
+        received: Option<String>,
+    },
+
+    /// Input fields that failed validation (e.g. a password policy), rendered under "errors"
+    #[error("{message}")]
+    InvalidFields { message: String, errors: Vec<FieldError> },
+
+    #[error("{0}")]
+    UnsupportedMediaType(String),
+
+    /// Accept header names no format the endpoint can produce (see report::ReportFormat)
+    #[error("{0}")]
+    NotAcceptable(String),
+
+    /// Marketplace request cannot take `action` (accept, withdraw, ...) in its current status
+    #[error("Cannot {action} a request with status {from}")]
+    InvalidStateTransition {
+        from: ShiftRequestStatus,
+        action: &'static str,
+    },
+
+    #[error("Cannot accept your own request")]
+    SelfAcceptNotAllowed,
+
+    #[error("The marketplace is disabled for this workplace")]
+    MarketplaceDisabled,
+
+    /// Caller is not the requester/candidate/target the action needs
+    #[error("You are not the {party} of this request")]
+    NotRequestParty { party: &'static str },
+
+    #[error("You can only create requests for your own shifts")]
+    NotShiftOwner,
+
+    #[error("Target shift does not belong to the expected user")]
+    SwapTargetMismatch,
+
+    /// Super admin has TOTP or a passkey but sent neither X-MFA-Code nor X-MFA-Passkey
+    #[error("This action requires a TOTP code in X-MFA-Code or a passkey step-up token in X-MFA-Passkey")]
+    MfaRequired,
+
+    /// X-MFA-Code or X-MFA-Passkey was wrong, expired or already used
+    #[error("Invalid or already used TOTP code or passkey step-up token")]
+    MfaInvalid,
+
+    /// The role's month has been locked for payroll
+    #[error("The {year}-{month:02} rota is locked for payroll")]
+    PeriodLocked { year: i32, month: u32 },
+
+    /// Feature flag is off for the caller
+    #[error("The {feature} feature is not enabled for you")]
+    FeatureDisabled { feature: &'static str },
+
+    /// Action is limited in frequency; rendered as 429 with a Retry-After header
+    #[error("{message}")]
+    RateLimited { message: String, retry_after_secs: i64 },
+}
+
+impl AppError {
+    /// Stable machine-readable code, rendered under "code" so clients need not match on messages
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::Unauthorized(_) => "UNAUTHORIZED",
+            AppError::Forbidden(_) => "FORBIDDEN",
+            AppError::NotFound(_) => "NOT_FOUND",
+            AppError::BadRequest(_) => "BAD_REQUEST",
+            AppError::Conflict(_) | AppError::ConflictWith { .. } => "CONFLICT",
+            AppError::Internal(_) | AppError::Database(_) => "INTERNAL",
+            AppError::Validation(_) => "VALIDATION",
+            AppError::InvalidBody { .. } => "INVALID_BODY",
+            AppError::InvalidFields { .. } => "INVALID_FIELDS",
+            AppError::UnsupportedMediaType(_) => "UNSUPPORTED_MEDIA_TYPE",
+            AppError::NotAcceptable(_) => "NOT_ACCEPTABLE",
+            AppError::InvalidStateTransition { .. } => "INVALID_STATE_TRANSITION",
+            AppError::SelfAcceptNotAllowed => "SELF_ACCEPT_NOT_ALLOWED",
+            AppError::MarketplaceDisabled => "MARKETPLACE_DISABLED",
+            AppError::NotRequestParty { .. } => "NOT_REQUEST_PARTY",
+            AppError::NotShiftOwner => "NOT_SHIFT_OWNER",
+            AppError::SwapTargetMismatch => "SWAP_TARGET_MISMATCH",
+            AppError::MfaRequired => "MFA_REQUIRED",
+            AppError::MfaInvalid => "MFA_INVALID",
+            AppError::PeriodLocked { .. } => "PERIOD_LOCKED",
+            AppError::FeatureDisabled { .. } => "FEATURE_DISABLED",
+            AppError::RateLimited { .. } => "RATE_LIMITED",
+        }
+    }
 }
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
+        let code = self.code();
         let (status, message) = match self {
+            AppError::ConflictWith { message, conflict } => {
+                let body = Json(json!({
+                    "error": message,
+                    "code": code,
+                    "conflict": conflict
+                }));
+                return (StatusCode::CONFLICT, body).into_response();
+            }
+            AppError::InvalidStateTransition { from, action } => {
+                let body = Json(json!({
+                    "error": format!("Cannot {} a request with status {}", action, from),
+                    "code": code,
+                    "from": from,
+                    "action": action
+                }));
+                return (StatusCode::BAD_REQUEST, body).into_response();
+            }
+            AppError::InvalidBody { message, field, expected, received } => {
+                let body = Json(json!({
+                    "error": message,
+                    "code": code,
+                    "field": field,
+                    "expected": expected,
+                    "received": received
+                }));
+                return (StatusCode::UNPROCESSABLE_ENTITY, body).into_response();
+            }
+            AppError::InvalidFields { message, errors } => {
+                let body = Json(json!({
+                    "error": message,
+                    "code": code,
+                    "errors": errors
+                }));
+                return (StatusCode::UNPROCESSABLE_ENTITY, body).into_response();
+            }
+            AppError::RateLimited { message, retry_after_secs } => {
+                let body = Json(json!({
+                    "error": message,
+                    "code": code,
+                    "retry_after_seconds": retry_after_secs
+                }));
+                let retry_after = [(axum::http::header::RETRY_AFTER, retry_after_secs.to_string())];
+                return (StatusCode::TOO_MANY_REQUESTS, retry_after, body).into_response();
+            }
             AppError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg),
             AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
             AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
             AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
             AppError::Conflict(msg) => (StatusCode::CONFLICT, msg),
             AppError::Validation(msg) => (StatusCode::UNPROCESSABLE_ENTITY, msg),
+            AppError::UnsupportedMediaType(msg) => (StatusCode::UNSUPPORTED_MEDIA_TYPE, msg),
+            AppError::NotAcceptable(msg) => (StatusCode::NOT_ACCEPTABLE, msg),
             AppError::Database(e) => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
             AppError::Internal(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+            e @ (AppError::SelfAcceptNotAllowed | AppError::SwapTargetMismatch) => (StatusCode::BAD_REQUEST, e.to_string()),
+            e @ AppError::PeriodLocked { .. } => (StatusCode::CONFLICT, e.to_string()),
+            e @ (AppError::MarketplaceDisabled
+            | AppError::NotRequestParty { .. }
+            | AppError::NotShiftOwner
+            | AppError::MfaRequired
+            | AppError::MfaInvalid
+            | AppError::FeatureDisabled { .. }) => (StatusCode::FORBIDDEN, e.to_string()),
         };
 
         let body = Json(json!({
-            "error": message
+            "error": message,
+            "code": code
         }));
 
         (status, body).into_response()
     }
 }
 
+/// A field that failed validation, with a stable code such as "TOO_SHORT"
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct FieldError {
+    pub field: String,
+    pub code: &'static str,
+    pub message: String,
+}
+
+/// Body of an InvalidFields error (422), for the OpenAPI spec
+#[derive(Debug, Serialize, ToSchema)]
+pub struct InvalidFieldsBody {
+    pub error: String,
+    /// "INVALID_FIELDS"
+    pub code: String,
+    pub errors: Vec<FieldError>,
+}
+
+/// Postgres SQLSTATEs surfaced as client errors rather than 500s
+const UNIQUE_VIOLATION: &str = "23505";
+const FOREIGN_KEY_VIOLATION: &str = "23503";
+
+/// Messages for constraints whose generated name does not read well on its own
+const CONSTRAINT_MESSAGES: &[(&str, &str)] = &[
+    ("RotaSnapshots_role_id_snapshot_date_key", "A snapshot of this rota already exists for that date"),
+    ("RotaPatternEntries_pattern_id_weekday_template_id_key", "This template is already on that weekday in the pattern"),
+    ("LocumAvailability_user_profile_id_role_id_date_key", "Availability for this role and date has already been offered"),
+    ("idx_rota_publish_approvals_active", "This month's rota already has an open or approved publish request"),
+    ("idx_data_exports_pending", "An export is already being prepared"),
+    ("SavedViews_user_profile_id_name_key", "You already have a saved view with this name"),
+];
+
+/// "role_id" -> "role", "snapshot_date" -> "snapshot date"
+fn humanize_column(column: &str) -> String {
+    column.strip_suffix("_id").unwrap_or(column).replace('_', " ")
+}
+
+/// Columns from a Postgres-generated constraint name: "<table>_<columns>_<suffix>"
+fn constraint_columns(constraint: &str, table: Option<&str>, suffix: &str) -> Option<String> {
+    let rest = constraint.strip_suffix(suffix)?;
+    let rest = match table {
+        Some(table) => rest.strip_prefix(table)?.strip_prefix('_')?,
+        None => rest,
+    };
+    (!rest.is_empty()).then(|| humanize_column(rest))
+}
+
+fn unique_violation_message(constraint: Option<&str>, table: Option<&str>) -> String {
+    let Some(constraint) = constraint else {
+        return "A record with these details already exists".to_string();
+    };
+    if let Some((_, message)) = CONSTRAINT_MESSAGES.iter().find(|(name, _)| *name == constraint) {
+        return message.to_string();
+    }
+    if constraint.ends_with("_pkey") {
+        return "A record with this key already exists".to_string();
+    }
+    match constraint_columns(constraint, table, "_key") {
+        Some(columns) => format!("A record with this {} already exists", columns),
+        None => "A record with these details already exists".to_string(),
+    }
+}
+
+/// `deleting` is true when the violation came from removing a row that is still referenced
+fn foreign_key_violation_message(constraint: Option<&str>, table: Option<&str>, deleting: bool) -> String {
+    if deleting {
+        return match table {
+            Some(table) => format!("This record is still referenced by {} and cannot be removed", table),
+            None => "This record is still referenced elsewhere and cannot be removed".to_string(),
+        };
+    }
+    match constraint.and_then(|c| constraint_columns(c, table, "_fkey")) {
+        Some(column) => format!("The referenced {} does not exist", column),
+        None => "A referenced record does not exist".to_string(),
+    }
+}
+
+impl From<sqlx::Error> for AppError {
+    fn from(err: sqlx::Error) -> Self {
+        let Some(db_err) = err.as_database_error() else {
+            return AppError::Database(err);
+        };
+        let constraint = db_err.constraint();
+        let table = db_err.table();
+        match db_err.code().as_deref() {
+            Some(UNIQUE_VIOLATION) => AppError::Conflict(unique_violation_message(constraint, table)),
+            Some(FOREIGN_KEY_VIOLATION) => {
+                let deleting = db_err.message().starts_with("update or delete");
+                AppError::BadRequest(foreign_key_violation_message(constraint, table, deleting))
+            }
+            _ => AppError::Database(err),
+        }
+    }
+}
+
 pub type AppResult<T> = Result<T, AppError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_marketplace_errors_have_stable_codes() {
+        let err = AppError::InvalidStateTransition { from: ShiftRequestStatus::Approved, action: "cancel" };
+        assert_eq!(err.code(), "INVALID_STATE_TRANSITION");
+        assert_eq!(err.to_string(), "Cannot cancel a request with status APPROVED");
+        assert_eq!(err.into_response().status(), StatusCode::BAD_REQUEST);
+
+        assert_eq!(AppError::MarketplaceDisabled.into_response().status(), StatusCode::FORBIDDEN);
+        assert_eq!(AppError::SelfAcceptNotAllowed.code(), "SELF_ACCEPT_NOT_ALLOWED");
+
+        let limited = AppError::RateLimited { message: "Try again later".to_string(), retry_after_secs: 90 }.into_response();
+        assert_eq!(limited.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(limited.headers()[axum::http::header::RETRY_AFTER], "90");
+    }
+
+    #[test]
+    fn test_constraint_violation_messages() {
+        assert_eq!(
+            unique_violation_message(Some("RotaSnapshots_role_id_snapshot_date_key"), Some("RotaSnapshots")),
+            "A snapshot of this rota already exists for that date"
+        );
+        assert_eq!(
+            unique_violation_message(Some("Users_email_key"), Some("Users")),
+            "A record with this email already exists"
+        );
+        assert_eq!(
+            unique_violation_message(Some("FeatureFlags_pkey"), Some("FeatureFlags")),
+            "A record with this key already exists"
+        );
+        assert_eq!(
+            foreign_key_violation_message(Some("Shifts_role_id_fkey"), Some("Shifts"), false),
+            "The referenced role does not exist"
+        );
+        assert_eq!(
+            foreign_key_violation_message(Some("Shifts_role_id_fkey"), Some("Shifts"), true),
+            "This record is still referenced by Shifts and cannot be removed"
+        );
+        assert_eq!(
+            foreign_key_violation_message(Some("custom_fk"), None, false),
+            "A referenced record does not exist"
+        );
+    }
+}
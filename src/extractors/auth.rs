@@ -1,34 +1,100 @@
 use axum::{
-    extract::FromRequestParts,
-    http::{header, request::Parts, StatusCode},
+    extract::{ConnectInfo, FromRequestParts},
+    http::{header, request::Parts, HeaderMap, StatusCode},
 };
+use axum_extra::extract::CookieJar;
 use moka::future::Cache;
 use serde_json::json;
 use std::future::Future;
+use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
 
-use crate::{auth, AppError, AppResult, AppState};
-
-/// Extracts JWT token from either __session cookie (frontend) or Authorization header (testing)
-fn extract_token_from_request(parts: &Parts) -> Option<String> {
-    // Try __session cookie first (for TanStack frontend)
-    if let Some(cookie_header) = parts.headers.get(header::COOKIE) {
-        if let Ok(cookie_str) = cookie_header.to_str() {
-            // Parse cookies manually (cookie = "name=value; name2=value2")
-            for cookie in cookie_str.split(';') {
-                let cookie = cookie.trim();
-                if let Some(value) = cookie.strip_prefix("__session=") {
-                    return Some(value.to_string());
-                }
-            }
+use crate::{auth, auth::claims::ClerkClaims, cache::CachedProfile, AppError, AppResult, AppState};
+
+/// Longest user agent stored in "LoginAudit"
+const MAX_USER_AGENT_LENGTH: usize = 500;
+
+/// Where the session token came from, recorded with each sign-in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthMethod {
+    Cookie,
+    Bearer,
+}
+
+impl AuthMethod {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AuthMethod::Cookie => "COOKIE",
+            AuthMethod::Bearer => "BEARER",
         }
     }
+}
+
+/// Device and address of the request, captured before the async part of the extractor
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct ClientInfo {
+    ip: Option<String>,
+    user_agent: Option<String>,
+}
+
+/// The client address. Each of the `trusted_hops` proxies in front of the API appends the address it saw to
+/// X-Forwarded-For, so the client is the entry that many places from the right; anything further left was sent
+/// by the client and could be anything. Without trusted proxies, or when the header is short or malformed, the
+/// socket peer (when the server was started with connect info).
+pub(crate) fn client_ip(headers: &HeaderMap, peer: Option<SocketAddr>, trusted_hops: usize) -> Option<String> {
+    let hops: Vec<&str> = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(str::trim)
+        .collect();
+    let forwarded = match trusted_hops {
+        0 => None,
+        n => hops.len().checked_sub(n).map(|i| hops[i]),
+    };
+    forwarded
+        .and_then(|v| v.parse::<IpAddr>().ok())
+        .or(peer.map(|p| p.ip()))
+        .map(|ip| ip.to_string())
+}
+
+/// `client_ip` for a request, with the connect info and proxy count the server was started with
+pub(crate) fn request_client_ip(parts: &Parts, trusted_hops: usize) -> Option<String> {
+    let peer = parts.extensions.get::<ConnectInfo<SocketAddr>>().map(|c| c.0);
+    client_ip(&parts.headers, peer, trusted_hops)
+}
+
+fn client_info(parts: &Parts, trusted_hops: usize) -> ClientInfo {
+    ClientInfo {
+        ip: request_client_ip(parts, trusted_hops),
+        user_agent: parts
+            .headers
+            .get(header::USER_AGENT)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.chars().take(MAX_USER_AGENT_LENGTH).collect()),
+    }
+}
+
+/// Extracts the JWT from the first configured session cookie that is present (frontend),
+/// falling back to the Authorization header (testing)
+fn extract_token_from_request(parts: &Parts, cookie_names: &[String]) -> Option<(String, AuthMethod)> {
+    // CookieJar handles multiple Cookie headers, quoting and names that merely share a prefix
+    let jar = CookieJar::from_headers(&parts.headers);
+    let from_cookie = cookie_names
+        .iter()
+        .filter_map(|name| jar.get(name))
+        .map(|cookie| cookie.value_trimmed())
+        .find(|value| !value.is_empty());
+    if let Some(token) = from_cookie {
+        return Some((token.to_string(), AuthMethod::Cookie));
+    }
 
     // Fallback to Authorization header (for testing with Bearer tokens)
     if let Some(auth_header) = parts.headers.get(header::AUTHORIZATION) {
         if let Ok(auth_str) = auth_header.to_str() {
             if let Some(token) = auth_str.strip_prefix("Bearer ") {
-                return Some(token.to_string());
+                return Some((token.to_string(), AuthMethod::Bearer));
             }
         }
     }
@@ -44,24 +110,34 @@ pub struct AuthenticatedUser {
     pub is_super_admin: bool,
 }
 
+type AuthRejection = (StatusCode, axum::Json<serde_json::Value>);
+
 impl FromRequestParts<Arc<AppState>> for AuthenticatedUser {
-    type Rejection = (StatusCode, axum::Json<serde_json::Value>);
+    type Rejection = AuthRejection;
 
     fn from_request_parts(
         parts: &mut Parts,
         state: &Arc<AppState>,
     ) -> impl Future<Output = Result<Self, Self::Rejection>> + Send {
+        // Already resolved by require_auth_middleware for this request
+        let resolved = parts.extensions.get::<AuthenticatedUser>().cloned();
+
         // Try both cookie-based auth (for frontend) and Bearer token (for testing)
-        let token = extract_token_from_request(parts);
+        let token = extract_token_from_request(parts, &state.config.session_cookie_names);
+        let client = client_info(parts, state.config.trusted_proxy_hops);
 
         let state = state.clone();
 
         async move {
+            if let Some(user) = resolved {
+                return Ok(user);
+            }
+
             // Extract token (from cookie or Authorization header)
-            let token = token.ok_or_else(|| {
+            let (token, auth_method) = token.ok_or_else(|| {
                 (
                     StatusCode::UNAUTHORIZED,
-                    axum::Json(json!({"error": "Missing authentication: no __session cookie or Authorization header"})),
+                    axum::Json(json!({"error": "Missing authentication: no session cookie or Authorization header"})),
                 )
             })?;
 
@@ -76,120 +152,181 @@ impl FromRequestParts<Arc<AppState>> for AuthenticatedUser {
                     )
                 })?;
 
-            let clerk_user_id = claims.sub.clone();
-
-            // OPTIMIZATION: Check profile cache first (eliminates DB query for repeat requests)
-            if let Some((profile_id, is_super_admin, email)) = state.profile_cache.get(&clerk_user_id).await {
-                tracing::debug!(clerk_user_id, profile_id, "📋 Profile resolved from cache");
-                return Ok(AuthenticatedUser {
-                    clerk_user_id,
-                    email,
-                    profile_id,
-                    is_super_admin,
-                });
-            }
+            let session_id = claims.sid.clone();
+            let user = resolve_authenticated_user(&state, claims).await?;
+            record_login(&state, &user, session_id, auth_method, client).await;
+            Ok(user)
+        }
+    }
+}
 
-            // Cache miss - try database lookup (99% of requests)
-            let user_opt = sqlx::query_as::<_, crate::models::User>(
-                r#"SELECT * FROM "Users" WHERE auth_id = $1"#,
-            )
-            .bind(&clerk_user_id)
-            .fetch_optional(&state.db)
-            .await
-            .map_err(|e| {
-                tracing::error!(error = %e, clerk_user_id, "Database query failed");
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    axum::Json(json!({"error": "Database error"})),
-                )
-            })?;
+/// Map validated claims to a profile: profile cache, then auth_id, then auto-link by email
+async fn resolve_authenticated_user(state: &AppState, claims: ClerkClaims) -> Result<AuthenticatedUser, AuthRejection> {
+    let clerk_user_id = claims.sub.clone();
+
+    // OPTIMIZATION: Check profile cache first (eliminates DB query for repeat requests)
+    if let Some(CachedProfile { profile_id, is_super_admin, email }) = state.caches.profiles.get(&clerk_user_id).await {
+        tracing::debug!(clerk_user_id, profile_id, "📋 Profile resolved from cache");
+        return Ok(AuthenticatedUser {
+            clerk_user_id,
+            email,
+            profile_id,
+            is_super_admin,
+        });
+    }
 
-            if let Some(user) = user_opt {
-                let email = user.primary_email.clone().unwrap_or_else(|| {
-                    tracing::warn!(clerk_user_id, profile_id = user.user_profile_id, "User has no primary_email");
-                    String::from("")
-                });
-
-                // Cache the profile for future requests
-                state.profile_cache.insert(
-                    clerk_user_id.clone(),
-                    (user.user_profile_id, user.is_super_admin, email.clone()),
-                ).await;
-
-                tracing::debug!(clerk_user_id, profile_id = user.user_profile_id, "✅ User found by auth_id (cached)");
-                return Ok(AuthenticatedUser {
-                    clerk_user_id,
-                    email,
-                    profile_id: user.user_profile_id,
-                    is_super_admin: user.is_super_admin,
-                });
-            }
+    // Cache miss - try database lookup (99% of requests)
+    let user_opt = sqlx::query_as::<_, crate::models::User>(
+        r#"SELECT * FROM "Users" WHERE auth_id = $1"#,
+    )
+    .bind(&clerk_user_id)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, clerk_user_id, "Database query failed");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            axum::Json(json!({"error": "Database error"})),
+        )
+    })?;
+
+    if let Some(user) = user_opt {
+        let email = user.primary_email.clone().unwrap_or_else(|| {
+            tracing::warn!(clerk_user_id, profile_id = user.user_profile_id, "User has no primary_email");
+            String::from("")
+        });
 
-            // User not found by auth_id - need email for auto-linking (rare case)
-            tracing::debug!(clerk_user_id, "User not found by auth_id, attempting auto-link by email");
-
-            // Try to get email from JWT claims (custom claim or standard claim)
-            let email = if let Some(email) = claims.get_email() {
-                email.to_string()
-            } else {
-                // Only call Clerk API if email is not in JWT at all
-                tracing::debug!(clerk_user_id, "Email not in JWT claims, fetching from Clerk API");
-                resolve_email(&state.user_cache, &clerk_user_id, &state.config.clerk_secret_key)
-                    .await
-                    .map_err(|e| {
-                        (
-                            StatusCode::UNAUTHORIZED,
-                            axum::Json(json!({"error": format!("Failed to resolve email: {}", e)})),
-                        )
-                    })?
-            };
-
-            // Auto-link user by email
-            let user = sqlx::query_as::<_, crate::models::User>(
-                r#"UPDATE "Users" SET auth_id = $1 WHERE LOWER(primary_email) = LOWER($2) RETURNING *"#,
-            )
-            .bind(&clerk_user_id)
-            .bind(&email)
-            .fetch_optional(&state.db)
+        // Cache the profile for future requests
+        state.caches.profiles.insert(
+            clerk_user_id.clone(),
+            CachedProfile {
+                profile_id: user.user_profile_id,
+                is_super_admin: user.is_super_admin,
+                email: email.clone(),
+            },
+        ).await;
+
+        tracing::debug!(clerk_user_id, profile_id = user.user_profile_id, "✅ User found by auth_id (cached)");
+        return Ok(AuthenticatedUser {
+            clerk_user_id,
+            email,
+            profile_id: user.user_profile_id,
+            is_super_admin: user.is_super_admin,
+        });
+    }
+
+    // User not found by auth_id - need email for auto-linking (rare case)
+    tracing::debug!(clerk_user_id, "User not found by auth_id, attempting auto-link by email");
+
+    // Try to get email from JWT claims (custom claim or standard claim)
+    let email = if let Some(email) = claims.get_email() {
+        email.to_string()
+    } else {
+        // Only call Clerk API if email is not in JWT at all
+        tracing::debug!(clerk_user_id, "Email not in JWT claims, fetching from Clerk API");
+        resolve_email(&state.user_cache, &clerk_user_id, &state.config.clerk_secret_key)
             .await
             .map_err(|e| {
-                tracing::error!(error = %e, clerk_user_id, email, "Auto-link query failed");
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    axum::Json(json!({"error": "Database error"})),
-                )
-            })?
-            .ok_or_else(|| {
-                tracing::warn!(clerk_user_id, email, "User profile not found for auto-linking");
                 (
                     StatusCode::UNAUTHORIZED,
-                    axum::Json(json!({"error": format!("User profile not found for email: {}", email)})),
+                    axum::Json(json!({"error": format!("Failed to resolve email: {}", e)})),
                 )
-            })?;
-
-            tracing::info!(
-                clerk_user_id,
-                profile_id = user.user_profile_id,
-                email,
-                "🔗 User auto-linked by email"
-            );
+            })?
+    };
 
-            let user_email = user.primary_email.clone().unwrap_or_else(|| email.clone());
+    // Auto-link user by email
+    let user = sqlx::query_as::<_, crate::models::User>(
+        r#"UPDATE "Users" SET auth_id = $1 WHERE LOWER(primary_email) = LOWER($2) AND deactivated_at IS NULL RETURNING *"#,
+    )
+    .bind(&clerk_user_id)
+    .bind(&email)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(|e| {
+        tracing::error!(error = %e, clerk_user_id, email, "Auto-link query failed");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            axum::Json(json!({"error": "Database error"})),
+        )
+    })?
+    .ok_or_else(|| {
+        tracing::warn!(clerk_user_id, email, "User profile not found for auto-linking");
+        (
+            StatusCode::UNAUTHORIZED,
+            axum::Json(json!({"error": format!("User profile not found for email: {}", email)})),
+        )
+    })?;
 
-            // Cache the newly linked profile
-            state.profile_cache.insert(
-                clerk_user_id.clone(),
-                (user.user_profile_id, user.is_super_admin, user_email.clone()),
-            ).await;
+    tracing::info!(
+        clerk_user_id,
+        profile_id = user.user_profile_id,
+        email,
+        "🔗 User auto-linked by email"
+    );
+
+    let user_email = user.primary_email.clone().unwrap_or_else(|| email.clone());
+
+    // Cache the newly linked profile
+    state.caches.profiles.insert(
+        clerk_user_id.clone(),
+        CachedProfile {
+            profile_id: user.user_profile_id,
+            is_super_admin: user.is_super_admin,
+            email: user_email.clone(),
+        },
+    ).await;
+
+    Ok(AuthenticatedUser {
+        clerk_user_id,
+        email: user_email,
+        profile_id: user.user_profile_id,
+        is_super_admin: user.is_super_admin,
+    })
+}
 
-            Ok(AuthenticatedUser {
-                clerk_user_id,
-                email: user_email,
-                profile_id: user.user_profile_id,
-                is_super_admin: user.is_super_admin,
-            })
-        }
+/// Write the sign-in to "LoginAudit" the first time this session is seen from this IP and user agent.
+/// The insert runs in the background so authentication never waits on it.
+async fn record_login(
+    state: &AppState,
+    user: &AuthenticatedUser,
+    session_id: Option<String>,
+    auth_method: AuthMethod,
+    client: ClientInfo,
+) {
+    let key = format!(
+        "{}|{}|{}|{}",
+        user.profile_id,
+        session_id.as_deref().unwrap_or(&user.clerk_user_id),
+        client.ip.as_deref().unwrap_or_default(),
+        client.user_agent.as_deref().unwrap_or_default()
+    );
+    if state.caches.recorded_logins.contains_key(&key) {
+        return;
     }
+    state.caches.recorded_logins.insert(key, ()).await;
+
+    let db = state.db.clone();
+    let profile_id = user.profile_id;
+    tokio::spawn(async move {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO "LoginAudit" (user_profile_id, session_id, ip, user_agent, auth_method)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+        )
+        .bind(profile_id)
+        .bind(&session_id)
+        .bind(&client.ip)
+        .bind(&client.user_agent)
+        .bind(auth_method.as_str())
+        .execute(&db)
+        .await;
+
+        match result {
+            Ok(_) => tracing::info!(profile_id, ip = ?client.ip, auth_method = auth_method.as_str(), "🔑 Sign-in recorded"),
+            Err(e) => tracing::warn!(error = %e, profile_id, "⚠️ Failed to record sign-in"),
+        }
+    });
 }
 
 async fn resolve_email(
@@ -293,6 +430,7 @@ async fn resolve_user_profile(
         UPDATE "Users"
         SET auth_id = $1
         WHERE LOWER(primary_email) = LOWER($2)
+          AND deactivated_at IS NULL
         RETURNING *
         "#,
     )
@@ -324,3 +462,82 @@ async fn resolve_user_profile(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::Request;
+
+    fn token_for(headers: &[(&str, &str)], names: &[&str]) -> Option<String> {
+        let mut builder = Request::builder();
+        for (name, value) in headers {
+            builder = builder.header(*name, *value);
+        }
+        let (parts, _) = builder.body(()).unwrap().into_parts();
+        let names: Vec<String> = names.iter().map(|n| n.to_string()).collect();
+        extract_token_from_request(&parts, &names).map(|(token, _)| token)
+    }
+
+    #[test]
+    fn test_session_cookie_parsing() {
+        let session = ["__session"];
+
+        assert_eq!(token_for(&[("cookie", "theme=dark; __session=abc.def")], &session).as_deref(), Some("abc.def"));
+        // A cookie whose name only ends in __session must not be taken for it
+        assert_eq!(token_for(&[("cookie", "x__session=evil; __session=good")], &session).as_deref(), Some("good"));
+        assert_eq!(token_for(&[("cookie", "__session=\"quoted\"")], &session).as_deref(), Some("quoted"));
+        // Cookies split across several headers
+        assert_eq!(
+            token_for(&[("cookie", "theme=dark"), ("cookie", "__session=second")], &session).as_deref(),
+            Some("second")
+        );
+        // Malformed pairs are skipped rather than poisoning the rest of the header
+        assert_eq!(token_for(&[("cookie", ";;=oops; garbage; __session=ok")], &session).as_deref(), Some("ok"));
+    }
+
+    #[test]
+    fn test_cookie_names_and_bearer_fallback() {
+        // Configured names are tried in order
+        let names = ["__session_abc", "__session"];
+        assert_eq!(
+            token_for(&[("cookie", "__session=old; __session_abc=new")], &names).as_deref(),
+            Some("new")
+        );
+
+        // Empty cookie falls through to the Authorization header
+        let headers = [("cookie", "__session="), ("authorization", "Bearer tok")];
+        assert_eq!(token_for(&headers, &["__session"]).as_deref(), Some("tok"));
+
+        assert_eq!(token_for(&[("cookie", "other=1")], &["__session"]), None);
+    }
+
+    #[test]
+    fn test_auth_method_and_client_ip() {
+        let request = |headers: &[(&str, &str)]| {
+            let mut builder = Request::builder();
+            for (name, value) in headers {
+                builder = builder.header(*name, *value);
+            }
+            builder.body(()).unwrap().into_parts().0
+        };
+        let names = ["__session".to_string()];
+        let method = |headers| extract_token_from_request(&request(headers), &names).map(|(_, m)| m);
+        assert_eq!(method(&[("cookie", "__session=a")]), Some(AuthMethod::Cookie));
+        assert_eq!(method(&[("authorization", "Bearer b")]), Some(AuthMethod::Bearer));
+
+        let peer: SocketAddr = "10.0.0.5:443".parse().unwrap();
+        let ip = |headers, hops| client_ip(&request(headers).headers, Some(peer), hops);
+        // One proxy: the client is the address it appended, whatever the client put before it
+        let spoofed = [("x-forwarded-for", "198.51.100.1, 203.0.113.7")];
+        assert_eq!(ip(&spoofed, 1).as_deref(), Some("203.0.113.7"));
+        assert_eq!(ip(&spoofed, 2).as_deref(), Some("198.51.100.1"));
+        let split = [("x-forwarded-for", "198.51.100.1"), ("x-forwarded-for", "2001:db8::1")];
+        assert_eq!(ip(&split, 1).as_deref(), Some("2001:db8::1"));
+        // No trusted proxy, fewer hops than proxies, or garbage: the socket peer
+        assert_eq!(ip(&spoofed, 0).as_deref(), Some("10.0.0.5"));
+        assert_eq!(ip(&spoofed, 3).as_deref(), Some("10.0.0.5"));
+        assert_eq!(ip(&[("x-real-ip", "2001:db8::1")], 1).as_deref(), Some("10.0.0.5"));
+        assert_eq!(ip(&[("x-forwarded-for", "not-an-ip")], 1).as_deref(), Some("10.0.0.5"));
+        assert_eq!(client_ip(&HeaderMap::new(), None, 1), None);
+    }
+}
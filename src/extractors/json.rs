@@ -0,0 +1,177 @@
+use axum::{
+    body::Bytes,
+    extract::{FromRequest, Request},
+    http::{header, HeaderMap},
+    response::{IntoResponse, Response},
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::error::Category;
+
+use crate::AppError;
+
+/// Drop-in replacement for `axum::Json`. Bodies that parse but do not fit the input type are rejected with
+/// a 422 `INVALID_BODY` naming the field, the expected type and the value received, instead of axum's
+/// plain-text rejection. Responses serialise exactly as `axum::Json`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Json<T>(pub T);
+
+impl<T, S> FromRequest<S> for Json<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        if !has_json_content_type(req.headers()) {
+            return Err(AppError::UnsupportedMediaType(
+                "Expected request with `Content-Type: application/json`".to_string(),
+            ));
+        }
+
+        let bytes = Bytes::from_request(req, state)
+            .await
+            .map_err(|e| AppError::BadRequest(format!("Failed to read request body: {}", e.body_text())))?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+impl<T: DeserializeOwned> Json<T> {
+    /// Deserialise a body, tracking the path to the field that failed
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, AppError> {
+        let mut deserializer = serde_json::Deserializer::from_slice(bytes);
+        let value = serde_path_to_error::deserialize(&mut deserializer).map_err(|err| {
+            let path = err.path().to_string();
+            body_error(&path, err.into_inner())
+        })?;
+        deserializer.end().map_err(|err| body_error(".", err))?;
+        Ok(Json(value))
+    }
+}
+
+impl<T: Serialize> IntoResponse for Json<T> {
+    fn into_response(self) -> Response {
+        axum::Json(self.0).into_response()
+    }
+}
+
+fn has_json_content_type(headers: &HeaderMap) -> bool {
+    let Some(content_type) = headers.get(header::CONTENT_TYPE).and_then(|v| v.to_str().ok()) else {
+        return false;
+    };
+    let essence = content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+    essence == "application/json" || (essence.starts_with("application/") && essence.ends_with("+json"))
+}
+
+/// Syntax errors stay 400s; anything that parsed but did not fit the input type becomes INVALID_BODY
+fn body_error(path: &str, err: serde_json::Error) -> AppError {
+    match err.classify() {
+        Category::Data => {
+            let (field, expected, received) = describe_data_error(path, &err.to_string());
+            AppError::InvalidBody {
+                message: format!("Invalid value for {}: {}", field, strip_position(&err.to_string())),
+                field,
+                expected,
+                received,
+            }
+        }
+        Category::Syntax | Category::Eof | Category::Io => {
+            AppError::BadRequest(format!("Malformed JSON body: {}", err))
+        }
+    }
+}
+
+/// serde_json appends " at line L column C" to every message
+fn strip_position(message: &str) -> &str {
+    match message.rfind(" at line ") {
+        Some(i) => &message[..i],
+        None => message,
+    }
+}
+
+/// Field path, expected type and received value from a serde data error.
+/// `path` is serde_path_to_error's rendering, "." at the top level.
+fn describe_data_error(path: &str, message: &str) -> (String, Option<String>, Option<String>) {
+    let message = strip_position(message);
+    let join = |name: &str| if path == "." { name.to_string() } else { format!("{}.{}", path, name) };
+    let quoted = |rest: &str| rest.split('`').nth(1).map(str::to_string);
+
+    // "missing field `role_id`" / "unknown field `x`, expected one of ..." name a child of `path`
+    if let Some(rest) = message.strip_prefix("missing field ") {
+        let field = quoted(rest).map(|name| join(&name)).unwrap_or_else(|| path.to_string());
+        return (field, Some("a value".to_string()), None);
+    }
+    if let Some(rest) = message.strip_prefix("unknown field ") {
+        let field = quoted(rest).map(|name| join(&name)).unwrap_or_else(|| path.to_string());
+        let expected = rest.split_once(", expected ").map(|(_, e)| e.to_string());
+        return (field, expected, Some("an unknown field".to_string()));
+    }
+
+    // "invalid type: string \"abc\", expected i32", "invalid value: integer `13`, expected ...",
+    // "unknown variant `X`, expected one of ..."
+    let rest = message
+        .strip_prefix("invalid type: ")
+        .or_else(|| message.strip_prefix("invalid value: "))
+        .or_else(|| message.strip_prefix("unknown variant "));
+    match rest.and_then(|rest| rest.split_once(", expected ")) {
+        Some((received, expected)) => (path.to_string(), Some(expected.to_string()), Some(received.to_string())),
+        None => (path.to_string(), None, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Entry {
+        start: String,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Input {
+        role_id: i32,
+        #[serde(default)]
+        entries: Vec<Entry>,
+    }
+
+    fn invalid_body(body: &str) -> (String, Option<String>, Option<String>) {
+        match Json::<Input>::from_bytes(body.as_bytes()) {
+            Err(AppError::InvalidBody { field, expected, received, .. }) => (field, expected, received),
+            other => panic!("expected InvalidBody, got {:?}", other.map(|j| j.0)),
+        }
+    }
+
+    #[test]
+    fn test_data_errors_name_field_expected_and_received() {
+        assert_eq!(
+            invalid_body(r#"{"role_id": "abc"}"#),
+            ("role_id".to_string(), Some("i32".to_string()), Some("string \"abc\"".to_string()))
+        );
+        assert_eq!(
+            invalid_body(r#"{"role_id": 1, "entries": [{"start": 9}]}"#),
+            ("entries[0].start".to_string(), Some("a string".to_string()), Some("integer `9`".to_string()))
+        );
+        assert_eq!(invalid_body(r#"{}"#), ("role_id".to_string(), Some("a value".to_string()), None));
+        assert_eq!(invalid_body(r#"{"entries": [{}], "role_id": 1}"#).0, "entries[0].start");
+    }
+
+    #[test]
+    fn test_syntax_errors_and_content_type() {
+        assert!(matches!(Json::<Input>::from_bytes(b"{\"role_id\": 1"), Err(AppError::BadRequest(_))));
+        assert!(matches!(Json::<Input>::from_bytes(b"{\"role_id\": 1} x"), Err(AppError::BadRequest(_))));
+        assert_eq!(Json::<Input>::from_bytes(b"{\"role_id\": 1}").unwrap().0.role_id, 1);
+
+        let mut headers = HeaderMap::new();
+        assert!(!has_json_content_type(&headers));
+        headers.insert(header::CONTENT_TYPE, "application/json; charset=utf-8".parse().unwrap());
+        assert!(has_json_content_type(&headers));
+        headers.insert(header::CONTENT_TYPE, "application/merge-patch+json".parse().unwrap());
+        assert!(has_json_content_type(&headers));
+        headers.insert(header::CONTENT_TYPE, "text/plain".parse().unwrap());
+        assert!(!has_json_content_type(&headers));
+    }
+}
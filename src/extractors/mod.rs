@@ -1,4 +1,10 @@
 pub mod auth;
+pub mod json;
 pub mod permissions;
 
 pub use auth::AuthenticatedUser;
+pub use json::Json;
+pub use permissions::{
+    CanAccessDiary, CanApproveRota, CanSetPay, CanEditRota, CanEditStaff, CanEditTemplates, CanViewStaffDetails, CanWorkShifts,
+    RequirePermission,
+};
@@ -1,35 +1,57 @@
-use moka::future::Cache;
-use once_cell::sync::Lazy;
-use std::time::Duration;
-
-// Cache UserRoleRows per profile_id (30-second TTL)
-static ROLES_CACHE: Lazy<Cache<i32, Vec<UserRoleRow>>> = Lazy::new(|| {
-    Cache::builder()
-        .time_to_live(Duration::from_secs(30))
-        .max_capacity(1_000)
-        .build()
-});
-
-/// Fetch user roles with caching
-async fn get_cached_roles(db: &sqlx::PgPool, profile_id: i32) -> Result<Vec<UserRoleRow>, sqlx::Error> {
-    if let Some(cached) = ROLES_CACHE.get(&profile_id).await {
-        return Ok(cached);
+use axum::{
+    extract::FromRequestParts,
+    http::request::Parts,
+    response::{IntoResponse, Response},
+};
+use chrono::NaiveDate;
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use crate::{extractors::AuthenticatedUser, AppError, AppState};
+
+/// The day revocations are judged against: the server's local date, so the cache, the hourly purge and SQL agree
+pub fn today() -> NaiveDate {
+    chrono::Local::now().date_naive()
+}
+
+/// SQL condition for a "UserRoles" row aliased `ur` that is still in force on the date bound at `$param` (bind
+/// `today()`): like the permission checks, raw membership queries stop counting a revoked assignment once its
+/// `effective_until` day is over, not when the hourly job deletes it
+pub fn ur_in_force(param: usize) -> String {
+    format!("(ur.effective_until IS NULL OR ur.effective_until >= ${}::date)", param)
+}
+
+/// ur_in_force for queries that read "UserRoles" without an alias
+pub fn user_role_in_force(param: usize) -> String {
+    format!("(effective_until IS NULL OR effective_until >= ${}::date)", param)
+}
+
+/// Fetch a user's permission snapshot through the app's cache (invalidated by user role, role and workplace mutations).
+/// Assignments past their `effective_until` day grant nothing, and a snapshot holding one is reloaded once it lapses.
+async fn get_cached_roles(state: &AppState, profile_id: i32) -> Result<Arc<PermissionSnapshot>, sqlx::Error> {
+    let today = today();
+    if let Some(cached) = state.caches.user_roles.get(&profile_id).await {
+        if cached.is_current(today) {
+            return Ok(cached);
+        }
     }
 
     let roles = sqlx::query_as::<_, UserRoleRow>(
-        r#"SELECT * FROM "UserRoles" WHERE user_profile_id = $1"#,
+        &format!(r#"SELECT * FROM "UserRoles" WHERE user_profile_id = $1 AND {}"#, user_role_in_force(2)),
     )
     .bind(profile_id)
-    .fetch_all(db)
+    .bind(today)
+    .fetch_all(&state.db)
     .await?;
 
-    ROLES_CACHE.insert(profile_id, roles.clone()).await;
-    Ok(roles)
+    let snapshot = Arc::new(PermissionSnapshot::new(roles));
+    state.caches.user_roles.insert(profile_id, snapshot.clone()).await;
+    Ok(snapshot)
 }
 
 /// Check if user has the required permission
 pub async fn has_permission(
-    db: &sqlx::PgPool,
+    state: &AppState,
     profile_id: i32,
     is_super_admin: bool,
     permission_check: impl Fn(&UserRoleRow) -> bool,
@@ -38,13 +60,13 @@ pub async fn has_permission(
         return Ok(true);
     }
 
-    let roles = get_cached_roles(db, profile_id).await?;
-    Ok(roles.iter().any(permission_check))
+    let snapshot = get_cached_roles(state, profile_id).await?;
+    Ok(snapshot.roles.iter().any(permission_check))
 }
 
 /// Check if user has any of the specified permissions
 pub async fn has_any_permission(
-    db: &sqlx::PgPool,
+    state: &AppState,
     profile_id: i32,
     is_super_admin: bool,
     checks: &[fn(&UserRoleRow) -> bool],
@@ -53,10 +75,10 @@ pub async fn has_any_permission(
         return Ok(true);
     }
 
-    let roles = get_cached_roles(db, profile_id).await?;
+    let snapshot = get_cached_roles(state, profile_id).await?;
 
     for check in checks {
-        if roles.iter().any(check) {
+        if snapshot.roles.iter().any(check) {
             return Ok(true);
         }
     }
@@ -64,6 +86,16 @@ pub async fn has_any_permission(
     Ok(false)
 }
 
+/// IDs of the roles in which the user holds the permission (callers handle super admins)
+pub async fn roles_with_permission(
+    state: &AppState,
+    profile_id: i32,
+    permission_check: impl Fn(&UserRoleRow) -> bool,
+) -> Result<Vec<i32>, sqlx::Error> {
+    let snapshot = get_cached_roles(state, profile_id).await?;
+    Ok(snapshot.roles.iter().filter(|r| permission_check(r)).map(|r| r.role_id).collect())
+}
+
 #[derive(sqlx::FromRow, Clone)]
 pub struct UserRoleRow {
     pub id: i32,
@@ -75,6 +107,10 @@ pub struct UserRoleRow {
     pub can_edit_templates: bool,
     pub can_edit_staff: bool,
     pub can_view_staff_details: bool,
+    pub can_approve_rota: bool,
+    pub can_set_pay: bool,
+    /// Last day a revoked assignment still grants access; None until revoked
+    pub effective_until: Option<NaiveDate>,
 }
 
 // Permission check functions
@@ -102,10 +138,65 @@ pub fn can_view_staff_details(role: &UserRoleRow) -> bool {
     role.can_view_staff_details
 }
 
-/// Check if user has a specific permission by name (string-based for convenience in handlers)
-/// Uses cached roles data instead of individual DB queries
+pub fn can_approve_rota(role: &UserRoleRow) -> bool {
+    role.can_approve_rota
+}
+
+pub fn can_set_pay(role: &UserRoleRow) -> bool {
+    role.can_set_pay
+}
+
+type PermissionCheck = fn(&UserRoleRow) -> bool;
+
+/// Every permission flag by name, in the bit order of `PermissionSnapshot`
+const PERMISSIONS: [(&str, PermissionCheck); 8] = [
+    ("can_edit_rota", can_edit_rota),
+    ("can_access_diary", can_access_diary),
+    ("can_work_shifts", can_work_shifts),
+    ("can_edit_templates", can_edit_templates),
+    ("can_edit_staff", can_edit_staff),
+    ("can_view_staff_details", can_view_staff_details),
+    ("can_approve_rota", can_approve_rota),
+    ("can_set_pay", can_set_pay),
+];
+
+/// A user's role assignments plus the permissions they hold in any role, worked out once when cached
+#[derive(Clone, Default)]
+pub struct PermissionSnapshot {
+    pub roles: Vec<UserRoleRow>,
+    /// Bit i is set when `PERMISSIONS[i]` is held in at least one role
+    anywhere: u16,
+    /// Earliest `effective_until` among the roles: the snapshot is wrong from the day after
+    expires_after: Option<NaiveDate>,
+}
+
+impl PermissionSnapshot {
+    pub fn new(roles: Vec<UserRoleRow>) -> Self {
+        let anywhere = PERMISSIONS
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, check))| roles.iter().any(check))
+            .fold(0, |bits, (i, _)| bits | 1 << i);
+        let expires_after = roles.iter().filter_map(|r| r.effective_until).min();
+        Self { roles, anywhere, expires_after }
+    }
+
+    /// Whether every role in the snapshot still grants access on `today`
+    pub fn is_current(&self, today: NaiveDate) -> bool {
+        self.expires_after.is_none_or(|last_day| last_day >= today)
+    }
+
+    /// Whether the named permission is held in any role; None for an unknown name
+    pub fn holds(&self, permission_name: &str) -> Option<bool> {
+        let i = PERMISSIONS.iter().position(|(name, _)| *name == permission_name)?;
+        Some(self.anywhere & (1 << i) != 0)
+    }
+}
+
+/// Check if user has a specific permission by name (string-based for convenience in handlers).
+/// Super admins never reach the cache; everyone else is answered from the precomputed snapshot.
 pub async fn has_permission_by_name(
-    db: &sqlx::PgPool,
+    state: &AppState,
     profile_id: i32,
     is_super_admin: bool,
     permission_name: &str,
@@ -114,17 +205,126 @@ pub async fn has_permission_by_name(
         return Ok(true);
     }
 
-    let roles = get_cached_roles(db, profile_id).await?;
+    let snapshot = get_cached_roles(state, profile_id).await?;
+    snapshot.holds(permission_name).ok_or(sqlx::Error::RowNotFound)
+}
+
+/// A permission flag on "UserRoles", for use as `RequirePermission<P>`
+pub trait Permission: Send + Sync + 'static {
+    const NAME: &'static str;
+    fn check(role: &UserRoleRow) -> bool;
+}
+
+macro_rules! permission {
+    ($marker:ident, $name:literal, $check:ident) => {
+        #[doc = concat!("Requires `", $name, "` in at least one role")]
+        pub struct $marker;
 
-    let check: fn(&UserRoleRow) -> bool = match permission_name {
-        "can_edit_rota" => can_edit_rota,
-        "can_access_diary" => can_access_diary,
-        "can_work_shifts" => can_work_shifts,
-        "can_edit_templates" => can_edit_templates,
-        "can_edit_staff" => can_edit_staff,
-        "can_view_staff_details" => can_view_staff_details,
-        _ => return Err(sqlx::Error::RowNotFound),
+        impl Permission for $marker {
+            const NAME: &'static str = $name;
+            fn check(role: &UserRoleRow) -> bool {
+                $check(role)
+            }
+        }
     };
+}
+
+permission!(CanEditRota, "can_edit_rota", can_edit_rota);
+permission!(CanAccessDiary, "can_access_diary", can_access_diary);
+permission!(CanWorkShifts, "can_work_shifts", can_work_shifts);
+permission!(CanEditTemplates, "can_edit_templates", can_edit_templates);
+permission!(CanEditStaff, "can_edit_staff", can_edit_staff);
+permission!(CanViewStaffDetails, "can_view_staff_details", can_view_staff_details);
+permission!(CanApproveRota, "can_approve_rota", can_approve_rota);
+permission!(CanSetPay, "can_set_pay", can_set_pay);
+
+/// Authenticated user holding permission `P` in any of their roles (super admins always pass).
+/// Rejects with 403 "Missing <permission> permission" before the handler body or request body is touched.
+/// Checks scoped to one role (from the path or body) still belong in the handler.
+pub struct RequirePermission<P: Permission> {
+    pub auth: AuthenticatedUser,
+    _permission: PhantomData<P>,
+}
+
+impl<P: Permission> FromRequestParts<Arc<AppState>> for RequirePermission<P> {
+    type Rejection = Response;
 
-    Ok(roles.iter().any(check))
+    async fn from_request_parts(parts: &mut Parts, state: &Arc<AppState>) -> Result<Self, Self::Rejection> {
+        let auth = AuthenticatedUser::from_request_parts(parts, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+
+        let allowed = has_permission_by_name(state, auth.profile_id, auth.is_super_admin, P::NAME)
+            .await
+            .map_err(|e| AppError::from(e).into_response())?;
+        if !allowed {
+            tracing::debug!(profile_id = auth.profile_id, permission = P::NAME, "🔐 Permission check failed");
+            return Err(AppError::Forbidden(format!("Missing {} permission", P::NAME)).into_response());
+        }
+
+        Ok(Self { auth, _permission: PhantomData })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permission_markers_read_their_flag() {
+        let none = UserRoleRow {
+            id: 1,
+            role_id: 1,
+            user_profile_id: 1,
+            can_edit_rota: false,
+            can_access_diary: false,
+            can_work_shifts: false,
+            can_edit_templates: false,
+            can_edit_staff: false,
+            can_view_staff_details: false,
+            can_approve_rota: false,
+            can_set_pay: false,
+            effective_until: None,
+        };
+        let only = |name: &str| UserRoleRow {
+            can_edit_rota: name == "can_edit_rota",
+            can_access_diary: name == "can_access_diary",
+            can_work_shifts: name == "can_work_shifts",
+            can_edit_templates: name == "can_edit_templates",
+            can_edit_staff: name == "can_edit_staff",
+            can_view_staff_details: name == "can_view_staff_details",
+            can_approve_rota: name == "can_approve_rota",
+            can_set_pay: name == "can_set_pay",
+            ..none.clone()
+        };
+
+        fn assert_marker<P: Permission>(only: impl Fn(&str) -> UserRoleRow, none: &UserRoleRow) {
+            assert!(P::check(&only(P::NAME)), "{} not read", P::NAME);
+            assert!(!P::check(none));
+            assert!(!P::check(&only("other")));
+        }
+        assert_marker::<CanEditRota>(only, &none);
+        assert_marker::<CanAccessDiary>(only, &none);
+        assert_marker::<CanWorkShifts>(only, &none);
+        assert_marker::<CanEditTemplates>(only, &none);
+        assert_marker::<CanEditStaff>(only, &none);
+        assert_marker::<CanViewStaffDetails>(only, &none);
+        assert_marker::<CanApproveRota>(only, &none);
+        assert_marker::<CanSetPay>(only, &none);
+
+        let snapshot = PermissionSnapshot::new(vec![only("can_edit_rota"), only("can_set_pay"), none.clone()]);
+        assert_eq!(snapshot.holds("can_edit_rota"), Some(true));
+        assert_eq!(snapshot.holds("can_set_pay"), Some(true));
+        assert_eq!(snapshot.holds("can_edit_staff"), Some(false));
+        assert_eq!(snapshot.holds("can_fly"), None);
+        assert_eq!(PermissionSnapshot::default().holds("can_edit_rota"), Some(false));
+        let day = |d| NaiveDate::from_ymd_opt(2026, 10, d).unwrap();
+        let revoked = PermissionSnapshot::new(vec![UserRoleRow { effective_until: Some(day(19)), ..none.clone() }, none.clone()]);
+        assert!(revoked.is_current(day(19)));
+        assert!(!revoked.is_current(day(20)));
+        assert!(snapshot.is_current(day(20)));
+        for (name, _) in PERMISSIONS {
+            assert_eq!(PermissionSnapshot::new(vec![only(name)]).holds(name), Some(true), "{} not in the snapshot", name);
+        }
+    }
 }
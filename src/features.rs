@@ -0,0 +1,132 @@
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::collections::BTreeMap;
+
+use crate::{models::FeatureFlag, AppError, AppResult, AppState};
+
+/// A feature the backend checks. Without a FeatureFlags row the default applies, so features that
+/// already shipped stay on until a flag is created for them.
+#[derive(Debug, Clone, Copy)]
+pub struct Feature {
+    pub key: &'static str,
+    pub default_enabled: bool,
+}
+
+/// Issuing read-only rota tokens for wall displays / kiosks
+pub const DISPLAY_TOKENS: Feature = Feature { key: "display_tokens", default_enabled: true };
+
+/// Features guarded in handlers; always listed by GET /api/features
+pub const KNOWN_FEATURES: &[Feature] = &[DISPLAY_TOKENS];
+
+/// Stable 0-99 bucket for a user and flag, so a rollout percentage picks the same users every time
+/// and different flags pick different users
+fn rollout_bucket(key: &str, user_profile_id: i32) -> i16 {
+    let digest = Sha256::digest(format!("{}:{}", key, user_profile_id).as_bytes());
+    (u16::from_be_bytes([digest[0], digest[1]]) % 100) as i16
+}
+
+/// Whether a flag is on for a user with roles in `workplace_ids`
+pub fn evaluate(flag: &FeatureFlag, user_profile_id: i32, workplace_ids: &[i32]) -> bool {
+    flag.enabled
+        || flag.user_profile_ids.contains(&user_profile_id)
+        || flag.workplace_ids.iter().any(|id| workplace_ids.contains(id))
+        || rollout_bucket(&flag.key, user_profile_id) < flag.rollout_percent
+}
+
+/// All flags, cached until a flag changes (or the TTL runs out)
+pub async fn load_flags(state: &AppState) -> AppResult<Vec<FeatureFlag>> {
+    if let Some(cached) = state.caches.feature_flags.get().await {
+        return Ok(cached);
+    }
+
+    let flags = sqlx::query_as::<_, FeatureFlag>(r#"SELECT * FROM "FeatureFlags" ORDER BY key"#)
+        .fetch_all(&state.db)
+        .await?;
+
+    state.caches.feature_flags.insert(flags.clone()).await;
+    Ok(flags)
+}
+
+async fn user_workplace_ids(db: &PgPool, user_profile_id: i32) -> AppResult<Vec<i32>> {
+    let ids = sqlx::query_scalar(&format!(
+        r#"
+        SELECT DISTINCT r.workplace_id::int4
+        FROM "UserRoles" ur
+        INNER JOIN "Roles" r ON ur.role_id = r.id
+        WHERE ur.user_profile_id = $1 AND {ur_in_force}
+        "#,
+        ur_in_force = crate::extractors::permissions::ur_in_force(2)
+    ))
+    .bind(user_profile_id)
+    .bind(crate::extractors::permissions::today())
+    .fetch_all(db)
+    .await?;
+    Ok(ids)
+}
+
+/// Every known feature and every flag, resolved for one user
+pub async fn features_for_user(state: &AppState, user_profile_id: i32) -> AppResult<BTreeMap<String, bool>> {
+    let flags = load_flags(state).await?;
+    let workplace_ids = user_workplace_ids(&state.db, user_profile_id).await?;
+
+    let mut features: BTreeMap<String, bool> = KNOWN_FEATURES
+        .iter()
+        .map(|f| (f.key.to_string(), f.default_enabled))
+        .collect();
+    for flag in &flags {
+        features.insert(flag.key.clone(), evaluate(flag, user_profile_id, &workplace_ids));
+    }
+    Ok(features)
+}
+
+pub async fn is_enabled(state: &AppState, feature: Feature, user_profile_id: i32) -> AppResult<bool> {
+    let flags = load_flags(state).await?;
+    let Some(flag) = flags.iter().find(|f| f.key == feature.key) else {
+        return Ok(feature.default_enabled);
+    };
+    let workplace_ids = user_workplace_ids(&state.db, user_profile_id).await?;
+    Ok(evaluate(flag, user_profile_id, &workplace_ids))
+}
+
+/// Guard for handlers: FEATURE_DISABLED (403) unless the feature is on for the user
+pub async fn require(state: &AppState, feature: Feature, user_profile_id: i32) -> AppResult<()> {
+    if is_enabled(state, feature, user_profile_id).await? {
+        Ok(())
+    } else {
+        Err(AppError::FeatureDisabled { feature: feature.key })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flag(enabled: bool, workplace_ids: Vec<i32>, user_profile_ids: Vec<i32>, rollout_percent: i16) -> FeatureFlag {
+        FeatureFlag {
+            key: "swap_chains".to_string(),
+            description: None,
+            enabled,
+            workplace_ids,
+            user_profile_ids,
+            rollout_percent,
+            updated_by: None,
+            updated_at: chrono::NaiveDateTime::default(),
+        }
+    }
+
+    #[test]
+    fn test_evaluate_targets() {
+        assert!(evaluate(&flag(true, vec![], vec![], 0), 7, &[]));
+        assert!(!evaluate(&flag(false, vec![], vec![], 0), 7, &[1]));
+        assert!(evaluate(&flag(false, vec![2], vec![], 0), 7, &[1, 2]));
+        assert!(evaluate(&flag(false, vec![], vec![7], 0), 7, &[]));
+        assert!(evaluate(&flag(false, vec![], vec![], 100), 7, &[]));
+    }
+
+    #[test]
+    fn test_rollout_bucket_is_stable_and_spread() {
+        assert_eq!(rollout_bucket("swap_chains", 42), rollout_bucket("swap_chains", 42));
+        let in_half = (1..=1000).filter(|id| rollout_bucket("swap_chains", *id) < 50).count();
+        assert!((400..=600).contains(&in_half));
+    }
+}
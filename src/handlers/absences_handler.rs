@@ -0,0 +1,264 @@
+use axum::extract::State;
+use chrono::NaiveDate;
+use std::collections::HashMap;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    extractors::{permissions, AuthenticatedUser, Json},
+    models::{AbsenceReport, AbsenceShiftImpact, ReplacementCandidate, ReportAbsenceInput},
+    AppError, AppResult, AppState,
+};
+
+/// Longest sickness that can be reported in one go; longer absences go through the rota admins
+const MAX_ABSENCE_DAYS: i64 = 14;
+
+/// Days covered by a report: from today onwards, at most MAX_ABSENCE_DAYS long
+fn absence_dates(start: NaiveDate, end: Option<NaiveDate>, today: NaiveDate) -> AppResult<Vec<NaiveDate>> {
+    let end = end.unwrap_or(start);
+    if start < today {
+        return Err(AppError::BadRequest("Absences can only be reported from today onwards".to_string()));
+    }
+    if end < start {
+        return Err(AppError::BadRequest("end_date must not be before start_date".to_string()));
+    }
+    let days = (end - start).num_days() + 1;
+    if days > MAX_ABSENCE_DAYS {
+        return Err(AppError::BadRequest(format!("Absences can cover at most {} days", MAX_ABSENCE_DAYS)));
+    }
+    Ok(start.iter_days().take(days as usize).collect())
+}
+
+/// POST /api/absences - Report sickness: flags the user's shifts for cover, records sick leave in the diary,
+/// notifies the rota admins and suggests replacements
+#[utoipa::path(
+    post,
+    path = "/api/absences",
+    request_body = ReportAbsenceInput,
+    responses(
+        (status = 200, description = "Absence recorded; affected shifts with suggested replacements", body = AbsenceReport),
+        (status = 400, description = "Date range in the past, reversed or too long, or the user works no shifts"),
+        (status = 403, description = "Reporting for someone else without can_edit_rota in their roles")
+    ),
+    tag = "absences",
+    security(("cookie_auth" = []))
+)]
+pub async fn report_absence(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+    Json(input): Json<ReportAbsenceInput>,
+) -> AppResult<Json<AbsenceReport>> {
+    // Use confirmed user ID if provided (generic account flow) to attribute the entries; permissions are
+    // always the signed-in user's
+    let acting_user_id = input.confirmed_user_id.unwrap_or(auth.profile_id);
+    let absent_user_id = input.user_profile_id.unwrap_or(acting_user_id);
+
+    let today = chrono::Local::now().date_naive();
+    let dates = absence_dates(input.start_date, input.end_date, today)?;
+    let start_date = input.start_date;
+    let end_date = *dates.last().unwrap_or(&start_date);
+
+    let work_roles = permissions::roles_with_permission(&state, absent_user_id, |r| r.can_work_shifts).await?;
+    if work_roles.is_empty() {
+        return Err(AppError::BadRequest("User does not work shifts in any role".to_string()));
+    }
+
+    let mut tx = crate::db::begin_as_user(&state.db, acting_user_id).await?;
+
+    // Locking the user's row serialises reports for the same person, so a repeated report finds the first
+    // one's diary entries instead of adding its own
+    let short_name: String = sqlx::query_scalar(r#"SELECT short_name FROM "Users" WHERE user_profile_id = $1 FOR UPDATE"#)
+        .bind(absent_user_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("User {} not found", absent_user_id)))?;
+
+    let mut shifts = sqlx::query_as::<_, AbsenceShiftImpact>(
+        r#"
+        SELECT
+            uuid AS shift_uuid,
+            role_id,
+            date,
+            to_char(start, 'HH24:MI') AS start_time,
+            to_char("end", 'HH24:MI') AS end_time,
+            label
+        FROM "Shifts"
+        WHERE user_profile_id = $1 AND date BETWEEN $2 AND $3 AND time_off_category_id IS NULL
+        ORDER BY date, start
+        FOR UPDATE
+        "#,
+    )
+    .bind(absent_user_id)
+    .bind(start_date)
+    .bind(end_date)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let mut shift_roles: Vec<i32> = shifts.iter().map(|s| s.role_id).collect();
+    shift_roles.sort_unstable();
+    shift_roles.dedup();
+
+    // Reporting for someone else is a rota admin's job in every role it touches
+    if absent_user_id != auth.profile_id && !auth.is_super_admin {
+        let rota_roles = permissions::roles_with_permission(&state, auth.profile_id, |r| r.can_edit_rota).await?;
+        let covers = |role_id: &i32| rota_roles.contains(role_id);
+        if !shift_roles.iter().chain(&work_roles).all(covers) {
+            return Err(AppError::Forbidden("Missing can_edit_rota permission for this user's roles".to_string()));
+        }
+    }
+
+    let shift_ids: Vec<Uuid> = shifts.iter().map(|s| s.shift_uuid).collect();
+    crate::handlers::payroll_locks_handler::ensure_shifts_unlocked(&mut tx, &shift_ids).await?;
+    sqlx::query(r#"UPDATE "Shifts" SET needs_cover = true WHERE uuid = ANY($1)"#)
+        .bind(&shift_ids)
+        .execute(&mut *tx)
+        .await?;
+
+    let mut diary_entry_ids = Vec::with_capacity(work_roles.len() * dates.len());
+    for role_id in &work_roles {
+        for date in &dates {
+            // One sick entry per user, role and day, however often the absence is reported
+            let id: i32 = sqlx::query_scalar(
+                r#"
+                WITH existing AS (
+                    SELECT id FROM "Diary"
+                    WHERE role_id = $1 AND date = $2 AND user_profile_id = $4 AND sl AND NOT deleted
+                    ORDER BY id
+                    LIMIT 1
+                ),
+                inserted AS (
+                    INSERT INTO "Diary" (role_id, date, entry, al, sl, pl, user_profile_id, created_by, deleted)
+                    SELECT $1, $2, $3, false, true, false, $4, $5, false
+                    WHERE NOT EXISTS (SELECT 1 FROM existing)
+                    RETURNING id
+                )
+                SELECT id::int4 FROM existing
+                UNION ALL
+                SELECT id::int4 FROM inserted
+                "#,
+            )
+            .bind(role_id)
+            .bind(date)
+            .bind(&input.reason)
+            .bind(absent_user_id)
+            .bind(acting_user_id)
+            .fetch_one(&mut *tx)
+            .await?;
+            diary_entry_ids.push(id);
+        }
+    }
+
+    let admins: Vec<i32> = sqlx::query_scalar(&format!(
+        r#"
+        SELECT DISTINCT user_profile_id FROM "UserRoles"
+        WHERE role_id = ANY($1) AND can_edit_rota AND {in_force} AND user_profile_id <> $2
+        "#,
+        in_force = crate::extractors::permissions::user_role_in_force(3)
+    ))
+    .bind(&work_roles)
+    .bind(absent_user_id)
+    .bind(crate::extractors::permissions::today())
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let message = format!(
+        "{} has reported sick ({} to {}); {} shift(s) need cover.",
+        short_name,
+        start_date,
+        end_date,
+        shifts.len()
+    );
+    for admin_id in &admins {
+        crate::handlers::notifications_handler::notify(
+            &mut *tx,
+            *admin_id,
+            "ABSENCE_REPORTED",
+            &message,
+            serde_json::json!({
+                "user_profile_id": absent_user_id,
+                "start_date": start_date,
+                "end_date": end_date,
+                "shift_ids": shift_ids,
+            }),
+        )
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    tracing::info!(
+        absent_user_id,
+        reported_by = acting_user_id,
+        %start_date,
+        %end_date,
+        shifts = shifts.len(),
+        admins_notified = admins.len(),
+        "🤒 Absence reported"
+    );
+
+    // Suggestions: staff in the shift's role with nothing else on that day
+    let candidates: Vec<(Uuid, i32, String, String)> = sqlx::query_as(&format!(
+        r#"
+        SELECT s.uuid, u.user_profile_id, u.short_name, u.full_name
+        FROM "Shifts" s
+        INNER JOIN "UserRoles" ur ON ur.role_id = s.role_id AND ur.can_work_shifts AND {ur_in_force}
+        INNER JOIN "Users" u ON u.user_profile_id = ur.user_profile_id
+        WHERE s.uuid = ANY($1)
+          AND u.user_profile_id <> $2
+          AND NOT COALESCE(u.is_generic_login, false)
+          AND NOT EXISTS (
+              SELECT 1 FROM "Shifts" o WHERE o.user_profile_id = u.user_profile_id AND o.date = s.date
+          )
+          AND NOT EXISTS (
+              SELECT 1 FROM "Diary" d
+              WHERE d.user_profile_id = u.user_profile_id AND d.date = s.date AND NOT d.deleted AND (d.al OR d.sl OR d.pl)
+          )
+        ORDER BY u.short_name
+        "#,
+        ur_in_force = crate::extractors::permissions::ur_in_force(3)
+    ))
+    .bind(&shift_ids)
+    .bind(absent_user_id)
+    .bind(crate::extractors::permissions::today())
+    .fetch_all(&state.db)
+    .await?;
+
+    let mut by_shift: HashMap<Uuid, Vec<ReplacementCandidate>> = HashMap::new();
+    for (shift_uuid, user_profile_id, short_name, full_name) in candidates {
+        by_shift.entry(shift_uuid).or_default().push(ReplacementCandidate {
+            user_profile_id,
+            short_name,
+            full_name,
+        });
+    }
+    for shift in &mut shifts {
+        shift.suggested_replacements = by_shift.remove(&shift.shift_uuid).unwrap_or_default();
+    }
+
+    Ok(Json(AbsenceReport {
+        user_profile_id: absent_user_id,
+        start_date,
+        end_date,
+        diary_entry_ids,
+        shifts,
+        admins_notified: admins.len(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_absence_dates() {
+        let today = NaiveDate::from_ymd_opt(2025, 5, 1).unwrap();
+        let in_days = |n| today + chrono::Duration::days(n);
+
+        assert_eq!(absence_dates(today, None, today).unwrap(), vec![today]);
+        assert_eq!(absence_dates(today, Some(in_days(2)), today).unwrap().len(), 3);
+        assert_eq!(absence_dates(today, Some(in_days(MAX_ABSENCE_DAYS - 1)), today).unwrap().len(), 14);
+        assert!(absence_dates(today, Some(in_days(MAX_ABSENCE_DAYS)), today).is_err());
+        assert!(absence_dates(in_days(-1), None, today).is_err());
+        assert!(absence_dates(in_days(2), Some(in_days(1)), today).is_err());
+    }
+}
@@ -0,0 +1,396 @@
+use axum::{
+    extract::{Path, Query, State},
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+use crate::{
+    extractors::{permissions, AuthenticatedUser, Json},
+    models::{Announcement, AnnouncementMutationResponse, CreateAnnouncementInput, UpdateAnnouncementInput},
+    AppError, AppResult, AppState,
+};
+
+/// Announcement columns plus author and the caller's read state ($1 = caller)
+const ANNOUNCEMENT_SELECT: &str = r#"
+    SELECT
+        a.id,
+        a.title,
+        a.body,
+        a.workplace_id,
+        a.role_id,
+        a.pinned,
+        a.publish_at,
+        a.expires_at,
+        a.created_by,
+        u.short_name AS author_name,
+        a.created_at,
+        a.updated_at,
+        ar.read_at
+    FROM "Announcements" a
+    LEFT JOIN "Users" u ON a.created_by = u.user_profile_id
+    LEFT JOIN "AnnouncementReads" ar ON ar.announcement_id = a.id AND ar.user_profile_id = $1
+"#;
+
+/// Live announcements addressed to the caller ($1): everyone, one of their roles, or a workplace they work in,
+/// counting the roles in force on the date bound at `$today`
+fn visible_to_caller(today: usize) -> String {
+    format!(
+        r#"
+    a.publish_at <= NOW()
+    AND (a.expires_at IS NULL OR a.expires_at > NOW())
+    AND (
+        (a.workplace_id IS NULL AND a.role_id IS NULL)
+        OR a.role_id IN (
+            SELECT role_id FROM "UserRoles"
+            WHERE user_profile_id = $1 AND {in_force}
+        )
+        OR (a.role_id IS NULL AND a.workplace_id IN (
+            SELECT r.workplace_id FROM "UserRoles" ur INNER JOIN "Roles" r ON ur.role_id = r.id
+            WHERE ur.user_profile_id = $1 AND {ur_in_force}
+        ))
+    )
+"#,
+        in_force = permissions::user_role_in_force(today),
+        ur_in_force = permissions::ur_in_force(today)
+    )
+}
+
+const ANNOUNCEMENT_ORDER: &str = " ORDER BY a.pinned DESC, a.publish_at DESC, a.id DESC";
+
+/// Unread live announcements for a user, pinned first (used by the dashboard)
+pub async fn unread_announcements(db: &sqlx::PgPool, profile_id: i32) -> AppResult<Vec<Announcement>> {
+    let announcements = sqlx::query_as::<_, Announcement>(&format!(
+        "{} WHERE {} AND ar.read_at IS NULL{}",
+        ANNOUNCEMENT_SELECT,
+        visible_to_caller(2),
+        ANNOUNCEMENT_ORDER
+    ))
+    .bind(profile_id)
+    .bind(permissions::today())
+    .fetch_all(db)
+    .await?;
+
+    Ok(announcements)
+}
+
+/// Posting to a role needs can_edit_rota there (`edits_rota_in_role`); workplace-wide and everyone need super admin
+fn check_target(
+    is_super_admin: bool,
+    edits_rota_in_role: bool,
+    workplace_id: Option<i32>,
+    role_id: Option<i32>,
+) -> AppResult<()> {
+    let allowed = match role_id {
+        Some(_) => is_super_admin || edits_rota_in_role,
+        None => is_super_admin,
+    };
+
+    if !allowed {
+        let scope = match (role_id, workplace_id) {
+            (Some(_), _) => "can_edit_rota on the target role",
+            (None, Some(_)) => "Super admin permission for workplace-wide announcements",
+            (None, None) => "Super admin permission for announcements to everyone",
+        };
+        return Err(AppError::Forbidden(format!("{} required", scope)));
+    }
+
+    Ok(())
+}
+
+async fn ensure_can_target(
+    state: &AppState,
+    auth: &AuthenticatedUser,
+    workplace_id: Option<i32>,
+    role_id: Option<i32>,
+) -> AppResult<()> {
+    let edits_rota_in_role = match role_id {
+        Some(role_id) => {
+            permissions::has_permission(state, auth.profile_id, auth.is_super_admin, |r| {
+                r.role_id == role_id && r.can_edit_rota
+            })
+            .await?
+        }
+        None => false,
+    };
+    check_target(auth.is_super_admin, edits_rota_in_role, workplace_id, role_id)
+}
+
+async fn fetch_announcement(db: &sqlx::PgPool, profile_id: i32, announcement_id: i32) -> AppResult<Announcement> {
+    sqlx::query_as::<_, Announcement>(&format!("{} WHERE a.id = $2", ANNOUNCEMENT_SELECT))
+        .bind(profile_id)
+        .bind(announcement_id)
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Announcement {} not found", announcement_id)))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct GetAnnouncementsQuery {
+    #[serde(rename = "unreadOnly")]
+    pub unread_only: Option<bool>,
+}
+
+/// GET /api/announcements?unreadOnly= - Live announcements addressed to the caller
+#[utoipa::path(
+    get,
+    path = "/api/announcements",
+    params(GetAnnouncementsQuery),
+    responses(
+        (status = 200, description = "Announcements for the caller's roles and workplaces, pinned first", body = Vec<Announcement>)
+    ),
+    tag = "announcements",
+    security(("cookie_auth" = []))
+)]
+pub async fn get_announcements(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+    Query(query): Query<GetAnnouncementsQuery>,
+) -> AppResult<Json<Vec<Announcement>>> {
+    let announcements = sqlx::query_as::<_, Announcement>(&format!(
+        "{} WHERE {} AND ($2 = FALSE OR ar.read_at IS NULL){}",
+        ANNOUNCEMENT_SELECT,
+        visible_to_caller(3),
+        ANNOUNCEMENT_ORDER
+    ))
+    .bind(auth.profile_id)
+    .bind(query.unread_only.unwrap_or(false))
+    .bind(permissions::today())
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(announcements))
+}
+
+/// POST /api/announcements - Post an announcement
+#[utoipa::path(
+    post,
+    path = "/api/announcements",
+    request_body = CreateAnnouncementInput,
+    responses(
+        (status = 200, description = "Announcement created", body = Announcement),
+        (status = 400, description = "Empty title/body or expiry before publish time"),
+        (status = 403, description = "Missing can_edit_rota on the role, or super admin for wider targeting")
+    ),
+    tag = "announcements",
+    security(("cookie_auth" = []))
+)]
+pub async fn create_announcement(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+    Json(input): Json<CreateAnnouncementInput>,
+) -> AppResult<Json<Announcement>> {
+    ensure_can_target(&state, &auth, input.workplace_id, input.role_id).await?;
+
+    if input.title.trim().is_empty() || input.body.trim().is_empty() {
+        return Err(AppError::BadRequest("title and body are required".to_string()));
+    }
+    let publish_at = input.publish_at.unwrap_or_else(|| chrono::Utc::now().naive_utc());
+    if input.expires_at.is_some_and(|expires_at| expires_at <= publish_at) {
+        return Err(AppError::BadRequest("expires_at must be after publish_at".to_string()));
+    }
+
+    let id: i32 = sqlx::query_scalar(
+        r#"
+        INSERT INTO "Announcements" (title, body, workplace_id, role_id, pinned, publish_at, expires_at, created_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING id
+        "#,
+    )
+    .bind(input.title.trim())
+    .bind(&input.body)
+    .bind(input.workplace_id)
+    .bind(input.role_id)
+    .bind(input.pinned)
+    .bind(publish_at)
+    .bind(input.expires_at)
+    .bind(auth.profile_id)
+    .fetch_one(&state.db)
+    .await?;
+
+    tracing::info!(
+        announcement_id = id,
+        role_id = ?input.role_id,
+        workplace_id = ?input.workplace_id,
+        "📢 Announcement posted"
+    );
+
+    Ok(Json(fetch_announcement(&state.db, auth.profile_id, id).await?))
+}
+
+/// PUT /api/announcements/{id} - Edit, pin/unpin or reschedule an announcement
+#[utoipa::path(
+    put,
+    path = "/api/announcements/{id}",
+    params(
+        ("id" = i32, Path, description = "Announcement ID")
+    ),
+    request_body = UpdateAnnouncementInput,
+    responses(
+        (status = 200, description = "Announcement updated", body = Announcement),
+        (status = 400, description = "Expiry before publish time"),
+        (status = 403, description = "Not allowed to post to this announcement's audience"),
+        (status = 404, description = "Announcement not found")
+    ),
+    tag = "announcements",
+    security(("cookie_auth" = []))
+)]
+pub async fn update_announcement(
+    State(state): State<Arc<AppState>>,
+    Path(announcement_id): Path<i32>,
+    auth: AuthenticatedUser,
+    Json(input): Json<UpdateAnnouncementInput>,
+) -> AppResult<Json<Announcement>> {
+    let existing = fetch_announcement(&state.db, auth.profile_id, announcement_id).await?;
+    ensure_can_target(&state, &auth, existing.workplace_id, existing.role_id).await?;
+
+    let publish_at = input.publish_at.unwrap_or(existing.publish_at);
+    let expires_at = input.expires_at.or(existing.expires_at);
+    if expires_at.is_some_and(|expires_at| expires_at <= publish_at) {
+        return Err(AppError::BadRequest("expires_at must be after publish_at".to_string()));
+    }
+    if input.title.as_deref().is_some_and(|t| t.trim().is_empty()) || input.body.as_deref().is_some_and(|b| b.trim().is_empty()) {
+        return Err(AppError::BadRequest("title and body cannot be empty".to_string()));
+    }
+
+    sqlx::query(
+        r#"
+        UPDATE "Announcements"
+        SET title = COALESCE($1, title),
+            body = COALESCE($2, body),
+            pinned = COALESCE($3, pinned),
+            publish_at = $4,
+            expires_at = $5,
+            updated_at = NOW()
+        WHERE id = $6
+        "#,
+    )
+    .bind(input.title.as_deref().map(str::trim))
+    .bind(&input.body)
+    .bind(input.pinned)
+    .bind(publish_at)
+    .bind(expires_at)
+    .bind(announcement_id)
+    .execute(&state.db)
+    .await?;
+
+    Ok(Json(fetch_announcement(&state.db, auth.profile_id, announcement_id).await?))
+}
+
+/// DELETE /api/announcements/{id} - Remove an announcement
+#[utoipa::path(
+    delete,
+    path = "/api/announcements/{id}",
+    params(
+        ("id" = i32, Path, description = "Announcement ID")
+    ),
+    responses(
+        (status = 200, description = "Announcement deleted", body = AnnouncementMutationResponse),
+        (status = 403, description = "Not allowed to post to this announcement's audience"),
+        (status = 404, description = "Announcement not found")
+    ),
+    tag = "announcements",
+    security(("cookie_auth" = []))
+)]
+pub async fn delete_announcement(
+    State(state): State<Arc<AppState>>,
+    Path(announcement_id): Path<i32>,
+    auth: AuthenticatedUser,
+) -> AppResult<Json<AnnouncementMutationResponse>> {
+    let existing = fetch_announcement(&state.db, auth.profile_id, announcement_id).await?;
+    ensure_can_target(&state, &auth, existing.workplace_id, existing.role_id).await?;
+
+    sqlx::query(r#"DELETE FROM "Announcements" WHERE id = $1"#)
+        .bind(announcement_id)
+        .execute(&state.db)
+        .await?;
+
+    Ok(Json(AnnouncementMutationResponse {
+        success: true,
+        message: Some("Announcement deleted".to_string()),
+    }))
+}
+
+/// POST /api/announcements/{id}/read - Mark an announcement as read
+#[utoipa::path(
+    post,
+    path = "/api/announcements/{id}/read",
+    params(
+        ("id" = i32, Path, description = "Announcement ID")
+    ),
+    responses(
+        (status = 200, description = "Marked as read", body = AnnouncementMutationResponse),
+        (status = 404, description = "Announcement not found or not addressed to you")
+    ),
+    tag = "announcements",
+    security(("cookie_auth" = []))
+)]
+pub async fn mark_announcement_read(
+    State(state): State<Arc<AppState>>,
+    Path(announcement_id): Path<i32>,
+    auth: AuthenticatedUser,
+) -> AppResult<Json<AnnouncementMutationResponse>> {
+    let visible: bool = sqlx::query_scalar(&format!(
+        r#"SELECT EXISTS(SELECT 1 FROM "Announcements" a WHERE a.id = $2 AND {})"#,
+        visible_to_caller(3)
+    ))
+    .bind(auth.profile_id)
+    .bind(announcement_id)
+    .bind(permissions::today())
+    .fetch_one(&state.db)
+    .await?;
+
+    if !visible {
+        return Err(AppError::NotFound(format!("Announcement {} not found", announcement_id)));
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO "AnnouncementReads" (announcement_id, user_profile_id)
+        VALUES ($1, $2)
+        ON CONFLICT (announcement_id, user_profile_id) DO NOTHING
+        "#,
+    )
+    .bind(announcement_id)
+    .bind(auth.profile_id)
+    .execute(&state.db)
+    .await?;
+
+    Ok(Json(AnnouncementMutationResponse {
+        success: true,
+        message: None,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_role_announcements_need_can_edit_rota_in_the_role() {
+        assert!(check_target(false, true, None, Some(3)).is_ok());
+        assert!(check_target(false, true, Some(1), Some(3)).is_ok());
+        assert!(check_target(true, false, None, Some(3)).is_ok());
+        assert!(matches!(check_target(false, false, None, Some(3)), Err(AppError::Forbidden(_))));
+    }
+
+    #[test]
+    fn test_workplace_and_everyone_announcements_need_super_admin() {
+        assert!(check_target(true, false, Some(1), None).is_ok());
+        assert!(check_target(true, false, None, None).is_ok());
+        // can_edit_rota in some role does not reach a whole workplace or everyone
+        assert!(matches!(check_target(false, true, Some(1), None), Err(AppError::Forbidden(_))));
+        assert!(matches!(check_target(false, false, None, None), Err(AppError::Forbidden(_))));
+    }
+
+    #[test]
+    fn test_forbidden_message_names_the_missing_scope() {
+        let message = |workplace_id, role_id| match check_target(false, false, workplace_id, role_id) {
+            Err(AppError::Forbidden(message)) => message,
+            other => panic!("expected Forbidden, got {:?}", other),
+        };
+        assert_eq!(message(None, Some(3)), "can_edit_rota on the target role required");
+        assert_eq!(message(Some(1), None), "Super admin permission for workplace-wide announcements required");
+        assert_eq!(message(None, None), "Super admin permission for announcements to everyone required");
+    }
+}
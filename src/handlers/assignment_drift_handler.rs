@@ -0,0 +1,207 @@
+use axum::extract::{Path, Query, State};
+use chrono::NaiveDate;
+use serde::Deserialize;
+use std::sync::Arc;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use crate::{
+    extractors::{permissions, AuthenticatedUser, Json},
+    jobs::assignment_drift::find_assignment_drift,
+    models::{AssignmentDrift, AssignmentDriftFix, FixAssignmentDriftInput, FixAssignmentDriftResponse},
+    AppError, AppResult, AppState,
+};
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct AssignmentDriftQuery {
+    #[serde(rename = "roleId")]
+    pub role_id: Option<i32>,
+    /// First shift date to check (default today)
+    pub from: Option<NaiveDate>,
+}
+
+/// GET /api/roles/assignment-drift?roleId=&from= - Shifts assigned to users who cannot work shifts in the role
+#[utoipa::path(
+    get,
+    path = "/api/roles/assignment-drift",
+    params(AssignmentDriftQuery),
+    responses(
+        (status = 200, description = "Affected shifts per role and user, with the fixes available", body = Vec<AssignmentDrift>),
+        (status = 403, description = "Missing can_edit_rota permission (for this role)")
+    ),
+    tag = "roles",
+    security(("cookie_auth" = []))
+)]
+pub async fn get_assignment_drift(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+    Query(query): Query<AssignmentDriftQuery>,
+) -> AppResult<Json<Vec<AssignmentDrift>>> {
+    // Super admins see every role (None); rota admins their own, or one of them
+    let roles: Option<Vec<i32>> = if auth.is_super_admin {
+        query.role_id.map(|role_id| vec![role_id])
+    } else {
+        let admin_roles = permissions::roles_with_permission(&state, auth.profile_id, permissions::can_edit_rota).await?;
+        let roles = match query.role_id {
+            Some(role_id) if admin_roles.contains(&role_id) => vec![role_id],
+            Some(_) => return Err(AppError::Forbidden("Missing can_edit_rota permission for this role".to_string())),
+            None => admin_roles,
+        };
+        if roles.is_empty() {
+            return Err(AppError::Forbidden("Missing can_edit_rota permission".to_string()));
+        }
+        Some(roles)
+    };
+
+    let from = query.from.unwrap_or_else(|| chrono::Local::now().date_naive());
+    let drift = find_assignment_drift(&state.db, roles.as_deref(), from).await?;
+
+    Ok(Json(drift))
+}
+
+/// POST /api/roles/{id}/assignment-drift/fix - Unassign the user's shifts in the role, or give them the role back
+#[utoipa::path(
+    post,
+    path = "/api/roles/{id}/assignment-drift/fix",
+    params(
+        ("id" = i32, Path, description = "Role ID")
+    ),
+    request_body = FixAssignmentDriftInput,
+    responses(
+        (status = 200, description = "Shifts unassigned or role granted with can_work_shifts", body = FixAssignmentDriftResponse),
+        (status = 400, description = "The user can already work shifts in the role, or is a generic login (REGRANT)"),
+        (status = 403, description = "Missing can_edit_rota (UNASSIGN) or can_edit_staff (REGRANT) permission for this role"),
+        (status = 404, description = "User not found"),
+        (status = 409, description = "A shift to unassign falls in a month locked for payroll")
+    ),
+    tag = "roles",
+    security(("cookie_auth" = []))
+)]
+pub async fn fix_assignment_drift(
+    State(state): State<Arc<AppState>>,
+    Path(role_id): Path<i32>,
+    auth: AuthenticatedUser,
+    Json(input): Json<FixAssignmentDriftInput>,
+) -> AppResult<Json<FixAssignmentDriftResponse>> {
+    let allowed = match input.action {
+        AssignmentDriftFix::Unassign => {
+            permissions::has_permission(&state, auth.profile_id, auth.is_super_admin, |r| {
+                r.role_id == role_id && r.can_edit_rota
+            })
+            .await?
+        }
+        AssignmentDriftFix::Regrant => {
+            permissions::has_permission(&state, auth.profile_id, auth.is_super_admin, |r| {
+                r.role_id == role_id && r.can_edit_staff
+            })
+            .await?
+        }
+    };
+    if !allowed {
+        let permission = if input.action == AssignmentDriftFix::Unassign { "can_edit_rota" } else { "can_edit_staff" };
+        return Err(AppError::Forbidden(format!("Missing {} permission for this role", permission)));
+    }
+
+    let user: Option<(bool, Option<bool>)> = sqlx::query_as(&format!(
+        r#"
+        SELECT COALESCE(u.is_generic_login, false), ur.can_work_shifts
+        FROM "Users" u
+        LEFT JOIN "UserRoles" ur ON ur.user_profile_id = u.user_profile_id AND ur.role_id = $2 AND {ur_in_force}
+        WHERE u.user_profile_id = $1
+        "#,
+        ur_in_force = crate::extractors::permissions::ur_in_force(3)
+    ))
+    .bind(input.user_profile_id)
+    .bind(role_id)
+    .bind(crate::extractors::permissions::today())
+    .fetch_optional(&state.db)
+    .await?;
+    let (is_generic, can_work_shifts) =
+        user.ok_or_else(|| AppError::NotFound(format!("User {} not found", input.user_profile_id)))?;
+    if can_work_shifts == Some(true) {
+        return Err(AppError::BadRequest("User can already work shifts in this role".to_string()));
+    }
+
+    let mut tx = crate::db::begin_as_user(&state.db, auth.profile_id).await?;
+    let response = match input.action {
+        AssignmentDriftFix::Unassign => {
+            let from = input.from.unwrap_or_else(|| chrono::Local::now().date_naive());
+            let shift_ids: Vec<Uuid> = sqlx::query_scalar(
+                r#"SELECT uuid FROM "Shifts" WHERE role_id = $1 AND user_profile_id = $2 AND date >= $3"#,
+            )
+            .bind(role_id)
+            .bind(input.user_profile_id)
+            .bind(from)
+            .fetch_all(&mut *tx)
+            .await?;
+            crate::handlers::payroll_locks_handler::ensure_shifts_unlocked(&mut tx, &shift_ids).await?;
+
+            crate::db::set_change_reason(&mut tx, "Assignee no longer holds the role").await?;
+            let before = crate::handlers::shifts_handler::shifts_by_uuid(&mut tx, &shift_ids).await?;
+            let unassigned = sqlx::query(r#"UPDATE "Shifts" SET user_profile_id = NULL WHERE uuid = ANY($1)"#)
+                .bind(&shift_ids)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+            crate::handlers::shifts_handler::notify_shift_changes(&mut tx, &before, auth.profile_id).await?;
+
+            FixAssignmentDriftResponse { success: true, action: input.action, unassigned, user_role_id: None }
+        }
+        AssignmentDriftFix::Regrant => {
+            if is_generic {
+                return Err(AppError::BadRequest("Generic accounts cannot have can_work_shifts permission".to_string()));
+            }
+
+            let existing: Option<i32> = sqlx::query_scalar(
+                r#"UPDATE "UserRoles" SET can_work_shifts = true WHERE user_profile_id = $1 AND role_id = $2 RETURNING id"#,
+            )
+            .bind(input.user_profile_id)
+            .bind(role_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+            let user_role_id = match existing {
+                Some(id) => id,
+                None => {
+                    sqlx::query_scalar(
+                        r#"
+                        INSERT INTO "UserRoles" (
+                            role_id, user_profile_id, can_edit_rota, can_access_diary,
+                            can_work_shifts, can_edit_templates, can_edit_staff, can_view_staff_details,
+                            can_approve_rota, can_set_pay
+                        )
+                        VALUES ($1, $2, false, false, true, false, false, false, false, false)
+                        RETURNING id
+                        "#,
+                    )
+                    .bind(role_id)
+                    .bind(input.user_profile_id)
+                    .fetch_one(&mut *tx)
+                    .await?
+                }
+            };
+
+            FixAssignmentDriftResponse {
+                success: true,
+                action: input.action,
+                unassigned: 0,
+                user_role_id: Some(user_role_id),
+            }
+        }
+    };
+    tx.commit().await?;
+
+    match input.action {
+        AssignmentDriftFix::Unassign => crate::handlers::metrics::record_shift_event("updated"),
+        AssignmentDriftFix::Regrant => state.caches.invalidate_user_roles(input.user_profile_id).await,
+    }
+    tracing::info!(
+        role_id,
+        user_profile_id = input.user_profile_id,
+        action = ?input.action,
+        unassigned = response.unassigned,
+        admin_id = auth.profile_id,
+        "🧭 Assignment drift fixed"
+    );
+
+    Ok(Json(response))
+}
@@ -0,0 +1,541 @@
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap},
+};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use crate::{
+    config::AttachmentStorage,
+    extractors::{permissions, AuthenticatedUser, Json},
+    models::{Attachment, AttachmentMutationResponse},
+    storage, AppError, AppResult, AppState,
+};
+
+/// Upload size limit, enforced by the routes' body limit (413 beyond it)
+pub const MAX_ATTACHMENT_BYTES: usize = 5 * 1024 * 1024;
+const MAX_ATTACHMENTS_PER_ITEM: i64 = 10;
+const MAX_FILENAME_LENGTH: usize = 255;
+/// Download links are handed out with every listing, so they only need to last a short while
+const DOWNLOAD_URL_EXPIRY_SECS: u32 = 15 * 60;
+
+const ATTACHMENT_SELECT: &str = r#"
+    SELECT
+        a.id,
+        a.diary_id,
+        a.shift_id,
+        a.filename,
+        a.content_type,
+        a.size_bytes,
+        a.sha256,
+        a.scan_status,
+        a.uploaded_by,
+        u.short_name AS uploaded_by_short_name,
+        a.created_at,
+        a.storage_key
+    FROM "Attachments" a
+    LEFT JOIN "Users" u ON u.user_profile_id = a.uploaded_by
+"#;
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct UploadAttachmentQuery {
+    /// Original file name, shown in listings and used for downloads
+    pub filename: String,
+}
+
+/// Verdict from the ATTACHMENT_SCAN_URL hook
+#[derive(Debug, Deserialize)]
+struct ScanVerdict {
+    infected: bool,
+    signature: Option<String>,
+}
+
+/// What an attachment hangs off
+#[derive(Debug, Clone, Copy)]
+enum AttachmentTarget {
+    Diary(i32),
+    Shift(Uuid),
+}
+
+/// What the caller may do with a target's attachments (seeing them is checked on the way in)
+struct TargetAccess {
+    can_upload: bool,
+    /// Delete anyone's attachment, not just your own
+    can_manage: bool,
+}
+
+/// Diary attachments follow can_access_diary in the entry's role (announcements' are visible to all its members);
+/// shift attachments are visible to the role's members and added by its rota admins or the assignee.
+/// can_edit_rota admins manage both.
+async fn target_access(state: &AppState, auth: &AuthenticatedUser, target: AttachmentTarget) -> AppResult<TargetAccess> {
+    let (role_id, assignee, announcement): (i32, Option<i32>, bool) = match target {
+        AttachmentTarget::Diary(id) => {
+            sqlx::query_as(
+                r#"SELECT role_id, NULL::int4, entry_type = 'ANNOUNCEMENT' FROM "Diary" WHERE id = $1 AND NOT deleted"#,
+            )
+                .bind(id)
+                .fetch_optional(&state.db)
+                .await?
+                .ok_or_else(|| AppError::NotFound(format!("Diary entry {} not found", id)))?
+        }
+        AttachmentTarget::Shift(uuid) => {
+            sqlx::query_as(r#"SELECT role_id, user_profile_id, false FROM "Shifts" WHERE uuid = $1"#)
+                .bind(uuid)
+                .fetch_optional(&state.db)
+                .await?
+                .ok_or_else(|| AppError::NotFound(format!("Shift {} not found", uuid)))?
+        }
+    };
+
+    let diary_access = permissions::has_permission(state, auth.profile_id, auth.is_super_admin, |r| {
+        r.role_id == role_id && r.can_access_diary
+    })
+    .await?;
+    let can_view = match target {
+        AttachmentTarget::Diary(_) if !announcement => diary_access,
+        _ => permissions::has_permission(state, auth.profile_id, auth.is_super_admin, |r| r.role_id == role_id).await?,
+    };
+    if !can_view {
+        return Err(AppError::Forbidden("No access to this role's attachments".to_string()));
+    }
+
+    let can_manage = permissions::has_permission(state, auth.profile_id, auth.is_super_admin, |r| {
+        r.role_id == role_id && r.can_edit_rota
+    })
+    .await?;
+    let can_upload = match target {
+        AttachmentTarget::Diary(_) => diary_access,
+        AttachmentTarget::Shift(_) => can_manage || assignee == Some(auth.profile_id),
+    };
+
+    Ok(TargetAccess { can_upload, can_manage })
+}
+
+fn attachment_storage(state: &AppState) -> AppResult<&AttachmentStorage> {
+    state
+        .config
+        .attachments
+        .as_ref()
+        .ok_or_else(|| AppError::Internal("Attachment storage is not configured (ATTACHMENT_BUCKET_URL)".to_string()))
+}
+
+fn with_download_url(storage: &AttachmentStorage, mut attachment: Attachment) -> Attachment {
+    let disposition = format!("attachment; filename=\"{}\"", attachment.filename);
+    attachment.download_url = storage::presign(
+        storage,
+        "GET",
+        &attachment.storage_key,
+        &[("response-content-disposition", &disposition)],
+        DOWNLOAD_URL_EXPIRY_SECS,
+        chrono::Utc::now(),
+    );
+    attachment
+}
+
+async fn fetch_attachments(state: &AppState, target: AttachmentTarget) -> AppResult<Vec<Attachment>> {
+    let attachments = match target {
+        AttachmentTarget::Diary(id) => {
+            sqlx::query_as::<_, Attachment>(&format!("{} WHERE a.diary_id = $1 ORDER BY a.created_at, a.id", ATTACHMENT_SELECT))
+                .bind(id)
+                .fetch_all(&state.db)
+                .await?
+        }
+        AttachmentTarget::Shift(uuid) => {
+            sqlx::query_as::<_, Attachment>(&format!("{} WHERE a.shift_id = $1 ORDER BY a.created_at, a.id", ATTACHMENT_SELECT))
+                .bind(uuid)
+                .fetch_all(&state.db)
+                .await?
+        }
+    };
+    Ok(attachments)
+}
+
+/// Last path segment, without control characters or quotes since it ends up in Content-Disposition
+fn clean_filename(raw: &str) -> AppResult<String> {
+    let name: String = raw
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| !c.is_control() && *c != '"')
+        .collect();
+    let name = name.trim();
+    if name.is_empty() || name == "." || name == ".." {
+        return Err(AppError::BadRequest("filename is required".to_string()));
+    }
+    if name.chars().count() > MAX_FILENAME_LENGTH {
+        return Err(AppError::BadRequest(format!("filename must be at most {} characters", MAX_FILENAME_LENGTH)));
+    }
+    Ok(name.to_string())
+}
+
+/// Allowed type from the file's leading bytes; the declared Content-Type has to agree with it
+fn sniff_content_type(body: &[u8]) -> Option<&'static str> {
+    const SIGNATURES: &[(&[u8], &str)] = &[
+        (b"%PDF-", "application/pdf"),
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+    ];
+    if body.len() >= 12 && body.starts_with(b"RIFF") && &body[8..12] == b"WEBP" {
+        return Some("image/webp");
+    }
+    SIGNATURES.iter().find(|(magic, _)| body.starts_with(magic)).map(|(_, content_type)| *content_type)
+}
+
+/// Send the file to the scanning hook; anything but a clean verdict stops the upload
+async fn scan_for_viruses(scan_url: &str, content_type: &str, body: &Bytes) -> AppResult<()> {
+    let response = reqwest::Client::new()
+        .post(scan_url)
+        .header(reqwest::header::CONTENT_TYPE, content_type)
+        .body(body.clone())
+        .send()
+        .await
+        .map_err(|e| AppError::Internal(format!("Virus scan failed: {}", e)))?;
+    if !response.status().is_success() {
+        return Err(AppError::Internal(format!("Virus scan failed with status {}", response.status())));
+    }
+    let verdict: ScanVerdict = response
+        .json()
+        .await
+        .map_err(|e| AppError::Internal(format!("Virus scan returned an unreadable verdict: {}", e)))?;
+    if verdict.infected {
+        return Err(AppError::Validation(format!(
+            "File rejected by the virus scan{}",
+            verdict.signature.map(|s| format!(": {}", s)).unwrap_or_default()
+        )));
+    }
+    Ok(())
+}
+
+async fn list(state: &AppState, auth: &AuthenticatedUser, target: AttachmentTarget) -> AppResult<Vec<Attachment>> {
+    let storage = attachment_storage(state)?;
+    target_access(state, auth, target).await?;
+    let attachments = fetch_attachments(state, target).await?;
+    Ok(attachments.into_iter().map(|a| with_download_url(storage, a)).collect())
+}
+
+async fn upload(
+    state: &AppState,
+    auth: &AuthenticatedUser,
+    target: AttachmentTarget,
+    filename: &str,
+    headers: &HeaderMap,
+    body: Bytes,
+) -> AppResult<Attachment> {
+    let storage = attachment_storage(state)?;
+    let access = target_access(state, auth, target).await?;
+    if !access.can_upload {
+        return Err(AppError::Forbidden("Only rota admins and the assignee can attach files to a shift".to_string()));
+    }
+
+    let filename = clean_filename(filename)?;
+    if body.is_empty() {
+        return Err(AppError::BadRequest("The request body must be the file".to_string()));
+    }
+    let content_type = sniff_content_type(&body)
+        .ok_or_else(|| AppError::UnsupportedMediaType("Attachments must be PDF, PNG, JPEG, GIF or WebP files".to_string()))?;
+    let declared = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.split(';').next().unwrap_or_default().trim().to_ascii_lowercase());
+    if declared.as_deref() != Some(content_type) {
+        return Err(AppError::UnsupportedMediaType(format!(
+            "Content-Type must be {} for this file",
+            content_type
+        )));
+    }
+
+    let (existing,): (i64,) = match target {
+        AttachmentTarget::Diary(id) => {
+            sqlx::query_as(r#"SELECT COUNT(*) FROM "Attachments" WHERE diary_id = $1"#).bind(id).fetch_one(&state.db).await?
+        }
+        AttachmentTarget::Shift(uuid) => {
+            sqlx::query_as(r#"SELECT COUNT(*) FROM "Attachments" WHERE shift_id = $1"#).bind(uuid).fetch_one(&state.db).await?
+        }
+    };
+    if existing >= MAX_ATTACHMENTS_PER_ITEM {
+        return Err(AppError::Conflict(format!("At most {} attachments per item", MAX_ATTACHMENTS_PER_ITEM)));
+    }
+
+    let scan_status = match &storage.scan_url {
+        Some(scan_url) => {
+            scan_for_viruses(scan_url, content_type, &body).await?;
+            "CLEAN"
+        }
+        None => "NOT_SCANNED",
+    };
+
+    let (diary_id, shift_id, prefix) = match target {
+        AttachmentTarget::Diary(id) => (Some(id), None, format!("diary/{}", id)),
+        AttachmentTarget::Shift(uuid) => (None, Some(uuid), format!("shifts/{}", uuid)),
+    };
+    // The key never contains the user's file name
+    let storage_key = format!("{}/{}", prefix, Uuid::new_v4());
+    let sha256 = hex::encode(Sha256::digest(&body));
+    let size_bytes = body.len() as i32;
+
+    storage::put_object(storage, &storage_key, content_type, body.to_vec()).await?;
+
+    let id: Result<i32, sqlx::Error> = sqlx::query_scalar(
+        r#"
+        INSERT INTO "Attachments"
+            (diary_id, shift_id, storage_key, filename, content_type, size_bytes, sha256, scan_status, uploaded_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        RETURNING id
+        "#,
+    )
+    .bind(diary_id)
+    .bind(shift_id)
+    .bind(&storage_key)
+    .bind(&filename)
+    .bind(content_type)
+    .bind(size_bytes)
+    .bind(&sha256)
+    .bind(scan_status)
+    .bind(auth.profile_id)
+    .fetch_one(&state.db)
+    .await;
+    let id = match id {
+        Ok(id) => id,
+        Err(e) => {
+            // Don't leave an object nothing points at
+            if let Err(cleanup) = storage::delete_object(storage, &storage_key).await {
+                tracing::warn!(storage_key, error = %cleanup, "⚠️ Failed to remove orphaned attachment object");
+            }
+            return Err(e.into());
+        }
+    };
+
+    tracing::info!(attachment_id = id, ?target, content_type, size_bytes, scan_status, uploaded_by = auth.profile_id, "📎 Attachment uploaded");
+
+    let attachment = sqlx::query_as::<_, Attachment>(&format!("{} WHERE a.id = $1", ATTACHMENT_SELECT))
+        .bind(id)
+        .fetch_one(&state.db)
+        .await?;
+    Ok(with_download_url(storage, attachment))
+}
+
+async fn remove(
+    state: &AppState,
+    auth: &AuthenticatedUser,
+    target: AttachmentTarget,
+    attachment_id: i32,
+) -> AppResult<AttachmentMutationResponse> {
+    let storage = attachment_storage(state)?;
+    let access = target_access(state, auth, target).await?;
+
+    let attachment = fetch_attachments(state, target)
+        .await?
+        .into_iter()
+        .find(|a| a.id == attachment_id)
+        .ok_or_else(|| AppError::NotFound(format!("Attachment {} not found", attachment_id)))?;
+    if attachment.uploaded_by != auth.profile_id && !access.can_manage {
+        return Err(AppError::Forbidden("Only the uploader or a rota admin can delete this attachment".to_string()));
+    }
+
+    // Object first: if the bucket refuses, the row stays and the delete can be retried
+    storage::delete_object(storage, &attachment.storage_key).await?;
+    sqlx::query(r#"DELETE FROM "Attachments" WHERE id = $1"#)
+        .bind(attachment_id)
+        .execute(&state.db)
+        .await?;
+
+    tracing::info!(attachment_id, ?target, deleted_by = auth.profile_id, "🗑️ Attachment deleted");
+
+    Ok(AttachmentMutationResponse {
+        success: true,
+        message: Some("Attachment deleted successfully".to_string()),
+    })
+}
+
+/// GET /api/diary/{id}/attachments - Files attached to a diary entry, with download links
+#[utoipa::path(
+    get,
+    path = "/api/diary/{id}/attachments",
+    params(("id" = i32, Path, description = "Diary entry ID")),
+    responses(
+        (status = 200, description = "Attachments, oldest first, each with a presigned download_url valid for 15 minutes", body = Vec<Attachment>),
+        (status = 403, description = "Missing can_access_diary permission for the entry's role"),
+        (status = 404, description = "Diary entry not found"),
+        (status = 500, description = "Attachment storage is not configured")
+    ),
+    tag = "diary",
+    security(("cookie_auth" = []))
+)]
+pub async fn get_diary_attachments(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i32>,
+    auth: AuthenticatedUser,
+) -> AppResult<Json<Vec<Attachment>>> {
+    Ok(Json(list(&state, &auth, AttachmentTarget::Diary(id)).await?))
+}
+
+/// POST /api/diary/{id}/attachments?filename= - Attach a file (the raw request body) to a diary entry
+#[utoipa::path(
+    post,
+    path = "/api/diary/{id}/attachments",
+    params(("id" = i32, Path, description = "Diary entry ID"), UploadAttachmentQuery),
+    request_body(description = "The file itself, at most 5 MB", content(
+        (Vec<u8> = "application/pdf"),
+        (Vec<u8> = "image/png"),
+        (Vec<u8> = "image/jpeg"),
+        (Vec<u8> = "image/gif"),
+        (Vec<u8> = "image/webp")
+    )),
+    responses(
+        (status = 200, description = "Attachment stored", body = Attachment),
+        (status = 400, description = "Missing or overlong filename, or empty body"),
+        (status = 403, description = "Missing can_access_diary permission for the entry's role"),
+        (status = 404, description = "Diary entry not found"),
+        (status = 409, description = "The entry already has 10 attachments"),
+        (status = 413, description = "File larger than 5 MB"),
+        (status = 415, description = "Not a PDF or image, or Content-Type does not match the file"),
+        (status = 422, description = "Rejected by the virus scan"),
+        (status = 500, description = "Attachment storage is not configured, or storage or the scan failed")
+    ),
+    tag = "diary",
+    security(("cookie_auth" = []))
+)]
+pub async fn upload_diary_attachment(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i32>,
+    auth: AuthenticatedUser,
+    Query(query): Query<UploadAttachmentQuery>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> AppResult<Json<Attachment>> {
+    Ok(Json(upload(&state, &auth, AttachmentTarget::Diary(id), &query.filename, &headers, body).await?))
+}
+
+/// DELETE /api/diary/{id}/attachments/{attachment_id} - Remove a diary attachment and its file
+#[utoipa::path(
+    delete,
+    path = "/api/diary/{id}/attachments/{attachment_id}",
+    params(
+        ("id" = i32, Path, description = "Diary entry ID"),
+        ("attachment_id" = i32, Path, description = "Attachment ID")
+    ),
+    responses(
+        (status = 200, description = "Attachment deleted", body = AttachmentMutationResponse),
+        (status = 403, description = "Not the uploader or a can_edit_rota admin of the role"),
+        (status = 404, description = "Diary entry or attachment not found")
+    ),
+    tag = "diary",
+    security(("cookie_auth" = []))
+)]
+pub async fn delete_diary_attachment(
+    State(state): State<Arc<AppState>>,
+    Path((id, attachment_id)): Path<(i32, i32)>,
+    auth: AuthenticatedUser,
+) -> AppResult<Json<AttachmentMutationResponse>> {
+    Ok(Json(remove(&state, &auth, AttachmentTarget::Diary(id), attachment_id).await?))
+}
+
+/// GET /api/shifts/{uuid}/attachments - Files attached to a shift, with download links
+#[utoipa::path(
+    get,
+    path = "/api/shifts/{uuid}/attachments",
+    params(("uuid" = Uuid, Path, description = "Shift UUID")),
+    responses(
+        (status = 200, description = "Attachments, oldest first, each with a presigned download_url valid for 15 minutes", body = Vec<Attachment>),
+        (status = 403, description = "Not a member of the shift's role"),
+        (status = 404, description = "Shift not found"),
+        (status = 500, description = "Attachment storage is not configured")
+    ),
+    tag = "shifts",
+    security(("cookie_auth" = []))
+)]
+pub async fn get_shift_attachments(
+    State(state): State<Arc<AppState>>,
+    Path(uuid): Path<Uuid>,
+    auth: AuthenticatedUser,
+) -> AppResult<Json<Vec<Attachment>>> {
+    Ok(Json(list(&state, &auth, AttachmentTarget::Shift(uuid)).await?))
+}
+
+/// POST /api/shifts/{uuid}/attachments?filename= - Attach a file (the raw request body) to a shift
+#[utoipa::path(
+    post,
+    path = "/api/shifts/{uuid}/attachments",
+    params(("uuid" = Uuid, Path, description = "Shift UUID"), UploadAttachmentQuery),
+    request_body(description = "The file itself, at most 5 MB", content(
+        (Vec<u8> = "application/pdf"),
+        (Vec<u8> = "image/png"),
+        (Vec<u8> = "image/jpeg"),
+        (Vec<u8> = "image/gif"),
+        (Vec<u8> = "image/webp")
+    )),
+    responses(
+        (status = 200, description = "Attachment stored", body = Attachment),
+        (status = 400, description = "Missing or overlong filename, or empty body"),
+        (status = 403, description = "Not a can_edit_rota admin of the role or the shift's assignee"),
+        (status = 404, description = "Shift not found"),
+        (status = 409, description = "The shift already has 10 attachments"),
+        (status = 413, description = "File larger than 5 MB"),
+        (status = 415, description = "Not a PDF or image, or Content-Type does not match the file"),
+        (status = 422, description = "Rejected by the virus scan"),
+        (status = 500, description = "Attachment storage is not configured, or storage or the scan failed")
+    ),
+    tag = "shifts",
+    security(("cookie_auth" = []))
+)]
+pub async fn upload_shift_attachment(
+    State(state): State<Arc<AppState>>,
+    Path(uuid): Path<Uuid>,
+    auth: AuthenticatedUser,
+    Query(query): Query<UploadAttachmentQuery>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> AppResult<Json<Attachment>> {
+    Ok(Json(upload(&state, &auth, AttachmentTarget::Shift(uuid), &query.filename, &headers, body).await?))
+}
+
+/// DELETE /api/shifts/{uuid}/attachments/{attachment_id} - Remove a shift attachment and its file
+#[utoipa::path(
+    delete,
+    path = "/api/shifts/{uuid}/attachments/{attachment_id}",
+    params(
+        ("uuid" = Uuid, Path, description = "Shift UUID"),
+        ("attachment_id" = i32, Path, description = "Attachment ID")
+    ),
+    responses(
+        (status = 200, description = "Attachment deleted", body = AttachmentMutationResponse),
+        (status = 403, description = "Not the uploader or a can_edit_rota admin of the role"),
+        (status = 404, description = "Shift or attachment not found")
+    ),
+    tag = "shifts",
+    security(("cookie_auth" = []))
+)]
+pub async fn delete_shift_attachment(
+    State(state): State<Arc<AppState>>,
+    Path((uuid, attachment_id)): Path<(Uuid, i32)>,
+    auth: AuthenticatedUser,
+) -> AppResult<Json<AttachmentMutationResponse>> {
+    Ok(Json(remove(&state, &auth, AttachmentTarget::Shift(uuid), attachment_id).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_filename_and_sniff() {
+        assert_eq!(clean_filename("C:\\docs\\handover \"final\".pdf").unwrap(), "handover final.pdf");
+        assert_eq!(clean_filename("../../etc/passwd").unwrap(), "passwd");
+        assert!(clean_filename(" ").is_err());
+        assert!(clean_filename("a/..").is_err());
+        assert!(clean_filename(&"x".repeat(MAX_FILENAME_LENGTH + 1)).is_err());
+
+        assert_eq!(sniff_content_type(b"%PDF-1.7\n..."), Some("application/pdf"));
+        assert_eq!(sniff_content_type(b"\x89PNG\r\n\x1a\n\0\0"), Some("image/png"));
+        assert_eq!(sniff_content_type(b"RIFF\x10\0\0\0WEBPVP8 "), Some("image/webp"));
+        assert_eq!(sniff_content_type(b"MZ\x90\0"), None);
+        assert_eq!(sniff_content_type(b""), None);
+    }
+}
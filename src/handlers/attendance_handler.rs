@@ -0,0 +1,491 @@
+use axum::extract::{Path, Query, State};
+use chrono::{Duration, NaiveDate, NaiveDateTime, NaiveTime};
+use serde::Deserialize;
+use std::sync::Arc;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use crate::{
+    extractors::{permissions, AuthenticatedUser, Json},
+    handlers::{roles_handler::month_range, workplaces_handler::load_role_settings},
+    models::{
+        AttendanceDiscrepancy, AttendanceException, AttendanceLocationInput, AttendanceReport, ShiftAttendance,
+        WorkplaceSettings,
+    },
+    report::{Report, ReportFormat},
+    AppError, AppResult, AppState,
+};
+
+/// How long before the rostered start a user may check in
+const CHECK_IN_EARLY_MINUTES: i64 = 120;
+
+/// How long after the rostered end a user may still check out; later corrections go through a rota admin
+const CHECK_OUT_LATE_MINUTES: i64 = 240;
+
+/// Default gap between actual and rostered minutes before a shift counts as a discrepancy
+const DEFAULT_THRESHOLD_MINUTES: i64 = 15;
+const MAX_THRESHOLD_MINUTES: i64 = 24 * 60;
+
+const EARTH_RADIUS_M: f64 = 6_371_000.0;
+
+#[derive(sqlx::FromRow)]
+struct AttendanceShiftRow {
+    user_profile_id: Option<i32>,
+    role_id: i32,
+    date: NaiveDate,
+    start: Option<NaiveTime>,
+    end: Option<NaiveTime>,
+    published: bool,
+    is_time_off: bool,
+}
+
+/// Rostered start and end of a shift; an end at or before the start is the next day
+pub(crate) fn rostered_window(date: NaiveDate, start: NaiveTime, end: NaiveTime) -> (NaiveDateTime, NaiveDateTime) {
+    let start_at = date.and_time(start);
+    let mut end_at = date.and_time(end);
+    if end_at <= start_at {
+        end_at += Duration::days(1);
+    }
+    (start_at, end_at)
+}
+
+/// Refuse a check-out that comes too long after the shift ended to be the actual finishing time
+fn check_out_window(end_at: NaiveDateTime, now: NaiveDateTime) -> AppResult<()> {
+    if now > end_at + Duration::minutes(CHECK_OUT_LATE_MINUTES) {
+        return Err(AppError::BadRequest(format!(
+            "Check-out closed {} minutes after the shift ended",
+            CHECK_OUT_LATE_MINUTES
+        )));
+    }
+    Ok(())
+}
+
+/// Great-circle distance in metres between two points given in degrees
+fn distance_m(from: (f64, f64), to: (f64, f64)) -> f64 {
+    let (lat1, lat2) = (from.0.to_radians(), to.0.to_radians());
+    let d_lat = lat2 - lat1;
+    let d_lon = (to.1 - from.1).to_radians();
+    let a = (d_lat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (d_lon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_M * a.sqrt().asin()
+}
+
+/// Refuse a location outside the workplace's geofence (or a missing one when a geofence is set)
+fn check_geofence(settings: &WorkplaceSettings, location: &AttendanceLocationInput) -> AppResult<()> {
+    let (Some(centre_lat), Some(centre_lon)) = (settings.geofence_latitude, settings.geofence_longitude) else {
+        return Ok(());
+    };
+    if settings.geofence_radius_m <= 0 {
+        return Ok(());
+    }
+
+    let (Some(latitude), Some(longitude)) = (location.latitude, location.longitude) else {
+        return Err(AppError::BadRequest("latitude and longitude are required at this workplace".to_string()));
+    };
+    let distance = distance_m((centre_lat, centre_lon), (latitude, longitude));
+    if distance > f64::from(settings.geofence_radius_m) {
+        return Err(AppError::Forbidden(format!(
+            "Location is {:.0}m from the workplace; check-ins must be within {}m",
+            distance, settings.geofence_radius_m
+        )));
+    }
+    Ok(())
+}
+
+/// Load a shift and check the caller is its assignee and it can be attended
+async fn load_attendance_shift(
+    state: &AppState,
+    auth: &AuthenticatedUser,
+    shift_id: Uuid,
+) -> AppResult<(AttendanceShiftRow, (NaiveDateTime, NaiveDateTime))> {
+    let shift = sqlx::query_as::<_, AttendanceShiftRow>(
+        r#"
+        SELECT user_profile_id, role_id, date, start, "end", published,
+               time_off_category_id IS NOT NULL AS is_time_off
+        FROM "Shifts"
+        WHERE uuid = $1
+        "#,
+    )
+    .bind(shift_id)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Shift not found".to_string()))?;
+
+    if shift.user_profile_id != Some(auth.profile_id) {
+        return Err(AppError::Forbidden("Only the user assigned to the shift can check in or out".to_string()));
+    }
+    if !shift.published || shift.is_time_off {
+        return Err(AppError::BadRequest("Only published working shifts can be checked in to".to_string()));
+    }
+    let (Some(start), Some(end)) = (shift.start, shift.end) else {
+        return Err(AppError::BadRequest("Shift has no start and end times".to_string()));
+    };
+
+    let window = rostered_window(shift.date, start, end);
+    Ok((shift, window))
+}
+
+/// POST /api/shifts/{uuid}/check-in - Record the assigned user starting a shift
+#[utoipa::path(
+    post,
+    path = "/api/shifts/{uuid}/check-in",
+    params(("uuid" = Uuid, Path, description = "Shift UUID")),
+    request_body = AttendanceLocationInput,
+    responses(
+        (status = 200, description = "Checked in", body = ShiftAttendance),
+        (status = 400, description = "Shift not checkable, outside the check-in window, or location missing at a geofenced workplace"),
+        (status = 403, description = "Not the assigned user, or outside the workplace geofence"),
+        (status = 404, description = "Shift not found"),
+        (status = 409, description = "Already checked in")
+    ),
+    tag = "shifts",
+    security(("cookie_auth" = []))
+)]
+pub async fn check_in(
+    State(state): State<Arc<AppState>>,
+    Path(shift_id): Path<Uuid>,
+    auth: AuthenticatedUser,
+    Json(input): Json<AttendanceLocationInput>,
+) -> AppResult<Json<ShiftAttendance>> {
+    let (shift, (start_at, end_at)) = load_attendance_shift(&state, &auth, shift_id).await?;
+
+    let now = chrono::Local::now().naive_local();
+    if now < start_at - Duration::minutes(CHECK_IN_EARLY_MINUTES) {
+        return Err(AppError::BadRequest(format!(
+            "Check-in opens {} minutes before the shift starts",
+            CHECK_IN_EARLY_MINUTES
+        )));
+    }
+    if now >= end_at {
+        return Err(AppError::BadRequest("The shift has already ended".to_string()));
+    }
+
+    let settings = load_role_settings(&state.db, shift.role_id).await?;
+    check_geofence(&settings, &input)?;
+
+    let attendance = sqlx::query_as::<_, ShiftAttendance>(
+        r#"
+        INSERT INTO "ShiftAttendance" (shift_id, user_profile_id, actual_start, check_in_latitude, check_in_longitude)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (shift_id, user_profile_id) DO NOTHING
+        RETURNING shift_id, user_profile_id, actual_start, actual_end,
+                  check_in_latitude, check_in_longitude, check_out_latitude, check_out_longitude
+        "#,
+    )
+    .bind(shift_id)
+    .bind(auth.profile_id)
+    .bind(now)
+    .bind(input.latitude)
+    .bind(input.longitude)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::Conflict("Already checked in to this shift".to_string()))?;
+
+    tracing::info!(shift_id = %shift_id, user_id = auth.profile_id, "🕘 Checked in");
+
+    Ok(Json(attendance))
+}
+
+/// POST /api/shifts/{uuid}/check-out - Record the assigned user finishing a shift
+#[utoipa::path(
+    post,
+    path = "/api/shifts/{uuid}/check-out",
+    params(("uuid" = Uuid, Path, description = "Shift UUID")),
+    request_body = AttendanceLocationInput,
+    responses(
+        (status = 200, description = "Checked out", body = ShiftAttendance),
+        (status = 400, description = "Shift not checkable, more than 4 hours past its end, or location missing at a geofenced workplace"),
+        (status = 403, description = "Not the assigned user, or outside the workplace geofence"),
+        (status = 404, description = "Shift not found"),
+        (status = 409, description = "Not checked in, or already checked out")
+    ),
+    tag = "shifts",
+    security(("cookie_auth" = []))
+)]
+pub async fn check_out(
+    State(state): State<Arc<AppState>>,
+    Path(shift_id): Path<Uuid>,
+    auth: AuthenticatedUser,
+    Json(input): Json<AttendanceLocationInput>,
+) -> AppResult<Json<ShiftAttendance>> {
+    let (shift, (_, end_at)) = load_attendance_shift(&state, &auth, shift_id).await?;
+
+    let now = chrono::Local::now().naive_local();
+    check_out_window(end_at, now)?;
+
+    let settings = load_role_settings(&state.db, shift.role_id).await?;
+    check_geofence(&settings, &input)?;
+
+    let attendance = sqlx::query_as::<_, ShiftAttendance>(
+        r#"
+        UPDATE "ShiftAttendance"
+        SET actual_end = GREATEST($3, actual_start), check_out_latitude = $4, check_out_longitude = $5
+        WHERE shift_id = $1 AND user_profile_id = $2 AND actual_end IS NULL
+        RETURNING shift_id, user_profile_id, actual_start, actual_end,
+                  check_in_latitude, check_in_longitude, check_out_latitude, check_out_longitude
+        "#,
+    )
+    .bind(shift_id)
+    .bind(auth.profile_id)
+    .bind(now)
+    .bind(input.latitude)
+    .bind(input.longitude)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::Conflict("Not checked in to this shift, or already checked out".to_string()))?;
+
+    tracing::info!(shift_id = %shift_id, user_id = auth.profile_id, "🕔 Checked out");
+
+    Ok(Json(attendance))
+}
+
+/// Exception for a shift that has started, given its rostered window and actual times. Returns the
+/// exception and the actual minus rostered minutes when both actual times are known.
+fn attendance_exception(
+    rostered: (NaiveDateTime, NaiveDateTime),
+    actual_start: Option<NaiveDateTime>,
+    actual_end: Option<NaiveDateTime>,
+    now: NaiveDateTime,
+    threshold_minutes: i64,
+) -> Option<(AttendanceException, Option<i64>)> {
+    let (start, end) = rostered;
+    match (actual_start, actual_end) {
+        (None, _) if now >= end => Some((AttendanceException::NoCheckIn, None)),
+        (Some(_), None) if now >= end => Some((AttendanceException::NoCheckOut, None)),
+        (Some(actual_start), Some(actual_end)) => {
+            let difference = (actual_end - actual_start).num_minutes() - (end - start).num_minutes();
+            if difference < -threshold_minutes {
+                Some((AttendanceException::UnderHours, Some(difference)))
+            } else if difference > threshold_minutes {
+                Some((AttendanceException::OverHours, Some(difference)))
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+fn hours(minutes: i64) -> f64 {
+    (minutes as f64 / 60.0 * 100.0).round() / 100.0
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct AttendanceReportQuery {
+    pub year: i32,
+    /// 1-12
+    pub month: i32,
+    /// Minutes actual hours may differ from rostered before a shift is listed (default 15)
+    #[serde(rename = "thresholdMinutes")]
+    pub threshold_minutes: Option<i64>,
+}
+
+#[derive(sqlx::FromRow)]
+struct AttendedShiftRow {
+    uuid: Uuid,
+    date: NaiveDate,
+    label: String,
+    start: NaiveTime,
+    end: NaiveTime,
+    user_profile_id: i32,
+    user_name: Option<String>,
+    /// Checked in, but the shift is now someone else's
+    not_assigned: bool,
+    actual_start: Option<NaiveDateTime>,
+    actual_end: Option<NaiveDateTime>,
+}
+
+/// GET /api/roles/{id}/attendance?year=&month=&thresholdMinutes= - Shifts whose actual hours differ from the rota
+#[utoipa::path(
+    get,
+    path = "/api/roles/{id}/attendance",
+    params(
+        ("id" = i32, Path, description = "Role ID"),
+        AttendanceReportQuery
+    ),
+    responses(
+        (status = 200, description = "Missing check-ins and check-outs, and shifts worked short or long", content(
+            (AttendanceReport = "application/json"),
+            (String = "text/csv"),
+            (Vec<u8> = "application/pdf")
+        )),
+        (status = 400, description = "Invalid year, month or threshold"),
+        (status = 403, description = "Missing can_edit_rota permission for this role"),
+        (status = 406, description = "Accept names no supported format")
+    ),
+    tag = "roles",
+    security(("cookie_auth" = []))
+)]
+pub async fn get_attendance_report(
+    State(state): State<Arc<AppState>>,
+    Path(role_id): Path<i32>,
+    auth: AuthenticatedUser,
+    format: ReportFormat,
+    Query(query): Query<AttendanceReportQuery>,
+) -> AppResult<Report<AttendanceReport>> {
+    if !permissions::has_permission(&state, auth.profile_id, auth.is_super_admin, |r| {
+        r.role_id == role_id && r.can_edit_rota
+    })
+    .await?
+    {
+        return Err(AppError::Forbidden("Missing can_edit_rota permission for this role".to_string()));
+    }
+
+    let (from, to) = month_range(query.year, query.month)?;
+    let threshold_minutes = query.threshold_minutes.unwrap_or(DEFAULT_THRESHOLD_MINUTES);
+    if !(0..=MAX_THRESHOLD_MINUTES).contains(&threshold_minutes) {
+        return Err(AppError::BadRequest(format!(
+            "thresholdMinutes must be between 0 and {}",
+            MAX_THRESHOLD_MINUTES
+        )));
+    }
+
+    let rows = sqlx::query_as::<_, AttendedShiftRow>(
+        r#"
+        SELECT
+            s.uuid,
+            s.date,
+            s.label,
+            s.start,
+            s."end",
+            p.user_profile_id,
+            u.full_name AS user_name,
+            p.user_profile_id IS DISTINCT FROM s.user_profile_id AS not_assigned,
+            a.actual_start,
+            a.actual_end
+        FROM "Shifts" s
+        -- The assignee, plus anyone else who checked in before the shift was reassigned
+        CROSS JOIN LATERAL (
+            SELECT s.user_profile_id WHERE s.user_profile_id IS NOT NULL
+            UNION
+            SELECT sa.user_profile_id FROM "ShiftAttendance" sa WHERE sa.shift_id = s.uuid
+        ) p
+        LEFT JOIN "ShiftAttendance" a ON a.shift_id = s.uuid AND a.user_profile_id = p.user_profile_id
+        LEFT JOIN "Users" u ON u.user_profile_id = p.user_profile_id
+        WHERE s.role_id = $1
+          AND s.date BETWEEN $2 AND $3
+          AND s.published
+          AND s.time_off_category_id IS NULL
+          AND s.start IS NOT NULL
+          AND s."end" IS NOT NULL
+        ORDER BY s.date, s.start, p.user_profile_id
+        "#,
+    )
+    .bind(role_id)
+    .bind(from)
+    .bind(to)
+    .fetch_all(&state.db)
+    .await?;
+
+    let now = chrono::Local::now().naive_local();
+    let mut report = AttendanceReport {
+        role_id,
+        year: query.year,
+        month: query.month,
+        threshold_minutes,
+        shifts_checked: 0,
+        discrepancies: Vec::new(),
+    };
+
+    for row in rows {
+        let (rostered_start, rostered_end) = rostered_window(row.date, row.start, row.end);
+        if rostered_start > now {
+            continue;
+        }
+        report.shifts_checked += 1;
+
+        let exception = if row.not_assigned {
+            Some((AttendanceException::NotAssigned, None))
+        } else {
+            attendance_exception((rostered_start, rostered_end), row.actual_start, row.actual_end, now, threshold_minutes)
+        };
+        let Some((exception, difference)) = exception else {
+            continue;
+        };
+        let actual_minutes = row.actual_start.zip(row.actual_end).map(|(start, end)| (end - start).num_minutes());
+        report.discrepancies.push(AttendanceDiscrepancy {
+            shift_uuid: row.uuid,
+            date: row.date,
+            label: row.label,
+            user_profile_id: row.user_profile_id,
+            user_name: row.user_name,
+            exception,
+            rostered_start,
+            rostered_end,
+            rostered_hours: hours((rostered_end - rostered_start).num_minutes()),
+            actual_start: row.actual_start,
+            actual_end: row.actual_end,
+            actual_hours: actual_minutes.map(hours),
+            difference_hours: difference.map(hours),
+        });
+    }
+
+    let title = format!("Role {} attendance {}-{:02}", role_id, query.year, query.month);
+    Ok(Report::new(format, title, report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(day: u32, h: u32, m: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 6, day).unwrap().and_hms_opt(h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn test_rostered_window_crosses_midnight() {
+        let date = NaiveDate::from_ymd_opt(2025, 6, 2).unwrap();
+        let time = |h| NaiveTime::from_hms_opt(h, 0, 0).unwrap();
+        assert_eq!(rostered_window(date, time(9), time(17)), (at(2, 9, 0), at(2, 17, 0)));
+        assert_eq!(rostered_window(date, time(20), time(8)), (at(2, 20, 0), at(3, 8, 0)));
+    }
+
+    #[test]
+    fn test_check_out_window() {
+        let end = at(2, 17, 0);
+        assert!(check_out_window(end, at(2, 12, 0)).is_ok());
+        assert!(check_out_window(end, at(2, 21, 0)).is_ok());
+        assert!(check_out_window(end, at(2, 21, 1)).is_err());
+    }
+
+    #[test]
+    fn test_distance_and_geofence() {
+        // Roughly 111m per 0.001 degrees of latitude
+        let d = distance_m((51.5, -0.12), (51.501, -0.12));
+        assert!((d - 111.2).abs() < 1.0, "{}", d);
+
+        let settings = WorkplaceSettings {
+            geofence_latitude: Some(51.5),
+            geofence_longitude: Some(-0.12),
+            geofence_radius_m: 200,
+            ..WorkplaceSettings::defaults(1)
+        };
+        let location = |latitude, longitude| AttendanceLocationInput { latitude, longitude };
+        assert!(check_geofence(&settings, &location(Some(51.501), Some(-0.12))).is_ok());
+        assert!(check_geofence(&settings, &location(Some(51.51), Some(-0.12))).is_err());
+        assert!(check_geofence(&settings, &location(None, None)).is_err());
+        assert!(check_geofence(&WorkplaceSettings::defaults(1), &location(None, None)).is_ok());
+    }
+
+    #[test]
+    fn test_attendance_exception() {
+        let rostered = (at(2, 9, 0), at(2, 17, 0));
+        let during = at(2, 12, 0);
+        let after = at(3, 0, 0);
+
+        assert_eq!(attendance_exception(rostered, None, None, during, 15), None);
+        assert_eq!(attendance_exception(rostered, None, None, after, 15), Some((AttendanceException::NoCheckIn, None)));
+        assert_eq!(
+            attendance_exception(rostered, Some(at(2, 9, 0)), None, after, 15),
+            Some((AttendanceException::NoCheckOut, None))
+        );
+        assert_eq!(attendance_exception(rostered, Some(at(2, 9, 10)), Some(at(2, 17, 0)), after, 15), None);
+        assert_eq!(
+            attendance_exception(rostered, Some(at(2, 9, 0)), Some(at(2, 16, 0)), after, 15),
+            Some((AttendanceException::UnderHours, Some(-60)))
+        );
+        assert_eq!(
+            attendance_exception(rostered, Some(at(2, 8, 30)), Some(at(2, 17, 30)), after, 15),
+            Some((AttendanceException::OverHours, Some(60)))
+        );
+    }
+}
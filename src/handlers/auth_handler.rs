@@ -1,14 +1,68 @@
-use axum::{extract::State, Json};
+use axum::extract::State;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
 use std::sync::Arc;
 use utoipa::ToSchema;
 
-use crate::{extractors::AuthenticatedUser, models::User, AppResult, AppState};
+use crate::{
+    extractors::{AuthenticatedUser, Json},
+    handlers::workplaces_handler::pin_policy_for_user,
+    models::{PinPolicy, User},
+    AppResult, AppState,
+};
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ToSchema)]
 pub struct UserResponse {
     #[serde(flatten)]
     user: User,
+    /// The PIN was set by an admin or has expired; prompt for a new one
+    must_change_pin: bool,
+    pin_policy: PinPolicy,
+    /// Give-away offer notifications are switched off
+    marketplace_offers_opt_out: bool,
+}
+
+/// "Users" columns deciding whether the PIN must be changed
+#[derive(Debug, Clone, FromRow)]
+struct PinStatus {
+    has_pin: bool,
+    is_generic_login: bool,
+    pin_must_change: bool,
+    pin_changed_at: Option<DateTime<Utc>>,
+}
+
+impl PinStatus {
+    /// Whether the user has to replace their PIN before relying on it. Generic accounts never do (they cannot
+    /// change their own PIN), nor do users without one.
+    fn change_required(&self, policy: &PinPolicy, now: DateTime<Utc>) -> bool {
+        self.has_pin && !self.is_generic_login && (self.pin_must_change || policy.is_expired(self.pin_changed_at, now))
+    }
+}
+
+/// The caller's profile row with the columns /me adds to it, read in one query
+#[derive(FromRow)]
+struct MeRow {
+    #[sqlx(flatten)]
+    user: User,
+    #[sqlx(flatten)]
+    pin: PinStatus,
+    marketplace_offers_opt_out: bool,
+}
+
+async fn pin_change_required(db: &sqlx::PgPool, user_profile_id: i32, policy: &PinPolicy) -> AppResult<bool> {
+    let status: Option<PinStatus> = sqlx::query_as(
+        r#"
+        SELECT auth_pin IS NOT NULL AS has_pin, is_generic_login, pin_must_change, pin_changed_at
+        FROM "Users"
+        WHERE user_profile_id = $1
+        "#,
+    )
+    .bind(user_profile_id)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(status.is_some_and(|status| status.change_required(policy, Utc::now())))
 }
 
 /// GET /api/auth/me
@@ -16,7 +70,7 @@ pub struct UserResponse {
     get,
     path = "/api/auth/me",
     responses(
-        (status = 200, description = "Current authenticated user", body = User),
+        (status = 200, description = "Current authenticated user, with their PIN policy and whether the PIN must be changed", body = UserResponse),
         (status = 401, description = "Unauthorized")
     ),
     tag = "auth",
@@ -27,13 +81,22 @@ pub struct UserResponse {
 pub async fn get_me(
     State(state): State<Arc<AppState>>,
     auth: AuthenticatedUser,
-) -> AppResult<Json<User>> {
-    let user = sqlx::query_as::<_, User>(r#"SELECT * FROM "Users" WHERE user_profile_id = $1"#)
-        .bind(auth.profile_id)
-        .fetch_one(&state.db)
-        .await?;
+) -> AppResult<Json<UserResponse>> {
+    let me = sqlx::query_as::<_, MeRow>(
+        r#"SELECT *, auth_pin IS NOT NULL AS has_pin FROM "Users" WHERE user_profile_id = $1"#,
+    )
+    .bind(auth.profile_id)
+    .fetch_one(&state.db)
+    .await?;
 
-    Ok(Json(user))
+    let pin_policy = pin_policy_for_user(&state.db, auth.profile_id).await?;
+
+    Ok(Json(UserResponse {
+        must_change_pin: me.pin.change_required(&pin_policy, Utc::now()),
+        user: me.user,
+        pin_policy,
+        marketplace_offers_opt_out: me.marketplace_offers_opt_out,
+    }))
 }
 
 #[derive(Debug, Deserialize, ToSchema)]
@@ -45,6 +108,8 @@ pub struct VerifyPinRequest {
 #[derive(Debug, Serialize, ToSchema)]
 pub struct VerifyPinResponse {
     pub valid: bool,
+    /// Only set for a valid PIN: it was set by an admin or has expired
+    pub must_change_pin: bool,
 }
 
 /// POST /api/auth/verify-pin
@@ -75,5 +140,38 @@ pub async fn verify_pin(
         None => false,
     };
 
-    Ok(Json(VerifyPinResponse { valid }))
+    let must_change_pin = if valid {
+        let policy = pin_policy_for_user(&state.db, payload.user_profile_id).await?;
+        pin_change_required(&state.db, payload.user_profile_id, &policy).await?
+    } else {
+        false
+    };
+
+    Ok(Json(VerifyPinResponse { valid, must_change_pin }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pin_change_required() {
+        let now = Utc::now();
+        let expiring = PinPolicy { expiry_days: 90, ..PinPolicy::default() };
+        let status = PinStatus {
+            has_pin: true,
+            is_generic_login: false,
+            pin_must_change: false,
+            pin_changed_at: Some(now - chrono::Duration::days(30)),
+        };
+
+        assert!(!status.change_required(&expiring, now));
+        assert!(PinStatus { pin_must_change: true, ..status.clone() }.change_required(&expiring, now));
+        assert!(PinStatus { pin_changed_at: Some(now - chrono::Duration::days(90)), ..status.clone() }.change_required(&expiring, now));
+        assert!(!PinStatus { pin_changed_at: None, ..status.clone() }.change_required(&PinPolicy::default(), now));
+
+        // Generic accounts and users without a PIN are never asked
+        assert!(!PinStatus { is_generic_login: true, pin_must_change: true, ..status.clone() }.change_required(&expiring, now));
+        assert!(!PinStatus { has_pin: false, pin_must_change: true, ..status }.change_required(&expiring, now));
+    }
 }
@@ -0,0 +1,363 @@
+use axum::{
+    extract::{Path, Query, State},
+};
+use chrono::NaiveDate;
+use serde::Deserialize;
+use std::sync::Arc;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use crate::{
+    extractors::{permissions, AuthenticatedUser, Json},
+    handlers::{
+        marketplace_handler::fetch_shift_request_with_details,
+        marketplace_sla_handler::{record_transition, record_transitions, RequestTransition},
+        workplaces_handler::load_role_settings,
+    },
+    models::{
+        CoverShift, SetNeedsCoverInput, ShiftMutationResponse, ShiftRequestStatus, ShiftRequestType,
+        ShiftRequestWithDetails, VolunteerForCoverInput,
+    },
+    AppError, AppResult, AppState,
+};
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct GetCoverBoardQuery {
+    #[serde(rename = "roleId")]
+    pub role_id: i32,
+}
+
+/// Whether a shift belongs on the cover board: published, not time off, not in the past, and either
+/// flagged by an admin or unassigned within the workplace's cover window
+fn is_on_cover_board(
+    published: bool,
+    is_time_off: bool,
+    assigned: bool,
+    needs_cover: bool,
+    date: NaiveDate,
+    today: NaiveDate,
+    window_days: i16,
+) -> bool {
+    let in_window = (date - today).num_days() < i64::from(window_days);
+    published && !is_time_off && date >= today && (needs_cover || (!assigned && in_window))
+}
+
+/// GET /api/cover-board?roleId= - Shifts in a role that need someone to cover them
+#[utoipa::path(
+    get,
+    path = "/api/cover-board",
+    params(GetCoverBoardQuery),
+    responses(
+        (status = 200, description = "Flagged shifts and upcoming unassigned published shifts, soonest first", body = Vec<CoverShift>),
+        (status = 403, description = "Not a member of the role")
+    ),
+    tag = "cover-board",
+    security(("cookie_auth" = []))
+)]
+pub async fn get_cover_board(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+    Query(query): Query<GetCoverBoardQuery>,
+) -> AppResult<Json<Vec<CoverShift>>> {
+    let role_id = query.role_id;
+    if !permissions::has_permission(&state, auth.profile_id, auth.is_super_admin, |r| r.role_id == role_id).await? {
+        return Err(AppError::Forbidden("Not a member of this role".to_string()));
+    }
+
+    let settings = load_role_settings(&state.db, role_id).await?;
+    let today = chrono::Local::now().date_naive();
+
+    // Same rule as is_on_cover_board
+    let shifts = sqlx::query_as::<_, CoverShift>(
+        r#"
+        SELECT
+            s.uuid,
+            s.role_id,
+            s.date,
+            to_char(s.start, 'HH24:MI') AS start_time,
+            to_char(s."end", 'HH24:MI') AS end_time,
+            s.label,
+            s.user_profile_id,
+            u.short_name AS user_short_name,
+            s.needs_cover,
+            COUNT(sr.id) AS volunteer_count,
+            MAX(sr.id) FILTER (WHERE sr.requester_id = $2) AS my_request_id
+        FROM "Shifts" s
+        LEFT JOIN "Users" u ON s.user_profile_id = u.user_profile_id
+        LEFT JOIN "ShiftRequests" sr ON sr.shift_id = s.uuid
+            AND sr.type = 'PICKUP' AND sr.status = 'PENDING_APPROVAL'
+        WHERE s.role_id = $1
+          AND s.published
+          AND s.time_off_category_id IS NULL
+          AND s.date >= $3
+          AND (s.needs_cover OR (s.user_profile_id IS NULL AND s.date < $3 + $4::int4))
+        GROUP BY s.uuid, s.role_id, s.date, s.start, s."end", s.label, s.user_profile_id, u.short_name, s.needs_cover
+        ORDER BY s.date, s.start
+        "#,
+    )
+    .bind(role_id)
+    .bind(auth.profile_id)
+    .bind(today)
+    .bind(i32::from(settings.cover_window_days))
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(shifts))
+}
+
+/// PUT /api/cover-board/{uuid} - Flag a shift as needing cover, or clear the flag
+#[utoipa::path(
+    put,
+    path = "/api/cover-board/{uuid}",
+    params(
+        ("uuid" = Uuid, Path, description = "Shift UUID")
+    ),
+    request_body = SetNeedsCoverInput,
+    responses(
+        (status = 200, description = "Flag updated", body = ShiftMutationResponse),
+        (status = 403, description = "Missing can_edit_rota permission on the shift's role"),
+        (status = 404, description = "Shift not found")
+    ),
+    tag = "cover-board",
+    security(("cookie_auth" = []))
+)]
+pub async fn set_needs_cover(
+    State(state): State<Arc<AppState>>,
+    Path(shift_id): Path<Uuid>,
+    auth: AuthenticatedUser,
+    Json(input): Json<SetNeedsCoverInput>,
+) -> AppResult<Json<ShiftMutationResponse>> {
+    let role_id: i32 = sqlx::query_scalar(r#"SELECT role_id FROM "Shifts" WHERE uuid = $1"#)
+        .bind(shift_id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Shift {} not found", shift_id)))?;
+
+    if !permissions::has_permission(&state, auth.profile_id, auth.is_super_admin, |r| {
+        r.role_id == role_id && r.can_edit_rota
+    })
+    .await?
+    {
+        return Err(AppError::Forbidden("Missing can_edit_rota permission".to_string()));
+    }
+
+    let mut tx = crate::db::begin_as_user(&state.db, auth.profile_id).await?;
+    crate::handlers::payroll_locks_handler::ensure_shifts_unlocked(&mut tx, &[shift_id]).await?;
+    sqlx::query(r#"UPDATE "Shifts" SET needs_cover = $1 WHERE uuid = $2"#)
+        .bind(input.needs_cover)
+        .bind(shift_id)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    tracing::info!(shift_id = %shift_id, needs_cover = input.needs_cover, admin_id = auth.profile_id, "🆘 Shift cover flag updated");
+
+    Ok(Json(ShiftMutationResponse {
+        success: true,
+        shift_uuid: Some(shift_id),
+        message: Some(if input.needs_cover { "Shift needs cover" } else { "Cover flag cleared" }.to_string()),
+    }))
+}
+
+/// POST /api/cover-board/{uuid}/volunteer - Offer to cover a shift (creates a PICKUP request pending admin approval)
+#[utoipa::path(
+    post,
+    path = "/api/cover-board/{uuid}/volunteer",
+    params(
+        ("uuid" = Uuid, Path, description = "Shift UUID")
+    ),
+    request_body = VolunteerForCoverInput,
+    responses(
+        (status = 200, description = "Volunteer request created, waiting for admin approval", body = ShiftRequestWithDetails),
+        (status = 400, description = "Shift is not on the cover board, or is already yours"),
+        (status = 403, description = "Volunteer cannot work shifts in this role, or the marketplace is disabled"),
+        (status = 404, description = "Shift not found"),
+        (status = 409, description = "Already volunteered for this shift")
+    ),
+    tag = "cover-board",
+    security(("cookie_auth" = []))
+)]
+pub async fn volunteer_for_cover(
+    State(state): State<Arc<AppState>>,
+    Path(shift_id): Path<Uuid>,
+    auth: AuthenticatedUser,
+    Json(input): Json<VolunteerForCoverInput>,
+) -> AppResult<Json<ShiftRequestWithDetails>> {
+    // The signed-in account volunteers; a confirmed volunteer (generic account flow) is only logged
+    let volunteer_id = auth.profile_id;
+
+    #[derive(sqlx::FromRow)]
+    struct CoverCandidate {
+        role_id: i32,
+        date: NaiveDate,
+        published: bool,
+        time_off_category_id: Option<i32>,
+        user_profile_id: Option<i32>,
+        needs_cover: bool,
+    }
+
+    let mut tx = crate::db::begin_as_user(&state.db, auth.profile_id).await?;
+
+    let shift = sqlx::query_as::<_, CoverCandidate>(
+        r#"
+        SELECT role_id, date, published, time_off_category_id, user_profile_id, needs_cover
+        FROM "Shifts"
+        WHERE uuid = $1
+        FOR UPDATE
+        "#,
+    )
+    .bind(shift_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Shift {} not found", shift_id)))?;
+    crate::handlers::payroll_locks_handler::ensure_unlocked(&mut tx, shift.role_id, shift.date).await?;
+
+    let role_id = shift.role_id;
+    if !permissions::has_permission(&state, volunteer_id, false, |r| r.role_id == role_id && r.can_work_shifts).await? {
+        return Err(AppError::Forbidden("Volunteer cannot work shifts in this role".to_string()));
+    }
+
+    let settings = load_role_settings(&state.db, role_id).await?;
+    if !settings.marketplace_enabled {
+        return Err(AppError::MarketplaceDisabled);
+    }
+
+    let today = chrono::Local::now().date_naive();
+    if !is_on_cover_board(
+        shift.published,
+        shift.time_off_category_id.is_some(),
+        shift.user_profile_id.is_some(),
+        shift.needs_cover,
+        shift.date,
+        today,
+        settings.cover_window_days,
+    ) {
+        return Err(AppError::BadRequest("Shift does not need cover".to_string()));
+    }
+    if shift.user_profile_id == Some(volunteer_id) {
+        return Err(AppError::BadRequest("You are already assigned to this shift".to_string()));
+    }
+
+    let already_volunteered: bool = sqlx::query_scalar(
+        r#"
+        SELECT EXISTS (
+            SELECT 1 FROM "ShiftRequests"
+            WHERE shift_id = $1 AND requester_id = $2 AND type = 'PICKUP' AND status = 'PENDING_APPROVAL'
+        )
+        "#,
+    )
+    .bind(shift_id)
+    .bind(volunteer_id)
+    .fetch_one(&mut *tx)
+    .await?;
+    if already_volunteered {
+        return Err(AppError::Conflict("You have already volunteered for this shift".to_string()));
+    }
+
+    // The volunteer is both requester and candidate, so the usual admin decision assigns them the shift
+    let request_id: i32 = sqlx::query_scalar(
+        r#"
+        INSERT INTO "ShiftRequests" (shift_id, requester_id, candidate_id, type, status, notes)
+        VALUES ($1, $2, $2, $3, $4, $5)
+        RETURNING id
+        "#,
+    )
+    .bind(shift_id)
+    .bind(volunteer_id)
+    .bind(ShiftRequestType::Pickup)
+    .bind(ShiftRequestStatus::PendingApproval)
+    .bind(&input.notes)
+    .fetch_one(&mut *tx)
+    .await?;
+    let transition =
+        record_transition(&mut *tx, request_id, None, ShiftRequestStatus::PendingApproval, volunteer_id).await?;
+
+    tx.commit().await?;
+
+    tracing::info!(
+        request_id,
+        shift_id = %shift_id,
+        volunteer_id,
+        confirmed_volunteer_id = ?input.confirmed_volunteer_id,
+        "🙋 Volunteered to cover shift"
+    );
+    crate::handlers::metrics::record_marketplace_event("cover_volunteered");
+    transition.emit();
+
+    let request = fetch_shift_request_with_details(&state.db, request_id).await?;
+
+    Ok(Json(request))
+}
+
+/// After a volunteer's PICKUP request is approved: clear the flag and turn down the other volunteers.
+/// Returns their transitions to emit once the approval commits.
+pub async fn close_cover(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    shift_id: Uuid,
+    approved_request_id: i32,
+    admin_id: i32,
+) -> AppResult<Vec<RequestTransition>> {
+    sqlx::query(r#"UPDATE "Shifts" SET needs_cover = false WHERE uuid = $1"#)
+        .bind(shift_id)
+        .execute(&mut **tx)
+        .await?;
+
+    let turned_down: Vec<(i32, i32)> = sqlx::query_as(
+        r#"
+        UPDATE "ShiftRequests"
+        SET status = $1, resolved_by = $2, resolved_at = NOW(), updated_at = NOW()
+        WHERE shift_id = $3 AND id <> $4 AND type = 'PICKUP' AND status = 'PENDING_APPROVAL'
+        RETURNING id, requester_id
+        "#,
+    )
+    .bind(ShiftRequestStatus::Rejected)
+    .bind(admin_id)
+    .bind(shift_id)
+    .bind(approved_request_id)
+    .fetch_all(&mut **tx)
+    .await?;
+
+    for (request_id, volunteer_id) in &turned_down {
+        crate::handlers::notifications_handler::notify(
+            &mut **tx,
+            *volunteer_id,
+            "COVER_FILLED",
+            "Thanks for volunteering: that shift has been covered by someone else.",
+            serde_json::json!({ "shift_request_id": request_id, "shift_id": shift_id }),
+        )
+        .await?;
+    }
+
+    let request_ids: Vec<i32> = turned_down.iter().map(|(request_id, _)| *request_id).collect();
+    let transitions = record_transitions(
+        &mut **tx,
+        &request_ids,
+        Some(ShiftRequestStatus::PendingApproval),
+        ShiftRequestStatus::Rejected,
+        admin_id,
+    )
+    .await?;
+
+    Ok(transitions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_on_cover_board() {
+        let today = NaiveDate::from_ymd_opt(2025, 5, 1).unwrap();
+        let in_days = |n| today + chrono::Duration::days(n);
+
+        // Unassigned: only inside the window
+        assert!(is_on_cover_board(true, false, false, false, in_days(6), today, 7));
+        assert!(!is_on_cover_board(true, false, false, false, in_days(7), today, 7));
+        // Flagged: any future date, even when assigned or the window is off
+        assert!(is_on_cover_board(true, false, true, true, in_days(30), today, 0));
+        // Never drafts, time off or past shifts
+        assert!(!is_on_cover_board(false, false, false, true, in_days(1), today, 7));
+        assert!(!is_on_cover_board(true, true, false, true, in_days(1), today, 7));
+        assert!(!is_on_cover_board(true, false, false, true, in_days(-1), today, 7));
+    }
+}
@@ -0,0 +1,336 @@
+use axum::extract::{Path, State};
+use serde_json::Value;
+use sqlx::FromRow;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::{
+    error::FieldError,
+    extractors::{permissions, AuthenticatedUser, CanEditStaff, Json, RequirePermission},
+    handlers::workplaces_handler::ensure_workplace_exists,
+    models::{CreateCustomFieldInput, CustomFieldDefinition, CustomFieldValue, SuccessResponse, UpdateCustomFieldInput},
+    AppError, AppResult, AppState,
+};
+
+const MAX_LABEL_LENGTH: usize = 100;
+
+fn validate_key(key: &str) -> AppResult<()> {
+    let mut chars = key.chars();
+    let valid = (1..=50).contains(&key.len())
+        && chars.next().is_some_and(|c| c.is_ascii_lowercase())
+        && chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if !valid {
+        return Err(AppError::BadRequest(
+            "key must be 1-50 lowercase letters, digits or underscores, starting with a letter".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_label(label: &str) -> AppResult<()> {
+    let length = label.trim().chars().count();
+    if length == 0 || length > MAX_LABEL_LENGTH {
+        return Err(AppError::BadRequest(format!("label must be 1-{} characters", MAX_LABEL_LENGTH)));
+    }
+    Ok(())
+}
+
+/// Fields are managed by super admins and by anyone who can edit staff in one of the workplace's roles
+async fn ensure_can_manage(state: &AppState, auth: &AuthenticatedUser, workplace_id: i32) -> AppResult<()> {
+    if auth.is_super_admin {
+        return Ok(());
+    }
+
+    let role_ids: Vec<i32> = sqlx::query_scalar(r#"SELECT id::int4 FROM "Roles" WHERE workplace_id = $1"#)
+        .bind(workplace_id)
+        .fetch_all(&state.db)
+        .await?;
+
+    if !permissions::has_permission(state, auth.profile_id, false, |r| {
+        r.can_edit_staff && role_ids.contains(&r.role_id)
+    })
+    .await?
+    {
+        return Err(AppError::Forbidden(
+            "Missing can_edit_staff permission in this workplace".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+#[derive(FromRow)]
+struct UserCustomFieldRow {
+    user_profile_id: i32,
+    #[sqlx(flatten)]
+    field: CustomFieldValue,
+}
+
+/// Custom field values held by these users, with their definitions, as (user_profile_id, value) pairs
+pub async fn custom_field_values(db: &sqlx::PgPool, user_ids: &[i32]) -> AppResult<Vec<(i32, CustomFieldValue)>> {
+    let rows = sqlx::query_as::<_, UserCustomFieldRow>(
+        r#"
+        SELECT v.user_profile_id, d.id AS definition_id, d.workplace_id, d.key, d.label, d.field_type, v.value
+        FROM "UserCustomFieldValues" v
+        INNER JOIN "CustomFieldDefinitions" d ON d.id = v.definition_id
+        WHERE v.user_profile_id = ANY($1)
+        ORDER BY v.user_profile_id, d.workplace_id, d.key
+        "#,
+    )
+    .bind(user_ids)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows.into_iter().map(|row| (row.user_profile_id, row.field)).collect())
+}
+
+/// Set or clear (null) a user's custom field values, keyed by definition id. Values must match their field's
+/// type, and the editor must be able to manage every workplace the fields belong to.
+pub async fn set_custom_field_values(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    state: &AppState,
+    auth: &AuthenticatedUser,
+    user_id: i32,
+    values: &BTreeMap<i32, Value>,
+) -> AppResult<()> {
+    let ids: Vec<i32> = values.keys().copied().collect();
+    let definitions = sqlx::query_as::<_, CustomFieldDefinition>(
+        r#"SELECT * FROM "CustomFieldDefinitions" WHERE id = ANY($1)"#,
+    )
+    .bind(&ids)
+    .fetch_all(&mut **tx)
+    .await?;
+
+    let mut errors = Vec::new();
+    for (id, value) in values {
+        let field = format!("custom_fields.{}", id);
+        match definitions.iter().find(|d| d.id == *id) {
+            None => errors.push(FieldError {
+                field,
+                code: "UNKNOWN_FIELD",
+                message: format!("No custom field with id {}", id),
+            }),
+            Some(_) if value.is_null() => {}
+            Some(definition) => {
+                if let Err(problem) = definition.field_type.check(value) {
+                    errors.push(FieldError {
+                        field: format!("custom_fields.{}", definition.key),
+                        code: "INVALID_TYPE",
+                        message: format!("{} {}", definition.label, problem),
+                    });
+                }
+            }
+        }
+    }
+    if !errors.is_empty() {
+        return Err(AppError::InvalidFields { message: "Invalid custom field values".to_string(), errors });
+    }
+
+    let mut workplace_ids: Vec<i32> = definitions.iter().map(|d| d.workplace_id).collect();
+    workplace_ids.sort_unstable();
+    workplace_ids.dedup();
+    for workplace_id in workplace_ids {
+        ensure_can_manage(state, auth, workplace_id).await?;
+    }
+
+    let (cleared, set): (Vec<_>, Vec<_>) = values.iter().partition(|(_, value)| value.is_null());
+    let cleared: Vec<i32> = cleared.into_iter().map(|(id, _)| *id).collect();
+    let (set_ids, set_values): (Vec<i32>, Vec<Value>) = set.into_iter().map(|(id, value)| (*id, value.clone())).unzip();
+
+    sqlx::query(r#"DELETE FROM "UserCustomFieldValues" WHERE user_profile_id = $1 AND definition_id = ANY($2)"#)
+        .bind(user_id)
+        .bind(&cleared)
+        .execute(&mut **tx)
+        .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO "UserCustomFieldValues" (user_profile_id, definition_id, value)
+        SELECT $1, * FROM UNNEST($2::int4[], $3::jsonb[])
+        ON CONFLICT (user_profile_id, definition_id) DO UPDATE SET value = EXCLUDED.value, updated_at = NOW()
+        "#,
+    )
+    .bind(user_id)
+    .bind(&set_ids)
+    .bind(&set_values)
+    .execute(&mut **tx)
+    .await?;
+
+    tracing::info!(user_id, admin_id = auth.profile_id, set = set_ids.len(), cleared = cleared.len(), "🏷️ Custom field values updated");
+
+    Ok(())
+}
+
+/// GET /api/workplaces/{id}/custom-fields - Profile fields this workplace tracks for its staff
+#[utoipa::path(
+    get,
+    path = "/api/workplaces/{id}/custom-fields",
+    params(
+        ("id" = i32, Path, description = "Workplace ID")
+    ),
+    responses(
+        (status = 200, description = "Field definitions ordered by key", body = Vec<CustomFieldDefinition>),
+        (status = 404, description = "Workplace not found")
+    ),
+    tag = "workplaces",
+    security(("cookie_auth" = []))
+)]
+pub async fn get_custom_fields(
+    State(state): State<Arc<AppState>>,
+    Path(workplace_id): Path<i32>,
+    _auth: AuthenticatedUser,
+) -> AppResult<Json<Vec<CustomFieldDefinition>>> {
+    ensure_workplace_exists(&state.db, workplace_id).await?;
+
+    let fields = sqlx::query_as::<_, CustomFieldDefinition>(
+        r#"SELECT * FROM "CustomFieldDefinitions" WHERE workplace_id = $1 ORDER BY key"#,
+    )
+    .bind(workplace_id)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(fields))
+}
+
+/// POST /api/workplaces/{id}/custom-fields - Define a profile field
+#[utoipa::path(
+    post,
+    path = "/api/workplaces/{id}/custom-fields",
+    params(
+        ("id" = i32, Path, description = "Workplace ID")
+    ),
+    request_body = CreateCustomFieldInput,
+    responses(
+        (status = 200, description = "Field defined", body = CustomFieldDefinition),
+        (status = 400, description = "Invalid key or label"),
+        (status = 403, description = "Missing can_edit_staff permission in this workplace"),
+        (status = 404, description = "Workplace not found"),
+        (status = 409, description = "The workplace already has a field with this key")
+    ),
+    tag = "workplaces",
+    security(("cookie_auth" = ["can_edit_staff"]))
+)]
+pub async fn create_custom_field(
+    State(state): State<Arc<AppState>>,
+    Path(workplace_id): Path<i32>,
+    RequirePermission { auth, .. }: RequirePermission<CanEditStaff>,
+    Json(input): Json<CreateCustomFieldInput>,
+) -> AppResult<Json<CustomFieldDefinition>> {
+    ensure_workplace_exists(&state.db, workplace_id).await?;
+    ensure_can_manage(&state, &auth, workplace_id).await?;
+    validate_key(&input.key)?;
+    validate_label(&input.label)?;
+
+    let field = sqlx::query_as::<_, CustomFieldDefinition>(
+        r#"
+        INSERT INTO "CustomFieldDefinitions" (workplace_id, key, label, field_type)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (workplace_id, key) DO NOTHING
+        RETURNING *
+        "#,
+    )
+    .bind(workplace_id)
+    .bind(&input.key)
+    .bind(input.label.trim())
+    .bind(input.field_type)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::Conflict(format!("This workplace already has a field {}", input.key)))?;
+
+    tracing::info!(workplace_id, key = %field.key, admin_id = auth.profile_id, "🏷️ Custom field defined");
+
+    Ok(Json(field))
+}
+
+/// PUT /api/workplaces/{id}/custom-fields/{field_id} - Rename a profile field
+#[utoipa::path(
+    put,
+    path = "/api/workplaces/{id}/custom-fields/{field_id}",
+    params(
+        ("id" = i32, Path, description = "Workplace ID"),
+        ("field_id" = i32, Path, description = "Field definition ID")
+    ),
+    request_body = UpdateCustomFieldInput,
+    responses(
+        (status = 200, description = "Field renamed", body = CustomFieldDefinition),
+        (status = 400, description = "Invalid label"),
+        (status = 403, description = "Missing can_edit_staff permission in this workplace"),
+        (status = 404, description = "Field not found")
+    ),
+    tag = "workplaces",
+    security(("cookie_auth" = ["can_edit_staff"]))
+)]
+pub async fn update_custom_field(
+    State(state): State<Arc<AppState>>,
+    Path((workplace_id, field_id)): Path<(i32, i32)>,
+    RequirePermission { auth, .. }: RequirePermission<CanEditStaff>,
+    Json(input): Json<UpdateCustomFieldInput>,
+) -> AppResult<Json<CustomFieldDefinition>> {
+    ensure_can_manage(&state, &auth, workplace_id).await?;
+    validate_label(&input.label)?;
+
+    let field = sqlx::query_as::<_, CustomFieldDefinition>(
+        r#"UPDATE "CustomFieldDefinitions" SET label = $3 WHERE id = $1 AND workplace_id = $2 RETURNING *"#,
+    )
+    .bind(field_id)
+    .bind(workplace_id)
+    .bind(input.label.trim())
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Custom field {} not found", field_id)))?;
+
+    tracing::info!(workplace_id, key = %field.key, admin_id = auth.profile_id, "🏷️ Custom field renamed");
+
+    Ok(Json(field))
+}
+
+/// DELETE /api/workplaces/{id}/custom-fields/{field_id} - Remove a profile field and every user's value for it
+#[utoipa::path(
+    delete,
+    path = "/api/workplaces/{id}/custom-fields/{field_id}",
+    params(
+        ("id" = i32, Path, description = "Workplace ID"),
+        ("field_id" = i32, Path, description = "Field definition ID")
+    ),
+    responses(
+        (status = 200, description = "Field removed", body = SuccessResponse),
+        (status = 403, description = "Missing can_edit_staff permission in this workplace"),
+        (status = 404, description = "Field not found")
+    ),
+    tag = "workplaces",
+    security(("cookie_auth" = ["can_edit_staff"]))
+)]
+pub async fn delete_custom_field(
+    State(state): State<Arc<AppState>>,
+    Path((workplace_id, field_id)): Path<(i32, i32)>,
+    RequirePermission { auth, .. }: RequirePermission<CanEditStaff>,
+) -> AppResult<Json<SuccessResponse>> {
+    ensure_can_manage(&state, &auth, workplace_id).await?;
+
+    let key: String = sqlx::query_scalar(
+        r#"DELETE FROM "CustomFieldDefinitions" WHERE id = $1 AND workplace_id = $2 RETURNING key"#,
+    )
+    .bind(field_id)
+    .bind(workplace_id)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Custom field {} not found", field_id)))?;
+
+    tracing::info!(workplace_id, %key, admin_id = auth.profile_id, "🏷️ Custom field removed");
+
+    Ok(Json(SuccessResponse { success: true }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_custom_field_key() {
+        assert!(validate_key("bleep").is_ok());
+        assert!(validate_key("smart_card_2").is_ok());
+        assert!(validate_key("Bleep").is_err());
+        assert!(validate_key("2fa").is_err());
+        assert!(validate_key("smart card").is_err());
+        assert!(validate_key("").is_err());
+    }
+}
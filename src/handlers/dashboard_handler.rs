@@ -0,0 +1,382 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use chrono::{Datelike, NaiveDate, Utc};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::{
+    db::fieldset::{FieldSet, SHIFT_FIELDS},
+    extractors::{permissions, AuthenticatedUser},
+    handlers::{
+        announcements_handler::unread_announcements,
+        delegations_handler::approval_authority,
+        roles_handler::{month_range, RoleStatsQuery},
+    },
+    job_plans::JobPlanResolver,
+    models::{
+        CalendarDay, CalendarDiaryItem, CalendarShift, CalendarSwap, CalendarTimeOff, Dashboard, DiaryEntry,
+        LeaveSummary, MarketplaceCounts, PendingApprovals, PersonalCalendar, Shift,
+    },
+    AppError, AppResult, AppState,
+};
+
+/// How far ahead role-wide diary notes are shown
+const DIARY_NOTE_DAYS: i32 = 14;
+
+/// GET /api/dashboard - Home screen summary for the caller in one request
+#[utoipa::path(
+    get,
+    path = "/api/dashboard",
+    responses(
+        (status = 200, description = "Next shifts, marketplace counts, unread announcements, diary notes, leave and pending approvals", body = Dashboard)
+    ),
+    tag = "dashboard",
+    security(("cookie_auth" = []))
+)]
+pub async fn get_dashboard(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+) -> AppResult<Json<Dashboard>> {
+    let db = &state.db;
+    let user_id = auth.profile_id;
+    let today = Utc::now().date_naive();
+    let shift_columns = FieldSet::parse(None, SHIFT_FIELDS)?.select_list(&[]);
+
+    let (
+        next_shifts,
+        marketplace,
+        announcements,
+        diary_notes,
+        unread_notifications,
+        leave,
+        marketplace_approvals,
+        rota_publish_approvals,
+    ) = tokio::try_join!(
+        async {
+            let sql = crate::db::tag_sql(&format!(
+                r#"SELECT {} FROM "Shifts" WHERE user_profile_id = $1 AND date >= $2 AND time_off_category_id IS NULL ORDER BY date, start LIMIT 5"#,
+                shift_columns
+            ));
+            sqlx::query_as::<_, Shift>(&sql)
+                .persistent(false)
+                .bind(user_id)
+                .bind(today)
+                .fetch_all(db)
+                .await
+                .map_err(Into::into)
+        },
+        async {
+            let (open, awaiting_approval, incoming_proposals): (i64, i64, i64) = sqlx::query_as(
+                r#"
+                SELECT
+                    COUNT(*) FILTER (WHERE requester_id = $1 AND status = 'OPEN'),
+                    COUNT(*) FILTER (WHERE (requester_id = $1 OR candidate_id = $1) AND status = 'PENDING_APPROVAL'),
+                    COUNT(*) FILTER (WHERE target_user_id = $1 AND status IN ('PROPOSED', 'PEER_ACCEPTED'))
+                FROM "ShiftRequests"
+                WHERE requester_id = $1 OR candidate_id = $1 OR target_user_id = $1
+                "#,
+            )
+            .bind(user_id)
+            .fetch_one(db)
+            .await?;
+
+            AppResult::Ok(MarketplaceCounts { open, awaiting_approval, incoming_proposals })
+        },
+        unread_announcements(db, user_id),
+        async {
+            sqlx::query_as::<_, DiaryEntry>(&format!(
+                r#"
+                SELECT d.*, u.short_name
+                FROM "Diary" d
+                LEFT JOIN "Users" u ON d.created_by = u.user_profile_id
+                WHERE d.user_profile_id IS NULL
+                  AND NOT d.deleted
+                  AND NOT (d.al OR d.sl OR d.pl)
+                  AND d.date BETWEEN $2 AND $2 + $3
+                  AND d.role_id IN (
+                      SELECT role_id FROM "UserRoles"
+                      WHERE user_profile_id = $1 AND {in_force} AND (can_access_diary OR d.entry_type = 'ANNOUNCEMENT')
+                  )
+                ORDER BY d.date, d.created_at
+                "#,
+                in_force = crate::extractors::permissions::user_role_in_force(4)
+            ))
+            .bind(user_id)
+            .bind(today)
+            .bind(DIARY_NOTE_DAYS)
+            .bind(crate::extractors::permissions::today())
+            .fetch_all(db)
+            .await
+            .map_err(Into::into)
+        },
+        async {
+            sqlx::query_scalar::<_, i64>(
+                r#"SELECT COUNT(*) FROM "Notifications" WHERE user_profile_id = $1 AND read_at IS NULL"#,
+            )
+            .bind(user_id)
+            .fetch_one(db)
+            .await
+            .map_err(Into::into)
+        },
+        async {
+            let (annual_leave_days, study_leave_days, professional_leave_days, upcoming_days): (i64, i64, i64, i64) =
+                sqlx::query_as(
+                    r#"
+                    SELECT
+                        COUNT(DISTINCT date) FILTER (WHERE al),
+                        COUNT(DISTINCT date) FILTER (WHERE sl),
+                        COUNT(DISTINCT date) FILTER (WHERE pl),
+                        COUNT(DISTINCT date) FILTER (WHERE date >= $3)
+                    FROM "Diary"
+                    WHERE user_profile_id = $1
+                      AND NOT deleted
+                      AND (al OR sl OR pl)
+                      AND EXTRACT(YEAR FROM date) = $2
+                    "#,
+                )
+                .bind(user_id)
+                .bind(today.year())
+                .bind(today)
+                .fetch_one(db)
+                .await?;
+
+            let year_start = NaiveDate::from_ymd_opt(today.year(), 1, 1).unwrap_or(today);
+            let year_end = NaiveDate::from_ymd_opt(today.year(), 12, 31).unwrap_or(today);
+            let plans = JobPlanResolver::load(db, Some(user_id), None, year_start, year_end).await?;
+            let allowance = |per_year: fn(&crate::models::JobPlan) -> f32| {
+                (plans.prorated(user_id, year_start, year_end, per_year) * 10.0).round() / 10.0
+            };
+
+            AppResult::Ok(LeaveSummary {
+                year: today.year(),
+                annual_leave_days,
+                study_leave_days,
+                professional_leave_days,
+                annual_leave_allowance: allowance(|p| p.al_per_year),
+                study_leave_allowance: allowance(|p| p.sl_per_year),
+                professional_leave_allowance: allowance(|p| p.pl_per_year),
+                upcoming_days,
+            })
+        },
+        async {
+            if approval_authority(&state, &auth).await?.is_none() {
+                return AppResult::Ok(None);
+            }
+            let count: i64 = sqlx::query_scalar(r#"SELECT COUNT(*) FROM "ShiftRequests" WHERE status = 'PENDING_APPROVAL'"#)
+                .fetch_one(db)
+                .await?;
+            Ok(Some(count))
+        },
+        async {
+            if !permissions::has_permission(&state, user_id, auth.is_super_admin, permissions::can_approve_rota).await? {
+                return AppResult::Ok(None);
+            }
+            let count: i64 = sqlx::query_scalar(
+                r#"SELECT COUNT(*) FROM "RotaPublishApprovals" WHERE status = 'PENDING' AND submitted_by <> $1"#,
+            )
+            .bind(user_id)
+            .fetch_one(db)
+            .await?;
+            Ok(Some(count))
+        },
+    )?;
+
+    Ok(Json(Dashboard {
+        next_shifts,
+        marketplace,
+        announcements,
+        diary_notes,
+        unread_notifications,
+        leave,
+        approvals: PendingApprovals {
+            marketplace: marketplace_approvals,
+            rota_publish: rota_publish_approvals,
+        },
+    }))
+}
+
+/// GET /api/users/me/calendar?year=&month= - The caller's shifts, time off, pending swaps and diary items for
+/// a month, keyed by date, for month views on mobile
+#[utoipa::path(
+    get,
+    path = "/api/users/me/calendar",
+    params(RoleStatsQuery),
+    responses(
+        (status = 200, description = "Days of the month that have something on them, each with shifts, time_off, swaps and diary", body = PersonalCalendar),
+        (status = 400, description = "Invalid year or month")
+    ),
+    tag = "dashboard",
+    security(("cookie_auth" = []))
+)]
+pub async fn get_my_calendar(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+    Query(query): Query<RoleStatsQuery>,
+) -> AppResult<Json<PersonalCalendar>> {
+    let db = &state.db;
+    let user_id = auth.profile_id;
+    let (from, to) = month_range(query.year, query.month)?;
+
+    let (shifts, rota_time_off, diary_leave, swaps, diary) = tokio::try_join!(
+        async {
+            let sql = crate::db::tag_sql(
+                r#"
+                SELECT date, uuid, role_id AS role, label,
+                    to_char(start, 'HH24:MI') AS start, to_char("end", 'HH24:MI') AS "end",
+                    font_color, bk_color, published, COALESCE("end" <= start, FALSE) AS crosses_midnight
+                FROM "Shifts"
+                WHERE user_profile_id = $1 AND date BETWEEN $2 AND $3 AND time_off_category_id IS NULL
+                ORDER BY date, start
+                "#,
+            );
+            sqlx::query_as::<_, CalendarShift>(&sql)
+                .persistent(false)
+                .bind(user_id)
+                .bind(from)
+                .bind(to)
+                .fetch_all(db)
+                .await
+                .map_err(AppError::from)
+        },
+        async {
+            sqlx::query_as::<_, CalendarTimeOff>(
+                r#"
+                SELECT date, role_id AS role, label, time_off_category_id AS time_off
+                FROM "Shifts"
+                WHERE user_profile_id = $1 AND date BETWEEN $2 AND $3 AND time_off_category_id IS NOT NULL
+                ORDER BY date
+                "#,
+            )
+            .bind(user_id)
+            .bind(from)
+            .bind(to)
+            .fetch_all(db)
+            .await
+            .map_err(AppError::from)
+        },
+        async {
+            sqlx::query_as::<_, CalendarTimeOff>(
+                r#"
+                SELECT date, role_id AS role,
+                    CASE WHEN al THEN 'AL' WHEN sl THEN 'SL' ELSE 'PL' END AS label,
+                    NULL::int4 AS time_off
+                FROM "Diary"
+                WHERE user_profile_id = $1 AND NOT deleted AND (al OR sl OR pl) AND date BETWEEN $2 AND $3
+                ORDER BY date, created_at
+                "#,
+            )
+            .bind(user_id)
+            .bind(from)
+            .bind(to)
+            .fetch_all(db)
+            .await
+            .map_err(AppError::from)
+        },
+        async {
+            // Filed under the caller's own shift: the swap target when someone else proposed it, otherwise the
+            // offered shift (the one a candidate would take on)
+            sqlx::query_as::<_, CalendarSwap>(
+                r#"
+                SELECT * FROM (
+                    SELECT
+                        CASE WHEN r.requester_id <> $1 AND t.user_profile_id = $1 THEN t.date ELSE s.date END AS date,
+                        r.id, r."type", r.status, r.shift_id, r.target_shift_id,
+                        r.requester_id <> $1 AS incoming
+                    FROM "ShiftRequests" r
+                    JOIN "Shifts" s ON s.uuid = r.shift_id
+                    LEFT JOIN "Shifts" t ON t.uuid = r.target_shift_id
+                    WHERE (r.requester_id = $1 OR r.target_user_id = $1 OR r.candidate_id = $1)
+                      AND r.status NOT IN ('APPROVED', 'REJECTED', 'CANCELLED')
+                ) swaps
+                WHERE date BETWEEN $2 AND $3
+                ORDER BY date, id
+                "#,
+            )
+            .bind(user_id)
+            .bind(from)
+            .bind(to)
+            .fetch_all(db)
+            .await
+            .map_err(AppError::from)
+        },
+        async {
+            sqlx::query_as::<_, CalendarDiaryItem>(&format!(
+                r#"
+                SELECT d.date, d.id, d.role_id AS role, d.entry, d.entry_type, d.user_profile_id IS NOT NULL AS personal
+                FROM "Diary" d
+                WHERE NOT d.deleted
+                  AND NOT (d.al OR d.sl OR d.pl)
+                  AND d.date BETWEEN $2 AND $3
+                  AND (
+                      d.user_profile_id = $1
+                      OR (d.user_profile_id IS NULL AND d.role_id IN (
+                          SELECT role_id FROM "UserRoles"
+                          WHERE user_profile_id = $1 AND {in_force} AND (can_access_diary OR d.entry_type = 'ANNOUNCEMENT')
+                      ))
+                  )
+                ORDER BY d.date, d.created_at
+                "#,
+                in_force = crate::extractors::permissions::user_role_in_force(4)
+            ))
+            .bind(user_id)
+            .bind(from)
+            .bind(to)
+            .bind(crate::extractors::permissions::today())
+            .fetch_all(db)
+            .await
+            .map_err(AppError::from)
+        },
+    )?;
+
+    let mut days = BTreeMap::new();
+    file_by_date(&mut days, shifts, |s| s.date, |d| &mut d.shifts);
+    file_by_date(&mut days, rota_time_off, |t| t.date, |d| &mut d.time_off);
+    file_by_date(&mut days, diary_leave, |t| t.date, |d| &mut d.time_off);
+    file_by_date(&mut days, swaps, |s| s.date, |d| &mut d.swaps);
+    file_by_date(&mut days, diary, |e| e.date, |d| &mut d.diary);
+
+    Ok(Json(PersonalCalendar { year: query.year, month: query.month, days }))
+}
+
+/// Append each item to its day's list, creating the day on first use so empty days never appear
+fn file_by_date<T>(
+    days: &mut BTreeMap<NaiveDate, CalendarDay>,
+    items: Vec<T>,
+    date: fn(&T) -> NaiveDate,
+    list: fn(&mut CalendarDay) -> &mut Vec<T>,
+) {
+    for item in items {
+        list(days.entry(date(&item)).or_default()).push(item);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::DiaryEntryType;
+
+    fn diary_item(id: i32, date: NaiveDate) -> CalendarDiaryItem {
+        CalendarDiaryItem { date, id, role: 1, entry: None, entry_type: DiaryEntryType::Note, personal: true }
+    }
+
+    #[test]
+    fn test_file_by_date() {
+        let first = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+        let second = NaiveDate::from_ymd_opt(2026, 3, 9).unwrap();
+        let mut days = BTreeMap::new();
+        let items = vec![diary_item(2, second), diary_item(1, first), diary_item(3, second)];
+        file_by_date(&mut days, items, |e| e.date, |d| &mut d.diary);
+
+        assert_eq!(days.keys().copied().collect::<Vec<_>>(), vec![first, second]);
+        assert_eq!(days[&second].diary.iter().map(|e| e.id).collect::<Vec<_>>(), vec![2, 3]);
+
+        // Only non-empty lists are sent, and the date is the key rather than repeated per item
+        let json = serde_json::to_value(&days).unwrap();
+        assert_eq!(
+            json["2026-03-02"],
+            serde_json::json!({"diary": [{"id": 1, "role": 1, "entry": null, "entry_type": "NOTE", "personal": true}]})
+        );
+    }
+}
@@ -0,0 +1,320 @@
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderName, HeaderValue},
+    Json,
+};
+use serde_json::Value;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    extractors::AuthenticatedUser,
+    models::{DataExport, DataExportBundle, User},
+    AppError, AppResult, AppState,
+};
+
+const EXPORT_SELECT: &str = r#"
+    SELECT token, status, error, requested_at, completed_at, expires_at
+    FROM "DataExports"
+"#;
+
+/// Exports older than this that are still PENDING were cut short (e.g. by a restart) and stop blocking new ones
+const STALE_PENDING_SQL: &str = "INTERVAL '1 hour'";
+
+/// How long a requested export can be downloaded
+const EXPORT_VALID_SQL: &str = "INTERVAL '7 days'";
+
+/// Bundle sections: each query takes the user's profile id as $1 and is aggregated into a JSON array
+const ROLES_SQL: &str = r#"
+    SELECT ur.role_id, r.role_name, w.hospital, w.ward,
+           ur.can_edit_rota, ur.can_access_diary, ur.can_work_shifts, ur.can_edit_templates,
+           ur.can_edit_staff, ur.can_view_staff_details, ur.can_approve_rota, ur.can_set_pay, ur.created_at
+    FROM "UserRoles" ur
+    INNER JOIN "Roles" r ON r.id = ur.role_id
+    LEFT JOIN "Workplaces" w ON w.id = r.workplace_id
+    WHERE ur.user_profile_id = $1
+    ORDER BY ur.created_at, ur.id
+"#;
+
+const SHIFTS_SQL: &str = r#"
+    SELECT s.uuid, s.role_id, r.role_name, s.date, s.label,
+           to_char(s.start, 'HH24:MI') AS start, to_char(s."end", 'HH24:MI') AS "end",
+           s.is_locum, s.is_dcc, s.is_spa, s.published, s.time_off_category_id, s.created_at
+    FROM "Shifts" s
+    INNER JOIN "Roles" r ON r.id = s.role_id
+    WHERE s.user_profile_id = $1
+    ORDER BY s.date, s.start
+"#;
+
+const DIARY_SQL: &str = r#"
+    SELECT d.id, d.role_id, d.date, d.entry, d.al, d.sl, d.pl, d.deleted,
+           d.user_profile_id = $1 AS about_you, d.created_by = $1 AS written_by_you, d.created_at
+    FROM "Diary" d
+    WHERE d.user_profile_id = $1 OR (d.created_by = $1 AND d.user_profile_id IS NULL)
+    ORDER BY d.date, d.id
+"#;
+
+const MARKETPLACE_REQUESTS_SQL: &str = r#"
+    SELECT sr.id, sr.shift_id, sr.type, sr.status, sr.requester_id, sr.target_user_id, sr.target_shift_id,
+           sr.candidate_id, sr.resolved_by, sr.resolved_at, sr.notes, sr.created_at, sr.updated_at
+    FROM "ShiftRequests" sr
+    WHERE $1 IN (sr.requester_id, sr.target_user_id, sr.candidate_id)
+    ORDER BY sr.created_at, sr.id
+"#;
+
+const MARKETPLACE_OFFERS_SQL: &str = r#"
+    SELECT o.shift_request_id, o.notified_at
+    FROM "ShiftRequestOffers" o
+    WHERE o.user_profile_id = $1
+    ORDER BY o.notified_at
+"#;
+
+const AUDIT_REFERENCES_SQL: &str = r#"
+    SELECT sa.uuid AS audit_id, sa.role_id, sa.date, sa.created_at,
+           COALESCE(sa.created_by = $1 OR sa.acted_by = $1 OR sa.acted_as_delegate_of = $1, false) AS made_by_you,
+           COALESCE(sa.old_user_profile_id = $1 OR sa.new_user_profile_id = $1, false) AS concerns_you
+    FROM "ShiftAudit" sa
+    WHERE $1 IN (sa.created_by, sa.acted_by, sa.acted_as_delegate_of, sa.old_user_profile_id, sa.new_user_profile_id)
+    ORDER BY sa.created_at, sa.uuid
+"#;
+
+/// The profile as exported: everything but the PIN hash
+fn exportable_profile(mut user: User) -> User {
+    user.auth_pin = None;
+    user
+}
+
+fn export_filename(token: Uuid) -> String {
+    format!("edrota-export-{}.json", token.simple())
+}
+
+/// Rows of one section query as a JSON array
+async fn section(state: &AppState, sql: &str, user_profile_id: i32) -> Result<Value, sqlx::Error> {
+    sqlx::query_scalar(&format!(
+        "SELECT COALESCE(jsonb_agg(to_jsonb(t)), '[]'::jsonb) FROM ({}) t",
+        sql
+    ))
+    .bind(user_profile_id)
+    .fetch_one(&state.db)
+    .await
+}
+
+async fn build_bundle(state: &AppState, user_profile_id: i32) -> AppResult<DataExportBundle> {
+    let profile = sqlx::query_as::<_, User>(r#"SELECT * FROM "Users" WHERE user_profile_id = $1"#)
+        .bind(user_profile_id)
+        .fetch_one(&state.db)
+        .await?;
+
+    Ok(DataExportBundle {
+        generated_at: chrono::Utc::now(),
+        profile: exportable_profile(profile),
+        roles: section(state, ROLES_SQL, user_profile_id).await?,
+        shifts: section(state, SHIFTS_SQL, user_profile_id).await?,
+        diary: section(state, DIARY_SQL, user_profile_id).await?,
+        marketplace_requests: section(state, MARKETPLACE_REQUESTS_SQL, user_profile_id).await?,
+        marketplace_offers: section(state, MARKETPLACE_OFFERS_SQL, user_profile_id).await?,
+        audit_references: section(state, AUDIT_REFERENCES_SQL, user_profile_id).await?,
+    })
+}
+
+/// Background task: build the bundle, store it and tell the user it is ready (or why it failed)
+async fn prepare_export(state: Arc<AppState>, token: Uuid, user_profile_id: i32) {
+    let result = match build_bundle(&state, user_profile_id).await {
+        Ok(bundle) => store_bundle(&state, token, user_profile_id, bundle).await,
+        Err(e) => Err(e),
+    };
+
+    if let Err(e) = result {
+        tracing::error!(error = %e, %token, user_profile_id, "❌ Data export failed");
+        let failed = sqlx::query(
+            r#"UPDATE "DataExports" SET status = 'FAILED', error = $2, completed_at = NOW() WHERE token = $1"#,
+        )
+        .bind(token)
+        .bind("The export could not be prepared; please request a new one")
+        .execute(&state.db)
+        .await;
+        if let Err(e) = failed {
+            tracing::error!(error = %e, %token, "❌ Failed to mark data export as failed");
+        }
+    }
+}
+
+async fn store_bundle(state: &AppState, token: Uuid, user_profile_id: i32, bundle: DataExportBundle) -> AppResult<()> {
+    let bundle = serde_json::to_value(&bundle)
+        .map_err(|e| AppError::Internal(format!("Failed to serialise data export: {}", e)))?;
+
+    let mut tx = state.db.begin().await?;
+    sqlx::query(r#"UPDATE "DataExports" SET status = 'READY', bundle = $2, completed_at = NOW() WHERE token = $1"#)
+        .bind(token)
+        .bind(&bundle)
+        .execute(&mut *tx)
+        .await?;
+    crate::handlers::notifications_handler::notify(
+        &mut *tx,
+        user_profile_id,
+        "DATA_EXPORT_READY",
+        "Your data export is ready to download",
+        serde_json::json!({ "token": token }),
+    )
+    .await?;
+    tx.commit().await?;
+
+    tracing::info!(%token, user_profile_id, "📦 Data export ready");
+    Ok(())
+}
+
+/// POST /api/users/me/export - Request a copy of all personal data held about the caller
+#[utoipa::path(
+    post,
+    path = "/api/users/me/export",
+    responses(
+        (status = 200, description = "Export requested and being prepared in the background; poll GET /api/users/me/export or wait for the DATA_EXPORT_READY notification", body = DataExport),
+        (status = 409, description = "An export is already being prepared")
+    ),
+    tag = "users",
+    security(("cookie_auth" = []))
+)]
+pub async fn request_data_export(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+) -> AppResult<Json<DataExport>> {
+    // Tidy up before checking for one in progress: expired bundles go, interrupted ones stop blocking
+    sqlx::query(r#"DELETE FROM "DataExports" WHERE expires_at < NOW()"#)
+        .execute(&state.db)
+        .await?;
+    sqlx::query(&format!(
+        r#"
+        UPDATE "DataExports"
+        SET status = 'FAILED', error = 'The export was interrupted; please request a new one', completed_at = NOW()
+        WHERE status = 'PENDING' AND requested_at < NOW() - {}
+        "#,
+        STALE_PENDING_SQL
+    ))
+    .execute(&state.db)
+    .await?;
+
+    let export = sqlx::query_as::<_, DataExport>(&format!(
+        r#"
+        INSERT INTO "DataExports" (token, user_profile_id, expires_at)
+        VALUES ($1, $2, NOW() + {})
+        RETURNING token, status, error, requested_at, completed_at, expires_at
+        "#,
+        EXPORT_VALID_SQL
+    ))
+    .bind(Uuid::new_v4())
+    .bind(auth.profile_id)
+    .fetch_one(&state.db)
+    .await?;
+
+    tokio::spawn(prepare_export(state.clone(), export.token, auth.profile_id));
+    tracing::info!(token = %export.token, user_id = auth.profile_id, "📦 Data export requested");
+
+    Ok(Json(export))
+}
+
+/// GET /api/users/me/export - The caller's data exports and their status
+#[utoipa::path(
+    get,
+    path = "/api/users/me/export",
+    responses(
+        (status = 200, description = "Unexpired exports, newest first", body = Vec<DataExport>)
+    ),
+    tag = "users",
+    security(("cookie_auth" = []))
+)]
+pub async fn get_data_exports(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+) -> AppResult<Json<Vec<DataExport>>> {
+    let exports = sqlx::query_as::<_, DataExport>(&format!(
+        "{} WHERE user_profile_id = $1 AND expires_at >= NOW() ORDER BY requested_at DESC",
+        EXPORT_SELECT
+    ))
+    .bind(auth.profile_id)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(exports))
+}
+
+/// GET /api/users/me/export/{token} - Download a prepared export
+#[utoipa::path(
+    get,
+    path = "/api/users/me/export/{token}",
+    params(
+        ("token" = Uuid, Path, description = "Download token from POST /api/users/me/export")
+    ),
+    responses(
+        (status = 200, description = "The caller's data as a JSON attachment", body = DataExportBundle),
+        (status = 404, description = "No such export for the caller, or it has expired"),
+        (status = 409, description = "The export is still being prepared or failed")
+    ),
+    tag = "users",
+    security(("cookie_auth" = []))
+)]
+pub async fn download_data_export(
+    State(state): State<Arc<AppState>>,
+    Path(token): Path<Uuid>,
+    auth: AuthenticatedUser,
+) -> AppResult<([(HeaderName, HeaderValue); 1], Json<Value>)> {
+    let (status, bundle): (String, Option<Value>) = sqlx::query_as(
+        r#"SELECT status, bundle FROM "DataExports" WHERE token = $1 AND user_profile_id = $2 AND expires_at >= NOW()"#,
+    )
+    .bind(token)
+    .bind(auth.profile_id)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Data export not found or expired".to_string()))?;
+
+    let bundle = match (status.as_str(), bundle) {
+        ("READY", Some(bundle)) => bundle,
+        ("FAILED", _) => return Err(AppError::Conflict("This export failed; please request a new one".to_string())),
+        _ => return Err(AppError::Conflict("This export is still being prepared".to_string())),
+    };
+
+    let disposition = HeaderValue::from_str(&format!("attachment; filename=\"{}\"", export_filename(token)))
+        .map_err(|e| AppError::Internal(format!("Invalid export filename: {}", e)))?;
+
+    tracing::info!(%token, user_id = auth.profile_id, "📦 Data export downloaded");
+    Ok(([(header::CONTENT_DISPOSITION, disposition)], Json(bundle)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_leaves_out_pin_and_names_file() {
+        let user = User {
+            user_profile_id: 7,
+            auth_pin: Some("$argon2id$hash".to_string()),
+            primary_email: Some("dr.jones@example.org".to_string()),
+            ..Default::default()
+        };
+        let profile = exportable_profile(user);
+        assert!(profile.auth_pin.is_none());
+        assert_eq!(profile.primary_email.as_deref(), Some("dr.jones@example.org"));
+
+        let token = Uuid::parse_str("6f1c2e9a-0b7d-4c1e-9a3f-2d5e8b7c6a41").unwrap();
+        assert_eq!(export_filename(token), "edrota-export-6f1c2e9a0b7d4c1e9a3f2d5e8b7c6a41.json");
+    }
+
+    #[test]
+    fn test_export_timestamps_are_utc() {
+        let at = chrono::NaiveDate::from_ymd_opt(2026, 5, 1).unwrap().and_hms_opt(9, 30, 0).unwrap();
+        let export = DataExport {
+            token: Uuid::nil(),
+            status: "READY".to_string(),
+            error: None,
+            requested_at: at,
+            completed_at: Some(at),
+            expires_at: at,
+        };
+        let json = serde_json::to_value(&export).unwrap();
+        assert_eq!(json["completed_at"], "2026-05-01T09:30:00.000Z");
+        assert_eq!(json["requested_at"], json["completed_at"]);
+
+        let pending = DataExport { completed_at: None, ..export };
+        assert!(serde_json::to_value(&pending).unwrap()["completed_at"].is_null());
+    }
+}
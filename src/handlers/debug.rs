@@ -2,8 +2,15 @@ use axum::{extract::State, Json};
 use serde::Serialize;
 use std::sync::Arc;
 use std::time::SystemTime;
+use utoipa::OpenApi;
 
-use crate::AppState;
+use crate::{
+    db::{
+        latency::RouteDbLatency,
+        schema::{self, MigrationStatus, SchemaObject, SchemaSnapshot},
+    },
+    AppError, AppResult, AppState,
+};
 
 #[derive(Serialize)]
 pub struct DebugInfo {
@@ -13,6 +20,10 @@ pub struct DebugInfo {
     pub uptime_seconds: u64,
     pub database_status: String,
     pub database_connections: u32,
+    pub database_idle_connections: usize,
+    /// Rolling p50/p95/p99 of pool acquire and query time per route: slow acquires with fast
+    /// queries mean the pool is exhausted, slow queries mean the database is
+    pub db_latency: Vec<RouteDbLatency>,
     pub timestamp: u64,
 }
 
@@ -42,6 +53,8 @@ pub async fn debug_handler(State(state): State<Arc<AppState>>) -> Json<DebugInfo
         uptime_seconds: uptime,
         database_status: db_status,
         database_connections: pool_size,
+        database_idle_connections: state.db.num_idle(),
+        db_latency: state.metrics.db_latency.snapshot(),
         timestamp: SystemTime::now()
             .duration_since(SystemTime::UNIX_EPOCH)
             .unwrap()
@@ -50,3 +63,235 @@ pub async fn debug_handler(State(state): State<Arc<AppState>>) -> Json<DebugInfo
 
     Json(info)
 }
+
+/// Planner statistics for a table; row counts are Postgres' estimates, so reading them is cheap
+#[derive(Serialize, sqlx::FromRow)]
+pub struct TableStats {
+    pub name: String,
+    pub estimated_rows: i64,
+    pub total_bytes: i64,
+    pub seq_scans: i64,
+    pub index_scans: Option<i64>,
+}
+
+/// An index some migration creates, and whether this database has it
+#[derive(Serialize)]
+pub struct ExpectedIndex {
+    pub name: String,
+    pub migration: &'static str,
+    pub present: bool,
+    /// Scans since statistics were last reset; 0 on a large table suggests the planner ignores it
+    pub scans: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct SchemaReport {
+    pub migrations: Vec<MigrationStatus>,
+    pub tables: Vec<TableStats>,
+    pub expected_indexes: Vec<ExpectedIndex>,
+    pub timestamp: u64,
+}
+
+/// Handler for /api/debug/schema: which migrations are applied, table sizes and expected indexes
+pub async fn schema_handler(State(state): State<Arc<AppState>>) -> AppResult<Json<SchemaReport>> {
+    let snapshot = SchemaSnapshot::load(&state.db).await?;
+
+    let migrations = schema::MIGRATIONS
+        .iter()
+        .map(|(version, sql)| schema::migration_status(version, sql, &snapshot))
+        .collect();
+
+    let tables = sqlx::query_as::<_, TableStats>(
+        r#"
+        SELECT
+            relname::text AS name,
+            n_live_tup AS estimated_rows,
+            pg_total_relation_size(relid) AS total_bytes,
+            seq_scan AS seq_scans,
+            idx_scan AS index_scans
+        FROM pg_stat_user_tables
+        WHERE schemaname = 'public'
+        ORDER BY n_live_tup DESC, relname
+        "#,
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    let scans: std::collections::HashMap<String, i64> = sqlx::query_as::<_, (String, i64)>(
+        "SELECT indexrelname::text, idx_scan FROM pg_stat_user_indexes WHERE schemaname = 'public'",
+    )
+    .fetch_all(&state.db)
+    .await?
+    .into_iter()
+    .collect();
+
+    let expected_indexes = schema::MIGRATIONS
+        .iter()
+        .flat_map(|(version, sql)| {
+            schema::expected_objects(sql).into_iter().filter_map(move |object| match object {
+                SchemaObject::Index(name) => Some((*version, name)),
+                _ => None,
+            })
+        })
+        .map(|(migration, name)| ExpectedIndex {
+            present: snapshot.indexes.contains(&name),
+            scans: scans.get(&name).copied(),
+            name,
+            migration,
+        })
+        .collect();
+
+    Ok(Json(SchemaReport {
+        migrations,
+        tables,
+        expected_indexes,
+        timestamp: SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+    }))
+}
+
+const HTTP_METHODS: [&str; 8] = ["get", "put", "post", "delete", "options", "head", "patch", "trace"];
+
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub struct RoutePermissions {
+    pub method: String,
+    pub path: String,
+    pub operation_id: Option<String>,
+    /// Permissions declared as `cookie_auth` scopes, i.e. enforced by `RequirePermission<P>` before
+    /// the handler runs. Empty for routes that are public or check permissions per role in the handler
+    pub permissions: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct PermissionMatrix {
+    pub version: String,
+    pub git_sha: String,
+    pub routes: Vec<RoutePermissions>,
+    pub timestamp: u64,
+}
+
+/// Every documented operation with the permission scopes on its security requirements, ordered by path
+/// then method so two releases' matrices diff cleanly
+fn permission_matrix(spec: &serde_json::Value) -> Vec<RoutePermissions> {
+    let Some(paths) = spec["paths"].as_object() else {
+        return Vec::new();
+    };
+
+    let mut routes = Vec::new();
+    for (path, item) in paths {
+        for method in HTTP_METHODS {
+            let Some(operation) = item.get(method) else {
+                continue;
+            };
+
+            let mut permissions: Vec<String> = operation["security"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|requirement| requirement.as_object())
+                .flat_map(|requirement| requirement.values())
+                .filter_map(|scopes| scopes.as_array())
+                .flatten()
+                .filter_map(|scope| scope.as_str().map(str::to_string))
+                .collect();
+            permissions.sort();
+            permissions.dedup();
+
+            routes.push(RoutePermissions {
+                method: method.to_uppercase(),
+                path: path.clone(),
+                operation_id: operation["operationId"].as_str().map(str::to_string),
+                permissions,
+            });
+        }
+    }
+
+    routes
+}
+
+/// Handler for the /api/debug/permission-matrix endpoint
+pub async fn permission_matrix_handler() -> AppResult<Json<PermissionMatrix>> {
+    let spec = serde_json::to_value(crate::openapi::ApiDoc::openapi()).map_err(|e| AppError::Internal(e.to_string()))?;
+
+    Ok(Json(PermissionMatrix {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        git_sha: option_env!("GIT_SHA").unwrap_or("unknown").to_string(),
+        routes: permission_matrix(&spec),
+        timestamp: SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matrix() -> Vec<RoutePermissions> {
+        permission_matrix(&serde_json::to_value(crate::openapi::ApiDoc::openapi()).unwrap())
+    }
+
+    #[test]
+    fn test_permission_matrix_fixtures() {
+        let routes = matrix();
+        let permissions = |method: &str, path: &str| {
+            routes
+                .iter()
+                .find(|r| r.method == method && r.path == path)
+                .map(|r| r.permissions.clone())
+                .unwrap_or_else(|| panic!("{} {} missing from the matrix", method, path))
+        };
+
+        assert_eq!(permissions("POST", "/api/shifts"), vec!["can_edit_rota"]);
+        assert_eq!(permissions("GET", "/api/users/export"), vec!["can_edit_staff"]);
+        assert!(permissions("GET", "/api/shifts").is_empty());
+    }
+
+    /// Every `RequirePermission<P>` in a handler signature must be declared on its `#[utoipa::path]`,
+    /// otherwise the matrix would understate what the route enforces
+    #[test]
+    fn test_require_permission_extractors_are_declared() {
+        let routes = matrix();
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/src/handlers");
+        let mut checked = 0;
+
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let source = std::fs::read_to_string(entry.unwrap().path()).unwrap();
+            for (at, _) in source.match_indices("RequirePermission<Can") {
+                let rest = &source[at + "RequirePermission<".len()..];
+                let marker: String = rest.chars().take_while(|c| c.is_alphanumeric()).collect();
+                if !rest[marker.len()..].starts_with('>') {
+                    continue;
+                }
+                let permission = marker
+                    .chars()
+                    .enumerate()
+                    .flat_map(|(i, c)| {
+                        let sep = (i > 0 && c.is_uppercase()).then_some('_');
+                        sep.into_iter().chain(c.to_lowercase())
+                    })
+                    .collect::<String>();
+
+                let block = &source[source[..at].rfind("#[utoipa::path(").expect("handler without utoipa::path")..at];
+                let method = block["#[utoipa::path(".len()..].trim_start().split(',').next().unwrap().to_uppercase();
+                let path = block.split("path = \"").nth(1).and_then(|rest| rest.split('"').next()).unwrap();
+
+                let route = routes.iter().find(|r| r.method == method && r.path == path);
+                assert!(
+                    route.is_some_and(|r| r.permissions.contains(&permission)),
+                    "{} {} takes RequirePermission<{}> but does not declare {}",
+                    method,
+                    path,
+                    marker,
+                    permission
+                );
+                checked += 1;
+            }
+        }
+
+        assert!(checked > 0);
+    }
+}
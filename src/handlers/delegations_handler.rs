@@ -0,0 +1,276 @@
+use axum::{
+    extract::{Path, State},
+};
+use std::sync::Arc;
+
+use crate::{
+    extractors::{permissions, AuthenticatedUser, Json},
+    models::{ApprovalDelegation, CreateDelegationInput, MarketplaceMutationResponse},
+    AppError, AppResult, AppState,
+};
+
+/// Longest delegation that can be set up in one go
+const MAX_DELEGATION_DAYS: i64 = 180;
+
+const DELEGATION_SELECT: &str = r#"
+    SELECT
+        d.id,
+        d.delegator_id,
+        u_from.short_name AS delegator_name,
+        d.delegate_id,
+        u_to.short_name AS delegate_name,
+        d.start_date,
+        d.end_date,
+        d.reason,
+        d.created_at
+    FROM "ApprovalDelegations" d
+    LEFT JOIN "Users" u_from ON d.delegator_id = u_from.user_profile_id
+    LEFT JOIN "Users" u_to ON d.delegate_id = u_to.user_profile_id
+"#;
+
+/// How the caller may decide marketplace approvals
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApprovalAuthority {
+    /// Holds can_edit_rota (or is super admin)
+    Own,
+    /// Stands in for this approver under a delegation active today
+    DelegateOf(i32),
+}
+
+impl ApprovalAuthority {
+    /// The approver being stood in for, recorded alongside decisions made under a delegation
+    pub fn delegate_of(self) -> Option<i32> {
+        match self {
+            ApprovalAuthority::Own => None,
+            ApprovalAuthority::DelegateOf(delegator_id) => Some(delegator_id),
+        }
+    }
+}
+
+/// The delegator of a new delegation, once the caller may name them and the delegate and dates are valid
+fn check_delegation(caller_id: i32, is_super_admin: bool, input: &CreateDelegationInput) -> AppResult<i32> {
+    let delegator_id = match input.delegator_id {
+        Some(id) if id != caller_id && !is_super_admin => {
+            return Err(AppError::Forbidden("Only super admins can delegate for another user".to_string()));
+        }
+        Some(id) => id,
+        None => caller_id,
+    };
+
+    if input.delegate_id == delegator_id {
+        return Err(AppError::BadRequest("Cannot delegate approval to yourself".to_string()));
+    }
+    if input.end_date < input.start_date {
+        return Err(AppError::BadRequest("end_date must not be before start_date".to_string()));
+    }
+    if (input.end_date - input.start_date).num_days() >= MAX_DELEGATION_DAYS {
+        return Err(AppError::BadRequest(format!(
+            "Delegations can cover at most {} days",
+            MAX_DELEGATION_DAYS
+        )));
+    }
+
+    Ok(delegator_id)
+}
+
+/// Resolve the caller's approval authority, or None if they cannot approve
+pub async fn approval_authority(state: &AppState, auth: &AuthenticatedUser) -> AppResult<Option<ApprovalAuthority>> {
+    if permissions::has_permission(state, auth.profile_id, auth.is_super_admin, permissions::can_edit_rota).await? {
+        return Ok(Some(ApprovalAuthority::Own));
+    }
+
+    // The delegator must still be an approver themselves
+    let delegator_id: Option<i32> = sqlx::query_scalar(&format!(
+        r#"
+        SELECT d.delegator_id
+        FROM "ApprovalDelegations" d
+        INNER JOIN "Users" u ON d.delegator_id = u.user_profile_id
+        WHERE d.delegate_id = $1
+          AND CURRENT_DATE BETWEEN d.start_date AND d.end_date
+          AND (
+              u.is_super_admin
+              OR EXISTS (SELECT 1 FROM "UserRoles" ur WHERE ur.user_profile_id = d.delegator_id AND ur.can_edit_rota = true AND {ur_in_force})
+          )
+        ORDER BY d.start_date, d.id
+        LIMIT 1
+        "#,
+        ur_in_force = crate::extractors::permissions::ur_in_force(2)
+    ))
+    .bind(auth.profile_id)
+    .bind(crate::extractors::permissions::today())
+    .fetch_optional(&state.db)
+    .await?;
+
+    Ok(delegator_id.map(ApprovalAuthority::DelegateOf))
+}
+
+/// GET /api/marketplace/delegations - Delegations the caller gave or received (all for super admins)
+#[utoipa::path(
+    get,
+    path = "/api/marketplace/delegations",
+    responses(
+        (status = 200, description = "Approval delegations, latest first", body = Vec<ApprovalDelegation>)
+    ),
+    tag = "marketplace",
+    security(("cookie_auth" = []))
+)]
+pub async fn get_delegations(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+) -> AppResult<Json<Vec<ApprovalDelegation>>> {
+    let delegations = sqlx::query_as::<_, ApprovalDelegation>(&format!(
+        "{} WHERE $1 OR d.delegator_id = $2 OR d.delegate_id = $2 ORDER BY d.start_date DESC, d.id DESC",
+        DELEGATION_SELECT
+    ))
+    .bind(auth.is_super_admin)
+    .bind(auth.profile_id)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(delegations))
+}
+
+/// POST /api/marketplace/delegations - Lend approval rights to a colleague for a date range
+#[utoipa::path(
+    post,
+    path = "/api/marketplace/delegations",
+    request_body = CreateDelegationInput,
+    responses(
+        (status = 200, description = "Delegation created", body = ApprovalDelegation),
+        (status = 400, description = "Invalid date range or delegating to yourself"),
+        (status = 403, description = "Missing can_edit_rota permission, or naming another delegator without super admin"),
+        (status = 404, description = "Delegate not found")
+    ),
+    tag = "marketplace",
+    security(("cookie_auth" = []))
+)]
+pub async fn create_delegation(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+    Json(input): Json<CreateDelegationInput>,
+) -> AppResult<Json<ApprovalDelegation>> {
+    let delegator_id = check_delegation(auth.profile_id, auth.is_super_admin, &input)?;
+
+    // Only approvers have rights to lend
+    if delegator_id == auth.profile_id
+        && !crate::extractors::permissions::has_permission_by_name(&state, auth.profile_id, auth.is_super_admin, "can_edit_rota").await?
+    {
+        return Err(AppError::Forbidden("Missing can_edit_rota permission".to_string()));
+    }
+
+    let delegate_exists: bool = sqlx::query_scalar(r#"SELECT EXISTS(SELECT 1 FROM "Users" WHERE user_profile_id = $1)"#)
+        .bind(input.delegate_id)
+        .fetch_one(&state.db)
+        .await?;
+    if !delegate_exists {
+        return Err(AppError::NotFound(format!("User {} not found", input.delegate_id)));
+    }
+
+    let id: i32 = sqlx::query_scalar(
+        r#"
+        INSERT INTO "ApprovalDelegations" (delegator_id, delegate_id, start_date, end_date, reason)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id
+        "#,
+    )
+    .bind(delegator_id)
+    .bind(input.delegate_id)
+    .bind(input.start_date)
+    .bind(input.end_date)
+    .bind(&input.reason)
+    .fetch_one(&state.db)
+    .await?;
+
+    tracing::info!(
+        delegation_id = id,
+        delegator_id,
+        delegate_id = input.delegate_id,
+        start = %input.start_date,
+        end = %input.end_date,
+        "🤝 Approval delegated"
+    );
+
+    let delegation = sqlx::query_as::<_, ApprovalDelegation>(&format!("{} WHERE d.id = $1", DELEGATION_SELECT))
+        .bind(id)
+        .fetch_one(&state.db)
+        .await?;
+
+    Ok(Json(delegation))
+}
+
+/// DELETE /api/marketplace/delegations/{id} - Revoke a delegation
+#[utoipa::path(
+    delete,
+    path = "/api/marketplace/delegations/{id}",
+    params(
+        ("id" = i32, Path, description = "Delegation ID")
+    ),
+    responses(
+        (status = 200, description = "Delegation revoked", body = MarketplaceMutationResponse),
+        (status = 403, description = "Only the delegator or a super admin can revoke"),
+        (status = 404, description = "Delegation not found")
+    ),
+    tag = "marketplace",
+    security(("cookie_auth" = []))
+)]
+pub async fn delete_delegation(
+    State(state): State<Arc<AppState>>,
+    Path(delegation_id): Path<i32>,
+    auth: AuthenticatedUser,
+) -> AppResult<Json<MarketplaceMutationResponse>> {
+    let delegator_id: i32 = sqlx::query_scalar(r#"SELECT delegator_id FROM "ApprovalDelegations" WHERE id = $1"#)
+        .bind(delegation_id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Delegation {} not found", delegation_id)))?;
+
+    if delegator_id != auth.profile_id && !auth.is_super_admin {
+        return Err(AppError::Forbidden("Only the delegator can revoke this delegation".to_string()));
+    }
+
+    sqlx::query(r#"DELETE FROM "ApprovalDelegations" WHERE id = $1"#)
+        .bind(delegation_id)
+        .execute(&state.db)
+        .await?;
+
+    Ok(Json(MarketplaceMutationResponse {
+        success: true,
+        message: Some("Delegation revoked".to_string()),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn input(delegator_id: Option<i32>, delegate_id: i32, start: &str, end: &str) -> CreateDelegationInput {
+        CreateDelegationInput {
+            delegator_id,
+            delegate_id,
+            start_date: NaiveDate::parse_from_str(start, "%Y-%m-%d").unwrap(),
+            end_date: NaiveDate::parse_from_str(end, "%Y-%m-%d").unwrap(),
+            reason: None,
+        }
+    }
+
+    #[test]
+    fn test_check_delegation() {
+        assert_eq!(check_delegation(1, false, &input(None, 2, "2026-03-01", "2026-03-14")).unwrap(), 1);
+        assert_eq!(check_delegation(1, false, &input(Some(1), 2, "2026-03-01", "2026-03-01")).unwrap(), 1);
+        assert_eq!(check_delegation(1, true, &input(Some(3), 2, "2026-03-01", "2026-03-14")).unwrap(), 3);
+
+        assert!(matches!(check_delegation(1, false, &input(Some(3), 2, "2026-03-01", "2026-03-14")), Err(AppError::Forbidden(_))));
+        assert!(matches!(check_delegation(1, false, &input(None, 1, "2026-03-01", "2026-03-14")), Err(AppError::BadRequest(_))));
+        assert!(matches!(check_delegation(1, true, &input(Some(2), 2, "2026-03-01", "2026-03-14")), Err(AppError::BadRequest(_))));
+        assert!(matches!(check_delegation(1, false, &input(None, 2, "2026-03-14", "2026-03-01")), Err(AppError::BadRequest(_))));
+        assert!(check_delegation(1, false, &input(None, 2, "2026-01-01", "2026-06-29")).is_ok());
+        assert!(matches!(check_delegation(1, false, &input(None, 2, "2026-01-01", "2026-06-30")), Err(AppError::BadRequest(_))));
+    }
+
+    #[test]
+    fn test_approval_authority_delegate_of() {
+        assert_eq!(ApprovalAuthority::Own.delegate_of(), None);
+        assert_eq!(ApprovalAuthority::DelegateOf(7).delegate_of(), Some(7));
+    }
+}
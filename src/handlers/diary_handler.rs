@@ -1,18 +1,26 @@
-use axum::{
-    extract::{Path, Query, State},
-    Json,
-};
-use chrono::NaiveDate;
+use axum::extract::{Path, Query, State};
+use chrono::{Datelike, NaiveDate};
 use serde::Deserialize;
 use std::sync::Arc;
 use utoipa::IntoParams;
 
 use crate::{
-    extractors::AuthenticatedUser,
-    models::{CreateDiaryInput, DiaryEntry, DiaryMutationResponse},
+    error::{FieldError, InvalidFieldsBody},
+    extractors::{permissions, AuthenticatedUser, Json},
+    job_plans::JobPlanResolver,
+    models::{CreateDiaryInput, DiaryEntry, DiaryEntryType, DiaryMutationResponse, JobPlan},
     AppError, AppResult, AppState,
 };
 
+/// A leave flag on a diary entry: (field, name, yearly allowance in the job plan)
+type LeaveFlag = (&'static str, &'static str, fn(&JobPlan) -> f32);
+
+const LEAVE_FLAGS: [LeaveFlag; 3] = [
+    ("al", "annual leave", |p| p.al_per_year),
+    ("sl", "study leave", |p| p.sl_per_year),
+    ("pl", "professional leave", |p| p.pl_per_year),
+];
+
 #[derive(Debug, Deserialize, IntoParams)]
 pub struct GetDiaryQuery {
     #[serde(rename = "roleId")]
@@ -27,90 +35,197 @@ pub struct DeleteDiaryQuery {
     pub confirmed_user_id: Option<i32>,
 }
 
-/// GET /api/diary?roleId=&start=&end=
+/// GET /api/diary?roleId=&start=&end= - Entries in roles where the caller has can_access_diary, plus
+/// announcements in every role they belong to
 #[utoipa::path(
     get,
     path = "/api/diary",
     params(GetDiaryQuery),
     responses(
         (status = 200, description = "List of diary entries", body = Vec<DiaryEntry>),
-        (status = 400, description = "Invalid date format")
+        (status = 400, description = "Invalid date format"),
+        (status = 403, description = "roleId is a role the caller does not belong to")
     ),
-    tag = "diary"
+    tag = "diary",
+    security(("cookie_auth" = []))
 )]
 pub async fn get_diary(
     State(state): State<Arc<AppState>>,
     auth: AuthenticatedUser,
     Query(query): Query<GetDiaryQuery>,
 ) -> AppResult<Json<Vec<DiaryEntry>>> {
-    // Check permission
-    if !crate::extractors::permissions::has_permission_by_name(
-        &state.db, auth.profile_id, auth.is_super_admin, "can_access_diary"
-    ).await? {
-        return Err(AppError::Forbidden("Missing can_access_diary permission".to_string()));
-    }
+    let parse_date = |value: &Option<String>, name: &str| {
+        value
+            .as_deref()
+            .map(|v| NaiveDate::parse_from_str(v, "%Y-%m-%d"))
+            .transpose()
+            .map_err(|e| AppError::BadRequest(format!("Invalid {} date: {}", name, e)))
+    };
+    let start_date = parse_date(&query.start, "start")?;
+    let end_date = parse_date(&query.end, "end")?;
 
-    // Handle different query combinations
-    let entries = match (query.role_id, query.start, query.end) {
-        (Some(role_id), Some(start), Some(end)) => {
-            let start_date = NaiveDate::parse_from_str(&start, "%Y-%m-%d")
-                .map_err(|e| crate::AppError::BadRequest(format!("Invalid start date: {}", e)))?;
-            let end_date = NaiveDate::parse_from_str(&end, "%Y-%m-%d")
-                .map_err(|e| crate::AppError::BadRequest(format!("Invalid end date: {}", e)))?;
-            sqlx::query_as::<sqlx::Postgres, DiaryEntry>(
-                r#"
-                SELECT d.*, u.short_name
-                FROM "Diary" d
-                LEFT JOIN "Users" u ON d.user_profile_id = u.user_profile_id
-                WHERE d.role_id = $1 AND d.date >= $2 AND d.date <= $3
-                ORDER BY d.created_at DESC
-                "#
-            )
-            .bind(role_id)
-            .bind(start_date)
-            .bind(end_date)
-            .fetch_all(&state.db)
-            .await?
-        }
-        (Some(role_id), None, None) => {
-            sqlx::query_as::<sqlx::Postgres, DiaryEntry>(
-                r#"
-                SELECT d.*, u.short_name
-                FROM "Diary" d
-                LEFT JOIN "Users" u ON d.user_profile_id = u.user_profile_id
-                WHERE d.role_id = $1
-                ORDER BY d.created_at DESC
-                "#
-            )
-            .bind(role_id)
-            .fetch_all(&state.db)
-            .await?
-        }
-        _ => {
-            sqlx::query_as::<sqlx::Postgres, DiaryEntry>(
-                r#"
-                SELECT d.*, u.short_name
-                FROM "Diary" d
-                LEFT JOIN "Users" u ON d.user_profile_id = u.user_profile_id
-                ORDER BY d.created_at DESC
-                "#
-            )
-            .fetch_all(&state.db)
-            .await?
+    // Super admins see everything (None); everyone else is scoped to their own roles
+    let scope = |roles: Vec<i32>| (!auth.is_super_admin).then_some(roles);
+    let diary_roles = scope(permissions::roles_with_permission(&state, auth.profile_id, permissions::can_access_diary).await?);
+    let member_roles = scope(permissions::roles_with_permission(&state, auth.profile_id, |_| true).await?);
+
+    if let (Some(role_id), Some(member_roles)) = (query.role_id, &member_roles) {
+        if !member_roles.contains(&role_id) {
+            return Err(AppError::Forbidden("No access to this role's diary".to_string()));
         }
-    };
+    }
+
+    let entries = sqlx::query_as::<_, DiaryEntry>(
+        r#"
+        SELECT d.*, u.short_name
+        FROM "Diary" d
+        LEFT JOIN "Users" u ON d.user_profile_id = u.user_profile_id
+        WHERE ($1::int4 IS NULL OR d.role_id = $1)
+          AND ($2::date IS NULL OR d.date >= $2)
+          AND ($3::date IS NULL OR d.date <= $3)
+          AND ($4::int4[] IS NULL OR d.role_id = ANY($4) OR (d.entry_type = 'ANNOUNCEMENT' AND d.role_id = ANY($5)))
+        ORDER BY d.created_at DESC
+        "#,
+    )
+    .bind(query.role_id)
+    .bind(start_date)
+    .bind(end_date)
+    .bind(&diary_roles)
+    .bind(&member_roles)
+    .fetch_all(&state.db)
+    .await?;
 
     Ok(Json(entries))
 }
 
-/// POST /api/diary - Create a new diary entry
+/// Announcements are role-wide notices, so they name no user and record no leave
+fn validate_entry_type(input: &CreateDiaryInput) -> AppResult<()> {
+    if input.entry_type == DiaryEntryType::Announcement
+        && (input.user_profile_id.is_some() || input.al || input.sl || input.pl)
+    {
+        return Err(AppError::BadRequest(
+            "Announcements cannot name a user or record leave".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Leave flags an entry sets, by field name
+fn leave_fields(input: &CreateDiaryInput) -> Vec<&'static str> {
+    [("al", input.al), ("sl", input.sl), ("pl", input.pl)]
+        .into_iter()
+        .filter_map(|(field, set)| set.then_some(field))
+        .collect()
+}
+
+/// Whether one more day fits: `booked` other days of this leave in the year against the pro-rata `allowance`,
+/// rounded to 0.1 day as on the dashboard
+pub fn within_allowance(booked: i64, allowance: f64) -> bool {
+    (booked + 1) as f64 <= (allowance * 10.0).round() / 10.0
+}
+
+/// Leave must name a user with can_access_diary in the role and fit their remaining allowance for the year.
+/// Users without a job plan in force that year have no allowance on record and are not limited.
+async fn validate_leave(state: &AppState, input: &CreateDiaryInput) -> AppResult<()> {
+    let fields = leave_fields(input);
+    if fields.is_empty() {
+        return Ok(());
+    }
+    let Some(user_id) = input.user_profile_id else {
+        return Err(AppError::InvalidFields {
+            message: "Leave must be recorded for a user".to_string(),
+            errors: vec![FieldError {
+                field: "user_profile_id".to_string(),
+                code: "REQUIRED",
+                message: "Name the user taking the leave".to_string(),
+            }],
+        });
+    };
+
+    let role_id = input.role_id;
+    if !permissions::has_permission(state, user_id, false, |r| r.role_id == role_id && r.can_access_diary).await? {
+        return Err(AppError::InvalidFields {
+            message: "Leave can only be recorded for staff with diary access in this role".to_string(),
+            errors: vec![FieldError {
+                field: "user_profile_id".to_string(),
+                code: "NO_DIARY_ACCESS",
+                message: format!("User {} lacks can_access_diary in role {}", user_id, role_id),
+            }],
+        });
+    }
+
+    let year_start = NaiveDate::from_ymd_opt(input.date.year(), 1, 1).unwrap_or(input.date);
+    let year_end = NaiveDate::from_ymd_opt(input.date.year(), 12, 31).unwrap_or(input.date);
+    let plans = JobPlanResolver::load(&state.db, Some(user_id), None, year_start, year_end).await?;
+    if plans.assignments().next().is_none() {
+        return Ok(());
+    }
+
+    // Other days already booked this year; re-booking the same day costs nothing extra
+    let (al_days, sl_days, pl_days): (i64, i64, i64) = sqlx::query_as(
+        r#"
+        SELECT
+            COUNT(DISTINCT date) FILTER (WHERE al),
+            COUNT(DISTINCT date) FILTER (WHERE sl),
+            COUNT(DISTINCT date) FILTER (WHERE pl)
+        FROM "Diary"
+        WHERE user_profile_id = $1
+          AND NOT deleted
+          AND (al OR sl OR pl)
+          AND date BETWEEN $2 AND $3
+          AND date <> $4
+        "#,
+    )
+    .bind(user_id)
+    .bind(year_start)
+    .bind(year_end)
+    .bind(input.date)
+    .fetch_one(&state.db)
+    .await?;
+
+    let errors: Vec<FieldError> = LEAVE_FLAGS
+        .iter()
+        .zip([al_days, sl_days, pl_days])
+        .filter(|((field, _, _), _)| fields.contains(field))
+        .filter_map(|((field, name, per_year), booked)| {
+            let allowance = plans.prorated(user_id, year_start, year_end, per_year);
+            (!within_allowance(booked, allowance)).then(|| FieldError {
+                field: field.to_string(),
+                code: "OVER_ALLOWANCE",
+                message: format!(
+                    "{} days of {} already booked in {} against an allowance of {:.1}",
+                    booked,
+                    name,
+                    input.date.year(),
+                    allowance
+                ),
+            })
+        })
+        .collect();
+    if errors.is_empty() {
+        return Ok(());
+    }
+    Err(AppError::InvalidFields { message: "Leave exceeds the remaining allowance".to_string(), errors })
+}
+
+/// Whether the caller needs can_edit_rota for this entry: leave for anyone but themselves, or skipping the leave
+/// checks. `caller` is the signed-in account, never the client-supplied confirmed user.
+fn needs_rota_permission(input: &CreateDiaryInput, caller: i32) -> bool {
+    let records_leave = input.al || input.sl || input.pl;
+    let for_someone_else = input.user_profile_id.is_some_and(|user_id| user_id != caller);
+    (records_leave && for_someone_else) || input.override_leave_checks
+}
+
+/// POST /api/diary - Create a new diary entry (can_access_diary in the entry's role)
 #[utoipa::path(
     post,
     path = "/api/diary",
     request_body = CreateDiaryInput,
     responses(
         (status = 200, description = "Diary entry created successfully", body = DiaryEntry),
-        (status = 403, description = "Missing can_access_diary permission")
+        (status = 400, description = "Announcement names a user or records leave"),
+        (status = 403, description = "Missing can_access_diary permission in this role, or can_edit_rota to record leave for someone else or override the leave checks"),
+        (status = 422, description = "Leave names no user, a user without diary access in the role, or exceeds the remaining allowance; one entry per problem under errors", body = InvalidFieldsBody)
     ),
     tag = "diary",
     security(("cookie_auth" = []))
@@ -120,15 +235,49 @@ pub async fn create_diary_entry(
     auth: AuthenticatedUser,
     Json(mut input): Json<CreateDiaryInput>,
 ) -> AppResult<Json<DiaryEntry>> {
-    // Use confirmed user ID if provided (generic account flow), otherwise use authenticated user
+    // The confirmed user ID (generic account flow) comes from the client unverified, so it only attributes the
+    // entry; every permission decision is made for the signed-in account
     let acting_user_id = input.confirmed_user_id.unwrap_or(auth.profile_id);
 
-    // Check permission
-    if !crate::extractors::permissions::has_permission_by_name(&state.db, acting_user_id, auth.is_super_admin, "can_access_diary").await? {
+    // Check permission in the entry's role
+    let role_id = input.role_id;
+    if !permissions::has_permission(&state, auth.profile_id, auth.is_super_admin, |r| {
+        r.role_id == role_id && r.can_access_diary
+    })
+    .await?
+    {
+        return Err(AppError::Forbidden(
+            "Missing can_access_diary permission in this role".to_string(),
+        ));
+    }
+    validate_entry_type(&input)?;
+
+    // Leave for someone else, or past the leave checks, is an admin's call
+    if needs_rota_permission(&input, auth.profile_id)
+        && !permissions::has_permission(&state, auth.profile_id, auth.is_super_admin, |r| {
+            r.role_id == role_id && r.can_edit_rota
+        })
+        .await?
+    {
         return Err(AppError::Forbidden(
-            "Missing can_access_diary permission".to_string(),
+            "Recording leave for another user or overriding the leave checks needs can_edit_rota in this role"
+                .to_string(),
         ));
     }
+    if input.override_leave_checks {
+        if input.al || input.sl || input.pl {
+            tracing::info!(
+                role_id,
+                user_profile_id = ?input.user_profile_id,
+                date = %input.date,
+                overridden_by = auth.profile_id,
+                confirmed_user_id = ?input.confirmed_user_id,
+                "⚠️ Diary leave checks overridden"
+            );
+        }
+    } else {
+        validate_leave(&state, &input).await?;
+    }
 
     // Set created_by to acting user
     input.created_by = Some(acting_user_id);
@@ -136,10 +285,10 @@ pub async fn create_diary_entry(
     let entry = sqlx::query_as::<_, DiaryEntry>(
         r#"
         INSERT INTO "Diary" (
-            role_id, date, entry, al, sl, pl, user_profile_id, created_by, deleted
+            role_id, date, entry, al, sl, pl, user_profile_id, created_by, deleted, entry_type
         )
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, false)
-        RETURNING id::int4, role_id, date, entry, al, sl, pl, created_at, user_profile_id, created_by, deleted
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, false, $9)
+        RETURNING id::int4, role_id, date, entry, al, sl, pl, created_at, user_profile_id, created_by, deleted, entry_type
         "#,
     )
     .bind(input.role_id)
@@ -150,6 +299,7 @@ pub async fn create_diary_entry(
     .bind(input.pl)
     .bind(input.user_profile_id)
     .bind(input.created_by.unwrap_or(acting_user_id))
+    .bind(input.entry_type)
     .fetch_one(&state.db)
     .await?;
 
@@ -170,7 +320,7 @@ pub async fn create_diary_entry(
     ),
     responses(
         (status = 200, description = "Diary entry deleted successfully", body = DiaryMutationResponse),
-        (status = 403, description = "Missing can_access_diary permission"),
+        (status = 403, description = "Missing can_access_diary permission in the entry's role"),
         (status = 404, description = "Diary entry not found")
     ),
     tag = "diary",
@@ -182,25 +332,16 @@ pub async fn delete_diary_entry(
     Query(params): Query<DeleteDiaryQuery>,
     auth: AuthenticatedUser,
 ) -> AppResult<Json<DiaryMutationResponse>> {
-    // Use confirmed user ID if provided (generic account flow), otherwise use authenticated user
-    let acting_user_id = params.confirmed_user_id.unwrap_or(auth.profile_id);
-
-    // Check permission
-    if !crate::extractors::permissions::has_permission_by_name(&state.db, acting_user_id, auth.is_super_admin, "can_access_diary").await? {
-        return Err(AppError::Forbidden(
-            "Missing can_access_diary permission".to_string(),
-        ));
-    }
-
-    // Fetch entry to check creation time and user_profile_id
+    // Fetch entry to check its role, creation time and user_profile_id
     #[derive(sqlx::FromRow)]
     struct DiaryCheck {
+        role_id: i32,
         user_profile_id: Option<i32>,
         created_at: chrono::NaiveDateTime,
     }
 
     let entry = sqlx::query_as::<_, DiaryCheck>(
-        r#"SELECT user_profile_id, created_at FROM "Diary" WHERE id = $1"#
+        r#"SELECT role_id, user_profile_id, created_at FROM "Diary" WHERE id = $1"#
     )
     .bind(entry_id)
     .fetch_optional(&state.db)
@@ -211,6 +352,17 @@ pub async fn delete_diary_entry(
         entry_id
     )))?;
 
+    // Checked for the signed-in account; the confirmed user ID (generic account flow) is only logged
+    if !permissions::has_permission(&state, auth.profile_id, auth.is_super_admin, |r| {
+        r.role_id == entry.role_id && r.can_access_diary
+    })
+    .await?
+    {
+        return Err(AppError::Forbidden(
+            "Missing can_access_diary permission in this role".to_string(),
+        ));
+    }
+
     // Decide: hard delete or soft delete
     let should_hard_delete = if entry.user_profile_id.is_none() {
         // Announcements (no user profile) are always hard deleted
@@ -238,9 +390,81 @@ pub async fn delete_diary_entry(
             .execute(&state.db)
             .await?;
     }
+    tracing::info!(
+        entry_id,
+        hard_delete = should_hard_delete,
+        deleted_by = auth.profile_id,
+        confirmed_user_id = ?params.confirmed_user_id,
+        "🗑️ Diary entry deleted"
+    );
 
     Ok(Json(DiaryMutationResponse {
         success: true,
         message: Some("Diary entry deleted successfully".to_string()),
     }))
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_entry_type() {
+        let mut input = CreateDiaryInput {
+            role_id: 1,
+            date: NaiveDate::from_ymd_opt(2026, 10, 19).unwrap(),
+            entry: Some("Fire drill at 10:00".to_string()),
+            al: false,
+            sl: false,
+            pl: false,
+            user_profile_id: None,
+            entry_type: DiaryEntryType::Announcement,
+            created_by: None,
+            confirmed_user_id: None,
+            override_leave_checks: false,
+        };
+        assert!(validate_entry_type(&input).is_ok());
+
+        input.al = true;
+        assert!(validate_entry_type(&input).is_err());
+
+        input.entry_type = DiaryEntryType::Note;
+        input.user_profile_id = Some(7);
+        assert!(validate_entry_type(&input).is_ok());
+
+        input.pl = true;
+        assert_eq!(leave_fields(&input), ["al", "pl"]);
+    }
+
+    #[test]
+    fn test_confirmed_user_cannot_claim_leave_as_their_own() {
+        let mut input = CreateDiaryInput {
+            role_id: 1,
+            date: NaiveDate::from_ymd_opt(2026, 10, 19).unwrap(),
+            entry: None,
+            al: true,
+            sl: false,
+            pl: false,
+            user_profile_id: Some(7),
+            entry_type: DiaryEntryType::Note,
+            created_by: None,
+            confirmed_user_id: Some(7),
+            override_leave_checks: false,
+        };
+        // A terminal (profile 3) naming 7 as the confirmed user is still recording leave for someone else
+        assert!(needs_rota_permission(&input, 3));
+        assert!(!needs_rota_permission(&input, 7));
+
+        input.override_leave_checks = true;
+        assert!(needs_rota_permission(&input, 7));
+    }
+
+    #[test]
+    fn test_within_allowance() {
+        assert!(within_allowance(26, 27.0));
+        assert!(!within_allowance(27, 27.0));
+        // 13.96 pro rata rounds to 14.0 as shown on the dashboard
+        assert!(within_allowance(13, 13.96));
+        assert!(!within_allowance(0, 0.0));
+    }
+}
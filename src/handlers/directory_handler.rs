@@ -0,0 +1,172 @@
+use axum::{extract::State, Json};
+use sqlx::FromRow;
+use std::sync::Arc;
+
+use crate::{
+    db::encrypted::EncryptedString,
+    extractors::{permissions, AuthenticatedUser},
+    models::{DirectoryContact, DirectoryMember, DirectoryRole, DirectoryWorkplace},
+    AppError, AppResult, AppState,
+};
+
+#[derive(FromRow)]
+struct DirectoryRow {
+    workplace_id: i32,
+    hospital: Option<String>,
+    ward: Option<String>,
+    role_id: i32,
+    role_name: String,
+    user_profile_id: i32,
+    full_name: String,
+    short_name: String,
+    color: Option<String>,
+    primary_email: Option<String>,
+    tel: Option<Vec<EncryptedString>>,
+}
+
+/// Group the flat directory rows (already sorted by workplace, role, name) into workplaces and roles
+fn group_directory(rows: Vec<DirectoryRow>) -> Vec<DirectoryWorkplace> {
+    let mut workplaces: Vec<DirectoryWorkplace> = Vec::new();
+
+    for row in rows {
+        if workplaces.last().is_none_or(|w| w.workplace_id != row.workplace_id) {
+            workplaces.push(DirectoryWorkplace {
+                workplace_id: row.workplace_id,
+                hospital: row.hospital,
+                ward: row.ward,
+                roles: Vec::new(),
+            });
+        }
+        let Some(workplace) = workplaces.last_mut() else { continue };
+
+        if workplace.roles.last().is_none_or(|r| r.role_id != row.role_id) {
+            workplace.roles.push(DirectoryRole {
+                role_id: row.role_id,
+                role_name: row.role_name,
+                members: Vec::new(),
+            });
+        }
+        let Some(role) = workplace.roles.last_mut() else { continue };
+
+        role.members.push(DirectoryMember {
+            user_profile_id: row.user_profile_id,
+            full_name: row.full_name,
+            short_name: row.short_name,
+            color: row.color,
+            contact: Some(DirectoryContact {
+                primary_email: row.primary_email,
+                tel: row.tel.unwrap_or_default().into_iter().map(|t| t.0).collect(),
+            }),
+        });
+    }
+
+    workplaces
+}
+
+/// Drop contact details the caller may not see: kept for roles they can view staff details in, and for themselves
+fn redact_contacts(workplaces: &mut [DirectoryWorkplace], viewer_id: i32, can_view_role: impl Fn(i32) -> bool) {
+    for role in workplaces.iter_mut().flat_map(|w| w.roles.iter_mut()) {
+        if can_view_role(role.role_id) {
+            continue;
+        }
+        for member in role.members.iter_mut().filter(|m| m.user_profile_id != viewer_id) {
+            member.contact = None;
+        }
+    }
+}
+
+/// GET /api/directory - Who's who: staff grouped by workplace and role
+#[utoipa::path(
+    get,
+    path = "/api/directory",
+    responses(
+        (status = 200, description = "Workplaces with their roles and members; contact details only where the caller has can_view_staff_details", body = Vec<DirectoryWorkplace>)
+    ),
+    tag = "users",
+    security(("cookie_auth" = []))
+)]
+pub async fn get_directory(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+) -> AppResult<Json<Vec<DirectoryWorkplace>>> {
+    let mut directory = match state.caches.directory.get().await {
+        Some(cached) => cached,
+        None => {
+            let rows = sqlx::query_as::<_, DirectoryRow>(&format!(
+                r#"
+                SELECT
+                    w.id::int4 AS workplace_id,
+                    w.hospital,
+                    w.ward,
+                    r.id::int4 AS role_id,
+                    r.role_name,
+                    u.user_profile_id,
+                    u.full_name,
+                    u.short_name,
+                    u.color,
+                    u.primary_email,
+                    u.tel
+                FROM "UserRoles" ur
+                INNER JOIN "Users" u ON ur.user_profile_id = u.user_profile_id
+                INNER JOIN "Roles" r ON ur.role_id = r.id
+                INNER JOIN "Workplaces" w ON r.workplace_id = w.id
+                WHERE u.is_generic_login = false AND {ur_in_force}
+                ORDER BY w.hospital, w.ward, w.id, r.role_name, r.id, u.full_name
+                "#,
+                ur_in_force = crate::extractors::permissions::ur_in_force(1)
+            ))
+            .bind(crate::extractors::permissions::today())
+            .fetch_all(&state.db)
+            .await?;
+
+            let directory = group_directory(rows);
+            state.caches.directory.insert(directory.clone()).await;
+            directory
+        }
+    };
+
+    if !auth.is_super_admin {
+        let viewable = permissions::roles_with_permission(&state, auth.profile_id, permissions::can_view_staff_details)
+            .await
+            .map_err(|e| AppError::Internal(e.to_string()))?;
+        redact_contacts(&mut directory, auth.profile_id, |role_id| viewable.contains(&role_id));
+    }
+
+    Ok(Json(directory))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(workplace_id: i32, role_id: i32, user_profile_id: i32) -> DirectoryRow {
+        DirectoryRow {
+            workplace_id,
+            hospital: Some("General".to_string()),
+            ward: Some(format!("Ward {}", workplace_id)),
+            role_id,
+            role_name: format!("Role {}", role_id),
+            user_profile_id,
+            full_name: format!("User {}", user_profile_id),
+            short_name: format!("U{}", user_profile_id),
+            color: None,
+            primary_email: Some(format!("u{}@example.org", user_profile_id)),
+            tel: None,
+        }
+    }
+
+    #[test]
+    fn test_group_and_redact_directory() {
+        let mut directory = group_directory(vec![row(1, 10, 100), row(1, 10, 101), row(1, 11, 100), row(2, 20, 102)]);
+        assert_eq!(directory.len(), 2);
+        assert_eq!(directory[0].roles.len(), 2);
+        assert_eq!(directory[0].roles[0].members.len(), 2);
+
+        redact_contacts(&mut directory, 101, |role_id| role_id == 11);
+        let contact = |w: usize, r: usize, m: usize| directory[w].roles[r].members[m].contact.is_some();
+        assert!(!contact(0, 0, 0)); // another user, role without permission
+        assert!(contact(0, 0, 1)); // the viewer themselves
+        assert!(contact(0, 1, 0)); // role with can_view_staff_details
+        assert!(!contact(1, 0, 0));
+    }
+}
@@ -0,0 +1,229 @@
+use axum::{
+    extract::{Path, State},
+};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::{
+    extractors::{AuthenticatedUser, Json},
+    features,
+    models::{CreateFeatureFlagInput, FeatureFlag, FeatureFlagMutationResponse, UpdateFeatureFlagInput},
+    AppError, AppResult, AppState,
+};
+
+fn require_super_admin(auth: &AuthenticatedUser) -> AppResult<()> {
+    if !auth.is_super_admin {
+        return Err(AppError::Forbidden("Super admin permission required".to_string()));
+    }
+    Ok(())
+}
+
+fn validate_key(key: &str) -> AppResult<()> {
+    let valid = (1..=64).contains(&key.len())
+        && key.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if !valid {
+        return Err(AppError::BadRequest(
+            "key must be 1-64 lowercase letters, digits or underscores".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_rollout(rollout_percent: i16) -> AppResult<()> {
+    if !(0..=100).contains(&rollout_percent) {
+        return Err(AppError::BadRequest("rollout_percent must be between 0 and 100".to_string()));
+    }
+    Ok(())
+}
+
+/// GET /api/features - Feature switches resolved for the caller
+#[utoipa::path(
+    get,
+    path = "/api/features",
+    responses(
+        (status = 200, description = "Map of feature key to whether it is on for the caller (known features plus every flag)", body = BTreeMap<String, bool>)
+    ),
+    tag = "features",
+    security(("cookie_auth" = []))
+)]
+pub async fn get_features(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+) -> AppResult<Json<BTreeMap<String, bool>>> {
+    let features = features::features_for_user(&state, auth.profile_id).await?;
+    Ok(Json(features))
+}
+
+/// GET /api/features/flags - All feature flags with their targeting
+#[utoipa::path(
+    get,
+    path = "/api/features/flags",
+    responses(
+        (status = 200, description = "Feature flags, by key", body = Vec<FeatureFlag>),
+        (status = 403, description = "Super admin permission required")
+    ),
+    tag = "features",
+    security(("cookie_auth" = []))
+)]
+pub async fn get_feature_flags(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+) -> AppResult<Json<Vec<FeatureFlag>>> {
+    require_super_admin(&auth)?;
+
+    let flags = features::load_flags(&state).await?;
+    Ok(Json(flags))
+}
+
+/// POST /api/features/flags - Create a feature flag
+#[utoipa::path(
+    post,
+    path = "/api/features/flags",
+    request_body = CreateFeatureFlagInput,
+    responses(
+        (status = 200, description = "Feature flag created", body = FeatureFlag),
+        (status = 400, description = "Invalid key or rollout_percent"),
+        (status = 403, description = "Super admin permission required"),
+        (status = 409, description = "A flag with this key already exists")
+    ),
+    tag = "features",
+    security(("cookie_auth" = []))
+)]
+pub async fn create_feature_flag(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+    Json(input): Json<CreateFeatureFlagInput>,
+) -> AppResult<Json<FeatureFlag>> {
+    require_super_admin(&auth)?;
+    validate_key(&input.key)?;
+    validate_rollout(input.rollout_percent)?;
+
+    let flag = sqlx::query_as::<_, FeatureFlag>(
+        r#"
+        INSERT INTO "FeatureFlags" (key, description, enabled, workplace_ids, user_profile_ids, rollout_percent, updated_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        ON CONFLICT (key) DO NOTHING
+        RETURNING *
+        "#,
+    )
+    .bind(&input.key)
+    .bind(&input.description)
+    .bind(input.enabled)
+    .bind(&input.workplace_ids)
+    .bind(&input.user_profile_ids)
+    .bind(input.rollout_percent)
+    .bind(auth.profile_id)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::Conflict(format!("Feature flag {} already exists", input.key)))?;
+
+    state.caches.feature_flags.invalidate().await;
+    tracing::info!(key = %flag.key, admin_id = auth.profile_id, "🚩 Feature flag created");
+
+    Ok(Json(flag))
+}
+
+/// PUT /api/features/flags/{key} - Update a feature flag
+#[utoipa::path(
+    put,
+    path = "/api/features/flags/{key}",
+    params(
+        ("key" = String, Path, description = "Feature flag key")
+    ),
+    request_body = UpdateFeatureFlagInput,
+    responses(
+        (status = 200, description = "Feature flag updated", body = FeatureFlag),
+        (status = 400, description = "Invalid rollout_percent"),
+        (status = 403, description = "Super admin permission required"),
+        (status = 404, description = "Feature flag not found")
+    ),
+    tag = "features",
+    security(("cookie_auth" = []))
+)]
+pub async fn update_feature_flag(
+    State(state): State<Arc<AppState>>,
+    Path(key): Path<String>,
+    auth: AuthenticatedUser,
+    Json(input): Json<UpdateFeatureFlagInput>,
+) -> AppResult<Json<FeatureFlag>> {
+    require_super_admin(&auth)?;
+    if let Some(rollout_percent) = input.rollout_percent {
+        validate_rollout(rollout_percent)?;
+    }
+
+    let flag = sqlx::query_as::<_, FeatureFlag>(
+        r#"
+        UPDATE "FeatureFlags" SET
+            description = COALESCE($2, description),
+            enabled = COALESCE($3, enabled),
+            workplace_ids = COALESCE($4, workplace_ids),
+            user_profile_ids = COALESCE($5, user_profile_ids),
+            rollout_percent = COALESCE($6, rollout_percent),
+            updated_by = $7,
+            updated_at = NOW()
+        WHERE key = $1
+        RETURNING *
+        "#,
+    )
+    .bind(&key)
+    .bind(&input.description)
+    .bind(input.enabled)
+    .bind(&input.workplace_ids)
+    .bind(&input.user_profile_ids)
+    .bind(input.rollout_percent)
+    .bind(auth.profile_id)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Feature flag {} not found", key)))?;
+
+    state.caches.feature_flags.invalidate().await;
+    tracing::info!(
+        key = %flag.key,
+        enabled = flag.enabled,
+        rollout_percent = flag.rollout_percent,
+        admin_id = auth.profile_id,
+        "🚩 Feature flag updated"
+    );
+
+    Ok(Json(flag))
+}
+
+/// DELETE /api/features/flags/{key} - Delete a feature flag (known features fall back to their default)
+#[utoipa::path(
+    delete,
+    path = "/api/features/flags/{key}",
+    params(
+        ("key" = String, Path, description = "Feature flag key")
+    ),
+    responses(
+        (status = 200, description = "Feature flag deleted", body = FeatureFlagMutationResponse),
+        (status = 403, description = "Super admin permission required"),
+        (status = 404, description = "Feature flag not found")
+    ),
+    tag = "features",
+    security(("cookie_auth" = []))
+)]
+pub async fn delete_feature_flag(
+    State(state): State<Arc<AppState>>,
+    Path(key): Path<String>,
+    auth: AuthenticatedUser,
+) -> AppResult<Json<FeatureFlagMutationResponse>> {
+    require_super_admin(&auth)?;
+
+    let result = sqlx::query(r#"DELETE FROM "FeatureFlags" WHERE key = $1"#)
+        .bind(&key)
+        .execute(&state.db)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound(format!("Feature flag {} not found", key)));
+    }
+
+    state.caches.feature_flags.invalidate().await;
+    tracing::info!(key = %key, admin_id = auth.profile_id, "🚩 Feature flag deleted");
+
+    Ok(Json(FeatureFlagMutationResponse {
+        success: true,
+        message: Some("Feature flag deleted successfully".to_string()),
+    }))
+}
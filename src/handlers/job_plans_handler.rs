@@ -1,15 +1,19 @@
 use axum::{
     extract::{Path, Query, State},
-    Json,
 };
-use chrono::Utc;
+use chrono::{NaiveDate, Utc};
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::sync::Arc;
 use utoipa::IntoParams;
 
 use crate::{
-    extractors::{permissions, AuthenticatedUser},
-    models::{CreateJobPlanInput, JobPlan, JobPlanMutationResponse, UpdateJobPlanInput},
+    extractors::{permissions, AuthenticatedUser, CanEditStaff, Json, RequirePermission},
+    models::{
+        CreateJobPlanFromTemplateInput, CreateJobPlanInput, CreateJobPlanTemplateInput, JobPlan, JobPlanIssue,
+        JobPlanIssueKind, JobPlanMutationResponse, JobPlanTemplate, UpdateJobPlanInput, UpdateJobPlanTemplateInput,
+    },
+    report::{Report, ReportFormat},
     AppError, AppResult, AppState,
 };
 
@@ -38,7 +42,7 @@ pub async fn get_job_plans(
 ) -> AppResult<Json<Vec<JobPlan>>> {
     // Check permission
     let has_perm = permissions::has_permission(
-        &state.db,
+        &state,
         auth.profile_id,
         auth.is_super_admin,
         permissions::can_edit_staff,
@@ -96,6 +100,183 @@ pub async fn get_job_plans(
     Ok(Json(job_plans))
 }
 
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct GetJobPlanIssuesQuery {
+    pub role_id: Option<i32>,
+}
+
+/// GET /api/job-plans/issues?role_id= - Users whose job plans overlap or leave gaps
+#[utoipa::path(
+    get,
+    path = "/api/job-plans/issues",
+    params(GetJobPlanIssuesQuery),
+    responses(
+        (status = 200, description = "Overlaps and gaps between consecutive job plans per user and role", content(
+            (Vec<JobPlanIssue> = "application/json"),
+            (String = "text/csv"),
+            (Vec<u8> = "application/pdf")
+        )),
+        (status = 403, description = "Missing can_edit_staff permission"),
+        (status = 406, description = "Accept names no supported format")
+    ),
+    tag = "job-plans",
+    security(("cookie_auth" = ["can_edit_staff"]))
+)]
+pub async fn get_job_plan_issues(
+    State(state): State<Arc<AppState>>,
+    _: RequirePermission<CanEditStaff>,
+    format: ReportFormat,
+    Query(query): Query<GetJobPlanIssuesQuery>,
+) -> AppResult<Report<Vec<JobPlanIssue>>> {
+    let plans = sqlx::query_as::<_, JobPlan>(
+        r#"
+        SELECT
+            id,
+            role_id,
+            user_profile_id,
+            dcc_pa,
+            dcc_hour,
+            spa_pa,
+            spa_hour,
+            al_per_year,
+            sl_per_year,
+            pl_per_year,
+            "from",
+            until,
+            comment
+        FROM "JobPlans"
+        WHERE ($1::int4 IS NULL OR role_id = $1)
+        ORDER BY user_profile_id, role_id, "from"
+        "#,
+    )
+    .bind(query.role_id)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Report::new(format, "Job plan issues", find_job_plan_issues(plans)))
+}
+
+/// Walk each user+role's plans in start-date order, comparing every plan with the
+/// furthest-reaching plan before it. Bounds are inclusive; `until = None` is open-ended.
+fn find_job_plan_issues(plans: Vec<JobPlan>) -> Vec<JobPlanIssue> {
+    let mut groups: HashMap<(i32, i32), Vec<JobPlan>> = HashMap::new();
+    for plan in plans {
+        groups.entry((plan.user_profile_id, plan.role_id)).or_default().push(plan);
+    }
+
+    let mut issues = vec![];
+
+    for ((user_profile_id, role_id), mut group) in groups {
+        group.sort_by_key(|p| (p.from, p.id));
+
+        let mut reach: Option<&JobPlan> = None;
+        for plan in &group {
+            if let Some(prev) = reach {
+                let issue = |kind, from, until| JobPlanIssue {
+                    kind,
+                    user_profile_id,
+                    role_id,
+                    first_plan_id: prev.id,
+                    second_plan_id: plan.id,
+                    from,
+                    until,
+                };
+
+                match prev.until {
+                    // Open-ended or still running when this plan starts
+                    None => issues.push(issue(JobPlanIssueKind::Overlap, plan.from, plan.until)),
+                    Some(prev_until) if plan.from <= prev_until => {
+                        let until = plan.until.map_or(prev_until, |u| u.min(prev_until));
+                        issues.push(issue(JobPlanIssueKind::Overlap, plan.from, Some(until)));
+                    }
+                    Some(prev_until) => {
+                        if let (Some(gap_from), Some(gap_until)) = (prev_until.succ_opt(), plan.from.pred_opt()) {
+                            if gap_from <= gap_until {
+                                issues.push(issue(JobPlanIssueKind::Gap, gap_from, Some(gap_until)));
+                            }
+                        }
+                    }
+                }
+            }
+
+            // Keep whichever plan runs furthest into the future
+            let extends = match reach {
+                None => true,
+                Some(prev) => match (prev.until, plan.until) {
+                    (None, _) => false,
+                    (Some(_), None) => true,
+                    (Some(a), Some(b)) => b > a,
+                },
+            };
+            if extends {
+                reach = Some(plan);
+            }
+        }
+    }
+
+    issues.sort_by_key(|i| (i.user_profile_id, i.role_id, i.from));
+    issues
+}
+
+/// Reject a plan whose dates overlap another plan for the same user and role (409 with that plan)
+async fn ensure_no_overlap(
+    db: &sqlx::PgPool,
+    user_profile_id: i32,
+    role_id: i32,
+    from: NaiveDate,
+    until: Option<NaiveDate>,
+    exclude_id: Option<i32>,
+) -> AppResult<()> {
+    if let Some(until) = until {
+        if until < from {
+            return Err(AppError::BadRequest("until must not be before from".to_string()));
+        }
+    }
+
+    let conflicting = sqlx::query_as::<_, JobPlan>(
+        r#"
+        SELECT
+            id,
+            role_id,
+            user_profile_id,
+            dcc_pa,
+            dcc_hour,
+            spa_pa,
+            spa_hour,
+            al_per_year,
+            sl_per_year,
+            pl_per_year,
+            "from",
+            until,
+            comment
+        FROM "JobPlans"
+        WHERE user_profile_id = $1
+          AND role_id = $2
+          AND ($5::int4 IS NULL OR id <> $5)
+          AND "from" <= COALESCE($4::DATE, 'infinity'::DATE)
+          AND COALESCE(until, 'infinity'::DATE) >= $3::DATE
+        ORDER BY "from"
+        LIMIT 1
+        "#,
+    )
+    .bind(user_profile_id)
+    .bind(role_id)
+    .bind(from)
+    .bind(until)
+    .bind(exclude_id)
+    .fetch_optional(db)
+    .await?;
+
+    if let Some(plan) = conflicting {
+        return Err(AppError::ConflictWith {
+            message: format!("Job plan overlaps existing plan {} for this user and role", plan.id),
+            conflict: serde_json::to_value(&plan).map_err(|e| AppError::Internal(e.to_string()))?,
+        });
+    }
+
+    Ok(())
+}
+
 /// POST /api/job-plans - Create a new job plan
 #[utoipa::path(
     post,
@@ -103,22 +284,26 @@ pub async fn get_job_plans(
     request_body = CreateJobPlanInput,
     responses(
         (status = 200, description = "Job plan created successfully", body = JobPlan),
-        (status = 403, description = "Missing can_edit_staff permission")
+        (status = 400, description = "until is before from"),
+        (status = 403, description = "Missing can_edit_staff permission"),
+        (status = 409, description = "Overlaps an existing plan for the same user and role (returned under conflict)")
     ),
     tag = "job-plans",
-    security(("cookie_auth" = []))
+    security(("cookie_auth" = ["can_edit_staff"]))
 )]
 pub async fn create_job_plan(
     State(state): State<Arc<AppState>>,
-    auth: AuthenticatedUser,
+    _: RequirePermission<CanEditStaff>,
     Json(input): Json<CreateJobPlanInput>,
 ) -> AppResult<Json<JobPlan>> {
-    // Check permission
-    if !crate::extractors::permissions::has_permission_by_name(&state.db, auth.profile_id, auth.is_super_admin, "can_edit_staff").await? {
-        return Err(AppError::Forbidden(
-            "Missing can_edit_staff permission".to_string(),
-        ));
-    }
+    let job_plan = insert_job_plan(&state.db, &input).await?;
+
+    Ok(Json(job_plan))
+}
+
+/// Insert a plan after checking it does not overlap the user's other plans for the role
+async fn insert_job_plan(db: &sqlx::PgPool, input: &CreateJobPlanInput) -> AppResult<JobPlan> {
+    ensure_no_overlap(db, input.user_profile_id, input.role_id, input.from, input.until, None).await?;
 
     let job_plan = sqlx::query_as::<_, JobPlan>(
         r#"
@@ -155,10 +340,10 @@ pub async fn create_job_plan(
     .bind(input.from)
     .bind(input.until)
     .bind(&input.comment)
-    .fetch_one(&state.db)
+    .fetch_one(db)
     .await?;
 
-    Ok(Json(job_plan))
+    Ok(job_plan)
 }
 
 /// PUT /api/job-plans/{id} - Update a job plan
@@ -171,26 +356,20 @@ pub async fn create_job_plan(
     request_body = UpdateJobPlanInput,
     responses(
         (status = 200, description = "Job plan updated successfully", body = JobPlan),
-        (status = 400, description = "No fields to update"),
+        (status = 400, description = "No fields to update or until is before from"),
         (status = 403, description = "Missing can_edit_staff permission"),
-        (status = 404, description = "Job plan not found")
+        (status = 404, description = "Job plan not found"),
+        (status = 409, description = "Overlaps an existing plan for the same user and role (returned under conflict)")
     ),
     tag = "job-plans",
-    security(("cookie_auth" = []))
+    security(("cookie_auth" = ["can_edit_staff"]))
 )]
 pub async fn update_job_plan(
     State(state): State<Arc<AppState>>,
     Path(job_plan_id): Path<i32>,
-    auth: AuthenticatedUser,
+    _: RequirePermission<CanEditStaff>,
     Json(input): Json<UpdateJobPlanInput>,
 ) -> AppResult<Json<JobPlan>> {
-    // Check permission
-    if !crate::extractors::permissions::has_permission_by_name(&state.db, auth.profile_id, auth.is_super_admin, "can_edit_staff").await? {
-        return Err(AppError::Forbidden(
-            "Missing can_edit_staff permission".to_string(),
-        ));
-    }
-
     // Build dynamic UPDATE query
     let mut updates = vec![];
     let mut bind_count = 1;
@@ -248,6 +427,24 @@ pub async fn update_job_plan(
         return Err(AppError::BadRequest("No fields to update".to_string()));
     }
 
+    // Check the plan's resulting dates against the user's other plans
+    let (current_role_id, current_user_id, current_from, current_until): (i32, i32, NaiveDate, Option<NaiveDate>) =
+        sqlx::query_as(r#"SELECT role_id, user_profile_id, "from", until FROM "JobPlans" WHERE id = $1"#)
+            .bind(job_plan_id)
+            .fetch_optional(&state.db)
+            .await?
+            .ok_or_else(|| AppError::NotFound(format!("Job plan {} not found", job_plan_id)))?;
+
+    ensure_no_overlap(
+        &state.db,
+        input.user_profile_id.unwrap_or(current_user_id),
+        input.role_id.unwrap_or(current_role_id),
+        input.from.unwrap_or(current_from),
+        input.until.or(current_until),
+        Some(job_plan_id),
+    )
+    .await?;
+
     let sql = format!(
         r#"
         UPDATE "JobPlans"
@@ -332,20 +529,13 @@ pub async fn update_job_plan(
         (status = 404, description = "Job plan not found")
     ),
     tag = "job-plans",
-    security(("cookie_auth" = []))
+    security(("cookie_auth" = ["can_edit_staff"]))
 )]
 pub async fn delete_job_plan(
     State(state): State<Arc<AppState>>,
     Path(job_plan_id): Path<i32>,
-    auth: AuthenticatedUser,
+    _: RequirePermission<CanEditStaff>,
 ) -> AppResult<Json<JobPlanMutationResponse>> {
-    // Check permission
-    if !crate::extractors::permissions::has_permission_by_name(&state.db, auth.profile_id, auth.is_super_admin, "can_edit_staff").await? {
-        return Err(AppError::Forbidden(
-            "Missing can_edit_staff permission".to_string(),
-        ));
-    }
-
     let result = sqlx::query(r#"DELETE FROM "JobPlans" WHERE id = $1"#)
         .bind(job_plan_id)
         .execute(&state.db)
@@ -377,20 +567,13 @@ pub async fn delete_job_plan(
         (status = 404, description = "Job plan not found")
     ),
     tag = "job-plans",
-    security(("cookie_auth" = []))
+    security(("cookie_auth" = ["can_edit_staff"]))
 )]
 pub async fn terminate_job_plan(
     State(state): State<Arc<AppState>>,
     Path(job_plan_id): Path<i32>,
-    auth: AuthenticatedUser,
+    _: RequirePermission<CanEditStaff>,
 ) -> AppResult<Json<JobPlan>> {
-    // Check permission
-    if !crate::extractors::permissions::has_permission_by_name(&state.db, auth.profile_id, auth.is_super_admin, "can_edit_staff").await? {
-        return Err(AppError::Forbidden(
-            "Missing can_edit_staff permission".to_string(),
-        ));
-    }
-
     // Set 'until' to today
     let today = Utc::now().date_naive();
 
@@ -421,4 +604,432 @@ pub async fn terminate_job_plan(
     .await?;
 
     Ok(Json(updated_plan))
-}
\ No newline at end of file
+}
+
+const TEMPLATE_COLUMNS: &str = r#"
+    id,
+    workplace_id AS workplace,
+    role_id,
+    label,
+    dcc_pa,
+    dcc_hour,
+    spa_pa,
+    spa_hour,
+    al_per_year,
+    sl_per_year,
+    pl_per_year
+"#;
+
+/// PAs, hours and leave allowances cannot be negative
+fn check_template_values(values: &[Option<f32>]) -> AppResult<()> {
+    if values.iter().flatten().any(|v| !v.is_finite() || *v < 0.0) {
+        return Err(AppError::BadRequest("PAs, hours and leave allowances must be zero or more".to_string()));
+    }
+    Ok(())
+}
+
+/// The plan a template gives for a user; leave allowances the template leaves out default to 0
+fn plan_from_template(template: &JobPlanTemplate, input: &CreateJobPlanFromTemplateInput) -> AppResult<CreateJobPlanInput> {
+    let role_id = match (template.role_id, input.role_id) {
+        (Some(template_role), Some(role_id)) if template_role != role_id => {
+            return Err(AppError::BadRequest(format!("Template {} is for role {}", template.id, template_role)));
+        }
+        (Some(role_id), _) | (None, Some(role_id)) => role_id,
+        (None, None) => {
+            return Err(AppError::BadRequest("role_id is required for a workplace-wide template".to_string()));
+        }
+    };
+
+    Ok(CreateJobPlanInput {
+        role_id,
+        user_profile_id: input.user_profile_id,
+        dcc_pa: template.dcc_pa,
+        dcc_hour: template.dcc_hour,
+        spa_pa: template.spa_pa,
+        spa_hour: template.spa_hour,
+        al_per_year: template.al_per_year.unwrap_or(0.0),
+        sl_per_year: template.sl_per_year.unwrap_or(0.0),
+        pl_per_year: template.pl_per_year.unwrap_or(0.0),
+        from: input.from,
+        until: input.until,
+        comment: input.comment.clone(),
+    })
+}
+
+async fn fetch_template(db: &sqlx::PgPool, template_id: i32) -> AppResult<JobPlanTemplate> {
+    let sql = format!(r#"SELECT {} FROM "JobPlanTemplates" WHERE id = $1"#, TEMPLATE_COLUMNS);
+    sqlx::query_as::<_, JobPlanTemplate>(&sql)
+        .bind(template_id)
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Job plan template {} not found", template_id)))
+}
+
+/// Role templates need can_edit_staff in that role; workplace-wide ones a super admin
+async fn require_template_editor(state: &AppState, auth: &AuthenticatedUser, role_id: Option<i32>) -> AppResult<()> {
+    let allowed = match role_id {
+        Some(role_id) => {
+            permissions::has_permission(state, auth.profile_id, auth.is_super_admin, |r| {
+                r.role_id == role_id && r.can_edit_staff
+            })
+            .await?
+        }
+        None => auth.is_super_admin,
+    };
+    if !allowed {
+        return Err(AppError::Forbidden("Missing can_edit_staff permission for this role".to_string()));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct GetJobPlanTemplatesQuery {
+    pub role_id: i32,
+}
+
+/// GET /api/job-plans/templates?role_id= - Templates for a role, then those shared by its workplace
+#[utoipa::path(
+    get,
+    path = "/api/job-plans/templates",
+    params(GetJobPlanTemplatesQuery),
+    responses(
+        (status = 200, description = "The role's templates followed by workplace-wide ones", body = Vec<JobPlanTemplate>),
+        (status = 403, description = "Missing can_edit_staff permission")
+    ),
+    tag = "job-plans",
+    security(("cookie_auth" = ["can_edit_staff"]))
+)]
+pub async fn get_job_plan_templates(
+    State(state): State<Arc<AppState>>,
+    _: RequirePermission<CanEditStaff>,
+    Query(query): Query<GetJobPlanTemplatesQuery>,
+) -> AppResult<Json<Vec<JobPlanTemplate>>> {
+    let sql = format!(
+        r#"
+        SELECT {}
+        FROM "JobPlanTemplates"
+        WHERE role_id = $1
+           OR (role_id IS NULL AND workplace_id = (SELECT workplace_id FROM "Roles" WHERE id = $1))
+        ORDER BY role_id NULLS LAST, label
+        "#,
+        TEMPLATE_COLUMNS
+    );
+    let templates = sqlx::query_as::<_, JobPlanTemplate>(&sql)
+        .bind(query.role_id)
+        .fetch_all(&state.db)
+        .await?;
+
+    Ok(Json(templates))
+}
+
+/// POST /api/job-plans/templates - Create a job plan template for a role
+#[utoipa::path(
+    post,
+    path = "/api/job-plans/templates",
+    request_body = CreateJobPlanTemplateInput,
+    responses(
+        (status = 200, description = "Template created", body = JobPlanTemplate),
+        (status = 400, description = "Empty label or a negative value"),
+        (status = 403, description = "Missing can_edit_staff permission for this role"),
+        (status = 404, description = "Role not found")
+    ),
+    tag = "job-plans",
+    security(("cookie_auth" = ["can_edit_staff"]))
+)]
+pub async fn create_job_plan_template(
+    State(state): State<Arc<AppState>>,
+    RequirePermission { auth, .. }: RequirePermission<CanEditStaff>,
+    Json(input): Json<CreateJobPlanTemplateInput>,
+) -> AppResult<Json<JobPlanTemplate>> {
+    require_template_editor(&state, &auth, Some(input.role_id)).await?;
+    if input.label.trim().is_empty() {
+        return Err(AppError::BadRequest("label is required".to_string()));
+    }
+    check_template_values(&[
+        input.dcc_pa,
+        input.dcc_hour,
+        input.spa_pa,
+        input.spa_hour,
+        input.al_per_year,
+        input.sl_per_year,
+        input.pl_per_year,
+    ])?;
+
+    let sql = format!(
+        r#"
+        INSERT INTO "JobPlanTemplates" (
+            workplace_id, role_id, label, dcc_pa, dcc_hour, spa_pa, spa_hour, al_per_year, sl_per_year, pl_per_year
+        )
+        SELECT workplace_id, id, $2, $3, $4, $5, $6, $7, $8, $9
+        FROM "Roles"
+        WHERE id = $1
+        RETURNING {}
+        "#,
+        TEMPLATE_COLUMNS
+    );
+    let template = sqlx::query_as::<_, JobPlanTemplate>(&sql)
+        .bind(input.role_id)
+        .bind(input.label.trim())
+        .bind(input.dcc_pa)
+        .bind(input.dcc_hour)
+        .bind(input.spa_pa)
+        .bind(input.spa_hour)
+        .bind(input.al_per_year)
+        .bind(input.sl_per_year)
+        .bind(input.pl_per_year)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Role {} not found", input.role_id)))?;
+
+    Ok(Json(template))
+}
+
+/// PUT /api/job-plans/templates/{id} - Update a job plan template
+#[utoipa::path(
+    put,
+    path = "/api/job-plans/templates/{id}",
+    params(
+        ("id" = i32, Path, description = "Job plan template ID")
+    ),
+    request_body = UpdateJobPlanTemplateInput,
+    responses(
+        (status = 200, description = "Template updated", body = JobPlanTemplate),
+        (status = 400, description = "Empty label or a negative value"),
+        (status = 403, description = "Missing can_edit_staff permission for the template's role (super admin for workplace-wide templates)"),
+        (status = 404, description = "Job plan template not found")
+    ),
+    tag = "job-plans",
+    security(("cookie_auth" = ["can_edit_staff"]))
+)]
+pub async fn update_job_plan_template(
+    State(state): State<Arc<AppState>>,
+    Path(template_id): Path<i32>,
+    RequirePermission { auth, .. }: RequirePermission<CanEditStaff>,
+    Json(input): Json<UpdateJobPlanTemplateInput>,
+) -> AppResult<Json<JobPlanTemplate>> {
+    let existing = fetch_template(&state.db, template_id).await?;
+    require_template_editor(&state, &auth, existing.role_id).await?;
+    if input.label.as_deref().is_some_and(|l| l.trim().is_empty()) {
+        return Err(AppError::BadRequest("label must not be empty".to_string()));
+    }
+    check_template_values(&[
+        input.dcc_pa,
+        input.dcc_hour,
+        input.spa_pa,
+        input.spa_hour,
+        input.al_per_year,
+        input.sl_per_year,
+        input.pl_per_year,
+    ])?;
+
+    let sql = format!(
+        r#"
+        UPDATE "JobPlanTemplates" SET
+            label = COALESCE($2, label),
+            dcc_pa = COALESCE($3, dcc_pa),
+            dcc_hour = COALESCE($4, dcc_hour),
+            spa_pa = COALESCE($5, spa_pa),
+            spa_hour = COALESCE($6, spa_hour),
+            al_per_year = COALESCE($7, al_per_year),
+            sl_per_year = COALESCE($8, sl_per_year),
+            pl_per_year = COALESCE($9, pl_per_year)
+        WHERE id = $1
+        RETURNING {}
+        "#,
+        TEMPLATE_COLUMNS
+    );
+    let template = sqlx::query_as::<_, JobPlanTemplate>(&sql)
+        .bind(template_id)
+        .bind(input.label.as_deref().map(str::trim))
+        .bind(input.dcc_pa)
+        .bind(input.dcc_hour)
+        .bind(input.spa_pa)
+        .bind(input.spa_hour)
+        .bind(input.al_per_year)
+        .bind(input.sl_per_year)
+        .bind(input.pl_per_year)
+        .fetch_one(&state.db)
+        .await?;
+
+    Ok(Json(template))
+}
+
+/// DELETE /api/job-plans/templates/{id} - Delete a job plan template (plans made from it are kept)
+#[utoipa::path(
+    delete,
+    path = "/api/job-plans/templates/{id}",
+    params(
+        ("id" = i32, Path, description = "Job plan template ID")
+    ),
+    responses(
+        (status = 200, description = "Template deleted", body = JobPlanMutationResponse),
+        (status = 403, description = "Missing can_edit_staff permission for the template's role (super admin for workplace-wide templates)"),
+        (status = 404, description = "Job plan template not found")
+    ),
+    tag = "job-plans",
+    security(("cookie_auth" = ["can_edit_staff"]))
+)]
+pub async fn delete_job_plan_template(
+    State(state): State<Arc<AppState>>,
+    Path(template_id): Path<i32>,
+    RequirePermission { auth, .. }: RequirePermission<CanEditStaff>,
+) -> AppResult<Json<JobPlanMutationResponse>> {
+    let existing = fetch_template(&state.db, template_id).await?;
+    require_template_editor(&state, &auth, existing.role_id).await?;
+
+    sqlx::query(r#"DELETE FROM "JobPlanTemplates" WHERE id = $1"#)
+        .bind(template_id)
+        .execute(&state.db)
+        .await?;
+
+    Ok(Json(JobPlanMutationResponse {
+        success: true,
+        message: Some("Job plan template deleted successfully".to_string()),
+    }))
+}
+
+/// POST /api/job-plans/from-template - Create a user's job plan from a template
+#[utoipa::path(
+    post,
+    path = "/api/job-plans/from-template",
+    request_body = CreateJobPlanFromTemplateInput,
+    responses(
+        (status = 200, description = "Job plan created with the template's PAs, hours and leave allowances", body = JobPlan),
+        (status = 400, description = "role_id missing for a workplace-wide template, not the template's role or workplace, or until is before from"),
+        (status = 403, description = "Missing can_edit_staff permission"),
+        (status = 404, description = "Job plan template not found"),
+        (status = 409, description = "Overlaps an existing plan for the same user and role (returned under conflict)")
+    ),
+    tag = "job-plans",
+    security(("cookie_auth" = ["can_edit_staff"]))
+)]
+pub async fn create_job_plan_from_template(
+    State(state): State<Arc<AppState>>,
+    _: RequirePermission<CanEditStaff>,
+    Json(input): Json<CreateJobPlanFromTemplateInput>,
+) -> AppResult<Json<JobPlan>> {
+    let template = fetch_template(&state.db, input.template_id).await?;
+    let plan = plan_from_template(&template, &input)?;
+
+    if template.role_id.is_none() {
+        let same_workplace: bool = sqlx::query_scalar(
+            r#"SELECT EXISTS (SELECT 1 FROM "Roles" WHERE id = $1 AND workplace_id = $2)"#,
+        )
+        .bind(plan.role_id)
+        .bind(template.workplace)
+        .fetch_one(&state.db)
+        .await?;
+        if !same_workplace {
+            return Err(AppError::BadRequest(format!(
+                "Role {} is not in template {}'s workplace",
+                plan.role_id, template.id
+            )));
+        }
+    }
+
+    let job_plan = insert_job_plan(&state.db, &plan).await?;
+    tracing::info!(
+        job_plan_id = job_plan.id,
+        template_id = template.id,
+        user_profile_id = job_plan.user_profile_id,
+        "📋 Job plan created from template"
+    );
+
+    Ok(Json(job_plan))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plan(id: i32, from: &str, until: Option<&str>) -> JobPlan {
+        let date = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+        JobPlan {
+            id,
+            role_id: 1,
+            user_profile_id: 10,
+            dcc_pa: None,
+            dcc_hour: None,
+            spa_pa: None,
+            spa_hour: None,
+            al_per_year: 0.0,
+            sl_per_year: 0.0,
+            pl_per_year: 0.0,
+            from: date(from),
+            until: until.map(date),
+            comment: None,
+        }
+    }
+
+    #[test]
+    fn test_plan_from_template() {
+        let template = JobPlanTemplate {
+            id: 3,
+            workplace: 1,
+            role_id: Some(5),
+            label: "ST3 registrar".to_string(),
+            al_per_year: Some(27.0),
+            sl_per_year: None,
+            pl_per_year: None,
+            dcc_pa: Some(7.5),
+            dcc_hour: None,
+            spa_pa: Some(2.5),
+            spa_hour: None,
+        };
+        let input = CreateJobPlanFromTemplateInput {
+            template_id: 3,
+            user_profile_id: 10,
+            role_id: None,
+            from: NaiveDate::from_ymd_opt(2026, 8, 5).unwrap(),
+            until: None,
+            comment: None,
+        };
+
+        let plan = plan_from_template(&template, &input).unwrap();
+        assert_eq!(plan.role_id, 5);
+        assert_eq!(plan.dcc_pa, Some(7.5));
+        assert_eq!(plan.al_per_year, 27.0);
+        assert_eq!(plan.sl_per_year, 0.0);
+
+        assert!(plan_from_template(&template, &CreateJobPlanFromTemplateInput { role_id: Some(6), ..input.clone() }).is_err());
+        let shared = JobPlanTemplate { role_id: None, ..template };
+        assert!(plan_from_template(&shared, &input).is_err());
+        assert_eq!(plan_from_template(&shared, &CreateJobPlanFromTemplateInput { role_id: Some(6), ..input }).unwrap().role_id, 6);
+    }
+
+    #[test]
+    fn test_contiguous_plans_have_no_issues() {
+        let plans = vec![
+            plan(1, "2025-01-01", Some("2025-06-30")),
+            plan(2, "2025-07-01", None),
+        ];
+        assert!(find_job_plan_issues(plans).is_empty());
+    }
+
+    #[test]
+    fn test_detects_overlap_and_gap() {
+        let plans = vec![
+            plan(1, "2025-01-01", Some("2025-03-31")),
+            plan(2, "2025-03-15", Some("2025-04-30")),
+            plan(3, "2025-06-01", None),
+        ];
+        let issues = find_job_plan_issues(plans);
+
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0].kind, JobPlanIssueKind::Overlap);
+        assert_eq!((issues[0].first_plan_id, issues[0].second_plan_id), (1, 2));
+        assert_eq!(issues[0].until, NaiveDate::from_ymd_opt(2025, 3, 31));
+        assert_eq!(issues[1].kind, JobPlanIssueKind::Gap);
+        assert_eq!(issues[1].from, NaiveDate::from_ymd_opt(2025, 5, 1).unwrap());
+        assert_eq!(issues[1].until, NaiveDate::from_ymd_opt(2025, 5, 31));
+    }
+
+    #[test]
+    fn test_open_ended_plan_overlaps_later_plans() {
+        let plans = vec![plan(1, "2025-01-01", None), plan(2, "2026-01-01", Some("2026-12-31"))];
+        let issues = find_job_plan_issues(plans);
+
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].kind, JobPlanIssueKind::Overlap);
+    }
+}
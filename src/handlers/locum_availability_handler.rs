@@ -0,0 +1,382 @@
+use axum::{
+    extract::{Path, Query, State},
+};
+use chrono::NaiveDate;
+use serde::Deserialize;
+use std::sync::Arc;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use crate::{
+    extractors::{AuthenticatedUser, CanEditRota, Json, RequirePermission},
+    models::{AssignLocumInput, CreateAvailabilityInput, LocumAvailability, MarketplaceMutationResponse},
+    AppError, AppResult, AppState,
+};
+
+/// Most dates that can be advertised in one request
+const MAX_DATES_PER_POST: usize = 62;
+
+const AVAILABILITY_SELECT: &str = r#"
+    SELECT
+        la.id,
+        la.user_profile_id,
+        u.short_name AS user_short_name,
+        la.role_id,
+        la.date,
+        la.notes,
+        la.created_at,
+        la.assigned_shift_id,
+        la.assigned_at
+    FROM "LocumAvailability" la
+    LEFT JOIN "Users" u ON la.user_profile_id = u.user_profile_id
+"#;
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct GetAvailabilityQuery {
+    #[serde(rename = "roleId")]
+    pub role_id: Option<i32>,
+    /// First date (inclusive)
+    pub from: Option<NaiveDate>,
+    /// Last date (inclusive)
+    pub to: Option<NaiveDate>,
+    /// Only the caller's own postings (no can_edit_rota needed)
+    #[serde(default)]
+    pub mine: bool,
+    /// Include postings already consumed by an assignment
+    #[serde(rename = "includeAssigned", default)]
+    pub include_assigned: bool,
+}
+
+/// Sort and de-duplicate advertised dates, rejecting empty or oversized posts
+fn normalise_dates(mut dates: Vec<NaiveDate>) -> AppResult<Vec<NaiveDate>> {
+    dates.sort();
+    dates.dedup();
+
+    if dates.is_empty() {
+        return Err(AppError::BadRequest("At least one date is required".to_string()));
+    }
+    if dates.len() > MAX_DATES_PER_POST {
+        return Err(AppError::BadRequest(format!(
+            "At most {} dates can be posted at once",
+            MAX_DATES_PER_POST
+        )));
+    }
+
+    Ok(dates)
+}
+
+async fn can_work_shifts_for_role(state: &AppState, auth: &AuthenticatedUser, role_id: i32) -> AppResult<bool> {
+    if auth.is_super_admin {
+        return Ok(true);
+    }
+
+    let allowed: bool = sqlx::query_scalar(&format!(
+        r#"SELECT EXISTS(SELECT 1 FROM "UserRoles" WHERE user_profile_id = $1 AND role_id = $2 AND can_work_shifts = true AND {in_force})"#,
+        in_force = crate::extractors::permissions::user_role_in_force(3)
+    ))
+    .bind(auth.profile_id)
+    .bind(role_id)
+    .bind(crate::extractors::permissions::today())
+    .fetch_one(&state.db)
+    .await?;
+
+    Ok(allowed)
+}
+
+/// GET /api/marketplace/availability?roleId=&from=&to=&mine=&includeAssigned=
+#[utoipa::path(
+    get,
+    path = "/api/marketplace/availability",
+    params(GetAvailabilityQuery),
+    responses(
+        (status = 200, description = "Advertised locum availability, by date", body = Vec<LocumAvailability>),
+        (status = 403, description = "Missing can_edit_rota permission (unless mine=true)")
+    ),
+    tag = "marketplace",
+    security(("cookie_auth" = []))
+)]
+pub async fn get_availability(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+    Query(query): Query<GetAvailabilityQuery>,
+) -> AppResult<Json<Vec<LocumAvailability>>> {
+    let user_filter = if query.mine {
+        Some(auth.profile_id)
+    } else if crate::extractors::permissions::has_permission_by_name(&state, auth.profile_id, auth.is_super_admin, "can_edit_rota").await? {
+        None
+    } else {
+        return Err(AppError::Forbidden(
+            "Missing can_edit_rota permission".to_string(),
+        ));
+    };
+
+    let sql = format!(
+        r#"{}
+        WHERE ($1::int4 IS NULL OR la.role_id = $1)
+          AND ($2::date IS NULL OR la.date >= $2)
+          AND ($3::date IS NULL OR la.date <= $3)
+          AND ($4::int4 IS NULL OR la.user_profile_id = $4)
+          AND ($5 OR la.assigned_shift_id IS NULL)
+        ORDER BY la.date, u.short_name"#,
+        AVAILABILITY_SELECT
+    );
+
+    let availability = sqlx::query_as::<_, LocumAvailability>(&sql)
+        .bind(query.role_id)
+        .bind(query.from)
+        .bind(query.to)
+        .bind(user_filter)
+        .bind(query.include_assigned)
+        .fetch_all(&state.db)
+        .await?;
+
+    Ok(Json(availability))
+}
+
+/// POST /api/marketplace/availability - Advertise dates the caller can work for a role
+#[utoipa::path(
+    post,
+    path = "/api/marketplace/availability",
+    request_body = CreateAvailabilityInput,
+    responses(
+        (status = 200, description = "Postings for the requested dates (already-advertised dates are kept)", body = Vec<LocumAvailability>),
+        (status = 400, description = "No dates, or too many"),
+        (status = 403, description = "Caller cannot work shifts for this role")
+    ),
+    tag = "marketplace",
+    security(("cookie_auth" = []))
+)]
+pub async fn create_availability(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+    Json(input): Json<CreateAvailabilityInput>,
+) -> AppResult<Json<Vec<LocumAvailability>>> {
+    if !can_work_shifts_for_role(&state, &auth, input.role_id).await? {
+        return Err(AppError::Forbidden(
+            "Missing can_work_shifts permission for this role".to_string(),
+        ));
+    }
+
+    let dates = normalise_dates(input.dates)?;
+
+    let ids: Vec<i32> = sqlx::query_scalar(
+        r#"
+        INSERT INTO "LocumAvailability" (user_profile_id, role_id, date, notes)
+        SELECT $1, $2, d, $4
+        FROM UNNEST($3::date[]) AS d
+        ON CONFLICT (user_profile_id, role_id, date) DO UPDATE SET notes = EXCLUDED.notes
+        RETURNING id
+        "#,
+    )
+    .bind(auth.profile_id)
+    .bind(input.role_id)
+    .bind(&dates)
+    .bind(&input.notes)
+    .fetch_all(&state.db)
+    .await?;
+
+    tracing::info!(
+        user_profile_id = auth.profile_id,
+        role_id = input.role_id,
+        dates = dates.len(),
+        "📣 Locum availability posted"
+    );
+
+    let availability = sqlx::query_as::<_, LocumAvailability>(&format!(
+        "{} WHERE la.id = ANY($1) ORDER BY la.date",
+        AVAILABILITY_SELECT
+    ))
+    .bind(&ids)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(availability))
+}
+
+/// DELETE /api/marketplace/availability/{id} - Withdraw an unassigned posting
+#[utoipa::path(
+    delete,
+    path = "/api/marketplace/availability/{id}",
+    params(
+        ("id" = i32, Path, description = "Availability ID")
+    ),
+    responses(
+        (status = 200, description = "Posting withdrawn", body = MarketplaceMutationResponse),
+        (status = 400, description = "Posting already assigned to a shift"),
+        (status = 403, description = "Not the caller's posting and missing can_edit_rota permission"),
+        (status = 404, description = "Posting not found")
+    ),
+    tag = "marketplace",
+    security(("cookie_auth" = []))
+)]
+pub async fn delete_availability(
+    State(state): State<Arc<AppState>>,
+    Path(availability_id): Path<i32>,
+    auth: AuthenticatedUser,
+) -> AppResult<Json<MarketplaceMutationResponse>> {
+    let posting: Option<(i32, Option<Uuid>)> = sqlx::query_as(
+        r#"SELECT user_profile_id, assigned_shift_id FROM "LocumAvailability" WHERE id = $1"#,
+    )
+    .bind(availability_id)
+    .fetch_optional(&state.db)
+    .await?;
+
+    let (owner_id, assigned_shift_id) =
+        posting.ok_or_else(|| AppError::NotFound(format!("Availability {} not found", availability_id)))?;
+
+    if owner_id != auth.profile_id
+        && !crate::extractors::permissions::has_permission_by_name(&state, auth.profile_id, auth.is_super_admin, "can_edit_rota").await?
+    {
+        return Err(AppError::Forbidden(
+            "Missing can_edit_rota permission".to_string(),
+        ));
+    }
+    if assigned_shift_id.is_some() {
+        return Err(AppError::BadRequest(
+            "Availability has already been assigned to a shift".to_string(),
+        ));
+    }
+
+    sqlx::query(r#"DELETE FROM "LocumAvailability" WHERE id = $1 AND assigned_shift_id IS NULL"#)
+        .bind(availability_id)
+        .execute(&state.db)
+        .await?;
+
+    Ok(Json(MarketplaceMutationResponse {
+        success: true,
+        message: Some("Availability withdrawn".to_string()),
+    }))
+}
+
+/// POST /api/marketplace/availability/{id}/assign - Give an unfilled shift to the advertising locum
+#[utoipa::path(
+    post,
+    path = "/api/marketplace/availability/{id}/assign",
+    params(
+        ("id" = i32, Path, description = "Availability ID")
+    ),
+    request_body = AssignLocumInput,
+    responses(
+        (status = 200, description = "Locum assigned, posting consumed and locum notified", body = LocumAvailability),
+        (status = 400, description = "Shift already filled, time off, or on a different role/date"),
+        (status = 403, description = "Missing can_edit_rota permission"),
+        (status = 404, description = "Posting or shift not found"),
+        (status = 409, description = "Posting already assigned")
+    ),
+    tag = "marketplace",
+    security(("cookie_auth" = ["can_edit_rota"]))
+)]
+pub async fn assign_locum(
+    State(state): State<Arc<AppState>>,
+    Path(availability_id): Path<i32>,
+    RequirePermission { auth, .. }: RequirePermission<CanEditRota>,
+    Json(input): Json<AssignLocumInput>,
+) -> AppResult<Json<LocumAvailability>> {
+    let mut tx = crate::db::begin_as_user(&state.db, auth.profile_id).await?;
+
+    let posting: Option<(i32, i32, NaiveDate, Option<Uuid>)> = sqlx::query_as(
+        r#"SELECT user_profile_id, role_id, date, assigned_shift_id FROM "LocumAvailability" WHERE id = $1 FOR UPDATE"#,
+    )
+    .bind(availability_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let (locum_id, role_id, date, assigned_shift_id) =
+        posting.ok_or_else(|| AppError::NotFound(format!("Availability {} not found", availability_id)))?;
+
+    if assigned_shift_id.is_some() {
+        return Err(AppError::Conflict(
+            "Availability has already been assigned to a shift".to_string(),
+        ));
+    }
+
+    #[derive(sqlx::FromRow)]
+    struct TargetShift {
+        role_id: i32,
+        date: NaiveDate,
+        user_profile_id: Option<i32>,
+        time_off_category_id: Option<i32>,
+        label: String,
+    }
+
+    let shift = sqlx::query_as::<_, TargetShift>(
+        r#"SELECT role_id, date, user_profile_id, time_off_category_id, label FROM "Shifts" WHERE uuid = $1 FOR UPDATE"#,
+    )
+    .bind(input.shift_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Shift {} not found", input.shift_id)))?;
+
+    if shift.role_id != role_id || shift.date != date {
+        return Err(AppError::BadRequest(
+            "Shift is not on the role and date the locum advertised".to_string(),
+        ));
+    }
+    if shift.user_profile_id.is_some() {
+        return Err(AppError::BadRequest("Shift is already filled".to_string()));
+    }
+    if shift.time_off_category_id.is_some() {
+        return Err(AppError::BadRequest("Cannot assign a locum to time off".to_string()));
+    }
+    crate::handlers::payroll_locks_handler::ensure_unlocked(&mut tx, shift.role_id, shift.date).await?;
+
+    let before = crate::handlers::shifts_handler::shifts_by_uuid(&mut tx, &[input.shift_id]).await?;
+    sqlx::query(r#"UPDATE "Shifts" SET user_profile_id = $1, is_locum = true WHERE uuid = $2"#)
+        .bind(locum_id)
+        .bind(input.shift_id)
+        .execute(&mut *tx)
+        .await?;
+
+    sqlx::query(r#"UPDATE "LocumAvailability" SET assigned_shift_id = $1, assigned_at = NOW() WHERE id = $2"#)
+        .bind(input.shift_id)
+        .bind(availability_id)
+        .execute(&mut *tx)
+        .await?;
+
+    // A published shift gets the usual SHIFT_ASSIGNED; the booking notice covers drafts without telling them twice
+    let told = crate::handlers::shifts_handler::notify_shift_changes(&mut tx, &before, auth.profile_id).await?;
+    if !told.iter().any(|&(user_id, _)| user_id == locum_id) {
+        crate::handlers::notifications_handler::notify(
+            &mut *tx,
+            locum_id,
+            "LOCUM_ASSIGNED",
+            &format!("You have been booked for {} on {}.", shift.label, date),
+            serde_json::json!({ "availability_id": availability_id, "shift_id": input.shift_id }),
+        )
+        .await?;
+    }
+
+    tx.commit().await?;
+
+    tracing::info!(
+        availability_id,
+        shift_id = %input.shift_id,
+        locum_id,
+        assigned_by = auth.profile_id,
+        "✅ Locum assigned from availability"
+    );
+    crate::handlers::metrics::record_marketplace_event("locum_assigned");
+
+    let availability = sqlx::query_as::<_, LocumAvailability>(&format!("{} WHERE la.id = $1", AVAILABILITY_SELECT))
+        .bind(availability_id)
+        .fetch_one(&state.db)
+        .await?;
+
+    Ok(Json(availability))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalise_dates() {
+        let d = |day| NaiveDate::from_ymd_opt(2025, 3, day).unwrap();
+
+        assert_eq!(normalise_dates(vec![d(5), d(1), d(5)]).unwrap(), vec![d(1), d(5)]);
+        assert!(normalise_dates(vec![]).is_err());
+
+        let too_many: Vec<NaiveDate> = (0..63).map(|i| d(1) + chrono::Duration::days(i)).collect();
+        assert!(normalise_dates(too_many).is_err());
+    }
+}
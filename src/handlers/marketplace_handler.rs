@@ -1,8 +1,7 @@
 use axum::{
     extract::{Path, Query, State},
-    Json,
 };
-use chrono::{NaiveDate, NaiveDateTime};
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
 use serde::Deserialize;
 use sqlx::FromRow;
 use std::sync::Arc;
@@ -10,8 +9,9 @@ use utoipa::IntoParams;
 use uuid::Uuid;
 
 use crate::{
-    extractors::{permissions, AuthenticatedUser},
-    models::{AcceptRequestInput, AdminDecisionInput, CreateShiftRequestInput, MarketplaceMutationResponse, RespondToProposalInput, ShiftRequest, ShiftRequestWithDetails, SwappableShift, UserWithSwappableShifts},
+    extractors::{AuthenticatedUser, Json},
+    handlers::delegations_handler::{approval_authority, ApprovalAuthority},
+    models::{AcceptRequestInput, AdminDecisionInput, BumpRequestInput, BumpRequestResponse, CreateShiftRequestInput, ForceCancelRequestInput, ForceCancelResponse, MarketplaceMutationResponse, RespondToProposalInput, MarketplaceSort, ReasonOutcome, ShiftOfferRecipient, ShiftRequestByToken, ShiftRequestStatus, ShiftRequestType, ShiftRequestWithDetails, SwapCheck, SwapEligibility, SwappableShift, UserWithSwappableShifts, ValidateSwapInput, WithdrawRequestInput},
     AppError, AppResult, AppState,
 };
 
@@ -23,14 +23,85 @@ pub struct GetMarketplaceQuery {
     pub user_id: Option<i32>,
     #[serde(rename = "excludeUserId")]
     pub exclude_user_id: Option<i32>,
+    /// Shift month (1-12), requires year (open and approval lists)
     pub month: Option<i32>,
+    /// Shift year (open and approval lists)
     pub year: Option<i32>,
+    /// Page size (default 100, max 500; open and approval lists)
+    pub limit: Option<i64>,
+    /// Rows to skip (open and approval lists)
+    pub offset: Option<i64>,
+    /// Sort order (open defaults to created_at, approvals to urgency)
+    pub sort: Option<MarketplaceSort>,
+}
+
+/// Default and maximum page size for the open and approval lists
+const DEFAULT_PAGE_SIZE: i64 = 100;
+const MAX_PAGE_SIZE: i64 = 500;
+
+/// Append role/month/year filters, ORDER BY and LIMIT/OFFSET to a list query
+/// built on MARKETPLACE_BASE_QUERY. Returns the values to bind, in order.
+fn apply_list_params(sql: &mut String, query: &GetMarketplaceQuery, default_sort: MarketplaceSort) -> AppResult<Vec<i32>> {
+    let mut bindings = vec![];
+
+    // roleId = 0 means all roles (TanStack only filters by role if roleId > 0)
+    if let Some(role_id) = query.role_id.filter(|&id| id > 0) {
+        bindings.push(role_id);
+        sql.push_str(&format!(" AND s.role_id = ${}", bindings.len()));
+    }
+
+    if let Some(month) = query.month {
+        if !(1..=12).contains(&month) {
+            return Err(AppError::BadRequest("month must be between 1 and 12".to_string()));
+        }
+        if query.year.is_none() {
+            return Err(AppError::BadRequest("month filter requires year".to_string()));
+        }
+        bindings.push(month);
+        sql.push_str(&format!(" AND EXTRACT(MONTH FROM s.date) = ${}", bindings.len()));
+    }
+
+    if let Some(year) = query.year {
+        bindings.push(year);
+        sql.push_str(&format!(" AND EXTRACT(YEAR FROM s.date) = ${}", bindings.len()));
+    }
+
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE);
+    if !(1..=MAX_PAGE_SIZE).contains(&limit) {
+        return Err(AppError::BadRequest(format!("limit must be between 1 and {}", MAX_PAGE_SIZE)));
+    }
+    let offset = query.offset.unwrap_or(0);
+    if offset < 0 {
+        return Err(AppError::BadRequest("offset must not be negative".to_string()));
+    }
+
+    // limit/offset are validated integers, safe to inline
+    sql.push_str(&format!(
+        " ORDER BY {} LIMIT {} OFFSET {}",
+        query.sort.unwrap_or(default_sort).order_by(),
+        limit,
+        offset
+    ));
+
+    Ok(bindings)
+}
+
+async fn fetch_request_list(db: &sqlx::PgPool, sql: &str, bindings: Vec<i32>) -> Result<Vec<ShiftRequestRow>, sqlx::Error> {
+    let sql = crate::db::tag_sql(sql);
+    let mut query_builder = sqlx::query_as::<sqlx::Postgres, ShiftRequestRow>(&sql).persistent(false);
+    for binding in bindings {
+        query_builder = query_builder.bind(binding);
+    }
+    query_builder.fetch_all(db).await
 }
 
 #[derive(Debug, Deserialize, IntoParams)]
 pub struct CancelRequestQuery {
     #[serde(rename = "confirmedRequesterId")]
     pub confirmed_requester_id: Option<i32>,
+    /// Reason code from /api/marketplace/reasons
+    #[serde(rename = "reasonCode")]
+    pub reason_code: Option<String>,
 }
 
 #[derive(Debug, FromRow)]
@@ -39,16 +110,23 @@ struct ShiftRequestRow {
     id: i32,
     shift_id: Uuid,
     requester_id: i32,
-    request_type: String,
-    status: String,
+    request_type: ShiftRequestType,
+    status: ShiftRequestStatus,
     target_user_id: Option<i32>,
     target_shift_id: Option<Uuid>,
     candidate_id: Option<i32>,
     resolved_by: Option<i32>,
     resolved_at: Option<NaiveDateTime>,
+    resolved_as_delegate_of: Option<i32>,
+    created_by_admin_id: Option<i32>,
     notes: Option<String>,
     created_at: NaiveDateTime,
     updated_at: NaiveDateTime,
+    bumped_at: Option<NaiveDateTime>,
+    swap_cap_exceeded: bool,
+    swap_cap_override_by: Option<i32>,
+    series_id: Option<Uuid>,
+    reason_code: Option<String>,
     // Enriched fields
     shift_date: NaiveDate,
     shift_label: String,
@@ -67,6 +145,7 @@ struct ShiftRequestRow {
     target_shift_end: Option<String>,
     candidate_name: Option<String>,
     candidate_short_name: Option<String>,
+    created_by_admin_name: Option<String>,
     role_auto_approve: bool,
 }
 
@@ -82,9 +161,16 @@ const MARKETPLACE_BASE_QUERY: &str = r#"
         sr.candidate_id,
         sr.resolved_by,
         sr.resolved_at,
+        sr.resolved_as_delegate_of,
+        sr.created_by_admin_id,
         sr.notes,
         sr.created_at,
         sr.updated_at,
+        sr.bumped_at,
+        sr.swap_cap_exceeded,
+        sr.swap_cap_override_by,
+        sr.series_id,
+        sr.reason_code,
         s.date AS shift_date,
         s.label AS shift_label,
         to_char(s.start, 'HH24:MI') AS shift_start,
@@ -102,6 +188,7 @@ const MARKETPLACE_BASE_QUERY: &str = r#"
         to_char(ts."end", 'HH24:MI') AS target_shift_end,
         u_cand.full_name AS candidate_name,
         u_cand.short_name AS candidate_short_name,
+        u_admin.short_name AS created_by_admin_name,
         r.marketplace_auto_approve AS role_auto_approve
     FROM "ShiftRequests" sr
     INNER JOIN "Shifts" s ON sr.shift_id = s.uuid
@@ -110,6 +197,7 @@ const MARKETPLACE_BASE_QUERY: &str = r#"
     LEFT JOIN "Users" u_target ON sr.target_user_id = u_target.user_profile_id
     LEFT JOIN "Shifts" ts ON sr.target_shift_id = ts.uuid
     LEFT JOIN "Users" u_cand ON sr.candidate_id = u_cand.user_profile_id
+    LEFT JOIN "Users" u_admin ON sr.created_by_admin_id = u_admin.user_profile_id
 "#;
 
 fn row_to_shift_request_with_details(row: ShiftRequestRow) -> ShiftRequestWithDetails {
@@ -127,9 +215,16 @@ fn row_to_shift_request_with_details(row: ShiftRequestRow) -> ShiftRequestWithDe
             candidate_id: row.candidate_id,
             resolved_by: row.resolved_by,
             resolved_at: row.resolved_at,
+            resolved_as_delegate_of: row.resolved_as_delegate_of,
+            created_by_admin_id: row.created_by_admin_id,
             notes: row.notes,
             created_at: row.created_at,
             updated_at: row.updated_at,
+            bumped_at: row.bumped_at,
+            swap_cap_exceeded: row.swap_cap_exceeded,
+            swap_cap_override_by: row.swap_cap_override_by,
+            series_id: row.series_id,
+            reason_code: row.reason_code,
         },
         shift_date: row.shift_date,
         shift_label: row.shift_label,
@@ -148,17 +243,19 @@ fn row_to_shift_request_with_details(row: ShiftRequestRow) -> ShiftRequestWithDe
         target_shift_end: row.target_shift_end,
         candidate_name: row.candidate_name,
         candidate_short_name: row.candidate_short_name,
+        created_by_admin_name: row.created_by_admin_name,
         role_auto_approve: row.role_auto_approve,
     }
 }
 
-/// GET /api/marketplace/open?roleId=
+/// GET /api/marketplace/open?roleId=&month=&year=&limit=&offset=&sort=
 #[utoipa::path(
     get,
     path = "/api/marketplace/open",
     params(GetMarketplaceQuery),
     responses(
-        (status = 200, description = "List of open shift requests available for acceptance", body = Vec<ShiftRequestWithDetails>)
+        (status = 200, description = "Page of open shift requests available for acceptance", body = Vec<ShiftRequestWithDetails>),
+        (status = 400, description = "Invalid month, year, limit or offset")
     ),
     tag = "marketplace"
 )]
@@ -167,28 +264,12 @@ pub async fn get_open_requests(
     Query(query): Query<GetMarketplaceQuery>,
 ) -> AppResult<Json<Vec<ShiftRequestWithDetails>>> {
     let mut sql = format!("{} WHERE sr.status = 'OPEN'", MARKETPLACE_BASE_QUERY);
+    let bindings = apply_list_params(&mut sql, &query, MarketplaceSort::CreatedAt)?;
 
-    // Build query with parameterized filters
-    let rows = if let Some(role_id) = query.role_id {
-        sql.push_str(" AND s.role_id = $1 ORDER BY sr.created_at DESC");
-        sqlx::query_as::<sqlx::Postgres, ShiftRequestRow>(&sql)
-            .bind(role_id)
-            .fetch_all(&state.db)
-            .await
-            .map_err(|e| {
-                tracing::error!(error = %e, role_id, "❌ Failed to fetch open requests");
-                e
-            })?
-    } else {
-        sql.push_str(" ORDER BY sr.created_at DESC");
-        sqlx::query_as::<sqlx::Postgres, ShiftRequestRow>(&sql)
-            .fetch_all(&state.db)
-            .await
-            .map_err(|e| {
-                tracing::error!(error = %e, "❌ Failed to fetch open requests");
-                e
-            })?
-    };
+    let rows = fetch_request_list(&state.db, &sql, bindings).await.map_err(|e| {
+        tracing::error!(error = %e, role_id = ?query.role_id, "❌ Failed to fetch open requests");
+        e
+    })?;
 
     tracing::debug!(count = rows.len(), "🔍 Fetched open shift requests");
     let requests = rows.into_iter().map(row_to_shift_request_with_details).collect();
@@ -273,13 +354,14 @@ pub async fn get_incoming_requests(
     Ok(Json(requests))
 }
 
-/// GET /api/marketplace/approvals?roleId=
+/// GET /api/marketplace/approvals?roleId=&month=&year=&limit=&offset=&sort=
 #[utoipa::path(
     get,
     path = "/api/marketplace/approvals",
     params(GetMarketplaceQuery),
     responses(
-        (status = 200, description = "List of shift requests pending admin approval", body = Vec<ShiftRequestWithDetails>),
+        (status = 200, description = "Page of shift requests pending admin approval", body = Vec<ShiftRequestWithDetails>),
+        (status = 400, description = "Invalid month, year, limit or offset"),
         (status = 403, description = "Missing can_edit_rota permission")
     ),
     tag = "marketplace",
@@ -290,60 +372,21 @@ pub async fn get_approval_requests(
     auth: AuthenticatedUser,
     Query(query): Query<GetMarketplaceQuery>,
 ) -> AppResult<Json<Vec<ShiftRequestWithDetails>>> {
-    // Check permission
-    let has_perm = permissions::has_permission(
-        &state.db,
-        auth.profile_id,
-        auth.is_super_admin,
-        permissions::can_edit_rota,
-    )
-    .await
-    .map_err(|e| {
-        tracing::error!(error = %e, profile_id = auth.profile_id, "❌ Permission check failed");
-        AppError::Internal(format!("Permission check failed for user {}: {}", auth.profile_id, e))
-    })?;
+    // Check permission (approvers, or their delegates while a delegation is active)
+    let authority = approval_authority(&state, &auth).await?;
 
-    if !has_perm {
+    if authority.is_none() {
         tracing::warn!(profile_id = auth.profile_id, "🔐 User attempted to access approval requests without permission");
         return Err(AppError::Forbidden("Missing can_edit_rota permission".to_string()));
     }
 
     let mut sql = format!("{} WHERE sr.status = 'PENDING_APPROVAL'", MARKETPLACE_BASE_QUERY);
+    let bindings = apply_list_params(&mut sql, &query, MarketplaceSort::Urgency)?;
 
-    // Build query with parameterized filters
-    // TanStack only filters by role if roleId > 0
-    let rows = if let Some(role_id) = query.role_id {
-        if role_id > 0 {
-            sql.push_str(" AND s.role_id = $1 ORDER BY sr.created_at ASC");
-            sqlx::query_as::<sqlx::Postgres, ShiftRequestRow>(&sql)
-                .bind(role_id)
-                .fetch_all(&state.db)
-                .await
-                .map_err(|e| {
-                    tracing::error!(error = %e, role_id, "❌ Failed to fetch approval requests");
-                    e
-                })?
-        } else {
-            // roleId = 0 means fetch all (no role filter)
-            sql.push_str(" ORDER BY sr.created_at ASC");
-            sqlx::query_as::<sqlx::Postgres, ShiftRequestRow>(&sql)
-                .fetch_all(&state.db)
-                .await
-                .map_err(|e| {
-                    tracing::error!(error = %e, "❌ Failed to fetch approval requests with roleId=0");
-                    e
-                })?
-        }
-    } else {
-        sql.push_str(" ORDER BY sr.created_at ASC");
-        sqlx::query_as::<sqlx::Postgres, ShiftRequestRow>(&sql)
-            .fetch_all(&state.db)
-            .await
-            .map_err(|e| {
-                tracing::error!(error = %e, "❌ Failed to fetch approval requests");
-                e
-            })?
-    };
+    let rows = fetch_request_list(&state.db, &sql, bindings).await.map_err(|e| {
+        tracing::error!(error = %e, role_id = ?query.role_id, "❌ Failed to fetch approval requests");
+        e
+    })?;
 
     tracing::debug!(profile_id = auth.profile_id, count = rows.len(), "🔍 Fetched approval requests");
     let requests = rows.into_iter().map(row_to_shift_request_with_details).collect();
@@ -469,7 +512,7 @@ pub async fn get_swappable_shifts(
         time_off_id: Option<i32>,
     }
 
-    let rows = sqlx::query_as::<_, ShiftRow>(
+    let rows = sqlx::query_as::<_, ShiftRow>(&format!(
         r#"
         SELECT
             u.user_profile_id AS user_id,
@@ -486,14 +529,17 @@ pub async fn get_swappable_shifts(
             AND s.date >= $3
             AND s.date <= $4
         WHERE ur.role_id = $1
+          AND {ur_in_force}
           AND u.user_profile_id != $2
         ORDER BY u.full_name, s.date
-        "#
-    )
+        "#,
+        ur_in_force = crate::extractors::permissions::ur_in_force(5)
+    ))
     .bind(role_id)
     .bind(exclude_user_id)
     .bind(effective_start)
     .bind(end_of_month)
+    .bind(crate::extractors::permissions::today())
     .fetch_all(&state.db)
     .await?;
 
@@ -527,6 +573,169 @@ pub async fn get_swappable_shifts(
     Ok(Json(result))
 }
 
+/// One side of a proposed swap, as loaded for the eligibility check
+#[derive(Debug, FromRow)]
+struct SwapSide {
+    uuid: Uuid,
+    user_profile_id: Option<i32>,
+    role_id: i32,
+    date: NaiveDate,
+    time_off_category_id: Option<i32>,
+    marketplace_auto_approve: bool,
+    /// A non-terminal marketplace request already covers this shift
+    has_active_request: bool,
+    /// The user who would receive this shift already works another shift that day
+    receiver_busy: bool,
+}
+
+/// Run the marketplace rules over a proposed swap. Every rule is reported, not just the first failure.
+fn evaluate_swap(
+    shift: &SwapSide,
+    target: &SwapSide,
+    acting_user_id: i32,
+    marketplace_enabled: bool,
+    today: NaiveDate,
+) -> SwapEligibility {
+    let mut checks = Vec::new();
+    let mut check = |rule: &str, passed: bool, reason: String| {
+        checks.push(SwapCheck {
+            rule: rule.to_string(),
+            passed,
+            reason: (!passed).then_some(reason),
+        });
+    };
+
+    check(
+        "SHIFT_OWNER",
+        shift.user_profile_id == Some(acting_user_id),
+        format!("Shift {} is not assigned to you", shift.uuid),
+    );
+    check(
+        "TARGET_ASSIGNED",
+        target.user_profile_id.is_some_and(|owner| owner != acting_user_id),
+        format!("Shift {} must belong to another member of staff", target.uuid),
+    );
+    check(
+        "SAME_ROLE",
+        shift.role_id == target.role_id,
+        "Shifts can only be swapped within the same role".to_string(),
+    );
+    check(
+        "MARKETPLACE_ENABLED",
+        marketplace_enabled,
+        "The marketplace is disabled for this workplace".to_string(),
+    );
+    check(
+        "NOT_IN_PAST",
+        shift.date >= today && target.date >= today,
+        "Shifts that have already happened cannot be swapped".to_string(),
+    );
+    check(
+        "NOT_TIME_OFF",
+        shift.time_off_category_id.is_none() && target.time_off_category_id.is_none(),
+        "Time off entries cannot be swapped".to_string(),
+    );
+    check(
+        "NO_ACTIVE_REQUEST",
+        !shift.has_active_request && !target.has_active_request,
+        "One of the shifts already has an active marketplace request".to_string(),
+    );
+    check(
+        "NO_DOUBLE_BOOKING",
+        !shift.receiver_busy && !target.receiver_busy,
+        match (target.receiver_busy, shift.receiver_busy) {
+            (true, true) => "Both of you already work another shift on the day you would take on".to_string(),
+            (true, false) => format!("You already work another shift on {}", target.date),
+            _ => format!("Your colleague already works another shift on {}", shift.date),
+        },
+    );
+
+    SwapEligibility {
+        eligible: checks.iter().all(|c| c.passed),
+        auto_approve: shift.marketplace_auto_approve,
+        checks,
+    }
+}
+
+/// POST /api/marketplace/validate-swap - Check a proposed swap against the marketplace rules without creating it
+#[utoipa::path(
+    post,
+    path = "/api/marketplace/validate-swap",
+    request_body = ValidateSwapInput,
+    responses(
+        (status = 200, description = "Pass/fail result for every swap rule; nothing is created", body = SwapEligibility),
+        (status = 400, description = "shift_id and target_shift_id are the same shift"),
+        (status = 404, description = "Shift not found")
+    ),
+    tag = "marketplace",
+    security(("cookie_auth" = []))
+)]
+pub async fn validate_swap(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+    Json(input): Json<ValidateSwapInput>,
+) -> AppResult<Json<SwapEligibility>> {
+    if input.shift_id == input.target_shift_id {
+        return Err(AppError::BadRequest("shift_id and target_shift_id must be different shifts".to_string()));
+    }
+    let acting_user_id = input.confirmed_requester_id.unwrap_or(auth.profile_id);
+
+    // Each shift is checked against the diary of the user who would receive it: the requester
+    // takes the target shift, the target's owner takes the requester's shift
+    let sides = sqlx::query_as::<_, SwapSide>(
+        r#"
+        SELECT
+            s.uuid,
+            s.user_profile_id,
+            s.role_id,
+            s.date,
+            s.time_off_category_id,
+            r.marketplace_auto_approve,
+            EXISTS (
+                SELECT 1 FROM "ShiftRequests" sr
+                WHERE (sr.shift_id = s.uuid OR sr.target_shift_id = s.uuid)
+                  AND sr.status NOT IN ('APPROVED', 'REJECTED', 'CANCELLED')
+            ) AS has_active_request,
+            EXISTS (
+                SELECT 1 FROM "Shifts" other
+                WHERE other.user_profile_id = receiver.user_profile_id
+                  AND other.date = s.date
+                  AND other.uuid NOT IN ($1, $2)
+            ) AS receiver_busy
+        FROM "Shifts" s
+        INNER JOIN "Roles" r ON s.role_id = r.id
+        LEFT JOIN "Shifts" receiver ON receiver.uuid = CASE WHEN s.uuid = $1 THEN $2 ELSE $1 END
+        WHERE s.uuid IN ($1, $2)
+        "#,
+    )
+    .bind(input.shift_id)
+    .bind(input.target_shift_id)
+    .fetch_all(&state.db)
+    .await?;
+
+    let find = |uuid: Uuid| {
+        sides
+            .iter()
+            .find(|side| side.uuid == uuid)
+            .ok_or_else(|| AppError::NotFound(format!("Shift {} not found", uuid)))
+    };
+    let shift = find(input.shift_id)?;
+    let target = find(input.target_shift_id)?;
+
+    let settings = crate::handlers::workplaces_handler::load_role_settings(&state.db, shift.role_id).await?;
+    let today = chrono::Local::now().date_naive();
+    let eligibility = evaluate_swap(shift, target, acting_user_id, settings.marketplace_enabled, today);
+
+    tracing::debug!(
+        shift_id = %input.shift_id,
+        target_shift_id = %input.target_shift_id,
+        eligible = eligibility.eligible,
+        "🔎 Swap eligibility checked"
+    );
+
+    Ok(Json(eligibility))
+}
+
 /// POST /api/marketplace/requests - Create a new shift swap request
 #[utoipa::path(
     post,
@@ -534,9 +743,10 @@ pub async fn get_swappable_shifts(
     request_body = CreateShiftRequestInput,
     responses(
         (status = 200, description = "Shift request created successfully", body = ShiftRequestWithDetails),
-        (status = 400, description = "Invalid request_type or missing target_user_id for SWAP"),
-        (status = 403, description = "You can only create requests for your own shifts"),
-        (status = 404, description = "Shift not found")
+        (status = 400, description = "Invalid request_type, missing target_user_id for SWAP, or whole_series on a shift outside a series"),
+        (status = 403, description = "You can only create requests for your own shifts (or on_behalf_of without can_edit_rota in the shift's role), or the marketplace is disabled"),
+        (status = 404, description = "Shift not found"),
+        (status = 409, description = "The month is locked for payroll")
     ),
     tag = "marketplace",
     security(("cookie_auth" = []))
@@ -546,50 +756,117 @@ pub async fn create_shift_request(
     auth: AuthenticatedUser,
     Json(input): Json<CreateShiftRequestInput>,
 ) -> AppResult<Json<ShiftRequestWithDetails>> {
-    // Use confirmed requester ID if provided (generic account flow), otherwise use authenticated user
-    let acting_user_id = input.confirmed_requester_id.unwrap_or(auth.profile_id);
+    // Admins may raise a request for a colleague; otherwise use the confirmed requester ID
+    // (generic account flow) or the authenticated user
+    let created_by_admin_id = on_behalf_admin(input.on_behalf_of, input.confirmed_requester_id, auth.profile_id)?;
+    let acting_user_id = input.on_behalf_of.or(input.confirmed_requester_id).unwrap_or(auth.profile_id);
 
     // Verify the shift exists and belongs to the requester
-    let shift: (Option<i32>,) = sqlx::query_as(
-        r#"SELECT user_profile_id FROM "Shifts" WHERE uuid = $1"#
+    let (shift_owner, shift_role_id, shift_series_id): (Option<i32>, i32, Option<Uuid>) = sqlx::query_as(
+        r#"SELECT user_profile_id, role_id, series_id FROM "Shifts" WHERE uuid = $1"#
     )
     .bind(input.shift_id)
     .fetch_optional(&state.db)
     .await?
     .ok_or_else(|| AppError::NotFound(format!("Shift {} not found", input.shift_id)))?;
 
-    if shift.0 != Some(acting_user_id) {
-        return Err(AppError::Forbidden("You can only create requests for your own shifts".to_string()));
+    // Raising a request for a colleague needs can_edit_rota in the shift's role, as force-cancel does
+    if created_by_admin_id.is_some()
+        && !crate::extractors::permissions::has_permission(&state, auth.profile_id, auth.is_super_admin, |r| {
+            r.role_id == shift_role_id && r.can_edit_rota
+        })
+        .await?
+    {
+        return Err(AppError::Forbidden("Missing can_edit_rota permission for this shift's role".to_string()));
+    }
+
+    if shift_owner != Some(acting_user_id) {
+        return Err(AppError::NotShiftOwner);
+    }
+    let (series_id, mut shift_ids) = match (input.whole_series, shift_series_id) {
+        (false, _) => (None, Vec::new()),
+        (true, Some(series_id)) => {
+            let series_shift_ids: Vec<Uuid> = sqlx::query_scalar(
+                r#"SELECT uuid FROM "Shifts" WHERE series_id = $1 AND user_profile_id = $2"#
+            )
+            .bind(series_id)
+            .bind(acting_user_id)
+            .fetch_all(&state.db)
+            .await?;
+            (Some(series_id), series_shift_ids)
+        }
+        (true, None) => {
+            return Err(AppError::BadRequest(format!("Shift {} is not part of a series", input.shift_id)));
+        }
+    };
+    shift_ids.extend(std::iter::once(input.shift_id).chain(input.target_shift_id));
+
+    let settings = crate::handlers::workplaces_handler::load_role_settings(&state.db, shift_role_id).await?;
+    if !settings.marketplace_enabled {
+        return Err(AppError::MarketplaceDisabled);
     }
 
     // Determine initial status based on request type
-    let status = if input.request_type == "SWAP" && input.target_user_id.is_some() {
-        "PROPOSED"
-    } else if input.request_type == "GIVE_AWAY" {
-        "OPEN"
-    } else {
-        return Err(AppError::BadRequest("Invalid request_type or missing target_user_id for SWAP".to_string()));
+    let status = match input.request_type {
+        ShiftRequestType::Swap if input.target_user_id.is_some() => ShiftRequestStatus::Proposed,
+        ShiftRequestType::GiveAway => ShiftRequestStatus::Open,
+        _ => {
+            return Err(AppError::BadRequest("Invalid request_type or missing target_user_id for SWAP".to_string()));
+        }
     };
 
-    // Insert the new shift request
+    // Insert the new shift request (acting user recorded for the audit triggers)
+    let mut tx = crate::db::begin_as_user(&state.db, auth.profile_id).await?;
+    crate::handlers::payroll_locks_handler::ensure_shifts_unlocked(&mut tx, &shift_ids).await?;
     let request_id: i32 = sqlx::query_scalar(
         r#"
         INSERT INTO "ShiftRequests" (
-            shift_id, requester_id, type, status, target_user_id, target_shift_id, notes
+            shift_id, requester_id, type, status, target_user_id, target_shift_id, notes, created_by_admin_id,
+            series_id
         )
-        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
         RETURNING id
         "#,
     )
     .bind(input.shift_id)
     .bind(acting_user_id)
-    .bind(&input.request_type)
+    .bind(input.request_type)
     .bind(status)
     .bind(input.target_user_id)
     .bind(input.target_shift_id)
     .bind(&input.notes)
-    .fetch_one(&state.db)
+    .bind(created_by_admin_id)
+    .bind(series_id)
+    .fetch_one(&mut *tx)
     .await?;
+    let transition = crate::handlers::marketplace_sla_handler::record_transition(&mut *tx, request_id, None, status, auth.profile_id).await?;
+
+    if input.request_type == ShiftRequestType::GiveAway {
+        offer_give_away(&mut tx, request_id, input.shift_id, acting_user_id, false).await?;
+    }
+
+    if let Some(admin_id) = created_by_admin_id {
+        crate::handlers::notifications_handler::notify(
+            &mut *tx,
+            acting_user_id,
+            "MARKETPLACE_REQUEST_CREATED_FOR_YOU",
+            "An admin has put one of your shifts on the marketplace on your behalf.",
+            serde_json::json!({ "shift_request_id": request_id, "created_by_admin_id": admin_id }),
+        )
+        .await?;
+
+        tracing::info!(
+            request_id,
+            requester_id = acting_user_id,
+            admin_id,
+            "🛡️ Shift request created by admin on behalf of user"
+        );
+    }
+
+    tx.commit().await?;
+
+    crate::handlers::metrics::record_marketplace_event("created");
+    transition.emit();
 
     // Fetch the created request with full details
     let request = fetch_shift_request_with_details(&state.db, request_id).await?;
@@ -597,137 +874,595 @@ pub async fn create_shift_request(
     Ok(Json(request))
 }
 
-/// POST /api/marketplace/requests/{id}/accept - Accept an OPEN request
+/// Notification text for a give-away offer, or for its reminder after a bump
+fn give_away_offer_message(label: Option<&str>, date: NaiveDate, reminder: bool) -> String {
+    let message = match label {
+        Some(label) => format!("A {} shift on {} is up for grabs.", label, date),
+        None => format!("A shift on {} is up for grabs.", date),
+    };
+    if reminder {
+        format!("Still looking for cover: {}", message)
+    } else {
+        message
+    }
+}
+
+/// Notify everyone who could take a GIVE_AWAY: staff who work shifts in its role, are free that day
+/// (no shift or leave), are not the requester and have not opted out. Recipients are kept in
+/// "ShiftRequestOffers" for audit; a `reminder` (bump) tells them again and moves their notified_at.
+/// Returns how many were notified.
+async fn offer_give_away(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    request_id: i32,
+    shift_id: Uuid,
+    requester_id: i32,
+    reminder: bool,
+) -> AppResult<usize> {
+    let recipients: Vec<i32> = sqlx::query_scalar(&format!(
+        r#"
+        INSERT INTO "ShiftRequestOffers" (shift_request_id, user_profile_id)
+        SELECT $1, u.user_profile_id
+        FROM "Shifts" s
+        INNER JOIN "UserRoles" ur ON ur.role_id = s.role_id AND ur.can_work_shifts AND {ur_in_force}
+        INNER JOIN "Users" u ON u.user_profile_id = ur.user_profile_id
+        WHERE s.uuid = $2
+          AND u.user_profile_id <> $3
+          AND NOT COALESCE(u.is_generic_login, false)
+          AND NOT u.marketplace_offers_opt_out
+          AND NOT EXISTS (
+              SELECT 1 FROM "Shifts" o WHERE o.user_profile_id = u.user_profile_id AND o.date = s.date
+          )
+          AND NOT EXISTS (
+              SELECT 1 FROM "Diary" d
+              WHERE d.user_profile_id = u.user_profile_id AND d.date = s.date AND NOT d.deleted AND (d.al OR d.sl OR d.pl)
+          )
+        ON CONFLICT (shift_request_id, user_profile_id) DO UPDATE SET notified_at = NOW()
+        RETURNING user_profile_id
+        "#,
+        ur_in_force = crate::extractors::permissions::ur_in_force(4)
+    ))
+    .bind(request_id)
+    .bind(shift_id)
+    .bind(requester_id)
+    .bind(crate::extractors::permissions::today())
+    .fetch_all(&mut **tx)
+    .await?;
+
+    let (date, label): (NaiveDate, Option<String>) =
+        sqlx::query_as(r#"SELECT date, label FROM "Shifts" WHERE uuid = $1"#)
+            .bind(shift_id)
+            .fetch_one(&mut **tx)
+            .await?;
+    let message = give_away_offer_message(label.as_deref(), date, reminder);
+
+    for user_profile_id in &recipients {
+        crate::handlers::notifications_handler::notify(
+            &mut **tx,
+            *user_profile_id,
+            "MARKETPLACE_GIVE_AWAY_OFFER",
+            &message,
+            serde_json::json!({ "shift_request_id": request_id, "shift_id": shift_id, "reminder": reminder }),
+        )
+        .await?;
+    }
+
+    tracing::info!(request_id, notified = recipients.len(), reminder, "📣 Give-away offered to eligible staff");
+    Ok(recipients.len())
+}
+
+/// Tell the shift role's approvers, and anyone standing in for them today, that a request awaits their decision.
+/// Each notification carries its own action token so the link opens the request for that recipient only.
+async fn notify_approvers(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    config: &crate::AppConfig,
+    request_id: i32,
+    shift_id: Uuid,
+    parties: &[i32],
+) -> AppResult<usize> {
+    let recipients: Vec<i32> = sqlx::query_scalar(&format!(
+        r#"
+        WITH approvers AS (
+            SELECT ur.user_profile_id
+            FROM "Shifts" s
+            INNER JOIN "UserRoles" ur ON ur.role_id = s.role_id AND ur.can_edit_rota = true AND {ur_in_force}
+            WHERE s.uuid = $1
+        )
+        SELECT DISTINCT recipient FROM (
+            SELECT user_profile_id AS recipient FROM approvers
+            UNION
+            SELECT d.delegate_id FROM "ApprovalDelegations" d
+            WHERE d.delegator_id IN (SELECT user_profile_id FROM approvers)
+              AND CURRENT_DATE BETWEEN d.start_date AND d.end_date
+        ) r
+        INNER JOIN "Users" u ON u.user_profile_id = r.recipient
+        WHERE u.deactivated_at IS NULL AND NOT (r.recipient = ANY($2))
+        ORDER BY recipient
+        "#,
+        ur_in_force = crate::extractors::permissions::ur_in_force(3)
+    ))
+    .bind(shift_id)
+    .bind(parties)
+    .bind(crate::extractors::permissions::today())
+    .fetch_all(&mut **tx)
+    .await?;
+
+    for user_profile_id in &recipients {
+        let (token, expires_at) = crate::auth::generate_action_token(request_id, *user_profile_id, &config.pin_token_secret)?;
+        let link = config
+            .app_base_url
+            .as_ref()
+            .map(|base| format!("{}/marketplace/approvals/{}", base.trim_end_matches('/'), token));
+        crate::handlers::notifications_handler::notify(
+            &mut **tx,
+            *user_profile_id,
+            "MARKETPLACE_APPROVAL_NEEDED",
+            "A shift request is waiting for your approval.",
+            serde_json::json!({
+                "shift_request_id": request_id,
+                "shift_id": shift_id,
+                "action_token": token,
+                "action_expires_at": expires_at,
+                "link": link,
+            }),
+        )
+        .await?;
+    }
+
+    tracing::info!(request_id, notified = recipients.len(), "📨 Approvers notified with action links");
+    Ok(recipients.len())
+}
+
+/// An OPEN request can be bumped once a day, counting from its creation or previous bump
+const BUMP_INTERVAL_HOURS: i64 = 24;
+const MAX_BUMP_NOTE_LENGTH: usize = 500;
+
+fn next_bump_at(created_at: NaiveDateTime, bumped_at: Option<NaiveDateTime>) -> NaiveDateTime {
+    bumped_at.unwrap_or(created_at) + chrono::Duration::hours(BUMP_INTERVAL_HOURS)
+}
+
+/// Bumping re-sends the give-away offer, so other request types have nobody to re-notify
+fn check_bumpable(request_type: ShiftRequestType) -> AppResult<()> {
+    match request_type {
+        ShiftRequestType::GiveAway => Ok(()),
+        other => Err(AppError::BadRequest(format!(
+            "Only GIVE_AWAY requests can be bumped, not {}",
+            other.as_str()
+        ))),
+    }
+}
+
+/// POST /api/marketplace/requests/{id}/bump - Move an OPEN request back to the top of the open list and re-notify
 #[utoipa::path(
     post,
-    path = "/api/marketplace/requests/{id}/accept",
+    path = "/api/marketplace/requests/{id}/bump",
     params(
         ("id" = i32, Path, description = "Shift request ID")
     ),
-    request_body = AcceptRequestInput,
+    request_body = BumpRequestInput,
     responses(
-        (status = 200, description = "Request accepted, may be auto-approved or pending approval", body = ShiftRequestWithDetails),
-        (status = 400, description = "Request is not OPEN or cannot accept your own request"),
-        (status = 404, description = "Request not found")
+        (status = 200, description = "Request bumped and eligible staff notified again", body = BumpRequestResponse),
+        (status = 400, description = "Request is not an OPEN give-away, or the note is too long"),
+        (status = 403, description = "Only the requester or a rota admin for the shift's role can bump, or the marketplace is disabled"),
+        (status = 404, description = "Request not found"),
+        (status = 429, description = "Already bumped (or created) in the last 24 hours; see Retry-After")
     ),
     tag = "marketplace",
     security(("cookie_auth" = []))
 )]
-pub async fn accept_shift_request(
+pub async fn bump_shift_request(
     State(state): State<Arc<AppState>>,
     Path(request_id): Path<i32>,
     auth: AuthenticatedUser,
-    Json(input): Json<AcceptRequestInput>,
-) -> AppResult<Json<ShiftRequestWithDetails>> {
-    // Use confirmed candidate ID if provided (generic account flow), otherwise use authenticated user
-    let acting_user_id = input.confirmed_candidate_id.unwrap_or(auth.profile_id);
+    Json(input): Json<BumpRequestInput>,
+) -> AppResult<Json<BumpRequestResponse>> {
+    // Use confirmed requester ID if provided (generic account flow), otherwise use authenticated user
+    let acting_user_id = input.confirmed_requester_id.unwrap_or(auth.profile_id);
 
-    // Fetch the current request
-    let (current_status, requester_id, shift_id): (String, i32, Uuid) = sqlx::query_as(
-        r#"SELECT status, requester_id, shift_id FROM "ShiftRequests" WHERE id = $1"#
+    let note = input.note.as_deref().map(str::trim).filter(|n| !n.is_empty());
+    if note.is_some_and(|n| n.chars().count() > MAX_BUMP_NOTE_LENGTH) {
+        return Err(AppError::BadRequest(format!("note must be at most {} characters", MAX_BUMP_NOTE_LENGTH)));
+    }
+
+    let (current_status, request_type, requester_id, shift_id, role_id, created_at, bumped_at): (
+        ShiftRequestStatus,
+        ShiftRequestType,
+        i32,
+        Uuid,
+        i32,
+        NaiveDateTime,
+        Option<NaiveDateTime>,
+    ) = sqlx::query_as(
+        r#"
+        SELECT sr.status, sr.type, sr.requester_id, sr.shift_id, s.role_id, sr.created_at, sr.bumped_at
+        FROM "ShiftRequests" sr
+        INNER JOIN "Shifts" s ON s.uuid = sr.shift_id
+        WHERE sr.id = $1
+        "#,
     )
     .bind(request_id)
     .fetch_optional(&state.db)
     .await?
     .ok_or_else(|| AppError::NotFound(format!("Request {} not found", request_id)))?;
 
-    // Validate request is OPEN
-    if current_status != "OPEN" {
-        return Err(AppError::BadRequest(format!("Request is not OPEN, current status: {}", current_status)));
+    if requester_id != acting_user_id
+        && !crate::extractors::permissions::has_permission(&state, auth.profile_id, auth.is_super_admin, |r| {
+            r.role_id == role_id && r.can_edit_rota
+        })
+        .await?
+    {
+        return Err(AppError::NotRequestParty { party: "requester" });
     }
 
-    // Requester cannot accept their own request
-    if requester_id == acting_user_id {
-        return Err(AppError::BadRequest("Cannot accept your own request".to_string()));
+    if current_status != ShiftRequestStatus::Open {
+        return Err(AppError::InvalidStateTransition { from: current_status, action: "bump" });
     }
+    check_bumpable(request_type)?;
 
-    // Check if role has auto-approve enabled
-    let auto_approve: bool = sqlx::query_scalar(
+    let settings = crate::handlers::workplaces_handler::load_role_settings(&state.db, role_id).await?;
+    if !settings.marketplace_enabled {
+        return Err(AppError::MarketplaceDisabled);
+    }
+
+    let mut tx = crate::db::begin_as_user(&state.db, auth.profile_id).await?;
+
+    // The interval is checked in the UPDATE so concurrent bumps cannot both get through
+    let bumped: Option<NaiveDateTime> = sqlx::query_scalar(
         r#"
-        SELECT r.marketplace_auto_approve
-        FROM "Shifts" s
-        INNER JOIN "Roles" r ON s.role_id = r.id
-        WHERE s.uuid = $1
-        "#
+        UPDATE "ShiftRequests"
+        SET bumped_at = NOW(), updated_at = NOW()
+        WHERE id = $1
+          AND status = 'OPEN'
+          AND COALESCE(bumped_at, created_at) <= NOW() - make_interval(hours => $2)
+        RETURNING bumped_at
+        "#,
     )
-    .bind(shift_id)
-    .fetch_one(&state.db)
+    .bind(request_id)
+    .bind(BUMP_INTERVAL_HOURS as i32)
+    .fetch_optional(&mut *tx)
     .await?;
 
-    // Determine new status
-    let new_status = if auto_approve { "APPROVED" } else { "PENDING_APPROVAL" };
+    let Some(bumped_at) = bumped else {
+        let next = next_bump_at(created_at, bumped_at);
+        let retry_after_secs = (next - chrono::Utc::now().naive_utc()).num_seconds().max(1);
+        return Err(AppError::RateLimited {
+            message: format!("This request can be bumped again after {}", next.format("%Y-%m-%d %H:%M")),
+            retry_after_secs,
+        });
+    };
 
-    // Start transaction for potential shift swap
-    let mut tx = state.db.begin().await?;
+    let notified = offer_give_away(&mut tx, request_id, shift_id, requester_id, true).await?;
 
-    // Update request
     sqlx::query(
         r#"
-        UPDATE "ShiftRequests"
-        SET candidate_id = $1, target_shift_id = $2, status = $3, updated_at = NOW()
-        WHERE id = $4
-        "#
+        INSERT INTO "ShiftRequestBumps" (shift_request_id, bumped_by, note, notified, bumped_at)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
     )
-    .bind(acting_user_id)
-    .bind(input.target_shift_id)
-    .bind(new_status)
     .bind(request_id)
+    .bind(acting_user_id)
+    .bind(note)
+    .bind(notified as i32)
+    .bind(bumped_at)
     .execute(&mut *tx)
     .await?;
 
-    // If auto-approve, perform the swap immediately
-    if auto_approve {
-        tracing::info!(
-            request_id,
-            shift_id = %shift_id,
-            candidate_id = acting_user_id,
-            "✅🔄 Auto-approving shift request and performing swap"
-        );
-        perform_shift_swap(&mut tx, shift_id, acting_user_id, input.target_shift_id, requester_id).await?;
-
-        // Mark as resolved
-        sqlx::query(r#"UPDATE "ShiftRequests" SET resolved_by = $1, resolved_at = NOW() WHERE id = $2"#)
-            .bind(acting_user_id)
-            .bind(request_id)
-            .execute(&mut *tx)
-            .await?;
-    } else {
-        tracing::info!(
-            request_id,
-            candidate_id = acting_user_id,
-            "📝 Request accepted, pending admin approval"
-        );
-    }
-
-    tx.commit().await.map_err(|e| {
-        tracing::error!(
-            error = %e,
-            request_id,
-            auto_approve,
-            "❌ Transaction rollback in accept_shift_request"
-        );
-        AppError::Internal(format!("Failed to commit shift request acceptance for request {}: {}", request_id, e))
-    })?;
-
-    tracing::debug!(request_id, "✅ Shift request transaction committed successfully");
+    tx.commit().await?;
 
-    // Fetch updated request
-    let request = fetch_shift_request_with_details(&state.db, request_id).await?;
+    crate::handlers::metrics::record_marketplace_event("bumped");
+    tracing::info!(request_id, bumped_by = acting_user_id, notified, "⏫ Shift request bumped");
 
-    Ok(Json(request))
+    Ok(Json(BumpRequestResponse {
+        request: fetch_shift_request_with_details(&state.db, request_id).await?,
+        notified: notified as i32,
+        next_bump_at: next_bump_at(created_at, Some(bumped_at)),
+    }))
 }
 
-/// POST /api/marketplace/requests/{id}/respond - Target user responds to PROPOSED swap
+/// GET /api/marketplace/requests/{id}/offers - Who was notified when a GIVE_AWAY was posted
 #[utoipa::path(
-    post,
+    get,
+    path = "/api/marketplace/requests/{id}/offers",
+    params(
+        ("id" = i32, Path, description = "Shift request ID")
+    ),
+    responses(
+        (status = 200, description = "Notified staff, in the order they were told", body = Vec<ShiftOfferRecipient>),
+        (status = 403, description = "Only the requester or a rota admin for the shift's role can see this"),
+        (status = 404, description = "Request not found")
+    ),
+    tag = "marketplace",
+    security(("cookie_auth" = []))
+)]
+pub async fn get_offer_recipients(
+    State(state): State<Arc<AppState>>,
+    Path(request_id): Path<i32>,
+    auth: AuthenticatedUser,
+) -> AppResult<Json<Vec<ShiftOfferRecipient>>> {
+    let (requester_id, role_id): (i32, i32) = sqlx::query_as(
+        r#"
+        SELECT sr.requester_id, s.role_id
+        FROM "ShiftRequests" sr
+        INNER JOIN "Shifts" s ON s.uuid = sr.shift_id
+        WHERE sr.id = $1
+        "#,
+    )
+    .bind(request_id)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Request {} not found", request_id)))?;
+
+    if requester_id != auth.profile_id
+        && !crate::extractors::permissions::has_permission(&state, auth.profile_id, auth.is_super_admin, |r| {
+            r.role_id == role_id && r.can_edit_rota
+        })
+        .await?
+    {
+        return Err(AppError::Forbidden(
+            "Only the requester or a rota admin can see who was offered this shift".to_string(),
+        ));
+    }
+
+    let recipients = sqlx::query_as::<_, ShiftOfferRecipient>(
+        r#"
+        SELECT o.user_profile_id, u.short_name, u.full_name, o.notified_at
+        FROM "ShiftRequestOffers" o
+        INNER JOIN "Users" u ON u.user_profile_id = o.user_profile_id
+        WHERE o.shift_request_id = $1
+        ORDER BY o.notified_at, u.short_name
+        "#,
+    )
+    .bind(request_id)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(recipients))
+}
+
+/// The admin raising a request for a colleague (`on_behalf_of` someone other than the caller), if any. They still
+/// need can_edit_rota in the shift's role; on_behalf_of for yourself is a plain request.
+fn on_behalf_admin(on_behalf_of: Option<i32>, confirmed_requester_id: Option<i32>, caller: i32) -> AppResult<Option<i32>> {
+    match on_behalf_of {
+        Some(_) if confirmed_requester_id.is_some() => {
+            Err(AppError::BadRequest("on_behalf_of cannot be combined with confirmedRequesterId".to_string()))
+        }
+        Some(user_id) if user_id != caller => Ok(Some(caller)),
+        _ => Ok(None),
+    }
+}
+
+/// POST /api/marketplace/requests/{id}/accept - Accept an OPEN request
+#[utoipa::path(
+    post,
+    path = "/api/marketplace/requests/{id}/accept",
+    params(
+        ("id" = i32, Path, description = "Shift request ID")
+    ),
+    request_body = AcceptRequestInput,
+    responses(
+        (status = 200, description = "Request accepted, may be auto-approved or pending approval (always pending past the role's monthly swap cap)", body = ShiftRequestWithDetails),
+        (status = 400, description = "Request is not OPEN or cannot accept your own request"),
+        (status = 403, description = "The marketplace is disabled for this workplace"),
+        (status = 404, description = "Request not found"),
+        (status = 409, description = "The month is locked for payroll")
+    ),
+    tag = "marketplace",
+    security(("cookie_auth" = []))
+)]
+pub async fn accept_shift_request(
+    State(state): State<Arc<AppState>>,
+    Path(request_id): Path<i32>,
+    auth: AuthenticatedUser,
+    Json(input): Json<AcceptRequestInput>,
+) -> AppResult<Json<ShiftRequestWithDetails>> {
+    // Use confirmed candidate ID if provided (generic account flow), otherwise use authenticated user
+    let acting_user_id = input.confirmed_candidate_id.unwrap_or(auth.profile_id);
+
+    // Fetch the current request
+    let (current_status, requester_id, shift_id): (ShiftRequestStatus, i32, Uuid) = sqlx::query_as(
+        r#"SELECT status, requester_id, shift_id FROM "ShiftRequests" WHERE id = $1"#
+    )
+    .bind(request_id)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Request {} not found", request_id)))?;
+
+    // Validate request is OPEN
+    if current_status != ShiftRequestStatus::Open {
+        return Err(AppError::InvalidStateTransition { from: current_status, action: "accept" });
+    }
+
+    // Requester cannot accept their own request
+    if requester_id == acting_user_id {
+        return Err(AppError::SelfAcceptNotAllowed);
+    }
+
+    // Check if role has auto-approve enabled
+    let (auto_approve, workplace_id): (bool, i32) = sqlx::query_as(
+        r#"
+        SELECT r.marketplace_auto_approve, r.workplace_id::int4
+        FROM "Shifts" s
+        INNER JOIN "Roles" r ON s.role_id = r.id
+        WHERE s.uuid = $1
+        "#
+    )
+    .bind(shift_id)
+    .fetch_one(&state.db)
+    .await?;
+
+    let settings = crate::handlers::workplaces_handler::load_workplace_settings(&state.db, workplace_id).await?;
+    if !settings.marketplace_enabled {
+        return Err(AppError::MarketplaceDisabled);
+    }
+
+    // Start transaction for potential shift swap (acting user recorded for the audit triggers)
+    let mut tx = crate::db::begin_as_user(&state.db, acting_user_id).await?;
+
+    // Past the role's monthly swap cap only an admin can approve
+    lock_swap_cap(&mut tx, shift_id).await?;
+    let over_cap = parties_over_swap_cap(&mut *tx, shift_id, &[requester_id, acting_user_id]).await?;
+    let swap_cap_exceeded = !over_cap.is_empty();
+    if swap_cap_exceeded {
+        tracing::info!(request_id, over_cap = ?over_cap, "⚖️ Monthly swap cap reached, request needs an admin override");
+    }
+    let auto_approve = auto_approve && !swap_cap_exceeded;
+
+    // Determine new status
+    let new_status = if auto_approve { ShiftRequestStatus::Approved } else { ShiftRequestStatus::PendingApproval };
+    validate_transition(current_status, new_status, "accept")?;
+
+    // Update request
+    sqlx::query(
+        r#"
+        UPDATE "ShiftRequests"
+        SET candidate_id = $1, target_shift_id = $2, status = $3, swap_cap_exceeded = $5, updated_at = NOW()
+        WHERE id = $4
+        "#
+    )
+    .bind(acting_user_id)
+    .bind(input.target_shift_id)
+    .bind(new_status)
+    .bind(request_id)
+    .bind(swap_cap_exceeded)
+    .execute(&mut *tx)
+    .await?;
+    let transition = crate::handlers::marketplace_sla_handler::record_transition(&mut *tx, request_id, Some(current_status), new_status, acting_user_id).await?;
+
+    // If auto-approve, perform the swap immediately
+    if auto_approve {
+        tracing::info!(
+            request_id,
+            shift_id = %shift_id,
+            candidate_id = acting_user_id,
+            "✅🔄 Auto-approving shift request and performing swap"
+        );
+        perform_shift_swap(&mut tx, request_id, shift_id, acting_user_id, input.target_shift_id, requester_id).await?;
+
+        // Mark as resolved
+        sqlx::query(r#"UPDATE "ShiftRequests" SET resolved_by = $1, resolved_at = NOW() WHERE id = $2"#)
+            .bind(acting_user_id)
+            .bind(request_id)
+            .execute(&mut *tx)
+            .await?;
+    } else {
+        tracing::info!(
+            request_id,
+            candidate_id = acting_user_id,
+            "📝 Request accepted, pending admin approval"
+        );
+        notify_approvers(&mut tx, &state.config, request_id, shift_id, &[requester_id, acting_user_id]).await?;
+    }
+
+    tx.commit().await.map_err(|e| {
+        tracing::error!(
+            error = %e,
+            request_id,
+            auto_approve,
+            "❌ Transaction rollback in accept_shift_request"
+        );
+        AppError::Internal(format!("Failed to commit shift request acceptance for request {}: {}", request_id, e))
+    })?;
+
+    tracing::debug!(request_id, "✅ Shift request transaction committed successfully");
+    crate::handlers::metrics::record_marketplace_event(if auto_approve { "auto_approved" } else { "accepted" });
+    transition.emit();
+
+    // Fetch updated request
+    let request = fetch_shift_request_with_details(&state.db, request_id).await?;
+
+    Ok(Json(request))
+}
+
+/// POST /api/marketplace/requests/{id}/withdraw - Candidate withdraws their claim on an OPEN request
+#[utoipa::path(
+    post,
+    path = "/api/marketplace/requests/{id}/withdraw",
+    params(
+        ("id" = i32, Path, description = "Shift request ID")
+    ),
+    request_body = WithdrawRequestInput,
+    responses(
+        (status = 200, description = "Claim withdrawn, request is OPEN again", body = ShiftRequestWithDetails),
+        (status = 400, description = "Request is not PENDING_APPROVAL or was not an open request"),
+        (status = 403, description = "You are not the candidate of this request"),
+        (status = 404, description = "Request not found")
+    ),
+    tag = "marketplace",
+    security(("cookie_auth" = []))
+)]
+pub async fn withdraw_shift_request(
+    State(state): State<Arc<AppState>>,
+    Path(request_id): Path<i32>,
+    auth: AuthenticatedUser,
+    Json(input): Json<WithdrawRequestInput>,
+) -> AppResult<Json<ShiftRequestWithDetails>> {
+    // The confirmed candidate (generic account flow) is only recorded; the signed-in account must hold the claim
+    let acting_user_id = input.confirmed_candidate_id.unwrap_or(auth.profile_id);
+
+    let mut tx = crate::db::begin_as_user(&state.db, auth.profile_id).await?;
+
+    // Lock the request so an approval committing meanwhile cannot be undone by the withdrawal
+    let (current_status, candidate_id, target_user_id, requester_id): (ShiftRequestStatus, Option<i32>, Option<i32>, i32) = sqlx::query_as(
+        r#"SELECT status, candidate_id, target_user_id, requester_id FROM "ShiftRequests" WHERE id = $1 FOR UPDATE"#
+    )
+    .bind(request_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Request {} not found", request_id)))?;
+
+    check_withdrawable(current_status, candidate_id, target_user_id, auth.profile_id)?;
+
+    // Revert to OPEN so someone else can claim it
+    sqlx::query(
+        r#"
+        UPDATE "ShiftRequests"
+        SET status = $1, candidate_id = NULL, target_shift_id = NULL, updated_at = NOW()
+        WHERE id = $2
+        "#
+    )
+    .bind(ShiftRequestStatus::Open)
+    .bind(request_id)
+    .execute(&mut *tx)
+    .await?;
+    let transition =
+        crate::handlers::marketplace_sla_handler::record_transition(&mut *tx, request_id, Some(current_status), ShiftRequestStatus::Open, acting_user_id)
+            .await?;
+
+    crate::handlers::notifications_handler::notify(
+        &mut *tx,
+        requester_id,
+        "MARKETPLACE_CANDIDATE_WITHDREW",
+        "The colleague who accepted your shift request has withdrawn. Your request is open again.",
+        serde_json::json!({ "shift_request_id": request_id }),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    tracing::info!(
+        request_id,
+        candidate_id = acting_user_id,
+        "↩️ Candidate withdrew from shift request, reopened"
+    );
+    crate::handlers::metrics::record_marketplace_event("withdrawn");
+    transition.emit();
+
+    // Fetch updated request
+    let request = fetch_shift_request_with_details(&state.db, request_id).await?;
+
+    Ok(Json(request))
+}
+
+/// POST /api/marketplace/requests/{id}/respond - Target user responds to PROPOSED swap
+#[utoipa::path(
+    post,
     path = "/api/marketplace/requests/{id}/respond",
     params(
         ("id" = i32, Path, description = "Shift request ID")
     ),
     request_body = RespondToProposalInput,
     responses(
-        (status = 200, description = "Response processed, may be auto-approved, rejected, or pending approval", body = ShiftRequestWithDetails),
-        (status = 400, description = "Request is not PROPOSED"),
+        (status = 200, description = "Response processed, may be auto-approved, rejected, or pending approval (always pending past the role's monthly swap cap)", body = ShiftRequestWithDetails),
+        (status = 400, description = "Request is not PROPOSED, or the reason code is unknown, inactive or not for rejections"),
         (status = 403, description = "You are not the target of this proposal"),
         (status = 404, description = "Request not found")
     ),
@@ -744,7 +1479,7 @@ pub async fn respond_to_proposal(
     let acting_user_id = input.confirmed_responder_id.unwrap_or(auth.profile_id);
 
     // Fetch the current request
-    let (current_status, target_user_id, requester_id, shift_id, target_shift_id): (String, Option<i32>, i32, Uuid, Option<Uuid>) = sqlx::query_as(
+    let (current_status, target_user_id, requester_id, shift_id, target_shift_id): (ShiftRequestStatus, Option<i32>, i32, Uuid, Option<Uuid>) = sqlx::query_as(
         r#"SELECT status, target_user_id, requester_id, shift_id, target_shift_id FROM "ShiftRequests" WHERE id = $1"#
     )
     .bind(request_id)
@@ -753,13 +1488,13 @@ pub async fn respond_to_proposal(
     .ok_or_else(|| AppError::NotFound(format!("Request {} not found", request_id)))?;
 
     // Validate request is PROPOSED
-    if current_status != "PROPOSED" {
-        return Err(AppError::BadRequest(format!("Request is not PROPOSED, current status: {}", current_status)));
+    if current_status != ShiftRequestStatus::Proposed {
+        return Err(AppError::InvalidStateTransition { from: current_status, action: "respond to" });
     }
 
     // Validate user is the target
     if target_user_id != Some(acting_user_id) {
-        return Err(AppError::Forbidden("You are not the target of this proposal".to_string()));
+        return Err(AppError::NotRequestParty { party: "target" });
     }
 
     if input.accept {
@@ -776,24 +1511,36 @@ pub async fn respond_to_proposal(
         .fetch_one(&state.db)
         .await?;
 
-        let new_status = if auto_approve { "APPROVED" } else { "PENDING_APPROVAL" };
-
         // Start transaction
-        let mut tx = state.db.begin().await?;
+        let mut tx = crate::db::begin_as_user(&state.db, acting_user_id).await?;
+
+        // Past the role's monthly swap cap only an admin can approve
+        lock_swap_cap(&mut tx, shift_id).await?;
+        let over_cap = parties_over_swap_cap(&mut *tx, shift_id, &[requester_id, acting_user_id]).await?;
+        let swap_cap_exceeded = !over_cap.is_empty();
+        if swap_cap_exceeded {
+            tracing::info!(request_id, over_cap = ?over_cap, "⚖️ Monthly swap cap reached, proposal needs an admin override");
+        }
+        let auto_approve = auto_approve && !swap_cap_exceeded;
+
+        let new_status = if auto_approve { ShiftRequestStatus::Approved } else { ShiftRequestStatus::PendingApproval };
+        validate_transition(current_status, new_status, "respond to")?;
 
         // Update request status
         sqlx::query(
             r#"
             UPDATE "ShiftRequests"
-            SET candidate_id = $1, status = $2, updated_at = NOW()
+            SET candidate_id = $1, status = $2, swap_cap_exceeded = $4, updated_at = NOW()
             WHERE id = $3
             "#
         )
         .bind(acting_user_id)
         .bind(new_status)
         .bind(request_id)
+        .bind(swap_cap_exceeded)
         .execute(&mut *tx)
         .await?;
+        let transition = crate::handlers::marketplace_sla_handler::record_transition(&mut *tx, request_id, Some(current_status), new_status, acting_user_id).await?;
 
         // If auto-approve, perform the swap immediately
         if auto_approve {
@@ -803,7 +1550,7 @@ pub async fn respond_to_proposal(
                 target_user_id = acting_user_id,
                 "✅🔄 Target user accepted proposal, auto-approving swap"
             );
-            perform_shift_swap(&mut tx, shift_id, acting_user_id, target_shift_id, requester_id).await?;
+            perform_shift_swap(&mut tx, request_id, shift_id, acting_user_id, target_shift_id, requester_id).await?;
 
             // Mark as resolved
             sqlx::query(r#"UPDATE "ShiftRequests" SET resolved_by = $1, resolved_at = NOW() WHERE id = $2"#)
@@ -817,6 +1564,7 @@ pub async fn respond_to_proposal(
                 target_user_id = acting_user_id,
                 "📝 Target user accepted proposal, pending admin approval"
             );
+            notify_approvers(&mut tx, &state.config, request_id, shift_id, &[requester_id, acting_user_id]).await?;
         }
 
         tx.commit().await.map_err(|e| {
@@ -828,24 +1576,33 @@ pub async fn respond_to_proposal(
             );
             AppError::Internal(format!("Failed to commit proposal response for request {}: {}", request_id, e))
         })?;
+        crate::handlers::metrics::record_marketplace_event("proposal_accepted");
+        transition.emit();
     } else {
+        validate_transition(current_status, ShiftRequestStatus::Rejected, "respond to")?;
+        let reason_code = crate::handlers::marketplace_reasons_handler::validate_reason_code(&state.db, input.reason_code.as_deref(), ReasonOutcome::Reject).await?;
+
         tracing::info!(
             request_id,
             target_user_id = acting_user_id,
             "❌ Target user rejected proposal"
         );
 
+        let mut tx = crate::db::begin_as_user(&state.db, acting_user_id).await?;
+
         // Rejected by target user
         sqlx::query(
             r#"
             UPDATE "ShiftRequests"
-            SET status = 'REJECTED', resolved_by = $1, resolved_at = NOW(), updated_at = NOW()
-            WHERE id = $2
+            SET status = $1, resolved_by = $2, resolved_at = NOW(), updated_at = NOW(), reason_code = $4
+            WHERE id = $3
             "#
         )
+        .bind(ShiftRequestStatus::Rejected)
         .bind(acting_user_id)
         .bind(request_id)
-        .execute(&state.db)
+        .bind(reason_code)
+        .execute(&mut *tx)
         .await
         .map_err(|e| {
             tracing::error!(
@@ -856,6 +1613,12 @@ pub async fn respond_to_proposal(
             );
             e
         })?;
+        let transition =
+            crate::handlers::marketplace_sla_handler::record_transition(&mut *tx, request_id, Some(current_status), ShiftRequestStatus::Rejected, acting_user_id)
+                .await?;
+        tx.commit().await?;
+        transition.emit();
+        crate::handlers::metrics::record_marketplace_event("proposal_rejected");
     }
 
     // Fetch updated request
@@ -874,9 +1637,10 @@ pub async fn respond_to_proposal(
     request_body = AdminDecisionInput,
     responses(
         (status = 200, description = "Admin decision processed, shift swap performed if approved", body = ShiftRequestWithDetails),
-        (status = 400, description = "Request is not PENDING_APPROVAL or has no candidate"),
+        (status = 400, description = "Request is not PENDING_APPROVAL, has no candidate, or the reason code is unknown, inactive or not for rejections"),
         (status = 403, description = "Missing can_edit_rota permission"),
-        (status = 404, description = "Request not found")
+        (status = 404, description = "Request not found"),
+        (status = 409, description = "The month is locked for payroll, or approving passes the role's monthly swap cap without override_swap_cap")
     ),
     tag = "marketplace",
     security(("cookie_auth" = []))
@@ -887,14 +1651,15 @@ pub async fn admin_decision(
     auth: AuthenticatedUser,
     Json(input): Json<AdminDecisionInput>,
 ) -> AppResult<Json<ShiftRequestWithDetails>> {
-    // Check permission
-    if !crate::extractors::permissions::has_permission_by_name(&state.db, auth.profile_id, auth.is_super_admin, "can_edit_rota").await? {
-        return Err(AppError::Forbidden("Missing can_edit_rota permission".to_string()));
-    }
+    // Check permission (approvers, or their delegates while a delegation is active)
+    let delegate_of = approval_authority(&state, &auth)
+        .await?
+        .ok_or_else(|| AppError::Forbidden("Missing can_edit_rota permission".to_string()))?
+        .delegate_of();
 
     // Fetch the current request
-    let (current_status, shift_id, candidate_id, target_shift_id, requester_id): (String, Uuid, Option<i32>, Option<Uuid>, i32) = sqlx::query_as(
-        r#"SELECT status, shift_id, candidate_id, target_shift_id, requester_id FROM "ShiftRequests" WHERE id = $1"#
+    let (current_status, request_type, shift_id, candidate_id, target_shift_id, requester_id): (ShiftRequestStatus, ShiftRequestType, Uuid, Option<i32>, Option<Uuid>, i32) = sqlx::query_as(
+        r#"SELECT status, type, shift_id, candidate_id, target_shift_id, requester_id FROM "ShiftRequests" WHERE id = $1"#
     )
     .bind(request_id)
     .fetch_optional(&state.db)
@@ -902,40 +1667,74 @@ pub async fn admin_decision(
     .ok_or_else(|| AppError::NotFound(format!("Request {} not found", request_id)))?;
 
     // Validate request is PENDING_APPROVAL
-    if current_status != "PENDING_APPROVAL" {
-        return Err(AppError::BadRequest(format!("Request is not PENDING_APPROVAL, current status: {}", current_status)));
+    if current_status != ShiftRequestStatus::PendingApproval {
+        return Err(AppError::InvalidStateTransition { from: current_status, action: "decide on" });
     }
 
     let candidate_id = candidate_id.ok_or_else(|| AppError::BadRequest("Request has no candidate".to_string()))?;
 
+    let new_status = if input.approve { ShiftRequestStatus::Approved } else { ShiftRequestStatus::Rejected };
+    validate_transition(current_status, new_status, "decide on")?;
+
     if input.approve {
+        // Start transaction
+        let mut tx = crate::db::begin_as_user(&state.db, auth.profile_id).await?;
+        if let Some(delegator_id) = delegate_of {
+            crate::db::set_acting_for(&mut tx, delegator_id).await?;
+        }
+
+        // Counts are taken again now: other approvals may have used up the cap since the request was accepted
+        lock_swap_cap(&mut tx, shift_id).await?;
+        let over_cap = parties_over_swap_cap(&mut *tx, shift_id, &[requester_id, candidate_id]).await?;
+        if !over_cap.is_empty() && !input.override_swap_cap {
+            return Err(AppError::Conflict(format!(
+                "Approving would take user(s) {} past the role's monthly swap cap; set override_swap_cap to approve anyway",
+                over_cap.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+            )));
+        }
+        let swap_cap_override_by = (!over_cap.is_empty()).then_some(auth.profile_id);
+
         tracing::info!(
             request_id,
             shift_id = %shift_id,
             candidate_id,
             admin_id = auth.profile_id,
+            delegate_of = ?delegate_of,
+            swap_cap_override = swap_cap_override_by.is_some(),
             "✅ Admin approving shift request"
         );
 
-        // Start transaction
-        let mut tx = state.db.begin().await?;
-
         // Perform the swap
-        perform_shift_swap(&mut tx, shift_id, candidate_id, target_shift_id, requester_id).await?;
+        perform_shift_swap(&mut tx, request_id, shift_id, candidate_id, target_shift_id, requester_id).await?;
+
+        // A cover volunteer was accepted: take the shift off the cover board
+        let mut transitions = Vec::new();
+        if request_type == ShiftRequestType::Pickup {
+            transitions =
+                crate::handlers::cover_board_handler::close_cover(&mut tx, shift_id, request_id, auth.profile_id).await?;
+        }
 
         // Update request status
         sqlx::query(
             r#"
             UPDATE "ShiftRequests"
-            SET status = 'APPROVED', resolved_by = $1, resolved_at = NOW(), notes = $2, updated_at = NOW()
-            WHERE id = $3
+            SET status = $1, resolved_by = $2, resolved_at = NOW(), notes = $3, updated_at = NOW(),
+                resolved_as_delegate_of = $5, swap_cap_exceeded = $6, swap_cap_override_by = $7
+            WHERE id = $4
             "#
         )
+        .bind(new_status)
         .bind(auth.profile_id)
         .bind(&input.notes)
         .bind(request_id)
+        .bind(delegate_of)
+        .bind(swap_cap_override_by.is_some())
+        .bind(swap_cap_override_by)
         .execute(&mut *tx)
         .await?;
+        transitions.push(
+            crate::handlers::marketplace_sla_handler::record_transition(&mut *tx, request_id, Some(current_status), new_status, auth.profile_id).await?,
+        );
 
         tx.commit().await.map_err(|e| {
             tracing::error!(
@@ -948,25 +1747,36 @@ pub async fn admin_decision(
         })?;
 
         tracing::info!(request_id, "✅ Admin approval transaction committed successfully");
+        crate::handlers::metrics::record_marketplace_event("admin_approved");
+        transitions.iter().for_each(|t| t.emit());
     } else {
+        let reason_code = crate::handlers::marketplace_reasons_handler::validate_reason_code(&state.db, input.reason_code.as_deref(), ReasonOutcome::Reject).await?;
         tracing::info!(
             request_id,
             admin_id = auth.profile_id,
+            delegate_of = ?delegate_of,
+            reason_code = ?reason_code,
             "❌ Admin rejecting shift request"
         );
 
+        let mut tx = crate::db::begin_as_user(&state.db, auth.profile_id).await?;
+
         // Rejected by admin
         sqlx::query(
             r#"
             UPDATE "ShiftRequests"
-            SET status = 'REJECTED', resolved_by = $1, resolved_at = NOW(), notes = $2, updated_at = NOW()
-            WHERE id = $3
+            SET status = $1, resolved_by = $2, resolved_at = NOW(), notes = $3, updated_at = NOW(),
+                resolved_as_delegate_of = $5, reason_code = $6
+            WHERE id = $4
             "#
         )
+        .bind(new_status)
         .bind(auth.profile_id)
         .bind(&input.notes)
         .bind(request_id)
-        .execute(&state.db)
+        .bind(delegate_of)
+        .bind(reason_code)
+        .execute(&mut *tx)
         .await
         .map_err(|e| {
             tracing::error!(
@@ -978,7 +1788,12 @@ pub async fn admin_decision(
             e
         })?;
 
+        let transition =
+            crate::handlers::marketplace_sla_handler::record_transition(&mut *tx, request_id, Some(current_status), new_status, auth.profile_id).await?;
+        tx.commit().await?;
+        transition.emit();
         tracing::info!(request_id, "✅ Shift request rejected successfully");
+        crate::handlers::metrics::record_marketplace_event("admin_rejected");
     }
 
     // Fetch updated request
@@ -993,11 +1808,12 @@ pub async fn admin_decision(
     path = "/api/marketplace/requests/{id}",
     params(
         ("id" = i32, Path, description = "Shift request ID"),
-        ("confirmedRequesterId" = Option<i32>, Query, description = "For generic accounts - PIN-verified user ID")
+        ("confirmedRequesterId" = Option<i32>, Query, description = "For generic accounts - PIN-verified user ID"),
+        ("reasonCode" = Option<String>, Query, description = "Active reason code that applies to cancellations")
     ),
     responses(
         (status = 200, description = "Request cancelled successfully", body = MarketplaceMutationResponse),
-        (status = 400, description = "Cannot cancel request with current status"),
+        (status = 400, description = "Cannot cancel request with current status, or the reason code is not valid for cancellations"),
         (status = 403, description = "You can only cancel your own requests"),
         (status = 404, description = "Request not found")
     ),
@@ -1014,7 +1830,7 @@ pub async fn cancel_shift_request(
     let acting_user_id = params.confirmed_requester_id.unwrap_or(auth.profile_id);
 
     // Fetch the current request
-    let (current_status, requester_id): (String, i32) = sqlx::query_as(
+    let (current_status, requester_id): (ShiftRequestStatus, i32) = sqlx::query_as(
         r#"SELECT status, requester_id FROM "ShiftRequests" WHERE id = $1"#
     )
     .bind(request_id)
@@ -1024,26 +1840,38 @@ pub async fn cancel_shift_request(
 
     // Only requester can cancel (compare with acting user, not auth user)
     if requester_id != acting_user_id {
-        return Err(AppError::Forbidden("You can only cancel your own requests".to_string()));
+        return Err(AppError::NotRequestParty { party: "requester" });
     }
 
     // Cannot cancel if already resolved
-    if current_status == "APPROVED" || current_status == "REJECTED" || current_status == "CANCELLED" {
-        return Err(AppError::BadRequest(format!("Cannot cancel request with status: {}", current_status)));
+    if !current_status.can_transition_to(ShiftRequestStatus::Cancelled) {
+        return Err(AppError::InvalidStateTransition { from: current_status, action: "cancel" });
     }
+    let reason_code = crate::handlers::marketplace_reasons_handler::validate_reason_code(&state.db, params.reason_code.as_deref(), ReasonOutcome::Cancel).await?;
+
+    let mut tx = crate::db::begin_as_user(&state.db, auth.profile_id).await?;
 
     // Update request status to CANCELLED
     sqlx::query(
         r#"
         UPDATE "ShiftRequests"
-        SET status = 'CANCELLED', resolved_by = $1, resolved_at = NOW(), updated_at = NOW()
-        WHERE id = $2
+        SET status = $1, resolved_by = $2, resolved_at = NOW(), updated_at = NOW(), reason_code = $4
+        WHERE id = $3
         "#
     )
+    .bind(ShiftRequestStatus::Cancelled)
     .bind(acting_user_id)
     .bind(request_id)
-    .execute(&state.db)
+    .bind(reason_code)
+    .execute(&mut *tx)
     .await?;
+    let transition =
+        crate::handlers::marketplace_sla_handler::record_transition(&mut *tx, request_id, Some(current_status), ShiftRequestStatus::Cancelled, acting_user_id)
+            .await?;
+    tx.commit().await?;
+    transition.emit();
+
+    crate::handlers::metrics::record_marketplace_event("cancelled");
 
     Ok(Json(MarketplaceMutationResponse {
         success: true,
@@ -1051,59 +1879,410 @@ pub async fn cancel_shift_request(
     }))
 }
 
-/// Helper function to perform the actual shift swap in a transaction
+const MAX_FORCE_CANCEL_REASON_LENGTH: usize = 500;
+
+/// A request with its shifts as they are now; either shift may have been deleted since the request was made
+#[derive(Debug, FromRow)]
+struct StuckRequestRow {
+    status: ShiftRequestStatus,
+    request_type: ShiftRequestType,
+    requester_id: i32,
+    candidate_id: Option<i32>,
+    target_user_id: Option<i32>,
+    shift_id: Uuid,
+    target_shift_id: Option<Uuid>,
+    shift_exists: bool,
+    shift_role_id: Option<i32>,
+    shift_owner: Option<i32>,
+    target_shift_exists: bool,
+    target_shift_role_id: Option<i32>,
+    target_owner: Option<i32>,
+}
+
+/// Ways the request no longer matches its shifts
+fn request_issues(row: &StuckRequestRow) -> Vec<String> {
+    let owner = |owner: Option<i32>| owner.map_or("nobody".to_string(), |id| format!("user {}", id));
+    let mut issues = Vec::new();
+
+    if !row.shift_exists {
+        issues.push(format!("Shift {} no longer exists", row.shift_id));
+    } else if row.request_type != ShiftRequestType::Pickup && row.shift_owner != Some(row.requester_id) {
+        // Volunteers pick up unassigned shifts; everything else hands over the requester's own shift
+        issues.push(format!("Shift {} is assigned to {}, not the requester", row.shift_id, owner(row.shift_owner)));
+    }
+
+    if let Some(target_shift_id) = row.target_shift_id {
+        let other_party = row.candidate_id.or(row.target_user_id);
+        if !row.target_shift_exists {
+            issues.push(format!("Target shift {} no longer exists", target_shift_id));
+        } else if other_party.is_some() && row.target_owner != other_party {
+            issues.push(format!(
+                "Target shift {} is assigned to {}, not the other party",
+                target_shift_id,
+                owner(row.target_owner)
+            ));
+        }
+    }
+    issues
+}
+
+/// GET /api/marketplace/requests/by-token/{token} - Open the request behind a notification's action link
+#[utoipa::path(
+    get,
+    path = "/api/marketplace/requests/by-token/{token}",
+    params(
+        ("token" = String, Path, description = "action_token from a MARKETPLACE_APPROVAL_NEEDED notification")
+    ),
+    responses(
+        (status = 200, description = "The request with the caller's permission context", body = ShiftRequestByToken),
+        (status = 401, description = "Invalid or expired link"),
+        (status = 403, description = "The link was sent to someone else"),
+        (status = 404, description = "Request not found")
+    ),
+    tag = "marketplace",
+    security(("cookie_auth" = []))
+)]
+pub async fn get_shift_request_by_token(
+    State(state): State<Arc<AppState>>,
+    Path(token): Path<String>,
+    auth: AuthenticatedUser,
+) -> AppResult<Json<ShiftRequestByToken>> {
+    let action = crate::auth::validate_action_token(&token, &state.config.pin_token_secret)?;
+
+    // The link only identifies the request; what the caller may do is still decided by their own permissions
+    if action.user_profile_id != auth.profile_id {
+        tracing::warn!(request_id = action.request_id, recipient = action.user_profile_id, profile_id = auth.profile_id, "🔐 Action link opened by another user");
+        return Err(AppError::Forbidden("This link was sent to someone else".to_string()));
+    }
+
+    let request = fetch_shift_request_with_details(&state.db, action.request_id).await?;
+
+    let authority = approval_authority(&state, &auth).await?;
+    let can_decide = authority.is_some() && request.request.status == ShiftRequestStatus::PendingApproval;
+    let delegate_of = authority.and_then(ApprovalAuthority::delegate_of);
+
+    let requires_swap_cap_override = match (can_decide, request.request.candidate_id) {
+        (true, Some(candidate_id)) => {
+            !parties_over_swap_cap(&state.db, request.request.shift_id, &[request.request.requester_id, candidate_id])
+                .await?
+                .is_empty()
+        }
+        _ => false,
+    };
+
+    Ok(Json(ShiftRequestByToken {
+        request,
+        can_decide,
+        delegate_of,
+        requires_swap_cap_override,
+        link_expires_at: DateTime::from_timestamp(action.expires_at, 0).unwrap_or_default(),
+    }))
+}
+
+/// POST /api/marketplace/requests/{id}/force-cancel - Admin cancels a request that cannot proceed
+#[utoipa::path(
+    post,
+    path = "/api/marketplace/requests/{id}/force-cancel",
+    params(
+        ("id" = i32, Path, description = "Shift request ID")
+    ),
+    request_body = ForceCancelRequestInput,
+    responses(
+        (status = 200, description = "Request cancelled, inconsistencies found and parties notified", body = ForceCancelResponse),
+        (status = 400, description = "Missing or overlong reason, invalid reason code, or the request is already resolved"),
+        (status = 403, description = "Missing can_edit_rota permission for the request's role (super admin when both shifts are gone)"),
+        (status = 404, description = "Request not found")
+    ),
+    tag = "marketplace",
+    security(("cookie_auth" = []))
+)]
+pub async fn force_cancel_shift_request(
+    State(state): State<Arc<AppState>>,
+    Path(request_id): Path<i32>,
+    auth: AuthenticatedUser,
+    Json(input): Json<ForceCancelRequestInput>,
+) -> AppResult<Json<ForceCancelResponse>> {
+    let reason = input.reason.trim();
+    if reason.is_empty() {
+        return Err(AppError::BadRequest("reason is required".to_string()));
+    }
+    if reason.chars().count() > MAX_FORCE_CANCEL_REASON_LENGTH {
+        return Err(AppError::BadRequest(format!("reason must be at most {} characters", MAX_FORCE_CANCEL_REASON_LENGTH)));
+    }
+    let reason_code = crate::handlers::marketplace_reasons_handler::validate_reason_code(&state.db, input.reason_code.as_deref(), ReasonOutcome::Cancel).await?;
+
+    let row = sqlx::query_as::<_, StuckRequestRow>(
+        r#"
+        SELECT
+            sr.status,
+            sr.type AS request_type,
+            sr.requester_id,
+            sr.candidate_id,
+            sr.target_user_id,
+            sr.shift_id,
+            sr.target_shift_id,
+            s.uuid IS NOT NULL AS shift_exists,
+            s.role_id AS shift_role_id,
+            s.user_profile_id AS shift_owner,
+            ts.uuid IS NOT NULL AS target_shift_exists,
+            ts.role_id AS target_shift_role_id,
+            ts.user_profile_id AS target_owner
+        FROM "ShiftRequests" sr
+        LEFT JOIN "Shifts" s ON s.uuid = sr.shift_id
+        LEFT JOIN "Shifts" ts ON ts.uuid = sr.target_shift_id
+        WHERE sr.id = $1
+        "#,
+    )
+    .bind(request_id)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Request {} not found", request_id)))?;
+
+    // The role comes from whichever shift is left; with both gone only a super admin can tell whose request it was
+    let roles: Vec<i32> = row.shift_role_id.into_iter().chain(row.target_shift_role_id).collect();
+    let allowed = crate::extractors::permissions::has_permission(&state, auth.profile_id, auth.is_super_admin, |r| {
+        roles.contains(&r.role_id) && r.can_edit_rota
+    })
+    .await?;
+    if !allowed {
+        return Err(AppError::Forbidden("Missing can_edit_rota permission".to_string()));
+    }
+
+    validate_transition(row.status, ShiftRequestStatus::Cancelled, "force-cancel")?;
+    let issues = request_issues(&row);
+
+    let mut tx = crate::db::begin_as_user(&state.db, auth.profile_id).await?;
+
+    // A deleted target shift would otherwise stay referenced from the request
+    sqlx::query(
+        r#"
+        UPDATE "ShiftRequests"
+        SET status = $1, resolved_by = $2, resolved_at = NOW(), notes = $3, updated_at = NOW(),
+            target_shift_id = CASE WHEN $5 THEN target_shift_id END, reason_code = $6
+        WHERE id = $4
+        "#,
+    )
+    .bind(ShiftRequestStatus::Cancelled)
+    .bind(auth.profile_id)
+    .bind(reason)
+    .bind(request_id)
+    .bind(row.target_shift_exists)
+    .bind(reason_code)
+    .execute(&mut *tx)
+    .await?;
+    let transition = crate::handlers::marketplace_sla_handler::record_transition(
+        &mut *tx,
+        request_id,
+        Some(row.status),
+        ShiftRequestStatus::Cancelled,
+        auth.profile_id,
+    )
+    .await?;
+
+    let mut notified: Vec<i32> = Vec::new();
+    for party in std::iter::once(row.requester_id).chain(row.candidate_id).chain(row.target_user_id) {
+        if party == auth.profile_id || notified.contains(&party) {
+            continue;
+        }
+        crate::handlers::notifications_handler::notify(
+            &mut *tx,
+            party,
+            "MARKETPLACE_REQUEST_FORCE_CANCELLED",
+            &format!("A rota admin cancelled a shift request you were part of: {}", reason),
+            serde_json::json!({ "shift_request_id": request_id, "shift_id": row.shift_id, "reason": reason }),
+        )
+        .await?;
+        notified.push(party);
+    }
+
+    tx.commit().await?;
+
+    tracing::warn!(
+        request_id,
+        admin_id = auth.profile_id,
+        previous_status = row.status.as_str(),
+        issues = ?issues,
+        "🧹 Shift request force-cancelled"
+    );
+    crate::handlers::metrics::record_marketplace_event("force_cancelled");
+    transition.emit();
+
+    Ok(Json(ForceCancelResponse {
+        request_id,
+        previous_status: row.status,
+        issues,
+        notified,
+    }))
+}
+
+/// Reject status changes that the marketplace state machine does not allow
+fn validate_transition(from: ShiftRequestStatus, to: ShiftRequestStatus, action: &'static str) -> AppResult<()> {
+    if !from.can_transition_to(to) {
+        return Err(AppError::InvalidStateTransition { from, action });
+    }
+    Ok(())
+}
+
+/// Parties already at the cap: one more approval would take them past it
+fn over_swap_cap(cap: i16, approved: &[(i32, i64)]) -> Vec<i32> {
+    approved
+        .iter()
+        .filter(|(_, count)| *count >= i64::from(cap))
+        .map(|(user_id, _)| *user_id)
+        .collect()
+}
+
+/// Serialise swap cap checks on the shift's role and month until the transaction ends, so two approvals cannot
+/// both count the cap as free. Call before `parties_over_swap_cap` on the transaction that performs the swap.
+async fn lock_swap_cap(conn: &mut sqlx::PgConnection, shift_id: Uuid) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        SELECT pg_advisory_xact_lock(hashtextextended(
+            'swap-cap:' || s.role_id::text || ':' || date_trunc('month', s.date)::date::text, 0
+        ))
+        FROM "Shifts" s
+        WHERE s.uuid = $1
+        "#,
+    )
+    .bind(shift_id)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// Parties that approving a request on `shift_id` would take past the role's max_swaps_per_month, counting
+/// their approved requests (as requester or candidate) on the role's shifts in the shift's month. Empty when
+/// the role has no cap.
+async fn parties_over_swap_cap<'e, E>(db: E, shift_id: Uuid, parties: &[i32]) -> AppResult<Vec<i32>>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let rows: Vec<(i32, i64, i16)> = sqlx::query_as(
+        r#"
+        SELECT p.user_id, COUNT(sr.id), r.max_swaps_per_month
+        FROM "Shifts" target
+        INNER JOIN "Roles" r ON r.id = target.role_id
+        CROSS JOIN unnest($2::int4[]) AS p(user_id)
+        LEFT JOIN "ShiftRequests" sr ON sr.status = 'APPROVED'
+            AND p.user_id IN (sr.requester_id, sr.candidate_id)
+            AND EXISTS (
+                SELECT 1 FROM "Shifts" s
+                WHERE s.uuid = sr.shift_id
+                  AND s.role_id = target.role_id
+                  AND date_trunc('month', s.date) = date_trunc('month', target.date)
+            )
+        WHERE target.uuid = $1 AND r.max_swaps_per_month IS NOT NULL
+        GROUP BY p.user_id, r.max_swaps_per_month
+        "#,
+    )
+    .bind(shift_id)
+    .bind(parties)
+    .fetch_all(db)
+    .await?;
+
+    let Some(&(_, _, cap)) = rows.first() else {
+        return Ok(Vec::new());
+    };
+    let approved: Vec<(i32, i64)> = rows.into_iter().map(|(user_id, count, _)| (user_id, count)).collect();
+    Ok(over_swap_cap(cap, &approved))
+}
+
+/// Helper function to perform the actual shift swap in a transaction. A request that covers a series moves
+/// every shift of it the original owner still holds, and a target shift in a series brings its series (as
+/// held by the new owner) back the other way, so a week of nights swaps for a week of nights.
 async fn perform_shift_swap(
     tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    request_id: i32,
     shift_id: Uuid,
     new_owner_id: i32,
     target_shift_id: Option<Uuid>,
     original_owner_id: i32,
 ) -> AppResult<()> {
-    // Assign the original shift to the new owner
-    sqlx::query(r#"UPDATE "Shifts" SET user_profile_id = $1 WHERE uuid = $2"#)
-        .bind(new_owner_id)
-        .bind(shift_id)
-        .execute(&mut **tx)
+    let series_id: Option<Uuid> = sqlx::query_scalar(r#"SELECT series_id FROM "ShiftRequests" WHERE id = $1"#)
+        .bind(request_id)
+        .fetch_one(&mut **tx)
         .await?;
+    let given = series_shift_ids(tx, shift_id, series_id, original_owner_id).await?;
 
-    // If there's a target shift (for swaps), verify ownership and assign to original owner
+    // If there's a target shift (for swaps), verify ownership before anything changes hands
+    let mut taken = Vec::new();
     if let Some(target_shift_id) = target_shift_id {
-        let target_owner: Option<(Option<i32>,)> = sqlx::query_as(
-            r#"SELECT user_profile_id FROM "Shifts" WHERE uuid = $1"#
+        let target: Option<(Option<i32>, Option<Uuid>)> = sqlx::query_as(
+            r#"SELECT user_profile_id, series_id FROM "Shifts" WHERE uuid = $1"#
         )
         .bind(target_shift_id)
         .fetch_optional(&mut **tx)
         .await?;
 
-        match target_owner {
+        let target_series_id = match target {
             None => {
                 return Err(AppError::NotFound(format!("🔍 Target shift {} not found", target_shift_id)));
             }
-            Some((owner,)) if owner != Some(new_owner_id) => {
+            Some((owner, _)) if owner != Some(new_owner_id) => {
                 tracing::warn!(
                     target_shift = %target_shift_id,
                     expected_owner = new_owner_id,
                     actual_owner = ?owner,
                     "⚠️ Target shift ownership mismatch"
                 );
-                return Err(AppError::BadRequest("Target shift does not belong to the expected user".to_string()));
+                return Err(AppError::SwapTargetMismatch);
             }
-            _ => {}
-        }
+            Some((_, target_series_id)) => target_series_id.filter(|_| series_id.is_some()),
+        };
+        taken = series_shift_ids(tx, target_shift_id, target_series_id, new_owner_id).await?;
+    }
 
-        sqlx::query(r#"UPDATE "Shifts" SET user_profile_id = $1 WHERE uuid = $2"#)
-            .bind(original_owner_id)
-            .bind(target_shift_id)
+    // Shifts in a month locked for payroll never change hands, whichever path approved the swap
+    let shift_ids: Vec<Uuid> = given.iter().chain(&taken).copied().collect();
+    crate::handlers::payroll_locks_handler::ensure_shifts_unlocked(tx, &shift_ids).await?;
+
+    // Assign the original shift(s) to the new owner, and any target shift(s) to the original owner
+    for (owner_id, uuids) in [(new_owner_id, &given), (original_owner_id, &taken)] {
+        if uuids.is_empty() {
+            continue;
+        }
+        sqlx::query(r#"UPDATE "Shifts" SET user_profile_id = $1 WHERE uuid = ANY($2)"#)
+            .bind(owner_id)
+            .bind(uuids)
             .execute(&mut **tx)
             .await?;
     }
 
+    if series_id.is_some() {
+        tracing::info!(request_id, given = given.len(), taken = taken.len(), "🔗 Shift series changed hands");
+    }
+
     Ok(())
 }
 
+/// `shift_id` alone, or with `series_id` every shift of that series `owner_id` holds, in date order
+async fn series_shift_ids(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    shift_id: Uuid,
+    series_id: Option<Uuid>,
+    owner_id: i32,
+) -> AppResult<Vec<Uuid>> {
+    let Some(series_id) = series_id else {
+        return Ok(vec![shift_id]);
+    };
+
+    Ok(sqlx::query_scalar(
+        r#"
+        SELECT uuid FROM "Shifts"
+        WHERE uuid = $1 OR (series_id = $2 AND user_profile_id = $3)
+        ORDER BY date, start
+        "#,
+    )
+    .bind(shift_id)
+    .bind(series_id)
+    .bind(owner_id)
+    .fetch_all(&mut **tx)
+    .await?)
+}
+
 /// Helper function to check if user has a specific permission
 /// Helper function to fetch a shift request by ID with full details
-async fn fetch_shift_request_with_details(
+pub async fn fetch_shift_request_with_details(
     db: &sqlx::PgPool,
     request_id: i32,
 ) -> AppResult<ShiftRequestWithDetails> {
@@ -1117,3 +2296,160 @@ async fn fetch_shift_request_with_details(
 
     Ok(row_to_shift_request_with_details(row))
 }
+
+/// A claim can be withdrawn while it awaits approval, only on a request that went through OPEN, and only by the
+/// signed-in candidate
+fn check_withdrawable(
+    status: ShiftRequestStatus,
+    candidate_id: Option<i32>,
+    target_user_id: Option<i32>,
+    caller: i32,
+) -> AppResult<()> {
+    if status != ShiftRequestStatus::PendingApproval {
+        return Err(AppError::InvalidStateTransition { from: status, action: "withdraw from" });
+    }
+
+    // Proposed swaps have a named target and never went through OPEN
+    if target_user_id.is_some() {
+        return Err(AppError::BadRequest("Only claims on open requests can be withdrawn".to_string()));
+    }
+
+    if candidate_id != Some(caller) {
+        return Err(AppError::NotRequestParty { party: "candidate" });
+    }
+
+    validate_transition(status, ShiftRequestStatus::Open, "withdraw from")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn side(uuid: u128, owner: i32, date: NaiveDate) -> SwapSide {
+        SwapSide {
+            uuid: Uuid::from_u128(uuid),
+            user_profile_id: Some(owner),
+            role_id: 1,
+            date,
+            time_off_category_id: None,
+            marketplace_auto_approve: true,
+            has_active_request: false,
+            receiver_busy: false,
+        }
+    }
+
+    #[test]
+    fn test_evaluate_swap_reports_every_failed_rule() {
+        let today = NaiveDate::from_ymd_opt(2025, 6, 1).unwrap();
+        let shift = side(1, 10, today);
+        let target = side(2, 20, NaiveDate::from_ymd_opt(2025, 6, 3).unwrap());
+
+        let ok = evaluate_swap(&shift, &target, 10, true, today);
+        assert!(ok.eligible && ok.auto_approve);
+        assert!(ok.checks.iter().all(|c| c.reason.is_none()));
+
+        let mut target = target;
+        target.role_id = 2;
+        target.receiver_busy = true;
+        let failed = evaluate_swap(&shift, &target, 20, true, today);
+        let failed_rules: Vec<&str> = failed.checks.iter().filter(|c| !c.passed).map(|c| c.rule.as_str()).collect();
+        assert!(!failed.eligible);
+        assert_eq!(failed_rules, ["SHIFT_OWNER", "TARGET_ASSIGNED", "SAME_ROLE", "NO_DOUBLE_BOOKING"]);
+    }
+
+    #[test]
+    fn test_next_bump_counts_from_last_bump_or_creation() {
+        let created = NaiveDate::from_ymd_opt(2025, 6, 1).unwrap().and_hms_opt(9, 30, 0).unwrap();
+        let bumped = NaiveDate::from_ymd_opt(2025, 6, 3).unwrap().and_hms_opt(18, 0, 0).unwrap();
+        assert_eq!(next_bump_at(created, None), created + chrono::Duration::days(1));
+        assert_eq!(next_bump_at(created, Some(bumped)), bumped + chrono::Duration::days(1));
+    }
+
+    #[test]
+    fn test_only_give_aways_can_be_bumped() {
+        assert!(check_bumpable(ShiftRequestType::GiveAway).is_ok());
+        assert!(matches!(check_bumpable(ShiftRequestType::Swap), Err(AppError::BadRequest(_))));
+        assert!(matches!(check_bumpable(ShiftRequestType::Pickup), Err(AppError::BadRequest(_))));
+    }
+
+    #[test]
+    fn test_on_behalf_admin() {
+        assert_eq!(on_behalf_admin(None, None, 7).unwrap(), None);
+        assert_eq!(on_behalf_admin(Some(7), None, 7).unwrap(), None);
+        assert_eq!(on_behalf_admin(Some(9), None, 7).unwrap(), Some(7));
+        assert_eq!(on_behalf_admin(None, Some(9), 7).unwrap(), None);
+        assert!(matches!(on_behalf_admin(Some(9), Some(9), 7), Err(AppError::BadRequest(_))));
+    }
+
+    #[test]
+    fn test_only_the_signed_in_candidate_can_withdraw() {
+        let pending = ShiftRequestStatus::PendingApproval;
+        assert!(check_withdrawable(pending, Some(7), None, 7).is_ok());
+        assert!(matches!(
+            check_withdrawable(pending, Some(7), None, 8),
+            Err(AppError::NotRequestParty { party: "candidate" })
+        ));
+        assert!(matches!(check_withdrawable(pending, Some(7), Some(9), 7), Err(AppError::BadRequest(_))));
+        assert!(matches!(
+            check_withdrawable(ShiftRequestStatus::Approved, Some(7), None, 7),
+            Err(AppError::InvalidStateTransition { .. })
+        ));
+        assert!(matches!(
+            check_withdrawable(ShiftRequestStatus::Open, None, None, 7),
+            Err(AppError::InvalidStateTransition { .. })
+        ));
+    }
+
+    #[test]
+    fn test_request_issues() {
+        let mut row = StuckRequestRow {
+            status: ShiftRequestStatus::PendingApproval,
+            request_type: ShiftRequestType::Swap,
+            requester_id: 10,
+            candidate_id: Some(20),
+            target_user_id: None,
+            shift_id: Uuid::from_u128(1),
+            target_shift_id: Some(Uuid::from_u128(2)),
+            shift_exists: true,
+            shift_role_id: Some(1),
+            shift_owner: Some(10),
+            target_shift_exists: true,
+            target_shift_role_id: Some(1),
+            target_owner: Some(20),
+        };
+        assert!(request_issues(&row).is_empty());
+
+        row.shift_owner = None;
+        row.target_shift_exists = false;
+        assert_eq!(request_issues(&row), vec![
+            format!("Shift {} is assigned to nobody, not the requester", Uuid::from_u128(1)),
+            format!("Target shift {} no longer exists", Uuid::from_u128(2)),
+        ]);
+
+        // A volunteer's pickup is for an unassigned shift
+        row.request_type = ShiftRequestType::Pickup;
+        row.target_shift_id = None;
+        assert!(request_issues(&row).is_empty());
+
+        row.shift_exists = false;
+        assert_eq!(request_issues(&row), vec![format!("Shift {} no longer exists", Uuid::from_u128(1))]);
+    }
+
+    #[test]
+    fn test_over_swap_cap() {
+        assert_eq!(over_swap_cap(3, &[(10, 2), (20, 0)]), Vec::<i32>::new());
+        assert_eq!(over_swap_cap(3, &[(10, 3), (20, 1)]), vec![10]);
+        assert_eq!(over_swap_cap(1, &[(10, 1), (20, 4)]), vec![10, 20]);
+    }
+
+    #[test]
+    fn test_give_away_offer_message() {
+        let date = NaiveDate::from_ymd_opt(2026, 3, 7).unwrap();
+        assert_eq!(give_away_offer_message(Some("Night"), date, false), "A Night shift on 2026-03-07 is up for grabs.");
+        assert_eq!(give_away_offer_message(None, date, false), "A shift on 2026-03-07 is up for grabs.");
+        assert_eq!(
+            give_away_offer_message(None, date, true),
+            "Still looking for cover: A shift on 2026-03-07 is up for grabs."
+        );
+    }
+}
@@ -0,0 +1,199 @@
+use axum::extract::{Query, State};
+use once_cell::sync::Lazy;
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::time::Instant;
+use utoipa::IntoParams;
+
+use crate::{
+    extractors::{permissions, AuthenticatedUser, Json},
+    models::{MarketplaceEvent, MarketplacePollResponse},
+    AppResult, AppState,
+};
+
+/// Polls wait this long for an event unless they ask for less; proxies tend to cut idle requests at 60s
+const DEFAULT_WAIT_SECS: u64 = 25;
+const MAX_WAIT_SECS: u64 = 30;
+
+/// While waiting, re-read the table this often to pick up changes committed by other instances
+const RECHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+const MAX_EVENTS: i64 = 200;
+
+/// Transition ids are taken before commit, so a gap may be a transaction that has yet to commit. Events after a gap
+/// are held back until the row after it is this old; after that the gap is taken to be a rollback or a deletion.
+const SETTLE_SECS: f64 = 30.0;
+
+/// Transitions after cursor $1, and the first one ($2 seconds settling time) that follows a gap still in flight
+const SETTLING_CTE: &str = r#"
+        later AS (
+            SELECT id, changed_at, id - COALESCE(LAG(id) OVER (ORDER BY id), $1) > 1 AS after_gap
+            FROM "ShiftRequestTransitions"
+            WHERE id > $1
+        ),
+        unsettled AS (
+            SELECT MIN(id) AS id FROM later WHERE after_gap AND changed_at > NOW() - make_interval(secs => $2)
+        )"#;
+
+/// Marketplace event bus: bumped after every committed shift request transition (see
+/// `RequestTransition::emit`). Waiting clients subscribe to it to wake up instead of re-querying on a
+/// timer; the events themselves are read from "ShiftRequestTransitions", so any transport can share it.
+static MARKETPLACE_EVENTS: Lazy<watch::Sender<u64>> = Lazy::new(|| watch::channel(0).0);
+
+/// Wake everyone waiting for marketplace events
+pub fn publish_event() {
+    MARKETPLACE_EVENTS.send_modify(|generation| *generation = generation.wrapping_add(1));
+}
+
+pub fn subscribe() -> watch::Receiver<u64> {
+    MARKETPLACE_EVENTS.subscribe()
+}
+
+/// How long a poll may wait, from its ?wait= seconds
+fn poll_wait(requested: Option<u64>) -> Duration {
+    Duration::from_secs(requested.unwrap_or(DEFAULT_WAIT_SECS).min(MAX_WAIT_SECS))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct PollQuery {
+    /// Cursor from the previous poll; omit to get the current cursor straight away
+    pub since: Option<i64>,
+    /// Seconds to wait for an event (default 25, max 30; 0 returns at once)
+    pub wait: Option<u64>,
+}
+
+/// Status changes after `since` that the caller may see: open requests (including their leaving OPEN), requests
+/// they are a party to, and requests in roles where they can edit the rota. Stops short of any unsettled gap, so a
+/// transition that commits late is not skipped.
+async fn events_after(
+    state: &AppState,
+    auth: &AuthenticatedUser,
+    since: i64,
+    rota_roles: &[i32],
+) -> AppResult<Vec<MarketplaceEvent>> {
+    let events = sqlx::query_as::<_, MarketplaceEvent>(&format!(
+        r#"
+        WITH {settling}
+        SELECT
+            t.id AS cursor,
+            t.shift_request_id,
+            sr.type AS request_type,
+            s.role_id,
+            t.from_status,
+            t.to_status,
+            t.changed_by,
+            t.changed_at
+        FROM "ShiftRequestTransitions" t
+        INNER JOIN "ShiftRequests" sr ON sr.id = t.shift_request_id
+        INNER JOIN "Shifts" s ON s.uuid = sr.shift_id
+        CROSS JOIN unsettled u
+        WHERE t.id > $1
+          AND (u.id IS NULL OR t.id < u.id)
+          AND (
+            $3
+            OR t.to_status = 'OPEN'
+            OR t.from_status = 'OPEN'
+            OR $4 IN (sr.requester_id, sr.target_user_id, sr.candidate_id)
+            OR s.role_id = ANY($5)
+          )
+        ORDER BY t.id
+        LIMIT $6
+        "#,
+        settling = SETTLING_CTE
+    ))
+    .bind(since)
+    .bind(SETTLE_SECS)
+    .bind(auth.is_super_admin)
+    .bind(auth.profile_id)
+    .bind(rota_roles)
+    .bind(MAX_EVENTS)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(events)
+}
+
+/// Cursor to hand a new poller: the newest transition, less any that follow a gap still in flight
+async fn current_cursor(state: &AppState) -> AppResult<i64> {
+    let settled: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COALESCE(MAX(id), 0)::int8 FROM (
+            SELECT id FROM "ShiftRequestTransitions"
+            WHERE changed_at <= NOW() - make_interval(secs => $1)
+            ORDER BY id DESC
+            LIMIT 1
+        ) settled
+        "#,
+    )
+    .bind(SETTLE_SECS)
+    .fetch_one(&state.db)
+    .await?;
+
+    let cursor: i64 = sqlx::query_scalar(&format!(
+        r#"
+        WITH {settling}
+        SELECT COALESCE(u.id - 1, (SELECT MAX(id) FROM later), $1)::int8
+        FROM unsettled u
+        "#,
+        settling = SETTLING_CTE
+    ))
+    .bind(settled)
+    .bind(SETTLE_SECS)
+    .fetch_one(&state.db)
+    .await?;
+
+    Ok(cursor)
+}
+
+/// GET /api/marketplace/poll?since=&wait= - Long-poll for shift request changes, for clients that cannot stream
+#[utoipa::path(
+    get,
+    path = "/api/marketplace/poll",
+    params(PollQuery),
+    responses(
+        (status = 200, description = "Events after the cursor (empty if none arrived within the wait)", body = MarketplacePollResponse)
+    ),
+    tag = "marketplace",
+    security(("cookie_auth" = []))
+)]
+pub async fn poll_marketplace(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+    Query(query): Query<PollQuery>,
+) -> AppResult<Json<MarketplacePollResponse>> {
+    let Some(since) = query.since else {
+        let cursor = current_cursor(&state).await?;
+        return Ok(Json(MarketplacePollResponse { events: vec![], cursor }));
+    };
+
+    let rota_roles = permissions::roles_with_permission(&state, auth.profile_id, permissions::can_edit_rota).await?;
+    // Subscribe before the first read so a transition committed in between still wakes us
+    let mut wake = subscribe();
+    let deadline = Instant::now() + poll_wait(query.wait);
+
+    let events = loop {
+        let events = events_after(&state, &auth, since, &rota_roles).await?;
+        let now = Instant::now();
+        if !events.is_empty() || now >= deadline {
+            break events;
+        }
+        let _ = tokio::time::timeout(RECHECK_INTERVAL.min(deadline - now), wake.changed()).await;
+    };
+
+    let cursor = events.last().map_or(since, |e| e.cursor);
+    Ok(Json(MarketplacePollResponse { events, cursor }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_poll_wait() {
+        assert_eq!(poll_wait(None), Duration::from_secs(25));
+        assert_eq!(poll_wait(Some(0)), Duration::ZERO);
+        assert_eq!(poll_wait(Some(300)), Duration::from_secs(30));
+    }
+}
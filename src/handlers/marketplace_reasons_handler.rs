@@ -0,0 +1,216 @@
+use axum::extract::{Path, Query, State};
+use serde::Deserialize;
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+use crate::{
+    extractors::{AuthenticatedUser, Json},
+    handlers::mfa_handler::require_super_admin,
+    models::{CreateMarketplaceReasonInput, MarketplaceReason, ReasonOutcome, UpdateMarketplaceReasonInput},
+    AppError, AppResult, AppState,
+};
+
+const MAX_LABEL_LENGTH: usize = 200;
+
+fn validate_code(code: &str) -> AppResult<()> {
+    let mut chars = code.chars();
+    let valid = (2..=40).contains(&code.len())
+        && chars.next().is_some_and(|c| c.is_ascii_uppercase())
+        && chars.all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_');
+    if !valid {
+        return Err(AppError::BadRequest(
+            "code must be 2-40 uppercase letters, digits or underscores, starting with a letter".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_label(label: &str) -> AppResult<()> {
+    let length = label.trim().chars().count();
+    if length == 0 || length > MAX_LABEL_LENGTH {
+        return Err(AppError::BadRequest(format!("label must be 1-{} characters", MAX_LABEL_LENGTH)));
+    }
+    Ok(())
+}
+
+/// Check a reason code given with a rejection or cancellation: it must exist, be active and cover the outcome
+pub async fn validate_reason_code<'e, E>(
+    executor: E,
+    code: Option<&str>,
+    outcome: ReasonOutcome,
+) -> AppResult<Option<String>>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let Some(code) = code.map(str::trim).filter(|c| !c.is_empty()) else {
+        return Ok(None);
+    };
+
+    let applies_to: Option<ReasonOutcome> =
+        sqlx::query_scalar(r#"SELECT applies_to FROM "MarketplaceReasons" WHERE code = $1 AND active"#)
+            .bind(code)
+            .fetch_optional(executor)
+            .await?;
+
+    match applies_to {
+        Some(applies_to) if applies_to.covers(outcome) => Ok(Some(code.to_string())),
+        Some(_) => Err(AppError::BadRequest(format!(
+            "Reason {} cannot be given for a {}",
+            code,
+            if outcome == ReasonOutcome::Cancel { "cancellation" } else { "rejection" }
+        ))),
+        None => Err(AppError::BadRequest(format!("Unknown or inactive reason code {}", code))),
+    }
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct GetReasonsQuery {
+    /// Include deactivated reasons (default false)
+    #[serde(rename = "includeInactive")]
+    pub include_inactive: Option<bool>,
+}
+
+/// GET /api/marketplace/reasons?includeInactive= - Reasons to offer when rejecting or cancelling a request
+#[utoipa::path(
+    get,
+    path = "/api/marketplace/reasons",
+    params(GetReasonsQuery),
+    responses(
+        (status = 200, description = "Reasons in display order", body = Vec<MarketplaceReason>)
+    ),
+    tag = "marketplace",
+    security(("cookie_auth" = []))
+)]
+pub async fn get_reasons(
+    State(state): State<Arc<AppState>>,
+    _auth: AuthenticatedUser,
+    Query(query): Query<GetReasonsQuery>,
+) -> AppResult<Json<Vec<MarketplaceReason>>> {
+    let reasons = sqlx::query_as::<_, MarketplaceReason>(
+        r#"SELECT * FROM "MarketplaceReasons" WHERE active OR $1 ORDER BY sort_order, code"#,
+    )
+    .bind(query.include_inactive.unwrap_or(false))
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(reasons))
+}
+
+/// POST /api/marketplace/reasons - Add a reason
+#[utoipa::path(
+    post,
+    path = "/api/marketplace/reasons",
+    request_body = CreateMarketplaceReasonInput,
+    responses(
+        (status = 200, description = "Reason created", body = MarketplaceReason),
+        (status = 400, description = "Invalid code or label"),
+        (status = 403, description = "Super admin permission required"),
+        (status = 409, description = "A reason with this code already exists")
+    ),
+    tag = "marketplace",
+    security(("cookie_auth" = []))
+)]
+pub async fn create_reason(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+    Json(input): Json<CreateMarketplaceReasonInput>,
+) -> AppResult<Json<MarketplaceReason>> {
+    require_super_admin(&auth)?;
+    validate_code(&input.code)?;
+    validate_label(&input.label)?;
+
+    let reason = sqlx::query_as::<_, MarketplaceReason>(
+        r#"
+        INSERT INTO "MarketplaceReasons" (code, label, applies_to, sort_order, updated_by)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (code) DO NOTHING
+        RETURNING *
+        "#,
+    )
+    .bind(&input.code)
+    .bind(input.label.trim())
+    .bind(input.applies_to)
+    .bind(input.sort_order)
+    .bind(auth.profile_id)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::Conflict(format!("Reason {} already exists", input.code)))?;
+
+    tracing::info!(code = %reason.code, admin_id = auth.profile_id, "🏷️ Marketplace reason created");
+
+    Ok(Json(reason))
+}
+
+/// PUT /api/marketplace/reasons/{code} - Update or deactivate a reason
+#[utoipa::path(
+    put,
+    path = "/api/marketplace/reasons/{code}",
+    params(
+        ("code" = String, Path, description = "Reason code")
+    ),
+    request_body = UpdateMarketplaceReasonInput,
+    responses(
+        (status = 200, description = "Reason updated", body = MarketplaceReason),
+        (status = 400, description = "Invalid label"),
+        (status = 403, description = "Super admin permission required"),
+        (status = 404, description = "Reason not found")
+    ),
+    tag = "marketplace",
+    security(("cookie_auth" = []))
+)]
+pub async fn update_reason(
+    State(state): State<Arc<AppState>>,
+    Path(code): Path<String>,
+    auth: AuthenticatedUser,
+    Json(input): Json<UpdateMarketplaceReasonInput>,
+) -> AppResult<Json<MarketplaceReason>> {
+    require_super_admin(&auth)?;
+    if let Some(label) = &input.label {
+        validate_label(label)?;
+    }
+
+    let reason = sqlx::query_as::<_, MarketplaceReason>(
+        r#"
+        UPDATE "MarketplaceReasons" SET
+            label = COALESCE($2, label),
+            applies_to = COALESCE($3, applies_to),
+            active = COALESCE($4, active),
+            sort_order = COALESCE($5, sort_order),
+            updated_by = $6,
+            updated_at = NOW()
+        WHERE code = $1
+        RETURNING *
+        "#,
+    )
+    .bind(&code)
+    .bind(input.label.as_deref().map(str::trim))
+    .bind(input.applies_to)
+    .bind(input.active)
+    .bind(input.sort_order)
+    .bind(auth.profile_id)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Reason {} not found", code)))?;
+
+    tracing::info!(code = %reason.code, active = reason.active, admin_id = auth.profile_id, "🏷️ Marketplace reason updated");
+
+    Ok(Json(reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reason_code_and_outcome() {
+        assert!(validate_code("SHORT_NOTICE").is_ok());
+        assert!(validate_code("R2").is_ok());
+        assert!(validate_code("short_notice").is_err());
+        assert!(validate_code("_SHORT").is_err());
+        assert!(validate_code("X").is_err());
+
+        assert!(ReasonOutcome::Any.covers(ReasonOutcome::Cancel));
+        assert!(ReasonOutcome::Reject.covers(ReasonOutcome::Reject));
+        assert!(!ReasonOutcome::Reject.covers(ReasonOutcome::Cancel));
+    }
+}
@@ -0,0 +1,317 @@
+use axum::extract::{Query, State};
+use chrono::{Days, NaiveDate};
+use serde::Deserialize;
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+use crate::{
+    extractors::{permissions, AuthenticatedUser},
+    models::{FailureReasonCount, MarketplaceSlaReport, ShiftRequestStatus, SwapUsage},
+    report::{Report, ReportFormat},
+    AppError, AppResult, AppState,
+};
+
+/// Report range when none is given, and the longest allowed
+const DEFAULT_WINDOW_DAYS: u64 = 30;
+const MAX_WINDOW_DAYS: i64 = 366;
+
+/// Approvals are expected within two days unless the caller asks about another target
+const DEFAULT_TARGET_HOURS: i32 = 48;
+const MAX_TARGET_HOURS: i32 = 720;
+
+/// A status change written to "ShiftRequestTransitions"; emit its metrics (and wake marketplace pollers) once
+/// the change is committed
+#[derive(Debug, Clone, Copy)]
+#[must_use = "emit the transition's metrics after committing"]
+pub struct RequestTransition {
+    from: Option<ShiftRequestStatus>,
+    to: ShiftRequestStatus,
+    waited_seconds: f64,
+}
+
+impl RequestTransition {
+    pub fn emit(&self) {
+        crate::handlers::metrics::record_request_transition(self.from.map(|s| s.as_str()), self.to.as_str(), self.waited_seconds);
+        crate::handlers::marketplace_poll_handler::publish_event();
+    }
+}
+
+/// Record that requests moved from `from` (None on creation) to `to`; the wait is measured from each
+/// request's previous transition, or its creation
+pub async fn record_transitions<'e, E>(
+    executor: E,
+    request_ids: &[i32],
+    from: Option<ShiftRequestStatus>,
+    to: ShiftRequestStatus,
+    changed_by: i32,
+) -> Result<Vec<RequestTransition>, sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let waits: Vec<f64> = sqlx::query_scalar(
+        r#"
+        INSERT INTO "ShiftRequestTransitions" (shift_request_id, from_status, to_status, changed_by, waited_seconds)
+        SELECT sr.id, $2, $3, $4, GREATEST(EXTRACT(EPOCH FROM NOW() - COALESCE(
+            (SELECT MAX(t.changed_at) FROM "ShiftRequestTransitions" t WHERE t.shift_request_id = sr.id),
+            sr.created_at
+        ))::float8, 0)
+        FROM "ShiftRequests" sr
+        WHERE sr.id = ANY($1)
+        RETURNING waited_seconds
+        "#,
+    )
+    .bind(request_ids)
+    .bind(from)
+    .bind(to)
+    .bind(changed_by)
+    .fetch_all(executor)
+    .await?;
+
+    Ok(waits.into_iter().map(|waited_seconds| RequestTransition { from, to, waited_seconds }).collect())
+}
+
+/// Single-request form of `record_transitions`
+pub async fn record_transition<'e, E>(
+    executor: E,
+    request_id: i32,
+    from: Option<ShiftRequestStatus>,
+    to: ShiftRequestStatus,
+    changed_by: i32,
+) -> Result<RequestTransition, sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let transitions = record_transitions(executor, &[request_id], from, to, changed_by).await?;
+    transitions.into_iter().next().ok_or(sqlx::Error::RowNotFound)
+}
+
+/// Requests that left PENDING_APPROVAL in the report range
+#[derive(sqlx::FromRow)]
+struct DecisionStats {
+    decided: i64,
+    approved: i64,
+    rejected: i64,
+    decided_over_target: i64,
+    p50: Option<f64>,
+    p90: Option<f64>,
+    p95: Option<f64>,
+    max: Option<f64>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct SlaQuery {
+    #[serde(rename = "roleId")]
+    pub role_id: Option<i32>,
+    /// First day of decisions counted (default 30 days before `to`)
+    pub from: Option<NaiveDate>,
+    /// Last day of decisions counted (default today)
+    pub to: Option<NaiveDate>,
+    /// Waits longer than this count as over target (default 48, max 720)
+    #[serde(rename = "targetHours")]
+    pub target_hours: Option<i32>,
+}
+
+/// Inclusive report range: defaults to the last 30 days, at most a year
+fn sla_window(from: Option<NaiveDate>, to: Option<NaiveDate>, today: NaiveDate) -> AppResult<(NaiveDate, NaiveDate)> {
+    let to = to.unwrap_or(today);
+    let from = match from {
+        Some(from) => from,
+        None => to
+            .checked_sub_days(Days::new(DEFAULT_WINDOW_DAYS - 1))
+            .ok_or_else(|| AppError::BadRequest("Invalid to date".to_string()))?,
+    };
+    if from > to {
+        return Err(AppError::BadRequest("from must not be after to".to_string()));
+    }
+    if (to - from).num_days() >= MAX_WINDOW_DAYS {
+        return Err(AppError::BadRequest(format!("The range can cover at most {} days", MAX_WINDOW_DAYS)));
+    }
+    Ok((from, to))
+}
+
+/// GET /api/marketplace/sla?roleId=&from=&to=&targetHours= - How long requests wait for approval
+#[utoipa::path(
+    get,
+    path = "/api/marketplace/sla",
+    params(SlaQuery),
+    responses(
+        (status = 200, description = "Wait percentiles for requests decided in the range, requests still waiting, and approved requests per member and month, and rejections and cancellations by reason", content(
+            (MarketplaceSlaReport = "application/json"),
+            (String = "text/csv"),
+            (Vec<u8> = "application/pdf")
+        )),
+        (status = 400, description = "Invalid range or targetHours"),
+        (status = 403, description = "Missing can_edit_rota permission (for this role)"),
+        (status = 406, description = "Accept names no supported format")
+    ),
+    tag = "marketplace",
+    security(("cookie_auth" = []))
+)]
+pub async fn get_sla_report(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+    format: ReportFormat,
+    Query(query): Query<SlaQuery>,
+) -> AppResult<Report<MarketplaceSlaReport>> {
+    let (from, to) = sla_window(query.from, query.to, chrono::Local::now().date_naive())?;
+    let target_hours = query.target_hours.unwrap_or(DEFAULT_TARGET_HOURS);
+    if !(1..=MAX_TARGET_HOURS).contains(&target_hours) {
+        return Err(AppError::BadRequest(format!("targetHours must be between 1 and {}", MAX_TARGET_HOURS)));
+    }
+
+    // Super admins see every role (None); rota admins their own, or one of them
+    let roles: Option<Vec<i32>> = if auth.is_super_admin {
+        query.role_id.map(|role_id| vec![role_id])
+    } else {
+        let admin_roles = permissions::roles_with_permission(&state, auth.profile_id, permissions::can_edit_rota).await?;
+        let roles = match query.role_id {
+            Some(role_id) if admin_roles.contains(&role_id) => vec![role_id],
+            Some(_) => return Err(AppError::Forbidden("Missing can_edit_rota permission for this role".to_string())),
+            None => admin_roles,
+        };
+        if roles.is_empty() {
+            return Err(AppError::Forbidden("Missing can_edit_rota permission".to_string()));
+        }
+        Some(roles)
+    };
+
+    let target_seconds = f64::from(target_hours) * 3600.0;
+
+    let decisions = sqlx::query_as::<_, DecisionStats>(
+        r#"
+        SELECT
+            COUNT(*) AS decided,
+            COUNT(*) FILTER (WHERE t.to_status = 'APPROVED') AS approved,
+            COUNT(*) FILTER (WHERE t.to_status = 'REJECTED') AS rejected,
+            COUNT(*) FILTER (WHERE t.waited_seconds > $4) AS decided_over_target,
+            percentile_cont(0.5) WITHIN GROUP (ORDER BY t.waited_seconds) AS p50,
+            percentile_cont(0.9) WITHIN GROUP (ORDER BY t.waited_seconds) AS p90,
+            percentile_cont(0.95) WITHIN GROUP (ORDER BY t.waited_seconds) AS p95,
+            MAX(t.waited_seconds) AS max
+        FROM "ShiftRequestTransitions" t
+        INNER JOIN "ShiftRequests" sr ON sr.id = t.shift_request_id
+        INNER JOIN "Shifts" s ON s.uuid = sr.shift_id
+        WHERE t.from_status = 'PENDING_APPROVAL'
+          AND t.changed_at >= $1 AND t.changed_at < $2::date + 1
+          AND ($3::int4[] IS NULL OR s.role_id = ANY($3))
+        "#,
+    )
+    .bind(from)
+    .bind(to)
+    .bind(&roles)
+    .bind(target_seconds)
+    .fetch_one(&state.db)
+    .await?;
+
+    let (pending, pending_over_target, oldest_pending_seconds): (i64, i64, Option<f64>) = sqlx::query_as(
+        r#"
+        WITH waiting AS (
+            SELECT EXTRACT(EPOCH FROM NOW() - COALESCE(
+                (SELECT MAX(t.changed_at) FROM "ShiftRequestTransitions" t
+                 WHERE t.shift_request_id = sr.id AND t.to_status = 'PENDING_APPROVAL'),
+                sr.updated_at
+            ))::float8 AS waited_seconds
+            FROM "ShiftRequests" sr
+            INNER JOIN "Shifts" s ON s.uuid = sr.shift_id
+            WHERE sr.status = 'PENDING_APPROVAL'
+              AND ($1::int4[] IS NULL OR s.role_id = ANY($1))
+        )
+        SELECT COUNT(*), COUNT(*) FILTER (WHERE waited_seconds > $2), MAX(waited_seconds)
+        FROM waiting
+        "#,
+    )
+    .bind(&roles)
+    .bind(target_seconds)
+    .fetch_one(&state.db)
+    .await?;
+
+    let swap_usage = sqlx::query_as::<_, SwapUsage>(
+        r#"
+        SELECT
+            s.role_id,
+            date_trunc('month', s.date)::date AS month,
+            u.user_profile_id,
+            u.short_name,
+            COUNT(*) AS approved,
+            COUNT(*) FILTER (WHERE sr.swap_cap_override_by IS NOT NULL) AS overrides,
+            r.max_swaps_per_month AS cap
+        FROM "ShiftRequests" sr
+        INNER JOIN "Shifts" s ON s.uuid = sr.shift_id
+        INNER JOIN "Roles" r ON r.id = s.role_id
+        CROSS JOIN LATERAL (VALUES (sr.requester_id), (sr.candidate_id)) AS p(user_id)
+        INNER JOIN "Users" u ON u.user_profile_id = p.user_id
+        WHERE sr.status = 'APPROVED'
+          AND s.date BETWEEN $1 AND $2
+          AND ($3::int4[] IS NULL OR s.role_id = ANY($3))
+        GROUP BY s.role_id, month, u.user_profile_id, u.short_name, r.max_swaps_per_month
+        ORDER BY month, s.role_id, approved DESC, u.user_profile_id
+        "#,
+    )
+    .bind(from)
+    .bind(to)
+    .bind(&roles)
+    .fetch_all(&state.db)
+    .await?;
+
+    let failure_reasons = sqlx::query_as::<_, FailureReasonCount>(
+        r#"
+        SELECT
+            sr.reason_code,
+            mr.label,
+            COUNT(*) FILTER (WHERE sr.status = 'REJECTED') AS rejected,
+            COUNT(*) FILTER (WHERE sr.status = 'CANCELLED') AS cancelled
+        FROM "ShiftRequests" sr
+        INNER JOIN "Shifts" s ON s.uuid = sr.shift_id
+        LEFT JOIN "MarketplaceReasons" mr ON mr.code = sr.reason_code
+        WHERE sr.status IN ('REJECTED', 'CANCELLED')
+          AND sr.resolved_at >= $1 AND sr.resolved_at < $2::date + 1
+          AND ($3::int4[] IS NULL OR s.role_id = ANY($3))
+        GROUP BY sr.reason_code, mr.label
+        ORDER BY COUNT(*) DESC, sr.reason_code
+        "#,
+    )
+    .bind(from)
+    .bind(to)
+    .bind(&roles)
+    .fetch_all(&state.db)
+    .await?;
+
+    let title = format!("Marketplace SLA {} to {}", from, to);
+    Ok(Report::new(format, title, MarketplaceSlaReport {
+        role_id: query.role_id,
+        from,
+        to,
+        target_hours,
+        decided: decisions.decided,
+        approved: decisions.approved,
+        rejected: decisions.rejected,
+        decided_over_target: decisions.decided_over_target,
+        wait_p50_seconds: decisions.p50,
+        wait_p90_seconds: decisions.p90,
+        wait_p95_seconds: decisions.p95,
+        wait_max_seconds: decisions.max,
+        pending,
+        pending_over_target,
+        oldest_pending_seconds,
+        swap_usage,
+        failure_reasons,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sla_window() {
+        let today = NaiveDate::from_ymd_opt(2025, 3, 31).unwrap();
+        let day = |m, d| NaiveDate::from_ymd_opt(2025, m, d).unwrap();
+
+        assert_eq!(sla_window(None, None, today).unwrap(), (day(3, 2), today));
+        assert_eq!(sla_window(None, Some(day(1, 30)), today).unwrap(), (day(1, 1), day(1, 30)));
+        assert_eq!(sla_window(Some(day(2, 1)), None, today).unwrap(), (day(2, 1), today));
+        assert!(sla_window(Some(day(3, 2)), Some(day(3, 1)), today).is_err());
+        assert!(sla_window(Some(NaiveDate::from_ymd_opt(2024, 3, 30).unwrap()), None, today).is_err());
+        assert!(sla_window(Some(NaiveDate::from_ymd_opt(2024, 3, 31).unwrap()), None, today).is_ok());
+    }
+}
@@ -1,22 +1,38 @@
 use axum::{extract::State, http::StatusCode, response::IntoResponse};
+use metrics::{counter, describe_counter, describe_gauge, describe_histogram, gauge, histogram};
 use metrics_exporter_prometheus::{Matcher, PrometheusBuilder, PrometheusHandle};
 use std::sync::Arc;
 
-use crate::AppState;
+use crate::{config::AppConfig, db::latency::DbLatency, AppState};
 
 pub struct MetricsState {
     pub handle: PrometheusHandle,
+    /// Rolling DB acquire/query percentiles per route (shown on /debug), fed by the tracing layer
+    pub db_latency: Arc<DbLatency>,
 }
 
+/// Status waits from a minute to a week; approvals are expected within a day or two
+const STATUS_WAIT_BUCKETS: [f64; 10] = [60.0, 300.0, 900.0, 3600.0, 14400.0, 43200.0, 86400.0, 172800.0, 259200.0, 604800.0];
+
 /// Set up the Prometheus metrics recorder
-pub fn setup_metrics_recorder() -> MetricsState {
-    let builder = PrometheusBuilder::new();
+pub fn setup_metrics_recorder(config: &AppConfig, db_latency: Arc<DbLatency>) -> MetricsState {
+    let mut builder = PrometheusBuilder::new();
+
+    // Constant labels (e.g. environment, region) added to every series
+    for (key, value) in &config.metrics_global_labels {
+        builder = builder.add_global_label(key, value);
+    }
 
     // Configure histogram buckets for latency (in seconds)
     let builder = builder
         .set_buckets_for_metric(
             Matcher::Full("http_request_duration_seconds".to_string()),
-            &[0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0],
+            &config.metrics_latency_buckets,
+        )
+        .expect("failed to set histogram buckets")
+        .set_buckets_for_metric(
+            Matcher::Full("marketplace_status_wait_seconds".to_string()),
+            &STATUS_WAIT_BUCKETS,
         )
         .expect("failed to set histogram buckets");
 
@@ -24,7 +40,53 @@ pub fn setup_metrics_recorder() -> MetricsState {
         .install_recorder()
         .expect("failed to install Prometheus recorder");
 
-    MetricsState { handle }
+    describe_counter!("marketplace_events_total", "Shift marketplace state changes, by event");
+    describe_counter!("shift_mutations_total", "Shifts created, updated or deleted via the API");
+    describe_counter!("marketplace_transitions_total", "Shift request status changes, by from and to status");
+    describe_histogram!(
+        "marketplace_status_wait_seconds",
+        "How long a shift request sat in a status before leaving it, by status"
+    );
+    describe_counter!("jwks_refresh_total", "Clerk JWKS fetches, by outcome (ok or error)");
+    describe_counter!("jwks_stale_served_total", "Requests authenticated with stale JWKS keys because Clerk was unreachable");
+    describe_gauge!("jwks_stale_age_seconds", "Age of the stale JWKS keys last served");
+    describe_gauge!("shifts_without_role", "Upcoming shifts assigned to users who cannot work shifts in the role");
+
+    MetricsState { handle, db_latency }
+}
+
+/// Upcoming shifts whose assignee lacks the role, as found by the assignment drift job
+pub fn record_assignment_drift(shifts: usize) {
+    gauge!("shifts_without_role").set(shifts as f64);
+}
+
+/// Count a marketplace state change (created, accepted, admin_approved, locum_assigned, ...)
+pub fn record_marketplace_event(event: &'static str) {
+    counter!("marketplace_events_total", "event" => event).increment(1);
+}
+
+/// Count a shift request status change and how long it waited in the status it left (None when created)
+pub fn record_request_transition(from: Option<&'static str>, to: &'static str, waited_seconds: f64) {
+    counter!("marketplace_transitions_total", "from" => from.unwrap_or("NONE"), "to" => to).increment(1);
+    if let Some(from) = from {
+        histogram!("marketplace_status_wait_seconds", "status" => from).record(waited_seconds);
+    }
+}
+
+/// Count a shift create/update/delete
+pub fn record_shift_event(action: &'static str) {
+    counter!("shift_mutations_total", "action" => action).increment(1);
+}
+
+/// Count a Clerk JWKS fetch ("ok" or "error")
+pub fn record_jwks_refresh(outcome: &'static str) {
+    counter!("jwks_refresh_total", "outcome" => outcome).increment(1);
+}
+
+/// Count a request served with stale JWKS keys, and how old they were
+pub fn record_jwks_stale_served(age_seconds: f64) {
+    counter!("jwks_stale_served_total").increment(1);
+    gauge!("jwks_stale_age_seconds").set(age_seconds);
 }
 
 /// Handler for the /metrics endpoint
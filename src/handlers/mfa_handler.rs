@@ -0,0 +1,260 @@
+use axum::{extract::State, http::HeaderMap};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+use crate::{
+    auth::{generate_totp_secret, totp_provisioning_uri, verify_totp},
+    db::encrypted::{encryption_enabled, EncryptedString},
+    extractors::{AuthenticatedUser, Json},
+    AppError, AppResult, AppState,
+};
+
+/// Header carrying the current TOTP code on high-risk endpoints
+pub const MFA_HEADER: &str = "x-mfa-code";
+
+/// Header carrying a step-up token from POST /api/auth/passkeys/verify, accepted instead of a TOTP code
+pub const PASSKEY_HEADER: &str = "x-mfa-passkey";
+
+/// Issuer shown in authenticator apps
+const TOTP_ISSUER: &str = "EdRota";
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MfaStatus {
+    /// A secret has been generated but not yet confirmed
+    pub pending: bool,
+    pub enabled: bool,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct MfaEnrollment {
+    /// Base32 secret for manual entry
+    pub secret: String,
+    /// otpauth:// URI for QR codes
+    pub provisioning_uri: String,
+}
+
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct ConfirmMfaInput {
+    pub code: String,
+}
+
+/// Verify a TOTP code for the user and consume its time step so it cannot be replayed.
+/// Returns false when the code is wrong or has already been used.
+async fn consume_code(db: &sqlx::PgPool, profile_id: i32, secret: &str, code: &str) -> AppResult<bool> {
+    let Some(step) = verify_totp(secret, code, Utc::now().timestamp()) else {
+        return Ok(false);
+    };
+
+    let consumed = sqlx::query(
+        r#"
+        UPDATE "SuperAdminMfa"
+        SET last_used_step = $2
+        WHERE user_profile_id = $1 AND (last_used_step IS NULL OR last_used_step < $2)
+        "#,
+    )
+    .bind(profile_id)
+    .bind(step)
+    .execute(db)
+    .await?
+    .rows_affected();
+
+    Ok(consumed == 1)
+}
+
+/// Guard for high-risk super admin endpoints: once the caller has enabled TOTP or registered a passkey,
+/// a fresh code must be sent in X-MFA-Code or a passkey step-up token in X-MFA-Passkey. Callers with
+/// neither pass through.
+pub async fn ensure_fresh_mfa(db: &sqlx::PgPool, auth: &AuthenticatedUser, headers: &HeaderMap) -> AppResult<()> {
+    if let Some(token) = headers.get(PASSKEY_HEADER).and_then(|v| v.to_str().ok()) {
+        if !crate::handlers::passkeys_handler::consume_step_up(db, auth.profile_id, token).await? {
+            tracing::warn!(profile_id = auth.profile_id, "🔐 Rejected passkey step-up token on high-risk endpoint");
+            return Err(AppError::MfaInvalid);
+        }
+        return Ok(());
+    }
+
+    let secret: Option<EncryptedString> = sqlx::query_scalar(
+        r#"SELECT secret FROM "SuperAdminMfa" WHERE user_profile_id = $1 AND enabled_at IS NOT NULL"#,
+    )
+    .bind(auth.profile_id)
+    .fetch_optional(db)
+    .await?;
+
+    let Some(secret) = secret else {
+        let has_passkey: bool =
+            sqlx::query_scalar(r#"SELECT EXISTS (SELECT 1 FROM "PasskeyCredentials" WHERE user_profile_id = $1)"#)
+                .bind(auth.profile_id)
+                .fetch_one(db)
+                .await?;
+        return if has_passkey { Err(AppError::MfaRequired) } else { Ok(()) };
+    };
+
+    let code = headers
+        .get(MFA_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .ok_or(AppError::MfaRequired)?;
+
+    if !consume_code(db, auth.profile_id, &secret.0, code).await? {
+        tracing::warn!(profile_id = auth.profile_id, "🔐 Rejected TOTP code on high-risk endpoint");
+        return Err(AppError::MfaInvalid);
+    }
+
+    Ok(())
+}
+
+pub(crate) fn require_super_admin(auth: &AuthenticatedUser) -> AppResult<()> {
+    if !auth.is_super_admin {
+        return Err(AppError::Forbidden("Super admin permission required".to_string()));
+    }
+    Ok(())
+}
+
+/// GET /api/auth/mfa - Caller's TOTP enrolment status
+#[utoipa::path(
+    get,
+    path = "/api/auth/mfa",
+    responses(
+        (status = 200, description = "TOTP enrolment status", body = MfaStatus)
+    ),
+    tag = "auth",
+    security(("cookie_auth" = []))
+)]
+pub async fn get_mfa_status(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+) -> AppResult<Json<MfaStatus>> {
+    let enabled: Option<bool> =
+        sqlx::query_scalar(r#"SELECT enabled_at IS NOT NULL FROM "SuperAdminMfa" WHERE user_profile_id = $1"#)
+            .bind(auth.profile_id)
+            .fetch_optional(&state.db)
+            .await?;
+
+    Ok(Json(MfaStatus {
+        pending: enabled == Some(false),
+        enabled: enabled == Some(true),
+    }))
+}
+
+/// POST /api/auth/mfa/enroll - Generate a TOTP secret (super admins); confirm it to enable
+#[utoipa::path(
+    post,
+    path = "/api/auth/mfa/enroll",
+    responses(
+        (status = 200, description = "New secret, not active until confirmed", body = MfaEnrollment),
+        (status = 403, description = "Super admin permission required"),
+        (status = 409, description = "TOTP is already enabled; disable it first"),
+        (status = 500, description = "PII_ENCRYPTION_KEY is not configured")
+    ),
+    tag = "auth",
+    security(("cookie_auth" = []))
+)]
+pub async fn enroll_mfa(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+) -> AppResult<Json<MfaEnrollment>> {
+    require_super_admin(&auth)?;
+
+    // Never store the shared secret in plaintext
+    if !encryption_enabled() {
+        return Err(AppError::Internal("TOTP enrolment requires PII_ENCRYPTION_KEY".to_string()));
+    }
+
+    let secret = generate_totp_secret();
+
+    // Replaces any unconfirmed secret, but never an enabled one
+    let stored = sqlx::query(
+        r#"
+        INSERT INTO "SuperAdminMfa" (user_profile_id, secret)
+        VALUES ($1, $2)
+        ON CONFLICT (user_profile_id) DO UPDATE
+            SET secret = EXCLUDED.secret, last_used_step = NULL, created_at = NOW()
+            WHERE "SuperAdminMfa".enabled_at IS NULL
+        "#,
+    )
+    .bind(auth.profile_id)
+    .bind(EncryptedString(secret.clone()))
+    .execute(&state.db)
+    .await?
+    .rows_affected();
+
+    if stored == 0 {
+        return Err(AppError::Conflict("TOTP is already enabled; disable it first".to_string()));
+    }
+
+    Ok(Json(MfaEnrollment {
+        provisioning_uri: totp_provisioning_uri(&secret, &auth.email, TOTP_ISSUER),
+        secret,
+    }))
+}
+
+/// POST /api/auth/mfa/confirm - Enable TOTP by proving the authenticator app works
+#[utoipa::path(
+    post,
+    path = "/api/auth/mfa/confirm",
+    request_body = ConfirmMfaInput,
+    responses(
+        (status = 200, description = "TOTP enabled", body = MfaStatus),
+        (status = 403, description = "Invalid code (MFA_INVALID)"),
+        (status = 404, description = "No pending enrolment")
+    ),
+    tag = "auth",
+    security(("cookie_auth" = []))
+)]
+pub async fn confirm_mfa(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+    Json(input): Json<ConfirmMfaInput>,
+) -> AppResult<Json<MfaStatus>> {
+    require_super_admin(&auth)?;
+
+    let secret: EncryptedString = sqlx::query_scalar(
+        r#"SELECT secret FROM "SuperAdminMfa" WHERE user_profile_id = $1 AND enabled_at IS NULL"#,
+    )
+    .bind(auth.profile_id)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound("No pending TOTP enrolment".to_string()))?;
+
+    if !consume_code(&state.db, auth.profile_id, &secret.0, &input.code).await? {
+        return Err(AppError::MfaInvalid);
+    }
+
+    sqlx::query(r#"UPDATE "SuperAdminMfa" SET enabled_at = NOW() WHERE user_profile_id = $1"#)
+        .bind(auth.profile_id)
+        .execute(&state.db)
+        .await?;
+
+    tracing::info!(profile_id = auth.profile_id, "🔐 TOTP enabled for super admin");
+
+    Ok(Json(MfaStatus { pending: false, enabled: true }))
+}
+
+/// DELETE /api/auth/mfa - Disable TOTP (requires a current X-MFA-Code)
+#[utoipa::path(
+    delete,
+    path = "/api/auth/mfa",
+    responses(
+        (status = 200, description = "TOTP disabled", body = MfaStatus),
+        (status = 403, description = "Missing or invalid X-MFA-Code (MFA_REQUIRED / MFA_INVALID)")
+    ),
+    tag = "auth",
+    security(("cookie_auth" = []))
+)]
+pub async fn disable_mfa(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+    headers: HeaderMap,
+) -> AppResult<Json<MfaStatus>> {
+    ensure_fresh_mfa(&state.db, &auth, &headers).await?;
+
+    sqlx::query(r#"DELETE FROM "SuperAdminMfa" WHERE user_profile_id = $1"#)
+        .bind(auth.profile_id)
+        .execute(&state.db)
+        .await?;
+
+    tracing::warn!(profile_id = auth.profile_id, "🔓 TOTP disabled");
+
+    Ok(Json(MfaStatus { pending: false, enabled: false }))
+}
@@ -1,20 +1,52 @@
+pub mod absences_handler;
+pub mod announcements_handler;
+pub mod assignment_drift_handler;
+pub mod attachments_handler;
+pub mod attendance_handler;
 pub mod audit_handler;
 pub mod auth_handler;
 pub mod comments_handler;
+pub mod cover_board_handler;
+pub mod custom_fields_handler;
+pub mod dashboard_handler;
+pub mod data_export_handler;
 pub mod debug;
+pub mod delegations_handler;
 pub mod diary_handler;
+pub mod directory_handler;
+pub mod features_handler;
 pub mod health;
 pub mod job_plans_handler;
+pub mod locum_availability_handler;
 pub mod marketplace_handler;
+pub mod marketplace_poll_handler;
+pub mod marketplace_reasons_handler;
+pub mod marketplace_sla_handler;
 pub mod metrics;
+pub mod mfa_handler;
+pub mod notifications_handler;
+pub mod passkeys_handler;
+pub mod patterns_handler;
+pub mod pay_rules_handler;
+pub mod payroll_locks_handler;
 pub mod references_handler;
 pub mod roles_handler;
+pub mod saved_views_handler;
+pub mod search_handler;
+pub mod rota_approval_handler;
+pub mod rota_handler;
+pub mod shift_stats_handler;
 pub mod shifts_handler;
 pub mod templates_handler;
+pub mod unlinked_users_handler;
+pub mod user_export_handler;
+pub mod user_import_handler;
 pub mod user_roles_handler;
 pub mod users_handler;
+pub mod version;
 pub mod workplaces_handler;
 
-pub use debug::debug_handler;
+pub use debug::{debug_handler, permission_matrix_handler, schema_handler};
 pub use health::health_check;
 pub use metrics::{metrics_handler, setup_metrics_recorder, MetricsState};
+pub use version::version_handler;
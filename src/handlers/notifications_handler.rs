@@ -0,0 +1,279 @@
+use axum::extract::{Path, Query, State};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+use crate::{
+    extractors::{AuthenticatedUser, Json},
+    models::{Notification, NotificationPreference, SuccessResponse, UpdateNotificationPreferencesInput},
+    AppError, AppResult, AppState,
+};
+
+/// Every kind `notify` is called with, so the preferences endpoints can list and validate them
+pub const NOTIFICATION_KINDS: &[&str] = &[
+    "MARKETPLACE_REQUEST_CREATED_FOR_YOU",
+    "MARKETPLACE_GIVE_AWAY_OFFER",
+    "MARKETPLACE_APPROVAL_NEEDED",
+    "MARKETPLACE_CANDIDATE_WITHDREW",
+    "MARKETPLACE_REQUEST_FORCE_CANCELLED",
+    "COVER_FILLED",
+    "DATA_EXPORT_READY",
+    "LOCUM_ASSIGNED",
+    "ROTA_APPROVED",
+    "ROTA_REJECTED",
+    "ABSENCE_REPORTED",
+    "SHIFT_CHANGED",
+    "SHIFT_ASSIGNED",
+    "SHIFT_UNASSIGNED",
+    "SHIFT_REMOVED",
+];
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct GetNotificationsQuery {
+    #[serde(rename = "unreadOnly")]
+    pub unread_only: Option<bool>,
+}
+
+/// Record a notification for a user, unless they switched its kind off (needs
+/// `sql/048_notification_preferences.sql`). Takes any executor so it can join the caller's transaction.
+pub async fn notify<'e, E>(
+    executor: E,
+    user_profile_id: i32,
+    kind: &str,
+    message: &str,
+    payload: serde_json::Value,
+) -> Result<(), sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    let result = sqlx::query(
+        r#"
+        INSERT INTO "Notifications" (user_profile_id, kind, message, payload)
+        SELECT $1, $2, $3, $4
+        WHERE NOT EXISTS (
+            SELECT 1 FROM "NotificationPreferences" WHERE user_profile_id = $1 AND kind = $2 AND NOT enabled
+        )
+        "#,
+    )
+    .bind(user_profile_id)
+    .bind(kind)
+    .bind(message)
+    .bind(payload)
+    .execute(executor)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        tracing::debug!(user_profile_id, kind, "🔕 Notification skipped by preference");
+    } else {
+        tracing::debug!(user_profile_id, kind, "🔔 Notification recorded");
+    }
+    Ok(())
+}
+
+/// Every known kind with the caller's setting; kinds without a stored row are on
+fn merge_preferences(stored: &[NotificationPreference]) -> Vec<NotificationPreference> {
+    NOTIFICATION_KINDS
+        .iter()
+        .map(|kind| NotificationPreference {
+            kind: kind.to_string(),
+            enabled: stored.iter().find(|p| p.kind == *kind).is_none_or(|p| p.enabled),
+        })
+        .collect()
+}
+
+async fn load_preferences(state: &AppState, user_profile_id: i32) -> AppResult<Vec<NotificationPreference>> {
+    let stored = sqlx::query_as::<_, NotificationPreference>(
+        r#"SELECT kind, enabled FROM "NotificationPreferences" WHERE user_profile_id = $1"#,
+    )
+    .bind(user_profile_id)
+    .fetch_all(&state.db)
+    .await?;
+    Ok(merge_preferences(&stored))
+}
+
+/// GET /api/notifications/preferences - Which notification kinds the caller receives
+#[utoipa::path(
+    get,
+    path = "/api/notifications/preferences",
+    responses(
+        (status = 200, description = "Every notification kind with whether the caller receives it", body = Vec<NotificationPreference>)
+    ),
+    tag = "notifications",
+    security(("cookie_auth" = []))
+)]
+pub async fn get_notification_preferences(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+) -> AppResult<Json<Vec<NotificationPreference>>> {
+    Ok(Json(load_preferences(&state, auth.profile_id).await?))
+}
+
+/// PUT /api/notifications/preferences - Switch notification kinds on or off for the caller
+#[utoipa::path(
+    put,
+    path = "/api/notifications/preferences",
+    request_body = UpdateNotificationPreferencesInput,
+    responses(
+        (status = 200, description = "Every notification kind with the caller's updated settings", body = Vec<NotificationPreference>),
+        (status = 400, description = "Unknown notification kind")
+    ),
+    tag = "notifications",
+    security(("cookie_auth" = []))
+)]
+pub async fn update_notification_preferences(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+    Json(input): Json<UpdateNotificationPreferencesInput>,
+) -> AppResult<Json<Vec<NotificationPreference>>> {
+    if let Some(unknown) = input.preferences.iter().find(|p| !NOTIFICATION_KINDS.contains(&p.kind.as_str())) {
+        return Err(AppError::BadRequest(format!("Unknown notification kind {}", unknown.kind)));
+    }
+
+    // A kind named twice takes its last setting
+    let changes: BTreeMap<&str, bool> = input.preferences.iter().map(|p| (p.kind.as_str(), p.enabled)).collect();
+    let kinds: Vec<&str> = changes.keys().copied().collect();
+    let enabled: Vec<bool> = changes.values().copied().collect();
+    sqlx::query(
+        r#"
+        INSERT INTO "NotificationPreferences" (user_profile_id, kind, enabled)
+        SELECT $1, kind, enabled FROM UNNEST($2::text[], $3::bool[]) AS p (kind, enabled)
+        ON CONFLICT (user_profile_id, kind) DO UPDATE SET enabled = EXCLUDED.enabled, updated_at = NOW()
+        "#,
+    )
+    .bind(auth.profile_id)
+    .bind(&kinds)
+    .bind(&enabled)
+    .execute(&state.db)
+    .await?;
+
+    tracing::info!(user_profile_id = auth.profile_id, changed = kinds.len(), "🔕 Notification preferences updated");
+    Ok(Json(load_preferences(&state, auth.profile_id).await?))
+}
+
+/// GET /api/notifications?unreadOnly=
+#[utoipa::path(
+    get,
+    path = "/api/notifications",
+    params(GetNotificationsQuery),
+    responses(
+        (status = 200, description = "Current user's notifications, newest first (last 100)", body = Vec<Notification>)
+    ),
+    tag = "notifications",
+    security(("cookie_auth" = []))
+)]
+pub async fn get_my_notifications(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+    Query(query): Query<GetNotificationsQuery>,
+) -> AppResult<Json<Vec<Notification>>> {
+    let notifications = sqlx::query_as::<_, Notification>(
+        r#"
+        SELECT id, user_profile_id, kind, message, payload, created_at, read_at
+        FROM "Notifications"
+        WHERE user_profile_id = $1
+          AND ($2 = FALSE OR read_at IS NULL)
+        ORDER BY created_at DESC
+        LIMIT 100
+        "#,
+    )
+    .bind(auth.profile_id)
+    .bind(query.unread_only.unwrap_or(false))
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(notifications))
+}
+
+/// POST /api/notifications/{id}/read - Mark one of your notifications as read
+#[utoipa::path(
+    post,
+    path = "/api/notifications/{id}/read",
+    params(
+        ("id" = i64, Path, description = "Notification ID")
+    ),
+    responses(
+        (status = 200, description = "Notification marked as read", body = SuccessResponse),
+        (status = 404, description = "Notification not found")
+    ),
+    tag = "notifications",
+    security(("cookie_auth" = []))
+)]
+pub async fn mark_notification_read(
+    State(state): State<Arc<AppState>>,
+    Path(notification_id): Path<i64>,
+    auth: AuthenticatedUser,
+) -> AppResult<Json<SuccessResponse>> {
+    let result = sqlx::query(
+        r#"
+        UPDATE "Notifications"
+        SET read_at = COALESCE(read_at, NOW())
+        WHERE id = $1 AND user_profile_id = $2
+        "#,
+    )
+    .bind(notification_id)
+    .bind(auth.profile_id)
+    .execute(&state.db)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound(format!("Notification {} not found", notification_id)));
+    }
+
+    Ok(Json(SuccessResponse { success: true }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_preferences() {
+        let off = |kind: &str| NotificationPreference { kind: kind.to_string(), enabled: false };
+        let merged = merge_preferences(&[off("SHIFT_CHANGED"), off("NO_LONGER_SENT")]);
+
+        assert_eq!(merged.len(), NOTIFICATION_KINDS.len());
+        assert!(merged.iter().all(|p| p.enabled == (p.kind != "SHIFT_CHANGED")));
+    }
+
+    /// Every source file under src, except this one
+    fn sources(dir: &std::path::Path, out: &mut Vec<String>) {
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                sources(&path, out);
+            } else if path.extension().is_some_and(|e| e == "rs") && !path.ends_with("notifications_handler.rs") {
+                out.push(std::fs::read_to_string(path).unwrap());
+            }
+        }
+    }
+
+    /// The list must name exactly what is sent: every literal kind handed to `notify` is listed, and every listed
+    /// kind still appears in the code that sends it (kinds passed through a variable, like shift notices, are set
+    /// as literals nearby)
+    #[test]
+    fn test_notification_kinds_match_what_is_sent() {
+        let mut files = Vec::new();
+        sources(std::path::Path::new(concat!(env!("CARGO_MANIFEST_DIR"), "/src")), &mut files);
+        let is_kind = |s: &str| s.len() > 3 && s.chars().all(|c| c.is_ascii_uppercase() || c == '_');
+
+        let mut calls = 0;
+        for source in &files {
+            for (at, _) in source.match_indices("notifications_handler::notify(") {
+                calls += 1;
+                let call = source[at..].split(".await").next().unwrap();
+                let kind = call.lines().nth(3).unwrap().trim().trim_end_matches(',');
+                if let Some(literal) = kind.strip_prefix('"').and_then(|k| k.strip_suffix('"')) {
+                    assert!(NOTIFICATION_KINDS.contains(&literal), "{} is sent but missing from NOTIFICATION_KINDS", literal);
+                }
+            }
+        }
+        assert!(calls > 0);
+
+        for kind in NOTIFICATION_KINDS {
+            assert!(is_kind(kind));
+            let quoted = format!("\"{}\"", kind);
+            assert!(files.iter().any(|source| source.contains(&quoted)), "{} is listed but never sent", kind);
+        }
+    }
+}
@@ -0,0 +1,509 @@
+use axum::{
+    extract::{Path, State},
+    http::HeaderMap,
+};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+use crate::{
+    auth::webauthn::{self, SUPPORTED_ALGORITHMS},
+    config::WebAuthnConfig,
+    extractors::{AuthenticatedUser, Json},
+    handlers::mfa_handler::{ensure_fresh_mfa, require_super_admin},
+    AppError, AppResult, AppState,
+};
+
+/// Name shown by authenticators next to the account
+const RP_NAME: &str = "EdRota";
+
+/// How long the browser has to complete a ceremony
+const CHALLENGE_TTL_SECS: i64 = 300;
+
+/// How long a step-up token from a verified assertion may wait to be used
+const STEP_UP_TTL_SECS: i64 = 300;
+
+const MAX_PASSKEY_NAME_LEN: usize = 100;
+
+#[derive(Debug, Serialize, sqlx::FromRow, ToSchema)]
+pub struct Passkey {
+    pub id: i32,
+    pub name: String,
+    /// base64url credential ID
+    pub credential_id: String,
+    pub created_at: NaiveDateTime,
+    pub last_used_at: Option<NaiveDateTime>,
+}
+
+/// Everything `navigator.credentials.create()` needs besides the caller's display name
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PasskeyRegistrationOptions {
+    /// base64url, valid for 5 minutes
+    pub challenge: String,
+    pub rp_id: String,
+    pub rp_name: String,
+    /// base64url user handle
+    pub user_id: String,
+    pub user_name: String,
+    /// COSE algorithms in order of preference
+    pub algorithms: Vec<i32>,
+    /// Credentials already registered, to pass as excludeCredentials
+    pub exclude_credentials: Vec<String>,
+    pub timeout_ms: i64,
+}
+
+/// Everything `navigator.credentials.get()` needs
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PasskeyVerificationOptions {
+    /// base64url, valid for 5 minutes
+    pub challenge: String,
+    pub rp_id: String,
+    /// The caller's credentials, to pass as allowCredentials
+    pub allow_credentials: Vec<String>,
+    pub timeout_ms: i64,
+}
+
+/// The browser's registration response. Uses `response.getPublicKey()`, `getPublicKeyAlgorithm()` and
+/// `getAuthenticatorData()` rather than the CBOR attestation object; all binary fields are base64url.
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct RegisterPasskeyInput {
+    /// Label to tell passkeys apart, e.g. "YubiKey" or "Work laptop"
+    pub name: String,
+    pub credential_id: String,
+    pub client_data_json: String,
+    pub authenticator_data: String,
+    /// DER SubjectPublicKeyInfo
+    pub public_key: String,
+    pub public_key_algorithm: i32,
+}
+
+/// The browser's assertion response; all binary fields are base64url
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct VerifyPasskeyInput {
+    pub credential_id: String,
+    pub client_data_json: String,
+    pub authenticator_data: String,
+    pub signature: String,
+}
+
+#[derive(Debug, Serialize, ToSchema)]
+pub struct PasskeyStepUp {
+    /// Send in X-MFA-Passkey on one high-risk request
+    pub token: String,
+    pub expires_at: NaiveDateTime,
+}
+
+#[derive(sqlx::FromRow)]
+struct StoredCredential {
+    id: i32,
+    public_key: Vec<u8>,
+    algorithm: i32,
+    sign_count: i64,
+}
+
+fn webauthn_config(state: &AppState) -> AppResult<&WebAuthnConfig> {
+    state
+        .config
+        .webauthn
+        .as_ref()
+        .ok_or_else(|| AppError::Internal("Passkeys are not configured (WEBAUTHN_ORIGIN or APP_BASE_URL)".to_string()))
+}
+
+fn decode(field: &str, value: &str) -> AppResult<Vec<u8>> {
+    webauthn::b64url_decode(field, value).map_err(AppError::Validation)
+}
+
+fn hash_step_up(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// A counter that does not move past the stored one suggests a cloned authenticator. Authenticators that
+/// never count (most synced passkeys) report 0 every time and are accepted.
+fn sign_count_acceptable(stored: i64, reported: u32) -> bool {
+    (stored == 0 && reported == 0) || i64::from(reported) > stored
+}
+
+/// Issue a single-use challenge for the caller
+async fn create_challenge(db: &sqlx::PgPool, profile_id: i32, purpose: &str) -> AppResult<String> {
+    let challenge = webauthn::b64url_encode(&rand::random::<[u8; 32]>());
+
+    sqlx::query(r#"DELETE FROM "PasskeyChallenges" WHERE expires_at <= NOW()"#)
+        .execute(db)
+        .await?;
+    sqlx::query(
+        r#"
+        INSERT INTO "PasskeyChallenges" (challenge, user_profile_id, purpose, expires_at)
+        VALUES ($1, $2, $3, NOW() + make_interval(secs => $4))
+        "#,
+    )
+    .bind(&challenge)
+    .bind(profile_id)
+    .bind(purpose)
+    .bind(CHALLENGE_TTL_SECS as f64)
+    .execute(db)
+    .await?;
+
+    Ok(challenge)
+}
+
+/// Consume a challenge issued to the caller for this purpose; false when unknown, expired or already used
+async fn consume_challenge(db: &sqlx::PgPool, profile_id: i32, purpose: &str, challenge: &str) -> AppResult<bool> {
+    let consumed = sqlx::query(
+        r#"
+        DELETE FROM "PasskeyChallenges"
+        WHERE challenge = $1 AND user_profile_id = $2 AND purpose = $3 AND expires_at > NOW()
+        "#,
+    )
+    .bind(challenge)
+    .bind(profile_id)
+    .bind(purpose)
+    .execute(db)
+    .await?
+    .rows_affected();
+
+    Ok(consumed == 1)
+}
+
+/// Consume a step-up token from POST /api/auth/passkeys/verify (see mfa_handler::ensure_fresh_mfa).
+/// Returns false when the token is unknown, expired, already used or someone else's.
+pub async fn consume_step_up(db: &sqlx::PgPool, profile_id: i32, token: &str) -> AppResult<bool> {
+    let consumed = sqlx::query(
+        r#"
+        DELETE FROM "PasskeyStepUps"
+        WHERE token_hash = $1 AND user_profile_id = $2 AND expires_at > NOW()
+        "#,
+    )
+    .bind(hash_step_up(token))
+    .bind(profile_id)
+    .execute(db)
+    .await?
+    .rows_affected();
+
+    Ok(consumed == 1)
+}
+
+async fn credential_ids(db: &sqlx::PgPool, profile_id: i32) -> AppResult<Vec<String>> {
+    Ok(
+        sqlx::query_scalar(r#"SELECT credential_id FROM "PasskeyCredentials" WHERE user_profile_id = $1 ORDER BY id"#)
+            .bind(profile_id)
+            .fetch_all(db)
+            .await?,
+    )
+}
+
+/// GET /api/auth/passkeys - Caller's registered passkeys
+#[utoipa::path(
+    get,
+    path = "/api/auth/passkeys",
+    responses(
+        (status = 200, description = "Registered passkeys, oldest first", body = Vec<Passkey>)
+    ),
+    tag = "auth",
+    security(("cookie_auth" = []))
+)]
+pub async fn list_passkeys(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+) -> AppResult<Json<Vec<Passkey>>> {
+    let passkeys = sqlx::query_as::<_, Passkey>(
+        r#"
+        SELECT id, name, credential_id, created_at, last_used_at
+        FROM "PasskeyCredentials"
+        WHERE user_profile_id = $1
+        ORDER BY id
+        "#,
+    )
+    .bind(auth.profile_id)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(passkeys))
+}
+
+/// POST /api/auth/passkeys/register/options - Start registering a passkey (super admins)
+#[utoipa::path(
+    post,
+    path = "/api/auth/passkeys/register/options",
+    responses(
+        (status = 200, description = "Options for navigator.credentials.create()", body = PasskeyRegistrationOptions),
+        (status = 403, description = "Super admin permission required"),
+        (status = 500, description = "Passkeys are not configured")
+    ),
+    tag = "auth",
+    security(("cookie_auth" = []))
+)]
+pub async fn passkey_registration_options(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+) -> AppResult<Json<PasskeyRegistrationOptions>> {
+    require_super_admin(&auth)?;
+    let config = webauthn_config(&state)?;
+
+    Ok(Json(PasskeyRegistrationOptions {
+        challenge: create_challenge(&state.db, auth.profile_id, "REGISTER").await?,
+        rp_id: config.rp_id.clone(),
+        rp_name: RP_NAME.to_string(),
+        user_id: webauthn::b64url_encode(auth.profile_id.to_string().as_bytes()),
+        user_name: auth.email.clone(),
+        algorithms: SUPPORTED_ALGORITHMS.to_vec(),
+        exclude_credentials: credential_ids(&state.db, auth.profile_id).await?,
+        timeout_ms: CHALLENGE_TTL_SECS * 1000,
+    }))
+}
+
+/// POST /api/auth/passkeys/register - Save a new passkey (requires fresh MFA once TOTP or a passkey exists)
+#[utoipa::path(
+    post,
+    path = "/api/auth/passkeys/register",
+    request_body = RegisterPasskeyInput,
+    responses(
+        (status = 200, description = "Passkey registered", body = Passkey),
+        (status = 400, description = "Challenge unknown, expired or already used"),
+        (status = 403, description = "Super admin permission required, or missing/invalid MFA (MFA_REQUIRED / MFA_INVALID)"),
+        (status = 409, description = "Credential already registered"),
+        (status = 422, description = "Response does not verify")
+    ),
+    tag = "auth",
+    security(("cookie_auth" = []))
+)]
+pub async fn register_passkey(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+    headers: HeaderMap,
+    Json(input): Json<RegisterPasskeyInput>,
+) -> AppResult<Json<Passkey>> {
+    require_super_admin(&auth)?;
+    let config = webauthn_config(&state)?;
+    ensure_fresh_mfa(&state.db, &auth, &headers).await?;
+
+    let name = input.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_PASSKEY_NAME_LEN {
+        return Err(AppError::Validation(format!("name must be 1-{} characters", MAX_PASSKEY_NAME_LEN)));
+    }
+
+    let credential_id = decode("credential_id", &input.credential_id)?;
+    let client_data_json = decode("client_data_json", &input.client_data_json)?;
+    let authenticator_data = decode("authenticator_data", &input.authenticator_data)?;
+    let public_key = decode("public_key", &input.public_key)?;
+
+    let challenge = webauthn::verify_client_data(&client_data_json, "webauthn.create", &config.origin)
+        .map_err(AppError::Validation)?;
+    if !consume_challenge(&state.db, auth.profile_id, "REGISTER", &challenge).await? {
+        return Err(AppError::BadRequest("Registration challenge is unknown, expired or already used".to_string()));
+    }
+
+    let parsed = webauthn::parse_authenticator_data(&authenticator_data).map_err(AppError::Validation)?;
+    webauthn::check_authenticator_data(&parsed, &config.rp_id).map_err(AppError::Validation)?;
+    if parsed.credential_id.as_deref() != Some(&credential_id[..]) {
+        return Err(AppError::Validation("credential_id does not match authenticator_data".to_string()));
+    }
+    webauthn::check_public_key(input.public_key_algorithm, &public_key).map_err(AppError::Validation)?;
+
+    let passkey = sqlx::query_as::<_, Passkey>(
+        r#"
+        INSERT INTO "PasskeyCredentials" (user_profile_id, credential_id, public_key, algorithm, sign_count, name)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING id, name, credential_id, created_at, last_used_at
+        "#,
+    )
+    .bind(auth.profile_id)
+    .bind(webauthn::b64url_encode(&credential_id))
+    .bind(&public_key)
+    .bind(input.public_key_algorithm)
+    .bind(i64::from(parsed.sign_count))
+    .bind(name)
+    .fetch_one(&state.db)
+    .await?;
+
+    tracing::info!(profile_id = auth.profile_id, passkey_id = passkey.id, "🔑 Passkey registered for super admin");
+
+    Ok(Json(passkey))
+}
+
+/// POST /api/auth/passkeys/verify/options - Start a passkey step-up
+#[utoipa::path(
+    post,
+    path = "/api/auth/passkeys/verify/options",
+    responses(
+        (status = 200, description = "Options for navigator.credentials.get()", body = PasskeyVerificationOptions),
+        (status = 403, description = "Super admin permission required"),
+        (status = 404, description = "No passkeys registered"),
+        (status = 500, description = "Passkeys are not configured")
+    ),
+    tag = "auth",
+    security(("cookie_auth" = []))
+)]
+pub async fn passkey_verification_options(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+) -> AppResult<Json<PasskeyVerificationOptions>> {
+    require_super_admin(&auth)?;
+    let config = webauthn_config(&state)?;
+
+    let allow_credentials = credential_ids(&state.db, auth.profile_id).await?;
+    if allow_credentials.is_empty() {
+        return Err(AppError::NotFound("No passkeys registered".to_string()));
+    }
+
+    Ok(Json(PasskeyVerificationOptions {
+        challenge: create_challenge(&state.db, auth.profile_id, "VERIFY").await?,
+        rp_id: config.rp_id.clone(),
+        allow_credentials,
+        timeout_ms: CHALLENGE_TTL_SECS * 1000,
+    }))
+}
+
+/// POST /api/auth/passkeys/verify - Check a passkey assertion and issue a one-time step-up token
+#[utoipa::path(
+    post,
+    path = "/api/auth/passkeys/verify",
+    request_body = VerifyPasskeyInput,
+    responses(
+        (status = 200, description = "Step-up token for X-MFA-Passkey", body = PasskeyStepUp),
+        (status = 400, description = "Challenge unknown, expired or already used"),
+        (status = 403, description = "Super admin permission required, or the assertion does not verify (MFA_INVALID)"),
+        (status = 404, description = "Passkey not registered to the caller"),
+        (status = 422, description = "Malformed response")
+    ),
+    tag = "auth",
+    security(("cookie_auth" = []))
+)]
+pub async fn verify_passkey(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+    Json(input): Json<VerifyPasskeyInput>,
+) -> AppResult<Json<PasskeyStepUp>> {
+    require_super_admin(&auth)?;
+    let config = webauthn_config(&state)?;
+
+    let credential_id = webauthn::b64url_encode(&decode("credential_id", &input.credential_id)?);
+    let client_data_json = decode("client_data_json", &input.client_data_json)?;
+    let authenticator_data = decode("authenticator_data", &input.authenticator_data)?;
+    let signature = decode("signature", &input.signature)?;
+
+    let challenge = webauthn::verify_client_data(&client_data_json, "webauthn.get", &config.origin)
+        .map_err(AppError::Validation)?;
+    if !consume_challenge(&state.db, auth.profile_id, "VERIFY", &challenge).await? {
+        return Err(AppError::BadRequest("Verification challenge is unknown, expired or already used".to_string()));
+    }
+
+    let credential = sqlx::query_as::<_, StoredCredential>(
+        r#"
+        SELECT id, public_key, algorithm, sign_count
+        FROM "PasskeyCredentials"
+        WHERE credential_id = $1 AND user_profile_id = $2
+        "#,
+    )
+    .bind(&credential_id)
+    .bind(auth.profile_id)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound("Passkey not registered".to_string()))?;
+
+    let parsed = webauthn::parse_authenticator_data(&authenticator_data).map_err(AppError::Validation)?;
+    let verified = webauthn::check_authenticator_data(&parsed, &config.rp_id).and_then(|_| {
+        webauthn::verify_assertion_signature(
+            credential.algorithm,
+            &credential.public_key,
+            &authenticator_data,
+            &client_data_json,
+            &signature,
+        )
+    });
+    if let Err(e) = verified {
+        tracing::warn!(profile_id = auth.profile_id, passkey_id = credential.id, error = %e, "🔐 Rejected passkey assertion");
+        return Err(AppError::MfaInvalid);
+    }
+    if !sign_count_acceptable(credential.sign_count, parsed.sign_count) {
+        tracing::warn!(
+            profile_id = auth.profile_id,
+            passkey_id = credential.id,
+            stored = credential.sign_count,
+            reported = parsed.sign_count,
+            "🔐 Passkey signature counter went backwards, possible cloned authenticator"
+        );
+        return Err(AppError::MfaInvalid);
+    }
+
+    let token = webauthn::b64url_encode(&rand::random::<[u8; 32]>());
+    let mut tx = state.db.begin().await?;
+    sqlx::query(r#"UPDATE "PasskeyCredentials" SET sign_count = $2, last_used_at = NOW() WHERE id = $1"#)
+        .bind(credential.id)
+        .bind(i64::from(parsed.sign_count))
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(r#"DELETE FROM "PasskeyStepUps" WHERE expires_at <= NOW()"#)
+        .execute(&mut *tx)
+        .await?;
+    let expires_at: NaiveDateTime = sqlx::query_scalar(
+        r#"
+        INSERT INTO "PasskeyStepUps" (token_hash, user_profile_id, credential_id, expires_at)
+        VALUES ($1, $2, $3, NOW() + make_interval(secs => $4))
+        RETURNING expires_at
+        "#,
+    )
+    .bind(hash_step_up(&token))
+    .bind(auth.profile_id)
+    .bind(credential.id)
+    .bind(STEP_UP_TTL_SECS as f64)
+    .fetch_one(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    tracing::info!(profile_id = auth.profile_id, passkey_id = credential.id, "🔑 Passkey step-up verified");
+
+    Ok(Json(PasskeyStepUp { token, expires_at }))
+}
+
+/// DELETE /api/auth/passkeys/{id} - Remove a passkey (requires fresh MFA)
+#[utoipa::path(
+    delete,
+    path = "/api/auth/passkeys/{id}",
+    params(("id" = i32, Path, description = "Passkey ID")),
+    responses(
+        (status = 200, description = "Remaining passkeys", body = Vec<Passkey>),
+        (status = 403, description = "Missing or invalid MFA (MFA_REQUIRED / MFA_INVALID)"),
+        (status = 404, description = "Passkey not found")
+    ),
+    tag = "auth",
+    security(("cookie_auth" = []))
+)]
+pub async fn delete_passkey(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+    headers: HeaderMap,
+    Path(id): Path<i32>,
+) -> AppResult<Json<Vec<Passkey>>> {
+    ensure_fresh_mfa(&state.db, &auth, &headers).await?;
+
+    let deleted = sqlx::query(r#"DELETE FROM "PasskeyCredentials" WHERE id = $1 AND user_profile_id = $2"#)
+        .bind(id)
+        .bind(auth.profile_id)
+        .execute(&state.db)
+        .await?
+        .rows_affected();
+
+    if deleted == 0 {
+        return Err(AppError::NotFound("Passkey not found".to_string()));
+    }
+
+    tracing::warn!(profile_id = auth.profile_id, passkey_id = id, "🔓 Passkey removed");
+
+    list_passkeys(State(state), auth).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_count_acceptable() {
+        assert!(sign_count_acceptable(0, 0)); // authenticator never counts
+        assert!(sign_count_acceptable(0, 1));
+        assert!(sign_count_acceptable(5, 6));
+        assert!(!sign_count_acceptable(5, 5));
+        assert!(!sign_count_acceptable(5, 0));
+    }
+}
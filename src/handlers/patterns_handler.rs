@@ -0,0 +1,625 @@
+use axum::{
+    extract::{Path, Query, State},
+};
+use chrono::{Datelike, NaiveDate, NaiveDateTime};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use crate::{
+    extractors::{CanEditRota, CanEditTemplates, Json, RequirePermission},
+    models::{
+        ApplyPatternInput, ApplyPatternResponse, AssignmentSuggestion, CreatePatternInput, DuplicateShift, DuplicateShiftPolicy,
+        PatternEntryInput, PatternMutationResponse, RotaPattern, RotaPatternEntry, UpdatePatternInput,
+    },
+    AppError, AppResult, AppState,
+};
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct GetPatternsQuery {
+    #[serde(rename = "roleId")]
+    pub role_id: Option<i32>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct PatternRow {
+    id: i32,
+    role_id: i32,
+    name: String,
+    created_at: NaiveDateTime,
+}
+
+/// Load a pattern with its entries, or 404
+async fn fetch_pattern(db: &sqlx::PgPool, pattern_id: i32) -> AppResult<RotaPattern> {
+    let row = sqlx::query_as::<_, PatternRow>(r#"SELECT id, role_id, name, created_at FROM "RotaPatterns" WHERE id = $1"#)
+        .bind(pattern_id)
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Pattern {} not found", pattern_id)))?;
+
+    let entries = fetch_entries(db, &[pattern_id]).await?;
+
+    Ok(RotaPattern {
+        id: row.id,
+        role_id: row.role_id,
+        name: row.name,
+        created_at: row.created_at,
+        entries: entries.into_iter().map(|(_, e)| e).collect(),
+    })
+}
+
+/// Entries for the given patterns, paired with their pattern ID
+async fn fetch_entries(db: &sqlx::PgPool, pattern_ids: &[i32]) -> AppResult<Vec<(i32, RotaPatternEntry)>> {
+    #[derive(sqlx::FromRow)]
+    struct EntryRow {
+        pattern_id: i32,
+        #[sqlx(flatten)]
+        entry: RotaPatternEntry,
+    }
+
+    let rows = sqlx::query_as::<_, EntryRow>(
+        r#"
+        SELECT e.pattern_id, e.weekday, e.template_id, t.label AS template_label
+        FROM "RotaPatternEntries" e
+        INNER JOIN "ShiftTemplates" t ON e.template_id = t.id
+        WHERE e.pattern_id = ANY($1)
+        ORDER BY e.weekday, t.start NULLS LAST, t.label
+        "#,
+    )
+    .bind(pattern_ids)
+    .fetch_all(db)
+    .await?;
+
+    Ok(rows.into_iter().map(|r| (r.pattern_id, r.entry)).collect())
+}
+
+/// Check weekdays are 1-7 and every template belongs to the pattern's role
+async fn validate_entries(db: &sqlx::PgPool, role_id: i32, entries: &[PatternEntryInput]) -> AppResult<()> {
+    if let Some(entry) = entries.iter().find(|e| !(1..=7).contains(&e.weekday)) {
+        return Err(AppError::BadRequest(format!(
+            "weekday must be between 1 (Monday) and 7 (Sunday), got {}",
+            entry.weekday
+        )));
+    }
+
+    let template_ids: Vec<i32> = entries.iter().map(|e| e.template_id).collect();
+    let foreign: Vec<i32> = sqlx::query_scalar(
+        r#"
+        SELECT id FROM unnest($1::int4[]) AS requested(id)
+        WHERE NOT EXISTS (SELECT 1 FROM "ShiftTemplates" t WHERE t.id = requested.id AND t.role_id = $2)
+        "#,
+    )
+    .bind(&template_ids)
+    .bind(role_id)
+    .fetch_all(db)
+    .await?;
+
+    if !foreign.is_empty() {
+        return Err(AppError::BadRequest(format!(
+            "Templates {:?} do not exist for role {}",
+            foreign, role_id
+        )));
+    }
+
+    Ok(())
+}
+
+async fn insert_entries(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    pattern_id: i32,
+    entries: &[PatternEntryInput],
+) -> AppResult<()> {
+    let weekdays: Vec<i16> = entries.iter().map(|e| e.weekday).collect();
+    let template_ids: Vec<i32> = entries.iter().map(|e| e.template_id).collect();
+
+    sqlx::query(
+        r#"
+        INSERT INTO "RotaPatternEntries" (pattern_id, weekday, template_id)
+        SELECT $1, weekday, template_id FROM unnest($2::int2[], $3::int4[]) AS e(weekday, template_id)
+        ON CONFLICT (pattern_id, weekday, template_id) DO NOTHING
+        "#,
+    )
+    .bind(pattern_id)
+    .bind(&weekdays)
+    .bind(&template_ids)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Dates in the month on which each pattern entry falls, in date order
+fn pattern_dates(year: i32, month: u32, entries: &[RotaPatternEntry]) -> AppResult<Vec<(NaiveDate, i32)>> {
+    let first = NaiveDate::from_ymd_opt(year, month, 1)
+        .ok_or_else(|| AppError::BadRequest(format!("Invalid year/month: {}-{}", year, month)))?;
+
+    Ok(first
+        .iter_days()
+        .take_while(|d| d.month() == month)
+        .flat_map(|date| {
+            let weekday = date.weekday().number_from_monday() as i16;
+            entries
+                .iter()
+                .filter(move |e| e.weekday == weekday)
+                .map(move |e| (date, e.template_id))
+        })
+        .collect())
+}
+
+/// A day the pattern falls on and the template to copy there
+type Occurrence = (NaiveDate, i32);
+
+/// Existing shifts, assigned or not, matching the template each occurrence would create (same label and times)
+async fn find_pattern_duplicates(
+    conn: &mut sqlx::PgConnection,
+    occurrences: &[Occurrence],
+) -> AppResult<Vec<(Occurrence, DuplicateShift)>> {
+    #[derive(sqlx::FromRow)]
+    struct DuplicateRow {
+        template_id: i32,
+        #[sqlx(flatten)]
+        duplicate: DuplicateShift,
+    }
+
+    let dates: Vec<NaiveDate> = occurrences.iter().map(|(date, _)| *date).collect();
+    let template_ids: Vec<i32> = occurrences.iter().map(|(_, id)| *id).collect();
+
+    let rows = sqlx::query_as::<_, DuplicateRow>(
+        r#"
+        SELECT DISTINCT ON (o.date, o.template_id)
+            o.template_id, o.date, t.label, s.uuid AS existing_uuid
+        FROM unnest($1::date[], $2::int4[]) AS o(date, template_id)
+        INNER JOIN "ShiftTemplates" t ON t.id = o.template_id
+        INNER JOIN "Shifts" s
+            ON s.role_id = t.role_id AND s.date = o.date AND s.label = t.label
+           AND s.start IS NOT DISTINCT FROM t.start AND s."end" IS NOT DISTINCT FROM t."end"
+        ORDER BY o.date, o.template_id, s.created_at
+        "#,
+    )
+    .bind(&dates)
+    .bind(&template_ids)
+    .fetch_all(conn)
+    .await?;
+
+    Ok(rows.into_iter().map(|r| ((r.duplicate.date, r.template_id), r.duplicate)).collect())
+}
+
+/// Occurrences left to create under `policy`, and the duplicates that were skipped
+fn resolve_duplicates(
+    occurrences: Vec<Occurrence>,
+    duplicates: Vec<(Occurrence, DuplicateShift)>,
+    policy: DuplicateShiftPolicy,
+) -> AppResult<(Vec<Occurrence>, Vec<DuplicateShift>)> {
+    match policy {
+        DuplicateShiftPolicy::Allow => Ok((occurrences, Vec::new())),
+        _ if duplicates.is_empty() => Ok((occurrences, Vec::new())),
+        DuplicateShiftPolicy::Reject => {
+            let existing: Vec<DuplicateShift> = duplicates.into_iter().map(|(_, d)| d).collect();
+            Err(AppError::ConflictWith {
+                message: format!("{} of the pattern's shifts already exist; nothing was created", existing.len()),
+                conflict: serde_json::json!(existing),
+            })
+        }
+        DuplicateShiftPolicy::Skip => {
+            let remaining = occurrences
+                .into_iter()
+                .filter(|occurrence| !duplicates.iter().any(|(o, _)| o == occurrence))
+                .collect();
+            Ok((remaining, duplicates.into_iter().map(|(_, d)| d).collect()))
+        }
+    }
+}
+
+/// A series ID for each occurrence in a run of two or more consecutive days on the same template, so a
+/// week of nights comes out as one block; None for a template's one-off days. Indexes match `occurrences`.
+fn pattern_series(occurrences: &[Occurrence]) -> Vec<Option<Uuid>> {
+    let mut order: Vec<usize> = (0..occurrences.len()).collect();
+    order.sort_by_key(|&i| (occurrences[i].1, occurrences[i].0));
+
+    let mut series = vec![None; occurrences.len()];
+    let mut run_start = 0;
+    for k in 1..=order.len() {
+        let continues = k < order.len() && {
+            let (prev_date, prev_template) = occurrences[order[k - 1]];
+            let (date, template) = occurrences[order[k]];
+            template == prev_template && prev_date.succ_opt() == Some(date)
+        };
+        if !continues {
+            if k - run_start >= 2 {
+                let series_id = Uuid::new_v4();
+                for &i in &order[run_start..k] {
+                    series[i] = Some(series_id);
+                }
+            }
+            run_start = k;
+        }
+    }
+    series
+}
+
+/// GET /api/patterns?roleId=
+#[utoipa::path(
+    get,
+    path = "/api/patterns",
+    params(GetPatternsQuery),
+    responses(
+        (status = 200, description = "List of rota patterns with their entries", body = Vec<RotaPattern>)
+    ),
+    tag = "templates"
+)]
+pub async fn get_patterns(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<GetPatternsQuery>,
+) -> AppResult<Json<Vec<RotaPattern>>> {
+    let rows = sqlx::query_as::<_, PatternRow>(
+        r#"
+        SELECT id, role_id, name, created_at
+        FROM "RotaPatterns"
+        WHERE ($1::int4 IS NULL OR role_id = $1)
+        ORDER BY name
+        "#,
+    )
+    .bind(query.role_id)
+    .fetch_all(&state.db)
+    .await?;
+
+    let ids: Vec<i32> = rows.iter().map(|r| r.id).collect();
+    let entries = fetch_entries(&state.db, &ids).await?;
+
+    let patterns = rows
+        .into_iter()
+        .map(|row| RotaPattern {
+            entries: entries
+                .iter()
+                .filter(|(pid, _)| *pid == row.id)
+                .map(|(_, e)| e.clone())
+                .collect(),
+            id: row.id,
+            role_id: row.role_id,
+            name: row.name,
+            created_at: row.created_at,
+        })
+        .collect();
+
+    Ok(Json(patterns))
+}
+
+/// POST /api/patterns - Create a rota pattern
+#[utoipa::path(
+    post,
+    path = "/api/patterns",
+    request_body = CreatePatternInput,
+    responses(
+        (status = 200, description = "Pattern created successfully", body = RotaPattern),
+        (status = 400, description = "Invalid weekday or template not in role"),
+        (status = 403, description = "Missing can_edit_templates permission")
+    ),
+    tag = "templates",
+    security(("cookie_auth" = ["can_edit_templates"]))
+)]
+pub async fn create_pattern(
+    State(state): State<Arc<AppState>>,
+    _: RequirePermission<CanEditTemplates>,
+    Json(input): Json<CreatePatternInput>,
+) -> AppResult<Json<RotaPattern>> {
+    if input.name.trim().is_empty() {
+        return Err(AppError::BadRequest("name is required".to_string()));
+    }
+    validate_entries(&state.db, input.role_id, &input.entries).await?;
+
+    let mut tx = state.db.begin().await?;
+
+    let pattern_id: i32 = sqlx::query_scalar(r#"INSERT INTO "RotaPatterns" (role_id, name) VALUES ($1, $2) RETURNING id"#)
+        .bind(input.role_id)
+        .bind(input.name.trim())
+        .fetch_one(&mut *tx)
+        .await?;
+
+    insert_entries(&mut tx, pattern_id, &input.entries).await?;
+    tx.commit().await?;
+
+    Ok(Json(fetch_pattern(&state.db, pattern_id).await?))
+}
+
+/// PUT /api/patterns/{id} - Rename a pattern and/or replace its entries
+#[utoipa::path(
+    put,
+    path = "/api/patterns/{id}",
+    params(
+        ("id" = i32, Path, description = "Pattern ID")
+    ),
+    request_body = UpdatePatternInput,
+    responses(
+        (status = 200, description = "Pattern updated successfully", body = RotaPattern),
+        (status = 400, description = "Invalid weekday or template not in role"),
+        (status = 403, description = "Missing can_edit_templates permission"),
+        (status = 404, description = "Pattern not found")
+    ),
+    tag = "templates",
+    security(("cookie_auth" = ["can_edit_templates"]))
+)]
+pub async fn update_pattern(
+    State(state): State<Arc<AppState>>,
+    Path(pattern_id): Path<i32>,
+    _: RequirePermission<CanEditTemplates>,
+    Json(input): Json<UpdatePatternInput>,
+) -> AppResult<Json<RotaPattern>> {
+    let existing = fetch_pattern(&state.db, pattern_id).await?;
+
+    if let Some(ref entries) = input.entries {
+        validate_entries(&state.db, existing.role_id, entries).await?;
+    }
+
+    let mut tx = state.db.begin().await?;
+
+    if let Some(ref name) = input.name {
+        if name.trim().is_empty() {
+            return Err(AppError::BadRequest("name is required".to_string()));
+        }
+        sqlx::query(r#"UPDATE "RotaPatterns" SET name = $1 WHERE id = $2"#)
+            .bind(name.trim())
+            .bind(pattern_id)
+            .execute(&mut *tx)
+            .await?;
+    }
+
+    if let Some(ref entries) = input.entries {
+        sqlx::query(r#"DELETE FROM "RotaPatternEntries" WHERE pattern_id = $1"#)
+            .bind(pattern_id)
+            .execute(&mut *tx)
+            .await?;
+        insert_entries(&mut tx, pattern_id, entries).await?;
+    }
+
+    tx.commit().await?;
+
+    Ok(Json(fetch_pattern(&state.db, pattern_id).await?))
+}
+
+/// DELETE /api/patterns/{id} - Delete a pattern (shifts already generated are kept)
+#[utoipa::path(
+    delete,
+    path = "/api/patterns/{id}",
+    params(
+        ("id" = i32, Path, description = "Pattern ID")
+    ),
+    responses(
+        (status = 200, description = "Pattern deleted successfully", body = PatternMutationResponse),
+        (status = 403, description = "Missing can_edit_templates permission"),
+        (status = 404, description = "Pattern not found")
+    ),
+    tag = "templates",
+    security(("cookie_auth" = ["can_edit_templates"]))
+)]
+pub async fn delete_pattern(
+    State(state): State<Arc<AppState>>,
+    Path(pattern_id): Path<i32>,
+    _: RequirePermission<CanEditTemplates>,
+) -> AppResult<Json<PatternMutationResponse>> {
+    let result = sqlx::query(r#"DELETE FROM "RotaPatterns" WHERE id = $1"#)
+        .bind(pattern_id)
+        .execute(&state.db)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound(format!("Pattern {} not found", pattern_id)));
+    }
+
+    Ok(Json(PatternMutationResponse {
+        success: true,
+        message: Some("Pattern deleted successfully".to_string()),
+    }))
+}
+
+/// POST /api/patterns/{id}/apply - Create the pattern's shifts for every matching day of a month
+#[utoipa::path(
+    post,
+    path = "/api/patterns/{id}/apply",
+    params(
+        ("id" = i32, Path, description = "Pattern ID")
+    ),
+    request_body = ApplyPatternInput,
+    responses(
+        (status = 200, description = "Unassigned shifts created from the pattern's templates, or with dry_run those that would be (under preview) and the duplicates found, nothing written", body = ApplyPatternResponse),
+        (status = 400, description = "Invalid year/month"),
+        (status = 403, description = "Missing can_edit_rota permission"),
+        (status = 404, description = "Pattern not found"),
+        (status = 409, description = "Some shifts already exist (REJECT policy; listed under conflict), or the month is locked for payroll")
+    ),
+    tag = "templates",
+    security(("cookie_auth" = ["can_edit_rota"]))
+)]
+pub async fn apply_pattern(
+    State(state): State<Arc<AppState>>,
+    Path(pattern_id): Path<i32>,
+    RequirePermission { auth, .. }: RequirePermission<CanEditRota>,
+    Json(input): Json<ApplyPatternInput>,
+) -> AppResult<Json<ApplyPatternResponse>> {
+    let pattern = fetch_pattern(&state.db, pattern_id).await?;
+    let occurrences = pattern_dates(input.year, input.month, &pattern.entries)?;
+    let mut tx = crate::db::begin_as_user(&state.db, auth.profile_id).await?;
+    if let Some((date, _)) = occurrences.first() {
+        crate::handlers::payroll_locks_handler::ensure_unlocked(&mut tx, pattern.role_id, *date).await?;
+    }
+
+    if input.published {
+        if let Some((date, _)) = occurrences.first() {
+            crate::handlers::rota_approval_handler::ensure_publish_allowed(&mut tx, pattern.role_id, *date).await?;
+        }
+    }
+
+    let policy = crate::handlers::shifts_handler::preview_policy(
+        crate::handlers::shifts_handler::duplicate_policy(&state.db, pattern.role_id, input.on_duplicate).await?,
+        input.dry_run,
+    );
+    let duplicates = match policy {
+        DuplicateShiftPolicy::Allow => Vec::new(),
+        _ => {
+            let dates: Vec<NaiveDate> = occurrences.iter().map(|(date, _)| *date).collect();
+            crate::handlers::shifts_handler::lock_duplicate_checks(&mut tx, pattern.role_id, &dates).await?;
+            find_pattern_duplicates(&mut tx, &occurrences).await?
+        }
+    };
+    let (to_create, skipped) = resolve_duplicates(occurrences, duplicates, policy)?;
+
+    let template_ids: Vec<i32> = pattern.entries.iter().map(|e| e.template_id).collect();
+    let preferred: HashMap<i32, Vec<i32>> = sqlx::query_as::<_, (i32, Vec<i32>)>(
+        r#"SELECT id, preferred_user_ids FROM "ShiftTemplates" WHERE id = ANY($1) AND cardinality(preferred_user_ids) > 0"#,
+    )
+    .bind(&template_ids)
+    .fetch_all(&state.db)
+    .await?
+    .into_iter()
+    .collect();
+
+    let series = pattern_series(&to_create);
+    let mut created = 0;
+    let mut created_uuids = Vec::new();
+    let mut suggestions = Vec::new();
+
+    for ((date, template_id), series_id) in to_create.iter().zip(&series) {
+        let shift_uuid = Uuid::new_v4();
+        let result = sqlx::query(
+            r#"
+            INSERT INTO "Shifts" (
+                uuid, role_id, label, start, "end", money_per_hour,
+                pa_value, font_color, bk_color, is_locum, published,
+                date, is_dcc, is_spa, time_off_category_id,
+                user_profile_id, created_by, series_id
+            )
+            SELECT
+                $1, t.role_id, t.label, t.start, t."end", t.money_per_hour,
+                t.pa_value, t.font_color, t.bk_color, false, $2,
+                $3, t.is_dcc, t.is_spa, NULL,
+                NULL, $4, $6
+            FROM "ShiftTemplates" t
+            WHERE t.id = $5
+            "#,
+        )
+        .bind(shift_uuid)
+        .bind(input.published)
+        .bind(date)
+        .bind(auth.profile_id)
+        .bind(template_id)
+        .bind(series_id)
+        .execute(&mut *tx)
+        .await?;
+
+        created += result.rows_affected() as usize;
+        if result.rows_affected() == 1 {
+            created_uuids.push(shift_uuid);
+        }
+        if let (1, Some(preferred_user_ids)) = (result.rows_affected(), preferred.get(template_id)) {
+            suggestions.push(AssignmentSuggestion {
+                shift_uuid,
+                date: *date,
+                template_id: *template_id,
+                preferred_user_ids: preferred_user_ids.clone(),
+            });
+        }
+    }
+
+    let preview = if input.dry_run {
+        let preview = crate::handlers::shifts_handler::shifts_by_uuid(&mut tx, &created_uuids).await?;
+        tx.rollback().await?;
+        preview
+    } else {
+        tx.commit().await.map_err(|e| {
+            tracing::error!(error = %e, pattern_id, "❌ Transaction rollback in apply_pattern");
+            AppError::Internal(format!("Failed to commit pattern {}: {}", pattern_id, e))
+        })?;
+        Vec::new()
+    };
+
+    tracing::info!(
+        pattern_id,
+        role_id = pattern.role_id,
+        year = input.year,
+        month = input.month,
+        created,
+        skipped = skipped.len(),
+        dry_run = input.dry_run,
+        applied_by = auth.profile_id,
+        "📅 Rota pattern applied"
+    );
+
+    let series = series.into_iter().flatten().fold(Vec::new(), |mut ids, id| {
+        if !ids.contains(&id) {
+            ids.push(id);
+        }
+        ids
+    });
+
+    Ok(Json(ApplyPatternResponse {
+        success: true,
+        dry_run: input.dry_run,
+        created,
+        skipped: skipped.len(),
+        duplicates: skipped,
+        series,
+        suggestions,
+        preview,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(weekday: i16, template_id: i32) -> RotaPatternEntry {
+        RotaPatternEntry {
+            weekday,
+            template_id,
+            template_label: None,
+        }
+    }
+
+    #[test]
+    fn test_pattern_dates_rolls_weekdays_across_month() {
+        // February 2027 starts on a Monday and has exactly four weeks
+        let entries = vec![entry(1, 10), entry(7, 20), entry(7, 21)];
+        let dates = pattern_dates(2027, 2, &entries).unwrap();
+
+        assert_eq!(dates.len(), 4 * 3);
+        assert_eq!(dates[0], (NaiveDate::from_ymd_opt(2027, 2, 1).unwrap(), 10));
+        assert_eq!(dates[2], (NaiveDate::from_ymd_opt(2027, 2, 7).unwrap(), 21));
+        assert!(pattern_dates(2027, 13, &entries).is_err());
+    }
+
+    #[test]
+    fn test_resolve_duplicates_by_policy() {
+        let day = |d| NaiveDate::from_ymd_opt(2027, 2, d).unwrap();
+        let occurrences = vec![(day(1), 10), (day(1), 11), (day(8), 10)];
+        let existing = DuplicateShift { date: day(1), label: "Early".to_string(), existing_uuid: Uuid::from_u128(1) };
+        let duplicates = || vec![((day(1), 10), existing.clone())];
+
+        let (remaining, skipped) = resolve_duplicates(occurrences.clone(), duplicates(), DuplicateShiftPolicy::Skip).unwrap();
+        assert_eq!(remaining, vec![(day(1), 11), (day(8), 10)]);
+        assert_eq!(skipped, vec![existing.clone()]);
+
+        let (remaining, skipped) = resolve_duplicates(occurrences.clone(), duplicates(), DuplicateShiftPolicy::Allow).unwrap();
+        assert_eq!((remaining.len(), skipped.len()), (3, 0));
+
+        assert!(matches!(
+            resolve_duplicates(occurrences.clone(), duplicates(), DuplicateShiftPolicy::Reject),
+            Err(AppError::ConflictWith { .. })
+        ));
+        assert_eq!(resolve_duplicates(occurrences, Vec::new(), DuplicateShiftPolicy::Reject).unwrap().0.len(), 3);
+    }
+
+    #[test]
+    fn test_pattern_series_groups_consecutive_days_per_template() {
+        let day = |d| NaiveDate::from_ymd_opt(2027, 2, d).unwrap();
+        // Nights (20) Mon-Wed and again Sun; an early (10) on Tue only
+        let occurrences = vec![(day(1), 20), (day(2), 10), (day(2), 20), (day(3), 20), (day(7), 20)];
+        let series = pattern_series(&occurrences);
+
+        assert!(series[0].is_some());
+        assert_eq!(series[0], series[2]);
+        assert_eq!(series[0], series[3]);
+        assert_eq!((series[1], series[4]), (None, None));
+        assert!(pattern_series(&[]).is_empty());
+    }
+}
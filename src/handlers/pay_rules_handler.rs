@@ -0,0 +1,433 @@
+use axum::extract::{Path, Query, State};
+use chrono::{Datelike, Duration, NaiveDate, NaiveTime, Weekday};
+use serde::Deserialize;
+use sqlx::PgPool;
+use std::sync::Arc;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use crate::{
+    extractors::{permissions, AuthenticatedUser, Json},
+    handlers::roles_handler::month_range,
+    models::{RoleCostReport, RolePayRules, ShiftCost, UpdatePayRulesInput},
+    report::{Report, ReportFormat},
+    AppError, AppResult, AppState,
+};
+
+async fn ensure_can_edit_rota(state: &AppState, auth: &AuthenticatedUser, role_id: i32) -> AppResult<()> {
+    if !permissions::has_permission(state, auth.profile_id, auth.is_super_admin, |r| {
+        r.role_id == role_id && r.can_edit_rota
+    })
+    .await?
+    {
+        return Err(AppError::Forbidden("Missing can_edit_rota permission for this role".to_string()));
+    }
+    Ok(())
+}
+
+async fn ensure_role_exists(db: &PgPool, role_id: i32) -> AppResult<()> {
+    let exists: bool = sqlx::query_scalar(r#"SELECT EXISTS(SELECT 1 FROM "Roles" WHERE id = $1)"#)
+        .bind(role_id)
+        .fetch_one(db)
+        .await?;
+    if !exists {
+        return Err(AppError::NotFound(format!("Role {} not found", role_id)));
+    }
+    Ok(())
+}
+
+/// A role's pay rules, or the defaults (no enhancements) when none were saved
+pub async fn load_pay_rules(db: &PgPool, role_id: i32) -> AppResult<RolePayRules> {
+    let rules = sqlx::query_as::<_, RolePayRules>(
+        r#"
+        SELECT
+            role_id,
+            night_percent,
+            weekend_percent,
+            bank_holiday_percent,
+            to_char(night_start, 'HH24:MI:SS') AS night_start,
+            to_char(night_end, 'HH24:MI:SS') AS night_end,
+            bank_holidays
+        FROM "RolePayRules"
+        WHERE role_id = $1
+        "#,
+    )
+    .bind(role_id)
+    .fetch_optional(db)
+    .await?
+    .unwrap_or_else(|| RolePayRules::defaults(role_id));
+    Ok(rules)
+}
+
+fn parse_time(field: &str, value: &str) -> AppResult<NaiveTime> {
+    NaiveTime::parse_from_str(value, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(value, "%H:%M"))
+        .map_err(|_| AppError::BadRequest(format!("Invalid {}: expected HH:MM or HH:MM:SS", field)))
+}
+
+fn parse_percent(field: &str, value: f32) -> AppResult<f32> {
+    if !(0.0..=RolePayRules::MAX_PERCENT).contains(&value) {
+        return Err(AppError::BadRequest(format!(
+            "{} must be between 0 and {}",
+            field,
+            RolePayRules::MAX_PERCENT
+        )));
+    }
+    Ok(value)
+}
+
+/// Hours and pay for one shift
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ShiftPay {
+    hours: f64,
+    enhanced_hours: f64,
+    base: f64,
+    enhancement: f64,
+}
+
+/// Enhancement for the minute starting at `at`: the highest of the rules that apply
+fn enhancement_percent(rules: &RolePayRules, night: Option<(NaiveTime, NaiveTime)>, at: chrono::NaiveDateTime) -> f32 {
+    let mut percent: f32 = 0.0;
+    if rules.bank_holidays.contains(&at.date()) {
+        percent = percent.max(rules.bank_holiday_percent);
+    }
+    if matches!(at.weekday(), Weekday::Sat | Weekday::Sun) {
+        percent = percent.max(rules.weekend_percent);
+    }
+    if let Some((start, end)) = night {
+        let time = at.time();
+        let in_night = if start < end { time >= start && time < end } else { time >= start || time < end };
+        if in_night {
+            percent = percent.max(rules.night_percent);
+        }
+    }
+    percent
+}
+
+/// Pay for a shift starting on `date`, minute by minute so a shift running into the night or past midnight
+/// into a weekend is enhanced only for those hours. An end at or before the start is the next day.
+fn shift_pay(rules: &RolePayRules, date: NaiveDate, start: NaiveTime, end: NaiveTime, money_per_hour: f32) -> ShiftPay {
+    let minutes = i64::from(crate::models::shift::duration_minutes(start, end));
+
+    let night = match (parse_time("night_start", &rules.night_start), parse_time("night_end", &rules.night_end)) {
+        (Ok(start), Ok(end)) if start != end => Some((start, end)),
+        _ => None,
+    };
+
+    let per_minute = f64::from(money_per_hour) / 60.0;
+    let first = date.and_time(start);
+    let mut enhanced_minutes = 0;
+    let mut enhancement = 0.0;
+    for minute in 0..minutes {
+        let percent = enhancement_percent(rules, night, first + Duration::minutes(minute));
+        if percent > 0.0 {
+            enhanced_minutes += 1;
+            enhancement += per_minute * f64::from(percent) / 100.0;
+        }
+    }
+
+    ShiftPay {
+        hours: minutes as f64 / 60.0,
+        enhanced_hours: f64::from(enhanced_minutes) / 60.0,
+        base: f64::from(money_per_hour) * minutes as f64 / 60.0,
+        enhancement,
+    }
+}
+
+fn round_money(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+/// GET /api/roles/{id}/pay-rules - Night, weekend and bank-holiday enhancements for a role
+#[utoipa::path(
+    get,
+    path = "/api/roles/{id}/pay-rules",
+    params(("id" = i32, Path, description = "Role ID")),
+    responses(
+        (status = 200, description = "Pay rules (defaults with no enhancements when none were saved)", body = RolePayRules),
+        (status = 403, description = "Missing can_edit_rota permission for this role"),
+        (status = 404, description = "Role not found")
+    ),
+    tag = "roles",
+    security(("cookie_auth" = []))
+)]
+pub async fn get_pay_rules(
+    State(state): State<Arc<AppState>>,
+    Path(role_id): Path<i32>,
+    auth: AuthenticatedUser,
+) -> AppResult<Json<RolePayRules>> {
+    ensure_can_edit_rota(&state, &auth, role_id).await?;
+    ensure_role_exists(&state.db, role_id).await?;
+
+    Ok(Json(load_pay_rules(&state.db, role_id).await?))
+}
+
+/// PUT /api/roles/{id}/pay-rules - Update a role's pay enhancements
+#[utoipa::path(
+    put,
+    path = "/api/roles/{id}/pay-rules",
+    params(("id" = i32, Path, description = "Role ID")),
+    request_body = UpdatePayRulesInput,
+    responses(
+        (status = 200, description = "Pay rules updated", body = RolePayRules),
+        (status = 400, description = "Percentage out of range or invalid night time"),
+        (status = 403, description = "Missing can_edit_rota permission for this role"),
+        (status = 404, description = "Role not found")
+    ),
+    tag = "roles",
+    security(("cookie_auth" = []))
+)]
+pub async fn update_pay_rules(
+    State(state): State<Arc<AppState>>,
+    Path(role_id): Path<i32>,
+    auth: AuthenticatedUser,
+    Json(input): Json<UpdatePayRulesInput>,
+) -> AppResult<Json<RolePayRules>> {
+    ensure_can_edit_rota(&state, &auth, role_id).await?;
+    ensure_role_exists(&state.db, role_id).await?;
+
+    // Merge input over the current rules
+    let mut rules = load_pay_rules(&state.db, role_id).await?;
+    if let Some(percent) = input.night_percent {
+        rules.night_percent = parse_percent("night_percent", percent)?;
+    }
+    if let Some(percent) = input.weekend_percent {
+        rules.weekend_percent = parse_percent("weekend_percent", percent)?;
+    }
+    if let Some(percent) = input.bank_holiday_percent {
+        rules.bank_holiday_percent = parse_percent("bank_holiday_percent", percent)?;
+    }
+    if let Some(ref start) = input.night_start {
+        rules.night_start = parse_time("night_start", start)?.format("%H:%M:%S").to_string();
+    }
+    if let Some(ref end) = input.night_end {
+        rules.night_end = parse_time("night_end", end)?.format("%H:%M:%S").to_string();
+    }
+    if let Some(mut days) = input.bank_holidays {
+        days.sort();
+        days.dedup();
+        rules.bank_holidays = days;
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO "RolePayRules" (
+            role_id, night_percent, weekend_percent, bank_holiday_percent,
+            night_start, night_end, bank_holidays, updated_by
+        )
+        VALUES ($1, $2, $3, $4, $5::time, $6::time, $7, $8)
+        ON CONFLICT (role_id) DO UPDATE SET
+            night_percent = EXCLUDED.night_percent,
+            weekend_percent = EXCLUDED.weekend_percent,
+            bank_holiday_percent = EXCLUDED.bank_holiday_percent,
+            night_start = EXCLUDED.night_start,
+            night_end = EXCLUDED.night_end,
+            bank_holidays = EXCLUDED.bank_holidays,
+            updated_by = EXCLUDED.updated_by,
+            updated_at = NOW()
+        "#,
+    )
+    .bind(role_id)
+    .bind(rules.night_percent)
+    .bind(rules.weekend_percent)
+    .bind(rules.bank_holiday_percent)
+    .bind(&rules.night_start)
+    .bind(&rules.night_end)
+    .bind(&rules.bank_holidays)
+    .bind(auth.profile_id)
+    .execute(&state.db)
+    .await?;
+
+    tracing::info!(role_id, admin_id = auth.profile_id, "💷 Role pay rules updated");
+
+    Ok(Json(rules))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct RoleCostsQuery {
+    pub year: i32,
+    /// 1-12
+    pub month: i32,
+    /// Only locum shifts: the month's locum payments
+    #[serde(rename = "locumOnly")]
+    pub locum_only: Option<bool>,
+}
+
+#[derive(sqlx::FromRow)]
+struct CostedShiftRow {
+    uuid: Uuid,
+    date: NaiveDate,
+    label: String,
+    start: Option<String>,
+    end: Option<String>,
+    money_per_hour: Option<f32>,
+    is_locum: bool,
+    user_profile_id: Option<i32>,
+}
+
+/// GET /api/roles/{id}/costs?year=&month=&locumOnly= - Forecast pay for a role's month with enhancements applied
+#[utoipa::path(
+    get,
+    path = "/api/roles/{id}/costs",
+    params(
+        ("id" = i32, Path, description = "Role ID"),
+        RoleCostsQuery
+    ),
+    responses(
+        (status = 200, description = "Per-shift base pay and enhancement, with totals", content(
+            (RoleCostReport = "application/json"),
+            (String = "text/csv"),
+            (Vec<u8> = "application/pdf")
+        )),
+        (status = 400, description = "Invalid year or month"),
+        (status = 403, description = "Missing can_edit_rota permission for this role"),
+        (status = 404, description = "Role not found"),
+        (status = 406, description = "Accept names no supported format")
+    ),
+    tag = "roles",
+    security(("cookie_auth" = []))
+)]
+pub async fn get_role_costs(
+    State(state): State<Arc<AppState>>,
+    Path(role_id): Path<i32>,
+    auth: AuthenticatedUser,
+    format: ReportFormat,
+    Query(query): Query<RoleCostsQuery>,
+) -> AppResult<Report<RoleCostReport>> {
+    ensure_can_edit_rota(&state, &auth, role_id).await?;
+    let (from, to) = month_range(query.year, query.month)?;
+    ensure_role_exists(&state.db, role_id).await?;
+    let locum_only = query.locum_only.unwrap_or(false);
+
+    let rules = load_pay_rules(&state.db, role_id).await?;
+    let rows = sqlx::query_as::<_, CostedShiftRow>(
+        r#"
+        SELECT
+            uuid,
+            date,
+            label,
+            to_char(start, 'HH24:MI:SS') AS start,
+            to_char("end", 'HH24:MI:SS') AS "end",
+            money_per_hour,
+            is_locum,
+            user_profile_id
+        FROM "Shifts"
+        WHERE role_id = $1
+          AND date BETWEEN $2 AND $3
+          AND time_off_category_id IS NULL
+          AND (NOT $4 OR is_locum)
+        ORDER BY date, start
+        "#,
+    )
+    .bind(role_id)
+    .bind(from)
+    .bind(to)
+    .bind(locum_only)
+    .fetch_all(&state.db)
+    .await?;
+
+    let mut report = RoleCostReport {
+        role_id,
+        year: query.year,
+        month: query.month,
+        locum_only,
+        priced_shifts: 0,
+        unpriced_shifts: 0,
+        base_total: 0.0,
+        enhancement_total: 0.0,
+        total: 0.0,
+        locum_total: 0.0,
+        shifts: Vec::new(),
+    };
+
+    for row in rows {
+        let times = row.start.as_deref().zip(row.end.as_deref()).and_then(|(start, end)| {
+            Some((parse_time("start", start).ok()?, parse_time("end", end).ok()?))
+        });
+        let (Some((start, end)), Some(rate)) = (times, row.money_per_hour) else {
+            report.unpriced_shifts += 1;
+            continue;
+        };
+
+        let pay = shift_pay(&rules, row.date, start, end, rate);
+        let total = pay.base + pay.enhancement;
+        report.priced_shifts += 1;
+        report.base_total += pay.base;
+        report.enhancement_total += pay.enhancement;
+        report.total += total;
+        if row.is_locum {
+            report.locum_total += total;
+        }
+        report.shifts.push(ShiftCost {
+            shift_uuid: row.uuid,
+            date: row.date,
+            label: row.label,
+            start: row.start,
+            end: row.end,
+            user_profile_id: row.user_profile_id,
+            is_locum: row.is_locum,
+            money_per_hour: row.money_per_hour,
+            hours: pay.hours,
+            enhanced_hours: pay.enhanced_hours,
+            base: round_money(pay.base),
+            enhancement: round_money(pay.enhancement),
+            total: round_money(total),
+        });
+    }
+    report.base_total = round_money(report.base_total);
+    report.enhancement_total = round_money(report.enhancement_total);
+    report.total = round_money(report.total);
+    report.locum_total = round_money(report.locum_total);
+
+    let kind = if locum_only { "locum payments" } else { "costs" };
+    let title = format!("Role {} {} {}-{:02}", role_id, kind, query.year, query.month);
+    Ok(Report::new(format, title, report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    fn rules() -> RolePayRules {
+        RolePayRules {
+            night_percent: 50.0,
+            weekend_percent: 30.0,
+            bank_holiday_percent: 100.0,
+            bank_holidays: vec![NaiveDate::from_ymd_opt(2025, 5, 5).unwrap()],
+            ..RolePayRules::defaults(1)
+        }
+    }
+
+    #[test]
+    fn test_shift_pay_enhancements() {
+        // Wednesday 09:00-17:00: flat
+        let wednesday = NaiveDate::from_ymd_opt(2025, 5, 7).unwrap();
+        let pay = shift_pay(&rules(), wednesday, time(9, 0), time(17, 0), 60.0);
+        assert_eq!(pay, ShiftPay { hours: 8.0, enhanced_hours: 0.0, base: 480.0, enhancement: 0.0 });
+
+        // Wednesday 16:00-22:00: 2 night hours at +50%
+        let pay = shift_pay(&rules(), wednesday, time(16, 0), time(22, 0), 60.0);
+        assert_eq!((pay.hours, pay.enhanced_hours), (6.0, 2.0));
+        assert!((pay.enhancement - 60.0).abs() < 1e-6);
+
+        // Friday 20:00 to Saturday 08:00: Friday nights +50% (4h), Saturday 00:00-07:00 night beats
+        // weekend (7h at +50%), Saturday 07:00-08:00 weekend +30%
+        let friday = NaiveDate::from_ymd_opt(2025, 5, 9).unwrap();
+        let pay = shift_pay(&rules(), friday, time(20, 0), time(8, 0), 60.0);
+        assert_eq!((pay.hours, pay.enhanced_hours), (12.0, 12.0));
+        assert!((pay.enhancement - (11.0 * 30.0 + 18.0)).abs() < 1e-6);
+
+        // Bank holiday Monday daytime: +100%
+        let bank_holiday = NaiveDate::from_ymd_opt(2025, 5, 5).unwrap();
+        let pay = shift_pay(&rules(), bank_holiday, time(9, 0), time(10, 0), 60.0);
+        assert!((pay.enhancement - 60.0).abs() < 1e-6);
+
+        // Without rules nothing is enhanced, and a start equal to the end is a 24-hour shift
+        let pay = shift_pay(&RolePayRules::defaults(1), friday, time(8, 0), time(8, 0), 10.0);
+        assert_eq!(pay, ShiftPay { hours: 24.0, enhanced_hours: 0.0, base: 240.0, enhancement: 0.0 });
+    }
+}
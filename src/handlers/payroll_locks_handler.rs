@@ -0,0 +1,287 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use chrono::{Datelike, NaiveDate};
+use serde::Deserialize;
+use std::sync::Arc;
+use utoipa::IntoParams;
+use uuid::Uuid;
+
+use crate::{
+    extractors::{permissions, AuthenticatedUser},
+    models::{RotaLock, SuccessResponse},
+    AppError, AppResult, AppState,
+};
+
+const LOCK_SELECT: &str = r#"
+    SELECT l.role_id, l.year, l.month, l.locked_by, u.short_name AS locked_by_name, l.locked_at
+    FROM "RotaLocks" l
+    LEFT JOIN "Users" u ON l.locked_by = u.user_profile_id
+"#;
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct LockPeriodQuery {
+    #[serde(rename = "roleId")]
+    pub role_id: i32,
+    pub year: i32,
+    /// 1-12
+    pub month: u32,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct GetLocksQuery {
+    #[serde(rename = "roleId")]
+    pub role_id: Option<i32>,
+}
+
+/// Advisory lock key for one role's month. Writers hold it shared for the rest of their transaction once they
+/// have checked the month is open; locking a month takes it exclusively, so it waits for those writes to commit
+/// and no write can slip in between a check and the lock.
+fn period_key(year: i32, month: u32) -> i32 {
+    year * 12 + month as i32 - 1
+}
+
+/// SQL for `period_key` of a shift's date
+const SHIFT_PERIOD_KEY_SQL: &str = "(EXTRACT(YEAR FROM s.date)::int4 * 12 + EXTRACT(MONTH FROM s.date)::int4 - 1)";
+
+/// Refuse to create, move or change a shift in a locked month. Call inside the write's transaction.
+pub async fn ensure_unlocked(conn: &mut sqlx::PgConnection, role_id: i32, date: NaiveDate) -> AppResult<()> {
+    sqlx::query("SELECT pg_advisory_xact_lock_shared($1, $2)")
+        .bind(role_id)
+        .bind(period_key(date.year(), date.month()))
+        .execute(&mut *conn)
+        .await?;
+
+    let locked: bool = sqlx::query_scalar(
+        r#"SELECT EXISTS (SELECT 1 FROM "RotaLocks" WHERE role_id = $1 AND year = $2 AND month = $3)"#,
+    )
+    .bind(role_id)
+    .bind(date.year())
+    .bind(date.month() as i32)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    if locked {
+        return Err(AppError::PeriodLocked { year: date.year(), month: date.month() });
+    }
+    Ok(())
+}
+
+/// Refuse to touch existing shifts (edit, delete, swap, hand over) when any of them falls in a locked month.
+/// Call inside the write's transaction.
+pub async fn ensure_shifts_unlocked(conn: &mut sqlx::PgConnection, shift_ids: &[Uuid]) -> AppResult<()> {
+    sqlx::query(&format!(
+        r#"
+        SELECT pg_advisory_xact_lock_shared(k.role_id, k.period)
+        FROM (SELECT DISTINCT s.role_id, {} AS period FROM "Shifts" s WHERE s.uuid = ANY($1)) k
+        ORDER BY k.role_id, k.period
+        "#,
+        SHIFT_PERIOD_KEY_SQL
+    ))
+    .bind(shift_ids)
+    .execute(&mut *conn)
+    .await?;
+
+    let locked: Option<(i32, i32)> = sqlx::query_as(
+        r#"
+        SELECT l.year, l.month
+        FROM "Shifts" s
+        INNER JOIN "RotaLocks" l
+            ON l.role_id = s.role_id
+           AND l.year = EXTRACT(YEAR FROM s.date)::int4
+           AND l.month = EXTRACT(MONTH FROM s.date)::int4
+        WHERE s.uuid = ANY($1)
+        LIMIT 1
+        "#,
+    )
+    .bind(shift_ids)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    match locked {
+        Some((year, month)) => Err(AppError::PeriodLocked { year, month: month as u32 }),
+        None => Ok(()),
+    }
+}
+
+/// First day of the requested month; months after the current one cannot have been paid yet
+fn lockable_month(year: i32, month: u32, today: NaiveDate) -> AppResult<NaiveDate> {
+    let first = NaiveDate::from_ymd_opt(year, month, 1)
+        .ok_or_else(|| AppError::BadRequest(format!("Invalid month {}-{}", year, month)))?;
+    if first > today {
+        return Err(AppError::BadRequest("Only past or current months can be locked".to_string()));
+    }
+    Ok(first)
+}
+
+/// GET /api/shifts/locks?roleId= - Months locked for payroll
+#[utoipa::path(
+    get,
+    path = "/api/shifts/locks",
+    params(GetLocksQuery),
+    responses(
+        (status = 200, description = "Locked months, newest first", body = Vec<RotaLock>)
+    ),
+    tag = "shifts",
+    security(("cookie_auth" = []))
+)]
+pub async fn get_locks(
+    State(state): State<Arc<AppState>>,
+    _auth: AuthenticatedUser,
+    Query(query): Query<GetLocksQuery>,
+) -> AppResult<Json<Vec<RotaLock>>> {
+    let locks = sqlx::query_as::<_, RotaLock>(&format!(
+        "{} WHERE ($1::int4 IS NULL OR l.role_id = $1) ORDER BY l.year DESC, l.month DESC, l.role_id",
+        LOCK_SELECT
+    ))
+    .bind(query.role_id)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(locks))
+}
+
+/// POST /api/shifts/lock?roleId=&year=&month= - Lock a month after payroll (idempotent)
+#[utoipa::path(
+    post,
+    path = "/api/shifts/lock",
+    params(LockPeriodQuery),
+    responses(
+        (status = 200, description = "Month locked; its shifts can no longer be changed or swapped", body = RotaLock),
+        (status = 400, description = "Invalid or future month"),
+        (status = 403, description = "Missing can_edit_rota permission for this role"),
+        (status = 404, description = "Role not found")
+    ),
+    tag = "shifts",
+    security(("cookie_auth" = []))
+)]
+pub async fn lock_period(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+    Query(query): Query<LockPeriodQuery>,
+) -> AppResult<Json<RotaLock>> {
+    let role_id = query.role_id;
+    if !permissions::has_permission(&state, auth.profile_id, auth.is_super_admin, |r| {
+        r.role_id == role_id && r.can_edit_rota
+    })
+    .await?
+    {
+        return Err(AppError::Forbidden("Missing can_edit_rota permission for this role".to_string()));
+    }
+
+    let today = chrono::Local::now().date_naive();
+    lockable_month(query.year, query.month, today)?;
+
+    let role_exists: bool = sqlx::query_scalar(r#"SELECT EXISTS (SELECT 1 FROM "Roles" WHERE id = $1)"#)
+        .bind(role_id)
+        .fetch_one(&state.db)
+        .await?;
+    if !role_exists {
+        return Err(AppError::NotFound(format!("Role {} not found", role_id)));
+    }
+
+    let mut tx = crate::db::begin_as_user(&state.db, auth.profile_id).await?;
+    // Waits for writes that already found the month open
+    sqlx::query("SELECT pg_advisory_xact_lock($1, $2)")
+        .bind(role_id)
+        .bind(period_key(query.year, query.month))
+        .execute(&mut *tx)
+        .await?;
+    sqlx::query(
+        r#"
+        INSERT INTO "RotaLocks" (role_id, year, month, locked_by)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (role_id, year, month) DO NOTHING
+        "#,
+    )
+    .bind(role_id)
+    .bind(query.year)
+    .bind(query.month as i32)
+    .bind(auth.profile_id)
+    .execute(&mut *tx)
+    .await?;
+    tx.commit().await?;
+
+    let lock = sqlx::query_as::<_, RotaLock>(&format!(
+        "{} WHERE l.role_id = $1 AND l.year = $2 AND l.month = $3",
+        LOCK_SELECT
+    ))
+    .bind(role_id)
+    .bind(query.year)
+    .bind(query.month as i32)
+    .fetch_one(&state.db)
+    .await?;
+
+    tracing::info!(role_id, year = query.year, month = query.month, locked_by = lock.locked_by, "🔒 Rota month locked for payroll");
+
+    Ok(Json(lock))
+}
+
+/// POST /api/shifts/unlock?roleId=&year=&month= - Reopen a locked month (super admin)
+#[utoipa::path(
+    post,
+    path = "/api/shifts/unlock",
+    params(LockPeriodQuery),
+    responses(
+        (status = 200, description = "Month unlocked", body = SuccessResponse),
+        (status = 403, description = "Super admin permission required"),
+        (status = 404, description = "Month is not locked")
+    ),
+    tag = "shifts",
+    security(("cookie_auth" = []))
+)]
+pub async fn unlock_period(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+    Query(query): Query<LockPeriodQuery>,
+) -> AppResult<Json<SuccessResponse>> {
+    if !auth.is_super_admin {
+        return Err(AppError::Forbidden("Super admin permission required".to_string()));
+    }
+
+    let result = sqlx::query(r#"DELETE FROM "RotaLocks" WHERE role_id = $1 AND year = $2 AND month = $3"#)
+        .bind(query.role_id)
+        .bind(query.year)
+        .bind(query.month as i32)
+        .execute(&state.db)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound(format!(
+            "The {}-{:02} rota for role {} is not locked",
+            query.year, query.month, query.role_id
+        )));
+    }
+
+    tracing::warn!(
+        role_id = query.role_id,
+        year = query.year,
+        month = query.month,
+        unlocked_by = auth.profile_id,
+        "🔓 Rota month unlocked after payroll"
+    );
+
+    Ok(Json(SuccessResponse { success: true }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lockable_month() {
+        let today = NaiveDate::from_ymd_opt(2025, 5, 20).unwrap();
+        assert_eq!(lockable_month(2025, 5, today).unwrap(), NaiveDate::from_ymd_opt(2025, 5, 1).unwrap());
+        assert!(lockable_month(2024, 12, today).is_ok());
+        assert!(lockable_month(2025, 6, today).is_err());
+        assert!(lockable_month(2025, 13, today).is_err());
+    }
+
+    #[test]
+    fn test_period_key() {
+        assert_eq!(period_key(2025, 1), 2025 * 12);
+        assert_eq!(period_key(2025, 12) + 1, period_key(2026, 1));
+        assert!(SHIFT_PERIOD_KEY_SQL.contains("* 12 +") && SHIFT_PERIOD_KEY_SQL.ends_with("- 1)"));
+    }
+}
@@ -1,7 +1,11 @@
-use axum::{extract::State, Json};
+use axum::{
+    extract::State,
+    http::{HeaderName, HeaderValue},
+    Json,
+};
 use std::sync::Arc;
 
-use crate::{models::TimeOffCategory, AppResult, AppState};
+use crate::{cache, models::TimeOffCategory, AppResult, AppState};
 
 /// GET /api/references/time-off-categories
 #[utoipa::path(
@@ -14,7 +18,13 @@ use crate::{models::TimeOffCategory, AppResult, AppState};
 )]
 pub async fn get_time_off_categories(
     State(state): State<Arc<AppState>>,
-) -> AppResult<Json<Vec<TimeOffCategory>>> {
+) -> AppResult<([(HeaderName, HeaderValue); 1], Json<Vec<TimeOffCategory>>)> {
+    let headers = cache::cache_control(state.config.reference_cache_max_age);
+
+    if let Some(cached) = state.caches.time_off_categories.get().await {
+        return Ok((headers, Json(cached)));
+    }
+
     let categories = sqlx::query_as::<_, (i32, String, String, String, String)>(
         r#"
         SELECT
@@ -30,7 +40,7 @@ pub async fn get_time_off_categories(
     .fetch_all(&state.db)
     .await?;
 
-    let result = categories
+    let result: Vec<TimeOffCategory> = categories
         .into_iter()
         .map(|(id, label, short_name, font_color, bk_color)| TimeOffCategory {
             id,
@@ -41,5 +51,6 @@ pub async fn get_time_off_categories(
         })
         .collect();
 
-    Ok(Json(result))
+    state.caches.time_off_categories.insert(result.clone()).await;
+    Ok((headers, Json(result)))
 }
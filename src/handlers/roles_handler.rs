@@ -1,44 +1,128 @@
 use axum::{
     extract::{Path, Query, State},
-    Json,
+    http::HeaderMap,
 };
-use moka::future::Cache;
-use once_cell::sync::Lazy;
+use chrono::{Months, NaiveDate};
 use serde::Deserialize;
+use sqlx::PgPool;
 use std::sync::Arc;
-use std::time::Duration;
 use utoipa::IntoParams;
 
 use crate::{
-    extractors::AuthenticatedUser,
-    models::{CreateRoleInput, DependencyCount, Role, RoleMutationResponse, UpdateRoleInput, Workplace},
+    auth::generate_display_token,
+    extractors::{permissions, AuthenticatedUser, CanEditRota, Json, RequirePermission},
+    models::{
+        CreateDisplayTokenInput, CreateRoleInput, DependencyCount, DisplayTokenResponse, Role, RoleMutationResponse,
+        RoleStats, UpdateRoleInput, Workplace,
+    },
+    report::{Report, ReportFormat},
     AppError, AppResult, AppState,
 };
 
-// Cache all roles (unfiltered) with 60-second TTL
-static ROLES_CACHE: Lazy<Cache<&'static str, Vec<Role>>> = Lazy::new(|| {
-    Cache::builder()
-        .time_to_live(Duration::from_secs(60))
-        .build()
-});
-
-async fn invalidate_roles_cache() {
-    ROLES_CACHE.invalidate(&"all").await;
-}
-
 #[derive(Debug, Deserialize, IntoParams)]
 pub struct GetRolesQuery {
     pub hospital: Option<String>,
     pub ward: Option<String>,
+    #[serde(rename = "workplaceId")]
+    pub workplace_id: Option<i32>,
+    #[serde(rename = "includeArchived")]
+    pub include_archived: Option<bool>,  // Archived roles are left out by default
+}
+
+/// Role columns, joined workplace and active staff count, shared by the list and single-role queries;
+/// staff count as active while their assignment is in force on the date bound at `$today`
+fn role_select(today: usize) -> String {
+    format!(
+        r#"
+    SELECT
+        r.id::int4,
+        r.workplace_id::int4,
+        r.role_name,
+        r.marketplace_auto_approve,
+        r.publish_requires_approval,
+        r.max_swaps_per_month,
+        r.edit_reason_required,
+        r.archived,
+        (
+            SELECT COUNT(*)
+            FROM "UserRoles" ur
+            INNER JOIN "Users" u ON ur.user_profile_id = u.user_profile_id
+            WHERE ur.role_id = r.id AND ur.can_work_shifts AND NOT COALESCE(u.is_generic_login, false)
+              AND {ur_in_force}
+        )::int8 AS active_staff,
+        w.id::int4,
+        w.hospital,
+        w.ward,
+        w.address,
+        w.code
+    FROM "Roles" r
+    LEFT JOIN "Workplaces" w ON r.workplace_id = w.id
+"#,
+        ur_in_force = permissions::ur_in_force(today)
+    )
 }
 
-/// GET /api/roles?hospital=&ward=
+/// Highest monthly swap cap a role can set
+const MAX_SWAP_CAP: i16 = 100;
+
+/// 0 removes the cap (NULL), anything else must be between 1 and MAX_SWAP_CAP
+fn swap_cap(value: i16) -> AppResult<Option<i16>> {
+    match value {
+        0 => Ok(None),
+        1..=MAX_SWAP_CAP => Ok(Some(value)),
+        _ => Err(AppError::BadRequest(format!(
+            "max_swaps_per_month must be between 0 and {}",
+            MAX_SWAP_CAP
+        ))),
+    }
+}
+
+type RoleRow = (
+    i32,
+    i32,
+    String,
+    Option<bool>,
+    Option<bool>,
+    Option<i16>,
+    Option<bool>,
+    bool,
+    i64,
+    Option<i32>,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+);
+
+fn role_from_row(row: RoleRow) -> Role {
+    let (id, workplace, role_name, marketplace_auto_approve, publish_requires_approval, max_swaps_per_month, edit_reason_required, archived, active_staff, w_id, w_hospital, w_ward, w_address, w_code) = row;
+    Role {
+        id,
+        workplace,
+        role_name,
+        marketplace_auto_approve,
+        publish_requires_approval,
+        max_swaps_per_month,
+        edit_reason_required,
+        archived: Some(archived),
+        active_staff: Some(active_staff),
+        workplaces: w_id.map(|id| Workplace {
+            id,
+            hospital: w_hospital,
+            ward: w_ward,
+            address: w_address,
+            code: w_code,
+        }),
+    }
+}
+
+/// GET /api/roles?hospital=&ward=&workplaceId=&includeArchived=
 #[utoipa::path(
     get,
     path = "/api/roles",
     params(GetRolesQuery),
     responses(
-        (status = 200, description = "List of roles with joined workplace data (optionally filtered by workplace)", body = Vec<Role>)
+        (status = 200, description = "Roles with joined workplace data and active staff counts (optionally filtered by workplace)", body = Vec<Role>)
     ),
     tag = "roles"
 )]
@@ -46,34 +130,23 @@ pub async fn get_roles(
     State(state): State<Arc<AppState>>,
     Query(query): Query<GetRolesQuery>,
 ) -> AppResult<Json<Vec<Role>>> {
-    let has_filters = query.hospital.is_some() || query.ward.is_some();
+    let include_archived = query.include_archived.unwrap_or(false);
+    let has_filters = query.hospital.is_some() || query.ward.is_some() || query.workplace_id.is_some() || include_archived;
 
     // Use cache for unfiltered requests
     if !has_filters {
-        if let Some(cached) = ROLES_CACHE.get(&"all").await {
+        if let Some(cached) = state.caches.roles.get().await {
             return Ok(Json(cached));
         }
     }
 
-    // Build base query
-    let mut sql = r#"
-        SELECT
-            r.id::int4,
-            r.workplace_id::int4,
-            r.role_name,
-            r.marketplace_auto_approve,
-            w.id::int4,
-            w.hospital,
-            w.ward,
-            w.address,
-            w.code
-        FROM "Roles" r
-        LEFT JOIN "Workplaces" w ON r.workplace_id = w.id
-    "#.to_string();
-
     let mut conditions = vec![];
     let mut bind_values: Vec<String> = vec![];
 
+    if !include_archived {
+        conditions.push("NOT r.archived".to_string());
+    }
+
     if let Some(hospital) = query.hospital {
         conditions.push(format!("w.hospital = ${}", bind_values.len() + 1));
         bind_values.push(hospital);
@@ -84,6 +157,13 @@ pub async fn get_roles(
         bind_values.push(ward);
     }
 
+    // Bound after the string filters
+    if query.workplace_id.is_some() {
+        conditions.push(format!("r.workplace_id = ${}", bind_values.len() + 1));
+    }
+
+    // Bound last
+    let mut sql = role_select(bind_values.len() + usize::from(query.workplace_id.is_some()) + 1);
     if !conditions.is_empty() {
         sql.push_str(" WHERE ");
         sql.push_str(&conditions.join(" AND "));
@@ -91,34 +171,23 @@ pub async fn get_roles(
 
     sql.push_str(" ORDER BY r.id");
 
-    let mut query_builder = sqlx::query_as::<_, (i32, i32, String, Option<bool>, Option<i32>, Option<String>, Option<String>, Option<String>, Option<String>)>(&sql);
+    let mut query_builder = sqlx::query_as::<_, RoleRow>(&sql);
 
     for value in bind_values {
         query_builder = query_builder.bind(value);
     }
+    if let Some(workplace_id) = query.workplace_id {
+        query_builder = query_builder.bind(workplace_id);
+    }
+    query_builder = query_builder.bind(permissions::today());
 
     let rows = query_builder.fetch_all(&state.db).await?;
 
-    let result: Vec<Role> = rows
-        .into_iter()
-        .map(|(id, workplace, role_name, marketplace_auto_approve, w_id, w_hospital, w_ward, w_address, w_code)| Role {
-            id,
-            workplace,
-            role_name,
-            marketplace_auto_approve,
-            workplaces: w_id.map(|id| Workplace {
-                id,
-                hospital: w_hospital,
-                ward: w_ward,
-                address: w_address,
-                code: w_code,
-            }),
-        })
-        .collect();
+    let result: Vec<Role> = rows.into_iter().map(role_from_row).collect();
 
     // Cache unfiltered results
     if !has_filters {
-        ROLES_CACHE.insert("all", result.clone()).await;
+        state.caches.roles.insert(result.clone()).await;
     }
 
     Ok(Json(result))
@@ -131,7 +200,8 @@ pub async fn get_roles(
     request_body = CreateRoleInput,
     responses(
         (status = 200, description = "Role created successfully", body = Role),
-        (status = 403, description = "Missing can_edit_staff permission")
+        (status = 400, description = "max_swaps_per_month out of range"),
+        (status = 403, description = "Super admin permission required, or missing/invalid X-MFA-Code once TOTP is enabled")
     ),
     tag = "roles",
     security(("cookie_auth" = []))
@@ -139,6 +209,7 @@ pub async fn get_roles(
 pub async fn create_role(
     State(state): State<Arc<AppState>>,
     auth: AuthenticatedUser,
+    headers: HeaderMap,
     Json(input): Json<CreateRoleInput>,
 ) -> AppResult<Json<Role>> {
     // Check permission - super admin only
@@ -147,25 +218,33 @@ pub async fn create_role(
             "Super admin permission required".to_string(),
         ));
     }
+    crate::handlers::mfa_handler::ensure_fresh_mfa(&state.db, &auth, &headers).await?;
+    let max_swaps_per_month = swap_cap(input.max_swaps_per_month.unwrap_or(0))?;
 
     // Insert the new role
     let role_id: i32 = sqlx::query_scalar(
         r#"
-        INSERT INTO "Roles" (workplace_id, role_name, marketplace_auto_approve)
-        VALUES ($1, $2, $3)
+        INSERT INTO "Roles" (
+            workplace_id, role_name, marketplace_auto_approve, publish_requires_approval, max_swaps_per_month,
+            edit_reason_required
+        )
+        VALUES ($1, $2, $3, $4, $5, $6)
         RETURNING id::int4
         "#,
     )
     .bind(input.workplace_id)
     .bind(&input.role_name)
     .bind(input.marketplace_auto_approve.unwrap_or(false))
+    .bind(input.publish_requires_approval.unwrap_or(false))
+    .bind(max_swaps_per_month)
+    .bind(input.edit_reason_required.unwrap_or(false))
     .fetch_one(&state.db)
     .await?;
 
     // Fetch the created role with joined workplace data
     let role = fetch_role_by_id(&state.db, role_id).await?;
 
-    invalidate_roles_cache().await;
+    state.caches.invalidate_roles().await;
     Ok(Json(role))
 }
 
@@ -179,7 +258,7 @@ pub async fn create_role(
     request_body = UpdateRoleInput,
     responses(
         (status = 200, description = "Role updated successfully", body = Role),
-        (status = 400, description = "No fields to update"),
+        (status = 400, description = "No fields to update, or max_swaps_per_month out of range"),
         (status = 403, description = "Missing can_edit_staff permission"),
         (status = 404, description = "Role not found")
     ),
@@ -199,6 +278,8 @@ pub async fn update_role(
         ));
     }
 
+    let max_swaps_per_month = input.max_swaps_per_month.map(swap_cap).transpose()?;
+
     // Build dynamic UPDATE query
     let mut updates = vec![];
     let mut bind_count = 1;
@@ -215,6 +296,22 @@ pub async fn update_role(
         updates.push(format!("marketplace_auto_approve = ${}", bind_count));
         bind_count += 1;
     }
+    if input.publish_requires_approval.is_some() {
+        updates.push(format!("publish_requires_approval = ${}", bind_count));
+        bind_count += 1;
+    }
+    if max_swaps_per_month.is_some() {
+        updates.push(format!("max_swaps_per_month = ${}", bind_count));
+        bind_count += 1;
+    }
+    if input.edit_reason_required.is_some() {
+        updates.push(format!("edit_reason_required = ${}", bind_count));
+        bind_count += 1;
+    }
+    if input.archived.is_some() {
+        updates.push(format!("archived = ${}", bind_count));
+        bind_count += 1;
+    }
 
     if updates.is_empty() {
         return Err(AppError::BadRequest("No fields to update".to_string()));
@@ -238,6 +335,18 @@ pub async fn update_role(
     if let Some(marketplace_auto_approve) = input.marketplace_auto_approve {
         query = query.bind(marketplace_auto_approve);
     }
+    if let Some(publish_requires_approval) = input.publish_requires_approval {
+        query = query.bind(publish_requires_approval);
+    }
+    if let Some(max_swaps_per_month) = max_swaps_per_month {
+        query = query.bind(max_swaps_per_month);
+    }
+    if let Some(edit_reason_required) = input.edit_reason_required {
+        query = query.bind(edit_reason_required);
+    }
+    if let Some(archived) = input.archived {
+        query = query.bind(archived);
+    }
 
     query = query.bind(role_id);
 
@@ -250,10 +359,62 @@ pub async fn update_role(
     // Fetch the updated role with joined workplace data
     let role = fetch_role_by_id(&state.db, role_id).await?;
 
-    invalidate_roles_cache().await;
+    state.caches.invalidate_roles().await;
     Ok(Json(role))
 }
 
+/// POST /api/roles/{id}/display-token - Issue a read-only rota token for a wall display
+#[utoipa::path(
+    post,
+    path = "/api/roles/{id}/display-token",
+    params(
+        ("id" = i32, Path, description = "Role ID")
+    ),
+    request_body = CreateDisplayTokenInput,
+    responses(
+        (status = 200, description = "Display token issued (use with GET /api/display/rota)", body = DisplayTokenResponse),
+        (status = 400, description = "valid_days out of range"),
+        (status = 403, description = "Missing can_edit_rota permission, or the display_tokens feature is off"),
+        (status = 404, description = "Role not found")
+    ),
+    tag = "roles",
+    security(("cookie_auth" = ["can_edit_rota"]))
+)]
+pub async fn create_display_token(
+    State(state): State<Arc<AppState>>,
+    Path(role_id): Path<i32>,
+    RequirePermission { auth, .. }: RequirePermission<CanEditRota>,
+    Json(input): Json<CreateDisplayTokenInput>,
+) -> AppResult<Json<DisplayTokenResponse>> {
+    crate::features::require(&state, crate::features::DISPLAY_TOKENS, auth.profile_id).await?;
+
+    let valid_days = input.valid_days.unwrap_or(90);
+    if !(1..=365).contains(&valid_days) {
+        return Err(AppError::BadRequest("valid_days must be between 1 and 365".to_string()));
+    }
+
+    let exists: bool = sqlx::query_scalar(r#"SELECT EXISTS(SELECT 1 FROM "Roles" WHERE id = $1)"#)
+        .bind(role_id)
+        .fetch_one(&state.db)
+        .await?;
+
+    if !exists {
+        return Err(AppError::NotFound(format!("Role {} not found", role_id)));
+    }
+
+    let (token, expiry_time) = generate_display_token(role_id, valid_days, &state.config.pin_token_secret)?;
+    let expires_at = chrono::DateTime::from_timestamp(expiry_time, 0)
+        .ok_or_else(|| AppError::Internal("Invalid display token expiry".to_string()))?;
+
+    tracing::info!(role_id, valid_days, issued_by = auth.profile_id, "🖥️ Rota display token issued");
+
+    Ok(Json(DisplayTokenResponse {
+        role_id,
+        token,
+        expires_at,
+    }))
+}
+
 /// DELETE /api/roles/{id} - Delete a role
 #[utoipa::path(
     delete,
@@ -290,13 +451,101 @@ pub async fn delete_role(
         return Err(AppError::NotFound(format!("Role {} not found", role_id)));
     }
 
-    invalidate_roles_cache().await;
+    state.caches.invalidate_roles().await;
     Ok(Json(RoleMutationResponse {
         success: true,
         message: Some("Role deleted successfully".to_string()),
     }))
 }
 
+/// What the role and workplace nukes delete before the roles themselves, deepest children first: each
+/// table with the condition selecting the rows of the roles bound as `$1` (an int4 array). Both nukes and
+/// both dependency counts are built from this list so they cannot drift apart.
+pub const ROLE_DEPENDENCIES: [(&str, &str); 8] = [
+    ("ShiftRequests", r#"shift_id IN (SELECT uuid FROM "Shifts" WHERE role_id = ANY($1))"#),
+    ("JobPlans", "role_id = ANY($1)"),
+    ("ShiftAudit", "role_id = ANY($1)"),
+    ("Diary", "role_id = ANY($1)"),
+    ("Shifts", "role_id = ANY($1)"),
+    ("ShiftTemplates", "role_id = ANY($1)"),
+    ("UserRoles", "role_id = ANY($1)"),
+    ("COD", "role_id = ANY($1)"),
+];
+
+fn dependency_delete_sql(table: &str, condition: &str) -> String {
+    format!(r#"DELETE FROM "{}" WHERE {}"#, table, condition)
+}
+
+/// COUNT for one of `ROLE_DEPENDENCIES`
+fn dependency_count_sql(table: &str) -> String {
+    let (_, condition) = ROLE_DEPENDENCIES
+        .iter()
+        .find(|(name, _)| *name == table)
+        .expect("table is listed in ROLE_DEPENDENCIES");
+    format!(r#"SELECT COUNT(*)::int8 FROM "{}" WHERE {}"#, table, condition)
+}
+
+/// Delete everything in `ROLE_DEPENDENCIES` for the roles, leaving the roles themselves to the caller
+pub async fn delete_role_dependencies(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    role_ids: &[i32],
+) -> AppResult<()> {
+    for (table, condition) in ROLE_DEPENDENCIES {
+        let result = sqlx::query(&dependency_delete_sql(table, condition))
+            .bind(role_ids)
+            .execute(&mut **tx)
+            .await?;
+        tracing::info!(table, rows = result.rows_affected(), "🗑️ NUKE: Deleted dependencies");
+    }
+    Ok(())
+}
+
+/// Rows the nukes would delete for the roles (`roles` is the number of roles)
+pub async fn count_role_dependencies(db: &PgPool, role_ids: &[i32]) -> AppResult<DependencyCount> {
+    let count = |table: &str| {
+        let sql = dependency_count_sql(table);
+        async move { sqlx::query_scalar::<_, i64>(&sql).bind(role_ids).fetch_one(db).await }
+    };
+
+    // Run the COUNT queries in parallel
+    let (
+        user_roles_count,
+        job_plans_count,
+        shifts_count,
+        templates_count,
+        diary_count,
+        audit_count,
+        cod_count,
+        shift_requests_count,
+        unique_staff,
+    ) = tokio::try_join!(
+        count("UserRoles"),
+        count("JobPlans"),
+        count("Shifts"),
+        count("ShiftTemplates"),
+        count("Diary"),
+        count("ShiftAudit"),
+        count("COD"),
+        count("ShiftRequests"),
+        sqlx::query_scalar::<_, i64>(r#"SELECT COUNT(DISTINCT user_profile_id)::int8 FROM "UserRoles" WHERE role_id = ANY($1)"#)
+            .bind(role_ids)
+            .fetch_one(db),
+    )?;
+
+    Ok(DependencyCount {
+        roles: role_ids.len() as i32,
+        user_roles: user_roles_count as i32,
+        job_plans: job_plans_count as i32,
+        shifts: shifts_count as i32,
+        shift_requests: shift_requests_count as i32,
+        templates: templates_count as i32,
+        diary_entries: diary_count as i32,
+        audit_entries: audit_count as i32,
+        cod_entries: cod_count as i32,
+        unique_staff: unique_staff as i32,
+    })
+}
+
 /// GET /api/roles/{id}/dependencies - Get dependency counts before deletion
 #[utoipa::path(
     get,
@@ -323,51 +572,7 @@ pub async fn get_role_dependencies(
         ));
     }
 
-    // Run all 9 COUNT queries in parallel for ~9x speedup
-    let db = &state.db;
-    let (
-        user_roles_count,
-        job_plans_count,
-        shifts_count,
-        templates_count,
-        diary_count,
-        audit_count,
-        cod_count,
-        shift_requests_count,
-        unique_staff,
-    ) = tokio::try_join!(
-        sqlx::query_scalar::<_, i64>(r#"SELECT COUNT(*)::int8 FROM "UserRoles" WHERE role_id = $1"#)
-            .bind(role_id).fetch_one(db),
-        sqlx::query_scalar::<_, i64>(r#"SELECT COUNT(*)::int8 FROM "JobPlans" WHERE role_id = $1"#)
-            .bind(role_id).fetch_one(db),
-        sqlx::query_scalar::<_, i64>(r#"SELECT COUNT(*)::int8 FROM "Shifts" WHERE role_id = $1"#)
-            .bind(role_id).fetch_one(db),
-        sqlx::query_scalar::<_, i64>(r#"SELECT COUNT(*)::int8 FROM "ShiftTemplates" WHERE role_id = $1"#)
-            .bind(role_id).fetch_one(db),
-        sqlx::query_scalar::<_, i64>(r#"SELECT COUNT(*)::int8 FROM "Diary" WHERE role_id = $1"#)
-            .bind(role_id).fetch_one(db),
-        sqlx::query_scalar::<_, i64>(r#"SELECT COUNT(*)::int8 FROM "ShiftAudit" WHERE role_id = $1"#)
-            .bind(role_id).fetch_one(db),
-        sqlx::query_scalar::<_, i64>(r#"SELECT COUNT(*)::int8 FROM "COD" WHERE role_id = $1"#)
-            .bind(role_id).fetch_one(db),
-        sqlx::query_scalar::<_, i64>(r#"SELECT COUNT(*)::int8 FROM "ShiftRequests" WHERE shift_id IN (SELECT uuid FROM "Shifts" WHERE role_id = $1)"#)
-            .bind(role_id).fetch_one(db),
-        sqlx::query_scalar::<_, i64>(r#"SELECT COUNT(DISTINCT user_profile_id)::int8 FROM "UserRoles" WHERE role_id = $1"#)
-            .bind(role_id).fetch_one(db),
-    )?;
-
-    Ok(Json(DependencyCount {
-        roles: 1,  // Single role
-        user_roles: user_roles_count as i32,
-        job_plans: job_plans_count as i32,
-        shifts: shifts_count as i32,
-        shift_requests: shift_requests_count as i32,
-        templates: templates_count as i32,
-        diary_entries: diary_count as i32,
-        audit_entries: audit_count as i32,
-        cod_entries: cod_count as i32,
-        unique_staff: unique_staff as i32,
-    }))
+    Ok(Json(count_role_dependencies(&state.db, &[role_id]).await?))
 }
 
 /// DELETE /api/roles/{id}/nuke - CASCADE delete role and ALL related data
@@ -379,7 +584,7 @@ pub async fn get_role_dependencies(
     ),
     responses(
         (status = 200, description = "Role and all dependencies deleted", body = RoleMutationResponse),
-        (status = 403, description = "Super admin permission required"),
+        (status = 403, description = "Super admin permission required, or missing/invalid X-MFA-Code once TOTP is enabled"),
         (status = 404, description = "Role not found")
     ),
     tag = "roles",
@@ -389,6 +594,7 @@ pub async fn nuke_role(
     State(state): State<Arc<AppState>>,
     Path(role_id): Path<i32>,
     auth: AuthenticatedUser,
+    headers: HeaderMap,
 ) -> AppResult<Json<RoleMutationResponse>> {
     // Check permission - super admin only
     if !auth.is_super_admin {
@@ -396,64 +602,16 @@ pub async fn nuke_role(
             "Super admin permission required".to_string(),
         ));
     }
+    crate::handlers::mfa_handler::ensure_fresh_mfa(&state.db, &auth, &headers).await?;
 
     tracing::warn!("⚠️ NUKE: Starting cascade delete of role {}", role_id);
 
     // Start transaction
-    let mut tx = state.db.begin().await?;
+    let mut tx = crate::db::begin_as_user(&state.db, auth.profile_id).await?;
 
-    // Delete in order (deepest children → parent):
+    delete_role_dependencies(&mut tx, &[role_id]).await?;
 
-    // 1. Shift requests (references shifts via subquery)
-    sqlx::query(r#"DELETE FROM "ShiftRequests" WHERE shift_id IN (SELECT uuid FROM "Shifts" WHERE role_id = $1)"#)
-        .bind(role_id)
-        .execute(&mut *tx)
-        .await?;
-    tracing::info!("🗑️ NUKE: Deleted shift requests");
-
-    // 2. Job plans (references role)
-    sqlx::query(r#"DELETE FROM "JobPlans" WHERE role_id = $1"#)
-        .bind(role_id)
-        .execute(&mut *tx)
-        .await?;
-
-    // 3. Shift audit trail
-    sqlx::query(r#"DELETE FROM "ShiftAudit" WHERE role_id = $1"#)
-        .bind(role_id)
-        .execute(&mut *tx)
-        .await?;
-
-    // 4. Diary entries
-    sqlx::query(r#"DELETE FROM "Diary" WHERE role_id = $1"#)
-        .bind(role_id)
-        .execute(&mut *tx)
-        .await?;
-
-    // 5. Shifts
-    sqlx::query(r#"DELETE FROM "Shifts" WHERE role_id = $1"#)
-        .bind(role_id)
-        .execute(&mut *tx)
-        .await?;
-
-    // 6. Shift templates
-    sqlx::query(r#"DELETE FROM "ShiftTemplates" WHERE role_id = $1"#)
-        .bind(role_id)
-        .execute(&mut *tx)
-        .await?;
-
-    // 7. User role assignments
-    sqlx::query(r#"DELETE FROM "UserRoles" WHERE role_id = $1"#)
-        .bind(role_id)
-        .execute(&mut *tx)
-        .await?;
-
-    // 8. COD entries
-    sqlx::query(r#"DELETE FROM "COD" WHERE role_id = $1"#)
-        .bind(role_id)
-        .execute(&mut *tx)
-        .await?;
-
-    // 9. Finally, the role itself
+    // Finally, the role itself
     let result = sqlx::query(r#"DELETE FROM "Roles" WHERE id = $1"#)
         .bind(role_id)
         .execute(&mut *tx)
@@ -464,7 +622,7 @@ pub async fn nuke_role(
     }
 
     tx.commit().await?;
-    invalidate_roles_cache().await;
+    state.caches.invalidate_roles().await;
     tracing::warn!("⚠️ NUKE: Role {} annihilated", role_id);
 
     Ok(Json(RoleMutationResponse {
@@ -476,38 +634,174 @@ pub async fn nuke_role(
 /// Helper function to check if user has a specific permission
 /// Helper function to fetch a role by ID with joined Workplace data
 async fn fetch_role_by_id(db: &sqlx::PgPool, role_id: i32) -> AppResult<Role> {
-    let row = sqlx::query_as::<_, (i32, i32, String, Option<bool>, Option<i32>, Option<String>, Option<String>, Option<String>, Option<String>)>(
+    let row = sqlx::query_as::<_, RoleRow>(&format!("{} WHERE r.id = $1", role_select(2)))
+        .bind(role_id)
+        .bind(permissions::today())
+        .fetch_one(db)
+        .await?;
+
+    Ok(role_from_row(row))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct RoleStatsQuery {
+    pub year: i32,
+    /// 1-12
+    pub month: i32,
+}
+
+/// First and last day of a calendar month
+pub fn month_range(year: i32, month: i32) -> AppResult<(NaiveDate, NaiveDate)> {
+    let first = u32::try_from(month)
+        .ok()
+        .and_then(|month| NaiveDate::from_ymd_opt(year, month, 1))
+        .ok_or_else(|| AppError::BadRequest("Invalid year or month".to_string()))?;
+    let last = first
+        .checked_add_months(Months::new(1))
+        .and_then(|d| d.pred_opt())
+        .ok_or_else(|| AppError::BadRequest("Invalid year or month".to_string()))?;
+    Ok((first, last))
+}
+
+/// GET /api/roles/{id}/stats?year=&month= - Rota figures for a role's month, for department overviews
+#[utoipa::path(
+    get,
+    path = "/api/roles/{id}/stats",
+    params(
+        ("id" = i32, Path, description = "Role ID"),
+        RoleStatsQuery
+    ),
+    responses(
+        (status = 200, description = "Shift count, filled vs unfilled, locum proportion, total PAs and distinct staff", content(
+            (RoleStats = "application/json"),
+            (String = "text/csv"),
+            (Vec<u8> = "application/pdf")
+        )),
+        (status = 400, description = "Invalid year or month"),
+        (status = 403, description = "Missing can_edit_rota permission in the role"),
+        (status = 404, description = "Role not found"),
+        (status = 406, description = "Accept names no supported format")
+    ),
+    tag = "roles",
+    security(("cookie_auth" = []))
+)]
+pub async fn get_role_stats(
+    State(state): State<Arc<AppState>>,
+    Path(role_id): Path<i32>,
+    auth: AuthenticatedUser,
+    format: ReportFormat,
+    Query(query): Query<RoleStatsQuery>,
+) -> AppResult<Report<RoleStats>> {
+    let (from, to) = month_range(query.year, query.month)?;
+
+    if !auth.is_super_admin {
+        let rota_roles = permissions::roles_with_permission(&state, auth.profile_id, permissions::can_edit_rota).await?;
+        if !rota_roles.contains(&role_id) {
+            return Err(AppError::Forbidden("Missing can_edit_rota permission in this role".to_string()));
+        }
+    }
+
+    let role_exists: bool = sqlx::query_scalar(r#"SELECT EXISTS(SELECT 1 FROM "Roles" WHERE id = $1)"#)
+        .bind(role_id)
+        .fetch_one(&state.db)
+        .await?;
+    if !role_exists {
+        return Err(AppError::NotFound("Role not found".to_string()));
+    }
+
+    let stats = sqlx::query_as::<_, RoleStats>(
         r#"
         SELECT
-            r.id::int4,
-            r.workplace_id::int4,
-            r.role_name,
-            r.marketplace_auto_approve,
-            w.id::int4,
-            w.hospital,
-            w.ward,
-            w.address,
-            w.code
-        FROM "Roles" r
-        LEFT JOIN "Workplaces" w ON r.workplace_id = w.id
-        WHERE r.id = $1
+            $1::int4 AS role_id,
+            $4::int4 AS year,
+            $5::int4 AS month,
+            COUNT(*) FILTER (WHERE time_off_category_id IS NULL) AS shift_count,
+            COUNT(*) FILTER (WHERE time_off_category_id IS NULL AND user_profile_id IS NOT NULL) AS filled,
+            COUNT(*) FILTER (WHERE time_off_category_id IS NULL AND user_profile_id IS NULL) AS unfilled,
+            COUNT(*) FILTER (WHERE time_off_category_id IS NULL AND is_locum) AS locum_shifts,
+            COALESCE(
+                (COUNT(*) FILTER (WHERE time_off_category_id IS NULL AND is_locum))::float8
+                    / NULLIF(COUNT(*) FILTER (WHERE time_off_category_id IS NULL), 0),
+                0
+            ) AS locum_proportion,
+            COALESCE(SUM(pa_value) FILTER (WHERE time_off_category_id IS NULL), 0)::float8 AS total_pa,
+            COUNT(DISTINCT user_profile_id) FILTER (WHERE time_off_category_id IS NULL) AS distinct_staff,
+            COUNT(*) FILTER (WHERE time_off_category_id IS NOT NULL) AS time_off_entries
+        FROM "Shifts"
+        WHERE role_id = $1 AND date BETWEEN $2 AND $3
         "#,
     )
     .bind(role_id)
-    .fetch_one(db)
+    .bind(from)
+    .bind(to)
+    .bind(query.year)
+    .bind(query.month)
+    .fetch_one(&state.db)
     .await?;
 
-    Ok(Role {
-        id: row.0,
-        workplace: row.1,
-        role_name: row.2,
-        marketplace_auto_approve: row.3,
-        workplaces: row.4.map(|id| Workplace {
-            id,
-            hospital: row.5,
-            ward: row.6,
-            address: row.7,
-            code: row.8,
-        }),
-    })
+    let title = format!("Role {} stats {}-{:02}", role_id, query.year, query.month);
+    Ok(Report::new(format, title, stats))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_month_range() {
+        let day = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        assert_eq!(month_range(2024, 2).unwrap(), (day(2024, 2, 1), day(2024, 2, 29)));
+        assert_eq!(month_range(2025, 12).unwrap(), (day(2025, 12, 1), day(2025, 12, 31)));
+        assert!(month_range(2025, 0).is_err());
+        assert!(month_range(2025, 13).is_err());
+    }
+
+    #[test]
+    fn test_swap_cap() {
+        assert_eq!(swap_cap(0).unwrap(), None);
+        assert_eq!(swap_cap(4).unwrap(), Some(4));
+        assert_eq!(swap_cap(MAX_SWAP_CAP).unwrap(), Some(MAX_SWAP_CAP));
+        assert!(swap_cap(-1).is_err());
+        assert!(swap_cap(MAX_SWAP_CAP + 1).is_err());
+    }
+
+    #[test]
+    fn test_role_dependencies_are_bound_and_ordered() {
+        let position = |table: &str| ROLE_DEPENDENCIES.iter().position(|(name, _)| *name == table).unwrap();
+        for (table, condition) in ROLE_DEPENDENCIES {
+            assert!(condition.contains("ANY($1)"), "{} is not bound to the role ids", table);
+            assert_eq!(
+                dependency_delete_sql(table, condition),
+                format!(r#"DELETE FROM "{}" WHERE {}"#, table, condition)
+            );
+            assert!(dependency_count_sql(table).ends_with(condition));
+            // Rows found through shifts must go before the shifts
+            if condition.contains(r#"FROM "Shifts""#) {
+                assert!(position(table) < position("Shifts"), "{} deleted after Shifts", table);
+            }
+        }
+    }
+
+    /// The role and workplace nukes must delete the same dependency set: both go through
+    /// `delete_role_dependencies`, and neither deletes anything else before the roles themselves
+    #[test]
+    fn test_nukes_delete_the_same_dependencies() {
+        let body = |source: &'static str, name: &str| {
+            let start = source.find(&format!("pub async fn {}(", name)).unwrap();
+            let end = source[start..].find("\n}").unwrap();
+            &source[start..start + end]
+        };
+        let role_nuke = body(include_str!("roles_handler.rs"), "nuke_role");
+        let workplace_nuke = body(include_str!("workplaces_handler.rs"), "nuke_workplace");
+
+        for (nuke, own_tables) in [(role_nuke, vec!["Roles"]), (workplace_nuke, vec!["Roles", "Workplaces"])] {
+            assert_eq!(nuke.matches("delete_role_dependencies(&mut tx").count(), 1);
+            let deleted: Vec<&str> = nuke
+                .split("DELETE FROM \"")
+                .skip(1)
+                .filter_map(|rest| rest.split('"').next())
+                .collect();
+            assert!(deleted.iter().all(|table| own_tables.contains(table)), "unexpected deletes: {:?}", deleted);
+        }
+    }
 }
@@ -0,0 +1,366 @@
+use axum::{
+    extract::{Path, Query, State},
+};
+use chrono::{Datelike, NaiveDate};
+use serde::Deserialize;
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+use crate::{
+    extractors::{permissions, AuthenticatedUser, CanApproveRota, CanEditRota, Json, RequirePermission},
+    models::{RotaApprovalDecisionInput, RotaPublishApproval, SubmitRotaApprovalInput},
+    AppError, AppResult, AppState,
+};
+
+const APPROVAL_SELECT: &str = r#"
+    SELECT
+        a.id,
+        a.role_id,
+        a.year,
+        a.month,
+        a.status,
+        a.submitted_by,
+        u_sub.short_name AS submitted_by_name,
+        a.submitted_at,
+        a.submit_comment,
+        a.decided_by,
+        u_dec.short_name AS decided_by_name,
+        a.decided_at,
+        a.decision_comment
+    FROM "RotaPublishApprovals" a
+    LEFT JOIN "Users" u_sub ON a.submitted_by = u_sub.user_profile_id
+    LEFT JOIN "Users" u_dec ON a.decided_by = u_dec.user_profile_id
+"#;
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct GetRotaApprovalsQuery {
+    #[serde(rename = "roleId")]
+    pub role_id: Option<i32>,
+    /// PENDING, APPROVED or REJECTED
+    pub status: Option<String>,
+}
+
+/// Refuse to publish a shift in a month that still needs sign-off.
+/// Roles without `publish_requires_approval` are never blocked. Call it on the transaction that writes the shifts.
+pub async fn ensure_publish_allowed(conn: &mut sqlx::PgConnection, role_id: i32, date: NaiveDate) -> AppResult<()> {
+    let blocked: bool = sqlx::query_scalar(
+        r#"
+        SELECT r.publish_requires_approval AND NOT EXISTS (
+            SELECT 1 FROM "RotaPublishApprovals" a
+            WHERE a.role_id = r.id AND a.year = $2 AND a.month = $3 AND a.status = 'APPROVED'
+        )
+        FROM "Roles" r
+        WHERE r.id = $1
+        "#,
+    )
+    .bind(role_id)
+    .bind(date.year())
+    .bind(date.month() as i32)
+    .fetch_optional(&mut *conn)
+    .await?
+    .unwrap_or(false);
+
+    if blocked {
+        return Err(AppError::Forbidden(format!(
+            "The {}-{:02} rota for this role must be approved before shifts can be published",
+            date.year(),
+            date.month()
+        )));
+    }
+
+    Ok(())
+}
+
+/// Check that `decider` may decide `approval` as asked; returns the trimmed comment.
+/// Only pending requests can be decided, deciders need can_approve_rota in the approval's role (`approver_roles`)
+/// and cannot decide their own submission (super admins excepted), and a rejection needs a comment.
+fn check_decision<'a>(
+    approval: &RotaPublishApproval,
+    decider_id: i32,
+    is_super_admin: bool,
+    approver_roles: &[i32],
+    input: &'a RotaApprovalDecisionInput,
+) -> AppResult<Option<&'a str>> {
+    if !is_super_admin && !approver_roles.contains(&approval.role_id) {
+        return Err(AppError::Forbidden("Missing can_approve_rota permission for this role".to_string()));
+    }
+    if approval.status != "PENDING" {
+        return Err(AppError::BadRequest(format!(
+            "Approval request {} has already been {}",
+            approval.id,
+            approval.status.to_lowercase()
+        )));
+    }
+    if approval.submitted_by == decider_id && !is_super_admin {
+        return Err(AppError::Forbidden("You cannot decide on your own submission".to_string()));
+    }
+    let comment = input.comment.as_deref().map(str::trim).filter(|c| !c.is_empty());
+    if !input.approve && comment.is_none() {
+        return Err(AppError::BadRequest("A comment is required when rejecting".to_string()));
+    }
+    Ok(comment)
+}
+
+/// Notification kind and message sent to the submitter once a month is decided
+fn decision_notice(approval: &RotaPublishApproval, approve: bool, comment: Option<&str>) -> (&'static str, String) {
+    if approve {
+        (
+            "ROTA_APPROVED",
+            format!("The {}-{:02} rota was approved and can now be published.", approval.year, approval.month),
+        )
+    } else {
+        (
+            "ROTA_REJECTED",
+            format!("The {}-{:02} rota was rejected: {}", approval.year, approval.month, comment.unwrap_or_default()),
+        )
+    }
+}
+
+async fn fetch_approval(db: &sqlx::PgPool, approval_id: i32) -> AppResult<RotaPublishApproval> {
+    sqlx::query_as::<_, RotaPublishApproval>(&format!("{} WHERE a.id = $1", APPROVAL_SELECT))
+        .bind(approval_id)
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Approval request {} not found", approval_id)))
+}
+
+/// GET /api/rota/approvals?roleId=&status=
+#[utoipa::path(
+    get,
+    path = "/api/rota/approvals",
+    params(GetRotaApprovalsQuery),
+    responses(
+        (status = 200, description = "Publish approval requests, newest first", body = Vec<RotaPublishApproval>),
+        (status = 403, description = "Missing can_edit_rota or can_approve_rota permission")
+    ),
+    tag = "rota",
+    security(("cookie_auth" = []))
+)]
+pub async fn get_rota_approvals(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+    Query(query): Query<GetRotaApprovalsQuery>,
+) -> AppResult<Json<Vec<RotaPublishApproval>>> {
+    let has_perm = permissions::has_any_permission(
+        &state,
+        auth.profile_id,
+        auth.is_super_admin,
+        &[permissions::can_edit_rota, permissions::can_approve_rota],
+    )
+    .await?;
+
+    if !has_perm {
+        return Err(AppError::Forbidden(
+            "Missing can_edit_rota or can_approve_rota permission".to_string(),
+        ));
+    }
+
+    let approvals = sqlx::query_as::<_, RotaPublishApproval>(&format!(
+        "{} WHERE ($1::int4 IS NULL OR a.role_id = $1) AND ($2::text IS NULL OR a.status = $2) ORDER BY a.submitted_at DESC",
+        APPROVAL_SELECT
+    ))
+    .bind(query.role_id)
+    .bind(query.status.map(|s| s.to_uppercase()))
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(approvals))
+}
+
+/// POST /api/rota/approvals - Submit a month of a role's rota for publish approval
+#[utoipa::path(
+    post,
+    path = "/api/rota/approvals",
+    request_body = SubmitRotaApprovalInput,
+    responses(
+        (status = 200, description = "Approval request created", body = RotaPublishApproval),
+        (status = 400, description = "Invalid month"),
+        (status = 403, description = "Missing can_edit_rota permission"),
+        (status = 404, description = "Role not found"),
+        (status = 409, description = "Month already pending or approved")
+    ),
+    tag = "rota",
+    security(("cookie_auth" = ["can_edit_rota"]))
+)]
+pub async fn submit_rota_approval(
+    State(state): State<Arc<AppState>>,
+    RequirePermission { auth, .. }: RequirePermission<CanEditRota>,
+    Json(input): Json<SubmitRotaApprovalInput>,
+) -> AppResult<Json<RotaPublishApproval>> {
+    if !(1..=12).contains(&input.month) {
+        return Err(AppError::BadRequest("month must be between 1 and 12".to_string()));
+    }
+
+    let role_exists: bool = sqlx::query_scalar(r#"SELECT EXISTS(SELECT 1 FROM "Roles" WHERE id = $1)"#)
+        .bind(input.role_id)
+        .fetch_one(&state.db)
+        .await?;
+    if !role_exists {
+        return Err(AppError::NotFound(format!("Role {} not found", input.role_id)));
+    }
+
+    // The partial unique index allows one PENDING/APPROVED request per role and month
+    let approval_id: Option<i32> = sqlx::query_scalar(
+        r#"
+        INSERT INTO "RotaPublishApprovals" (role_id, year, month, submitted_by, submit_comment)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT DO NOTHING
+        RETURNING id
+        "#,
+    )
+    .bind(input.role_id)
+    .bind(input.year)
+    .bind(input.month)
+    .bind(auth.profile_id)
+    .bind(&input.comment)
+    .fetch_optional(&state.db)
+    .await?;
+
+    let approval_id = approval_id.ok_or_else(|| {
+        AppError::Conflict(format!(
+            "The {}-{:02} rota for role {} is already pending or approved",
+            input.year, input.month, input.role_id
+        ))
+    })?;
+
+    tracing::info!(
+        approval_id,
+        role_id = input.role_id,
+        year = input.year,
+        month = input.month,
+        submitted_by = auth.profile_id,
+        "📝 Rota submitted for publish approval"
+    );
+
+    Ok(Json(fetch_approval(&state.db, approval_id).await?))
+}
+
+/// POST /api/rota/approvals/{id}/decision - Approve or reject a pending month
+#[utoipa::path(
+    post,
+    path = "/api/rota/approvals/{id}/decision",
+    params(
+        ("id" = i32, Path, description = "Approval request ID")
+    ),
+    request_body = RotaApprovalDecisionInput,
+    responses(
+        (status = 200, description = "Decision recorded; the submitter is notified", body = RotaPublishApproval),
+        (status = 400, description = "Rejection without a comment, or request already decided"),
+        (status = 403, description = "Missing can_approve_rota permission in the approval's role, or approving your own submission"),
+        (status = 404, description = "Approval request not found")
+    ),
+    tag = "rota",
+    security(("cookie_auth" = ["can_approve_rota"]))
+)]
+pub async fn decide_rota_approval(
+    State(state): State<Arc<AppState>>,
+    Path(approval_id): Path<i32>,
+    RequirePermission { auth, .. }: RequirePermission<CanApproveRota>,
+    Json(input): Json<RotaApprovalDecisionInput>,
+) -> AppResult<Json<RotaPublishApproval>> {
+    let approval = fetch_approval(&state.db, approval_id).await?;
+    let approver_roles =
+        permissions::roles_with_permission(&state, auth.profile_id, permissions::can_approve_rota).await?;
+    let comment = check_decision(&approval, auth.profile_id, auth.is_super_admin, &approver_roles, &input)?;
+
+    let status = if input.approve { "APPROVED" } else { "REJECTED" };
+    let mut tx = crate::db::begin_as_user(&state.db, auth.profile_id).await?;
+
+    let updated = sqlx::query(
+        r#"
+        UPDATE "RotaPublishApprovals"
+        SET status = $1, decided_by = $2, decided_at = NOW(), decision_comment = $3
+        WHERE id = $4 AND status = 'PENDING'
+        "#,
+    )
+    .bind(status)
+    .bind(auth.profile_id)
+    .bind(comment)
+    .bind(approval_id)
+    .execute(&mut *tx)
+    .await?;
+
+    if updated.rows_affected() == 0 {
+        return Err(AppError::Conflict(format!("Approval request {} was decided concurrently", approval_id)));
+    }
+
+    let (kind, message) = decision_notice(&approval, input.approve, comment);
+    crate::handlers::notifications_handler::notify(
+        &mut *tx,
+        approval.submitted_by,
+        kind,
+        &message,
+        serde_json::json!({ "approval_id": approval_id, "role_id": approval.role_id }),
+    )
+    .await?;
+
+    tx.commit().await?;
+
+    tracing::info!(
+        approval_id,
+        role_id = approval.role_id,
+        status,
+        decided_by = auth.profile_id,
+        "✅ Rota publish approval decided"
+    );
+
+    Ok(Json(fetch_approval(&state.db, approval_id).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approval(status: &str, submitted_by: i32) -> RotaPublishApproval {
+        RotaPublishApproval {
+            id: 1,
+            role_id: 3,
+            year: 2026,
+            month: 4,
+            status: status.to_string(),
+            submitted_by,
+            submitted_by_name: None,
+            submitted_at: chrono::NaiveDateTime::default(),
+            submit_comment: None,
+            decided_by: None,
+            decided_by_name: None,
+            decided_at: None,
+            decision_comment: None,
+        }
+    }
+
+    fn decision(approve: bool, comment: Option<&str>) -> RotaApprovalDecisionInput {
+        RotaApprovalDecisionInput { approve, comment: comment.map(String::from) }
+    }
+
+    #[test]
+    fn test_check_decision() {
+        let pending = approval("PENDING", 7);
+        let roles = [3];
+        assert_eq!(check_decision(&pending, 8, false, &roles, &decision(true, None)).unwrap(), None);
+        assert_eq!(check_decision(&pending, 8, false, &roles, &decision(false, Some(" gaps on nights "))).unwrap(), Some("gaps on nights"));
+
+        assert!(check_decision(&pending, 8, false, &roles, &decision(false, Some("  "))).is_err());
+        assert!(check_decision(&pending, 7, false, &roles, &decision(true, None)).is_err()); // own submission
+        assert!(check_decision(&pending, 7, true, &[], &decision(true, None)).is_ok()); // super admin
+        assert!(check_decision(&approval("APPROVED", 7), 8, false, &roles, &decision(true, None)).is_err());
+    }
+
+    #[test]
+    fn test_check_decision_needs_approval_in_the_role() {
+        let pending = approval("PENDING", 7);
+        assert!(matches!(
+            check_decision(&pending, 8, false, &[4], &decision(true, None)),
+            Err(AppError::Forbidden(_))
+        ));
+        assert!(check_decision(&pending, 8, false, &[4, 3], &decision(true, None)).is_ok());
+    }
+
+    #[test]
+    fn test_decision_notice() {
+        let pending = approval("PENDING", 7);
+        assert_eq!(decision_notice(&pending, true, None).0, "ROTA_APPROVED");
+        let (kind, message) = decision_notice(&pending, false, Some("gaps on nights"));
+        assert_eq!((kind, message.as_str()), ("ROTA_REJECTED", "The 2026-04 rota was rejected: gaps on nights"));
+    }
+}
@@ -0,0 +1,376 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use axum_extra::extract::Query as MultiQuery;
+use chrono::{Months, NaiveDate, NaiveDateTime};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+use crate::{
+    auth::validate_display_token,
+    extractors::{AuthenticatedUser, CanEditRota, RequirePermission},
+    db::fieldset::{FieldSet, SHIFT_FIELDS},
+    handlers::shifts_handler::{check_role_scope, parse_role_ids, roles_in_scope},
+    models::{
+        shift::{CROSSES_MIDNIGHT_SQL, DURATION_MINUTES_SQL},
+        DisplayRota, DisplayShift, MovedAssignment, RoleRota, RotaDiff, Shift, SnapshotShift,
+    },
+    AppError, AppResult, AppState,
+};
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct GetRotaQuery {
+    pub year: i32,
+    pub month: u32,
+    /// Roles to include: comma-separated and/or repeated; defaults to all of the caller's roles
+    #[serde(default, rename = "roleId")]
+    pub role_id: Vec<String>,
+}
+
+/// GET /api/rota?year=&month=&roleId=1,2 - One month of shifts for several roles, grouped by role
+#[utoipa::path(
+    get,
+    path = "/api/rota",
+    params(GetRotaQuery),
+    responses(
+        (status = 200, description = "Shifts grouped by role, in the order requested", body = Vec<RoleRota>),
+        (status = 400, description = "Invalid month or roleId list"),
+        (status = 403, description = "A requested role is not one of the caller's roles")
+    ),
+    tag = "rota",
+    security(("cookie_auth" = []))
+)]
+pub async fn get_rota(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+    MultiQuery(query): MultiQuery<GetRotaQuery>,
+) -> AppResult<Json<Vec<RoleRota>>> {
+    let from = NaiveDate::from_ymd_opt(query.year, query.month, 1)
+        .ok_or_else(|| AppError::BadRequest("Invalid year or month".to_string()))?;
+    let until = from
+        .checked_add_months(Months::new(1))
+        .ok_or_else(|| AppError::BadRequest("Invalid year or month".to_string()))?;
+
+    let in_scope = roles_in_scope(&state, &auth).await?;
+
+    let requested = parse_role_ids(&query.role_id)?;
+    let roles: Vec<(i32, String)> = if requested.is_empty() {
+        in_scope
+    } else {
+        check_role_scope(&requested, &in_scope)?;
+        requested
+            .iter()
+            .filter_map(|id| in_scope.iter().find(|(scope_id, _)| scope_id == id).cloned())
+            .collect()
+    };
+
+    let role_ids: Vec<i32> = roles.iter().map(|(id, _)| *id).collect();
+    let sql = crate::db::tag_sql(&format!(
+        r#"SELECT {} FROM "Shifts" WHERE role_id = ANY($1) AND date >= $2 AND date < $3 ORDER BY date, start"#,
+        FieldSet::parse(None, SHIFT_FIELDS)?.select_list(&[])
+    ));
+    let shifts = sqlx::query_as::<_, Shift>(&sql)
+        .persistent(false)
+        .bind(&role_ids)
+        .bind(from)
+        .bind(until)
+        .fetch_all(&state.db)
+        .await?;
+
+    let mut by_role: HashMap<i32, Vec<Shift>> = HashMap::new();
+    for shift in shifts {
+        by_role.entry(shift.role).or_default().push(shift);
+    }
+
+    Ok(Json(
+        roles
+            .into_iter()
+            .map(|(role_id, role_name)| RoleRota {
+                role_id,
+                role_name,
+                shifts: by_role.remove(&role_id).unwrap_or_default(),
+            })
+            .collect(),
+    ))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct GetRotaDiffQuery {
+    #[serde(rename = "roleId")]
+    pub role_id: i32,
+    /// Earlier snapshot date (YYYY-MM-DD)
+    pub from: String,
+    /// Later snapshot date (YYYY-MM-DD)
+    pub to: String,
+}
+
+/// GET /api/rota/diff?roleId=&from=&to=
+#[utoipa::path(
+    get,
+    path = "/api/rota/diff",
+    params(GetRotaDiffQuery),
+    responses(
+        (status = 200, description = "Assignments added, removed or moved between two daily rota snapshots", body = RotaDiff),
+        (status = 400, description = "Invalid date format or from is after to"),
+        (status = 403, description = "Missing can_edit_rota permission"),
+        (status = 404, description = "No snapshot for one of the requested dates")
+    ),
+    tag = "rota",
+    security(("cookie_auth" = ["can_edit_rota"]))
+)]
+pub async fn get_rota_diff(
+    State(state): State<Arc<AppState>>,
+    _: RequirePermission<CanEditRota>,
+    Query(query): Query<GetRotaDiffQuery>,
+) -> AppResult<Json<RotaDiff>> {
+    let from = NaiveDate::parse_from_str(&query.from, "%Y-%m-%d")
+        .map_err(|e| AppError::BadRequest(format!("Invalid from date: {}", e)))?;
+    let to = NaiveDate::parse_from_str(&query.to, "%Y-%m-%d")
+        .map_err(|e| AppError::BadRequest(format!("Invalid to date: {}", e)))?;
+
+    if from > to {
+        return Err(AppError::BadRequest("from must not be after to".to_string()));
+    }
+
+    let (before, after) = tokio::try_join!(
+        fetch_snapshot(&state.db, query.role_id, from),
+        fetch_snapshot(&state.db, query.role_id, to),
+    )?;
+
+    let (from_taken_at, before) = before.ok_or_else(|| {
+        AppError::NotFound(format!("No rota snapshot for role {} on {}", query.role_id, from))
+    })?;
+    let (to_taken_at, after) = after.ok_or_else(|| {
+        AppError::NotFound(format!("No rota snapshot for role {} on {}", query.role_id, to))
+    })?;
+
+    let (added, removed, moved) = diff_snapshots(before, after, to);
+
+    tracing::debug!(
+        role_id = query.role_id,
+        %from,
+        %to,
+        added = added.len(),
+        removed = removed.len(),
+        moved = moved.len(),
+        "🔍 Computed rota diff"
+    );
+
+    Ok(Json(RotaDiff {
+        role_id: query.role_id,
+        from,
+        to,
+        from_taken_at,
+        to_taken_at,
+        added,
+        removed,
+        moved,
+    }))
+}
+
+/// Longest window a display may request
+const MAX_DISPLAY_DAYS: i64 = 62;
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct GetDisplayRotaQuery {
+    /// Display token issued by POST /api/roles/{id}/display-token
+    pub token: String,
+    /// First day (YYYY-MM-DD), defaults to today
+    pub from: Option<String>,
+    /// Number of days to show (default 14, max 62)
+    pub days: Option<i64>,
+}
+
+/// GET /api/display/rota?token=&from=&days= - Read-only published rota for wall displays (no login)
+#[utoipa::path(
+    get,
+    path = "/api/display/rota",
+    params(GetDisplayRotaQuery),
+    responses(
+        (status = 200, description = "Published shifts for the token's role; time off and contact details are omitted", body = DisplayRota),
+        (status = 400, description = "Invalid date or day count"),
+        (status = 401, description = "Invalid or expired display token"),
+        (status = 404, description = "Role no longer exists")
+    ),
+    tag = "rota"
+)]
+pub async fn get_display_rota(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<GetDisplayRotaQuery>,
+) -> AppResult<Json<DisplayRota>> {
+    let role_id = validate_display_token(&query.token, &state.config.pin_token_secret)?;
+
+    let from = match query.from {
+        Some(ref from) => NaiveDate::parse_from_str(from, "%Y-%m-%d")
+            .map_err(|e| AppError::BadRequest(format!("Invalid from date: {}", e)))?,
+        None => chrono::Local::now().date_naive(),
+    };
+    let days = query.days.unwrap_or(14);
+    if !(1..=MAX_DISPLAY_DAYS).contains(&days) {
+        return Err(AppError::BadRequest(format!("days must be between 1 and {}", MAX_DISPLAY_DAYS)));
+    }
+    let to = from + chrono::Duration::days(days - 1);
+
+    let role_name: String = sqlx::query_scalar(r#"SELECT role_name FROM "Roles" WHERE id = $1"#)
+        .bind(role_id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Role {} not found", role_id)))?;
+
+    let shifts = sqlx::query_as::<_, DisplayShift>(&format!(
+        r#"
+        SELECT
+            s.date,
+            s.label,
+            to_char(s.start, 'HH24:MI') AS start,
+            to_char(s."end", 'HH24:MI') AS "end",
+            s.font_color,
+            s.bk_color,
+            u.full_name AS staff_name,
+            u.short_name AS staff_short_name,
+            {},
+            {}
+        FROM "Shifts" s
+        LEFT JOIN "Users" u ON s.user_profile_id = u.user_profile_id
+        WHERE s.role_id = $1
+          AND s.published = true
+          AND s.time_off_category_id IS NULL
+          AND s.date BETWEEN $2 AND $3
+        ORDER BY s.date, s.start, s.label
+        "#,
+        DURATION_MINUTES_SQL, CROSSES_MIDNIGHT_SQL
+    ))
+    .bind(role_id)
+    .bind(from)
+    .bind(to)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(DisplayRota {
+        role_id,
+        role_name,
+        from,
+        to,
+        shifts,
+    }))
+}
+
+/// Fetch a role's snapshot for a given day, returning when it was taken and its shifts
+async fn fetch_snapshot(
+    db: &sqlx::PgPool,
+    role_id: i32,
+    snapshot_date: NaiveDate,
+) -> Result<Option<(NaiveDateTime, Vec<SnapshotShift>)>, sqlx::Error> {
+    let row: Option<(NaiveDateTime, sqlx::types::Json<Vec<SnapshotShift>>)> = sqlx::query_as(
+        r#"SELECT taken_at, shifts FROM "RotaSnapshots" WHERE role_id = $1 AND snapshot_date = $2"#,
+    )
+    .bind(role_id)
+    .bind(snapshot_date)
+    .fetch_optional(db)
+    .await?;
+
+    Ok(row.map(|(taken_at, shifts)| (taken_at, shifts.0)))
+}
+
+/// Compare two snapshots by shift UUID.
+/// Each snapshot only covers shifts dated on/after its own day, so shifts in `before`
+/// dated earlier than `window_start` (the later snapshot's day) are ignored rather than
+/// being reported as removed.
+fn diff_snapshots(
+    before: Vec<SnapshotShift>,
+    after: Vec<SnapshotShift>,
+    window_start: NaiveDate,
+) -> (Vec<SnapshotShift>, Vec<SnapshotShift>, Vec<MovedAssignment>) {
+    let mut before_by_uuid: HashMap<_, _> = before
+        .into_iter()
+        .filter(|s| s.date >= window_start)
+        .map(|s| (s.uuid, s))
+        .collect();
+
+    let mut added = vec![];
+    let mut moved = vec![];
+
+    for shift in after {
+        match before_by_uuid.remove(&shift.uuid) {
+            None => added.push(shift),
+            Some(prev) => {
+                let changed = prev.user_profile_id != shift.user_profile_id
+                    || prev.date != shift.date
+                    || prev.start != shift.start
+                    || prev.end != shift.end;
+                if changed {
+                    moved.push(MovedAssignment {
+                        uuid: shift.uuid,
+                        before: prev,
+                        after: shift,
+                    });
+                }
+            }
+        }
+    }
+
+    let mut removed: Vec<SnapshotShift> = before_by_uuid.into_values().collect();
+    removed.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.start.cmp(&b.start)));
+
+    (added, removed, moved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn shift(uuid: Uuid, date: &str, user_profile_id: Option<i32>) -> SnapshotShift {
+        SnapshotShift {
+            uuid,
+            date: NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap(),
+            label: "Day".to_string(),
+            start: Some("08:00:00".to_string()),
+            end: Some("18:00:00".to_string()),
+            user_profile_id,
+        }
+    }
+
+    #[test]
+    fn test_diff_detects_added_removed_and_moved() {
+        let (kept, reassigned, dropped, new) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let window = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+
+        let before = vec![
+            shift(kept, "2026-03-05", Some(1)),
+            shift(reassigned, "2026-03-06", Some(1)),
+            shift(dropped, "2026-03-07", Some(2)),
+        ];
+        let after = vec![
+            shift(kept, "2026-03-05", Some(1)),
+            shift(reassigned, "2026-03-06", Some(3)),
+            shift(new, "2026-03-08", None),
+        ];
+
+        let (added, removed, moved) = diff_snapshots(before, after, window);
+
+        assert_eq!(added.len(), 1);
+        assert_eq!(added[0].uuid, new);
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].uuid, dropped);
+        assert_eq!(moved.len(), 1);
+        assert_eq!(moved[0].uuid, reassigned);
+        assert_eq!(moved[0].after.user_profile_id, Some(3));
+    }
+
+    #[test]
+    fn test_diff_ignores_shifts_before_window() {
+        let past = Uuid::new_v4();
+        let window = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+
+        let before = vec![shift(past, "2026-03-01", Some(1))];
+        let (added, removed, moved) = diff_snapshots(before, vec![], window);
+
+        assert!(added.is_empty());
+        assert!(removed.is_empty());
+        assert!(moved.is_empty());
+    }
+}
@@ -0,0 +1,405 @@
+use axum::extract::{Path, State};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::{
+    extractors::{AuthenticatedUser, Json},
+    models::{CopySavedViewInput, CreateSavedViewInput, SavedView, SavedViewMutationResponse, UpdateSavedViewInput},
+    AppError, AppResult, AppState,
+};
+
+const VIEW_SELECT: &str = r#"
+    SELECT
+        v.id,
+        v.user_profile_id,
+        u.short_name AS owner_short_name,
+        v.name,
+        v.view_type,
+        v.role_id,
+        v.staff_ids,
+        v.share_token,
+        v.created_at,
+        v.updated_at
+    FROM "SavedViews" v
+    LEFT JOIN "Users" u ON u.user_profile_id = v.user_profile_id
+"#;
+
+const VIEW_TYPES: &[&str] = &["DAY", "WEEK", "MONTH", "LIST"];
+const MAX_NAME_LENGTH: usize = 100;
+const MAX_STAFF_IDS: usize = 500;
+const MAX_VIEWS_PER_USER: i64 = 50;
+
+fn validate_name(name: &str) -> AppResult<String> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+        return Err(AppError::BadRequest(format!("name must be 1-{} characters", MAX_NAME_LENGTH)));
+    }
+    Ok(name.to_string())
+}
+
+/// "week" -> "WEEK"
+fn validate_view_type(view_type: &str) -> AppResult<String> {
+    let view_type = view_type.trim().to_ascii_uppercase();
+    if !VIEW_TYPES.contains(&view_type.as_str()) {
+        return Err(AppError::BadRequest(format!("view_type must be one of {}", VIEW_TYPES.join(", "))));
+    }
+    Ok(view_type)
+}
+
+/// Sorted and deduplicated so equal subsets compare equal
+fn normalize_staff_ids(mut staff_ids: Vec<i32>) -> AppResult<Vec<i32>> {
+    staff_ids.sort_unstable();
+    staff_ids.dedup();
+    if staff_ids.len() > MAX_STAFF_IDS {
+        return Err(AppError::BadRequest(format!("staff_ids can hold at most {} users", MAX_STAFF_IDS)));
+    }
+    Ok(staff_ids)
+}
+
+async fn fetch_view(state: &AppState, id: i32) -> AppResult<SavedView> {
+    sqlx::query_as::<_, SavedView>(&format!("{} WHERE v.id = $1", VIEW_SELECT))
+        .bind(id)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Saved view {} not found", id)))
+}
+
+/// Someone else's view is reported as missing rather than forbidden
+async fn fetch_own_view(state: &AppState, auth: &AuthenticatedUser, id: i32) -> AppResult<SavedView> {
+    let view = fetch_view(state, id).await?;
+    if view.user_profile_id != auth.profile_id {
+        return Err(AppError::NotFound(format!("Saved view {} not found", id)));
+    }
+    Ok(view)
+}
+
+async fn insert_view(
+    state: &AppState,
+    user_profile_id: i32,
+    name: &str,
+    view_type: &str,
+    role_id: Option<i32>,
+    staff_ids: &[i32],
+) -> AppResult<SavedView> {
+    let mut tx = crate::db::begin_as_user(&state.db, user_profile_id).await?;
+
+    // Lock the owner's row so concurrent saves are counted one after the other
+    sqlx::query(r#"SELECT 1 FROM "Users" WHERE user_profile_id = $1 FOR UPDATE"#)
+        .bind(user_profile_id)
+        .execute(&mut *tx)
+        .await?;
+
+    let count: i64 = sqlx::query_scalar(r#"SELECT COUNT(*) FROM "SavedViews" WHERE user_profile_id = $1"#)
+        .bind(user_profile_id)
+        .fetch_one(&mut *tx)
+        .await?;
+    if count >= MAX_VIEWS_PER_USER {
+        return Err(AppError::BadRequest(format!(
+            "You can keep at most {} saved views; delete one first",
+            MAX_VIEWS_PER_USER
+        )));
+    }
+
+    let id: i32 = sqlx::query_scalar(
+        r#"
+        INSERT INTO "SavedViews" (user_profile_id, name, view_type, role_id, staff_ids)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING id
+        "#,
+    )
+    .bind(user_profile_id)
+    .bind(name)
+    .bind(view_type)
+    .bind(role_id)
+    .bind(staff_ids)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    fetch_view(state, id).await
+}
+
+/// GET /api/users/me/saved-views - The caller's saved views, by name
+#[utoipa::path(
+    get,
+    path = "/api/users/me/saved-views",
+    responses(
+        (status = 200, description = "Saved views", body = Vec<SavedView>)
+    ),
+    tag = "users",
+    security(("cookie_auth" = []))
+)]
+pub async fn get_saved_views(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+) -> AppResult<Json<Vec<SavedView>>> {
+    let views = sqlx::query_as::<_, SavedView>(&format!(
+        "{} WHERE v.user_profile_id = $1 ORDER BY LOWER(v.name), v.id",
+        VIEW_SELECT
+    ))
+    .bind(auth.profile_id)
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(Json(views))
+}
+
+/// POST /api/users/me/saved-views - Save a named filter set
+#[utoipa::path(
+    post,
+    path = "/api/users/me/saved-views",
+    request_body = CreateSavedViewInput,
+    responses(
+        (status = 200, description = "Saved view created", body = SavedView),
+        (status = 400, description = "Invalid name, view_type, role or staff_ids, or too many saved views"),
+        (status = 409, description = "You already have a saved view with this name")
+    ),
+    tag = "users",
+    security(("cookie_auth" = []))
+)]
+pub async fn create_saved_view(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+    Json(input): Json<CreateSavedViewInput>,
+) -> AppResult<Json<SavedView>> {
+    let name = validate_name(&input.name)?;
+    let view_type = validate_view_type(&input.view_type)?;
+    let staff_ids = normalize_staff_ids(input.staff_ids)?;
+    // roleId = 0 means all roles, as on the list endpoints
+    let role_id = input.role_id.filter(|&id| id > 0);
+
+    let view = insert_view(&state, auth.profile_id, &name, &view_type, role_id, &staff_ids).await?;
+    tracing::info!(view_id = view.id, user_profile_id = auth.profile_id, "🔖 Saved view created");
+
+    Ok(Json(view))
+}
+
+/// PUT /api/users/me/saved-views/{id} - Update one of the caller's saved views
+#[utoipa::path(
+    put,
+    path = "/api/users/me/saved-views/{id}",
+    params(
+        ("id" = i32, Path, description = "Saved view ID")
+    ),
+    request_body = UpdateSavedViewInput,
+    responses(
+        (status = 200, description = "Saved view updated", body = SavedView),
+        (status = 400, description = "Invalid name, view_type, role or staff_ids"),
+        (status = 404, description = "Saved view not found"),
+        (status = 409, description = "You already have a saved view with this name")
+    ),
+    tag = "users",
+    security(("cookie_auth" = []))
+)]
+pub async fn update_saved_view(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i32>,
+    auth: AuthenticatedUser,
+    Json(input): Json<UpdateSavedViewInput>,
+) -> AppResult<Json<SavedView>> {
+    let name = input.name.as_deref().map(validate_name).transpose()?;
+    let view_type = input.view_type.as_deref().map(validate_view_type).transpose()?;
+    let staff_ids = input.staff_ids.map(normalize_staff_ids).transpose()?;
+
+    let result = sqlx::query(
+        r#"
+        UPDATE "SavedViews" SET
+            name = COALESCE($3, name),
+            view_type = COALESCE($4, view_type),
+            role_id = NULLIF(COALESCE($5, role_id), 0),
+            staff_ids = COALESCE($6, staff_ids),
+            updated_at = NOW()
+        WHERE id = $1 AND user_profile_id = $2
+        "#,
+    )
+    .bind(id)
+    .bind(auth.profile_id)
+    .bind(&name)
+    .bind(&view_type)
+    .bind(input.role_id)
+    .bind(&staff_ids)
+    .execute(&state.db)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound(format!("Saved view {} not found", id)));
+    }
+
+    Ok(Json(fetch_view(&state, id).await?))
+}
+
+/// DELETE /api/users/me/saved-views/{id} - Delete one of the caller's saved views (its share link stops working)
+#[utoipa::path(
+    delete,
+    path = "/api/users/me/saved-views/{id}",
+    params(
+        ("id" = i32, Path, description = "Saved view ID")
+    ),
+    responses(
+        (status = 200, description = "Saved view deleted", body = SavedViewMutationResponse),
+        (status = 404, description = "Saved view not found")
+    ),
+    tag = "users",
+    security(("cookie_auth" = []))
+)]
+pub async fn delete_saved_view(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i32>,
+    auth: AuthenticatedUser,
+) -> AppResult<Json<SavedViewMutationResponse>> {
+    let result = sqlx::query(r#"DELETE FROM "SavedViews" WHERE id = $1 AND user_profile_id = $2"#)
+        .bind(id)
+        .bind(auth.profile_id)
+        .execute(&state.db)
+        .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(AppError::NotFound(format!("Saved view {} not found", id)));
+    }
+
+    Ok(Json(SavedViewMutationResponse {
+        success: true,
+        message: Some("Saved view deleted successfully".to_string()),
+    }))
+}
+
+/// POST /api/users/me/saved-views/{id}/share - Create (or return the existing) share token for a saved view
+#[utoipa::path(
+    post,
+    path = "/api/users/me/saved-views/{id}/share",
+    params(
+        ("id" = i32, Path, description = "Saved view ID")
+    ),
+    responses(
+        (status = 200, description = "Saved view with its share_token", body = SavedView),
+        (status = 404, description = "Saved view not found")
+    ),
+    tag = "users",
+    security(("cookie_auth" = []))
+)]
+pub async fn share_saved_view(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i32>,
+    auth: AuthenticatedUser,
+) -> AppResult<Json<SavedView>> {
+    fetch_own_view(&state, &auth, id).await?;
+
+    sqlx::query(r#"UPDATE "SavedViews" SET share_token = COALESCE(share_token, $2) WHERE id = $1"#)
+        .bind(id)
+        .bind(Uuid::new_v4())
+        .execute(&state.db)
+        .await?;
+
+    tracing::info!(view_id = id, user_profile_id = auth.profile_id, "🔗 Saved view shared");
+    Ok(Json(fetch_view(&state, id).await?))
+}
+
+/// DELETE /api/users/me/saved-views/{id}/share - Revoke a saved view's share token
+#[utoipa::path(
+    delete,
+    path = "/api/users/me/saved-views/{id}/share",
+    params(
+        ("id" = i32, Path, description = "Saved view ID")
+    ),
+    responses(
+        (status = 200, description = "Saved view, no longer shared", body = SavedView),
+        (status = 404, description = "Saved view not found")
+    ),
+    tag = "users",
+    security(("cookie_auth" = []))
+)]
+pub async fn unshare_saved_view(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i32>,
+    auth: AuthenticatedUser,
+) -> AppResult<Json<SavedView>> {
+    fetch_own_view(&state, &auth, id).await?;
+
+    sqlx::query(r#"UPDATE "SavedViews" SET share_token = NULL WHERE id = $1"#)
+        .bind(id)
+        .execute(&state.db)
+        .await?;
+
+    Ok(Json(fetch_view(&state, id).await?))
+}
+
+async fn fetch_shared_view(state: &AppState, token: Uuid) -> AppResult<SavedView> {
+    sqlx::query_as::<_, SavedView>(&format!("{} WHERE v.share_token = $1", VIEW_SELECT))
+        .bind(token)
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Shared view not found or no longer shared".to_string()))
+}
+
+/// GET /api/users/me/saved-views/shared/{token} - Open a view a colleague shared
+#[utoipa::path(
+    get,
+    path = "/api/users/me/saved-views/shared/{token}",
+    params(
+        ("token" = Uuid, Path, description = "Share token")
+    ),
+    responses(
+        (status = 200, description = "The shared view", body = SavedView),
+        (status = 404, description = "Shared view not found or no longer shared")
+    ),
+    tag = "users",
+    security(("cookie_auth" = []))
+)]
+pub async fn get_shared_view(
+    State(state): State<Arc<AppState>>,
+    Path(token): Path<Uuid>,
+    _auth: AuthenticatedUser,
+) -> AppResult<Json<SavedView>> {
+    Ok(Json(fetch_shared_view(&state, token).await?))
+}
+
+/// POST /api/users/me/saved-views/shared/{token}/copy - Keep a copy of a colleague's shared view
+#[utoipa::path(
+    post,
+    path = "/api/users/me/saved-views/shared/{token}/copy",
+    params(
+        ("token" = Uuid, Path, description = "Share token")
+    ),
+    request_body = CopySavedViewInput,
+    responses(
+        (status = 200, description = "The caller's copy (not shared)", body = SavedView),
+        (status = 400, description = "Invalid name, or too many saved views"),
+        (status = 404, description = "Shared view not found or no longer shared"),
+        (status = 409, description = "You already have a saved view with this name")
+    ),
+    tag = "users",
+    security(("cookie_auth" = []))
+)]
+pub async fn copy_shared_view(
+    State(state): State<Arc<AppState>>,
+    Path(token): Path<Uuid>,
+    auth: AuthenticatedUser,
+    Json(input): Json<CopySavedViewInput>,
+) -> AppResult<Json<SavedView>> {
+    let shared = fetch_shared_view(&state, token).await?;
+    let name = validate_name(input.name.as_deref().unwrap_or(&shared.name))?;
+
+    let view = insert_view(&state, auth.profile_id, &name, &shared.view_type, shared.role_id, &shared.staff_ids).await?;
+    tracing::info!(view_id = view.id, from_view_id = shared.id, user_profile_id = auth.profile_id, "🔖 Shared view copied");
+
+    Ok(Json(view))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_view_input_validation() {
+        assert_eq!(validate_name("  Nights  ").unwrap(), "Nights");
+        assert!(validate_name("   ").is_err());
+        assert!(validate_name(&"x".repeat(MAX_NAME_LENGTH + 1)).is_err());
+
+        assert_eq!(validate_view_type("week").unwrap(), "WEEK");
+        assert!(validate_view_type("YEAR").is_err());
+
+        assert_eq!(normalize_staff_ids(vec![9, 3, 9, 1]).unwrap(), vec![1, 3, 9]);
+        assert!(normalize_staff_ids((0..=MAX_STAFF_IDS as i32).collect()).is_err());
+    }
+}
@@ -0,0 +1,264 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+use crate::{
+    extractors::{permissions, AuthenticatedUser},
+    models::{DiarySearchHit, SearchResults, SearchType, ShiftSearchHit, UserSearchHit, Workplace},
+    AppError, AppResult, AppState,
+};
+
+/// Shortest query worth running; single letters match half the trust
+const MIN_QUERY_LEN: usize = 2;
+
+/// Default and maximum results per bucket
+const DEFAULT_LIMIT: i64 = 5;
+const MAX_LIMIT: i64 = 25;
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct SearchQuery {
+    /// Search text (at least 2 characters)
+    pub q: String,
+    /// Buckets to search, comma-separated: users,shifts,diary,workplaces (default all)
+    pub types: Option<String>,
+    /// Results per bucket (default 5, max 25)
+    pub limit: Option<i64>,
+}
+
+/// Parse `types=users,shifts`; empty means every bucket
+fn parse_types(types: Option<&str>) -> AppResult<Vec<SearchType>> {
+    let mut parsed = Vec::new();
+    for part in types.unwrap_or_default().split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let search_type = match part {
+            "users" => SearchType::Users,
+            "shifts" => SearchType::Shifts,
+            "diary" => SearchType::Diary,
+            "workplaces" => SearchType::Workplaces,
+            _ => return Err(AppError::BadRequest(format!("Unknown search type '{}'", part))),
+        };
+        if !parsed.contains(&search_type) {
+            parsed.push(search_type);
+        }
+    }
+    if parsed.is_empty() {
+        parsed = vec![SearchType::Users, SearchType::Shifts, SearchType::Diary, SearchType::Workplaces];
+    }
+    Ok(parsed)
+}
+
+/// ILIKE pattern matching `q` anywhere, with the caller's % and _ taken literally
+fn like_pattern(q: &str) -> String {
+    let escaped = q.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+/// Blank out emails the caller may not see: kept for themselves and for staff sharing a role they can view
+/// staff details in (`viewable` lists the users in those roles)
+fn redact_users(users: &mut [UserSearchHit], viewer_id: i32, viewable: &[i32]) {
+    for user in users.iter_mut() {
+        if user.user_profile_id != viewer_id && !viewable.contains(&user.user_profile_id) {
+            user.primary_email = None;
+        }
+    }
+}
+
+/// GET /api/search?q=&types=&limit= - Global search across users, shifts, diary entries and workplaces
+#[utoipa::path(
+    get,
+    path = "/api/search",
+    params(SearchQuery),
+    responses(
+        (status = 200, description = "Matches per type. Users and shifts come from the caller's roles (partial email matches only where they have can_edit_staff, otherwise the full address), diary entries from roles with can_access_diary; emails only with can_view_staff_details", body = SearchResults),
+        (status = 400, description = "Query too short, unknown type or invalid limit")
+    ),
+    tag = "search",
+    security(("cookie_auth" = []))
+)]
+pub async fn search(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+    Query(query): Query<SearchQuery>,
+) -> AppResult<Json<SearchResults>> {
+    let q = query.q.trim();
+    if q.chars().count() < MIN_QUERY_LEN {
+        return Err(AppError::BadRequest(format!(
+            "Search query must be at least {} characters",
+            MIN_QUERY_LEN
+        )));
+    }
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+    if !(1..=MAX_LIMIT).contains(&limit) {
+        return Err(AppError::BadRequest(format!("limit must be between 1 and {}", MAX_LIMIT)));
+    }
+    let types = parse_types(query.types.as_deref())?;
+    let pattern = like_pattern(q);
+
+    // Super admins see everything (None); everyone else is scoped to their own roles
+    let scope = |roles: Vec<i32>| (!auth.is_super_admin).then_some(roles);
+    let member_roles = scope(permissions::roles_with_permission(&state, auth.profile_id, |_| true).await?);
+
+    let mut results = SearchResults::default();
+
+    if types.contains(&SearchType::Users) {
+        // Part of an email only finds staff in roles the caller edits staff in; anyone else needs the full address,
+        // so the search cannot be used to enumerate addresses letter by letter
+        let staff_roles = scope(permissions::roles_with_permission(&state, auth.profile_id, permissions::can_edit_staff).await?);
+        results.users = sqlx::query_as::<_, UserSearchHit>(&format!(
+            r#"
+            SELECT u.user_profile_id, u.full_name, u.short_name, u.color, u.primary_email
+            FROM "Users" u
+            WHERE NOT u.is_generic_login
+              AND (
+                  u.full_name ILIKE $1
+                  OR u.short_name ILIKE $1
+                  OR LOWER(u.primary_email) = LOWER($4)
+                  OR (u.primary_email ILIKE $1 AND ($5::int4[] IS NULL OR EXISTS (
+                      SELECT 1 FROM "UserRoles" ur WHERE ur.user_profile_id = u.user_profile_id AND ur.role_id = ANY($5) AND {ur_in_force}
+                  )))
+              )
+              AND ($2::int4[] IS NULL OR EXISTS (
+                  SELECT 1 FROM "UserRoles" ur WHERE ur.user_profile_id = u.user_profile_id AND ur.role_id = ANY($2) AND {ur_in_force}
+              ))
+            ORDER BY u.short_name, u.user_profile_id
+            LIMIT $3
+            "#,
+            ur_in_force = crate::extractors::permissions::ur_in_force(6)
+        ))
+        .bind(&pattern)
+        .bind(&member_roles)
+        .bind(limit)
+        .bind(q)
+        .bind(&staff_roles)
+        .bind(crate::extractors::permissions::today())
+        .fetch_all(&state.db)
+        .await?;
+
+        if !auth.is_super_admin {
+            let detail_roles =
+                permissions::roles_with_permission(&state, auth.profile_id, permissions::can_view_staff_details).await?;
+            let found: Vec<i32> = results.users.iter().map(|u| u.user_profile_id).collect();
+            let viewable: Vec<i32> = sqlx::query_scalar(&format!(
+                r#"SELECT DISTINCT user_profile_id FROM "UserRoles" WHERE role_id = ANY($1) AND user_profile_id = ANY($2) AND {in_force}"#,
+                in_force = crate::extractors::permissions::user_role_in_force(3)
+            ))
+            .bind(&detail_roles)
+            .bind(&found)
+            .bind(crate::extractors::permissions::today())
+            .fetch_all(&state.db)
+            .await?;
+            redact_users(&mut results.users, auth.profile_id, &viewable);
+        }
+    }
+
+    if types.contains(&SearchType::Shifts) {
+        let edit_roles = scope(permissions::roles_with_permission(&state, auth.profile_id, |r| r.can_edit_rota).await?);
+        results.shifts = sqlx::query_as::<_, ShiftSearchHit>(
+            r#"
+            SELECT s.uuid, s.role_id, r.role_name, s.date, s.label, s.user_profile_id, u.short_name AS user_short_name
+            FROM "Shifts" s
+            INNER JOIN "Roles" r ON r.id = s.role_id
+            LEFT JOIN "Users" u ON u.user_profile_id = s.user_profile_id
+            WHERE s.label ILIKE $1
+              AND ($2::int4[] IS NULL OR s.role_id = ANY($2))
+              AND ($3::int4[] IS NULL OR s.published OR s.role_id = ANY($3))
+            ORDER BY (s.date < CURRENT_DATE), abs(s.date - CURRENT_DATE), s.start
+            LIMIT $4
+            "#,
+        )
+        .bind(&pattern)
+        .bind(&member_roles)
+        .bind(&edit_roles)
+        .bind(limit)
+        .fetch_all(&state.db)
+        .await?;
+    }
+
+    if types.contains(&SearchType::Diary) {
+        let diary_roles = scope(permissions::roles_with_permission(&state, auth.profile_id, |r| r.can_access_diary).await?);
+        results.diary = sqlx::query_as::<_, DiarySearchHit>(
+            r#"
+            SELECT d.id::int4, d.role_id, r.role_name, d.date, d.entry, u.short_name AS user_short_name
+            FROM "Diary" d
+            INNER JOIN "Roles" r ON r.id = d.role_id
+            LEFT JOIN "Users" u ON u.user_profile_id = d.user_profile_id
+            WHERE NOT d.deleted
+              AND d.entry ILIKE $1
+              AND ($2::int4[] IS NULL OR d.role_id = ANY($2) OR (d.entry_type = 'ANNOUNCEMENT' AND d.role_id = ANY($4)))
+            ORDER BY d.date DESC, d.id DESC
+            LIMIT $3
+            "#,
+        )
+        .bind(&pattern)
+        .bind(&diary_roles)
+        .bind(limit)
+        .bind(&member_roles)
+        .fetch_all(&state.db)
+        .await?;
+    }
+
+    if types.contains(&SearchType::Workplaces) {
+        // Workplaces are reference data, listed for everyone by GET /api/workplaces
+        results.workplaces = sqlx::query_as::<_, Workplace>(
+            r#"
+            SELECT id::int4, hospital, ward, address, code
+            FROM "Workplaces"
+            WHERE hospital ILIKE $1 OR ward ILIKE $1 OR code ILIKE $1
+            ORDER BY hospital, ward, id
+            LIMIT $2
+            "#,
+        )
+        .bind(&pattern)
+        .bind(limit)
+        .fetch_all(&state.db)
+        .await?;
+    }
+
+    tracing::info!(
+        user_id = auth.profile_id,
+        users = results.users.len(),
+        shifts = results.shifts.len(),
+        diary = results.diary.len(),
+        workplaces = results.workplaces.len(),
+        "🔍 Global search completed"
+    );
+
+    Ok(Json(results))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_types_and_pattern() {
+        assert_eq!(parse_types(None).unwrap().len(), 4);
+        assert_eq!(
+            parse_types(Some("diary, users,diary")).unwrap(),
+            vec![SearchType::Diary, SearchType::Users]
+        );
+        assert!(parse_types(Some("users,rotas")).is_err());
+
+        assert_eq!(like_pattern("night"), "%night%");
+        assert_eq!(like_pattern("100%_a\\b"), "%100\\%\\_a\\\\b%");
+    }
+
+    #[test]
+    fn test_redact_users() {
+        let hit = |id: i32| UserSearchHit {
+            user_profile_id: id,
+            full_name: format!("User {}", id),
+            short_name: format!("U{}", id),
+            color: None,
+            primary_email: Some(format!("u{}@example.org", id)),
+        };
+        let mut users = vec![hit(1), hit(2), hit(3)];
+        redact_users(&mut users, 1, &[3]);
+        assert!(users[0].primary_email.is_some()); // the caller
+        assert!(users[1].primary_email.is_none());
+        assert!(users[2].primary_email.is_some()); // shares a role with can_view_staff_details
+    }
+}
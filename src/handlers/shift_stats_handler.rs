@@ -0,0 +1,179 @@
+use axum::extract::{Query, State};
+use chrono::NaiveDate;
+use serde::Deserialize;
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+use crate::{
+    extractors::{permissions, AuthenticatedUser, Json},
+    handlers::{mfa_handler::require_super_admin, roles_handler::month_range},
+    jobs::shift_stats::{load_freshness, refresh_shift_stats},
+    models::{RoleMonthStats, RoleMonthStatsReport, StatsViewRefresh, UserMonthStats, UserMonthStatsReport},
+    report::{Report, ReportFormat},
+    AppError, AppResult, AppState,
+};
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ShiftStatsQuery {
+    #[serde(rename = "roleId")]
+    pub role_id: Option<i32>,
+    /// Only this user (users report)
+    #[serde(rename = "userId")]
+    pub user_id: Option<i32>,
+    pub year: i32,
+    /// 1-12; the whole year when omitted
+    pub month: Option<i32>,
+}
+
+impl ShiftStatsQuery {
+    /// First and last day covered, to compare against the views' first-of-month `month`
+    fn range(&self) -> AppResult<(NaiveDate, NaiveDate)> {
+        match self.month {
+            Some(month) => month_range(self.year, month),
+            None => Ok((month_range(self.year, 1)?.0, month_range(self.year, 12)?.1)),
+        }
+    }
+}
+
+/// Roles the caller may see figures for: None (all) for super admins, else their can_edit_rota roles or one of them
+async fn stats_roles(state: &AppState, auth: &AuthenticatedUser, role_id: Option<i32>) -> AppResult<Option<Vec<i32>>> {
+    if auth.is_super_admin {
+        return Ok(role_id.map(|role_id| vec![role_id]));
+    }
+    let admin_roles = permissions::roles_with_permission(state, auth.profile_id, permissions::can_edit_rota).await?;
+    let roles = match role_id {
+        Some(role_id) if admin_roles.contains(&role_id) => vec![role_id],
+        Some(_) => return Err(AppError::Forbidden("Missing can_edit_rota permission for this role".to_string())),
+        None => admin_roles,
+    };
+    if roles.is_empty() {
+        return Err(AppError::Forbidden("Missing can_edit_rota permission".to_string()));
+    }
+    Ok(Some(roles))
+}
+
+/// GET /api/shift-stats/users?year=&month=&roleId=&userId= - PA totals per user and month, precomputed
+#[utoipa::path(
+    get,
+    path = "/api/shift-stats/users",
+    params(ShiftStatsQuery),
+    responses(
+        (status = 200, description = "PA totals per user, role and month, with when the figures were last refreshed", content(
+            (UserMonthStatsReport = "application/json"),
+            (String = "text/csv"),
+            (Vec<u8> = "application/pdf")
+        )),
+        (status = 400, description = "Invalid year or month"),
+        (status = 403, description = "Missing can_edit_rota permission (for this role)"),
+        (status = 406, description = "Accept names no supported format")
+    ),
+    tag = "shift-stats",
+    security(("cookie_auth" = []))
+)]
+pub async fn get_user_month_stats(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+    format: ReportFormat,
+    Query(query): Query<ShiftStatsQuery>,
+) -> AppResult<Report<UserMonthStatsReport>> {
+    let (from, to) = query.range()?;
+    let roles = stats_roles(&state, &auth, query.role_id).await?;
+
+    let rows = sqlx::query_as::<_, UserMonthStats>(
+        r#"
+        SELECT
+            v.user_profile_id, u.short_name, v.role_id, v.month, v.shifts, v.locum_shifts,
+            v.total_pa, v.dcc_pa, v.spa_pa, v.time_off_entries
+        FROM "ShiftStatsUserMonth" v
+        LEFT JOIN "Users" u ON u.user_profile_id = v.user_profile_id
+        WHERE v.month BETWEEN $1 AND $2
+          AND ($3::int4[] IS NULL OR v.role_id = ANY($3))
+          AND ($4::int4 IS NULL OR v.user_profile_id = $4)
+        ORDER BY v.month, v.role_id, u.short_name, v.user_profile_id
+        "#,
+    )
+    .bind(from)
+    .bind(to)
+    .bind(roles)
+    .bind(query.user_id)
+    .fetch_all(&state.db)
+    .await?;
+    let freshness = load_freshness(&state.db, "ShiftStatsUserMonth").await?;
+
+    let title = format!("User PA totals {}", query.year);
+    Ok(Report::new(format, title, UserMonthStatsReport { freshness, rows }))
+}
+
+/// GET /api/shift-stats/roles?year=&month=&roleId= - Fill rates per role and month, precomputed
+#[utoipa::path(
+    get,
+    path = "/api/shift-stats/roles",
+    params(ShiftStatsQuery),
+    responses(
+        (status = 200, description = "Filled and unfilled shifts per role and month, with when the figures were last refreshed", content(
+            (RoleMonthStatsReport = "application/json"),
+            (String = "text/csv"),
+            (Vec<u8> = "application/pdf")
+        )),
+        (status = 400, description = "Invalid year or month"),
+        (status = 403, description = "Missing can_edit_rota permission (for this role)"),
+        (status = 406, description = "Accept names no supported format")
+    ),
+    tag = "shift-stats",
+    security(("cookie_auth" = []))
+)]
+pub async fn get_role_month_stats(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+    format: ReportFormat,
+    Query(query): Query<ShiftStatsQuery>,
+) -> AppResult<Report<RoleMonthStatsReport>> {
+    let (from, to) = query.range()?;
+    let roles = stats_roles(&state, &auth, query.role_id).await?;
+
+    let rows = sqlx::query_as::<_, RoleMonthStats>(
+        r#"
+        SELECT
+            v.role_id, r.role_name, v.month, v.shift_count, v.filled, v.unfilled,
+            COALESCE(v.filled::float8 / NULLIF(v.shift_count, 0), 0) AS fill_rate,
+            v.locum_shifts, v.total_pa, v.distinct_staff, v.time_off_entries
+        FROM "ShiftStatsRoleMonth" v
+        LEFT JOIN "Roles" r ON r.id = v.role_id
+        WHERE v.month BETWEEN $1 AND $2
+          AND ($3::int4[] IS NULL OR v.role_id = ANY($3))
+        ORDER BY v.month, r.role_name, v.role_id
+        "#,
+    )
+    .bind(from)
+    .bind(to)
+    .bind(roles)
+    .fetch_all(&state.db)
+    .await?;
+    let freshness = load_freshness(&state.db, "ShiftStatsRoleMonth").await?;
+
+    let title = format!("Role fill rates {}", query.year);
+    Ok(Report::new(format, title, RoleMonthStatsReport { freshness, rows }))
+}
+
+/// POST /api/shift-stats/refresh - Refresh the statistics now instead of waiting for the job
+#[utoipa::path(
+    post,
+    path = "/api/shift-stats/refresh",
+    responses(
+        (status = 200, description = "Views refreshed, with how long each took", body = Vec<StatsViewRefresh>),
+        (status = 403, description = "Super admin permission required")
+    ),
+    tag = "shift-stats",
+    security(("cookie_auth" = []))
+)]
+pub async fn refresh_stats(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+) -> AppResult<Json<Vec<StatsViewRefresh>>> {
+    require_super_admin(&auth)?;
+
+    let refreshed = refresh_shift_stats(&state.db).await?;
+    tracing::info!(admin_id = auth.profile_id, views = refreshed.len(), "📊 Shift statistics refreshed on demand");
+
+    Ok(Json(refreshed))
+}
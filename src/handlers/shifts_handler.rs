@@ -1,7 +1,8 @@
 use axum::{
     extract::{Path, Query, State},
-    Json,
+    response::Response,
 };
+use axum_extra::extract::Query as MultiQuery;
 use chrono::NaiveDate;
 use serde::Deserialize;
 use std::sync::Arc;
@@ -9,17 +10,476 @@ use utoipa::IntoParams;
 use uuid::Uuid;
 
 use crate::{
-    extractors::AuthenticatedUser,
-    models::{CreateShiftInput, Shift, ShiftMutationResponse, UpdateShiftInput},
+    db::fieldset::{FieldSet, SHIFT_FIELDS},
+    extractors::{permissions, AuthenticatedUser, CanEditRota, Json, RequirePermission},
+    models::{
+        shift::{
+            CROSSES_MIDNIGHT_SQL, DURATION_MINUTES_SQL, MARKETPLACE_COLUMNS_SQL, MARKETPLACE_FIELDS,
+            MARKETPLACE_LATERAL_SQL,
+        },
+        CreateShiftInput, CreateShiftSeriesInput, DuplicateShift, DuplicateShiftPolicy, Shift, ShiftFieldChange,
+        ShiftMutationResponse, ShiftSeriesResponse, UpdateShiftInput,
+    },
     AppError, AppResult, AppState,
 };
 
+/// Most roles one request may ask for
+const MAX_ROLES_PER_REQUEST: usize = 20;
+
+/// Role IDs from `roleId=1,2` and/or repeated `roleId=1&roleId=2`, de-duplicated in request order
+pub fn parse_role_ids(values: &[String]) -> AppResult<Vec<i32>> {
+    let mut role_ids = Vec::new();
+    for part in values.iter().flat_map(|v| v.split(',')).map(str::trim).filter(|p| !p.is_empty()) {
+        let role_id: i32 = part
+            .parse()
+            .map_err(|_| AppError::BadRequest(format!("Invalid roleId '{}'", part)))?;
+        if !role_ids.contains(&role_id) {
+            role_ids.push(role_id);
+        }
+    }
+
+    if role_ids.len() > MAX_ROLES_PER_REQUEST {
+        return Err(AppError::BadRequest(format!(
+            "At most {} roles can be requested at once",
+            MAX_ROLES_PER_REQUEST
+        )));
+    }
+
+    Ok(role_ids)
+}
+
+/// Roles the caller holds (every role for super admins), with their names in name order
+pub async fn roles_in_scope(state: &AppState, auth: &AuthenticatedUser) -> AppResult<Vec<(i32, String)>> {
+    let roles = sqlx::query_as(&format!(
+        r#"
+        SELECT r.id::int4, r.role_name
+        FROM "Roles" r
+        WHERE $1 OR r.id IN (SELECT role_id FROM "UserRoles" WHERE user_profile_id = $2 AND {in_force})
+        ORDER BY r.role_name
+        "#,
+        in_force = crate::extractors::permissions::user_role_in_force(3)
+    ))
+    .bind(auth.is_super_admin)
+    .bind(auth.profile_id)
+    .bind(crate::extractors::permissions::today())
+    .fetch_all(&state.db)
+    .await?;
+
+    Ok(roles)
+}
+
+/// Fail with 403 naming the requested roles that are not in `in_scope`
+pub fn check_role_scope(requested: &[i32], in_scope: &[(i32, String)]) -> AppResult<()> {
+    let out_of_scope: Vec<i32> = requested
+        .iter()
+        .copied()
+        .filter(|id| !in_scope.iter().any(|(scope_id, _)| scope_id == id))
+        .collect();
+    if !out_of_scope.is_empty() {
+        return Err(AppError::Forbidden(format!("Not a member of roles {:?}", out_of_scope)));
+    }
+    Ok(())
+}
+
+/// Existing shift with the same role, date, label, start, end and assignee (times as HH:MM or HH:MM:SS)
+pub async fn find_duplicate_shift<'e, E>(
+    db: E,
+    role_id: i32,
+    date: NaiveDate,
+    label: &str,
+    start: Option<&str>,
+    end: Option<&str>,
+    user_profile_id: Option<i32>,
+) -> Result<Option<DuplicateShift>, sqlx::Error>
+where
+    E: sqlx::PgExecutor<'e>,
+{
+    sqlx::query_as::<_, DuplicateShift>(
+        r#"
+        SELECT date, label, uuid AS existing_uuid
+        FROM "Shifts"
+        WHERE role_id = $1 AND date = $2 AND label = $3
+          AND start IS NOT DISTINCT FROM $4::time
+          AND "end" IS NOT DISTINCT FROM $5::time
+          AND user_profile_id IS NOT DISTINCT FROM $6
+        ORDER BY created_at
+        LIMIT 1
+        "#,
+    )
+    .bind(role_id)
+    .bind(date)
+    .bind(label)
+    .bind(start)
+    .bind(end)
+    .bind(user_profile_id)
+    .fetch_optional(db)
+    .await
+}
+
+/// Serialise duplicate checks on a role's days until the transaction ends, so of two requests creating the same
+/// shift the second sees the first's instead of both finding none. Call before `find_duplicate_shift`.
+pub async fn lock_duplicate_checks(
+    conn: &mut sqlx::PgConnection,
+    role_id: i32,
+    dates: &[NaiveDate],
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        SELECT pg_advisory_xact_lock(hashtextextended('shift-duplicates:' || $1::text || ':' || k.date::text, 0))
+        FROM (SELECT DISTINCT unnest($2::date[]) AS date) k
+        ORDER BY k.date
+        "#,
+    )
+    .bind(role_id)
+    .bind(dates)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// The request's policy, or the workplace's when the request does not say
+pub async fn duplicate_policy(
+    db: &sqlx::PgPool,
+    role_id: i32,
+    requested: Option<DuplicateShiftPolicy>,
+) -> AppResult<DuplicateShiftPolicy> {
+    match requested {
+        Some(policy) => Ok(policy),
+        None => Ok(crate::handlers::workplaces_handler::load_role_settings(db, role_id)
+            .await?
+            .duplicate_shift_policy()),
+    }
+}
+
+/// A dry run lists what REJECT would refuse instead of failing, so it resolves duplicates like SKIP
+pub fn preview_policy(policy: DuplicateShiftPolicy, dry_run: bool) -> DuplicateShiftPolicy {
+    if dry_run && policy == DuplicateShiftPolicy::Reject { DuplicateShiftPolicy::Skip } else { policy }
+}
+
+/// Shifts by uuid in date order, read inside `tx` so a dry run can return rows it is about to roll back
+pub async fn shifts_by_uuid(tx: &mut sqlx::Transaction<'_, sqlx::Postgres>, uuids: &[Uuid]) -> AppResult<Vec<Shift>> {
+    let shifts = sqlx::query_as::<_, Shift>(&format!(
+        r#"SELECT {} FROM "Shifts" WHERE uuid = ANY($1) ORDER BY date, start, label"#,
+        FieldSet::parse(None, SHIFT_FIELDS)?.select_list(&[])
+    ))
+    .bind(uuids)
+    .fetch_all(&mut **tx)
+    .await?;
+    Ok(shifts)
+}
+
+/// A shift time as HH:MM:SS, from HH:MM or HH:MM:SS
+fn with_seconds(time: &str) -> String {
+    if time.len() == 5 { format!("{}:00", time) } else { time.to_string() }
+}
+
+/// Insert `input` as a new shift on `date` (times as HH:MM:SS) and return it
+async fn insert_shift(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    input: &CreateShiftInput,
+    date: NaiveDate,
+    start_time: Option<&str>,
+    end_time: Option<&str>,
+    created_by: i32,
+    series_id: Option<Uuid>,
+) -> Result<Shift, sqlx::Error> {
+    sqlx::query_as::<_, Shift>(&format!(
+        r#"
+        INSERT INTO "Shifts" (
+            uuid, role_id, label, start, "end", money_per_hour,
+            pa_value, font_color, bk_color, is_locum, published,
+            date, is_dcc, is_spa, time_off_category_id,
+            user_profile_id, created_by, series_id
+        )
+        VALUES ($1, $2, $3, $4::time, $5::time, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18)
+        RETURNING
+            uuid,
+            role_id AS role,
+            label,
+            to_char(start, 'HH24:MI:SS') AS start,
+            to_char("end", 'HH24:MI:SS') AS "end",
+            money_per_hour,
+            pa_value,
+            font_color,
+            bk_color,
+            is_locum,
+            published,
+            date,
+            created_at,
+            is_dcc,
+            is_spa,
+            time_off_category_id AS time_off,
+            user_profile_id,
+            created_by,
+            {},
+            {},
+            series_id
+        "#,
+        DURATION_MINUTES_SQL, CROSSES_MIDNIGHT_SQL
+    ))
+    .bind(Uuid::new_v4())
+    .bind(input.role)
+    .bind(&input.label)
+    .bind(start_time)
+    .bind(end_time)
+    .bind(input.money_per_hour)
+    .bind(input.pa_value)
+    .bind(&input.font_color)
+    .bind(&input.bk_color)
+    .bind(input.is_locum)
+    .bind(input.published)
+    .bind(date)
+    .bind(input.is_dcc)
+    .bind(input.is_spa)
+    .bind(input.time_off)
+    .bind(input.user_profile_id)
+    .bind(created_by)
+    .bind(series_id)
+    .fetch_one(&mut **tx)
+    .await
+}
+
+/// Longest reason kept with a shift change
+const MAX_CHANGE_REASON_LENGTH: usize = 500;
+
+/// The trimmed reason, None when blank
+fn normalize_reason(reason: Option<&str>) -> AppResult<Option<String>> {
+    let reason = reason.map(str::trim).filter(|r| !r.is_empty());
+    if reason.is_some_and(|r| r.chars().count() > MAX_CHANGE_REASON_LENGTH) {
+        return Err(AppError::BadRequest(format!(
+            "reason must be at most {} characters",
+            MAX_CHANGE_REASON_LENGTH
+        )));
+    }
+    Ok(reason.map(str::to_string))
+}
+
+/// Reason for editing or deleting shift `uuid`, required when the shift is published and its role has
+/// edit_reason_required. A missing shift passes so the caller's 404 applies.
+async fn change_reason(db: &sqlx::PgPool, uuid: Uuid, reason: Option<&str>) -> AppResult<Option<String>> {
+    let reason = normalize_reason(reason)?;
+    if reason.is_none() {
+        let required: Option<bool> = sqlx::query_scalar(
+            r#"
+            SELECT s.published AND r.edit_reason_required
+            FROM "Shifts" s
+            INNER JOIN "Roles" r ON r.id = s.role_id
+            WHERE s.uuid = $1
+            "#,
+        )
+        .bind(uuid)
+        .fetch_optional(db)
+        .await?;
+
+        if required == Some(true) {
+            return Err(AppError::BadRequest(
+                "A reason is required to change a published shift in this role".to_string(),
+            ));
+        }
+    }
+    Ok(reason)
+}
+
+/// The hourly rate a shift pays as a locum shift; None when it is not one or has no rate
+fn locum_rate(is_locum: bool, money_per_hour: Option<f32>) -> Option<f32> {
+    money_per_hour.filter(|_| is_locum)
+}
+
+/// Setting, changing or dropping a locum rate (including by toggling is_locum) needs can_set_pay in the role
+async fn ensure_can_set_pay(state: &AppState, auth: &AuthenticatedUser, role_id: i32) -> AppResult<()> {
+    if !permissions::has_permission(state, auth.profile_id, auth.is_super_admin, |r| r.role_id == role_id && r.can_set_pay)
+        .await?
+    {
+        return Err(AppError::Forbidden("Missing can_set_pay permission to change a locum rate in this role".to_string()));
+    }
+    Ok(())
+}
+
+/// Record an approved rate change on a locum shift in "ShiftPayAudit"
+async fn record_pay_change(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    shift: &Shift,
+    old_money_per_hour: Option<f32>,
+    approved_by: i32,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO "ShiftPayAudit" (shift_uuid, role_id, old_money_per_hour, new_money_per_hour, approved_by)
+        VALUES ($1, $2, $3, $4, $5)
+        "#,
+    )
+    .bind(shift.uuid)
+    .bind(shift.role)
+    .bind(old_money_per_hour)
+    .bind(shift.money_per_hour)
+    .bind(approved_by)
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+/// HH:MM of a shift time as stored (HH:MM:SS)
+fn short_time(time: &Option<String>) -> Option<String> {
+    time.as_deref().map(|t| t.get(..5).unwrap_or(t).to_string())
+}
+
+/// Fields staff care about that differ between two versions of a shift
+pub fn shift_changes(before: &Shift, after: &Shift) -> Vec<ShiftFieldChange> {
+    [
+        ("date", Some(before.date.to_string()), Some(after.date.to_string())),
+        ("start", short_time(&before.start), short_time(&after.start)),
+        ("end", short_time(&before.end), short_time(&after.end)),
+        ("label", Some(before.label.clone()), Some(after.label.clone())),
+        ("role", Some(before.role.to_string()), Some(after.role.to_string())),
+        ("user_profile_id", before.user_profile_id.map(|id| id.to_string()), after.user_profile_id.map(|id| id.to_string())),
+    ]
+    .into_iter()
+    .filter(|(_, old, new)| old != new)
+    .map(|(field, old, new)| ShiftFieldChange { field: field.to_string(), old, new })
+    .collect()
+}
+
+/// A notification owed to someone whose published shift was changed
+#[derive(Debug)]
+struct ShiftNotice {
+    user_profile_id: i32,
+    kind: &'static str,
+    message: String,
+    payload: serde_json::Value,
+}
+
+/// Who to tell about an admin's change to a shift (`after` is None once deleted). Only published shifts count,
+/// so drafts and publishing itself stay quiet, and the admin who made the change is never told.
+fn shift_change_notices(before: &Shift, after: Option<&Shift>, changed_by: i32) -> Vec<ShiftNotice> {
+    let holder = |shift: &Shift| shift.user_profile_id.filter(|_| shift.published);
+    let (old_holder, new_holder) = (holder(before), after.and_then(holder));
+    let changes = after.map(|after| shift_changes(before, after)).unwrap_or_default();
+    let payload = serde_json::json!({
+        "shift_uuid": before.uuid,
+        "role_id": before.role,
+        "date": before.date,
+        "changes": changes,
+    });
+    let shift = format!("{} shift on {}", before.label, before.date);
+
+    let mut notices = Vec::new();
+    let mut notice = |user_profile_id: i32, kind: &'static str, message: String| {
+        if user_profile_id != changed_by {
+            notices.push(ShiftNotice { user_profile_id, kind, message, payload: payload.clone() });
+        }
+    };
+    match (old_holder, new_holder) {
+        (Some(old), Some(new)) if old == new => {
+            let summary: Vec<String> = changes
+                .iter()
+                .map(|c| format!("{} {} → {}", c.field, c.old.as_deref().unwrap_or("none"), c.new.as_deref().unwrap_or("none")))
+                .collect();
+            if !summary.is_empty() {
+                notice(old, "SHIFT_CHANGED", format!("Your {} changed: {}", shift, summary.join(", ")));
+            }
+        }
+        (old, new) => {
+            if let Some(old) = old {
+                match after {
+                    Some(after) if after.user_profile_id != Some(old) => {
+                        notice(old, "SHIFT_UNASSIGNED", format!("You were taken off the {}", shift))
+                    }
+                    _ => notice(old, "SHIFT_REMOVED", format!("Your {} was removed from the rota", shift)),
+                }
+            }
+            if let (Some(new), Some(after)) = (new, after) {
+                if before.published {
+                    let start = short_time(&after.start).map(|t| format!(" from {}", t)).unwrap_or_default();
+                    notice(new, "SHIFT_ASSIGNED", format!("You were put on the {} shift on {}{}", after.label, after.date, start));
+                }
+            }
+        }
+    }
+    notices
+}
+
+/// Record the notices for a shift change in the same transaction as the change; returns who was told what
+async fn notify_shift_change(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    before: &Shift,
+    after: Option<&Shift>,
+    changed_by: i32,
+) -> AppResult<Vec<(i32, &'static str)>> {
+    let mut told = Vec::new();
+    for notice in shift_change_notices(before, after, changed_by) {
+        told.push((notice.user_profile_id, notice.kind));
+        crate::handlers::notifications_handler::notify(
+            &mut **tx,
+            notice.user_profile_id,
+            notice.kind,
+            &notice.message,
+            notice.payload,
+        )
+        .await?;
+    }
+    Ok(told)
+}
+
+/// Record the notices for shifts changed in `tx` by a bulk path (auto-fill, locum booking, drift fixes), comparing
+/// `before` (read ahead of the change) with the rows as they are now. Returns who was told about what.
+pub(crate) async fn notify_shift_changes(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    before: &[Shift],
+    changed_by: i32,
+) -> AppResult<Vec<(i32, &'static str)>> {
+    let uuids: Vec<Uuid> = before.iter().map(|s| s.uuid).collect();
+    let after = shifts_by_uuid(tx, &uuids).await?;
+    let mut told = Vec::new();
+    for shift in before {
+        let after = after.iter().find(|a| a.uuid == shift.uuid);
+        told.extend(notify_shift_change(tx, shift, after, changed_by).await?);
+    }
+    Ok(told)
+}
+
+/// Whether `?include=` asks for marketplace state; the only expansion shift lists have
+fn include_marketplace(raw: Option<&str>) -> AppResult<bool> {
+    let mut marketplace = false;
+    for name in raw.unwrap_or_default().split(',').map(str::trim).filter(|n| !n.is_empty()) {
+        match name {
+            "marketplace" => marketplace = true,
+            other => return Err(AppError::BadRequest(format!("Unknown include '{}'", other))),
+        }
+    }
+    Ok(marketplace)
+}
+
+/// SELECT list and FROM clause for a shift list, with the marketplace lateral join when included
+fn shift_list_source(fields: &FieldSet, always: &[&str], marketplace: bool) -> String {
+    if marketplace {
+        format!(r#"{}, {} FROM "Shifts"{}"#, fields.select_list(always), MARKETPLACE_COLUMNS_SQL, MARKETPLACE_LATERAL_SQL)
+    } else {
+        format!(r#"{} FROM "Shifts""#, fields.select_list(always))
+    }
+}
+
+/// Longest block one POST /api/shifts/series may create
+const MAX_SERIES_DAYS: u32 = 31;
+
+/// The consecutive dates of a `days`-long series starting on `start`
+pub fn series_dates(start: NaiveDate, days: u32) -> AppResult<Vec<NaiveDate>> {
+    if !(2..=MAX_SERIES_DAYS).contains(&days) {
+        return Err(AppError::BadRequest(format!("days must be between 2 and {}", MAX_SERIES_DAYS)));
+    }
+    Ok(start.iter_days().take(days as usize).collect())
+}
+
 #[derive(Debug, Deserialize, IntoParams)]
 pub struct GetShiftsQuery {
     pub year: Option<i32>,
     pub month: Option<i32>,
-    #[serde(rename = "roleId")]
-    pub role_id: Option<i32>,
+    /// One role, a comma-separated list, or repeated roleId params
+    #[serde(default, rename = "roleId")]
+    pub role_id: Vec<String>,
+    /// Comma-separated Shift fields to return (e.g. uuid,date,user_profile_id); all when omitted
+    pub fields: Option<String>,
+    /// `marketplace` adds marketplace_request_id/_type/_status for shifts with an unresolved request
+    pub include: Option<String>,
 }
 
 #[derive(Debug, Deserialize, IntoParams)]
@@ -27,6 +487,10 @@ pub struct GetShiftsByDateQuery {
     pub date: String,
     #[serde(rename = "roleId")]
     pub role_id: Option<i32>,
+    /// Comma-separated Shift fields to return (e.g. uuid,date,user_profile_id); all when omitted
+    pub fields: Option<String>,
+    /// `marketplace` adds marketplace_request_id/_type/_status for shifts with an unresolved request
+    pub include: Option<String>,
 }
 
 #[derive(Debug, Deserialize, IntoParams)]
@@ -35,6 +499,16 @@ pub struct GetShiftsRangeQuery {
     pub end: String,
     #[serde(rename = "roleId")]
     pub role_id: Option<i32>,
+    /// Comma-separated Shift fields to return (e.g. uuid,date,user_profile_id); all when omitted
+    pub fields: Option<String>,
+    /// `marketplace` adds marketplace_request_id/_type/_status for shifts with an unresolved request
+    pub include: Option<String>,
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct DeleteShiftQuery {
+    /// Why the shift is being deleted, kept in its audit history (required for published shifts in roles with edit_reason_required)
+    pub reason: Option<String>,
 }
 
 /// GET /api/shifts?year=&month=&roleId=
@@ -43,41 +517,28 @@ pub struct GetShiftsRangeQuery {
     path = "/api/shifts",
     params(GetShiftsQuery),
     responses(
-        (status = 200, description = "List of shifts for specified month/year and optional role filter", body = Vec<Shift>)
+        (status = 200, description = "List of shifts for specified month/year and optional role filter", body = Vec<Shift>),
+        (status = 400, description = "Invalid roleId list or unknown field"),
+        (status = 403, description = "A requested role is not one of the caller's roles")
     ),
     tag = "shifts"
 )]
 pub async fn get_shifts_for_month(
     State(state): State<Arc<AppState>>,
-    Query(query): Query<GetShiftsQuery>,
-) -> AppResult<Json<Vec<Shift>>> {
+    auth: AuthenticatedUser,
+    MultiQuery(query): MultiQuery<GetShiftsQuery>,
+) -> AppResult<Response> {
     tracing::debug!("get_shifts_for_month called with year={:?}, month={:?}, role_id={:?}",
         query.year, query.month, query.role_id);
 
-    let mut sql = r#"
-        SELECT
-            uuid,
-            role_id AS role,
-            label,
-            to_char(start, 'HH24:MI:SS') AS start,
-            to_char("end", 'HH24:MI:SS') AS "end",
-            money_per_hour,
-            pa_value,
-            font_color,
-            bk_color,
-            is_locum,
-            published,
-            date,
-            created_at,
-            is_dcc,
-            is_spa,
-            time_off_category_id AS time_off,
-            user_profile_id,
-            created_by
-        FROM "Shifts"
-        WHERE 1=1
-    "#
-    .to_string();
+    let role_ids = parse_role_ids(&query.role_id)?;
+    if !role_ids.is_empty() {
+        check_role_scope(&role_ids, &roles_in_scope(&state, &auth).await?)?;
+    }
+
+    let marketplace = include_marketplace(query.include.as_deref())?;
+    let fields = FieldSet::parse(query.fields.as_deref(), SHIFT_FIELDS)?.keep(MARKETPLACE_FIELDS);
+    let mut sql = format!("SELECT {} WHERE 1=1", shift_list_source(&fields, &["date", "start"], marketplace));
 
     let mut bindings = vec![];
 
@@ -90,21 +551,24 @@ pub async fn get_shifts_for_month(
         }
     }
 
-    if let Some(role_id) = query.role_id {
-        sql.push_str(&format!(" AND role_id = ${}", bindings.len() + 1));
-        bindings.push(role_id);
+    if !role_ids.is_empty() {
+        sql.push_str(&format!(" AND role_id = ANY(${})", bindings.len() + 1));
     }
 
     sql.push_str(" ORDER BY date, start");
 
-    let mut query_builder = sqlx::query_as::<_, Shift>(&sql);
+    let sql = crate::db::tag_sql(&sql);
+    let mut query_builder = sqlx::query_as::<_, Shift>(&sql).persistent(false);
     for binding in bindings {
         query_builder = query_builder.bind(binding);
     }
+    if !role_ids.is_empty() {
+        query_builder = query_builder.bind(role_ids);
+    }
 
     let shifts = query_builder.fetch_all(&state.db).await?;
 
-    Ok(Json(shifts))
+    fields.respond(shifts)
 }
 
 /// GET /api/shifts/by-date?date=&roleId=
@@ -121,34 +585,16 @@ pub async fn get_shifts_for_month(
 pub async fn get_shifts_for_date(
     State(state): State<Arc<AppState>>,
     Query(query): Query<GetShiftsByDateQuery>,
-) -> AppResult<Json<Vec<Shift>>> {
+) -> AppResult<Response> {
     let date = NaiveDate::parse_from_str(&query.date, "%Y-%m-%d")
         .map_err(|e| crate::AppError::BadRequest(format!("Invalid date format: {}", e)))?;
 
-    let mut sql = r#"
-        SELECT
-            uuid,
-            role_id AS role,
-            label,
-            to_char(start, 'HH24:MI:SS') AS start,
-            to_char("end", 'HH24:MI:SS') AS "end",
-            money_per_hour,
-            pa_value,
-            font_color,
-            bk_color,
-            is_locum,
-            published,
-            date,
-            created_at,
-            is_dcc,
-            is_spa,
-            time_off_category_id AS time_off,
-            user_profile_id,
-            created_by
-        FROM "Shifts"
-        WHERE date = $1
-    "#
-    .to_string();
+    let marketplace = include_marketplace(query.include.as_deref())?;
+    let fields = FieldSet::parse(query.fields.as_deref(), SHIFT_FIELDS)?.keep(MARKETPLACE_FIELDS);
+    let mut sql = format!(
+        "SELECT {} WHERE date = $1",
+        shift_list_source(&fields, &["role", "start", "label"], marketplace)
+    );
 
     if let Some(role_id) = query.role_id {
         sql.push_str(" AND role_id = $2");
@@ -156,14 +602,15 @@ pub async fn get_shifts_for_date(
 
     sql.push_str(" ORDER BY start, role, label");
 
-    let mut query_builder = sqlx::query_as::<_, Shift>(&sql).bind(date);
+    let sql = crate::db::tag_sql(&sql);
+    let mut query_builder = sqlx::query_as::<_, Shift>(&sql).persistent(false).bind(date);
     if let Some(role_id) = query.role_id {
         query_builder = query_builder.bind(role_id);
     }
 
     let shifts = query_builder.fetch_all(&state.db).await?;
 
-    Ok(Json(shifts))
+    fields.respond(shifts)
 }
 
 /// GET /api/shifts/range?start=&end=&roleId=
@@ -180,36 +627,18 @@ pub async fn get_shifts_for_date(
 pub async fn get_shifts_for_range(
     State(state): State<Arc<AppState>>,
     Query(query): Query<GetShiftsRangeQuery>,
-) -> AppResult<Json<Vec<Shift>>> {
+) -> AppResult<Response> {
     let start_date = NaiveDate::parse_from_str(&query.start, "%Y-%m-%d")
         .map_err(|e| crate::AppError::BadRequest(format!("Invalid start date: {}", e)))?;
     let end_date = NaiveDate::parse_from_str(&query.end, "%Y-%m-%d")
         .map_err(|e| crate::AppError::BadRequest(format!("Invalid end date: {}", e)))?;
 
-    let mut sql = r#"
-        SELECT
-            uuid,
-            role_id AS role,
-            label,
-            to_char(start, 'HH24:MI:SS') AS start,
-            to_char("end", 'HH24:MI:SS') AS "end",
-            money_per_hour,
-            pa_value,
-            font_color,
-            bk_color,
-            is_locum,
-            published,
-            date,
-            created_at,
-            is_dcc,
-            is_spa,
-            time_off_category_id AS time_off,
-            user_profile_id,
-            created_by
-        FROM "Shifts"
-        WHERE date >= $1 AND date <= $2
-    "#
-    .to_string();
+    let marketplace = include_marketplace(query.include.as_deref())?;
+    let fields = FieldSet::parse(query.fields.as_deref(), SHIFT_FIELDS)?.keep(MARKETPLACE_FIELDS);
+    let mut sql = format!(
+        "SELECT {} WHERE date >= $1 AND date <= $2",
+        shift_list_source(&fields, &["date", "start"], marketplace)
+    );
 
     if let Some(role_id) = query.role_id {
         sql.push_str(" AND role_id = $3");
@@ -217,14 +646,15 @@ pub async fn get_shifts_for_range(
 
     sql.push_str(" ORDER BY date, start");
 
-    let mut query_builder = sqlx::query_as::<_, Shift>(&sql).bind(start_date).bind(end_date);
+    let sql = crate::db::tag_sql(&sql);
+    let mut query_builder = sqlx::query_as::<_, Shift>(&sql).persistent(false).bind(start_date).bind(end_date);
     if let Some(role_id) = query.role_id {
         query_builder = query_builder.bind(role_id);
     }
 
     let shifts = query_builder.fetch_all(&state.db).await?;
 
-    Ok(Json(shifts))
+    fields.respond(shifts)
 }
 
 /// POST /api/shifts - Create a new shift with audit trail
@@ -233,96 +663,215 @@ pub async fn get_shifts_for_range(
     path = "/api/shifts",
     request_body = CreateShiftInput,
     responses(
-        (status = 200, description = "Shift created successfully", body = Shift),
-        (status = 403, description = "Missing can_edit_rota permission")
+        (status = 200, description = "Shift created, or the existing duplicate when on_duplicate is SKIP", body = Shift),
+        (status = 403, description = "Missing can_edit_rota permission, or can_set_pay for a locum rate"),
+        (status = 409, description = "The month is locked for payroll, or the shift duplicates an existing one (REJECT)")
     ),
     tag = "shifts",
-    security(("cookie_auth" = []))
+    security(("cookie_auth" = ["can_edit_rota"]))
 )]
 pub async fn create_shift(
     State(state): State<Arc<AppState>>,
-    auth: AuthenticatedUser,
+    RequirePermission { auth, .. }: RequirePermission<CanEditRota>,
     Json(mut input): Json<CreateShiftInput>,
 ) -> AppResult<Json<Shift>> {
-    // Check permission
-    if !crate::extractors::permissions::has_permission_by_name(&state.db, auth.profile_id, auth.is_super_admin, "can_edit_rota").await? {
-        return Err(AppError::Forbidden(
-            "Missing can_edit_rota permission".to_string(),
-        ));
-    }
-
     // Set created_by to authenticated user if not specified
     if input.created_by.is_none() {
         input.created_by = Some(auth.profile_id);
     }
 
-    // Generate UUID for new shift
-    let shift_uuid = Uuid::new_v4();
+    // Fall back to the workplace's default shift times when none are given (not for time off)
+    if input.start.is_none() && input.end.is_none() && input.time_off.is_none() {
+        let settings = crate::handlers::workplaces_handler::load_role_settings(&state.db, input.role).await?;
+        input.start = settings.default_shift_start;
+        input.end = settings.default_shift_end;
+    }
+
+    let mut tx = crate::db::begin_as_user(&state.db, auth.profile_id).await?;
+    crate::handlers::payroll_locks_handler::ensure_unlocked(&mut tx, input.role, input.date).await?;
+    if input.published {
+        crate::handlers::rota_approval_handler::ensure_publish_allowed(&mut tx, input.role, input.date).await?;
+    }
+    let sets_pay = locum_rate(input.is_locum, input.money_per_hour).is_some();
+    if sets_pay {
+        ensure_can_set_pay(&state, &auth, input.role).await?;
+    }
 
     // Convert time strings to TIME format for database
     // Handle both HH:MM and HH:MM:SS formats
-    let start_time = input.start.as_ref().map(|s| {
-        if s.len() == 5 { format!("{}:00", s) } else { s.clone() }
-    });
-    let end_time = input.end.as_ref().map(|s| {
-        if s.len() == 5 { format!("{}:00", s) } else { s.clone() }
-    });
+    let start_time = input.start.as_deref().map(with_seconds);
+    let end_time = input.end.as_deref().map(with_seconds);
 
-    // Insert shift
-    let shift = sqlx::query_as::<_, Shift>(
-        r#"
-        INSERT INTO "Shifts" (
-            uuid, role_id, label, start, "end", money_per_hour,
-            pa_value, font_color, bk_color, is_locum, published,
-            date, is_dcc, is_spa, time_off_category_id,
-            user_profile_id, created_by
+    let policy = duplicate_policy(&state.db, input.role, input.on_duplicate).await?;
+    if policy != DuplicateShiftPolicy::Allow {
+        lock_duplicate_checks(&mut tx, input.role, &[input.date]).await?;
+        let duplicate = find_duplicate_shift(
+            &mut *tx,
+            input.role,
+            input.date,
+            &input.label,
+            start_time.as_deref(),
+            end_time.as_deref(),
+            input.user_profile_id,
         )
-        VALUES ($1, $2, $3, $4::time, $5::time, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
-        RETURNING
-            uuid,
-            role_id AS role,
-            label,
-            to_char(start, 'HH24:MI:SS') AS start,
-            to_char("end", 'HH24:MI:SS') AS "end",
-            money_per_hour,
-            pa_value,
-            font_color,
-            bk_color,
-            is_locum,
-            published,
-            date,
-            created_at,
-            is_dcc,
-            is_spa,
-            time_off_category_id AS time_off,
-            user_profile_id,
-            created_by
-        "#,
+        .await?;
+
+        if let Some(duplicate) = duplicate {
+            if policy == DuplicateShiftPolicy::Reject {
+                return Err(AppError::ConflictWith {
+                    message: format!(
+                        "A {} shift on {} with the same times and assignee already exists",
+                        duplicate.label, duplicate.date
+                    ),
+                    conflict: serde_json::json!(duplicate),
+                });
+            }
+
+            tracing::info!(existing = %duplicate.existing_uuid, role_id = input.role, "⏭️ Duplicate shift skipped");
+            let existing = sqlx::query_as::<_, Shift>(&format!(
+                r#"SELECT {} FROM "Shifts" WHERE uuid = $1"#,
+                FieldSet::parse(None, SHIFT_FIELDS)?.select_list(&[])
+            ))
+            .bind(duplicate.existing_uuid)
+            .fetch_one(&mut *tx)
+            .await?;
+            return Ok(Json(existing));
+        }
+    }
+
+    // Insert shift
+    let shift = insert_shift(
+        &mut tx,
+        &input,
+        input.date,
+        start_time.as_deref(),
+        end_time.as_deref(),
+        input.created_by.unwrap_or(auth.profile_id),
+        None,
     )
-    .bind(shift_uuid)
-    .bind(input.role)
-    .bind(&input.label)
-    .bind(start_time)
-    .bind(end_time)
-    .bind(input.money_per_hour)
-    .bind(input.pa_value)
-    .bind(&input.font_color)
-    .bind(&input.bk_color)
-    .bind(input.is_locum)
-    .bind(input.published)
-    .bind(input.date)
-    .bind(input.is_dcc)
-    .bind(input.is_spa)
-    .bind(input.time_off)
-    .bind(input.user_profile_id)
-    .bind(input.created_by.unwrap_or(auth.profile_id))
-    .fetch_one(&state.db)
     .await?;
+    if sets_pay {
+        record_pay_change(&mut tx, &shift, None, auth.profile_id).await?;
+    }
 
     // Audit trail is automatically created by PostgreSQL triggers
+    tx.commit().await?;
+    crate::handlers::metrics::record_shift_event("created");
     Ok(Json(shift))
 }
 
+/// POST /api/shifts/series - Create the same shift on consecutive days as one series (e.g. a week of nights)
+#[utoipa::path(
+    post,
+    path = "/api/shifts/series",
+    request_body = CreateShiftSeriesInput,
+    responses(
+        (status = 200, description = "Shifts created with a shared series_id (or that would be, with dry_run); days skipped as duplicates (SKIP, or REJECT with dry_run) are listed", body = ShiftSeriesResponse),
+        (status = 400, description = "days out of range"),
+        (status = 403, description = "Missing can_edit_rota permission, or can_set_pay for a locum rate"),
+        (status = 409, description = "A month the series touches is locked for payroll, or some days duplicate existing shifts (REJECT; listed under conflict)")
+    ),
+    tag = "shifts",
+    security(("cookie_auth" = ["can_edit_rota"]))
+)]
+pub async fn create_shift_series(
+    State(state): State<Arc<AppState>>,
+    RequirePermission { auth, .. }: RequirePermission<CanEditRota>,
+    Json(input): Json<CreateShiftSeriesInput>,
+) -> AppResult<Json<ShiftSeriesResponse>> {
+    let mut shift = input.shift;
+    let dates = series_dates(shift.date, input.days)?;
+    let (first, last) = (dates[0], dates[dates.len() - 1]);
+
+    if shift.start.is_none() && shift.end.is_none() && shift.time_off.is_none() {
+        let settings = crate::handlers::workplaces_handler::load_role_settings(&state.db, shift.role).await?;
+        shift.start = settings.default_shift_start;
+        shift.end = settings.default_shift_end;
+    }
+
+    // A series can run into the next month, which has its own lock and approval state
+    let mut tx = crate::db::begin_as_user(&state.db, auth.profile_id).await?;
+    for date in [first, last] {
+        crate::handlers::payroll_locks_handler::ensure_unlocked(&mut tx, shift.role, date).await?;
+        if shift.published {
+            crate::handlers::rota_approval_handler::ensure_publish_allowed(&mut tx, shift.role, date).await?;
+        }
+    }
+
+    let sets_pay = locum_rate(shift.is_locum, shift.money_per_hour).is_some();
+    if sets_pay {
+        ensure_can_set_pay(&state, &auth, shift.role).await?;
+    }
+
+    let start_time = shift.start.as_deref().map(with_seconds);
+    let end_time = shift.end.as_deref().map(with_seconds);
+
+    let policy = preview_policy(duplicate_policy(&state.db, shift.role, shift.on_duplicate).await?, input.dry_run);
+    if policy != DuplicateShiftPolicy::Allow {
+        lock_duplicate_checks(&mut tx, shift.role, &dates).await?;
+    }
+    let mut duplicates = Vec::new();
+    let mut to_create = Vec::new();
+    for date in dates {
+        let duplicate = match policy {
+            DuplicateShiftPolicy::Allow => None,
+            _ => find_duplicate_shift(
+                &mut *tx,
+                shift.role,
+                date,
+                &shift.label,
+                start_time.as_deref(),
+                end_time.as_deref(),
+                shift.user_profile_id,
+            )
+            .await?,
+        };
+        match duplicate {
+            Some(duplicate) => duplicates.push(duplicate),
+            None => to_create.push(date),
+        }
+    }
+
+    if policy == DuplicateShiftPolicy::Reject && !duplicates.is_empty() {
+        return Err(AppError::ConflictWith {
+            message: format!("{} day(s) of the series already have this shift", duplicates.len()),
+            conflict: serde_json::json!(duplicates),
+        });
+    }
+
+    let series_id = Uuid::new_v4();
+    let created_by = shift.created_by.unwrap_or(auth.profile_id);
+    let mut shifts = Vec::with_capacity(to_create.len());
+    for date in to_create {
+        let created =
+            insert_shift(&mut tx, &shift, date, start_time.as_deref(), end_time.as_deref(), created_by, Some(series_id))
+                .await?;
+        if sets_pay {
+            record_pay_change(&mut tx, &created, None, auth.profile_id).await?;
+        }
+        shifts.push(created);
+    }
+    if input.dry_run {
+        tx.rollback().await?;
+        return Ok(Json(ShiftSeriesResponse { dry_run: true, series_id, shifts, duplicates }));
+    }
+    tx.commit().await?;
+
+    for _ in &shifts {
+        crate::handlers::metrics::record_shift_event("created");
+    }
+    tracing::info!(
+        series_id = %series_id,
+        role_id = shift.role,
+        created = shifts.len(),
+        skipped = duplicates.len(),
+        created_by = auth.profile_id,
+        "🔗 Shift series created"
+    );
+
+    Ok(Json(ShiftSeriesResponse { dry_run: false, series_id, shifts, duplicates }))
+}
+
 /// PUT /api/shifts/{uuid} - Update a shift (audit trail via DB triggers)
 #[utoipa::path(
     put,
@@ -333,24 +882,61 @@ pub async fn create_shift(
     request_body = UpdateShiftInput,
     responses(
         (status = 200, description = "Shift updated successfully", body = Shift),
-        (status = 400, description = "No fields to update"),
-        (status = 403, description = "Missing can_edit_rota permission"),
-        (status = 404, description = "Shift not found")
+        (status = 400, description = "No fields to update, or a missing or too long reason"),
+        (status = 403, description = "Missing can_edit_rota permission, or can_set_pay to change a locum rate"),
+        (status = 404, description = "Shift not found"),
+        (status = 409, description = "The month is locked for payroll")
     ),
     tag = "shifts",
-    security(("cookie_auth" = []))
+    security(("cookie_auth" = ["can_edit_rota"]))
 )]
 pub async fn update_shift(
     State(state): State<Arc<AppState>>,
-    auth: AuthenticatedUser,
+    RequirePermission { auth, .. }: RequirePermission<CanEditRota>,
     Path(uuid): Path<Uuid>,
     Json(input): Json<UpdateShiftInput>,
 ) -> AppResult<Json<Shift>> {
-    // Check permission
-    if !crate::extractors::permissions::has_permission_by_name(&state.db, auth.profile_id, auth.is_super_admin, "can_edit_rota").await? {
-        return Err(AppError::Forbidden(
-            "Missing can_edit_rota permission".to_string(),
-        ));
+    let mut tx = crate::db::begin_as_user(&state.db, auth.profile_id).await?;
+    crate::handlers::payroll_locks_handler::ensure_shifts_unlocked(&mut tx, &[uuid]).await?;
+    let reason = change_reason(&state.db, uuid, input.reason.as_deref()).await?;
+
+    // Publishing, or moving an already-published shift, needs the target month approved (and moves need it unlocked)
+    if input.published.is_some() || input.role.is_some() || input.date.is_some() {
+        let existing: Option<(i32, NaiveDate, bool)> =
+            sqlx::query_as(r#"SELECT role_id, date, published FROM "Shifts" WHERE uuid = $1 FOR UPDATE"#)
+                .bind(uuid)
+                .fetch_optional(&mut *tx)
+                .await?;
+
+        if let Some((role_id, date, published)) = existing {
+            crate::handlers::payroll_locks_handler::ensure_unlocked(&mut tx, input.role.unwrap_or(role_id), input.date.unwrap_or(date)).await?;
+            if input.published.unwrap_or(published) {
+                crate::handlers::rota_approval_handler::ensure_publish_allowed(
+                    &mut tx,
+                    input.role.unwrap_or(role_id),
+                    input.date.unwrap_or(date),
+                )
+                .await?;
+            }
+        }
+    }
+
+    // A changed locum rate needs can_set_pay in the shift's (new) role; the old rate is kept for the audit
+    let mut pay_change: Option<Option<f32>> = None;
+    if input.money_per_hour.is_some() || input.is_locum.is_some() {
+        let existing: Option<(i32, bool, Option<f32>)> =
+            sqlx::query_as(r#"SELECT role_id, is_locum, money_per_hour FROM "Shifts" WHERE uuid = $1 FOR UPDATE"#)
+                .bind(uuid)
+                .fetch_optional(&mut *tx)
+                .await?;
+
+        if let Some((role_id, is_locum, money_per_hour)) = existing {
+            let new_rate = locum_rate(input.is_locum.unwrap_or(is_locum), input.money_per_hour.or(money_per_hour));
+            if new_rate != locum_rate(is_locum, money_per_hour) {
+                ensure_can_set_pay(&state, &auth, input.role.unwrap_or(role_id)).await?;
+                pay_change = Some(money_per_hour);
+            }
+        }
     }
 
     // Build dynamic UPDATE query
@@ -445,10 +1031,15 @@ pub async fn update_shift(
             is_spa,
             time_off_category_id AS time_off,
             user_profile_id,
-            created_by
+            created_by,
+            {duration},
+            {crosses_midnight},
+            series_id
         "#,
         updates.join(", "),
-        bind_count
+        bind_count,
+        duration = DURATION_MINUTES_SQL,
+        crosses_midnight = CROSSES_MIDNIGHT_SQL
     );
 
     // Build query with bindings
@@ -506,9 +1097,28 @@ pub async fn update_shift(
 
     query = query.bind(uuid);
 
-    let updated_shift = query.fetch_one(&state.db).await?;
+    if let Some(reason) = &reason {
+        crate::db::set_change_reason(&mut tx, reason).await?;
+    }
+    let before = shifts_by_uuid(&mut tx, &[uuid]).await?.pop();
+    let updated_shift = query.fetch_one(&mut *tx).await?;
+    if let Some(before) = &before {
+        notify_shift_change(&mut tx, before, Some(&updated_shift), auth.profile_id).await?;
+    }
+    if let Some(old_money_per_hour) = pay_change {
+        record_pay_change(&mut tx, &updated_shift, old_money_per_hour, auth.profile_id).await?;
+        tracing::info!(
+            shift = %uuid,
+            old = ?old_money_per_hour,
+            new = ?updated_shift.money_per_hour,
+            approved_by = auth.profile_id,
+            "💷 Locum rate changed"
+        );
+    }
 
     // Audit trail is automatically created by PostgreSQL triggers
+    tx.commit().await?;
+    crate::handlers::metrics::record_shift_event("updated");
     Ok(Json(updated_shift))
 }
 
@@ -517,41 +1127,179 @@ pub async fn update_shift(
     delete,
     path = "/api/shifts/{uuid}",
     params(
-        ("uuid" = Uuid, Path, description = "Shift UUID")
+        ("uuid" = Uuid, Path, description = "Shift UUID"),
+        DeleteShiftQuery
     ),
     responses(
         (status = 200, description = "Shift deleted successfully", body = ShiftMutationResponse),
+        (status = 400, description = "Missing or too long reason"),
         (status = 403, description = "Missing can_edit_rota permission"),
-        (status = 404, description = "Shift not found")
+        (status = 404, description = "Shift not found"),
+        (status = 409, description = "The month is locked for payroll")
     ),
     tag = "shifts",
-    security(("cookie_auth" = []))
+    security(("cookie_auth" = ["can_edit_rota"]))
 )]
 pub async fn delete_shift(
     State(state): State<Arc<AppState>>,
-    auth: AuthenticatedUser,
+    RequirePermission { auth, .. }: RequirePermission<CanEditRota>,
     Path(uuid): Path<Uuid>,
+    Query(query): Query<DeleteShiftQuery>,
 ) -> AppResult<Json<ShiftMutationResponse>> {
-    // Check permission
-    if !crate::extractors::permissions::has_permission_by_name(&state.db, auth.profile_id, auth.is_super_admin, "can_edit_rota").await? {
-        return Err(AppError::Forbidden(
-            "Missing can_edit_rota permission".to_string(),
-        ));
-    }
+    let mut tx = crate::db::begin_as_user(&state.db, auth.profile_id).await?;
+    crate::handlers::payroll_locks_handler::ensure_shifts_unlocked(&mut tx, &[uuid]).await?;
+    let reason = change_reason(&state.db, uuid, query.reason.as_deref()).await?;
 
     // Delete the shift (audit trail is automatically created by PostgreSQL triggers)
+    if let Some(reason) = &reason {
+        crate::db::set_change_reason(&mut tx, reason).await?;
+    }
+    let before = shifts_by_uuid(&mut tx, &[uuid]).await?.pop();
     let result = sqlx::query(r#"DELETE FROM "Shifts" WHERE uuid = $1"#)
         .bind(uuid)
-        .execute(&state.db)
+        .execute(&mut *tx)
         .await?;
 
     if result.rows_affected() == 0 {
         return Err(AppError::NotFound(format!("Shift {} not found", uuid)));
     }
+    if let Some(before) = &before {
+        notify_shift_change(&mut tx, before, None, auth.profile_id).await?;
+    }
+    tx.commit().await?;
+    crate::handlers::metrics::record_shift_event("deleted");
 
     Ok(Json(ShiftMutationResponse {
         success: true,
         shift_uuid: Some(uuid),
         message: Some("Shift deleted successfully".to_string()),
     }))
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preview_policy() {
+        assert_eq!(preview_policy(DuplicateShiftPolicy::Reject, true), DuplicateShiftPolicy::Skip);
+        assert_eq!(preview_policy(DuplicateShiftPolicy::Reject, false), DuplicateShiftPolicy::Reject);
+        assert_eq!(preview_policy(DuplicateShiftPolicy::Allow, true), DuplicateShiftPolicy::Allow);
+    }
+
+    #[test]
+    fn test_shift_change_notices() {
+        let before = Shift {
+            uuid: Uuid::nil(),
+            role: 1,
+            label: "Night".to_string(),
+            start: Some("20:00:00".to_string()),
+            end: Some("08:00:00".to_string()),
+            money_per_hour: None,
+            pa_value: 1.0,
+            font_color: String::new(),
+            bk_color: String::new(),
+            is_locum: false,
+            published: true,
+            date: NaiveDate::from_ymd_opt(2026, 11, 2).unwrap(),
+            created_at: chrono::NaiveDateTime::default(),
+            is_dcc: true,
+            is_spa: false,
+            time_off: None,
+            user_profile_id: Some(7),
+            created_by: 1,
+            duration_minutes: Some(720),
+            crosses_midnight: true,
+            series_id: None,
+            marketplace_request_id: None,
+            marketplace_request_type: None,
+            marketplace_request_status: None,
+        };
+        let kinds = |after: Option<&Shift>, changed_by| {
+            shift_change_notices(&before, after, changed_by)
+                .into_iter()
+                .map(|n| (n.user_profile_id, n.kind))
+                .collect::<Vec<_>>()
+        };
+
+        let moved = Shift { start: Some("21:00:00".to_string()), ..before.clone() };
+        assert_eq!(kinds(Some(&moved), 1), [(7, "SHIFT_CHANGED")]);
+        assert!(shift_change_notices(&before, Some(&moved), 1)[0].message.contains("start 20:00 → 21:00"));
+        assert!(kinds(Some(&moved), 7).is_empty());
+        assert!(kinds(Some(&Shift { bk_color: "#fff".to_string(), ..before.clone() }), 1).is_empty());
+
+        let reassigned = Shift { user_profile_id: Some(8), ..before.clone() };
+        assert_eq!(kinds(Some(&reassigned), 1), [(7, "SHIFT_UNASSIGNED"), (8, "SHIFT_ASSIGNED")]);
+        assert_eq!(kinds(None, 1), [(7, "SHIFT_REMOVED")]);
+
+        // Drafts and publishing stay quiet
+        let draft = Shift { published: false, ..before.clone() };
+        assert!(shift_change_notices(&draft, Some(&reassigned), 1).is_empty());
+        assert!(shift_change_notices(&draft, None, 1).is_empty());
+    }
+
+    #[test]
+    fn test_locum_rate() {
+        assert_eq!(locum_rate(true, Some(80.0)), Some(80.0));
+        assert_eq!(locum_rate(false, Some(80.0)), None);
+        assert_eq!(locum_rate(true, None), None);
+        // Turning a rated shift into a locum shift changes its locum rate
+        assert_ne!(locum_rate(true, Some(80.0)), locum_rate(false, Some(80.0)));
+    }
+
+    #[test]
+    fn test_parse_role_ids() {
+        let values = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(parse_role_ids(&values(&["3"])).unwrap(), vec![3]);
+        assert_eq!(parse_role_ids(&values(&["3, 1", "2", "3"])).unwrap(), vec![3, 1, 2]);
+        assert!(parse_role_ids(&values(&[])).unwrap().is_empty());
+        assert!(parse_role_ids(&values(&["1,x"])).is_err());
+
+        let too_many = (1..=21).map(|i| i.to_string()).collect::<Vec<_>>().join(",");
+        assert!(parse_role_ids(&[too_many]).is_err());
+    }
+
+    #[test]
+    fn test_check_role_scope() {
+        let in_scope = vec![(1, "ED".to_string()), (4, "AMU".to_string())];
+        assert!(check_role_scope(&[4, 1], &in_scope).is_ok());
+        assert!(check_role_scope(&[], &in_scope).is_ok());
+        match check_role_scope(&[1, 2, 3], &in_scope) {
+            Err(AppError::Forbidden(message)) => assert_eq!(message, "Not a member of roles [2, 3]"),
+            other => panic!("expected Forbidden, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_normalize_reason() {
+        assert_eq!(normalize_reason(Some("  Swapped with Dr B on the phone ")).unwrap().as_deref(), Some("Swapped with Dr B on the phone"));
+        assert_eq!(normalize_reason(Some("   ")).unwrap(), None);
+        assert_eq!(normalize_reason(None).unwrap(), None);
+        assert!(normalize_reason(Some(&"x".repeat(MAX_CHANGE_REASON_LENGTH + 1))).is_err());
+    }
+
+    #[test]
+    fn test_series_dates_run_across_month_end() {
+        let start = NaiveDate::from_ymd_opt(2027, 1, 29).unwrap();
+        let dates = series_dates(start, 7).unwrap();
+
+        assert_eq!(dates.len(), 7);
+        assert_eq!(dates[3], NaiveDate::from_ymd_opt(2027, 2, 1).unwrap());
+        assert_eq!(dates[6], NaiveDate::from_ymd_opt(2027, 2, 4).unwrap());
+        assert!(series_dates(start, 1).is_err());
+        assert!(series_dates(start, MAX_SERIES_DAYS + 1).is_err());
+    }
+
+    #[test]
+    fn test_include_marketplace() {
+        assert!(!include_marketplace(None).unwrap());
+        assert!(include_marketplace(Some(" marketplace, ")).unwrap());
+        assert!(include_marketplace(Some("marketplace,audit")).is_err());
+
+        let fields = FieldSet::parse(Some("uuid"), SHIFT_FIELDS).unwrap();
+        assert!(shift_list_source(&fields, &[], false).starts_with("uuid, 0 AS role"));
+        assert!(shift_list_source(&fields, &[], false).ends_with(r#"NULL::uuid AS series_id FROM "Shifts""#));
+        assert!(shift_list_source(&fields, &[], true).contains(r#"AS marketplace_request_status FROM "Shifts" LEFT JOIN LATERAL"#));
+    }
+}
@@ -1,14 +1,20 @@
 use axum::{
     extract::{Path, Query, State},
-    Json,
 };
+use chrono::{Days, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
 use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use utoipa::IntoParams;
+use uuid::Uuid;
 
 use crate::{
-    extractors::AuthenticatedUser,
-    models::{CreateTemplateInput, ShiftTemplate, TemplateMutationResponse, UpdateTemplateInput},
+    extractors::{permissions, AuthenticatedUser, CanEditTemplates, Json, RequirePermission},
+    job_plans::JobPlanResolver,
+    models::{
+        AutoFillAssignment, AutoFillInput, AutoFillResponse, AutoFillUnfilled, CreateTemplateInput, ShiftTemplate,
+        TemplateMonthUsage, TemplateMutationResponse, TemplateUsage, UpdateTemplateInput,
+    },
     AppError, AppResult, AppState,
 };
 
@@ -17,6 +23,46 @@ fn normalize_time(s: &str) -> String {
     if s.matches(':').count() >= 2 { s.to_string() } else { format!("{}:00", s) }
 }
 
+/// Longest preferred staff list a template may carry
+const MAX_PREFERRED_USERS: usize = 10;
+
+/// Preferred staff are an ordered list of distinct people
+fn check_preferred_list(user_ids: &[i32]) -> AppResult<()> {
+    if user_ids.len() > MAX_PREFERRED_USERS {
+        return Err(AppError::BadRequest(format!(
+            "A template can have at most {} preferred users",
+            MAX_PREFERRED_USERS
+        )));
+    }
+    if let Some((i, id)) = user_ids.iter().enumerate().find(|(i, id)| user_ids[..*i].contains(id)) {
+        return Err(AppError::BadRequest(format!("User {} is listed twice (position {})", id, i + 1)));
+    }
+    Ok(())
+}
+
+/// Every preferred user must work shifts in the template's role
+async fn validate_preferred_users(db: &sqlx::PgPool, role_id: i32, user_ids: &[i32]) -> AppResult<()> {
+    check_preferred_list(user_ids)?;
+    if user_ids.is_empty() {
+        return Ok(());
+    }
+
+    let members: Vec<i32> = sqlx::query_scalar(&format!(
+        r#"SELECT user_profile_id FROM "UserRoles" WHERE role_id = $1 AND can_work_shifts AND {in_force} AND user_profile_id = ANY($2)"#,
+        in_force = crate::extractors::permissions::user_role_in_force(3)
+    ))
+    .bind(role_id)
+    .bind(user_ids)
+    .bind(crate::extractors::permissions::today())
+    .fetch_all(db)
+    .await?;
+
+    match user_ids.iter().find(|id| !members.contains(id)) {
+        Some(id) => Err(AppError::BadRequest(format!("User {} does not work shifts in role {}", id, role_id))),
+        None => Ok(()),
+    }
+}
+
 #[derive(Debug, Deserialize, IntoParams)]
 pub struct GetTemplatesQuery {
     #[serde(rename = "roleId")]
@@ -49,7 +95,8 @@ pub async fn get_templates(
             pa_value,
             money_per_hour,
             is_spa,
-            is_dcc
+            is_dcc,
+            preferred_user_ids
         FROM "ShiftTemplates"
         WHERE 1=1
     "#
@@ -82,22 +129,18 @@ pub async fn get_templates(
     request_body = CreateTemplateInput,
     responses(
         (status = 200, description = "Template created successfully", body = ShiftTemplate),
+        (status = 400, description = "A preferred user is listed twice or does not work shifts in the role"),
         (status = 403, description = "Missing can_edit_templates permission")
     ),
     tag = "templates",
-    security(("cookie_auth" = []))
+    security(("cookie_auth" = ["can_edit_templates"]))
 )]
 pub async fn create_template(
     State(state): State<Arc<AppState>>,
-    auth: AuthenticatedUser,
+    _: RequirePermission<CanEditTemplates>,
     Json(input): Json<CreateTemplateInput>,
 ) -> AppResult<Json<ShiftTemplate>> {
-    // Check permission
-    if !crate::extractors::permissions::has_permission_by_name(&state.db, auth.profile_id, auth.is_super_admin, "can_edit_templates").await? {
-        return Err(AppError::Forbidden(
-            "Missing can_edit_templates permission".to_string(),
-        ));
-    }
+    validate_preferred_users(&state.db, input.role, &input.preferred_user_ids).await?;
 
     // Convert time strings to TIME format for database
     let start_time = input.start.as_ref().map(|s| normalize_time(s));
@@ -107,9 +150,9 @@ pub async fn create_template(
         r#"
         INSERT INTO "ShiftTemplates" (
             role_id, label, start, "end", pa_value, money_per_hour,
-            font_color, bk_color, is_spa, is_dcc
+            font_color, bk_color, is_spa, is_dcc, preferred_user_ids
         )
-        VALUES ($1, $2, $3::time, $4::time, $5, $6, $7, $8, $9, $10)
+        VALUES ($1, $2, $3::time, $4::time, $5, $6, $7, $8, $9, $10, $11)
         RETURNING
             id,
             role_id AS role,
@@ -121,7 +164,8 @@ pub async fn create_template(
             pa_value,
             money_per_hour,
             is_spa,
-            is_dcc
+            is_dcc,
+            preferred_user_ids
         "#,
     )
     .bind(input.role)
@@ -134,6 +178,7 @@ pub async fn create_template(
     .bind(&input.bk_color)
     .bind(input.is_spa)
     .bind(input.is_dcc)
+    .bind(&input.preferred_user_ids)
     .fetch_one(&state.db)
     .await?;
 
@@ -150,24 +195,33 @@ pub async fn create_template(
     request_body = UpdateTemplateInput,
     responses(
         (status = 200, description = "Template updated successfully", body = ShiftTemplate),
-        (status = 400, description = "No fields to update"),
+        (status = 400, description = "No fields to update, or a preferred user is listed twice or does not work shifts in the role"),
         (status = 403, description = "Missing can_edit_templates permission"),
         (status = 404, description = "Template not found")
     ),
     tag = "templates",
-    security(("cookie_auth" = []))
+    security(("cookie_auth" = ["can_edit_templates"]))
 )]
 pub async fn update_template(
     State(state): State<Arc<AppState>>,
     Path(template_id): Path<i32>,
-    auth: AuthenticatedUser,
+    _: RequirePermission<CanEditTemplates>,
     Json(input): Json<UpdateTemplateInput>,
 ) -> AppResult<Json<ShiftTemplate>> {
-    // Check permission
-    if !crate::extractors::permissions::has_permission_by_name(&state.db, auth.profile_id, auth.is_super_admin, "can_edit_templates").await? {
-        return Err(AppError::Forbidden(
-            "Missing can_edit_templates permission".to_string(),
-        ));
+    // Moving the template to another role re-checks the preferred staff it keeps
+    if input.role.is_some() || input.preferred_user_ids.is_some() {
+        let (role_id, preferred_user_ids): (i32, Vec<i32>) =
+            sqlx::query_as(r#"SELECT role_id, preferred_user_ids FROM "ShiftTemplates" WHERE id = $1"#)
+                .bind(template_id)
+                .fetch_optional(&state.db)
+                .await?
+                .ok_or_else(|| AppError::NotFound(format!("Template {} not found", template_id)))?;
+        validate_preferred_users(
+            &state.db,
+            input.role.unwrap_or(role_id),
+            input.preferred_user_ids.as_deref().unwrap_or(&preferred_user_ids),
+        )
+        .await?;
     }
 
     // Build dynamic UPDATE query
@@ -214,6 +268,10 @@ pub async fn update_template(
         updates.push(format!("is_dcc = ${}", bind_count));
         bind_count += 1;
     }
+    if input.preferred_user_ids.is_some() {
+        updates.push(format!("preferred_user_ids = ${}", bind_count));
+        bind_count += 1;
+    }
 
     if updates.is_empty() {
         return Err(AppError::BadRequest("No fields to update".to_string()));
@@ -235,7 +293,8 @@ pub async fn update_template(
             pa_value,
             money_per_hour,
             is_spa,
-            is_dcc
+            is_dcc,
+            preferred_user_ids
         "#,
         updates.join(", "),
         bind_count
@@ -274,6 +333,9 @@ pub async fn update_template(
     if let Some(is_dcc) = input.is_dcc {
         query = query.bind(is_dcc);
     }
+    if let Some(preferred_user_ids) = &input.preferred_user_ids {
+        query = query.bind(preferred_user_ids);
+    }
 
     query = query.bind(template_id);
 
@@ -282,31 +344,149 @@ pub async fn update_template(
     Ok(Json(updated_template))
 }
 
-/// DELETE /api/templates/{id} - Delete a template
+/// Shifts "generated from" a template: same role, label and times (shifts keep no template id)
+const MATCHES_TEMPLATE: &str = r#"
+    s.role_id = t.role_id
+    AND s.label = t.label
+    AND s.start IS NOT DISTINCT FROM t.start
+    AND s."end" IS NOT DISTINCT FROM t."end"
+"#;
+
+async fn load_template_usage(db: &sqlx::PgPool, template_id: i32) -> AppResult<TemplateUsage> {
+    let exists: bool = sqlx::query_scalar(r#"SELECT EXISTS(SELECT 1 FROM "ShiftTemplates" WHERE id = $1)"#)
+        .bind(template_id)
+        .fetch_one(db)
+        .await?;
+    if !exists {
+        return Err(AppError::NotFound(format!("Template {} not found", template_id)));
+    }
+
+    let months = sqlx::query_as::<_, TemplateMonthUsage>(&format!(
+        r#"
+        SELECT
+            EXTRACT(YEAR FROM s.date)::int4 AS year,
+            EXTRACT(MONTH FROM s.date)::int4 AS month,
+            COUNT(*) AS shifts,
+            COUNT(*) FILTER (WHERE s.published) AS published_shifts
+        FROM "ShiftTemplates" t
+        INNER JOIN "Shifts" s ON {}
+        WHERE t.id = $1
+        GROUP BY 1, 2
+        ORDER BY 1 DESC, 2 DESC
+        "#,
+        MATCHES_TEMPLATE
+    ))
+    .bind(template_id)
+    .fetch_all(db)
+    .await?;
+
+    let last_used: Option<chrono::NaiveDate> = sqlx::query_scalar(&format!(
+        r#"SELECT MAX(s.date) FROM "ShiftTemplates" t INNER JOIN "Shifts" s ON {} WHERE t.id = $1"#,
+        MATCHES_TEMPLATE
+    ))
+    .bind(template_id)
+    .fetch_one(db)
+    .await?;
+
+    Ok(template_usage(template_id, months, last_used))
+}
+
+/// Usage from the per-month counts, newest month first
+fn template_usage(template_id: i32, months: Vec<TemplateMonthUsage>, last_used: Option<NaiveDate>) -> TemplateUsage {
+    TemplateUsage {
+        template_id,
+        total_shifts: months.iter().map(|m| m.shifts).sum(),
+        last_used,
+        months,
+    }
+}
+
+/// 409 for deleting a template the month being drafted still relies on, with its usage under conflict
+fn in_use_conflict(template_id: i32, unpublished_this_month: i64, usage: &TemplateUsage) -> AppError {
+    match serde_json::to_value(usage) {
+        Ok(conflict) => AppError::ConflictWith {
+            message: format!(
+                "Template {} is used by {} unpublished shift(s) this month; pass force=true to delete anyway",
+                template_id, unpublished_this_month
+            ),
+            conflict,
+        },
+        Err(e) => AppError::Internal(e.to_string()),
+    }
+}
+
+/// GET /api/templates/{id}/usage - Shifts matching a template per month and when it was last used
+#[utoipa::path(
+    get,
+    path = "/api/templates/{id}/usage",
+    params(
+        ("id" = i32, Path, description = "Template ID")
+    ),
+    responses(
+        (status = 200, description = "Template usage per month, newest first", body = TemplateUsage),
+        (status = 404, description = "Template not found")
+    ),
+    tag = "templates",
+    security(("cookie_auth" = []))
+)]
+pub async fn get_template_usage(
+    State(state): State<Arc<AppState>>,
+    Path(template_id): Path<i32>,
+    _auth: AuthenticatedUser,
+) -> AppResult<Json<TemplateUsage>> {
+    Ok(Json(load_template_usage(&state.db, template_id).await?))
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct DeleteTemplateQuery {
+    /// Delete even if the template is in use in the current, not yet published month
+    pub force: Option<bool>,
+}
+
+/// DELETE /api/templates/{id}?force= - Delete a template
 #[utoipa::path(
     delete,
     path = "/api/templates/{id}",
     params(
-        ("id" = i32, Path, description = "Template ID")
+        ("id" = i32, Path, description = "Template ID"),
+        DeleteTemplateQuery
     ),
     responses(
         (status = 200, description = "Template deleted successfully", body = TemplateMutationResponse),
         (status = 403, description = "Missing can_edit_templates permission"),
-        (status = 404, description = "Template not found")
+        (status = 404, description = "Template not found"),
+        (status = 409, description = "Template used by unpublished shifts this month; usage returned under conflict (retry with force=true)")
     ),
     tag = "templates",
-    security(("cookie_auth" = []))
+    security(("cookie_auth" = ["can_edit_templates"]))
 )]
 pub async fn delete_template(
     State(state): State<Arc<AppState>>,
     Path(template_id): Path<i32>,
-    auth: AuthenticatedUser,
+    Query(query): Query<DeleteTemplateQuery>,
+    _: RequirePermission<CanEditTemplates>,
 ) -> AppResult<Json<TemplateMutationResponse>> {
-    // Check permission
-    if !crate::extractors::permissions::has_permission_by_name(&state.db, auth.profile_id, auth.is_super_admin, "can_edit_templates").await? {
-        return Err(AppError::Forbidden(
-            "Missing can_edit_templates permission".to_string(),
-        ));
+    // The month being drafted still relies on the template
+    if !query.force.unwrap_or(false) {
+        let unpublished_this_month: i64 = sqlx::query_scalar(&format!(
+            r#"
+            SELECT COUNT(*)
+            FROM "ShiftTemplates" t
+            INNER JOIN "Shifts" s ON {}
+            WHERE t.id = $1
+              AND NOT s.published
+              AND date_trunc('month', s.date) = date_trunc('month', CURRENT_DATE)
+            "#,
+            MATCHES_TEMPLATE
+        ))
+        .bind(template_id)
+        .fetch_one(&state.db)
+        .await?;
+
+        if unpublished_this_month > 0 {
+            let usage = load_template_usage(&state.db, template_id).await?;
+            return Err(in_use_conflict(template_id, unpublished_this_month, &usage));
+        }
     }
 
     let result = sqlx::query(r#"DELETE FROM "ShiftTemplates" WHERE id = $1"#)
@@ -327,3 +507,340 @@ pub async fn delete_template(
     }))
 }
 
+
+/// An unassigned shift matching a template that has preferred staff
+#[derive(Debug, sqlx::FromRow)]
+struct AutoFillCandidate {
+    uuid: Uuid,
+    date: NaiveDate,
+    start: Option<NaiveTime>,
+    end: Option<NaiveTime>,
+    label: String,
+    pa_value: f32,
+    preferred_user_ids: Vec<i32>,
+}
+
+/// When a shift or leave day keeps someone busy: a timed shift from its start to its end (overnight shifts run
+/// into the next day), anything untimed the whole day
+fn busy_span(date: NaiveDate, start: Option<NaiveTime>, end: Option<NaiveTime>) -> (NaiveDateTime, NaiveDateTime) {
+    match (start, end) {
+        (Some(start), Some(end)) => crate::handlers::attendance_handler::rostered_window(date, start, end),
+        _ => (date.and_time(NaiveTime::MIN), (date + Days::new(1)).and_time(NaiveTime::MIN)),
+    }
+}
+
+/// Monday of the week `date` falls in
+fn week_start(date: NaiveDate) -> NaiveDate {
+    date.week(Weekday::Mon).first_day()
+}
+
+/// Give each shift, in order, to the first preferred user with no shift or leave overlapping it and whose job plan
+/// DCC PAs for the week still cover the shift (users without a DCC figure are not capped). Returns the user chosen
+/// for each shift; `busy` and `week_pas` are updated as shifts are handed out.
+fn plan_auto_fill(
+    shifts: &[AutoFillCandidate],
+    busy: &mut HashMap<i32, Vec<(NaiveDateTime, NaiveDateTime)>>,
+    week_pas: &mut HashMap<(i32, NaiveDate), f32>,
+    weekly_cap: impl Fn(i32, NaiveDate) -> Option<f32>,
+) -> Vec<Option<i32>> {
+    shifts
+        .iter()
+        .map(|shift| {
+            let week = week_start(shift.date);
+            let (from, to) = busy_span(shift.date, shift.start, shift.end);
+            let chosen = shift.preferred_user_ids.iter().copied().find(|&user_id| {
+                let used = week_pas.get(&(user_id, week)).copied().unwrap_or(0.0);
+                let clashes = busy.get(&user_id).is_some_and(|spans| spans.iter().any(|&(f, t)| f < to && from < t));
+                !clashes
+                    && weekly_cap(user_id, shift.date).is_none_or(|cap| used + shift.pa_value <= cap + 0.001)
+            });
+            if let Some(user_id) = chosen {
+                busy.entry(user_id).or_default().push((from, to));
+                *week_pas.entry((user_id, week)).or_default() += shift.pa_value;
+            }
+            chosen
+        })
+        .collect()
+}
+
+/// POST /api/templates/auto-fill - Assign a month's unassigned shifts to their templates' preferred staff
+#[utoipa::path(
+    post,
+    path = "/api/templates/auto-fill",
+    request_body = AutoFillInput,
+    responses(
+        (status = 200, description = "Shifts assigned (or that would be, with dry_run) and shifts nobody preferred could take", body = AutoFillResponse),
+        (status = 400, description = "Invalid year or month"),
+        (status = 403, description = "Missing can_edit_rota permission in the role"),
+        (status = 409, description = "The month is locked for payroll")
+    ),
+    tag = "templates",
+    security(("cookie_auth" = []))
+)]
+pub async fn auto_fill(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+    Json(input): Json<AutoFillInput>,
+) -> AppResult<Json<AutoFillResponse>> {
+    let role_id = input.role_id;
+    if !permissions::has_permission(&state, auth.profile_id, auth.is_super_admin, |r| {
+        r.role_id == role_id && r.can_edit_rota
+    })
+    .await?
+    {
+        return Err(AppError::Forbidden("Missing can_edit_rota permission in this role".to_string()));
+    }
+
+    let (from, to) = crate::handlers::roles_handler::month_range(input.year, input.month)?;
+    let mut tx = crate::db::begin_as_user(&state.db, auth.profile_id).await?;
+    crate::handlers::payroll_locks_handler::ensure_unlocked(&mut tx, role_id, from).await?;
+
+    let mut shifts = sqlx::query_as::<_, AutoFillCandidate>(&format!(
+        r#"
+        SELECT * FROM (
+            SELECT DISTINCT ON (s.uuid) s.uuid, s.date, s.start, s."end", s.label, s.pa_value, t.preferred_user_ids
+            FROM "Shifts" s
+            INNER JOIN "ShiftTemplates" t ON {}
+            WHERE s.role_id = $1
+              AND s.date BETWEEN $2 AND $3
+              AND s.user_profile_id IS NULL
+              AND NOT s.published
+              AND s.time_off_category_id IS NULL
+              AND cardinality(t.preferred_user_ids) > 0
+            ORDER BY s.uuid, t.id
+        ) candidates
+        ORDER BY date, start NULLS LAST, uuid
+        "#,
+        MATCHES_TEMPLATE
+    ))
+    .bind(role_id)
+    .bind(from)
+    .bind(to)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    // Preferred staff who have since left the role or stopped working shifts are passed over
+    let members: HashSet<i32> =
+        sqlx::query_scalar(&format!(r#"SELECT user_profile_id FROM "UserRoles" WHERE role_id = $1 AND can_work_shifts AND {in_force}"#, in_force = crate::extractors::permissions::user_role_in_force(2)))
+            .bind(role_id)
+            .bind(crate::extractors::permissions::today())
+            .fetch_all(&mut *tx)
+            .await?
+            .into_iter()
+            .collect();
+    for shift in &mut shifts {
+        shift.preferred_user_ids.retain(|id| members.contains(id));
+    }
+    let candidates: Vec<i32> = shifts
+        .iter()
+        .flat_map(|s| s.preferred_user_ids.iter().copied())
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+
+    // From the day before the month, so a night shift running into the 1st still counts
+    let mut busy: HashMap<i32, Vec<(NaiveDateTime, NaiveDateTime)>> = HashMap::new();
+    let existing = sqlx::query_as::<_, (i32, NaiveDate, Option<NaiveTime>, Option<NaiveTime>)>(
+        r#"
+        SELECT user_profile_id, date, start, "end" FROM "Shifts"
+        WHERE user_profile_id = ANY($1) AND date BETWEEN $2 AND $3
+        UNION
+        SELECT user_profile_id, date, NULL::time, NULL::time FROM "Diary"
+        WHERE user_profile_id = ANY($1) AND NOT deleted AND (al OR sl OR pl) AND date BETWEEN $2 AND $3
+        "#,
+    )
+    .bind(&candidates)
+    .bind(from - Days::new(1))
+    .bind(to)
+    .fetch_all(&mut *tx)
+    .await?;
+    for (user_id, date, start, end) in existing {
+        busy.entry(user_id).or_default().push(busy_span(date, start, end));
+    }
+
+    // PAs already worked in the role in each week touching the month
+    let weeks_from = week_start(from);
+    let weeks_to = week_start(to) + Days::new(6);
+    let mut week_pas: HashMap<(i32, NaiveDate), f32> = sqlx::query_as::<_, (i32, NaiveDate, f32)>(
+        r#"
+        SELECT user_profile_id, date_trunc('week', date)::date, SUM(pa_value)::float4
+        FROM "Shifts"
+        WHERE role_id = $1 AND user_profile_id = ANY($2) AND time_off_category_id IS NULL AND date BETWEEN $3 AND $4
+        GROUP BY 1, 2
+        "#,
+    )
+    .bind(role_id)
+    .bind(&candidates)
+    .bind(weeks_from)
+    .bind(weeks_to)
+    .fetch_all(&mut *tx)
+    .await?
+    .into_iter()
+    .map(|(user_id, week, pas)| ((user_id, week), pas))
+    .collect();
+
+    let plans = JobPlanResolver::load(&state.db, None, Some(role_id), from, to).await?;
+    let choices = plan_auto_fill(&shifts, &mut busy, &mut week_pas, |user_id, date| {
+        plans.plan_on(user_id, role_id, date).and_then(|plan| plan.dcc_pa)
+    });
+
+    let before = crate::handlers::shifts_handler::shifts_by_uuid(&mut tx, &shifts.iter().map(|s| s.uuid).collect::<Vec<_>>()).await?;
+    let mut assigned = Vec::new();
+    let mut unfilled = Vec::new();
+    for (shift, choice) in shifts.into_iter().zip(choices) {
+        let Some(user_profile_id) = choice else {
+            unfilled.push(AutoFillUnfilled { shift_uuid: shift.uuid, date: shift.date, label: shift.label });
+            continue;
+        };
+
+        if !input.dry_run {
+            // Skip a shift someone assigned or published since it was read
+            let result = sqlx::query(
+                r#"UPDATE "Shifts" SET user_profile_id = $1 WHERE uuid = $2 AND user_profile_id IS NULL AND NOT published"#,
+            )
+            .bind(user_profile_id)
+            .bind(shift.uuid)
+            .execute(&mut *tx)
+            .await?;
+            if result.rows_affected() == 0 {
+                continue;
+            }
+        }
+        assigned.push(AutoFillAssignment {
+            shift_uuid: shift.uuid,
+            date: shift.date,
+            label: shift.label,
+            user_profile_id,
+        });
+    }
+
+    if input.dry_run {
+        tx.rollback().await?;
+    } else {
+        // The same notices as any other assignment; drafts stay quiet, as they do for PUT /api/shifts/{uuid}
+        crate::handlers::shifts_handler::notify_shift_changes(&mut tx, &before, auth.profile_id).await?;
+        tx.commit().await?;
+        for _ in &assigned {
+            crate::handlers::metrics::record_shift_event("updated");
+        }
+    }
+
+    tracing::info!(
+        role_id,
+        year = input.year,
+        month = input.month,
+        assigned = assigned.len(),
+        unfilled = unfilled.len(),
+        dry_run = input.dry_run,
+        filled_by = auth.profile_id,
+        "🧩 Template auto-fill"
+    );
+
+    Ok(Json(AutoFillResponse { success: true, dry_run: input.dry_run, assigned, unfilled }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_preferred_list() {
+        assert!(check_preferred_list(&[]).is_ok());
+        assert!(check_preferred_list(&[3, 1, 2]).is_ok());
+        assert!(check_preferred_list(&[3, 1, 3]).is_err());
+        assert!(check_preferred_list(&(1..=11).collect::<Vec<_>>()).is_err());
+    }
+
+    fn month(year: i32, month: i32, shifts: i64, published_shifts: i64) -> TemplateMonthUsage {
+        TemplateMonthUsage { year, month, shifts, published_shifts }
+    }
+
+    #[test]
+    fn test_template_usage_totals_every_month() {
+        let last_used = NaiveDate::from_ymd_opt(2027, 3, 18);
+        let usage = template_usage(4, vec![month(2027, 3, 6, 0), month(2027, 2, 20, 20), month(2026, 12, 1, 1)], last_used);
+        assert_eq!(usage.total_shifts, 27);
+        assert_eq!(usage.last_used, last_used);
+        assert_eq!(usage.months.len(), 3);
+
+        let unused = template_usage(4, vec![], None);
+        assert_eq!(unused.total_shifts, 0);
+        assert_eq!(unused.last_used, None);
+    }
+
+    #[test]
+    fn test_in_use_conflict_carries_usage() {
+        let usage = template_usage(4, vec![month(2027, 3, 6, 2)], NaiveDate::from_ymd_opt(2027, 3, 18));
+        match in_use_conflict(4, 4, &usage) {
+            AppError::ConflictWith { message, conflict } => {
+                assert_eq!(
+                    message,
+                    "Template 4 is used by 4 unpublished shift(s) this month; pass force=true to delete anyway"
+                );
+                assert_eq!(conflict["template_id"], 4);
+                assert_eq!(conflict["total_shifts"], 6);
+                assert_eq!(conflict["months"][0]["published_shifts"], 2);
+            }
+            other => panic!("expected ConflictWith, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_plan_auto_fill_respects_conflicts_and_capacity() {
+        // 2027-03-01 is a Monday
+        let day = |d| NaiveDate::from_ymd_opt(2027, 3, d).unwrap();
+        let shift = |n: u128, date, preferred: &[i32]| AutoFillCandidate {
+            uuid: Uuid::from_u128(n),
+            date,
+            start: None,
+            end: None,
+            label: "Clinic".to_string(),
+            pa_value: 1.0,
+            preferred_user_ids: preferred.to_vec(),
+        };
+        let shifts = vec![
+            shift(1, day(1), &[10, 20]),
+            // 10 already works the morning one on the 1st
+            shift(2, day(1), &[10, 20]),
+            // 20 is on leave on the 2nd
+            shift(3, day(2), &[20, 10]),
+            // 10 has used their 2 PAs for the week by now
+            shift(4, day(3), &[10]),
+            // Next week the cap resets
+            shift(5, day(8), &[10]),
+        ];
+
+        let mut busy = HashMap::from([(20, vec![busy_span(day(2), None, None)])]);
+        let mut week_pas = HashMap::new();
+        let choices = plan_auto_fill(&shifts, &mut busy, &mut week_pas, |user_id, _| (user_id == 10).then_some(2.0));
+
+        assert_eq!(choices, vec![Some(10), Some(20), Some(10), None, Some(10)]);
+        assert_eq!(week_pas[&(10, day(1))], 2.0);
+    }
+
+    #[test]
+    fn test_plan_auto_fill_sees_overnight_clashes() {
+        let day = |d| NaiveDate::from_ymd_opt(2027, 3, d).unwrap();
+        let time = |h| NaiveTime::from_hms_opt(h, 0, 0).unwrap();
+        let shift = |n: u128, date, start, end| AutoFillCandidate {
+            uuid: Uuid::from_u128(n),
+            date,
+            start: Some(time(start)),
+            end: Some(time(end)),
+            label: "Ward".to_string(),
+            pa_value: 1.0,
+            preferred_user_ids: vec![10, 20],
+        };
+        let shifts = vec![
+            // 10 works the night from the 1st into the 2nd, so cannot start at 07:00 on the 2nd
+            shift(1, day(2), 7, 12),
+            // An evening after the morning on the same day does not clash
+            shift(2, day(2), 13, 20),
+        ];
+
+        let mut busy = HashMap::from([(10, vec![busy_span(day(1), Some(time(20)), Some(time(8)))])]);
+        let choices = plan_auto_fill(&shifts, &mut busy, &mut HashMap::new(), |_, _| None);
+
+        assert_eq!(choices, vec![Some(20), Some(10)]);
+    }
+}
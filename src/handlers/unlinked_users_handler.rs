@@ -0,0 +1,412 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, HeaderValue},
+};
+use chrono::{DateTime, NaiveDateTime};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+use crate::{
+    auth::{check_email_in_clerk, create_clerk_invitation, list_clerk_users, ClerkUser, ClerkUserList},
+    extractors::{permissions, AuthenticatedUser, Json},
+    handlers::audit_handler::TRUNCATED_HEADER,
+    models::{OrphanedClerkUser, PrunedRollbackFailures, UnlinkedProfile, UnlinkedProfileAction},
+    AppError, AppResult, AppState,
+};
+
+/// Profiles that never got a Clerk account keep the temp_ auth_id they were created with; role_count counts the
+/// assignments in force on the date bound at `$today`
+fn unlinked_select(today: usize) -> String {
+    format!(
+        r#"
+    SELECT
+        u.user_profile_id,
+        u.full_name,
+        u.short_name,
+        u.primary_email,
+        u.created_at,
+        (SELECT COUNT(*) FROM "UserRoles" ur
+         WHERE ur.user_profile_id = u.user_profile_id
+           AND {ur_in_force}) AS role_count,
+        GREATEST(
+            (SELECT MAX(s.date) FROM "Shifts" s WHERE s.user_profile_id = u.user_profile_id AND s.date <= CURRENT_DATE),
+            (SELECT MAX(d.date) FROM "Diary" d
+             WHERE d.user_profile_id = u.user_profile_id AND NOT d.deleted AND d.date <= CURRENT_DATE)
+        ) AS last_activity,
+        u.invited_at,
+        u.deactivated_at
+    FROM "Users" u
+    WHERE u.auth_id LIKE 'temp\_%'
+"#,
+        ur_in_force = permissions::ur_in_force(today)
+    )
+}
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct GetUnlinkedQuery {
+    /// Also list profiles that were already deactivated
+    #[serde(rename = "includeDeactivated")]
+    pub include_deactivated: Option<bool>,
+}
+
+/// Actions that still make sense for a profile; a deactivated profile has none
+fn available_actions(profile: &UnlinkedProfile) -> Vec<UnlinkedProfileAction> {
+    if profile.deactivated_at.is_some() {
+        return Vec::new();
+    }
+    let mut actions = Vec::new();
+    if profile.primary_email.is_some() {
+        actions.push(UnlinkedProfileAction::Invite);
+    }
+    actions.push(UnlinkedProfileAction::CreateLogin);
+    actions.push(UnlinkedProfileAction::Deactivate);
+    actions
+}
+
+fn with_actions(mut profile: UnlinkedProfile) -> UnlinkedProfile {
+    profile.actions = available_actions(&profile);
+    profile
+}
+
+async fn fetch_unlinked(state: &AppState, user_profile_id: i32) -> AppResult<UnlinkedProfile> {
+    let sql = format!("{} AND u.user_profile_id = $1", unlinked_select(2));
+    let profile = sqlx::query_as::<_, UnlinkedProfile>(&sql)
+        .bind(user_profile_id)
+        .bind(permissions::today())
+        .fetch_optional(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Unlinked user profile not found".to_string()))?;
+    Ok(with_actions(profile))
+}
+
+fn require_super_admin(auth: &AuthenticatedUser) -> AppResult<()> {
+    if !auth.is_super_admin {
+        return Err(AppError::Forbidden("Super admin permission required".to_string()));
+    }
+    Ok(())
+}
+
+/// GET /api/users/unlinked?includeDeactivated= - Profiles without a Clerk account, least recently active first
+#[utoipa::path(
+    get,
+    path = "/api/users/unlinked",
+    params(GetUnlinkedQuery),
+    responses(
+        (status = 200, description = "Profiles still on a temp_ auth_id, with last activity and available actions", body = Vec<UnlinkedProfile>),
+        (status = 403, description = "Super admin permission required")
+    ),
+    tag = "users",
+    security(("cookie_auth" = []))
+)]
+pub async fn get_unlinked_profiles(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+    Query(query): Query<GetUnlinkedQuery>,
+) -> AppResult<Json<Vec<UnlinkedProfile>>> {
+    require_super_admin(&auth)?;
+
+    let sql = format!(
+        "{} AND ($1 OR u.deactivated_at IS NULL) ORDER BY last_activity ASC NULLS FIRST, u.created_at",
+        unlinked_select(2)
+    );
+    let profiles = sqlx::query_as::<_, UnlinkedProfile>(&sql)
+        .bind(query.include_deactivated.unwrap_or(false))
+        .bind(permissions::today())
+        .fetch_all(&state.db)
+        .await?;
+
+    Ok(Json(profiles.into_iter().map(with_actions).collect()))
+}
+
+/// Clerk users no profile is linked to, matched to unlinked profiles by email (lower-cased) and to recorded
+/// create-login rollback failures
+fn find_clerk_orphans(
+    clerk_users: Vec<ClerkUser>,
+    linked_auth_ids: &HashSet<String>,
+    unlinked_emails: &HashMap<String, i32>,
+    rollback_failures: &HashMap<String, NaiveDateTime>,
+) -> Vec<OrphanedClerkUser> {
+    clerk_users
+        .into_iter()
+        .filter(|user| !linked_auth_ids.contains(&user.id))
+        .map(|user| OrphanedClerkUser {
+            matching_profile_id: user.email.as_ref().and_then(|e| unlinked_emails.get(&e.to_lowercase()).copied()),
+            rollback_failed_at: rollback_failures.get(&user.id).copied(),
+            created_at: user.created_at.and_then(DateTime::from_timestamp_millis),
+            clerk_user_id: user.id,
+            email: user.email,
+            external_id: user.external_id,
+        })
+        .collect()
+}
+
+/// Clerk IDs of every account, or a conflict when the listing was cut short: an account missing from a partial
+/// listing may still exist, so nothing can be concluded from its absence
+fn complete_clerk_ids(listing: &ClerkUserList) -> AppResult<Vec<&str>> {
+    if listing.truncated {
+        return Err(AppError::Conflict(format!(
+            "Only the first {} Clerk users could be listed; nothing was removed",
+            listing.users.len()
+        )));
+    }
+    Ok(listing.users.iter().map(|u| u.id.as_str()).collect())
+}
+
+/// GET /api/users/clerk-orphans - Clerk accounts with no matching profile, e.g. left behind by a failed create-login
+#[utoipa::path(
+    get,
+    path = "/api/users/clerk-orphans",
+    responses(
+        (status = 200, description = "Clerk users whose ID is no profile's auth_id, oldest first", body = Vec<OrphanedClerkUser>,
+            headers(("x-truncated" = String, description = "\"true\" when Clerk had more users than could be listed"))),
+        (status = 403, description = "Super admin permission required"),
+        (status = 500, description = "Clerk API error")
+    ),
+    tag = "users",
+    security(("cookie_auth" = []))
+)]
+pub async fn get_clerk_orphans(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+) -> AppResult<(HeaderMap, Json<Vec<OrphanedClerkUser>>)> {
+    require_super_admin(&auth)?;
+
+    let ClerkUserList { users: clerk_users, truncated } = list_clerk_users(&state.config.clerk_secret_key).await?;
+
+    let linked_auth_ids: HashSet<String> =
+        sqlx::query_scalar(r#"SELECT auth_id FROM "Users" WHERE auth_id NOT LIKE 'temp\_%'"#)
+            .fetch_all(&state.db)
+            .await?
+            .into_iter()
+            .collect();
+    let unlinked_emails: HashMap<String, i32> = sqlx::query_as::<_, (String, i32)>(
+        r#"
+        SELECT lower(primary_email), user_profile_id
+        FROM "Users"
+        WHERE auth_id LIKE 'temp\_%' AND primary_email IS NOT NULL AND deactivated_at IS NULL
+        "#,
+    )
+    .fetch_all(&state.db)
+    .await?
+    .into_iter()
+    .collect();
+
+    let rollback_failures: HashMap<String, NaiveDateTime> =
+        sqlx::query_as::<_, (String, NaiveDateTime)>(r#"SELECT clerk_user_id, created_at FROM "ClerkRollbackFailures""#)
+            .fetch_all(&state.db)
+            .await?
+            .into_iter()
+            .collect();
+
+    let orphans = find_clerk_orphans(clerk_users, &linked_auth_ids, &unlinked_emails, &rollback_failures);
+    tracing::info!(admin_id = auth.profile_id, orphans = orphans.len(), truncated, "🔗 Clerk orphans listed");
+
+    let mut headers = HeaderMap::new();
+    if truncated {
+        headers.insert(TRUNCATED_HEADER, HeaderValue::from_static("true"));
+    }
+    Ok((headers, Json(orphans)))
+}
+
+/// POST /api/users/clerk-orphans/prune - Forget recorded rollback failures whose Clerk account has since been deleted
+#[utoipa::path(
+    post,
+    path = "/api/users/clerk-orphans/prune",
+    responses(
+        (status = 200, description = "Resolved failures removed", body = PrunedRollbackFailures),
+        (status = 403, description = "Super admin permission required"),
+        (status = 409, description = "Clerk had more users than could be listed, so nothing was removed"),
+        (status = 500, description = "Clerk API error")
+    ),
+    tag = "users",
+    security(("cookie_auth" = []))
+)]
+pub async fn prune_rollback_failures(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+) -> AppResult<Json<PrunedRollbackFailures>> {
+    require_super_admin(&auth)?;
+
+    let listing = list_clerk_users(&state.config.clerk_secret_key).await?;
+    let clerk_ids = complete_clerk_ids(&listing)?;
+
+    let removed = sqlx::query(r#"DELETE FROM "ClerkRollbackFailures" WHERE clerk_user_id <> ALL($1)"#)
+        .bind(&clerk_ids)
+        .execute(&state.db)
+        .await?
+        .rows_affected();
+
+    tracing::info!(admin_id = auth.profile_id, removed, "🧹 Resolved Clerk rollback failures pruned");
+
+    Ok(Json(PrunedRollbackFailures { removed }))
+}
+
+/// POST /api/users/unlinked/{id}/invite - Email a Clerk sign-up invitation to the profile's primary email
+#[utoipa::path(
+    post,
+    path = "/api/users/unlinked/{id}/invite",
+    params(("id" = i32, Path, description = "User profile ID")),
+    responses(
+        (status = 200, description = "Invitation sent; the account is linked by email on first sign-in", body = UnlinkedProfile),
+        (status = 400, description = "Profile has no primary email, is deactivated, or the email already has a Clerk account"),
+        (status = 403, description = "Super admin permission required"),
+        (status = 404, description = "Unlinked user profile not found")
+    ),
+    tag = "users",
+    security(("cookie_auth" = []))
+)]
+pub async fn invite_unlinked_profile(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+    Path(id): Path<i32>,
+) -> AppResult<Json<UnlinkedProfile>> {
+    require_super_admin(&auth)?;
+
+    let profile = fetch_unlinked(&state, id).await?;
+    if !profile.actions.contains(&UnlinkedProfileAction::Invite) {
+        return Err(AppError::BadRequest(
+            "Only active profiles with a primary email can be invited".to_string(),
+        ));
+    }
+    let email = profile.primary_email.as_deref().unwrap_or_default();
+
+    // An existing account links itself on its next sign-in; inviting again would not help
+    if check_email_in_clerk(email, &state.config.clerk_secret_key).await? {
+        return Err(AppError::BadRequest(
+            "Email already registered with Clerk; the profile is linked on its next sign-in".to_string(),
+        ));
+    }
+
+    create_clerk_invitation(email, &state.config.clerk_secret_key).await?;
+
+    sqlx::query(r#"UPDATE "Users" SET invited_at = NOW() WHERE user_profile_id = $1"#)
+        .bind(id)
+        .execute(&state.db)
+        .await?;
+
+    tracing::info!(user_profile_id = id, invited_by = auth.profile_id, "📨 Unlinked profile invited to sign up");
+
+    Ok(Json(fetch_unlinked(&state, id).await?))
+}
+
+/// POST /api/users/unlinked/{id}/deactivate - Retire an unlinked profile so it can no longer be linked or invited
+#[utoipa::path(
+    post,
+    path = "/api/users/unlinked/{id}/deactivate",
+    params(("id" = i32, Path, description = "User profile ID")),
+    responses(
+        (status = 200, description = "Profile deactivated", body = UnlinkedProfile),
+        (status = 403, description = "Super admin permission required"),
+        (status = 404, description = "Unlinked user profile not found"),
+        (status = 409, description = "Already deactivated, or still assigned to shifts from today onwards")
+    ),
+    tag = "users",
+    security(("cookie_auth" = []))
+)]
+pub async fn deactivate_unlinked_profile(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+    Path(id): Path<i32>,
+) -> AppResult<Json<UnlinkedProfile>> {
+    require_super_admin(&auth)?;
+
+    let profile = fetch_unlinked(&state, id).await?;
+    if profile.deactivated_at.is_some() {
+        return Err(AppError::Conflict("Profile is already deactivated".to_string()));
+    }
+
+    let upcoming_shifts: i64 = sqlx::query_scalar(
+        r#"SELECT COUNT(*) FROM "Shifts" WHERE user_profile_id = $1 AND date >= CURRENT_DATE"#,
+    )
+    .bind(id)
+    .fetch_one(&state.db)
+    .await?;
+    if upcoming_shifts > 0 {
+        return Err(AppError::Conflict(format!(
+            "Profile is assigned to {} upcoming shift(s); reassign them first",
+            upcoming_shifts
+        )));
+    }
+
+    sqlx::query(
+        r#"UPDATE "Users" SET deactivated_at = NOW(), deactivated_by = $2 WHERE user_profile_id = $1"#,
+    )
+    .bind(id)
+    .bind(auth.profile_id)
+    .execute(&state.db)
+    .await?;
+    // A cached profile would keep authenticating until it expired
+    state.caches.invalidate_profile(id);
+
+    tracing::info!(user_profile_id = id, deactivated_by = auth.profile_id, "🗄️ Unlinked profile deactivated");
+
+    Ok(Json(fetch_unlinked(&state, id).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_available_actions() {
+        let created_at = NaiveDate::from_ymd_opt(2025, 1, 1).unwrap().and_hms_opt(9, 0, 0).unwrap();
+        let mut profile = UnlinkedProfile {
+            user_profile_id: 1,
+            full_name: "Alex Smith".to_string(),
+            short_name: "AS".to_string(),
+            primary_email: None,
+            created_at,
+            role_count: 0,
+            last_activity: None,
+            invited_at: None,
+            deactivated_at: None,
+            actions: Vec::new(),
+        };
+        assert_eq!(
+            available_actions(&profile),
+            vec![UnlinkedProfileAction::CreateLogin, UnlinkedProfileAction::Deactivate]
+        );
+
+        profile.primary_email = Some("alex@example.com".to_string());
+        assert_eq!(available_actions(&profile)[0], UnlinkedProfileAction::Invite);
+
+        profile.deactivated_at = Some(created_at);
+        assert!(available_actions(&profile).is_empty());
+    }
+
+    #[test]
+    fn test_find_clerk_orphans() {
+        let clerk_user = |id: &str, email: &str| ClerkUser {
+            id: id.to_string(),
+            email: Some(email.to_string()),
+            external_id: None,
+            created_at: Some(1_760_000_000_000),
+        };
+        let failed_at = NaiveDate::from_ymd_opt(2026, 10, 1).unwrap().and_hms_opt(9, 0, 0).unwrap();
+
+        let orphans = find_clerk_orphans(
+            vec![clerk_user("user_linked", "a@example.org"), clerk_user("user_new", "B@example.org"), clerk_user("user_failed", "c@example.org")],
+            &HashSet::from(["user_linked".to_string()]),
+            &HashMap::from([("b@example.org".to_string(), 7)]),
+            &HashMap::from([("user_failed".to_string(), failed_at)]),
+        );
+
+        assert_eq!(orphans.iter().map(|o| o.clerk_user_id.as_str()).collect::<Vec<_>>(), vec!["user_new", "user_failed"]);
+        assert_eq!((orphans[0].matching_profile_id, orphans[0].rollback_failed_at), (Some(7), None));
+        assert_eq!((orphans[1].matching_profile_id, orphans[1].rollback_failed_at), (None, Some(failed_at)));
+        assert!(orphans[0].created_at.is_some());
+    }
+
+    #[test]
+    fn test_truncated_listing_prunes_nothing() {
+        let user = |id: &str| ClerkUser { id: id.to_string(), email: None, external_id: None, created_at: None };
+        let complete = ClerkUserList { users: vec![user("user_a"), user("user_b")], truncated: false };
+        assert_eq!(complete_clerk_ids(&complete).unwrap(), vec!["user_a", "user_b"]);
+
+        let partial = ClerkUserList { truncated: true, ..complete };
+        assert!(matches!(complete_clerk_ids(&partial), Err(AppError::Conflict(_))));
+    }
+}
@@ -0,0 +1,258 @@
+use axum::extract::{Query, State};
+use serde::Deserialize;
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+use crate::{
+    extractors::{permissions, CanEditStaff, RequirePermission},
+    handlers::custom_fields_handler,
+    models::{CustomFieldValue, UserExportRow},
+    report::{Report, ReportFormat},
+    AppError, AppResult, AppState,
+};
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ExportUsersQuery {
+    /// Only this role's members; otherwise every role the caller can edit staff in
+    #[serde(rename = "roleId")]
+    pub role_id: Option<i32>,
+}
+
+/// Blank out emails and phone numbers the caller may not see: kept for themselves and for staff sharing a
+/// role they can view staff details in (`viewable` lists the users in those roles)
+fn redact_contacts(rows: &mut [UserExportRow], viewer_id: i32, viewable: &[i32]) {
+    for row in rows.iter_mut() {
+        if row.user_profile_id != viewer_id && !viewable.contains(&row.user_profile_id) {
+            row.primary_email = None;
+            row.secondary_emails = None;
+            row.tel = None;
+            row.contact_details_redacted = true;
+        }
+    }
+}
+
+/// Attach each user's custom field values: those of the row's workplace, or all of them on rows without a role
+fn fill_custom_fields(rows: &mut [UserExportRow], values: &[(i32, CustomFieldValue)]) {
+    for row in rows.iter_mut() {
+        row.custom_fields = values
+            .iter()
+            .filter(|(user_id, value)| {
+                *user_id == row.user_profile_id && row.workplace_id.is_none_or(|w| w == value.workplace_id)
+            })
+            .map(|(_, value)| (value.key.clone(), value.value.clone()))
+            .collect();
+    }
+}
+
+/// GET /api/users/export?roleId=&format=csv - Users with their active roles, permissions and job plans, for HR
+#[utoipa::path(
+    get,
+    path = "/api/users/export",
+    params(
+        ExportUsersQuery,
+        ("format" = Option<String>, Query, description = "json, csv or pdf; overrides the Accept header")
+    ),
+    responses(
+        (status = 200, description = "One row per user and active role assignment, ordered by name", content(
+            (Vec<UserExportRow> = "application/json"),
+            (String = "text/csv"),
+            (Vec<u8> = "application/pdf")
+        )),
+        (status = 400, description = "Unknown format"),
+        (status = 403, description = "Missing can_edit_staff permission (for roleId, in that role)"),
+        (status = 406, description = "Accept names no supported format")
+    ),
+    tag = "users",
+    security(("cookie_auth" = ["can_edit_staff"]))
+)]
+pub async fn export_users(
+    State(state): State<Arc<AppState>>,
+    RequirePermission { auth, .. }: RequirePermission<CanEditStaff>,
+    format: ReportFormat,
+    Query(query): Query<ExportUsersQuery>,
+) -> AppResult<Report<Vec<UserExportRow>>> {
+    // Super admins may export everyone, including users without a role; others only the roles they manage
+    let scope: Option<Vec<i32>> = if auth.is_super_admin {
+        query.role_id.map(|role_id| vec![role_id])
+    } else {
+        let editable = permissions::roles_with_permission(&state, auth.profile_id, permissions::can_edit_staff).await?;
+        match query.role_id {
+            Some(role_id) if !editable.contains(&role_id) => {
+                return Err(AppError::Forbidden("Missing can_edit_staff permission for this role".to_string()));
+            }
+            Some(role_id) => Some(vec![role_id]),
+            None => Some(editable),
+        }
+    };
+
+    let mut rows = sqlx::query_as::<_, UserExportRow>(&format!(
+        r#"
+        SELECT
+            u.user_profile_id,
+            u.full_name,
+            u.short_name,
+            u.gmc,
+            u.primary_email,
+            u.secondary_emails,
+            u.tel,
+            u.is_generic_login,
+            u.created_at,
+            u.deactivated_at,
+            r.id AS role_id,
+            r.role_name,
+            w.id AS workplace_id,
+            w.hospital,
+            w.ward,
+            ur.can_edit_rota,
+            ur.can_access_diary,
+            ur.can_work_shifts,
+            ur.can_edit_templates,
+            ur.can_edit_staff,
+            ur.can_view_staff_details,
+            ur.can_approve_rota,
+            ur.can_set_pay,
+            jp."from" AS job_plan_from,
+            jp.until AS job_plan_until,
+            jp.dcc_pa AS job_plan_dcc_pa,
+            jp.spa_pa AS job_plan_spa_pa,
+            jp.al_per_year AS job_plan_al_per_year,
+            (
+                SELECT MAX(s.date) FROM "Shifts" s
+                WHERE s.user_profile_id = u.user_profile_id
+                  AND s.role_id = r.id
+                  AND s.date <= CURRENT_DATE
+                  AND s.time_off_category_id IS NULL
+            ) AS last_shift_date
+        FROM "Users" u
+        LEFT JOIN (
+            "UserRoles" ur
+            INNER JOIN "Roles" r ON r.id = ur.role_id AND NOT r.archived
+            INNER JOIN "Workplaces" w ON w.id = r.workplace_id
+        ) ON ur.user_profile_id = u.user_profile_id AND {ur_in_force} AND ($1::int4[] IS NULL OR ur.role_id = ANY($1))
+        LEFT JOIN LATERAL (
+            SELECT "from", until, dcc_pa, spa_pa, al_per_year
+            FROM "JobPlans"
+            WHERE user_profile_id = u.user_profile_id
+              AND role_id = r.id
+              AND "from" <= CURRENT_DATE
+              AND (until IS NULL OR until >= CURRENT_DATE)
+            ORDER BY "from" DESC
+            LIMIT 1
+        ) jp ON TRUE
+        WHERE $1::int4[] IS NULL OR r.id IS NOT NULL
+        ORDER BY u.full_name, u.user_profile_id, r.role_name
+        "#,
+        ur_in_force = crate::extractors::permissions::ur_in_force(2)
+    ))
+    .bind(&scope)
+    .bind(crate::extractors::permissions::today())
+    .fetch_all(&state.db)
+    .await?;
+
+    if !auth.is_super_admin {
+        let detail_roles =
+            permissions::roles_with_permission(&state, auth.profile_id, permissions::can_view_staff_details).await?;
+        let exported: Vec<i32> = rows.iter().map(|r| r.user_profile_id).collect();
+        let viewable: Vec<i32> = sqlx::query_scalar(&format!(
+            r#"SELECT DISTINCT user_profile_id FROM "UserRoles" WHERE role_id = ANY($1) AND user_profile_id = ANY($2) AND {in_force}"#,
+            in_force = crate::extractors::permissions::user_role_in_force(3)
+        ))
+        .bind(&detail_roles)
+        .bind(&exported)
+        .bind(crate::extractors::permissions::today())
+        .fetch_all(&state.db)
+        .await?;
+        redact_contacts(&mut rows, auth.profile_id, &viewable);
+    }
+
+    let mut exported: Vec<i32> = rows.iter().map(|r| r.user_profile_id).collect();
+    exported.dedup();
+    let values = custom_fields_handler::custom_field_values(&state.db, &exported).await?;
+    fill_custom_fields(&mut rows, &values);
+
+    tracing::info!(
+        admin_id = auth.profile_id,
+        role_id = ?query.role_id,
+        rows = rows.len(),
+        "📤 Users exported"
+    );
+
+    let title = match query.role_id {
+        Some(role_id) => format!("Users role {}", role_id),
+        None => "Users".to_string(),
+    };
+    Ok(Report::new(format, title, rows))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::encrypted::EncryptedString;
+
+    fn row(user_profile_id: i32) -> UserExportRow {
+        UserExportRow {
+            user_profile_id,
+            full_name: format!("User {}", user_profile_id),
+            short_name: format!("U{}", user_profile_id),
+            gmc: None,
+            primary_email: Some(format!("u{}@example.org", user_profile_id)),
+            secondary_emails: None,
+            tel: Some(vec![EncryptedString("07700 900000".to_string())]),
+            contact_details_redacted: false,
+            is_generic_login: false,
+            created_at: chrono::NaiveDateTime::default(),
+            deactivated_at: None,
+            role_id: Some(1),
+            role_name: None,
+            hospital: None,
+            ward: None,
+            can_edit_rota: Some(false),
+            can_access_diary: Some(false),
+            can_work_shifts: Some(true),
+            can_edit_templates: Some(false),
+            can_edit_staff: Some(false),
+            can_view_staff_details: Some(false),
+            can_approve_rota: Some(false),
+            can_set_pay: Some(false),
+            job_plan_from: None,
+            job_plan_until: None,
+            job_plan_dcc_pa: None,
+            job_plan_spa_pa: None,
+            job_plan_al_per_year: None,
+            last_shift_date: None,
+            workplace_id: Some(1),
+            custom_fields: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_redact_contacts() {
+        let mut rows = vec![row(1), row(2), row(3)];
+        redact_contacts(&mut rows, 1, &[2]);
+
+        assert!(rows[0].primary_email.is_some() && !rows[0].contact_details_redacted); // the caller
+        assert!(rows[1].tel.is_some() && !rows[1].contact_details_redacted); // viewable
+        assert!(rows[2].primary_email.is_none() && rows[2].tel.is_none() && rows[2].contact_details_redacted);
+    }
+    #[test]
+    fn test_fill_custom_fields() {
+        let value = |workplace_id: i32, key: &str| CustomFieldValue {
+            definition_id: workplace_id,
+            workplace_id,
+            key: key.to_string(),
+            label: key.to_string(),
+            field_type: crate::models::CustomFieldType::Text,
+            value: serde_json::json!(format!("{} {}", key, workplace_id)),
+        };
+        let values = vec![(1, value(1, "bleep")), (1, value(2, "smart_card")), (2, value(1, "bleep"))];
+        let mut unscoped = row(1);
+        unscoped.role_id = None;
+        unscoped.workplace_id = None;
+        let mut rows = vec![row(1), unscoped, row(3)];
+        fill_custom_fields(&mut rows, &values);
+
+        assert_eq!(rows[0].custom_fields.keys().collect::<Vec<_>>(), ["bleep"]);
+        assert_eq!(rows[1].custom_fields.len(), 2);
+        assert!(rows[2].custom_fields.is_empty());
+    }
+}
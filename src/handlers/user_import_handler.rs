@@ -0,0 +1,320 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+use crate::{
+    extractors::{CanEditStaff, RequirePermission},
+    models::{ImportRowError, ImportUsersResponse, ImportedUser},
+    AppError, AppResult, AppState,
+};
+
+/// Upper bound on rows per import, to keep the transaction short
+const MAX_IMPORT_ROWS: usize = 500;
+
+const REQUIRED_COLUMNS: [&str; 2] = ["full_name", "short_name"];
+
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct ImportUsersQuery {
+    /// Validate only, do not create anything
+    #[serde(rename = "dryRun")]
+    pub dry_run: Option<bool>,
+}
+
+/// A CSV row that passed field-level validation
+#[derive(Debug)]
+struct ParsedRow {
+    row: usize,
+    full_name: String,
+    short_name: String,
+    email: Option<String>,
+    gmc: Option<i32>,
+    role_ids: Vec<i32>,
+}
+
+/// POST /api/users/import?dryRun= - Create user profiles (and role assignments) from a CSV
+///
+/// Columns (header row required, any order): full_name, short_name, email, gmc, roles.
+/// `roles` is a semicolon-separated list of role IDs, e.g. `3;7`.
+#[utoipa::path(
+    post,
+    path = "/api/users/import",
+    params(ImportUsersQuery),
+    request_body(content = String, content_type = "text/csv", description = "CSV with header: full_name,short_name,email,gmc,roles"),
+    responses(
+        (status = 200, description = "Import report; success is false and nothing is written if any row is invalid", body = ImportUsersResponse),
+        (status = 400, description = "Malformed CSV, missing required columns or too many rows"),
+        (status = 403, description = "Missing can_edit_staff permission")
+    ),
+    tag = "users",
+    security(("cookie_auth" = ["can_edit_staff"]))
+)]
+pub async fn import_users(
+    State(state): State<Arc<AppState>>,
+    RequirePermission { auth, .. }: RequirePermission<CanEditStaff>,
+    Query(query): Query<ImportUsersQuery>,
+    body: String,
+) -> AppResult<Json<ImportUsersResponse>> {
+    let dry_run = query.dry_run.unwrap_or(false);
+    let (rows, mut errors, total_rows) = parse_import_csv(&body)?;
+
+    // Emails already used by existing profiles
+    let emails: Vec<String> = rows.iter().filter_map(|r| r.email.as_ref().map(|e| e.to_lowercase())).collect();
+    let taken_emails: HashSet<String> = sqlx::query_scalar::<_, String>(
+        r#"SELECT LOWER(primary_email) FROM "Users" WHERE LOWER(primary_email) = ANY($1)"#,
+    )
+    .bind(&emails)
+    .fetch_all(&state.db)
+    .await?
+    .into_iter()
+    .collect();
+
+    // Referenced roles must exist
+    let role_ids: Vec<i32> = rows.iter().flat_map(|r| r.role_ids.iter().copied()).collect::<HashSet<_>>().into_iter().collect();
+    let known_roles: HashSet<i32> = sqlx::query_scalar::<_, i32>(r#"SELECT id::int4 FROM "Roles" WHERE id = ANY($1)"#)
+        .bind(&role_ids)
+        .fetch_all(&state.db)
+        .await?
+        .into_iter()
+        .collect();
+
+    for row in &rows {
+        if let Some(ref email) = row.email {
+            if taken_emails.contains(&email.to_lowercase()) {
+                errors.push(row_error(row.row, Some("email"), format!("Email {} is already used by another profile", email)));
+            }
+        }
+        for role_id in &row.role_ids {
+            if !known_roles.contains(role_id) {
+                errors.push(row_error(row.row, Some("roles"), format!("Role {} does not exist", role_id)));
+            }
+        }
+    }
+
+    errors.sort_by_key(|e| e.row);
+
+    let mut users: Vec<ImportedUser> = rows
+        .iter()
+        .map(|r| ImportedUser {
+            row: r.row,
+            user_profile_id: None,
+            full_name: r.full_name.clone(),
+            role_ids: r.role_ids.clone(),
+        })
+        .collect();
+
+    if !errors.is_empty() || dry_run {
+        return Ok(Json(ImportUsersResponse {
+            success: errors.is_empty(),
+            dry_run,
+            total_rows,
+            users,
+            errors,
+        }));
+    }
+
+    // All rows valid: create everything or nothing
+    let mut tx = crate::db::begin_as_user(&state.db, auth.profile_id).await?;
+
+    for (row, imported) in rows.iter().zip(users.iter_mut()) {
+        let user_profile_id: i32 = sqlx::query_scalar(
+            r#"
+            INSERT INTO "Users" (auth_id, full_name, short_name, gmc, primary_email, is_generic_login)
+            VALUES ($1, $2, $3, $4, $5, false)
+            RETURNING user_profile_id
+            "#,
+        )
+        .bind(format!("temp_{}", uuid::Uuid::new_v4()))
+        .bind(&row.full_name)
+        .bind(&row.short_name)
+        .bind(row.gmc)
+        .bind(&row.email)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        for role_id in &row.role_ids {
+            sqlx::query(r#"INSERT INTO "UserRoles" (role_id, user_profile_id, can_work_shifts) VALUES ($1, $2, true)"#)
+                .bind(role_id)
+                .bind(user_profile_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        imported.user_profile_id = Some(user_profile_id);
+    }
+
+    tx.commit().await.map_err(|e| {
+        tracing::error!(error = %e, rows = rows.len(), "❌ Transaction rollback in import_users");
+        AppError::Internal(format!("Failed to commit user import: {}", e))
+    })?;
+
+    tracing::info!(
+        created = users.len(),
+        imported_by = auth.profile_id,
+        "📥 Users imported from CSV"
+    );
+    state.caches.invalidate_roles().await;
+
+    Ok(Json(ImportUsersResponse {
+        success: true,
+        dry_run,
+        total_rows,
+        users,
+        errors,
+    }))
+}
+
+fn row_error(row: usize, field: Option<&str>, message: String) -> ImportRowError {
+    ImportRowError {
+        row,
+        field: field.map(String::from),
+        message,
+    }
+}
+
+/// Parse and field-validate the CSV. Returns valid rows, per-row errors and the total row count.
+fn parse_import_csv(body: &str) -> AppResult<(Vec<ParsedRow>, Vec<ImportRowError>, usize)> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .flexible(true)
+        .from_reader(body.as_bytes());
+
+    let headers = reader
+        .headers()
+        .map_err(|e| AppError::BadRequest(format!("Invalid CSV header: {}", e)))?;
+    let columns: HashMap<String, usize> = headers
+        .iter()
+        .enumerate()
+        .map(|(i, h)| (h.to_lowercase(), i))
+        .collect();
+
+    for required in REQUIRED_COLUMNS {
+        if !columns.contains_key(required) {
+            return Err(AppError::BadRequest(format!("Missing required column: {}", required)));
+        }
+    }
+
+    let mut rows = vec![];
+    let mut errors = vec![];
+    let mut seen_emails: HashMap<String, usize> = HashMap::new();
+    let mut total_rows = 0;
+
+    for (index, record) in reader.records().enumerate() {
+        let row = index + 1;
+        total_rows = row;
+
+        if row > MAX_IMPORT_ROWS {
+            return Err(AppError::BadRequest(format!("Too many rows (max {})", MAX_IMPORT_ROWS)));
+        }
+
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                errors.push(row_error(row, None, format!("Unreadable row: {}", e)));
+                continue;
+            }
+        };
+
+        let field = |name: &str| -> Option<String> {
+            columns
+                .get(name)
+                .and_then(|&i| record.get(i))
+                .filter(|v| !v.is_empty())
+                .map(String::from)
+        };
+
+        let row_errors_before = errors.len();
+
+        let full_name = field("full_name");
+        if full_name.is_none() {
+            errors.push(row_error(row, Some("full_name"), "full_name is required".to_string()));
+        }
+        let short_name = field("short_name");
+        if short_name.is_none() {
+            errors.push(row_error(row, Some("short_name"), "short_name is required".to_string()));
+        }
+
+        let email = field("email");
+        if let Some(ref email) = email {
+            if !email.contains('@') || email.contains(char::is_whitespace) {
+                errors.push(row_error(row, Some("email"), format!("Invalid email: {}", email)));
+            } else if let Some(first_row) = seen_emails.insert(email.to_lowercase(), row) {
+                errors.push(row_error(row, Some("email"), format!("Duplicate email, also on row {}", first_row)));
+            }
+        }
+
+        let gmc = match field("gmc").map(|g| g.parse::<i32>()) {
+            None => None,
+            Some(Ok(gmc)) => Some(gmc),
+            Some(Err(_)) => {
+                errors.push(row_error(row, Some("gmc"), "gmc must be a number".to_string()));
+                None
+            }
+        };
+
+        let mut role_ids = vec![];
+        for part in field("roles").unwrap_or_default().split(';').map(str::trim).filter(|p| !p.is_empty()) {
+            match part.parse::<i32>() {
+                Ok(id) if !role_ids.contains(&id) => role_ids.push(id),
+                Ok(_) => {}
+                Err(_) => errors.push(row_error(row, Some("roles"), format!("Invalid role ID: {}", part))),
+            }
+        }
+
+        if errors.len() == row_errors_before {
+            if let (Some(full_name), Some(short_name)) = (full_name, short_name) {
+                rows.push(ParsedRow {
+                    row,
+                    full_name,
+                    short_name,
+                    email,
+                    gmc,
+                    role_ids,
+                });
+            }
+        }
+    }
+
+    if total_rows == 0 {
+        return Err(AppError::BadRequest("CSV contains no data rows".to_string()));
+    }
+
+    Ok((rows, errors, total_rows))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_valid_rows() {
+        let csv = "full_name,short_name,email,gmc,roles\nJane Doe,JD,jane@nhs.net,1234567,3;7\nJohn Roe,JR,,,\n";
+        let (rows, errors, total) = parse_import_csv(csv).unwrap();
+
+        assert!(errors.is_empty());
+        assert_eq!(total, 2);
+        assert_eq!(rows[0].role_ids, vec![3, 7]);
+        assert_eq!(rows[0].gmc, Some(1234567));
+        assert_eq!(rows[1].email, None);
+    }
+
+    #[test]
+    fn test_parse_reports_row_errors() {
+        let csv = "short_name,full_name,email,gmc\nA,Alice,a@x.org,abc\n,Bob,A@X.org,\n";
+        let (rows, errors, _) = parse_import_csv(csv).unwrap();
+
+        assert!(rows.is_empty());
+        assert_eq!(errors.len(), 3);
+        assert_eq!(errors[0].field.as_deref(), Some("gmc"));
+        assert!(errors.iter().any(|e| e.row == 2 && e.field.as_deref() == Some("email")));
+    }
+
+    #[test]
+    fn test_parse_requires_columns() {
+        assert!(parse_import_csv("full_name,email\nJane,j@x.org\n").is_err());
+    }
+}
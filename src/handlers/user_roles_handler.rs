@@ -1,24 +1,67 @@
 use axum::{
     extract::{Path, Query, State},
-    Json,
 };
-use chrono::NaiveDateTime;
+use chrono::{DateTime, NaiveDate, NaiveDateTime};
 use serde::Deserialize;
 use sqlx::FromRow;
 use std::sync::Arc;
 use utoipa::IntoParams;
+use uuid::Uuid;
 
 use crate::{
-    extractors::{permissions, AuthenticatedUser},
-    models::{CreateUserRoleInput, Role, UpdateUserRoleInput, UserRole, UserRoleMutationResponse, Workplace},
+    extractors::{permissions, AuthenticatedUser, CanEditStaff, Json, RequirePermission},
+    models::{
+        BulkUpdateUserRolesInput, BulkUpdateUserRolesResponse, CreateUserRoleInput, PermissionChange,
+        PermissionFlagsPatch, Role, UpdateUserRoleInput, UserRole, UserRoleMutationResponse,
+        UserRolePermissionChanges, Workplace,
+    },
     AppError, AppResult, AppState,
 };
 
+/// Most profiles one bulk update may name
+const MAX_BULK_USER_ROLES: usize = 500;
+
 #[derive(Debug, Deserialize, IntoParams)]
 pub struct GetUserRolesQuery {
     pub user_profile_id: Option<i32>,
 }
 
+#[derive(Debug, Deserialize, IntoParams)]
+pub struct DeleteUserRoleQuery {
+    /// Remove access now (emergencies) instead of at the end of the day
+    #[serde(default)]
+    pub immediate: bool,
+    /// Last day of access (YYYY-MM-DD, today or later); defaults to today
+    pub until: Option<NaiveDate>,
+}
+
+/// Last day a revoked assignment keeps access: `until`, or the rest of today
+pub fn revocation_last_day(until: Option<NaiveDate>, today: NaiveDate) -> AppResult<NaiveDate> {
+    match until {
+        Some(until) if until < today => Err(AppError::BadRequest(format!("until ({}) is in the past", until))),
+        Some(until) => Ok(until),
+        None => Ok(today),
+    }
+}
+
+/// The role an edit newly gives can_set_pay in, if any: switching it on, or moving an assignment that has it to
+/// another role. `current` is the assignment's role and can_set_pay before the edit.
+fn pay_granted_in(current: (i32, bool), role_id: Option<i32>, can_set_pay: Option<bool>) -> Option<i32> {
+    let (current_role, current_pay) = current;
+    let target = role_id.unwrap_or(current_role);
+    (can_set_pay.unwrap_or(current_pay) && (!current_pay || target != current_role)).then_some(target)
+}
+
+/// Granting can_set_pay is as sensitive as setting pay, so only those who hold it in the role (or super admins) may
+async fn ensure_can_grant_pay(state: &AppState, auth: &AuthenticatedUser, role_id: i32) -> AppResult<()> {
+    if !permissions::has_permission(state, auth.profile_id, auth.is_super_admin, |r| r.role_id == role_id && r.can_set_pay)
+        .await?
+    {
+        return Err(AppError::Forbidden("Missing can_set_pay permission to grant it in this role".to_string()));
+    }
+    Ok(())
+}
+
 #[derive(Debug, FromRow)]
 struct UserRoleQueryRow {
     id: i32,
@@ -30,6 +73,9 @@ struct UserRoleQueryRow {
     can_edit_templates: bool,
     can_edit_staff: bool,
     can_view_staff_details: bool,
+    can_approve_rota: bool,
+    can_set_pay: bool,
+    effective_until: Option<NaiveDate>,
     created_at: NaiveDateTime,
     r_id: Option<i32>,
     r_workplace: Option<i32>,
@@ -41,6 +87,70 @@ struct UserRoleQueryRow {
     w_code: Option<String>,
 }
 
+/// Every role (archived included) with its workplace, for a super admin's synthetic assignments; cached until roles
+/// or workplaces change
+async fn super_admin_roles(state: &AppState) -> AppResult<Vec<Role>> {
+    if let Some(cached) = state.caches.super_admin_roles.get().await {
+        return Ok(cached);
+    }
+
+    #[derive(FromRow)]
+    struct RoleWorkplaceRow {
+        id: i32,
+        workplace_id: Option<i32>,
+        role_name: Option<String>,
+        w_id: Option<i32>,
+        w_hospital: Option<String>,
+        w_ward: Option<String>,
+        w_address: Option<String>,
+        w_code: Option<String>,
+    }
+
+    let rows = sqlx::query_as::<_, RoleWorkplaceRow>(
+        r#"
+        SELECT
+            r.id::int4 AS id,
+            r.workplace_id::int4 AS workplace_id,
+            r.role_name,
+            w.id::int4 AS w_id,
+            w.hospital AS w_hospital,
+            w.ward AS w_ward,
+            w.address AS w_address,
+            w.code AS w_code
+        FROM "Roles" r
+        LEFT JOIN "Workplaces" w ON r.workplace_id = w.id
+        ORDER BY r.id
+        "#,
+    )
+    .fetch_all(&state.db)
+    .await?;
+
+    let roles: Vec<Role> = rows
+        .into_iter()
+        .map(|row| Role {
+            id: row.id,
+            workplace: row.workplace_id.unwrap_or(0),
+            role_name: row.role_name.unwrap_or_default(),
+            marketplace_auto_approve: None,
+            publish_requires_approval: None,
+            max_swaps_per_month: None,
+            edit_reason_required: None,
+            archived: None,
+            active_staff: None,
+            workplaces: row.w_id.map(|w_id| Workplace {
+                id: w_id,
+                hospital: row.w_hospital,
+                ward: row.w_ward,
+                address: row.w_address,
+                code: row.w_code,
+            }),
+        })
+        .collect();
+
+    state.caches.super_admin_roles.insert(roles.clone()).await;
+    Ok(roles)
+}
+
 /// GET /api/user-roles?user_profile_id=
 #[utoipa::path(
     get,
@@ -66,7 +176,7 @@ pub async fn get_user_roles(
 
     if !is_viewing_self {
         let has_perm = permissions::has_permission(
-            &state.db,
+            &state,
             auth.profile_id,
             auth.is_super_admin,
             permissions::can_edit_staff,
@@ -84,6 +194,10 @@ pub async fn get_user_roles(
     // Run super admin check and roles query IN PARALLEL (not sequential!)
     let (is_target_super_admin, actual_user_roles) = tokio::try_join!(
         async {
+            // The extractor already knows whether the caller is a super admin
+            if is_viewing_self {
+                return Ok(auth.is_super_admin);
+            }
             sqlx::query_scalar::<_, bool>(
                 r#"SELECT is_super_admin FROM "Users" WHERE user_profile_id = $1"#
             )
@@ -93,7 +207,8 @@ pub async fn get_user_roles(
             .map(|opt| opt.unwrap_or(false))
         },
         async {
-            let query_str = r#"
+            let query_str = format!(
+                r#"
                 SELECT
                     ur.id::int4,
                     ur.role_id::int4,
@@ -104,6 +219,9 @@ pub async fn get_user_roles(
                     ur.can_edit_templates,
                     ur.can_edit_staff,
                     ur.can_view_staff_details,
+                    ur.can_approve_rota,
+                    ur.can_set_pay,
+                    ur.effective_until,
                     ur.created_at,
                     r.id::int4 AS r_id,
                     r.workplace_id::int4 AS r_workplace,
@@ -117,10 +235,14 @@ pub async fn get_user_roles(
                 LEFT JOIN "Roles" r ON ur.role_id = r.id
                 LEFT JOIN "Workplaces" w ON r.workplace_id = w.id
                 WHERE ur.user_profile_id = $1
+                  AND {ur_in_force}
                 ORDER BY ur.id
-            "#;
-            sqlx::query_as::<_, UserRoleQueryRow>(query_str)
+            "#,
+                ur_in_force = crate::extractors::permissions::ur_in_force(2)
+            );
+            sqlx::query_as::<_, UserRoleQueryRow>(&query_str)
                 .bind(target_user_id)
+                .bind(crate::extractors::permissions::today())
                 .fetch_all(&state.db)
                 .await
         }
@@ -138,12 +260,20 @@ pub async fn get_user_roles(
             can_edit_templates: row.can_edit_templates,
             can_edit_staff: row.can_edit_staff,
             can_view_staff_details: row.can_view_staff_details,
+            can_approve_rota: row.can_approve_rota,
+            can_set_pay: row.can_set_pay,
+            effective_until: row.effective_until,
             created_at: row.created_at,
             roles: row.r_id.map(|id| Role {
                 id,
                 workplace: row.r_workplace.unwrap_or(0),
                 role_name: row.r_role_name.clone().unwrap_or_default(),
                 marketplace_auto_approve: None,  // Not fetched in UserRoles query
+                publish_requires_approval: None,
+                max_swaps_per_month: None,
+                edit_reason_required: None,
+                archived: None,
+                active_staff: None,
                 workplaces: row.w_id.map(|w_id| Workplace {
                     id: w_id,
                     hospital: row.w_hospital.clone(),
@@ -168,65 +298,26 @@ pub async fn get_user_roles(
             .map(|row| row.role_id)
             .collect();
 
-        // Fetch all roles that user doesn't have
-        let all_roles = sqlx::query_as::<_, UserRoleQueryRow>(
-            r#"
-            SELECT
-                r.id::int4 AS id,
-                r.id::int4 AS role_id,
-                $1::int4 AS user_profile_id,
-                true AS can_edit_rota,
-                true AS can_access_diary,
-                true AS can_work_shifts,
-                true AS can_edit_templates,
-                true AS can_edit_staff,
-                true AS can_view_staff_details,
-                '1970-01-01 00:00:00'::timestamp AS created_at,
-                r.id::int4 AS r_id,
-                r.workplace_id::int4 AS r_workplace,
-                r.role_name AS r_role_name,
-                w.id::int4 AS w_id,
-                w.hospital AS w_hospital,
-                w.ward AS w_ward,
-                w.address AS w_address,
-                w.code AS w_code
-            FROM "Roles" r
-            LEFT JOIN "Workplaces" w ON r.workplace_id = w.id
-            ORDER BY r.id
-            "#,
-        )
-        .bind(target_user_id)
-        .fetch_all(&state.db)
-        .await?;
-
         // Create synthetic UserRole objects for roles not already assigned
-        let synthetic_roles: Vec<UserRole> = all_roles
-            .iter()
-            .filter(|row| !existing_role_ids.contains(&row.role_id))
-            .map(|row| UserRole {
-                id: row.id,
-                role_id: row.role_id,
-                user_profile_id: row.user_profile_id,
+        let synthetic_roles: Vec<UserRole> = super_admin_roles(&state)
+            .await?
+            .into_iter()
+            .filter(|role| !existing_role_ids.contains(&role.id))
+            .map(|role| UserRole {
+                id: role.id,
+                role_id: role.id,
+                user_profile_id: target_user_id,
                 can_edit_rota: true,
                 can_access_diary: true,
                 can_work_shifts: true,
                 can_edit_templates: true,
                 can_edit_staff: true,
                 can_view_staff_details: true,
-                created_at: row.created_at,
-                roles: row.r_id.map(|id| Role {
-                    id,
-                    workplace: row.r_workplace.unwrap_or(0),
-                    role_name: row.r_role_name.clone().unwrap_or_default(),
-                    marketplace_auto_approve: None,
-                    workplaces: row.w_id.map(|w_id| Workplace {
-                        id: w_id,
-                        hospital: row.w_hospital.clone(),
-                        ward: row.w_ward.clone(),
-                        address: row.w_address.clone(),
-                        code: row.w_code.clone(),
-                    }),
-                }),
+                can_approve_rota: true,
+                can_set_pay: true,
+                effective_until: None,
+                created_at: DateTime::UNIX_EPOCH.naive_utc(),
+                roles: Some(role),
             })
             .collect();
 
@@ -252,23 +343,16 @@ pub async fn get_user_roles(
     request_body = CreateUserRoleInput,
     responses(
         (status = 200, description = "User role created successfully", body = UserRole),
-        (status = 403, description = "Missing can_edit_staff permission")
+        (status = 403, description = "Missing can_edit_staff permission, or can_set_pay in the role to grant it")
     ),
     tag = "user-roles",
-    security(("cookie_auth" = []))
+    security(("cookie_auth" = ["can_edit_staff"]))
 )]
 pub async fn create_user_role(
     State(state): State<Arc<AppState>>,
-    auth: AuthenticatedUser,
+    RequirePermission { auth, .. }: RequirePermission<CanEditStaff>,
     Json(input): Json<CreateUserRoleInput>,
 ) -> AppResult<Json<UserRole>> {
-    // Check permission
-    if !crate::extractors::permissions::has_permission_by_name(&state.db, auth.profile_id, auth.is_super_admin, "can_edit_staff").await? {
-        return Err(AppError::Forbidden(
-            "Missing can_edit_staff permission".to_string(),
-        ));
-    }
-
     // Check for duplicate assignment
     let existing: Option<i32> = sqlx::query_scalar(
         r#"SELECT id FROM "UserRoles" WHERE user_profile_id = $1 AND role_id = $2"#
@@ -300,14 +384,20 @@ pub async fn create_user_role(
         ));
     }
 
-    // Insert the new user role
+    if input.can_set_pay {
+        ensure_can_grant_pay(&state, &auth, input.role_id).await?;
+    }
+
+    // Insert the new user role (acting user recorded for the role history trigger)
+    let mut tx = crate::db::begin_as_user(&state.db, auth.profile_id).await?;
     let user_role_id: i32 = sqlx::query_scalar(
         r#"
         INSERT INTO "UserRoles" (
             role_id, user_profile_id, can_edit_rota, can_access_diary,
-            can_work_shifts, can_edit_templates, can_edit_staff, can_view_staff_details
+            can_work_shifts, can_edit_templates, can_edit_staff, can_view_staff_details,
+            can_approve_rota, can_set_pay
         )
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
         RETURNING id
         "#,
     )
@@ -319,12 +409,17 @@ pub async fn create_user_role(
     .bind(input.can_edit_templates)
     .bind(input.can_edit_staff)
     .bind(input.can_view_staff_details)
-    .fetch_one(&state.db)
+    .bind(input.can_approve_rota)
+    .bind(input.can_set_pay)
+    .fetch_one(&mut *tx)
     .await?;
+    tx.commit().await?;
 
     // Fetch the created user role with joined data
     let user_role = fetch_user_role_by_id(&state.db, user_role_id).await?;
 
+    // Their permissions changed, and role listings carry active staff counts
+    state.caches.invalidate_user_roles(user_role.user_profile_id).await;
     Ok(Json(user_role))
 }
 
@@ -339,25 +434,18 @@ pub async fn create_user_role(
     responses(
         (status = 200, description = "User role updated successfully", body = UserRole),
         (status = 400, description = "No fields to update"),
-        (status = 403, description = "Missing can_edit_staff permission"),
+        (status = 403, description = "Missing can_edit_staff permission, or can_set_pay in the role to grant it"),
         (status = 404, description = "User role not found")
     ),
     tag = "user-roles",
-    security(("cookie_auth" = []))
+    security(("cookie_auth" = ["can_edit_staff"]))
 )]
 pub async fn update_user_role(
     State(state): State<Arc<AppState>>,
     Path(user_role_id): Path<i32>,
-    auth: AuthenticatedUser,
+    RequirePermission { auth, .. }: RequirePermission<CanEditStaff>,
     Json(input): Json<UpdateUserRoleInput>,
 ) -> AppResult<Json<UserRole>> {
-    // Check permission
-    if !crate::extractors::permissions::has_permission_by_name(&state.db, auth.profile_id, auth.is_super_admin, "can_edit_staff").await? {
-        return Err(AppError::Forbidden(
-            "Missing can_edit_staff permission".to_string(),
-        ));
-    }
-
     // If trying to enable can_work_shifts, check if user is generic
     if let Some(true) = input.can_work_shifts {
         // Get user_profile_id for this user_role
@@ -417,6 +505,22 @@ pub async fn update_user_role(
         updates.push(format!("can_view_staff_details = ${}", bind_count));
         bind_count += 1;
     }
+    if input.can_approve_rota.is_some() {
+        updates.push(format!("can_approve_rota = ${}", bind_count));
+        bind_count += 1;
+    }
+    if input.can_set_pay.is_some() {
+        updates.push(format!("can_set_pay = ${}", bind_count));
+        bind_count += 1;
+    }
+    if input.cancel_revocation {
+        // A lapsed assignment stays lapsed until the hourly job removes it; re-grant it instead
+        updates.push(format!(
+            "effective_until = CASE WHEN effective_until >= ${} THEN NULL ELSE effective_until END",
+            bind_count
+        ));
+        bind_count += 1;
+    }
 
     if updates.is_empty() {
         return Err(AppError::BadRequest("No fields to update".to_string()));
@@ -452,10 +556,30 @@ pub async fn update_user_role(
     if let Some(can_view_staff_details) = input.can_view_staff_details {
         query = query.bind(can_view_staff_details);
     }
+    if let Some(can_approve_rota) = input.can_approve_rota {
+        query = query.bind(can_approve_rota);
+    }
+    if let Some(can_set_pay) = input.can_set_pay {
+        query = query.bind(can_set_pay);
+    }
+    if input.cancel_revocation {
+        query = query.bind(permissions::today());
+    }
 
     query = query.bind(user_role_id);
 
-    let result = query.execute(&state.db).await?;
+    let mut tx = crate::db::begin_as_user(&state.db, auth.profile_id).await?;
+    if input.can_set_pay.is_some() || input.role_id.is_some() {
+        let current: Option<(i32, bool)> =
+            sqlx::query_as(r#"SELECT role_id, can_set_pay FROM "UserRoles" WHERE id = $1 FOR UPDATE"#)
+                .bind(user_role_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+        if let Some(role_id) = current.and_then(|current| pay_granted_in(current, input.role_id, input.can_set_pay)) {
+            ensure_can_grant_pay(&state, &auth, role_id).await?;
+        }
+    }
+    let result = query.execute(&mut *tx).await?;
 
     if result.rows_affected() == 0 {
         return Err(AppError::NotFound(format!(
@@ -463,55 +587,299 @@ pub async fn update_user_role(
             user_role_id
         )));
     }
+    tx.commit().await?;
 
     // Fetch the updated user role with joined data
     let user_role = fetch_user_role_by_id(&state.db, user_role_id).await?;
 
+    // Their permissions changed, and role listings carry active staff counts
+    state.caches.invalidate_user_roles(user_role.user_profile_id).await;
     Ok(Json(user_role))
 }
 
-/// DELETE /api/user-roles/{id} - Delete a user role assignment
+/// DELETE /api/user-roles/{id}?immediate=&until= - Revoke a user role assignment at the end of the day (or `until`),
+/// or straight away with immediate=true
 #[utoipa::path(
     delete,
     path = "/api/user-roles/{id}",
     params(
-        ("id" = i32, Path, description = "User role ID")
+        ("id" = i32, Path, description = "User role ID"),
+        DeleteUserRoleQuery
     ),
     responses(
-        (status = 200, description = "User role deleted successfully", body = UserRoleMutationResponse),
+        (status = 200, description = "User role deleted, or scheduled to lapse after its last day", body = UserRoleMutationResponse),
+        (status = 400, description = "until is in the past, or given with immediate=true"),
         (status = 403, description = "Missing can_edit_staff permission"),
         (status = 404, description = "User role not found")
     ),
     tag = "user-roles",
-    security(("cookie_auth" = []))
+    security(("cookie_auth" = ["can_edit_staff"]))
 )]
 pub async fn delete_user_role(
     State(state): State<Arc<AppState>>,
     Path(user_role_id): Path<i32>,
-    auth: AuthenticatedUser,
+    Query(query): Query<DeleteUserRoleQuery>,
+    RequirePermission { auth, .. }: RequirePermission<CanEditStaff>,
 ) -> AppResult<Json<UserRoleMutationResponse>> {
-    // Check permission
-    if !crate::extractors::permissions::has_permission_by_name(&state.db, auth.profile_id, auth.is_super_admin, "can_edit_staff").await? {
-        return Err(AppError::Forbidden(
-            "Missing can_edit_staff permission".to_string(),
-        ));
+    if query.immediate && query.until.is_some() {
+        return Err(AppError::BadRequest("until cannot be combined with immediate=true".to_string()));
     }
+    let last_day = revocation_last_day(query.until, permissions::today())?;
+
+    // The role history trigger keeps the revocation, attributed to the acting user
+    let mut tx = crate::db::begin_as_user(&state.db, auth.profile_id).await?;
+    let revoked = if query.immediate {
+        sqlx::query_scalar(r#"DELETE FROM "UserRoles" WHERE id = $1 RETURNING user_profile_id"#).bind(user_role_id)
+    } else {
+        sqlx::query_scalar(r#"UPDATE "UserRoles" SET effective_until = $2 WHERE id = $1 RETURNING user_profile_id"#)
+            .bind(user_role_id)
+            .bind(last_day)
+    };
+    let user_profile_id: i32 = revoked
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("User role {} not found", user_role_id)))?;
+    tx.commit().await?;
+
+    state.caches.invalidate_user_roles(user_profile_id).await;
+    let message = if query.immediate {
+        tracing::warn!(user_role_id, user_profile_id, revoked_by = auth.profile_id, "🔑 User role revoked immediately");
+        "User role deleted successfully".to_string()
+    } else {
+        tracing::info!(user_role_id, user_profile_id, %last_day, revoked_by = auth.profile_id, "🔑 User role revocation scheduled");
+        format!("Access ends after {}", last_day)
+    };
+    Ok(Json(UserRoleMutationResponse { success: true, message: Some(message) }))
+}
 
-    let result = sqlx::query(r#"DELETE FROM "UserRoles" WHERE id = $1"#)
-        .bind(user_role_id)
-        .execute(&state.db)
-        .await?;
+/// Permission flags of an assignment as stored
+#[derive(Debug, Clone, Copy, Default, FromRow)]
+struct PermissionFlags {
+    can_edit_rota: bool,
+    can_access_diary: bool,
+    can_work_shifts: bool,
+    can_edit_templates: bool,
+    can_edit_staff: bool,
+    can_view_staff_details: bool,
+    can_approve_rota: bool,
+    can_set_pay: bool,
+}
 
-    if result.rows_affected() == 0 {
-        return Err(AppError::NotFound(format!(
-            "User role {} not found",
-            user_role_id
-        )));
+impl PermissionFlags {
+    fn named(&self) -> [(&'static str, bool); 8] {
+        [
+            ("can_edit_rota", self.can_edit_rota),
+            ("can_access_diary", self.can_access_diary),
+            ("can_work_shifts", self.can_work_shifts),
+            ("can_edit_templates", self.can_edit_templates),
+            ("can_edit_staff", self.can_edit_staff),
+            ("can_view_staff_details", self.can_view_staff_details),
+            ("can_approve_rota", self.can_approve_rota),
+            ("can_set_pay", self.can_set_pay),
+        ]
+    }
+}
+
+/// The patch's flags in the same order as `PermissionFlags::named`
+fn patch_values(patch: &PermissionFlagsPatch) -> [Option<bool>; 8] {
+    [
+        patch.can_edit_rota,
+        patch.can_access_diary,
+        patch.can_work_shifts,
+        patch.can_edit_templates,
+        patch.can_edit_staff,
+        patch.can_view_staff_details,
+        patch.can_approve_rota,
+        patch.can_set_pay,
+    ]
+}
+
+/// Flags the patch would actually change on an assignment
+fn permission_changes(current: &PermissionFlags, patch: &PermissionFlagsPatch) -> Vec<PermissionChange> {
+    current
+        .named()
+        .into_iter()
+        .zip(patch_values(patch))
+        .filter_map(|((permission, from), to)| {
+            to.filter(|to| *to != from).map(|to| PermissionChange { permission: permission.to_string(), from, to })
+        })
+        .collect()
+}
+
+#[derive(Debug, FromRow)]
+struct BulkUserRoleRow {
+    id: i32,
+    user_profile_id: i32,
+    short_name: Option<String>,
+    is_generic: bool,
+    #[sqlx(flatten)]
+    flags: PermissionFlags,
+}
+
+/// PATCH /api/user-roles/bulk - Set permission flags on many assignments of one role in one transaction
+#[utoipa::path(
+    patch,
+    path = "/api/user-roles/bulk",
+    request_body = BulkUpdateUserRolesInput,
+    responses(
+        (status = 200, description = "What changed (or would change), with one audit row per changed assignment", body = BulkUpdateUserRolesResponse),
+        (status = 400, description = "No flags to set, or an empty or oversized profile list"),
+        (status = 403, description = "Missing can_edit_staff permission for the role, or can_set_pay to grant it")
+    ),
+    tag = "user-roles",
+    security(("cookie_auth" = []))
+)]
+pub async fn bulk_update_user_roles(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+    Json(input): Json<BulkUpdateUserRolesInput>,
+) -> AppResult<Json<BulkUpdateUserRolesResponse>> {
+    let role_id = input.role_id;
+    if !permissions::has_permission(&state, auth.profile_id, auth.is_super_admin, |r| r.role_id == role_id && r.can_edit_staff).await? {
+        return Err(AppError::Forbidden("Missing can_edit_staff permission for this role".to_string()));
+    }
+    if input.set.can_set_pay == Some(true) {
+        ensure_can_grant_pay(&state, &auth, role_id).await?;
     }
 
-    Ok(Json(UserRoleMutationResponse {
-        success: true,
-        message: Some("User role deleted successfully".to_string()),
+    if patch_values(&input.set).iter().all(Option::is_none) {
+        return Err(AppError::BadRequest("set must include at least one permission flag".to_string()));
+    }
+    if let Some(ids) = &input.user_profile_ids {
+        if ids.is_empty() || ids.len() > MAX_BULK_USER_ROLES {
+            return Err(AppError::BadRequest(format!(
+                "user_profile_ids must list between 1 and {} profiles (omit it for the whole role)",
+                MAX_BULK_USER_ROLES
+            )));
+        }
+    }
+
+    let mut tx = crate::db::begin_as_user(&state.db, auth.profile_id).await?;
+
+    let rows = sqlx::query_as::<_, BulkUserRoleRow>(
+        r#"
+        SELECT
+            ur.id::int4 AS id,
+            ur.user_profile_id::int4 AS user_profile_id,
+            u.short_name,
+            COALESCE(u.is_generic_login, false) AS is_generic,
+            ur.can_edit_rota,
+            ur.can_access_diary,
+            ur.can_work_shifts,
+            ur.can_edit_templates,
+            ur.can_edit_staff,
+            ur.can_view_staff_details,
+            ur.can_approve_rota,
+            ur.can_set_pay
+        FROM "UserRoles" ur
+        JOIN "Users" u ON u.user_profile_id = ur.user_profile_id
+        WHERE ur.role_id = $1
+          AND ($2::int4[] IS NULL OR ur.user_profile_id = ANY($2))
+        ORDER BY u.short_name, ur.id
+        FOR UPDATE OF ur
+        "#,
+    )
+    .bind(role_id)
+    .bind(&input.user_profile_ids)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    let mut not_in_role: Vec<i32> = input
+        .user_profile_ids
+        .iter()
+        .flatten()
+        .copied()
+        .filter(|id| !rows.iter().any(|r| r.user_profile_id == *id))
+        .collect();
+    not_in_role.sort_unstable();
+    not_in_role.dedup();
+
+    // Generic accounts cannot work shifts (as with PUT /api/user-roles/{id}); their other flags still apply
+    let mut generic_accounts = Vec::new();
+    let mut planned: Vec<(UserRolePermissionChanges, PermissionFlagsPatch)> = Vec::new();
+    for row in &rows {
+        let mut patch = input.set.clone();
+        if row.is_generic && patch.can_work_shifts == Some(true) {
+            patch.can_work_shifts = None;
+            generic_accounts.push(row.user_profile_id);
+        }
+        let changes = permission_changes(&row.flags, &patch);
+        if !changes.is_empty() {
+            planned.push((
+                UserRolePermissionChanges {
+                    user_role_id: row.id,
+                    user_profile_id: row.user_profile_id,
+                    short_name: row.short_name.clone(),
+                    changes,
+                },
+                patch,
+            ));
+        }
+    }
+
+    let batch_id = (!input.dry_run && !planned.is_empty()).then(Uuid::new_v4);
+    if let Some(batch_id) = batch_id {
+        for (assignment, patch) in &planned {
+            sqlx::query(
+                r#"
+                UPDATE "UserRoles" SET
+                    can_edit_rota = COALESCE($2, can_edit_rota),
+                    can_access_diary = COALESCE($3, can_access_diary),
+                    can_work_shifts = COALESCE($4, can_work_shifts),
+                    can_edit_templates = COALESCE($5, can_edit_templates),
+                    can_edit_staff = COALESCE($6, can_edit_staff),
+                    can_view_staff_details = COALESCE($7, can_view_staff_details),
+                    can_approve_rota = COALESCE($8, can_approve_rota),
+                    can_set_pay = COALESCE($9, can_set_pay)
+                WHERE id = $1
+                "#,
+            )
+            .bind(assignment.user_role_id)
+            .bind(patch.can_edit_rota)
+            .bind(patch.can_access_diary)
+            .bind(patch.can_work_shifts)
+            .bind(patch.can_edit_templates)
+            .bind(patch.can_edit_staff)
+            .bind(patch.can_view_staff_details)
+            .bind(patch.can_approve_rota)
+            .bind(patch.can_set_pay)
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query(
+                r#"
+                INSERT INTO "UserRoleAudit" (user_role_id, role_id, user_profile_id, changed_by, batch_id, changes)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                "#,
+            )
+            .bind(assignment.user_role_id)
+            .bind(role_id)
+            .bind(assignment.user_profile_id)
+            .bind(auth.profile_id)
+            .bind(batch_id)
+            .bind(serde_json::to_value(&assignment.changes).map_err(|e| AppError::Internal(e.to_string()))?)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        for (assignment, _) in &planned {
+            state.caches.invalidate_user_roles(assignment.user_profile_id).await;
+        }
+        tracing::info!(role_id, %batch_id, changed = planned.len(), changed_by = auth.profile_id, "🔐 Bulk permission update applied");
+    } else {
+        tx.rollback().await?;
+    }
+
+    Ok(Json(BulkUpdateUserRolesResponse {
+        dry_run: input.dry_run,
+        matched: rows.len(),
+        changed: planned.len(),
+        unchanged: rows.len() - planned.len(),
+        batch_id,
+        assignments: planned.into_iter().map(|(assignment, _)| assignment).collect(),
+        not_in_role,
+        generic_accounts,
     }))
 }
 
@@ -530,6 +898,9 @@ async fn fetch_user_role_by_id(db: &sqlx::PgPool, user_role_id: i32) -> AppResul
             ur.can_edit_templates,
             ur.can_edit_staff,
             ur.can_view_staff_details,
+            ur.can_approve_rota,
+            ur.can_set_pay,
+            ur.effective_until,
             ur.created_at,
             r.id::int4 AS r_id,
             r.workplace_id::int4 AS r_workplace,
@@ -559,12 +930,20 @@ async fn fetch_user_role_by_id(db: &sqlx::PgPool, user_role_id: i32) -> AppResul
         can_edit_templates: row.can_edit_templates,
         can_edit_staff: row.can_edit_staff,
         can_view_staff_details: row.can_view_staff_details,
+        can_approve_rota: row.can_approve_rota,
+        can_set_pay: row.can_set_pay,
+        effective_until: row.effective_until,
         created_at: row.created_at,
         roles: row.r_id.map(|id| Role {
             id,
             workplace: row.r_workplace.unwrap_or(0),
             role_name: row.r_role_name.unwrap_or_default(),
             marketplace_auto_approve: None,
+            publish_requires_approval: None,
+            max_swaps_per_month: None,
+            edit_reason_required: None,
+            archived: None,
+            active_staff: None,
             workplaces: row.w_id.map(|w_id| Workplace {
                 id: w_id,
                 hospital: row.w_hospital,
@@ -575,3 +954,50 @@ async fn fetch_user_role_by_id(db: &sqlx::PgPool, user_role_id: i32) -> AppResul
         }),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_permission_changes_only_reports_moved_flags() {
+        let current = PermissionFlags { can_edit_rota: true, can_work_shifts: true, ..Default::default() };
+        let patch = PermissionFlagsPatch {
+            can_edit_rota: Some(true),
+            can_access_diary: Some(true),
+            can_work_shifts: Some(false),
+            ..Default::default()
+        };
+        let changes = permission_changes(&current, &patch);
+        assert_eq!(
+            changes,
+            vec![
+                PermissionChange { permission: "can_access_diary".to_string(), from: false, to: true },
+                PermissionChange { permission: "can_work_shifts".to_string(), from: true, to: false },
+            ]
+        );
+        assert!(permission_changes(&current, &PermissionFlagsPatch::default()).is_empty());
+    }
+
+    #[test]
+    fn test_revocation_last_day() {
+        let day = |d| NaiveDate::from_ymd_opt(2026, 10, d).unwrap();
+        assert_eq!(revocation_last_day(None, day(19)).unwrap(), day(19));
+        assert_eq!(revocation_last_day(Some(day(31)), day(19)).unwrap(), day(31));
+        assert!(revocation_last_day(Some(day(18)), day(19)).is_err());
+    }
+
+    #[test]
+    fn test_pay_granted_in() {
+        // Switching it on, in place or while moving
+        assert_eq!(pay_granted_in((1, false), None, Some(true)), Some(1));
+        assert_eq!(pay_granted_in((1, false), Some(2), Some(true)), Some(2));
+        // Moving an assignment that already has it
+        assert_eq!(pay_granted_in((1, true), Some(2), None), Some(2));
+        // Keeping or dropping it grants nothing
+        assert_eq!(pay_granted_in((1, true), None, Some(true)), None);
+        assert_eq!(pay_granted_in((1, true), Some(1), None), None);
+        assert_eq!(pay_granted_in((1, true), Some(2), Some(false)), None);
+        assert_eq!(pay_granted_in((1, false), Some(2), None), None);
+    }
+}
@@ -1,41 +1,39 @@
 use axum::{
     extract::{Path, Query, State},
-    Json,
+    http::HeaderMap,
+    response::Response,
 };
-use serde::{Deserialize, Deserializer};
+use serde::Deserialize;
 use std::sync::Arc;
 
 use crate::{
-    auth::{check_email_in_clerk, generate_pin_token, validate_pin_token},
-    extractors::AuthenticatedUser,
+    auth::{
+        check_email_in_clerk, confirm_email_challenge, create_clerk_user, create_email_challenge, delete_clerk_user,
+        email_verification::EMAIL_CHALLENGE_TTL_SECS, generate_pin_token, mask_email, password_policy,
+        send_verification_email, validate_pin_token,
+    },
+    db::{
+        encrypted::{self, EncryptedString},
+        fieldset::{FieldSet, USER_FIELDS},
+    },
+    extractors::{AuthenticatedUser, CanEditStaff, Json, RequirePermission},
     models::{
         ChangeOwnPinInput, ChangePasswordInput, ChangeProfilePinRequest, CheckEmailRequest,
-        CheckEmailResponse, CreateLoginInput, CreateLoginResponse, CreateUserProfileRequest,
-        PinResponse, SearchUsersRequest, StaffFilterOption, SuccessResponse,
-        UpdateOwnProfileInput, UpdateUserProfileInput, User, VerifyIdentityRequest,
-        VerifyIdentityResponse,
+        CheckEmailResponse, ConfirmEmailVerificationRequest, CreateLoginInput, CreateLoginResponse,
+        CreateUserProfileRequest, CustomFieldValue, PinResponse, RequestEmailVerificationRequest, RequestEmailVerificationResponse,
+        SearchUsersRequest, SortDirection, StaffFilterOption, SuccessResponse,
+        UpdateOwnProfileInput, UpdateUserProfileInput, User, UserSort, VerifyIdentityRequest,
+        PinPolicy, VerifyIdentityResponse, WorkplaceSettings,
     },
+    handlers::{custom_fields_handler, workplaces_handler::pin_policy_for_user},
+    error::InvalidFieldsBody,
+    job_plans::JobPlanResolver,
     AppError, AppResult, AppState,
 };
 
-// Helper to deserialize string or number as i32
-fn deserialize_string_or_number<'de, D>(deserializer: D) -> Result<i32, D::Error>
-where
-    D: Deserializer<'de>,
-{
-    use serde::de::Error;
-
-    #[derive(Deserialize)]
-    #[serde(untagged)]
-    enum StringOrNumber {
-        String(String),
-        Number(i32),
-    }
-
-    match StringOrNumber::deserialize(deserializer)? {
-        StringOrNumber::String(s) => s.parse::<i32>().map_err(D::Error::custom),
-        StringOrNumber::Number(n) => Ok(n),
-    }
+/// Validate a new PIN against the user's PIN policy
+fn validate_pin(pin: &str, policy: &PinPolicy) -> AppResult<()> {
+    policy.validate(pin).map_err(AppError::BadRequest)
 }
 
 // ToSchema is used in auth_handler for VerifyPinRequest/Response
@@ -45,6 +43,9 @@ pub struct GetUsersQuery {
     hospital: Option<String>,
     ward: Option<String>,
     role_id: Option<i32>,
+    fields: Option<String>,
+    sort: Option<UserSort>,
+    dir: Option<SortDirection>,
 }
 
 /// GET /api/users
@@ -54,67 +55,87 @@ pub struct GetUsersQuery {
     params(
         ("hospital" = Option<String>, Query, description = "Filter by hospital name"),
         ("ward" = Option<String>, Query, description = "Filter by ward name"),
-        ("role_id" = Option<i32>, Query, description = "Filter by role assignment")
+        ("role_id" = Option<i32>, Query, description = "Filter by role assignment"),
+        ("fields" = Option<String>, Query, description = "Comma-separated User fields to return (e.g. user_profile_id,short_name,color); all when omitted"),
+        ("sort" = Option<UserSort>, Query, description = "Sort column (default name)"),
+        ("dir" = Option<SortDirection>, Query, description = "Sort direction (default depends on sort)")
     ),
     responses(
-        (status = 200, description = "List of users (filtered if params provided)", body = Vec<User>)
+        (status = 200, description = "List of users (filtered if params provided)", body = Vec<User>),
+        (status = 400, description = "Unknown field in fields, or invalid sort/dir")
     ),
     tag = "users"
 )]
 pub async fn get_users(
     State(state): State<Arc<AppState>>,
     Query(query): Query<GetUsersQuery>,
-) -> AppResult<Json<Vec<User>>> {
+) -> AppResult<Response> {
+    let fields = FieldSet::parse(query.fields.as_deref(), USER_FIELDS)?;
+    let columns = fields.select_list(&["user_profile_id"]);
+    let order_by = query.sort.unwrap_or(UserSort::Name).order_by(query.dir);
+
     // Filter by role if role_id is provided
     if let Some(role_id) = query.role_id {
-        let users = sqlx::query_as::<_, User>(
+        let users = sqlx::query_as::<_, User>(&crate::db::tag_sql(&format!(
             r#"
-            SELECT DISTINCT u.*
+            SELECT {}
             FROM "Users" u
-            INNER JOIN "UserRoles" ur ON u.user_profile_id = ur.user_profile_id
-            WHERE ur.role_id = $1
-            ORDER BY u.full_name
+            WHERE EXISTS (SELECT 1 FROM "UserRoles" ur WHERE ur.user_profile_id = u.user_profile_id AND ur.role_id = $1 AND {ur_in_force})
+            ORDER BY {}
             "#,
-        )
+            columns, order_by,
+            ur_in_force = crate::extractors::permissions::ur_in_force(2)
+        )))
+        .persistent(false)
         .bind(role_id)
+        .bind(crate::extractors::permissions::today())
         .fetch_all(&state.db)
         .await?;
 
-        return Ok(Json(users));
+        return fields.respond(users);
     }
 
     // Filter by workplace (hospital + ward)
     if let (Some(hospital), Some(ward)) = (query.hospital, query.ward) {
-        let users = sqlx::query_as::<_, User>(
+        let users = sqlx::query_as::<_, User>(&crate::db::tag_sql(&format!(
             r#"
-            SELECT DISTINCT u.*
+            SELECT {}
             FROM "Users" u
-            INNER JOIN "UserRoles" ur ON u.user_profile_id = ur.user_profile_id
-            INNER JOIN "Roles" r ON ur.role_id = r.id
-            INNER JOIN "Workplaces" w ON r.workplace_id = w.id
-            WHERE w.hospital = $1 AND w.ward = $2
-            ORDER BY u.full_name
+            WHERE EXISTS (
+                SELECT 1
+                FROM "UserRoles" ur
+                INNER JOIN "Roles" r ON ur.role_id = r.id
+                INNER JOIN "Workplaces" w ON r.workplace_id = w.id
+                WHERE ur.user_profile_id = u.user_profile_id AND w.hospital = $1 AND w.ward = $2 AND {ur_in_force}
+            )
+            ORDER BY {}
             "#,
-        )
+            columns, order_by,
+            ur_in_force = crate::extractors::permissions::ur_in_force(3)
+        )))
+        .persistent(false)
         .bind(hospital)
         .bind(ward)
+        .bind(crate::extractors::permissions::today())
         .fetch_all(&state.db)
         .await?;
 
-        return Ok(Json(users));
+        return fields.respond(users);
     }
 
     // No filters - return all users
-    let users = sqlx::query_as::<_, User>(
+    let users = sqlx::query_as::<_, User>(&crate::db::tag_sql(&format!(
         r#"
-        SELECT * FROM "Users"
-        ORDER BY full_name
+        SELECT {} FROM "Users" u
+        ORDER BY {}
         "#,
-    )
+        columns, order_by
+    )))
+    .persistent(false)
     .fetch_all(&state.db)
     .await?;
 
-    Ok(Json(users))
+    fields.respond(users)
 }
 
 /// GET /api/users/{id}
@@ -125,7 +146,7 @@ pub async fn get_users(
         ("id" = i32, Path, description = "User profile ID")
     ),
     responses(
-        (status = 200, description = "User found", body = User),
+        (status = 200, description = "User found; custom fields only from workplaces the caller shares", body = User),
         (status = 404, description = "User not found")
     ),
     tag = "users"
@@ -133,8 +154,9 @@ pub async fn get_users(
 pub async fn get_user(
     State(state): State<Arc<AppState>>,
     Path(id): Path<i32>,
+    auth: AuthenticatedUser,
 ) -> AppResult<Json<User>> {
-    let user = sqlx::query_as::<_, User>(
+    let mut user = sqlx::query_as::<_, User>(
         r#"
         SELECT * FROM "Users"
         WHERE user_profile_id = $1
@@ -143,10 +165,40 @@ pub async fn get_user(
     .bind(id)
     .fetch_one(&state.db)
     .await?;
+    user.custom_fields = user_custom_fields(&state.db, &auth, id).await?;
 
     Ok(Json(user))
 }
 
+/// A single user's custom field values, limited to workplaces the caller also holds a role in
+/// (all of them for the user themselves and super admins)
+async fn user_custom_fields(db: &sqlx::PgPool, auth: &AuthenticatedUser, user_id: i32) -> AppResult<Vec<CustomFieldValue>> {
+    let mut values: Vec<CustomFieldValue> = custom_fields_handler::custom_field_values(db, &[user_id])
+        .await?
+        .into_iter()
+        .map(|(_, value)| value)
+        .collect();
+    if auth.is_super_admin || auth.profile_id == user_id {
+        return Ok(values);
+    }
+
+    let shared: Vec<i32> = sqlx::query_scalar(&format!(
+        r#"
+        SELECT DISTINCT r.workplace_id::int4
+        FROM "UserRoles" ur
+        INNER JOIN "Roles" r ON r.id = ur.role_id
+        WHERE ur.user_profile_id = $1 AND {ur_in_force}
+        "#,
+        ur_in_force = crate::extractors::permissions::ur_in_force(2)
+    ))
+    .bind(auth.profile_id)
+    .bind(crate::extractors::permissions::today())
+    .fetch_all(db)
+    .await?;
+    values.retain(|value| shared.contains(&value.workplace_id));
+    Ok(values)
+}
+
 #[derive(Deserialize)]
 pub struct SubstantiveUsersQuery {
     role_id: Option<i32>, // Required but using Option for query param parsing
@@ -180,22 +232,22 @@ pub async fn get_substantive_users(
     })?;
 
     let users = if let (Some(year), Some(month)) = (query.year, query.month) {
-        // With date filter: job plan must be active during that month
-        let first_day_of_month = format!("{}-{:02}-01", year, month);
+        // With date filter: a job plan must be in force on some day of that month, so plans
+        // starting or ending mid-month count
+        let (first_day, last_day) = crate::handlers::roles_handler::month_range(year, month)?;
+
+        let resolver = JobPlanResolver::load(&state.db, None, Some(role_id), first_day, last_day).await?;
+        let user_ids: Vec<i32> = resolver.assignments().map(|(user_profile_id, _)| user_profile_id).collect();
 
         sqlx::query_as::<_, User>(
             r#"
-            SELECT DISTINCT u.*
-            FROM "JobPlans" jp
-            INNER JOIN "Users" u ON jp.user_profile_id = u.user_profile_id
-            WHERE jp.role_id = $1
-              AND jp.from <= $2::DATE
-              AND (jp.until >= $2::DATE OR jp.until IS NULL)
+            SELECT u.*
+            FROM "Users" u
+            WHERE u.user_profile_id = ANY($1)
             ORDER BY u.user_profile_id
             "#,
         )
-        .bind(role_id)
-        .bind(&first_day_of_month)
+        .bind(&user_ids)
         .fetch_all(&state.db)
         .await?
     } else {
@@ -219,7 +271,6 @@ pub async fn get_substantive_users(
 
 #[derive(Deserialize, utoipa::ToSchema, Debug)]
 pub struct LocumUsersRequest {
-    #[serde(deserialize_with = "deserialize_string_or_number")]
     role_id: i32,
     #[serde(default)]
     year: Option<i32>,
@@ -243,7 +294,6 @@ pub async fn get_locum_users(
     State(state): State<Arc<AppState>>,
     Json(req): Json<LocumUsersRequest>,
 ) -> AppResult<Json<Vec<User>>> {
-    tracing::debug!("🐛 get_locum_users: {:?}", req);
     // Locum users = users in UserRoles with can_work_shifts=true
     // TanStack ignores year/month parameters (they're prefixed with _ in the code)
     // Does NOT filter by is_generic_login!
@@ -251,50 +301,59 @@ pub async fn get_locum_users(
     let users = if let Some(ref exclude_ids) = req.exclude_user_ids {
         if !exclude_ids.is_empty() {
             // With exclusions
-            sqlx::query_as::<_, User>(
+            sqlx::query_as::<_, User>(&format!(
                 r#"
                 SELECT DISTINCT u.*
                 FROM "UserRoles" ur
                 INNER JOIN "Users" u ON ur.user_profile_id = u.user_profile_id
                 WHERE ur.role_id = $1
                   AND ur.can_work_shifts = true
+                  AND {ur_in_force}
                   AND u.user_profile_id != ALL($2)
                 ORDER BY u.user_profile_id
                 "#,
-            )
+                ur_in_force = crate::extractors::permissions::ur_in_force(3)
+            ))
             .bind(req.role_id)
             .bind(exclude_ids)
+            .bind(crate::extractors::permissions::today())
             .fetch_all(&state.db)
             .await?
         } else {
             // Empty exclusion list = no exclusions
-            sqlx::query_as::<_, User>(
+            sqlx::query_as::<_, User>(&format!(
                 r#"
                 SELECT DISTINCT u.*
                 FROM "UserRoles" ur
                 INNER JOIN "Users" u ON ur.user_profile_id = u.user_profile_id
                 WHERE ur.role_id = $1
                   AND ur.can_work_shifts = true
+                  AND {ur_in_force}
                 ORDER BY u.user_profile_id
                 "#,
-            )
+                ur_in_force = crate::extractors::permissions::ur_in_force(2)
+            ))
             .bind(req.role_id)
+            .bind(crate::extractors::permissions::today())
             .fetch_all(&state.db)
             .await?
         }
     } else {
         // No exclusions specified
-        sqlx::query_as::<_, User>(
+        sqlx::query_as::<_, User>(&format!(
             r#"
             SELECT DISTINCT u.*
             FROM "UserRoles" ur
             INNER JOIN "Users" u ON ur.user_profile_id = u.user_profile_id
             WHERE ur.role_id = $1
               AND ur.can_work_shifts = true
+              AND {ur_in_force}
             ORDER BY u.user_profile_id
             "#,
-        )
+            ur_in_force = crate::extractors::permissions::ur_in_force(2)
+        ))
         .bind(req.role_id)
+        .bind(crate::extractors::permissions::today())
         .fetch_all(&state.db)
         .await?
     };
@@ -305,6 +364,8 @@ pub async fn get_locum_users(
 #[derive(Deserialize)]
 pub struct StaffListQuery {
     role_id: Option<i32>,
+    sort: Option<UserSort>,
+    dir: Option<SortDirection>,
 }
 
 /// GET /api/users/staff-list
@@ -312,10 +373,13 @@ pub struct StaffListQuery {
     get,
     path = "/api/users/staff-list",
     params(
-        ("role_id" = Option<i32>, Query, description = "Filter by role assignment")
+        ("role_id" = Option<i32>, Query, description = "Filter by role assignment"),
+        ("sort" = Option<UserSort>, Query, description = "Sort column (user_profile_id order when omitted)"),
+        ("dir" = Option<SortDirection>, Query, description = "Sort direction (default depends on sort)")
     ),
     responses(
-        (status = 200, description = "Staff list for filters", body = Vec<StaffFilterOption>)
+        (status = 200, description = "Staff list for filters", body = Vec<StaffFilterOption>),
+        (status = 400, description = "Invalid sort/dir")
     ),
     tag = "users"
 )]
@@ -323,40 +387,50 @@ pub async fn get_staff_list(
     State(state): State<Arc<AppState>>,
     Query(query): Query<StaffListQuery>,
 ) -> AppResult<Json<Vec<StaffFilterOption>>> {
+    let order_by = query
+        .sort
+        .map(|sort| sort.order_by(query.dir))
+        .unwrap_or_else(|| "u.user_profile_id".to_string());
+
     let staff = if let Some(role_id) = query.role_id {
         // Filter by role and can_work_shifts
-        sqlx::query_as::<_, StaffFilterOption>(
+        sqlx::query_as::<_, StaffFilterOption>(&format!(
             r#"
-            SELECT DISTINCT
+            SELECT
                 u.user_profile_id,
                 u.short_name,
                 u.full_name,
                 u.color
             FROM "Users" u
-            INNER JOIN "UserRoles" ur ON u.user_profile_id = ur.user_profile_id
             WHERE u.is_generic_login = false
-              AND ur.role_id = $1
-              AND ur.can_work_shifts = true
-            ORDER BY u.user_profile_id
+              AND EXISTS (
+                  SELECT 1 FROM "UserRoles" ur
+                  WHERE ur.user_profile_id = u.user_profile_id AND ur.role_id = $1 AND ur.can_work_shifts = true AND {ur_in_force}
+              )
+            ORDER BY {}
             "#,
-        )
+            order_by,
+            ur_in_force = crate::extractors::permissions::ur_in_force(2)
+        ))
         .bind(role_id)
+        .bind(crate::extractors::permissions::today())
         .fetch_all(&state.db)
         .await?
     } else {
         // No filter - all staff
-        sqlx::query_as::<_, StaffFilterOption>(
+        sqlx::query_as::<_, StaffFilterOption>(&format!(
             r#"
             SELECT
-                user_profile_id,
-                short_name,
-                full_name,
-                color
-            FROM "Users"
-            WHERE is_generic_login = false
-            ORDER BY user_profile_id
+                u.user_profile_id,
+                u.short_name,
+                u.full_name,
+                u.color
+            FROM "Users" u
+            WHERE u.is_generic_login = false
+            ORDER BY {}
             "#,
-        )
+            order_by
+        ))
         .fetch_all(&state.db)
         .await?
     };
@@ -406,15 +480,17 @@ pub async fn update_own_profile(
     let updated_user = sqlx::query_as::<_, User>(
         r#"
         UPDATE "Users"
-        SET short_name = $1, tel = $2, color = $3
+        SET short_name = $1, tel = $2, color = $3,
+            marketplace_offers_opt_out = COALESCE($5, marketplace_offers_opt_out)
         WHERE user_profile_id = $4
         RETURNING *
         "#,
     )
     .bind(&input.short_name)
-    .bind(&input.tel)
+    .bind(input.tel.as_deref().map(EncryptedString::wrap_all))
     .bind(&input.color)
     .bind(auth.profile_id)
+    .bind(input.marketplace_offers_opt_out)
     .fetch_one(&state.db)
     .await?;
 
@@ -444,12 +520,9 @@ pub async fn change_own_pin(
         return Err(AppError::BadRequest("New PINs do not match".to_string()));
     }
 
-    // Validate PIN format (5 digits)
-    if input.new_pin.len() != 5 || !input.new_pin.chars().all(|c| c.is_ascii_digit()) {
-        return Err(AppError::BadRequest(
-            "PIN must be exactly 5 digits".to_string(),
-        ));
-    }
+    // Validate PIN against the workplace PIN policy
+    let policy = pin_policy_for_user(&state.db, auth.profile_id).await?;
+    validate_pin(&input.new_pin, &policy)?;
 
     // Get user to check generic account status and current PIN
     let user = sqlx::query_as::<_, User>(r#"SELECT * FROM "Users" WHERE user_profile_id = $1"#)
@@ -479,12 +552,14 @@ pub async fn change_own_pin(
         }
     }
 
-    // Update PIN
-    sqlx::query(r#"UPDATE "Users" SET auth_pin = $1 WHERE user_profile_id = $2"#)
-        .bind(&input.new_pin)
-        .bind(auth.profile_id)
-        .execute(&state.db)
-        .await?;
+    // Update PIN (a PIN the user chose clears any pending change)
+    sqlx::query(
+        r#"UPDATE "Users" SET auth_pin = $1, pin_changed_at = NOW(), pin_must_change = false WHERE user_profile_id = $2"#,
+    )
+    .bind(&input.new_pin)
+    .bind(auth.profile_id)
+    .execute(&state.db)
+    .await?;
 
     Ok(Json(PinResponse {
         success: true,
@@ -503,32 +578,23 @@ pub async fn change_own_pin(
     request_body = UpdateUserProfileInput,
     responses(
         (status = 200, description = "User profile updated", body = User),
-        (status = 403, description = "Missing can_edit_staff permission"),
-        (status = 404, description = "User not found")
+        (status = 403, description = "Missing can_edit_staff permission (for custom fields, in their workplace)"),
+        (status = 404, description = "User not found"),
+        (status = 422, description = "Unknown custom field, or a value of the wrong type")
     ),
     tag = "users",
-    security(("cookie_auth" = []))
+    security(("cookie_auth" = ["can_edit_staff"]))
 )]
 pub async fn update_user_profile(
     State(state): State<Arc<AppState>>,
     Path(user_id): Path<i32>,
-    auth: AuthenticatedUser,
+    RequirePermission { auth, .. }: RequirePermission<CanEditStaff>,
     Json(input): Json<UpdateUserProfileInput>,
 ) -> AppResult<Json<User>> {
-    // Check permission
-    if !crate::extractors::permissions::has_permission_by_name(&state.db, auth.profile_id, auth.is_super_admin, "can_edit_staff").await? {
-        return Err(AppError::Forbidden(
-            "Missing can_edit_staff permission".to_string(),
-        ));
-    }
-
     // Validate PIN format if provided
     if let Some(ref pin) = input.auth_pin {
-        if pin.len() != 5 || !pin.chars().all(|c| c.is_ascii_digit()) {
-            return Err(AppError::BadRequest(
-                "PIN must be exactly 5 digits".to_string(),
-            ));
-        }
+        let policy = pin_policy_for_user(&state.db, user_id).await?;
+        validate_pin(pin, &policy)?;
     }
 
     // Validate color format if provided
@@ -563,6 +629,8 @@ pub async fn update_user_profile(
     if input.secondary_emails.is_some() {
         updates.push(format!("secondary_emails = ${}", bind_count));
         bind_count += 1;
+        updates.push(format!("secondary_email_index = ${}", bind_count));
+        bind_count += 1;
     }
     if input.tel.is_some() {
         updates.push(format!("tel = ${}", bind_count));
@@ -573,7 +641,11 @@ pub async fn update_user_profile(
         bind_count += 1;
     }
     if input.auth_pin.is_some() {
-        updates.push(format!("auth_pin = ${}", bind_count));
+        // PINs set by an admin must be changed by the user (generic accounts cannot change theirs)
+        updates.push(format!(
+            "auth_pin = ${}, pin_changed_at = NOW(), pin_must_change = NOT is_generic_login",
+            bind_count
+        ));
         bind_count += 1;
     }
     if input.color.is_some() {
@@ -581,15 +653,19 @@ pub async fn update_user_profile(
         bind_count += 1;
     }
 
-    if updates.is_empty() {
+    if updates.is_empty() && input.custom_fields.is_none() {
         return Err(AppError::BadRequest("No fields to update".to_string()));
     }
 
-    let sql = format!(
-        r#"UPDATE "Users" SET {} WHERE user_profile_id = ${} RETURNING *"#,
-        updates.join(", "),
-        bind_count
-    );
+    let sql = if updates.is_empty() {
+        r#"SELECT * FROM "Users" WHERE user_profile_id = $1"#.to_string()
+    } else {
+        format!(
+            r#"UPDATE "Users" SET {} WHERE user_profile_id = ${} RETURNING *"#,
+            updates.join(", "),
+            bind_count
+        )
+    };
 
     // Build query with bindings
     let mut query = sqlx::query_as::<_, User>(&sql);
@@ -607,10 +683,12 @@ pub async fn update_user_profile(
         query = query.bind(primary_email);
     }
     if let Some(secondary_emails) = &input.secondary_emails {
-        query = query.bind(secondary_emails);
+        let secondary_emails = EncryptedString::wrap_all(secondary_emails);
+        let index = encrypted::secondary_email_index(&secondary_emails);
+        query = query.bind(secondary_emails).bind(index);
     }
     if let Some(tel) = &input.tel {
-        query = query.bind(tel);
+        query = query.bind(EncryptedString::wrap_all(tel));
     }
     if let Some(comment) = &input.comment {
         query = query.bind(comment);
@@ -624,7 +702,14 @@ pub async fn update_user_profile(
 
     query = query.bind(user_id);
 
-    let updated_user = query.fetch_one(&state.db).await?;
+    let mut tx = crate::db::begin_as_user(&state.db, auth.profile_id).await?;
+    let mut updated_user = query.fetch_one(&mut *tx).await?;
+    if let Some(custom_fields) = &input.custom_fields {
+        custom_fields_handler::set_custom_field_values(&mut tx, &state, &auth, user_id, custom_fields).await?;
+    }
+    tx.commit().await?;
+    state.caches.invalidate_profile(user_id);
+    updated_user.custom_fields = user_custom_fields(&state.db, &auth, user_id).await?;
 
     Ok(Json(updated_user))
 }
@@ -642,27 +727,30 @@ pub async fn update_user_profile(
         (status = 404, description = "User not found")
     ),
     tag = "users",
-    security(("cookie_auth" = []))
+    security(("cookie_auth" = ["can_edit_staff"]))
 )]
 pub async fn reset_user_pin(
     State(state): State<Arc<AppState>>,
     Path(user_id): Path<i32>,
-    auth: AuthenticatedUser,
+    _: RequirePermission<CanEditStaff>,
 ) -> AppResult<Json<PinResponse>> {
-    // Check permission
-    if !crate::extractors::permissions::has_permission_by_name(&state.db, auth.profile_id, auth.is_super_admin, "can_edit_staff").await? {
-        return Err(AppError::Forbidden(
-            "Missing can_edit_staff permission".to_string(),
-        ));
-    }
-
-    // Generate new random 5-digit PIN
+    // Generate new random PIN that satisfies the workplace PIN policy
+    let policy = pin_policy_for_user(&state.db, user_id).await?;
     use rand::{Rng, SeedableRng};
     let mut rng = rand::rngs::StdRng::from_entropy();
-    let new_pin = format!("{:05}", rng.gen_range(0..100000));
+    let new_pin = loop {
+        let pin: String = (0..policy.length)
+            .map(|_| char::from(b'0' + rng.gen_range(0..10u8)))
+            .collect();
+        if policy.validate(&pin).is_ok() {
+            break pin;
+        }
+    };
 
-    // Update PIN
-    sqlx::query(r#"UPDATE "Users" SET auth_pin = $1 WHERE user_profile_id = $2"#)
+    // Update PIN (the user must replace it, unless it belongs to a generic account)
+    sqlx::query(
+        r#"UPDATE "Users" SET auth_pin = $1, pin_changed_at = NOW(), pin_must_change = NOT is_generic_login WHERE user_profile_id = $2"#,
+    )
         .bind(&new_pin)
         .bind(user_id)
         .execute(&state.db)
@@ -702,40 +790,51 @@ pub async fn search_users(
     }
 
     let search_pattern = format!("%{}%", req.query);
+    // Encrypted secondary emails can only be matched exactly, via their blind index
+    let email_indexes = encrypted::blind_indexes(&req.query);
+
+    let order_by = req.sort.unwrap_or(UserSort::Name).order_by(req.dir);
 
     let users = if let Some(role_id) = req.role_id {
         // Search with role filter
-        sqlx::query_as::<_, User>(
+        sqlx::query_as::<_, User>(&format!(
             r#"
-            SELECT DISTINCT u.* FROM "Users" u
-            INNER JOIN "UserRoles" ur ON u.user_profile_id = ur.user_profile_id
-            WHERE ur.role_id = $2
+            SELECT u.* FROM "Users" u
+            WHERE EXISTS (SELECT 1 FROM "UserRoles" ur WHERE ur.user_profile_id = u.user_profile_id AND ur.role_id = $2 AND {ur_in_force})
               AND (u.full_name ILIKE $1
                    OR u.short_name ILIKE $1
                    OR u.primary_email ILIKE $1
-                   OR EXISTS (SELECT 1 FROM unnest(u.secondary_emails) e WHERE e ILIKE $1))
-            ORDER BY u.full_name
+                   OR EXISTS (SELECT 1 FROM unnest(u.secondary_emails) e WHERE e ILIKE $1)
+                   OR u.secondary_email_index && $3::text[])
+            ORDER BY {}
             LIMIT 50
             "#,
-        )
+            order_by,
+            ur_in_force = crate::extractors::permissions::ur_in_force(4)
+        ))
         .bind(&search_pattern)
         .bind(role_id)
+        .bind(&email_indexes)
+        .bind(crate::extractors::permissions::today())
         .fetch_all(&state.db)
         .await?
     } else {
         // Search without role filter
-        sqlx::query_as::<_, User>(
+        sqlx::query_as::<_, User>(&format!(
             r#"
-            SELECT * FROM "Users"
-            WHERE full_name ILIKE $1
-               OR short_name ILIKE $1
-               OR primary_email ILIKE $1
-               OR EXISTS (SELECT 1 FROM unnest(secondary_emails) e WHERE e ILIKE $1)
-            ORDER BY full_name
+            SELECT u.* FROM "Users" u
+            WHERE u.full_name ILIKE $1
+               OR u.short_name ILIKE $1
+               OR u.primary_email ILIKE $1
+               OR EXISTS (SELECT 1 FROM unnest(u.secondary_emails) e WHERE e ILIKE $1)
+               OR u.secondary_email_index && $2::text[]
+            ORDER BY {}
             LIMIT 50
             "#,
-        )
+            order_by
+        ))
         .bind(&search_pattern)
+        .bind(&email_indexes)
         .fetch_all(&state.db)
         .await?
     };
@@ -761,34 +860,16 @@ pub async fn search_users(
         (status = 403, description = "Missing can_edit_staff permission")
     ),
     tag = "users",
-    security(("cookie_auth" = []))
+    security(("cookie_auth" = ["can_edit_staff"]))
 )]
 pub async fn create_user_profile(
     State(state): State<Arc<AppState>>,
-    auth: AuthenticatedUser,
+    RequirePermission { auth, .. }: RequirePermission<CanEditStaff>,
     Json(req): Json<CreateUserProfileRequest>,
 ) -> AppResult<Json<User>> {
-    // Check permission
-    if !crate::extractors::permissions::has_permission_by_name(
-        &state.db,
-        auth.profile_id,
-        auth.is_super_admin,
-        "can_edit_staff",
-    )
-    .await?
-    {
-        return Err(AppError::Forbidden(
-            "Missing can_edit_staff permission".to_string(),
-        ));
-    }
-
-    // Validate PIN format if provided
+    // Validate PIN format if provided (new profiles have no workplace yet, so the default length applies)
     if let Some(ref pin) = req.auth_pin {
-        if pin.len() != 5 || !pin.chars().all(|c| c.is_ascii_digit()) {
-            return Err(AppError::BadRequest(
-                "PIN must be exactly 5 digits".to_string(),
-            ));
-        }
+        validate_pin(pin, &PinPolicy::default())?;
     }
 
     // Validate color format if provided
@@ -802,15 +883,20 @@ pub async fn create_user_profile(
 
     // Generate temporary auth_id using UUID
     let temp_auth_id = format!("temp_{}", uuid::Uuid::new_v4());
+    let secondary_emails = req.secondary_emails.as_deref().map(EncryptedString::wrap_all);
 
     // Insert user profile
     let user = sqlx::query_as::<_, User>(
         r#"
         INSERT INTO "Users" (
             auth_id, full_name, short_name, gmc, primary_email,
-            secondary_emails, tel, comment, auth_pin, color, is_generic_login
+            secondary_emails, tel, comment, auth_pin, color, is_generic_login,
+            secondary_email_index, pin_changed_at, pin_must_change
+        )
+        VALUES (
+            $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, false, $11,
+            CASE WHEN $9::text IS NULL THEN NULL ELSE NOW() END, $9::text IS NOT NULL
         )
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, false)
         RETURNING *
         "#,
     )
@@ -819,11 +905,12 @@ pub async fn create_user_profile(
     .bind(&req.short_name)
     .bind(req.gmc)
     .bind(&req.primary_email)
-    .bind(&req.secondary_emails)
-    .bind(&req.tel)
+    .bind(&secondary_emails)
+    .bind(req.tel.as_deref().map(EncryptedString::wrap_all))
     .bind(&req.comment)
     .bind(&req.auth_pin)
     .bind(&req.color)
+    .bind(secondary_emails.as_deref().map(encrypted::secondary_email_index))
     .fetch_one(&state.db)
     .await?;
 
@@ -847,27 +934,13 @@ pub async fn create_user_profile(
         (status = 403, description = "Missing can_edit_staff permission")
     ),
     tag = "users",
-    security(("cookie_auth" = []))
+    security(("cookie_auth" = ["can_edit_staff"]))
 )]
 pub async fn check_email_usage(
     State(state): State<Arc<AppState>>,
-    auth: AuthenticatedUser,
+    _: RequirePermission<CanEditStaff>,
     Json(req): Json<CheckEmailRequest>,
 ) -> AppResult<Json<CheckEmailResponse>> {
-    // Check permission
-    if !crate::extractors::permissions::has_permission_by_name(
-        &state.db,
-        auth.profile_id,
-        auth.is_super_admin,
-        "can_edit_staff",
-    )
-    .await?
-    {
-        return Err(AppError::Forbidden(
-            "Missing can_edit_staff permission".to_string(),
-        ));
-    }
-
     // Check database for email
     let db_result = sqlx::query_scalar::<_, Option<i32>>(
         r#"
@@ -875,10 +948,12 @@ pub async fn check_email_usage(
         FROM "Users"
         WHERE LOWER(primary_email) = LOWER($1)
            OR $1 = ANY(secondary_emails)
+           OR secondary_email_index && $2::text[]
         LIMIT 1
         "#,
     )
     .bind(&req.email)
+    .bind(encrypted::blind_indexes(&req.email))
     .fetch_optional(&state.db)
     .await?;
 
@@ -936,9 +1011,14 @@ pub async fn verify_profile_identity(
         ));
     }
 
-    // Validate PIN format
-    if req.pin.len() != 5 || !req.pin.chars().all(|c| c.is_ascii_digit()) {
-        return Err(AppError::BadRequest("PIN must be 5 digits".to_string()));
+    // Validate PIN format (any allowed length - stored PINs may predate a PIN policy change)
+    let allowed_lengths = WorkplaceSettings::MIN_PIN_LENGTH as usize..=WorkplaceSettings::MAX_PIN_LENGTH as usize;
+    if !allowed_lengths.contains(&req.pin.len()) || !req.pin.chars().all(|c| c.is_ascii_digit()) {
+        return Err(AppError::BadRequest(format!(
+            "PIN must be {}-{} digits",
+            WorkplaceSettings::MIN_PIN_LENGTH,
+            WorkplaceSettings::MAX_PIN_LENGTH
+        )));
     }
 
     // Fetch target user and their PIN
@@ -982,6 +1062,116 @@ pub async fn verify_profile_identity(
     }))
 }
 
+/// POST /api/users/verify-identity/email - Email a verification code to the selected user (alternative to PIN)
+#[utoipa::path(
+    post,
+    path = "/api/users/verify-identity/email",
+    request_body = RequestEmailVerificationRequest,
+    responses(
+        (status = 200, description = "Verification code emailed", body = RequestEmailVerificationResponse),
+        (status = 400, description = "User has no email address"),
+        (status = 403, description = "Only generic accounts can use this endpoint"),
+        (status = 404, description = "User not found"),
+        (status = 500, description = "Email delivery not configured or failed")
+    ),
+    tag = "users",
+    security(("cookie_auth" = []))
+)]
+pub async fn request_email_verification(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+    Json(req): Json<RequestEmailVerificationRequest>,
+) -> AppResult<Json<RequestEmailVerificationResponse>> {
+    // Verify this is a generic account
+    let is_generic: bool = sqlx::query_scalar(
+        r#"SELECT is_generic_login FROM "Users" WHERE user_profile_id = $1"#,
+    )
+    .bind(auth.profile_id)
+    .fetch_one(&state.db)
+    .await?;
+
+    if !is_generic {
+        return Err(AppError::Forbidden(
+            "This function is only for generic account users".to_string(),
+        ));
+    }
+
+    // Fetch target user's email
+    let email: Option<String> = sqlx::query_scalar(
+        r#"SELECT primary_email FROM "Users" WHERE user_profile_id = $1"#,
+    )
+    .bind(req.user_profile_id)
+    .fetch_optional(&state.db)
+    .await?
+    .ok_or_else(|| AppError::NotFound("User profile not found".to_string()))?;
+
+    let email = email
+        .filter(|e| !e.trim().is_empty())
+        .ok_or_else(|| AppError::BadRequest("No email address set for this user. Use PIN instead.".to_string()))?;
+
+    let (challenge_id, code) = create_email_challenge(req.user_profile_id, auth.profile_id).await;
+    send_verification_email(&state.config, &email, challenge_id, &code).await?;
+
+    tracing::info!(
+        user_profile_id = req.user_profile_id,
+        requested_by = auth.profile_id,
+        "📧🔐 Verification code emailed"
+    );
+
+    Ok(Json(RequestEmailVerificationResponse {
+        success: true,
+        challenge_id,
+        sent_to: mask_email(&email),
+        expires_in_seconds: EMAIL_CHALLENGE_TTL_SECS,
+    }))
+}
+
+/// POST /api/users/verify-identity/email/confirm - Confirm emailed code and issue token
+#[utoipa::path(
+    post,
+    path = "/api/users/verify-identity/email/confirm",
+    request_body = ConfirmEmailVerificationRequest,
+    responses(
+        (status = 200, description = "Identity verified, token issued", body = VerifyIdentityResponse),
+        (status = 400, description = "Code expired"),
+        (status = 401, description = "Incorrect code"),
+        (status = 403, description = "Verification was started from another account")
+    ),
+    tag = "users",
+    security(("cookie_auth" = []))
+)]
+pub async fn confirm_email_verification(
+    State(state): State<Arc<AppState>>,
+    auth: AuthenticatedUser,
+    Json(req): Json<ConfirmEmailVerificationRequest>,
+) -> AppResult<Json<VerifyIdentityResponse>> {
+    let user_profile_id = match confirm_email_challenge(req.challenge_id, req.code.trim(), auth.profile_id).await {
+        Ok(id) => id,
+        Err(e) => {
+            tracing::warn!(
+                challenge_id = %req.challenge_id,
+                attempted_by = auth.profile_id,
+                "📧❌ Email verification failed"
+            );
+            return Err(e);
+        }
+    };
+
+    // Same token contract as PIN verification (valid for 5 minutes)
+    let token = generate_pin_token(user_profile_id, &state.config.pin_token_secret)?;
+
+    tracing::info!(
+        user_profile_id,
+        verified_by = auth.profile_id,
+        "📧✅ Identity verified by email, token issued"
+    );
+
+    Ok(Json(VerifyIdentityResponse {
+        success: true,
+        token: Some(token),
+    }))
+}
+
 /// POST /api/users/change-profile-pin - Change PIN using verification token (Step 2)
 #[utoipa::path(
     post,
@@ -1003,16 +1193,13 @@ pub async fn change_profile_pin(
         return Err(AppError::BadRequest("New PINs do not match".to_string()));
     }
 
-    // Validate PIN format
-    if req.new_pin.len() != 5 || !req.new_pin.chars().all(|c| c.is_ascii_digit()) {
-        return Err(AppError::BadRequest(
-            "PIN must be exactly 5 digits".to_string(),
-        ));
-    }
-
     // Validate and decode token
     let user_profile_id = validate_pin_token(&req.verification_token, &state.config.pin_token_secret)?;
 
+    // Validate PIN against the workplace PIN policy
+    let policy = pin_policy_for_user(&state.db, user_profile_id).await?;
+    validate_pin(&req.new_pin, &policy)?;
+
     // Get current PIN
     let current_pin: Option<String> = sqlx::query_scalar(
         r#"SELECT auth_pin FROM "Users" WHERE user_profile_id = $1"#,
@@ -1031,11 +1218,13 @@ pub async fn change_profile_pin(
     }
 
     // Update PIN
-    sqlx::query(r#"UPDATE "Users" SET auth_pin = $1 WHERE user_profile_id = $2"#)
-        .bind(&req.new_pin)
-        .bind(user_profile_id)
-        .execute(&state.db)
-        .await?;
+    sqlx::query(
+        r#"UPDATE "Users" SET auth_pin = $1, pin_changed_at = NOW(), pin_must_change = false WHERE user_profile_id = $2"#,
+    )
+    .bind(&req.new_pin)
+    .bind(user_profile_id)
+    .execute(&state.db)
+    .await?;
 
     tracing::info!(
         user_profile_id,
@@ -1045,7 +1234,32 @@ pub async fn change_profile_pin(
     Ok(Json(SuccessResponse { success: true }))
 }
 
-/// POST /api/users/create-login - Create Clerk account for existing user profile
+/// Store the Clerk auth_id (and PIN, for generic accounts) on the profile; fails if the profile is gone
+async fn link_clerk_account(db: &sqlx::PgPool, user_profile_id: i32, auth_id: &str, pin: Option<&str>) -> AppResult<()> {
+    let linked = sqlx::query(
+        r#"
+        UPDATE "Users"
+        SET auth_id = $1,
+            auth_pin = COALESCE($2, auth_pin),
+            pin_changed_at = CASE WHEN $2::text IS NULL THEN pin_changed_at ELSE NOW() END
+        WHERE user_profile_id = $3
+        "#,
+    )
+    .bind(auth_id)
+    .bind(pin)
+    .bind(user_profile_id)
+    .execute(db)
+    .await?
+    .rows_affected();
+
+    if linked == 0 {
+        return Err(AppError::NotFound("User profile not found".to_string()));
+    }
+    Ok(())
+}
+
+/// POST /api/users/create-login - Create Clerk account for existing user profile.
+/// The Clerk account is deleted again if linking it fails, so the call can simply be retried.
 #[utoipa::path(
     post,
     path = "/api/users/create-login",
@@ -1053,8 +1267,9 @@ pub async fn change_profile_pin(
     responses(
         (status = 200, description = "Clerk account created and linked", body = CreateLoginResponse),
         (status = 400, description = "Invalid input or email already exists"),
-        (status = 403, description = "Super admin permission required"),
-        (status = 404, description = "User profile not found")
+        (status = 403, description = "Super admin permission required, or missing/invalid X-MFA-Code once TOTP is enabled"),
+        (status = 404, description = "User profile not found"),
+        (status = 500, description = "Clerk refused the account, or linking it failed and it was deleted again")
     ),
     tag = "users",
     security(("cookie_auth" = []))
@@ -1062,6 +1277,7 @@ pub async fn change_profile_pin(
 pub async fn create_login(
     State(state): State<Arc<AppState>>,
     auth: AuthenticatedUser,
+    headers: HeaderMap,
     Json(req): Json<CreateLoginInput>,
 ) -> AppResult<Json<CreateLoginResponse>> {
     // Check permission - super admin only
@@ -1070,6 +1286,7 @@ pub async fn create_login(
             "Super admin permission required".to_string(),
         ));
     }
+    crate::handlers::mfa_handler::ensure_fresh_mfa(&state.db, &auth, &headers).await?;
 
     // Verify user profile exists
     let user = sqlx::query_as::<_, User>(
@@ -1080,6 +1297,16 @@ pub async fn create_login(
     .await?
     .ok_or_else(|| AppError::NotFound("User profile not found".to_string()))?;
 
+    let deactivated: bool = sqlx::query_scalar(
+        r#"SELECT deactivated_at IS NOT NULL FROM "Users" WHERE user_profile_id = $1"#,
+    )
+    .bind(req.user_profile_id)
+    .fetch_one(&state.db)
+    .await?;
+    if deactivated {
+        return Err(AppError::BadRequest("User profile is deactivated".to_string()));
+    }
+
     // Check if email is already used in Clerk
     let email_exists = check_email_in_clerk(&req.email, &state.config.clerk_secret_key).await?;
     if email_exists {
@@ -1090,21 +1317,10 @@ pub async fn create_login(
 
     // Validate PIN format if provided (for generic accounts)
     if let Some(ref pin) = req.pin {
-        if pin.len() != 5 || !pin.chars().all(|c| c.is_ascii_digit()) {
-            return Err(AppError::BadRequest(
-                "PIN must be exactly 5 digits".to_string(),
-            ));
-        }
+        let policy = pin_policy_for_user(&state.db, req.user_profile_id).await?;
+        validate_pin(pin, &policy)?;
     }
 
-    // Call Clerk API to create user
-    let client = reqwest::Client::new();
-    let clerk_request = serde_json::json!({
-        "email_address": [req.email],
-        "password": req.temp_password,
-        "skip_password_requirement": req.is_generic_login,
-    });
-
     tracing::info!(
         user_profile_id = req.user_profile_id,
         email = %req.email,
@@ -1112,54 +1328,43 @@ pub async fn create_login(
         "✨ Creating Clerk account"
     );
 
-    let response = client
-        .post("https://api.clerk.com/v1/users")
-        .header("Authorization", format!("Bearer {}", state.config.clerk_secret_key))
-        .header("Content-Type", "application/json")
-        .json(&clerk_request)
-        .send()
-        .await
-        .map_err(|e| {
-            tracing::error!(error = %e, "❌ Failed to call Clerk API");
-            AppError::Internal(format!("Failed to create Clerk user: {}", e))
-        })?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let body = response.text().await.unwrap_or_default();
-        tracing::error!(status = %status, body, "❌ Clerk API returned error");
-        return Err(AppError::Internal(format!(
-            "Clerk API error: {} - {}",
-            status, body
-        )));
-    }
-
-    let clerk_user: serde_json::Value = response.json().await.map_err(|e| {
-        tracing::error!(error = %e, "❌ Failed to parse Clerk response");
-        AppError::Internal(format!("Failed to parse Clerk response: {}", e))
-    })?;
-
-    let auth_id = clerk_user["id"]
-        .as_str()
-        .ok_or_else(|| AppError::Internal("Clerk response missing user id".to_string()))?
-        .to_string();
+    let auth_id = create_clerk_user(
+        &req.email,
+        &req.temp_password,
+        req.is_generic_login,
+        req.user_profile_id,
+        &state.config.clerk_secret_key,
+    )
+    .await?;
 
-    // Update user profile with Clerk auth_id and PIN (if provided)
-    if let Some(pin) = req.pin {
-        sqlx::query(
-            r#"UPDATE "Users" SET auth_id = $1, auth_pin = $2 WHERE user_profile_id = $3"#,
-        )
-        .bind(&auth_id)
-        .bind(&pin)
-        .bind(req.user_profile_id)
-        .execute(&state.db)
-        .await?;
-    } else {
-        sqlx::query(r#"UPDATE "Users" SET auth_id = $1 WHERE user_profile_id = $2"#)
+    // The Clerk account exists from here on: if it cannot be linked, delete it again so a retry starts clean
+    if let Err(e) = link_clerk_account(&state.db, req.user_profile_id, &auth_id, req.pin.as_deref()).await {
+        tracing::error!(user_profile_id = req.user_profile_id, auth_id = %auth_id, error = %e, "❌ Linking the new Clerk account failed, deleting it");
+        if let Err(delete_error) = delete_clerk_user(&auth_id, &state.config.clerk_secret_key).await {
+            // Best effort: the database may be what failed, in which case only the log records the orphan
+            let recorded = sqlx::query(
+                r#"
+                INSERT INTO "ClerkRollbackFailures" (clerk_user_id, user_profile_id, email, error)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (clerk_user_id) DO NOTHING
+                "#,
+            )
             .bind(&auth_id)
             .bind(req.user_profile_id)
+            .bind(&req.email)
+            .bind(format!("{}; rollback: {}", e, delete_error))
             .execute(&state.db)
-            .await?;
+            .await
+            .is_ok();
+            tracing::error!(
+                user_profile_id = req.user_profile_id,
+                auth_id = %auth_id,
+                error = %delete_error,
+                recorded,
+                "❌ Could not delete the orphaned Clerk account; reconcile via /api/users/clerk-orphans"
+            );
+        }
+        return Err(e);
     }
 
     tracing::info!(
@@ -1175,6 +1380,19 @@ pub async fn create_login(
     }))
 }
 
+/// Failed password change attempts (wrong current password) per profile per window
+const PASSWORD_CHANGE_LIMIT: u32 = 5;
+const PASSWORD_CHANGE_WINDOW: std::time::Duration = std::time::Duration::from_secs(15 * 60);
+
+/// Failed attempts seen per profile in the current window (the entry expires with the first failure's window)
+static PASSWORD_CHANGE_ATTEMPTS: once_cell::sync::Lazy<moka::future::Cache<i32, Arc<std::sync::atomic::AtomicU32>>> =
+    once_cell::sync::Lazy::new(|| {
+        moka::future::Cache::builder()
+            .time_to_live(PASSWORD_CHANGE_WINDOW)
+            .max_capacity(10_000)
+            .build()
+    });
+
 /// POST /api/users/me/password - Change own password (self-service)
 #[utoipa::path(
     post,
@@ -1182,9 +1400,10 @@ pub async fn create_login(
     request_body = ChangePasswordInput,
     responses(
         (status = 200, description = "Password changed successfully", body = SuccessResponse),
-        (status = 400, description = "Invalid input or passwords don't match"),
-        (status = 401, description = "Current password incorrect"),
-        (status = 403, description = "Generic accounts cannot change password")
+        (status = 400, description = "Passwords don't match, or the current password is incorrect"),
+        (status = 403, description = "Generic accounts cannot change password"),
+        (status = 422, description = "The new password fails the password policy or Clerk's checks; one entry per problem under errors", body = InvalidFieldsBody),
+        (status = 429, description = "More than 5 failed attempts in 15 minutes")
     ),
     tag = "users",
     security(("cookie_auth" = []))
@@ -1194,12 +1413,26 @@ pub async fn change_own_password(
     auth: AuthenticatedUser,
     Json(input): Json<ChangePasswordInput>,
 ) -> AppResult<Json<SuccessResponse>> {
+    let failures = PASSWORD_CHANGE_ATTEMPTS
+        .get(&auth.profile_id)
+        .await
+        .map_or(0, |count| count.load(std::sync::atomic::Ordering::Relaxed));
+    if failures >= PASSWORD_CHANGE_LIMIT {
+        tracing::warn!(user_profile_id = auth.profile_id, "🔐 Password change rate limited");
+        return Err(AppError::RateLimited {
+            message: "Too many password change attempts; try again later".to_string(),
+            retry_after_secs: PASSWORD_CHANGE_WINDOW.as_secs() as i64,
+        });
+    }
+
     // Validate new passwords match
     if input.new_password != input.confirm_new_password {
         return Err(AppError::BadRequest(
             "New passwords do not match".to_string(),
         ));
     }
+    let policy = &state.config.password_policy;
+    password_policy::policy_error(password_policy::check_password(policy, &input.new_password, &input.current_password))?;
 
     // Get user to check generic account status and auth_id
     let user = sqlx::query_as::<_, User>(r#"SELECT * FROM "Users" WHERE user_profile_id = $1"#)
@@ -1249,18 +1482,27 @@ pub async fn change_own_password(
         if status == reqwest::StatusCode::UNPROCESSABLE_ENTITY
             || status == reqwest::StatusCode::BAD_REQUEST
         {
+            // Only wrong guesses count towards the limit
+            PASSWORD_CHANGE_ATTEMPTS
+                .get_with(auth.profile_id, async { Arc::new(std::sync::atomic::AtomicU32::new(0)) })
+                .await
+                .fetch_add(1, std::sync::atomic::Ordering::Relaxed);
             return Err(AppError::BadRequest(
                 "Current password is incorrect".to_string(),
             ));
         }
         let body = verify_response.text().await.unwrap_or_default();
-        tracing::error!(status = %status, body, "❌ Clerk password verification failed");
+        tracing::error!(status = %status, body = %crate::redaction::redact_body(&body), "❌ Clerk password verification failed");
         return Err(AppError::Internal(format!(
             "Password verification failed: {} - {}",
             status, body
         )));
     }
 
+    if policy.check_breached && password_policy::is_breached(&input.new_password).await {
+        password_policy::policy_error(vec![password_policy::breached()])?;
+    }
+
     // Update password with Clerk
     let update_request = serde_json::json!({
         "password": input.new_password,
@@ -1292,7 +1534,15 @@ pub async fn change_own_password(
     if !update_response.status().is_success() {
         let status = update_response.status();
         let body = update_response.text().await.unwrap_or_default();
-        tracing::error!(status = %status, body, "❌ Clerk password update failed");
+        // Clerk's own rules (strength, breaches) come back as 422 with per-field errors
+        if status == reqwest::StatusCode::UNPROCESSABLE_ENTITY || status == reqwest::StatusCode::BAD_REQUEST {
+            let errors = password_policy::clerk_field_errors(&body);
+            if !errors.is_empty() {
+                tracing::info!(user_profile_id = auth.profile_id, codes = ?errors.iter().map(|e| e.code).collect::<Vec<_>>(), "🔑 Clerk rejected the new password");
+                password_policy::policy_error(errors)?;
+            }
+        }
+        tracing::error!(status = %status, body = %crate::redaction::redact_body(&body), "❌ Clerk password update failed");
         return Err(AppError::Internal(format!(
             "Password update failed: {} - {}",
             status, body
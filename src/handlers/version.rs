@@ -0,0 +1,82 @@
+use axum::{extract::State, http::request::Parts};
+use moka::future::Cache;
+use once_cell::sync::Lazy;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::{
+    build_info::{build_info, BuildInfo},
+    extractors::Json,
+    AppError, AppResult, AppState,
+};
+
+/// Requests per client address per window; the endpoint is unauthenticated, so keep it cheap to serve
+const VERSION_RATE_LIMIT: u32 = 30;
+const VERSION_RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Requests seen per client address in the current window (the entry expires with the window)
+static VERSION_REQUESTS: Lazy<Cache<String, Arc<AtomicU32>>> = Lazy::new(|| {
+    Cache::builder()
+        .time_to_live(VERSION_RATE_WINDOW)
+        .max_capacity(10_000)
+        .build()
+});
+
+/// The rate-limit bucket for a request: the address the trusted proxy saw or the socket peer, never a header value
+/// the client chose. Without either (e.g. a Unix socket with no trusted proxy) every request shares one bucket.
+fn rate_limit_key(parts: &Parts, trusted_hops: usize) -> String {
+    crate::extractors::auth::request_client_ip(parts, trusted_hops).unwrap_or_default()
+}
+
+/// GET /api/version - Build info so frontends can spot a new or incompatible deployment
+#[utoipa::path(
+    get,
+    path = "/api/version",
+    responses(
+        (status = 200, description = "Version, git commit, build time and OpenAPI spec hash", body = BuildInfo),
+        (status = 429, description = "More than 30 requests a minute from this client")
+    ),
+    tag = "health"
+)]
+pub async fn version_handler(State(state): State<Arc<AppState>>, parts: Parts) -> AppResult<Json<&'static BuildInfo>> {
+    let client = rate_limit_key(&parts, state.config.trusted_proxy_hops);
+
+    let count = VERSION_REQUESTS
+        .get_with(client, async { Arc::new(AtomicU32::new(0)) })
+        .await
+        .fetch_add(1, Ordering::Relaxed);
+    if count >= VERSION_RATE_LIMIT {
+        return Err(AppError::RateLimited {
+            message: "Too many version requests".to_string(),
+            retry_after_secs: VERSION_RATE_WINDOW.as_secs() as i64,
+        });
+    }
+
+    Ok(Json(build_info()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::ConnectInfo, http::Request};
+    use std::net::SocketAddr;
+
+    fn parts(forwarded_for: &str) -> Parts {
+        let (mut parts, _) =
+            Request::builder().header("x-forwarded-for", forwarded_for).body(()).unwrap().into_parts();
+        parts.extensions.insert(ConnectInfo("10.0.0.2:443".parse::<SocketAddr>().unwrap()));
+        parts
+    }
+
+    #[test]
+    fn test_rotating_forwarded_for_keeps_the_bucket() {
+        // Directly exposed: the header is ignored and the peer is the key
+        assert_eq!(rate_limit_key(&parts("1.1.1.1"), 0), "10.0.0.2");
+        assert_eq!(rate_limit_key(&parts("2.2.2.2"), 0), "10.0.0.2");
+
+        // Behind one proxy: only the entry it appended counts, whatever the client put before it
+        assert_eq!(rate_limit_key(&parts("1.1.1.1, 203.0.113.7"), 1), "203.0.113.7");
+        assert_eq!(rate_limit_key(&parts("2.2.2.2, 203.0.113.7"), 1), "203.0.113.7");
+    }
+}
@@ -1,27 +1,129 @@
 use axum::{
     extract::{Path, State},
-    Json,
+    http::{HeaderMap, HeaderName, HeaderValue},
 };
+use chrono::NaiveTime;
 use moka::future::Cache;
 use once_cell::sync::Lazy;
+use sqlx::PgPool;
 use std::sync::Arc;
 use std::time::Duration;
 
 use crate::{
-    extractors::AuthenticatedUser,
-    models::{CreateWorkplaceInput, DependencyCount, UpdateWorkplaceInput, Workplace, WorkplaceMutationResponse},
+    cache,
+    extractors::{AuthenticatedUser, Json},
+    handlers::roles_handler::{count_role_dependencies, delete_role_dependencies},
+    models::{
+        CreateWorkplaceInput, DependencyCount, PinPolicy, UpdateWorkplaceInput, UpdateWorkplaceSettingsInput, Workplace,
+        WorkplaceMutationResponse, WorkplaceSettings,
+    },
     AppError, AppResult, AppState,
 };
 
-// Cache all workplaces with 60-second TTL
-static WORKPLACES_CACHE: Lazy<Cache<&'static str, Vec<Workplace>>> = Lazy::new(|| {
+
+// Cache settings per workplace with 60-second TTL (read on shift creation, marketplace and PIN changes)
+static SETTINGS_CACHE: Lazy<Cache<i32, WorkplaceSettings>> = Lazy::new(|| {
     Cache::builder()
+        .max_capacity(1_000)
         .time_to_live(Duration::from_secs(60))
         .build()
 });
 
-async fn invalidate_workplaces_cache() {
-    WORKPLACES_CACHE.invalidate(&"all").await;
+/// Load a workplace's settings, falling back to defaults when none have been saved
+pub async fn load_workplace_settings(db: &PgPool, workplace_id: i32) -> AppResult<WorkplaceSettings> {
+    if let Some(cached) = SETTINGS_CACHE.get(&workplace_id).await {
+        return Ok(cached);
+    }
+
+    let settings = sqlx::query_as::<_, WorkplaceSettings>(
+        r#"
+        SELECT
+            workplace_id,
+            to_char(default_shift_start, 'HH24:MI:SS') AS default_shift_start,
+            to_char(default_shift_end, 'HH24:MI:SS') AS default_shift_end,
+            marketplace_enabled,
+            pin_length,
+            pin_require_complex,
+            pin_expiry_days,
+            cover_window_days,
+            duplicate_shifts,
+            geofence_latitude,
+            geofence_longitude,
+            geofence_radius_m
+        FROM "WorkplaceSettings"
+        WHERE workplace_id = $1
+        "#,
+    )
+    .bind(workplace_id)
+    .fetch_optional(db)
+    .await?
+    .unwrap_or_else(|| WorkplaceSettings::defaults(workplace_id));
+
+    SETTINGS_CACHE.insert(workplace_id, settings.clone()).await;
+    Ok(settings)
+}
+
+/// Load the settings of the workplace a role belongs to
+pub async fn load_role_settings(db: &PgPool, role_id: i32) -> AppResult<WorkplaceSettings> {
+    let workplace_id: i32 = sqlx::query_scalar(r#"SELECT workplace_id::int4 FROM "Roles" WHERE id = $1"#)
+        .bind(role_id)
+        .fetch_optional(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Role {} not found", role_id)))?;
+
+    load_workplace_settings(db, workplace_id).await
+}
+
+/// PIN policy for a user: the strictest settings across the workplaces they have roles in
+/// (longest length, complexity if any workplace requires it, shortest non-zero expiry)
+pub async fn pin_policy_for_user(db: &PgPool, user_profile_id: i32) -> AppResult<PinPolicy> {
+    let (length, require_complex, expiry_days): (Option<i16>, bool, Option<i16>) = sqlx::query_as(&format!(
+        r#"
+        SELECT
+            MAX(COALESCE(ws.pin_length, $2))::int2,
+            COALESCE(BOOL_OR(ws.pin_require_complex), false),
+            MIN(NULLIF(ws.pin_expiry_days, 0))::int2
+        FROM "UserRoles" ur
+        INNER JOIN "Roles" r ON ur.role_id = r.id
+        LEFT JOIN "WorkplaceSettings" ws ON ws.workplace_id = r.workplace_id
+        WHERE ur.user_profile_id = $1 AND {ur_in_force}
+        "#,
+        ur_in_force = crate::extractors::permissions::ur_in_force(3)
+    ))
+    .bind(user_profile_id)
+    .bind(WorkplaceSettings::DEFAULT_PIN_LENGTH)
+    .bind(crate::extractors::permissions::today())
+    .fetch_one(db)
+    .await?;
+
+    Ok(PinPolicy {
+        length: length.unwrap_or(WorkplaceSettings::DEFAULT_PIN_LENGTH),
+        require_complex,
+        expiry_days: expiry_days.unwrap_or(0),
+    })
+}
+
+/// Normalise an HH:MM or HH:MM:SS time to HH:MM:SS; an empty string clears the value
+fn parse_settings_time(field: &str, value: &str) -> AppResult<Option<String>> {
+    if value.is_empty() {
+        return Ok(None);
+    }
+
+    NaiveTime::parse_from_str(value, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(value, "%H:%M"))
+        .map(|t| Some(t.format("%H:%M:%S").to_string()))
+        .map_err(|_| AppError::BadRequest(format!("Invalid {}: expected HH:MM or HH:MM:SS", field)))
+}
+
+fn check_pin_length(pin_length: i16) -> AppResult<()> {
+    if !(WorkplaceSettings::MIN_PIN_LENGTH..=WorkplaceSettings::MAX_PIN_LENGTH).contains(&pin_length) {
+        return Err(AppError::BadRequest(format!(
+            "pin_length must be between {} and {}",
+            WorkplaceSettings::MIN_PIN_LENGTH,
+            WorkplaceSettings::MAX_PIN_LENGTH
+        )));
+    }
+    Ok(())
 }
 
 /// GET /api/workplaces
@@ -35,9 +137,11 @@ async fn invalidate_workplaces_cache() {
 )]
 pub async fn get_workplaces(
     State(state): State<Arc<AppState>>,
-) -> AppResult<Json<Vec<Workplace>>> {
-    if let Some(cached) = WORKPLACES_CACHE.get(&"all").await {
-        return Ok(Json(cached));
+) -> AppResult<([(HeaderName, HeaderValue); 1], Json<Vec<Workplace>>)> {
+    let headers = cache::cache_control(state.config.reference_cache_max_age);
+
+    if let Some(cached) = state.caches.workplaces.get().await {
+        return Ok((headers, Json(cached)));
     }
 
     let workplaces =
@@ -45,8 +149,8 @@ pub async fn get_workplaces(
             .fetch_all(&state.db)
             .await?;
 
-    WORKPLACES_CACHE.insert("all", workplaces.clone()).await;
-    Ok(Json(workplaces))
+    state.caches.workplaces.insert(workplaces.clone()).await;
+    Ok((headers, Json(workplaces)))
 }
 
 /// POST /api/workplaces - Create a new workplace
@@ -88,7 +192,7 @@ pub async fn create_workplace(
     .fetch_one(&state.db)
     .await?;
 
-    invalidate_workplaces_cache().await;
+    state.caches.invalidate_workplaces().await;
     Ok(Json(workplace))
 }
 
@@ -180,7 +284,7 @@ pub async fn update_workplace(
 
     match workplace {
         Some(wp) => {
-            invalidate_workplaces_cache().await;
+            state.caches.invalidate_workplaces().await;
             Ok(Json(wp))
         }
         None => Err(AppError::NotFound(format!(
@@ -190,6 +294,188 @@ pub async fn update_workplace(
     }
 }
 
+/// GET /api/workplaces/{id}/settings
+#[utoipa::path(
+    get,
+    path = "/api/workplaces/{id}/settings",
+    params(
+        ("id" = i32, Path, description = "Workplace ID")
+    ),
+    responses(
+        (status = 200, description = "Workplace settings (defaults if never saved)", body = WorkplaceSettings),
+        (status = 404, description = "Workplace not found")
+    ),
+    tag = "workplaces",
+    security(("cookie_auth" = []))
+)]
+pub async fn get_workplace_settings(
+    State(state): State<Arc<AppState>>,
+    Path(workplace_id): Path<i32>,
+    _auth: AuthenticatedUser,
+) -> AppResult<Json<WorkplaceSettings>> {
+    ensure_workplace_exists(&state.db, workplace_id).await?;
+
+    let settings = load_workplace_settings(&state.db, workplace_id).await?;
+    Ok(Json(settings))
+}
+
+/// PUT /api/workplaces/{id}/settings - Update workplace settings
+#[utoipa::path(
+    put,
+    path = "/api/workplaces/{id}/settings",
+    params(
+        ("id" = i32, Path, description = "Workplace ID")
+    ),
+    request_body = UpdateWorkplaceSettingsInput,
+    responses(
+        (status = 200, description = "Workplace settings updated", body = WorkplaceSettings),
+        (status = 400, description = "Invalid time, PIN length, PIN expiry or cover window"),
+        (status = 403, description = "Super admin permission required"),
+        (status = 404, description = "Workplace not found")
+    ),
+    tag = "workplaces",
+    security(("cookie_auth" = []))
+)]
+pub async fn update_workplace_settings(
+    State(state): State<Arc<AppState>>,
+    Path(workplace_id): Path<i32>,
+    auth: AuthenticatedUser,
+    Json(input): Json<UpdateWorkplaceSettingsInput>,
+) -> AppResult<Json<WorkplaceSettings>> {
+    // Check permission - super admin only
+    if !auth.is_super_admin {
+        return Err(AppError::Forbidden(
+            "Super admin permission required".to_string(),
+        ));
+    }
+
+    ensure_workplace_exists(&state.db, workplace_id).await?;
+
+    // Merge input over the current settings
+    let mut settings = load_workplace_settings(&state.db, workplace_id).await?;
+
+    if let Some(ref start) = input.default_shift_start {
+        settings.default_shift_start = parse_settings_time("default_shift_start", start)?;
+    }
+    if let Some(ref end) = input.default_shift_end {
+        settings.default_shift_end = parse_settings_time("default_shift_end", end)?;
+    }
+    if let Some(enabled) = input.marketplace_enabled {
+        settings.marketplace_enabled = enabled;
+    }
+    if let Some(pin_length) = input.pin_length {
+        check_pin_length(pin_length)?;
+        settings.pin_length = pin_length;
+    }
+    if let Some(require_complex) = input.pin_require_complex {
+        settings.pin_require_complex = require_complex;
+    }
+    if let Some(days) = input.pin_expiry_days {
+        if !(0..=WorkplaceSettings::MAX_PIN_EXPIRY_DAYS).contains(&days) {
+            return Err(AppError::BadRequest(format!(
+                "pin_expiry_days must be between 0 and {}",
+                WorkplaceSettings::MAX_PIN_EXPIRY_DAYS
+            )));
+        }
+        settings.pin_expiry_days = days;
+    }
+    if let Some(days) = input.cover_window_days {
+        if !(0..=WorkplaceSettings::MAX_COVER_WINDOW_DAYS).contains(&days) {
+            return Err(AppError::BadRequest(format!(
+                "cover_window_days must be between 0 and {}",
+                WorkplaceSettings::MAX_COVER_WINDOW_DAYS
+            )));
+        }
+        settings.cover_window_days = days;
+    }
+    if let Some(policy) = input.duplicate_shifts {
+        settings.duplicate_shifts = policy.as_str().to_string();
+    }
+    if let Some(latitude) = input.geofence_latitude {
+        if !(-90.0..=90.0).contains(&latitude) {
+            return Err(AppError::BadRequest("geofence_latitude must be between -90 and 90".to_string()));
+        }
+        settings.geofence_latitude = Some(latitude);
+    }
+    if let Some(longitude) = input.geofence_longitude {
+        if !(-180.0..=180.0).contains(&longitude) {
+            return Err(AppError::BadRequest("geofence_longitude must be between -180 and 180".to_string()));
+        }
+        settings.geofence_longitude = Some(longitude);
+    }
+    if let Some(radius) = input.geofence_radius_m {
+        if !(0..=WorkplaceSettings::MAX_GEOFENCE_RADIUS_M).contains(&radius) {
+            return Err(AppError::BadRequest(format!(
+                "geofence_radius_m must be between 0 and {}",
+                WorkplaceSettings::MAX_GEOFENCE_RADIUS_M
+            )));
+        }
+        settings.geofence_radius_m = radius;
+    }
+    if settings.geofence_radius_m > 0 && (settings.geofence_latitude.is_none() || settings.geofence_longitude.is_none()) {
+        return Err(AppError::BadRequest(
+            "geofence_latitude and geofence_longitude are required when geofence_radius_m is set".to_string(),
+        ));
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO "WorkplaceSettings" (
+            workplace_id, default_shift_start, default_shift_end,
+            marketplace_enabled, pin_length, cover_window_days,
+            pin_require_complex, pin_expiry_days, duplicate_shifts,
+            geofence_latitude, geofence_longitude, geofence_radius_m
+        )
+        VALUES ($1, $2::time, $3::time, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+        ON CONFLICT (workplace_id) DO UPDATE SET
+            default_shift_start = EXCLUDED.default_shift_start,
+            default_shift_end = EXCLUDED.default_shift_end,
+            marketplace_enabled = EXCLUDED.marketplace_enabled,
+            pin_length = EXCLUDED.pin_length,
+            cover_window_days = EXCLUDED.cover_window_days,
+            pin_require_complex = EXCLUDED.pin_require_complex,
+            pin_expiry_days = EXCLUDED.pin_expiry_days,
+            duplicate_shifts = EXCLUDED.duplicate_shifts,
+            geofence_latitude = EXCLUDED.geofence_latitude,
+            geofence_longitude = EXCLUDED.geofence_longitude,
+            geofence_radius_m = EXCLUDED.geofence_radius_m,
+            updated_at = NOW()
+        "#,
+    )
+    .bind(workplace_id)
+    .bind(&settings.default_shift_start)
+    .bind(&settings.default_shift_end)
+    .bind(settings.marketplace_enabled)
+    .bind(settings.pin_length)
+    .bind(settings.cover_window_days)
+    .bind(settings.pin_require_complex)
+    .bind(settings.pin_expiry_days)
+    .bind(&settings.duplicate_shifts)
+    .bind(settings.geofence_latitude)
+    .bind(settings.geofence_longitude)
+    .bind(settings.geofence_radius_m)
+    .execute(&state.db)
+    .await?;
+
+    SETTINGS_CACHE.invalidate(&workplace_id).await;
+
+    tracing::info!(workplace_id, admin_id = auth.profile_id, "⚙️ Workplace settings updated");
+
+    Ok(Json(settings))
+}
+
+pub(crate) async fn ensure_workplace_exists(db: &PgPool, workplace_id: i32) -> AppResult<()> {
+    let exists: bool = sqlx::query_scalar(r#"SELECT EXISTS(SELECT 1 FROM "Workplaces" WHERE id = $1)"#)
+        .bind(workplace_id)
+        .fetch_one(db)
+        .await?;
+
+    if !exists {
+        return Err(AppError::NotFound(format!("Workplace {} not found", workplace_id)));
+    }
+    Ok(())
+}
+
 /// DELETE /api/workplaces/{id} - Delete a workplace
 #[utoipa::path(
     delete,
@@ -229,7 +515,7 @@ pub async fn delete_workplace(
         )));
     }
 
-    invalidate_workplaces_cache().await;
+    state.caches.invalidate_workplaces().await;
     Ok(Json(WorkplaceMutationResponse {
         success: true,
         message: Some("Workplace deleted successfully".to_string()),
@@ -270,66 +556,7 @@ pub async fn get_workplace_dependencies(
     .fetch_all(&state.db)
     .await?;
 
-    if role_ids.is_empty() {
-        return Ok(Json(DependencyCount {
-            roles: 0,
-            user_roles: 0,
-            job_plans: 0,
-            shifts: 0,
-            shift_requests: 0,
-            templates: 0,
-            diary_entries: 0,
-            audit_entries: 0,
-            cod_entries: 0,
-            unique_staff: 0,
-        }));
-    }
-
-    // Run all 9 COUNT queries in parallel for ~9x speedup
-    let db = &state.db;
-    let (
-        user_roles_count,
-        job_plans_count,
-        shifts_count,
-        templates_count,
-        diary_count,
-        audit_count,
-        cod_count,
-        shift_requests_count,
-        unique_staff,
-    ) = tokio::try_join!(
-        sqlx::query_scalar::<_, i64>(r#"SELECT COUNT(*)::int8 FROM "UserRoles" WHERE role_id = ANY($1)"#)
-            .bind(&role_ids).fetch_one(db),
-        sqlx::query_scalar::<_, i64>(r#"SELECT COUNT(*)::int8 FROM "JobPlans" WHERE role_id = ANY($1)"#)
-            .bind(&role_ids).fetch_one(db),
-        sqlx::query_scalar::<_, i64>(r#"SELECT COUNT(*)::int8 FROM "Shifts" WHERE role_id = ANY($1)"#)
-            .bind(&role_ids).fetch_one(db),
-        sqlx::query_scalar::<_, i64>(r#"SELECT COUNT(*)::int8 FROM "ShiftTemplates" WHERE role_id = ANY($1)"#)
-            .bind(&role_ids).fetch_one(db),
-        sqlx::query_scalar::<_, i64>(r#"SELECT COUNT(*)::int8 FROM "Diary" WHERE role_id = ANY($1)"#)
-            .bind(&role_ids).fetch_one(db),
-        sqlx::query_scalar::<_, i64>(r#"SELECT COUNT(*)::int8 FROM "ShiftAudit" WHERE role_id = ANY($1)"#)
-            .bind(&role_ids).fetch_one(db),
-        sqlx::query_scalar::<_, i64>(r#"SELECT COUNT(*)::int8 FROM "COD" WHERE role_id = ANY($1)"#)
-            .bind(&role_ids).fetch_one(db),
-        sqlx::query_scalar::<_, i64>(r#"SELECT COUNT(*)::int8 FROM "ShiftRequests" WHERE shift_id IN (SELECT uuid FROM "Shifts" WHERE role_id = ANY($1))"#)
-            .bind(&role_ids).fetch_one(db),
-        sqlx::query_scalar::<_, i64>(r#"SELECT COUNT(DISTINCT user_profile_id)::int8 FROM "UserRoles" WHERE role_id = ANY($1)"#)
-            .bind(&role_ids).fetch_one(db),
-    )?;
-
-    Ok(Json(DependencyCount {
-        roles: role_ids.len() as i32,
-        user_roles: user_roles_count as i32,
-        job_plans: job_plans_count as i32,
-        shifts: shifts_count as i32,
-        shift_requests: shift_requests_count as i32,
-        templates: templates_count as i32,
-        diary_entries: diary_count as i32,
-        audit_entries: audit_count as i32,
-        cod_entries: cod_count as i32,
-        unique_staff: unique_staff as i32,
-    }))
+    Ok(Json(count_role_dependencies(&state.db, &role_ids).await?))
 }
 
 /// DELETE /api/workplaces/{id}/nuke - CASCADE delete workplace and ALL related data
@@ -341,7 +568,7 @@ pub async fn get_workplace_dependencies(
     ),
     responses(
         (status = 200, description = "Workplace and all dependencies deleted", body = WorkplaceMutationResponse),
-        (status = 403, description = "Super admin permission required"),
+        (status = 403, description = "Super admin permission required, or missing/invalid X-MFA-Code once TOTP is enabled"),
         (status = 404, description = "Workplace not found")
     ),
     tag = "workplaces",
@@ -351,6 +578,7 @@ pub async fn nuke_workplace(
     State(state): State<Arc<AppState>>,
     Path(workplace_id): Path<i32>,
     auth: AuthenticatedUser,
+    headers: HeaderMap,
 ) -> AppResult<Json<WorkplaceMutationResponse>> {
     // Check permission - super admin only
     if !auth.is_super_admin {
@@ -358,11 +586,12 @@ pub async fn nuke_workplace(
             "Super admin permission required".to_string(),
         ));
     }
+    crate::handlers::mfa_handler::ensure_fresh_mfa(&state.db, &auth, &headers).await?;
 
     tracing::warn!("⚠️ NUKE: Starting cascade delete of workplace {}", workplace_id);
 
     // Start transaction
-    let mut tx = state.db.begin().await?;
+    let mut tx = crate::db::begin_as_user(&state.db, auth.profile_id).await?;
 
     // Get all roles for this workplace
     let role_ids: Vec<i32> = sqlx::query_scalar(
@@ -384,7 +613,7 @@ pub async fn nuke_workplace(
         }
 
         tx.commit().await?;
-        invalidate_workplaces_cache().await;
+        state.caches.invalidate_workplaces().await;
         tracing::info!("🗑️ NUKE: Workplace deleted (no roles)");
         return Ok(Json(WorkplaceMutationResponse {
             success: true,
@@ -394,64 +623,15 @@ pub async fn nuke_workplace(
 
     tracing::info!("🗑️ NUKE: Deleting {} roles and all related data", role_ids.len());
 
-    // Delete in order (deepest children → parent), using parameterized queries:
-
-    // 1. Shift requests (references shifts via subquery)
-    sqlx::query(r#"DELETE FROM "ShiftRequests" WHERE shift_id IN (SELECT uuid FROM "Shifts" WHERE role_id = ANY($1))"#)
-        .bind(&role_ids)
-        .execute(&mut *tx)
-        .await?;
-    tracing::info!("🗑️ NUKE: Deleted shift requests");
-
-    // 2. Job plans (references roles)
-    sqlx::query(r#"DELETE FROM "JobPlans" WHERE role_id = ANY($1)"#)
-        .bind(&role_ids)
-        .execute(&mut *tx)
-        .await?;
-
-    // 3. Shift audit trail
-    sqlx::query(r#"DELETE FROM "ShiftAudit" WHERE role_id = ANY($1)"#)
-        .bind(&role_ids)
-        .execute(&mut *tx)
-        .await?;
-
-    // 4. Diary entries
-    sqlx::query(r#"DELETE FROM "Diary" WHERE role_id = ANY($1)"#)
-        .bind(&role_ids)
-        .execute(&mut *tx)
-        .await?;
-
-    // 5. Shifts
-    sqlx::query(r#"DELETE FROM "Shifts" WHERE role_id = ANY($1)"#)
-        .bind(&role_ids)
-        .execute(&mut *tx)
-        .await?;
-
-    // 6. Shift templates
-    sqlx::query(r#"DELETE FROM "ShiftTemplates" WHERE role_id = ANY($1)"#)
-        .bind(&role_ids)
-        .execute(&mut *tx)
-        .await?;
+    delete_role_dependencies(&mut tx, &role_ids).await?;
 
-    // 7. User role assignments
-    sqlx::query(r#"DELETE FROM "UserRoles" WHERE role_id = ANY($1)"#)
-        .bind(&role_ids)
-        .execute(&mut *tx)
-        .await?;
-
-    // 8. COD entries
-    sqlx::query(r#"DELETE FROM "COD" WHERE role_id = ANY($1)"#)
-        .bind(&role_ids)
-        .execute(&mut *tx)
-        .await?;
-
-    // 9. Roles
+    // Then the roles
     sqlx::query(r#"DELETE FROM "Roles" WHERE workplace_id = $1"#)
         .bind(workplace_id)
         .execute(&mut *tx)
         .await?;
 
-    // 10. Finally, the workplace
+    // Finally, the workplace
     let result = sqlx::query(r#"DELETE FROM "Workplaces" WHERE id = $1"#)
         .bind(workplace_id)
         .execute(&mut *tx)
@@ -462,11 +642,33 @@ pub async fn nuke_workplace(
     }
 
     tx.commit().await?;
-    invalidate_workplaces_cache().await;
+    state.caches.invalidate_workplaces().await;
     tracing::warn!("⚠️ NUKE: Workplace {} annihilated ({} roles deleted)", workplace_id, role_ids.len());
 
     Ok(Json(WorkplaceMutationResponse {
         success: true,
         message: Some(format!("Workplace and {} roles with all dependencies deleted", role_ids.len())),
     }))
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_settings_time() {
+        assert_eq!(parse_settings_time("default_shift_start", "08:00").unwrap().as_deref(), Some("08:00:00"));
+        assert_eq!(parse_settings_time("default_shift_start", "20:30:15").unwrap().as_deref(), Some("20:30:15"));
+        assert_eq!(parse_settings_time("default_shift_end", "").unwrap(), None);
+        assert!(matches!(parse_settings_time("default_shift_end", "25:00"), Err(AppError::BadRequest(_))));
+        assert!(matches!(parse_settings_time("default_shift_end", "8am"), Err(AppError::BadRequest(_))));
+    }
+
+    #[test]
+    fn test_check_pin_length() {
+        assert!(check_pin_length(4).is_ok());
+        assert!(check_pin_length(8).is_ok());
+        assert!(matches!(check_pin_length(3), Err(AppError::BadRequest(_))));
+        assert!(matches!(check_pin_length(9), Err(AppError::BadRequest(_))));
+    }
+}
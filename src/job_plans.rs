@@ -0,0 +1,222 @@
+use chrono::{Datelike, Days, NaiveDate};
+use sqlx::PgPool;
+use std::collections::HashMap;
+
+use crate::models::JobPlan;
+
+/// Stretch of days (inclusive) during which one job plan is in force for a user and role
+#[derive(Debug, Clone, Copy)]
+pub struct JobPlanPeriod<'a> {
+    pub from: NaiveDate,
+    pub until: NaiveDate,
+    pub plan: &'a JobPlan,
+}
+
+impl JobPlanPeriod<'_> {
+    pub fn days(&self) -> i64 {
+        (self.until - self.from).num_days() + 1
+    }
+}
+
+/// Answers "which job plan applied on this date" for reports, so a plan that changes mid-month is only
+/// used for the days it covers. Bounds are inclusive and `until = None` is open-ended; where plans
+/// overlap, the one that started last wins (overlaps are reported by GET /api/job-plans/issues).
+#[derive(Debug, Default)]
+pub struct JobPlanResolver {
+    plans: HashMap<(i32, i32), Vec<JobPlan>>,
+}
+
+impl JobPlanResolver {
+    pub fn new(plans: Vec<JobPlan>) -> Self {
+        let mut grouped: HashMap<(i32, i32), Vec<JobPlan>> = HashMap::new();
+        for plan in plans {
+            grouped.entry((plan.user_profile_id, plan.role_id)).or_default().push(plan);
+        }
+        for plans in grouped.values_mut() {
+            plans.sort_by_key(|p| (p.from, p.id));
+        }
+        Self { plans: grouped }
+    }
+
+    /// Plans in force at any point between `from` and `to` (inclusive), optionally for one user and/or role
+    pub async fn load(
+        db: &PgPool,
+        user_profile_id: Option<i32>,
+        role_id: Option<i32>,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Self, sqlx::Error> {
+        let plans = sqlx::query_as::<_, JobPlan>(
+            r#"
+            SELECT
+                id,
+                role_id,
+                user_profile_id,
+                dcc_pa,
+                dcc_hour,
+                spa_pa,
+                spa_hour,
+                al_per_year,
+                sl_per_year,
+                pl_per_year,
+                "from",
+                until,
+                comment
+            FROM "JobPlans"
+            WHERE "from" <= $4
+              AND (until IS NULL OR until >= $3)
+              AND ($1::int4 IS NULL OR user_profile_id = $1)
+              AND ($2::int4 IS NULL OR role_id = $2)
+            "#,
+        )
+        .bind(user_profile_id)
+        .bind(role_id)
+        .bind(from)
+        .bind(to)
+        .fetch_all(db)
+        .await?;
+
+        Ok(Self::new(plans))
+    }
+
+    /// (user_profile_id, role_id) pairs with at least one loaded plan
+    pub fn assignments(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
+        self.plans.keys().copied()
+    }
+
+    /// The plan in force for a user and role on `date`
+    pub fn plan_on(&self, user_profile_id: i32, role_id: i32, date: NaiveDate) -> Option<&JobPlan> {
+        self.plans
+            .get(&(user_profile_id, role_id))?
+            .iter()
+            .rev()
+            .find(|p| p.from <= date && p.until.is_none_or(|until| until >= date))
+    }
+
+    /// Split `from..=to` into the periods each plan was in force; uncovered days are left out
+    pub fn periods(&self, user_profile_id: i32, role_id: i32, from: NaiveDate, to: NaiveDate) -> Vec<JobPlanPeriod<'_>> {
+        let Some(plans) = self.plans.get(&(user_profile_id, role_id)) else {
+            return Vec::new();
+        };
+
+        // The plan in force can only change where some plan starts or the day after one ends
+        let mut boundaries = vec![from];
+        for plan in plans {
+            boundaries.push(plan.from);
+            if let Some(next) = plan.until.and_then(|until| until.checked_add_days(Days::new(1))) {
+                boundaries.push(next);
+            }
+        }
+        boundaries.retain(|d| *d >= from && *d <= to);
+        boundaries.sort();
+        boundaries.dedup();
+
+        let mut periods: Vec<JobPlanPeriod<'_>> = Vec::new();
+        for (i, start) in boundaries.iter().enumerate() {
+            let end = boundaries.get(i + 1).and_then(|next| next.pred_opt()).unwrap_or(to);
+            let Some(plan) = self.plan_on(user_profile_id, role_id, *start) else {
+                continue;
+            };
+            match periods.last_mut() {
+                Some(last) if last.plan.id == plan.id && last.until.succ_opt() == Some(*start) => last.until = end,
+                _ => periods.push(JobPlanPeriod { from: *start, until: end, plan }),
+            }
+        }
+        periods
+    }
+
+    /// A yearly figure (e.g. `al_per_year`) pro rata to the days of `from..=to` each plan covered,
+    /// summed over the user's roles
+    pub fn prorated(&self, user_profile_id: i32, from: NaiveDate, to: NaiveDate, per_year: impl Fn(&JobPlan) -> f32) -> f64 {
+        self.assignments()
+            .filter(|(user, _)| *user == user_profile_id)
+            .flat_map(|(user, role)| self.periods(user, role, from, to))
+            .map(|period| f64::from(per_year(period.plan)) * period.days() as f64 / days_in_year(period.from.year()) as f64)
+            .sum()
+    }
+}
+
+fn days_in_year(year: i32) -> i64 {
+    if NaiveDate::from_ymd_opt(year, 2, 29).is_some() {
+        366
+    } else {
+        365
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn day(m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, m, d).unwrap()
+    }
+
+    fn plan(id: i32, from: NaiveDate, until: Option<NaiveDate>, al_per_year: f32) -> JobPlan {
+        JobPlan {
+            id,
+            role_id: 1,
+            user_profile_id: 7,
+            dcc_pa: None,
+            dcc_hour: None,
+            spa_pa: None,
+            spa_hour: None,
+            al_per_year,
+            sl_per_year: 0.0,
+            pl_per_year: 0.0,
+            from,
+            until,
+            comment: None,
+        }
+    }
+
+    #[test]
+    fn test_plan_transition_mid_month() {
+        let resolver = JobPlanResolver::new(vec![
+            plan(2, day(3, 15), None, 32.0),
+            plan(1, day(1, 1), Some(day(3, 14)), 27.0),
+        ]);
+
+        assert_eq!(resolver.plan_on(7, 1, day(3, 14)).map(|p| p.id), Some(1));
+        assert_eq!(resolver.plan_on(7, 1, day(3, 15)).map(|p| p.id), Some(2));
+        assert!(resolver.plan_on(7, 2, day(3, 15)).is_none());
+
+        let periods: Vec<_> = resolver
+            .periods(7, 1, day(3, 1), day(3, 31))
+            .iter()
+            .map(|p| (p.plan.id, p.from, p.until))
+            .collect();
+        assert_eq!(periods, vec![(1, day(3, 1), day(3, 14)), (2, day(3, 15), day(3, 31))]);
+
+        // 14 days at 27/year and 17 days at 32/year
+        let march = resolver.prorated(7, day(3, 1), day(3, 31), |p| p.al_per_year);
+        assert!((march - (14.0 * 27.0 + 17.0 * 32.0) / 365.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_gaps_and_overlaps() {
+        // Gap on 10-19 April; from 25 April the later plan overrides the open-ended one
+        let resolver = JobPlanResolver::new(vec![
+            plan(1, day(4, 1), Some(day(4, 9)), 27.0),
+            plan(2, day(4, 20), None, 30.0),
+            plan(3, day(4, 25), Some(day(4, 27)), 0.0),
+        ]);
+
+        let periods: Vec<_> = resolver
+            .periods(7, 1, day(4, 5), day(4, 30))
+            .iter()
+            .map(|p| (p.plan.id, p.from, p.until))
+            .collect();
+        assert_eq!(
+            periods,
+            vec![
+                (1, day(4, 5), day(4, 9)),
+                (2, day(4, 20), day(4, 24)),
+                (3, day(4, 25), day(4, 27)),
+                (2, day(4, 28), day(4, 30)),
+            ]
+        );
+        assert!(resolver.plan_on(7, 1, day(4, 15)).is_none());
+        assert_eq!(JobPlanResolver::new(vec![]).prorated(7, day(1, 1), day(12, 31), |p| p.al_per_year), 0.0);
+    }
+}
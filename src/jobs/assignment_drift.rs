@@ -0,0 +1,151 @@
+use chrono::{NaiveDate, Utc};
+use sqlx::PgPool;
+use std::collections::BTreeMap;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::models::{AssignmentDrift, AssignmentDriftFix, AssignmentIssue, DriftShift};
+
+/// The drift check runs once a day
+const DRIFT_CHECK_INTERVAL: Duration = Duration::from_secs(24 * 3600);
+
+/// A shift assigned to someone without a can_work_shifts "UserRoles" row for its role
+#[derive(Debug, sqlx::FromRow)]
+struct DriftRow {
+    role_id: i32,
+    role_name: String,
+    user_profile_id: i32,
+    short_name: String,
+    has_user_role: bool,
+    is_generic: bool,
+    uuid: Uuid,
+    date: NaiveDate,
+    label: String,
+    published: bool,
+}
+
+/// Spawn the background task that reports upcoming shifts assigned to users without the role.
+/// Nothing is changed; admins fix the drift through /api/roles/{id}/assignment-drift/fix.
+pub fn spawn_assignment_drift_job(db: PgPool) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(DRIFT_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            let today = Utc::now().date_naive();
+            match find_assignment_drift(&db, None, today).await {
+                Ok(drift) => {
+                    let shifts: usize = drift.iter().map(|d| d.shifts.len()).sum();
+                    crate::handlers::metrics::record_assignment_drift(shifts);
+                    for d in &drift {
+                        tracing::warn!(
+                            role_id = d.role_id,
+                            user_profile_id = d.user_profile_id,
+                            issue = ?d.issue,
+                            shifts = d.shifts.len(),
+                            "⚠️ Shifts assigned to a user without the role"
+                        );
+                    }
+                    if drift.is_empty() {
+                        tracing::debug!(%today, "🧭 No shifts assigned to users without the role");
+                    }
+                }
+                Err(e) => tracing::error!(error = %e, "❌ Assignment drift check failed"),
+            }
+        }
+    });
+}
+
+/// Shifts dated on/after `from` whose assignee cannot work shifts in the role, grouped per role and user.
+/// `roles` of None covers every role.
+pub async fn find_assignment_drift(
+    db: &PgPool,
+    roles: Option<&[i32]>,
+    from: NaiveDate,
+) -> Result<Vec<AssignmentDrift>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, DriftRow>(&format!(
+        r#"
+        SELECT
+            s.role_id,
+            r.role_name,
+            s.user_profile_id,
+            u.short_name,
+            ur.id IS NOT NULL AS has_user_role,
+            COALESCE(u.is_generic_login, false) AS is_generic,
+            s.uuid,
+            s.date,
+            s.label,
+            s.published
+        FROM "Shifts" s
+        INNER JOIN "Roles" r ON r.id = s.role_id
+        INNER JOIN "Users" u ON u.user_profile_id = s.user_profile_id
+        LEFT JOIN "UserRoles" ur ON ur.user_profile_id = s.user_profile_id AND ur.role_id = s.role_id AND {ur_in_force}
+        WHERE s.date >= $1
+          AND (ur.id IS NULL OR NOT ur.can_work_shifts)
+          AND ($2::int4[] IS NULL OR s.role_id = ANY($2))
+        ORDER BY s.role_id, s.user_profile_id, s.date, s.start
+        "#,
+        ur_in_force = crate::extractors::permissions::ur_in_force(3)
+    ))
+    .bind(from)
+    .bind(roles)
+    .bind(crate::extractors::permissions::today())
+    .fetch_all(db)
+    .await?;
+
+    Ok(group_drift(rows))
+}
+
+/// One entry per role and user, with the fixes that apply to it
+fn group_drift(rows: Vec<DriftRow>) -> Vec<AssignmentDrift> {
+    let mut grouped: BTreeMap<(i32, i32), AssignmentDrift> = BTreeMap::new();
+    for row in rows {
+        let entry = grouped.entry((row.role_id, row.user_profile_id)).or_insert_with(|| AssignmentDrift {
+            role_id: row.role_id,
+            role_name: row.role_name,
+            user_profile_id: row.user_profile_id,
+            short_name: row.short_name,
+            issue: if row.has_user_role { AssignmentIssue::CannotWorkShifts } else { AssignmentIssue::NoUserRole },
+            shifts: Vec::new(),
+            // Generic logins may never hold can_work_shifts
+            actions: if row.is_generic {
+                vec![AssignmentDriftFix::Unassign]
+            } else {
+                vec![AssignmentDriftFix::Unassign, AssignmentDriftFix::Regrant]
+            },
+        });
+        entry.shifts.push(DriftShift { uuid: row.uuid, date: row.date, label: row.label, published: row.published });
+    }
+    grouped.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(role_id: i32, user_profile_id: i32, has_user_role: bool, is_generic: bool, day: u32) -> DriftRow {
+        DriftRow {
+            role_id,
+            role_name: format!("Role {}", role_id),
+            user_profile_id,
+            short_name: format!("U{}", user_profile_id),
+            has_user_role,
+            is_generic,
+            uuid: Uuid::new_v4(),
+            date: NaiveDate::from_ymd_opt(2026, 11, day).unwrap(),
+            label: "Day".to_string(),
+            published: true,
+        }
+    }
+
+    #[test]
+    fn test_group_drift() {
+        let drift = group_drift(vec![row(1, 7, false, false, 2), row(1, 7, false, false, 3), row(2, 7, true, true, 2)]);
+        assert_eq!(drift.len(), 2);
+        assert_eq!(drift[0].shifts.len(), 2);
+        assert_eq!(drift[0].issue, AssignmentIssue::NoUserRole);
+        assert_eq!(drift[0].actions, vec![AssignmentDriftFix::Unassign, AssignmentDriftFix::Regrant]);
+        assert_eq!(drift[1].issue, AssignmentIssue::CannotWorkShifts);
+        assert_eq!(drift[1].actions, vec![AssignmentDriftFix::Unassign]);
+    }
+}
@@ -0,0 +1,11 @@
+pub mod assignment_drift;
+pub mod retention;
+pub mod rota_snapshot;
+pub mod shift_stats;
+pub mod user_role_expiry;
+
+pub use assignment_drift::spawn_assignment_drift_job;
+pub use retention::spawn_retention_job;
+pub use rota_snapshot::spawn_rota_snapshot_job;
+pub use shift_stats::spawn_shift_stats_job;
+pub use user_role_expiry::spawn_user_role_expiry_job;
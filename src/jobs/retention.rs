@@ -0,0 +1,171 @@
+use chrono::{Days, NaiveDate, Utc};
+use sqlx::PgPool;
+use std::time::Duration;
+
+use crate::{
+    config::RetentionPolicy,
+    models::{RetentionReport, RetentionRuleResult},
+};
+
+/// The retention job runs once a day
+const RETENTION_INTERVAL: Duration = Duration::from_secs(24 * 3600);
+
+/// Advisory lock key so only one instance applies retention at a time
+const RETENTION_LOCK_KEY: i64 = 0x5245_5445_4E54; // "RETENT"
+
+/// One retention rule: rows matching `filter` with a date before $1 are counted, then purged or anonymised
+struct RetentionRule {
+    name: &'static str,
+    action: &'static str,
+    table: &'static str,
+    filter: &'static str,
+    /// Statement applying the rule ($1 = cutoff date)
+    apply: &'static str,
+    days: fn(&RetentionPolicy) -> u32,
+}
+
+const RULES: &[RetentionRule] = &[
+    RetentionRule {
+        name: "deleted_diary_entries",
+        action: "purge",
+        table: "Diary",
+        filter: "deleted AND date < $1",
+        apply: r#"DELETE FROM "Diary" WHERE deleted AND date < $1"#,
+        days: |p| p.deleted_diary_days,
+    },
+    RetentionRule {
+        name: "audit_actors",
+        action: "anonymise",
+        table: "ShiftAudit",
+        filter: "anonymised_at IS NULL AND created_at < $1",
+        apply: r#"
+            UPDATE "ShiftAudit"
+            SET created_by = NULL, acted_by = NULL, acted_as_delegate_of = NULL, anonymised_at = NOW()
+            WHERE anonymised_at IS NULL AND created_at < $1
+        "#,
+        days: |p| p.audit_actor_days,
+    },
+    RetentionRule {
+        name: "data_access_log",
+        action: "purge",
+        table: "DataAccessLog",
+        filter: "created_at < $1",
+        apply: r#"DELETE FROM "DataAccessLog" WHERE created_at < $1"#,
+        days: |p| p.access_log_days,
+    },
+    RetentionRule {
+        name: "read_notifications",
+        action: "purge",
+        table: "Notifications",
+        filter: "read_at IS NOT NULL AND created_at < $1",
+        apply: r#"DELETE FROM "Notifications" WHERE read_at IS NOT NULL AND created_at < $1"#,
+        days: |p| p.read_notification_days,
+    },
+    RetentionRule {
+        name: "login_audit",
+        action: "purge",
+        table: "LoginAudit",
+        filter: "created_at < $1",
+        apply: r#"DELETE FROM "LoginAudit" WHERE created_at < $1"#,
+        days: |p| p.login_audit_days,
+    },
+];
+
+/// Spawn the daily retention task when RETENTION_JOB_ENABLED is set
+pub fn spawn_retention_job(db: PgPool, policy: RetentionPolicy) {
+    if !policy.job_enabled {
+        tracing::info!("🗄️ Retention job disabled (RETENTION_JOB_ENABLED is not true)");
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(RETENTION_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            match run_retention(&db, &policy, false).await {
+                Ok(Some(report)) => {
+                    for rule in report.rules.iter().filter(|r| r.rows > 0) {
+                        tracing::info!(rule = rule.rule, action = rule.action, rows = rule.rows, cutoff = %rule.cutoff, "🗄️ Retention applied");
+                    }
+                }
+                Ok(None) => tracing::debug!("🗄️ Retention already running on another instance"),
+                Err(e) => tracing::error!(error = %e, "❌ Retention job failed"),
+            }
+        }
+    });
+}
+
+/// Apply (or with `dry_run`, only count) every enabled retention rule in one transaction.
+/// Returns None when another instance holds the retention lock.
+pub async fn run_retention(db: &PgPool, policy: &RetentionPolicy, dry_run: bool) -> Result<Option<RetentionReport>, sqlx::Error> {
+    let today = Utc::now().date_naive();
+    let mut tx = db.begin().await?;
+
+    if !dry_run {
+        let locked: bool = sqlx::query_scalar("SELECT pg_try_advisory_xact_lock($1)")
+            .bind(RETENTION_LOCK_KEY)
+            .fetch_one(&mut *tx)
+            .await?;
+        if !locked {
+            return Ok(None);
+        }
+    }
+
+    let mut rules = Vec::new();
+    for rule in RULES {
+        let days = (rule.days)(policy);
+        let Some(cutoff) = retention_cutoff(today, days) else {
+            continue;
+        };
+
+        let rows = if dry_run {
+            sqlx::query_scalar::<_, i64>(&format!(r#"SELECT COUNT(*) FROM "{}" WHERE {}"#, rule.table, rule.filter))
+                .bind(cutoff)
+                .fetch_one(&mut *tx)
+                .await?
+        } else {
+            sqlx::query(rule.apply).bind(cutoff).execute(&mut *tx).await?.rows_affected() as i64
+        };
+
+        rules.push(RetentionRuleResult {
+            rule: rule.name.to_string(),
+            action: rule.action.to_string(),
+            retention_days: days,
+            cutoff,
+            rows,
+        });
+    }
+
+    if dry_run {
+        tx.rollback().await?;
+    } else {
+        tx.commit().await?;
+    }
+
+    Ok(Some(RetentionReport {
+        dry_run,
+        generated_at: Utc::now().naive_utc(),
+        rules,
+    }))
+}
+
+/// First date that is kept for a rule, or None when the rule is disabled (0 days)
+fn retention_cutoff(today: NaiveDate, days: u32) -> Option<NaiveDate> {
+    if days == 0 {
+        return None;
+    }
+    today.checked_sub_days(Days::new(days as u64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retention_cutoff() {
+        let today = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        assert_eq!(retention_cutoff(today, 730), NaiveDate::from_ymd_opt(2024, 3, 1));
+        assert_eq!(retention_cutoff(today, 0), None);
+    }
+}
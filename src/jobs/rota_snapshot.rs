@@ -0,0 +1,65 @@
+use chrono::{NaiveDate, Utc};
+use sqlx::PgPool;
+use std::time::Duration;
+
+/// How often the job wakes up to check whether today's snapshot exists
+const SNAPSHOT_CHECK_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Spawn the background task that snapshots the published rota once per role per day.
+/// The task wakes hourly; snapshots already taken for today are left untouched, so
+/// restarts and multiple instances are safe.
+pub fn spawn_rota_snapshot_job(db: PgPool) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SNAPSHOT_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            let today = Utc::now().date_naive();
+            match take_rota_snapshots(&db, today).await {
+                Ok(0) => tracing::debug!(%today, "📸 Rota snapshots already taken for today"),
+                Ok(count) => tracing::info!(%today, count, "📸 Rota snapshots taken"),
+                Err(e) => tracing::error!(error = %e, %today, "❌ Failed to take rota snapshots"),
+            }
+        }
+    });
+}
+
+/// Snapshot the published shifts (dated on/after `snapshot_date`) of every role.
+/// Returns the number of snapshots inserted.
+pub async fn take_rota_snapshots(db: &PgPool, snapshot_date: NaiveDate) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        INSERT INTO "RotaSnapshots" (role_id, snapshot_date, shifts)
+        SELECT
+            r.id,
+            $1,
+            COALESCE(
+                (
+                    SELECT jsonb_agg(
+                        jsonb_build_object(
+                            'uuid', s.uuid,
+                            'date', s.date,
+                            'label', s.label,
+                            'start', to_char(s.start, 'HH24:MI:SS'),
+                            'end', to_char(s."end", 'HH24:MI:SS'),
+                            'user_profile_id', s.user_profile_id
+                        )
+                        ORDER BY s.date, s.start
+                    )
+                    FROM "Shifts" s
+                    WHERE s.role_id = r.id
+                      AND s.published = true
+                      AND s.date >= $1
+                ),
+                '[]'::jsonb
+            )
+        FROM "Roles" r
+        ON CONFLICT (role_id, snapshot_date) DO NOTHING
+        "#,
+    )
+    .bind(snapshot_date)
+    .execute(db)
+    .await?;
+
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,92 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use sqlx::PgPool;
+use std::time::{Duration, Instant};
+
+use crate::models::{StatsFreshness, StatsViewRefresh};
+
+/// Materialised views behind /api/shift-stats, refreshed in this order
+pub const SHIFT_STATS_VIEWS: [&str; 2] = ["ShiftStatsUserMonth", "ShiftStatsRoleMonth"];
+
+const SHIFT_STATS_REFRESH_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Spawn the background task that refreshes the shift statistics views every 10 minutes
+pub fn spawn_shift_stats_job(db: PgPool) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SHIFT_STATS_REFRESH_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            match refresh_shift_stats(&db).await {
+                Ok(refreshed) => tracing::debug!(
+                    duration_ms = refreshed.iter().map(|r| r.duration_ms).sum::<i64>(),
+                    "📊 Shift statistics refreshed"
+                ),
+                Err(e) => tracing::error!(error = %e, "❌ Failed to refresh shift statistics"),
+            }
+        }
+    });
+}
+
+/// Refresh every shift statistics view (readers are not blocked) and record when
+pub async fn refresh_shift_stats(db: &PgPool) -> Result<Vec<StatsViewRefresh>, sqlx::Error> {
+    let mut refreshed = Vec::with_capacity(SHIFT_STATS_VIEWS.len());
+    for view in SHIFT_STATS_VIEWS {
+        let started = Instant::now();
+        sqlx::query(&format!(r#"REFRESH MATERIALIZED VIEW CONCURRENTLY "{}""#, view))
+            .execute(db)
+            .await?;
+        let duration_ms = started.elapsed().as_millis() as i64;
+
+        sqlx::query(
+            r#"
+            INSERT INTO "ShiftStatsRefreshes" (view_name, refreshed_at, duration_ms)
+            VALUES ($1, NOW(), $2)
+            ON CONFLICT (view_name) DO UPDATE SET refreshed_at = EXCLUDED.refreshed_at, duration_ms = EXCLUDED.duration_ms
+            "#,
+        )
+        .bind(view)
+        .bind(duration_ms as i32)
+        .execute(db)
+        .await?;
+
+        refreshed.push(StatsViewRefresh { view: view.to_string(), duration_ms });
+    }
+    Ok(refreshed)
+}
+
+/// How old a view's figures are at `now`; stale after two missed refreshes
+pub fn freshness(refreshed_at: Option<NaiveDateTime>, now: NaiveDateTime) -> StatsFreshness {
+    let age_seconds = refreshed_at.map(|at| (now - at).num_seconds().max(0));
+    StatsFreshness {
+        refreshed_at: refreshed_at.map(|at| DateTime::<Utc>::from_naive_utc_and_offset(at, Utc)),
+        age_seconds,
+        stale: age_seconds.is_none_or(|age| age > 2 * SHIFT_STATS_REFRESH_INTERVAL.as_secs() as i64),
+    }
+}
+
+/// Freshness of one view, as of the database clock
+pub async fn load_freshness(db: &PgPool, view: &str) -> Result<StatsFreshness, sqlx::Error> {
+    let (refreshed_at, now): (Option<NaiveDateTime>, NaiveDateTime) = sqlx::query_as(
+        r#"SELECT (SELECT refreshed_at FROM "ShiftStatsRefreshes" WHERE view_name = $1), NOW()::timestamp"#,
+    )
+    .bind(view)
+    .fetch_one(db)
+    .await?;
+    Ok(freshness(refreshed_at, now))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_freshness() {
+        let now = chrono::NaiveDate::from_ymd_opt(2025, 3, 1).unwrap().and_hms_opt(12, 0, 0).unwrap();
+        let fresh = freshness(Some(now - chrono::Duration::minutes(5)), now);
+        assert_eq!((fresh.age_seconds, fresh.stale), (Some(300), false));
+        assert!(freshness(Some(now - chrono::Duration::minutes(21)), now).stale);
+        assert!(freshness(None, now).stale);
+        // A refresh stamped just after `now` never gives a negative age
+        assert_eq!(freshness(Some(now + chrono::Duration::seconds(2)), now).age_seconds, Some(0));
+    }
+}
@@ -0,0 +1,42 @@
+use sqlx::PgPool;
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::cache::CacheRegistry;
+
+/// Expired assignments are removed hourly; permission checks ignore them as soon as their day is over
+const USER_ROLE_EXPIRY_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Spawn the background task that deletes user roles whose `effective_until` day has passed
+pub fn spawn_user_role_expiry_job(db: PgPool, caches: Arc<CacheRegistry>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(USER_ROLE_EXPIRY_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            match purge_expired_user_roles(&db).await {
+                Ok(profiles) if profiles.is_empty() => {}
+                Ok(profiles) => {
+                    // Role listings carry staff counts and the directory lists members, so drop them with the rows
+                    for user_profile_id in &profiles {
+                        caches.invalidate_user_roles(*user_profile_id).await;
+                    }
+                    tracing::info!(users = profiles.len(), "🔑 Expired user roles removed");
+                }
+                Err(e) => tracing::error!(error = %e, "❌ Failed to remove expired user roles"),
+            }
+        }
+    });
+}
+
+/// Delete assignments whose last day of access is before today; returns the profiles that lost one
+pub async fn purge_expired_user_roles(db: &PgPool) -> Result<BTreeSet<i32>, sqlx::Error> {
+    let profiles: Vec<i32> = sqlx::query_scalar(
+        r#"DELETE FROM "UserRoles" WHERE effective_until < $1 RETURNING user_profile_id"#,
+    )
+    .bind(crate::extractors::permissions::today())
+    .fetch_all(db)
+    .await?;
+    Ok(profiles.into_iter().collect())
+}
@@ -0,0 +1,38 @@
+//! EDrota API server. The `edrota4-axum` binary runs it; `gen-openapi` writes its OpenAPI spec.
+
+pub mod auth;
+pub mod build_info;
+pub mod cache;
+pub mod config;
+pub mod db;
+pub mod error;
+pub mod extractors;
+pub mod features;
+pub mod handlers;
+pub mod job_plans;
+pub mod jobs;
+pub mod middleware;
+pub mod models;
+pub mod openapi;
+pub mod redaction;
+pub mod report;
+pub mod startup;
+pub mod storage;
+
+use moka::future::Cache;
+use std::sync::Arc;
+
+pub use auth::JwksCache;
+pub use config::{AppConfig, ListenAddress, TlsFiles};
+pub use error::{AppError, AppResult};
+pub use handlers::MetricsState;
+
+#[derive(Clone)]
+pub struct AppState {
+    pub db: sqlx::PgPool,
+    pub jwks_cache: Arc<JwksCache>,
+    pub user_cache: Cache<String, String>, // clerk_user_id → email
+    pub config: AppConfig,
+    pub metrics: Arc<MetricsState>,
+    pub caches: Arc<cache::CacheRegistry>,
+}
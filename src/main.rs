@@ -1,34 +1,8 @@
-mod auth;
-mod config;
-mod db;
-mod error;
-mod extractors;
-mod handlers;
-mod middleware;
-mod models;
-mod openapi;
-mod startup;
-
+use edrota4_axum::{build_info::build_info, cache, db, handlers, jobs, redaction, startup, AppConfig, AppState, JwksCache};
 use moka::future::Cache;
 use std::sync::Arc;
 use std::time::Duration;
-use tokio::net::TcpListener;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
-
-pub use auth::JwksCache;
-pub use config::AppConfig;
-pub use error::{AppError, AppResult};
-pub use handlers::MetricsState;
-
-#[derive(Clone)]
-pub struct AppState {
-    pub db: sqlx::PgPool,
-    pub jwks_cache: Arc<JwksCache>,
-    pub user_cache: Cache<String, String>, // clerk_user_id → email
-    pub profile_cache: Cache<String, (i32, bool, String)>, // clerk_user_id → (profile_id, is_super_admin, email)
-    pub config: AppConfig,
-    pub metrics: Arc<MetricsState>,
-}
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, Layer};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -36,23 +10,48 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let use_json = std::env::var("LOG_FORMAT")
         .unwrap_or_else(|_| "text".to_string()) == "json";
 
+    // Keys whose values are masked in every log line (added to the built-in list)
+    redaction::init(redaction::parse_deny_keys(std::env::var("LOG_REDACT_KEYS").ok().as_deref()));
+
     let env_filter = tracing_subscriber::EnvFilter::try_from_default_env()
         .unwrap_or_else(|_| "info,edrota4_axum=debug,tower_http=debug".into());
 
+    // sqlx timing events feed the /debug DB latency percentiles; RUST_LOG only filters the console
+    let db_latency = Arc::new(db::latency::DbLatency::default());
+    let db_latency_layer = db::latency::DbLatencyLayer::new(db_latency.clone());
+
     if use_json {
         // Structured JSON logging for production
         tracing_subscriber::registry()
-            .with(env_filter)
-            .with(tracing_subscriber::fmt::layer().json())
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .json()
+                    .with_writer(redaction::RedactingMakeWriter(std::io::stdout))
+                    .with_filter(env_filter),
+            )
+            .with(db_latency_layer.with_filter(db::latency::DbLatencyLayer::targets()))
             .init();
     } else {
         // Human-readable for development
         tracing_subscriber::registry()
-            .with(env_filter)
-            .with(tracing_subscriber::fmt::layer())
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .fmt_fields(redaction::text_fields())
+                    .with_filter(env_filter),
+            )
+            .with(db_latency_layer.with_filter(db::latency::DbLatencyLayer::targets()))
             .init();
     }
 
+    let build = build_info();
+    tracing::info!(
+        version = build.version,
+        git_sha = build.git_sha,
+        built_at = %build.built_at,
+        openapi_sha256 = %build.openapi_sha256,
+        "🚀 Starting edrota4-axum"
+    );
+
     // Load environment variables
     dotenvy::dotenv().ok();
 
@@ -70,12 +69,32 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     tracing::info!("✅ Database pool created successfully");
 
+    db::encrypted::init_keys(
+        config.pii_encryption_key.as_deref(),
+        config.pii_encryption_key_previous.as_deref(),
+    )
+    .map_err(|e| {
+        tracing::error!("❌ PII key error: {}", e);
+        e
+    })?;
+
+    // Admin command: `edrota4-axum rekey-pii` re-encrypts PII columns with the current key and exits
+    if std::env::args().nth(1).as_deref() == Some("rekey-pii") {
+        if !db::encrypted::encryption_enabled() {
+            return Err("rekey-pii requires PII_ENCRYPTION_KEY".into());
+        }
+        let count = db::encrypted::rekey_pii(&db).await?;
+        tracing::info!(count, "🔐 PII columns re-encrypted with the current key");
+        return Ok(());
+    }
+
     // Initialize metrics recorder
-    let metrics_state = Arc::new(handlers::setup_metrics_recorder());
+    let metrics_state = Arc::new(handlers::setup_metrics_recorder(&config, db_latency));
     tracing::info!("✅ Metrics recorder initialized");
 
-    // Create JWKS cache
-    let jwks_cache = Arc::new(JwksCache::new(&config.clerk_domain));
+    // Create JWKS cache, kept warm in the background and served stale for a while if Clerk is down
+    let jwks_cache = Arc::new(JwksCache::new(&config.clerk_domain, config.jwks_stale_window));
+    jwks_cache.clone().spawn_refresh_job();
 
     // Create user cache (clerk_user_id → email) with 5-minute TTL
     let user_cache = Cache::builder()
@@ -83,30 +102,30 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .max_capacity(10_000)
         .build();
 
-    // Create profile cache (clerk_user_id → profile data) with 60-second TTL
-    let profile_cache = Cache::builder()
-        .time_to_live(Duration::from_secs(60))
-        .max_capacity(10_000)
-        .build();
-
     // Create application state
     let state = Arc::new(AppState {
         db,
         jwks_cache,
         user_cache,
-        profile_cache,
         config,
         metrics: metrics_state,
+        caches: Arc::new(cache::CacheRegistry::default()),
     });
 
+    // Start background jobs
+    jobs::spawn_rota_snapshot_job(state.db.clone());
+    jobs::spawn_retention_job(state.db.clone(), state.config.retention.clone());
+    jobs::spawn_assignment_drift_job(state.db.clone());
+    jobs::spawn_shift_stats_job(state.db.clone());
+    jobs::spawn_user_role_expiry_job(state.db.clone(), state.caches.clone());
+
     // Build router
+    let listen = state.config.listen.clone();
+    let tls = state.config.tls.clone();
     let app = startup::build_router(state);
 
     // Start server
-    let listener = TcpListener::bind("0.0.0.0:8080").await?;
-    tracing::info!("🚀 Server listening on {}", listener.local_addr()?);
-
-    axum::serve(listener, app).await?;
+    startup::serve(app, &listen, tls.as_ref()).await?;
 
     Ok(())
 }
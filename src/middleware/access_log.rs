@@ -0,0 +1,107 @@
+use axum::{
+    extract::{FromRequestParts, MatchedPath, Request, State},
+    http::Method,
+    middleware::Next,
+    response::Response,
+};
+use std::sync::Arc;
+
+use crate::{extractors::AuthenticatedUser, AppState};
+
+/// POST endpoints that only read personal data (search bodies are too large for a query string)
+const POST_READ_SUFFIXES: [&str; 2] = ["/search", "/locum"];
+
+/// Query parameters that name the profile being read
+const SUBJECT_PARAMS: [&str; 3] = ["userId", "user_profile_id", "userProfileId"];
+
+/// Middleware that records reads of personal data in "DataAccessLog"
+///
+/// Apply with `route_layer` to routers returning staff details or leave. Only successful
+/// reads are logged; the insert runs in the background so it never delays the response.
+pub async fn access_log_middleware(State(state): State<Arc<AppState>>, request: Request, next: Next) -> Response {
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+
+    let is_read = method == Method::GET || (method == Method::POST && POST_READ_SUFFIXES.iter().any(|s| path.ends_with(s)));
+    if !is_read {
+        return next.run(request).await;
+    }
+
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| path.clone());
+    let query = request.uri().query().map(String::from);
+    let subject_id = subject_from_request(&route, &path, query.as_deref());
+
+    // Resolve the viewer without rejecting: some of these endpoints are public
+    let (mut parts, body) = request.into_parts();
+    let viewer_id = AuthenticatedUser::from_request_parts(&mut parts, &state)
+        .await
+        .ok()
+        .map(|u| u.profile_id);
+
+    let response = next.run(Request::from_parts(parts, body)).await;
+
+    if response.status().is_success() {
+        let db = state.db.clone();
+        let status = response.status().as_u16() as i16;
+        let full_path = match query {
+            Some(q) => format!("{}?{}", path, q),
+            None => path,
+        };
+
+        tokio::spawn(async move {
+            let result = sqlx::query(
+                r#"
+                INSERT INTO "DataAccessLog" (viewer_id, subject_id, method, route, path, status)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                "#,
+            )
+            .bind(viewer_id)
+            .bind(subject_id)
+            .bind(method.as_str())
+            .bind(&route)
+            .bind(&full_path)
+            .bind(status)
+            .execute(&db)
+            .await;
+
+            if let Err(e) = result {
+                tracing::warn!(error = %e, route, "⚠️ Failed to record data access");
+            }
+        });
+    }
+
+    response
+}
+
+/// Work out whose data a request reads: a `{id}` path parameter, or a user query parameter
+fn subject_from_request(route: &str, path: &str, query: Option<&str>) -> Option<i32> {
+    // /users/{id} and nested routes such as /users/{id}/shift-changes
+    if let Some(index) = route.split('/').position(|segment| segment == "{id}") {
+        if let Some(id) = path.split('/').nth(index).and_then(|s| s.parse().ok()) {
+            return Some(id);
+        }
+    }
+
+    query?
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| SUBJECT_PARAMS.contains(key))
+        .and_then(|(_, value)| value.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_subject_from_request() {
+        assert_eq!(subject_from_request("/api/users/{id}", "/api/users/42", None), Some(42));
+        assert_eq!(subject_from_request("/api/users/{id}/shift-changes", "/api/users/7/shift-changes", None), Some(7));
+        assert_eq!(subject_from_request("/api/diary", "/api/diary", Some("roleId=3&userId=9")), Some(9));
+        assert_eq!(subject_from_request("/api/users/staff-list", "/api/users/staff-list", Some("roleId=3")), None);
+    }
+}
@@ -0,0 +1,108 @@
+use axum::{
+    body::{Body, HttpBody},
+    extract::Request,
+    http::{header, HeaderValue, Method, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+
+/// Largest body hashed for an ETag; bigger or streamed responses pass through untouched
+const MAX_ETAG_BODY_BYTES: u64 = 32 * 1024 * 1024;
+
+/// ETag and Content-Length for successful GET/HEAD responses, and 304 when If-None-Match still matches.
+/// Used as a route layer, so HEAD requests see the full GET body here before axum strips it.
+pub async fn conditional_get_middleware(request: Request, next: Next) -> Response {
+    if !matches!(*request.method(), Method::GET | Method::HEAD) {
+        return next.run(request).await;
+    }
+    let if_none_match = request.headers().get(header::IF_NONE_MATCH).cloned();
+
+    let response = next.run(request).await;
+    let body_len = response.body().size_hint().exact();
+    if response.status() != StatusCode::OK
+        || response.headers().contains_key(header::ETAG)
+        || body_len.is_none_or(|len| len > MAX_ETAG_BODY_BYTES)
+    {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match axum::body::to_bytes(body, MAX_ETAG_BODY_BYTES as usize).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::error!(error = %e, "❌ Could not buffer response body for ETag");
+            parts.status = StatusCode::INTERNAL_SERVER_ERROR;
+            parts.headers.remove(header::CONTENT_LENGTH);
+            return Response::from_parts(parts, Body::empty());
+        }
+    };
+
+    let digest = ring::digest::digest(&ring::digest::SHA256, &bytes);
+    let etag = format!("\"{}\"", hex::encode(&digest.as_ref()[..16]));
+    parts.headers.insert(header::ETAG, HeaderValue::from_str(&etag).expect("hex ETag is a valid header"));
+    // Responses depend on who is signed in: browsers may keep them but must revalidate, shared caches must not
+    parts
+        .headers
+        .entry(header::CACHE_CONTROL)
+        .or_insert(HeaderValue::from_static("private, no-cache"));
+
+    if if_none_match.as_ref().and_then(|v| v.to_str().ok()).is_some_and(|v| etag_matches(v, &etag)) {
+        parts.status = StatusCode::NOT_MODIFIED;
+        parts.headers.remove(header::CONTENT_LENGTH);
+        parts.headers.remove(header::CONTENT_TYPE);
+        return Response::from_parts(parts, Body::empty());
+    }
+
+    parts.headers.insert(header::CONTENT_LENGTH, HeaderValue::from(bytes.len()));
+    Response::from_parts(parts, Body::from(bytes))
+}
+
+/// If-None-Match uses weak comparison: "*" or any listed tag, with or without W/
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    if_none_match
+        .split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == etag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::get, Router};
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_etag_matches() {
+        assert!(etag_matches("\"abc\"", "\"abc\""));
+        assert!(etag_matches("\"x\", W/\"abc\"", "\"abc\""));
+        assert!(etag_matches("*", "\"abc\""));
+        assert!(!etag_matches("\"abcd\"", "\"abc\""));
+    }
+
+    #[tokio::test]
+    async fn test_head_and_if_none_match() {
+        let app = Router::new()
+            .route("/shifts", get(|| async { "[1,2,3]" }))
+            .route_layer(axum::middleware::from_fn(conditional_get_middleware));
+        let send = |method: Method, if_none_match: Option<&str>| {
+            let mut request = Request::builder().method(method).uri("/shifts");
+            if let Some(tag) = if_none_match {
+                request = request.header(header::IF_NONE_MATCH, tag);
+            }
+            app.clone().oneshot(request.body(Body::empty()).unwrap())
+        };
+
+        let get_response = send(Method::GET, None).await.unwrap();
+        let etag = get_response.headers()[header::ETAG].to_str().unwrap().to_string();
+
+        let head = send(Method::HEAD, None).await.unwrap();
+        assert_eq!(head.status(), StatusCode::OK);
+        assert_eq!(head.headers()[header::ETAG], etag.as_str());
+        assert_eq!(head.headers()[header::CONTENT_LENGTH], "7");
+        assert!(axum::body::to_bytes(head.into_body(), usize::MAX).await.unwrap().is_empty());
+
+        let not_modified = send(Method::GET, Some(&etag)).await.unwrap();
+        assert_eq!(not_modified.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(send(Method::GET, Some("\"stale\"")).await.unwrap().status(), StatusCode::OK);
+    }
+}
@@ -6,17 +6,17 @@ use std::time::Instant;
 pub async fn metrics_middleware(request: Request, next: Next) -> Response {
     let start = Instant::now();
     let method = request.method().clone();
-    let path = request.uri().path().to_string();
 
     // Extract route template (e.g., /api/users/:id -> /api/users/{id})
-    // This is better for metrics cardinality than using raw paths
+    // Unmatched requests share one label so scanners cannot create a series per raw path
     let route = request
         .extensions()
         .get::<axum::extract::MatchedPath>()
         .map(|p| p.as_str().to_string())
-        .unwrap_or_else(|| path.clone());
+        .unwrap_or_else(|| "unmatched".to_string());
 
-    let response = next.run(request).await;
+    // DB timings recorded while the handler runs are attributed to this route
+    let response = crate::db::latency::scope_route(route.clone(), next.run(request)).await;
 
     let status = response.status().as_u16().to_string();
     let duration = start.elapsed().as_secs_f64();
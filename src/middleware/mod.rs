@@ -1,7 +1,13 @@
+pub mod access_log;
+pub mod conditional;
 pub mod metrics;
 pub mod request_id;
+pub mod require_auth;
 pub mod secret_auth;
 
+pub use access_log::access_log_middleware;
+pub use conditional::conditional_get_middleware;
 pub use metrics::metrics_middleware;
 pub use request_id::{request_id_middleware, RequestId};
+pub use require_auth::require_auth_middleware;
 pub use secret_auth::require_debug_key;
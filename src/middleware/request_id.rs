@@ -1,14 +1,28 @@
 use axum::{
+    body::{Body, HttpBody},
     extract::Request,
+    http::header,
     middleware::Next,
     response::Response,
 };
 use uuid::Uuid;
 
+/// Largest error body that gets request_id added; bigger bodies pass through untouched
+const MAX_ERROR_BODY_BYTES: usize = 64 * 1024;
+
+tokio::task_local! {
+    static CURRENT_REQUEST_ID: String;
+}
+
 /// Extension type for request ID
 #[derive(Clone, Debug)]
 pub struct RequestId(pub String);
 
+/// Request ID of the request being handled on this task, if any
+pub fn current_request_id() -> Option<String> {
+    CURRENT_REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
 /// Middleware that generates a unique request ID for each request
 pub async fn request_id_middleware(
     mut request: Request,
@@ -22,7 +36,14 @@ pub async fn request_id_middleware(
     // Add span field for correlation in logs
     tracing::Span::current().record("request_id", &request_id.as_str());
 
-    let mut response = next.run(request).await;
+    // Scope the ID to the handler's task so SQL can be tagged with it (db::tag_sql)
+    let response = CURRENT_REQUEST_ID.scope(request_id.clone(), next.run(request)).await;
+
+    let mut response = if response.status().is_client_error() || response.status().is_server_error() {
+        add_request_id_to_error_body(response, &request_id).await
+    } else {
+        response
+    };
 
     // Add to response header for client-side correlation
     response.headers_mut().insert(
@@ -32,3 +53,105 @@ pub async fn request_id_middleware(
 
     response
 }
+
+/// Add "request_id" to JSON error bodies (AppError and extractor rejections alike)
+async fn add_request_id_to_error_body(response: Response, request_id: &str) -> Response {
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("application/json"));
+    if !is_json {
+        return response;
+    }
+
+    // Only buffer bodies known to fit; anything larger or of unknown length is left as it is
+    let fits = response
+        .body()
+        .size_hint()
+        .upper()
+        .is_some_and(|upper| upper <= MAX_ERROR_BODY_BYTES as u64);
+    if !fits {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match axum::body::to_bytes(body, MAX_ERROR_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::warn!(error = %e, "Could not buffer error body to add request_id");
+            parts.headers.remove(header::CONTENT_LENGTH);
+            return Response::from_parts(parts, Body::empty());
+        }
+    };
+
+    let body = match serde_json::from_slice::<serde_json::Value>(&bytes) {
+        Ok(serde_json::Value::Object(mut fields)) => {
+            fields
+                .entry("request_id")
+                .or_insert_with(|| serde_json::Value::String(request_id.to_string()));
+            parts.headers.remove(header::CONTENT_LENGTH);
+            Body::from(serde_json::Value::Object(fields).to_string())
+        }
+        _ => Body::from(bytes),
+    };
+
+    Response::from_parts(parts, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::StatusCode, response::IntoResponse, routing::get, Router};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_error_bodies_carry_request_id() {
+        let app = Router::new()
+            .route("/missing", get(|| async { crate::AppError::NotFound("nope".to_string()).into_response() }))
+            .route("/ok", get(|| async { current_request_id().unwrap_or_default() }))
+            .layer(axum::middleware::from_fn(request_id_middleware));
+
+        let response = app
+            .clone()
+            .oneshot(Request::builder().uri("/missing").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let header_id = response.headers()["X-Request-ID"].to_str().unwrap().to_string();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["request_id"], header_id.as_str());
+        assert_eq!(json["code"], "NOT_FOUND");
+
+        // Handlers see the same ID the client gets back
+        let response = app
+            .oneshot(Request::builder().uri("/ok").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let header_id = response.headers()["X-Request-ID"].to_str().unwrap().to_string();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(body, header_id.as_bytes());
+    }
+    #[tokio::test]
+    async fn test_oversized_error_body_passes_through() {
+        let large = serde_json::json!({ "error": "x".repeat(MAX_ERROR_BODY_BYTES) }).to_string();
+        let expected = large.clone();
+        let app = Router::new()
+            .route(
+                "/large",
+                get(move || async move {
+                    (StatusCode::INTERNAL_SERVER_ERROR, [(header::CONTENT_TYPE, "application/json")], large).into_response()
+                }),
+            )
+            .layer(axum::middleware::from_fn(request_id_middleware));
+
+        let response = app
+            .oneshot(Request::builder().uri("/large").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert!(response.headers().contains_key("X-Request-ID"));
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(body, expected.as_bytes());
+    }
+}
@@ -0,0 +1,76 @@
+use axum::{
+    extract::{FromRequestParts, MatchedPath, Request, State},
+    http::Method,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::sync::Arc;
+
+use crate::{extractors::AuthenticatedUser, AppState};
+
+/// Routes served without a session, as (method, route template). Everything else needs one.
+///
+/// Health and version probes, the debug-key routes (checked by their own middleware), the API docs,
+/// routes that carry their own token (display screens, PIN changes after identity verification) and
+/// public reference data.
+pub const PUBLIC_ROUTES: &[(&str, &str)] = &[
+    ("GET", "/health"),
+    ("GET", "/api/version"),
+    ("GET", "/metrics"),
+    ("GET", "/debug"),
+    ("GET", "/api/debug/schema"),
+    ("GET", "/api/debug/permission-matrix"),
+    ("GET", "/api-docs/openapi.json"),
+    ("GET", "/swagger-ui"),
+    ("GET", "/api/display/rota"),
+    ("POST", "/api/users/change-profile-pin"),
+    ("GET", "/api/references/time-off-categories"),
+    ("GET", "/api/workplaces"),
+];
+
+/// Whether `route` (a route template such as "/api/workplaces/{id}") may be called without a session
+pub fn is_public_route(method: &str, route: &str) -> bool {
+    let method = if method == Method::HEAD.as_str() { Method::GET.as_str() } else { method };
+    let route = match route.trim_end_matches('/') {
+        "" => "/",
+        trimmed => trimmed,
+    };
+    PUBLIC_ROUTES.iter().any(|(m, r)| *m == method && *r == route)
+}
+
+/// Middleware that rejects requests without a valid session unless the route is in `PUBLIC_ROUTES`
+///
+/// Apply with `route_layer` so unknown paths still 404. The resolved user is stored in the request
+/// extensions, where the `AuthenticatedUser` extractor picks it up instead of validating the token again.
+pub async fn require_auth_middleware(State(state): State<Arc<AppState>>, request: Request, next: Next) -> Response {
+    let public = request
+        .extensions()
+        .get::<MatchedPath>()
+        .is_some_and(|route| is_public_route(request.method().as_str(), route.as_str()));
+    if public {
+        return next.run(request).await;
+    }
+
+    let (mut parts, body) = request.into_parts();
+    match AuthenticatedUser::from_request_parts(&mut parts, &state).await {
+        Ok(user) => {
+            parts.extensions.insert(user);
+            next.run(Request::from_parts(parts, body)).await
+        }
+        Err(rejection) => rejection.into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_public_route() {
+        assert!(is_public_route("GET", "/health"));
+        assert!(is_public_route("HEAD", "/api/workplaces/"));
+        assert!(!is_public_route("POST", "/api/workplaces"));
+        assert!(!is_public_route("GET", "/api/workplaces/{id}/settings"));
+        assert!(!is_public_route("GET", "/api/shifts"));
+    }
+}
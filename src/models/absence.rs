@@ -0,0 +1,50 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Input for reporting sickness
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ReportAbsenceInput {
+    pub start_date: NaiveDate,
+    pub end_date: Option<NaiveDate>,  // Defaults to start_date
+    pub reason: Option<String>,       // Copied into the diary entries
+    /// Report for someone else (needs can_edit_rota in every role where they have shifts)
+    pub user_profile_id: Option<i32>,
+    #[serde(rename = "confirmedUserId")]
+    pub confirmed_user_id: Option<i32>, // For generic accounts - PIN-verified user ID
+}
+
+/// Someone free to take over a shift: works shifts in the role, and has no shift or leave that day
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ReplacementCandidate {
+    pub user_profile_id: i32,
+    pub short_name: String,
+    pub full_name: String,
+}
+
+/// A shift of the absent user, now flagged as needing cover
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct AbsenceShiftImpact {
+    pub shift_uuid: Uuid,
+    pub role_id: i32,
+    pub date: NaiveDate,
+    pub start_time: Option<String>,
+    pub end_time: Option<String>,
+    pub label: String,
+    #[sqlx(skip)]
+    pub suggested_replacements: Vec<ReplacementCandidate>,
+}
+
+/// Outcome of a sickness report
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AbsenceReport {
+    pub user_profile_id: i32,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    /// Sick-leave diary entries, one per day for each role the user works shifts in
+    pub diary_entry_ids: Vec<i32>,
+    pub shifts: Vec<AbsenceShiftImpact>,
+    pub admins_notified: usize,
+}
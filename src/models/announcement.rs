@@ -0,0 +1,23 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+
+/// Notice for everyone, one workplace or one role, shown between publish_at and expires_at
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct Announcement {
+    pub id: i32,
+    pub title: String,
+    pub body: String,
+    pub workplace_id: Option<i32>,
+    pub role_id: Option<i32>,
+    pub pinned: bool,
+    pub publish_at: NaiveDateTime,
+    pub expires_at: Option<NaiveDateTime>,
+    pub created_by: i32,
+    pub author_name: Option<String>, // From JOIN with Users
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    /// When the caller marked it read (None = unread)
+    pub read_at: Option<NaiveDateTime>,
+}
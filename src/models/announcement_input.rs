@@ -0,0 +1,33 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Input for posting an announcement. Leave workplace_id and role_id empty to reach everyone.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CreateAnnouncementInput {
+    pub title: String,
+    pub body: String,
+    pub workplace_id: Option<i32>,
+    pub role_id: Option<i32>,
+    #[serde(default)]
+    pub pinned: bool,
+    /// Defaults to now
+    pub publish_at: Option<NaiveDateTime>,
+    pub expires_at: Option<NaiveDateTime>,
+}
+
+/// Input for editing an announcement (targeting is fixed once posted)
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UpdateAnnouncementInput {
+    pub title: Option<String>,
+    pub body: Option<String>,
+    pub pinned: Option<bool>,
+    pub publish_at: Option<NaiveDateTime>,
+    pub expires_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AnnouncementMutationResponse {
+    pub success: bool,
+    pub message: Option<String>,
+}
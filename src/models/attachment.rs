@@ -0,0 +1,33 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// File attached to a diary entry or a shift, e.g. a PDF handover sheet
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct Attachment {
+    pub id: i32,
+    pub diary_id: Option<i32>,      // Exactly one of diary_id and shift_id is set
+    pub shift_id: Option<Uuid>,
+    pub filename: String,
+    pub content_type: String,       // application/pdf, image/png, image/jpeg, image/gif or image/webp
+    pub size_bytes: i32,
+    pub sha256: String,
+    pub scan_status: String,        // CLEAN | NOT_SCANNED (no ATTACHMENT_SCAN_URL configured)
+    pub uploaded_by: i32,
+    pub uploaded_by_short_name: Option<String>, // From JOIN with Users
+    pub created_at: NaiveDateTime,
+    /// Presigned bucket URL, valid for 15 minutes from the response
+    #[sqlx(skip)]
+    pub download_url: String,
+    #[serde(skip)]
+    pub storage_key: String,
+}
+
+/// Response for attachment deletion
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AttachmentMutationResponse {
+    pub success: bool,
+    pub message: Option<String>,
+}
@@ -0,0 +1,74 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Actual times worked on a shift, in local wall-clock time like the shift's date and start
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct ShiftAttendance {
+    pub shift_id: Uuid,
+    pub user_profile_id: i32,
+    pub actual_start: NaiveDateTime,
+    /// None until the user checks out
+    pub actual_end: Option<NaiveDateTime>,
+    pub check_in_latitude: Option<f64>,
+    pub check_in_longitude: Option<f64>,
+    pub check_out_latitude: Option<f64>,
+    pub check_out_longitude: Option<f64>,
+}
+
+/// Location sent with a check-in or check-out; required when the workplace has a geofence
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct AttendanceLocationInput {
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+}
+
+/// Why a shift is on the attendance discrepancy report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AttendanceException {
+    /// The shift has ended and nobody checked in
+    NoCheckIn,
+    /// Checked in, but not out by the end of the shift
+    NoCheckOut,
+    /// Worked fewer hours than rostered by more than the threshold
+    UnderHours,
+    /// Worked more hours than rostered by more than the threshold
+    OverHours,
+    /// Checked in by someone the shift is no longer assigned to
+    NotAssigned,
+}
+
+/// A shift whose actual hours differ from the rostered ones
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AttendanceDiscrepancy {
+    pub shift_uuid: Uuid,
+    pub date: NaiveDate,
+    pub label: String,
+    /// The assignee, or for NOT_ASSIGNED whoever checked in
+    pub user_profile_id: i32,
+    pub user_name: Option<String>,
+    pub exception: AttendanceException,
+    pub rostered_start: NaiveDateTime,
+    pub rostered_end: NaiveDateTime,
+    pub rostered_hours: f64,
+    pub actual_start: Option<NaiveDateTime>,
+    pub actual_end: Option<NaiveDateTime>,
+    pub actual_hours: Option<f64>,
+    /// Actual minus rostered hours; negative when short
+    pub difference_hours: Option<f64>,
+}
+
+/// Exceptions for a role's month: only shifts needing a payroll adjustment are listed
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AttendanceReport {
+    pub role_id: i32,
+    pub year: i32,
+    pub month: i32,
+    pub threshold_minutes: i64,
+    /// Timed, published shifts in the month that have started, once per assignee and per other user who checked in
+    pub shifts_checked: i64,
+    pub discrepancies: Vec<AttendanceDiscrepancy>,
+}
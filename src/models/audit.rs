@@ -9,8 +9,15 @@ use uuid::Uuid;
 pub struct AuditEntry {
     pub uuid: Uuid,
     pub role_id: i32,
-    pub created_by: i32,
+    /// None once the retention job has anonymised the entry
+    pub created_by: Option<i32>,
     pub created_by_name: String,
+    /// Profile that actually made the change (e.g. the admin approving a swap), from app.current_user
+    pub acted_by: Option<i32>,
+    pub acted_by_name: Option<String>,
+    /// Approver the actor stood in for under an approval delegation
+    pub acted_as_delegate_of: Option<i32>,
+    pub acted_as_delegate_of_name: Option<String>,
     pub old: Option<Value>,
     pub new: Option<Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -24,7 +31,202 @@ pub struct AuditEntry {
     pub date: String,
     #[serde(serialize_with = "serialize_naive_as_utc")]
     pub created_at: NaiveDateTime,
+    /// Reconstructed by the audit backfill rather than written by the shift triggers
+    pub backfilled: bool,
+    /// Why the change was made, when the editor gave one
+    pub reason: Option<String>,
 }
+/// Whether a shift moved onto or off the user whose history is being read
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ShiftChangeKind {
+    Assigned,
+    Removed,
+}
+
+/// An audit event that assigned a shift to, or removed it from, one member of staff
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UserShiftChange {
+    /// ShiftAudit entry
+    pub audit_id: Uuid,
+    pub kind: ShiftChangeKind,
+    pub shift_date: Option<NaiveDate>,
+    pub role_id: i32,
+    pub role_name: Option<String>,
+    /// Who held the shift before (for ASSIGNED) or after (for REMOVED); None when created or deleted
+    pub other_user_id: Option<i32>,
+    pub other_user_name: Option<String>,
+    /// None once the retention job has anonymised the entry
+    pub created_by: Option<i32>,
+    pub created_by_name: Option<String>,
+    pub acted_by: Option<i32>,
+    pub acted_by_name: Option<String>,
+    pub acted_as_delegate_of: Option<i32>,
+    pub acted_as_delegate_of_name: Option<String>,
+    pub old: Option<Value>,
+    pub new: Option<Value>,
+    /// Why the change was made, when the editor gave one
+    pub reason: Option<String>,
+    #[serde(serialize_with = "serialize_naive_as_utc")]
+    pub created_at: NaiveDateTime,
+}
+
+/// How a shift differs from its last audited state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum BackfillKind {
+    /// Shift exists but has no audit history (or was last audited as deleted)
+    Created,
+    /// Shift's assignee, times, label, role, date or status differ from the last audited state
+    Updated,
+    /// Last audited state exists but the shift is gone
+    Deleted,
+}
+
+/// Range to reconcile; everything when empty
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BackfillAuditInput {
+    pub role_id: Option<i32>,
+    /// First shift date to reconcile
+    pub from: Option<NaiveDate>,
+    /// Last shift date to reconcile
+    pub to: Option<NaiveDate>,
+    /// Report the gaps without writing anything (default true)
+    #[serde(default = "default_dry_run")]
+    pub dry_run: bool,
+}
+
+fn default_dry_run() -> bool {
+    true
+}
+
+/// One reconstructed audit entry
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BackfilledChange {
+    pub shift_uuid: Uuid,
+    pub role_id: i32,
+    pub date: Option<NaiveDate>,
+    pub kind: BackfillKind,
+}
+
+/// What an audit backfill wrote (or, for a dry run, would write)
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BackfillReport {
+    pub dry_run: bool,
+    pub generated_at: NaiveDateTime,
+    pub created: usize,
+    pub updated: usize,
+    pub deleted: usize,
+    pub changes: Vec<BackfilledChange>,
+}
+
+/// A recorded read of personal data (from "DataAccessLog")
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct DataAccessEntry {
+    pub id: i64,
+    pub viewer_id: Option<i32>,
+    pub viewer_name: Option<String>,
+    /// None for list endpoints covering many profiles
+    pub subject_id: Option<i32>,
+    pub subject_name: Option<String>,
+    pub method: String,
+    pub route: String,
+    pub path: String,
+    pub status: i16,
+    #[serde(serialize_with = "serialize_naive_as_utc")]
+    pub created_at: NaiveDateTime,
+}
+
+/// A recorded sign-in (from "LoginAudit"): one per Clerk session, IP and user agent
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct LoginAuditEntry {
+    pub id: i64,
+    pub user_profile_id: i32,
+    pub user_name: Option<String>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+    /// COOKIE (browser session) or BEARER (Authorization header)
+    pub auth_method: String,
+    /// Distinct profiles seen on this IP within the queried window (admin query only); more than one
+    /// points at a shared device or account
+    pub profiles_on_ip: Option<i64>,
+    #[serde(serialize_with = "serialize_naive_as_utc")]
+    pub created_at: NaiveDateTime,
+}
+
+/// A change to an assignment's permission flags (from "UserRoleAudit")
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct UserRoleAuditEntry {
+    pub id: i64,
+    pub user_role_id: i32,
+    pub role_id: i32,
+    pub role_name: Option<String>,
+    pub user_profile_id: i32,
+    pub user_name: Option<String>,
+    pub changed_by: Option<i32>,
+    pub changed_by_name: Option<String>,
+    /// Shared by every assignment changed in one bulk update
+    pub batch_id: Uuid,
+    #[schema(value_type = Vec<crate::models::PermissionChange>)]
+    pub changes: Value,
+    #[serde(serialize_with = "serialize_naive_as_utc")]
+    pub created_at: NaiveDateTime,
+}
+
+/// Permission flags an assignment held
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct RolePermissions {
+    pub can_edit_rota: bool,
+    pub can_access_diary: bool,
+    pub can_work_shifts: bool,
+    pub can_edit_templates: bool,
+    pub can_edit_staff: bool,
+    pub can_view_staff_details: bool,
+    pub can_approve_rota: bool,
+    pub can_set_pay: bool,
+}
+
+/// What ended a role membership period
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum RoleMembershipEnd {
+    /// The permission flags changed; the next period carries the new ones
+    Changed,
+    /// The assignment was deleted or moved to another role
+    Revoked,
+}
+
+/// A stretch of time one role assignment held the same permissions (from "UserRoleHistory")
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RoleMembershipPeriod {
+    pub user_role_id: i32,
+    pub role_id: i32,
+    pub role_name: Option<String>,
+    #[serde(flatten)]
+    pub permissions: RolePermissions,
+    #[serde(serialize_with = "serialize_naive_as_utc")]
+    pub from: NaiveDateTime,
+    /// None while still in force
+    #[serde(serialize_with = "serialize_optional_naive_as_utc")]
+    pub until: Option<NaiveDateTime>,
+    /// Who granted or changed the assignment; None for assignments older than the history
+    pub started_by: Option<i32>,
+    pub started_by_name: Option<String>,
+    pub ended: Option<RoleMembershipEnd>,
+    pub ended_by: Option<i32>,
+    pub ended_by_name: Option<String>,
+}
+
+fn serialize_optional_naive_as_utc<S>(dt: &Option<NaiveDateTime>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    match dt {
+        Some(dt) => serialize_naive_as_utc(dt, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
 fn serialize_naive_as_utc<S>(dt: &NaiveDateTime, serializer: S) -> Result<S::Ok, S::Error>
 where
     S: serde::Serializer,
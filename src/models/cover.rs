@@ -0,0 +1,39 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// A shift on the cover board
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct CoverShift {
+    pub uuid: Uuid,
+    pub role_id: i32,
+    pub date: NaiveDate,
+    pub start_time: Option<String>,
+    pub end_time: Option<String>,
+    pub label: String,
+    /// Current assignee of a flagged shift (None when unfilled)
+    pub user_profile_id: Option<i32>,
+    pub user_short_name: Option<String>,
+    /// Flagged by an admin; unflagged shifts are listed because they are unassigned and coming up
+    pub needs_cover: bool,
+    /// Volunteers waiting for admin approval
+    pub volunteer_count: i64,
+    /// The caller's own pending volunteer request, if any
+    pub my_request_id: Option<i32>,
+}
+
+/// Input for flagging or clearing a shift's needs-cover flag
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SetNeedsCoverInput {
+    pub needs_cover: bool,
+}
+
+/// Input for volunteering to cover a shift
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct VolunteerForCoverInput {
+    pub notes: Option<String>,
+    #[serde(rename = "confirmedVolunteerId")]
+    pub confirmed_volunteer_id: Option<i32>, // For generic accounts - PIN-verified user ID, logged only; the signed-in account volunteers
+}
@@ -0,0 +1,126 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::FromRow;
+use std::fmt;
+use std::str::FromStr;
+use utoipa::ToSchema;
+
+const MAX_TEXT_LENGTH: usize = 500;
+
+/// Stored in "CustomFieldDefinitions".field_type; decides which JSON values a field accepts
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum CustomFieldType {
+    Text,
+    Number,
+    Boolean,
+    /// "YYYY-MM-DD" string
+    Date,
+}
+
+impl CustomFieldType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CustomFieldType::Text => "TEXT",
+            CustomFieldType::Number => "NUMBER",
+            CustomFieldType::Boolean => "BOOLEAN",
+            CustomFieldType::Date => "DATE",
+        }
+    }
+
+    /// Why `value` cannot be stored in a field of this type, if it cannot
+    pub fn check(&self, value: &Value) -> Result<(), String> {
+        let valid = match (self, value) {
+            (CustomFieldType::Text, Value::String(text)) => {
+                if text.chars().count() > MAX_TEXT_LENGTH {
+                    return Err(format!("must be at most {} characters", MAX_TEXT_LENGTH));
+                }
+                true
+            }
+            (CustomFieldType::Number, Value::Number(_)) => true,
+            (CustomFieldType::Boolean, Value::Bool(_)) => true,
+            (CustomFieldType::Date, Value::String(date)) => NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok(),
+            _ => false,
+        };
+        match (valid, self) {
+            (true, _) => Ok(()),
+            (false, CustomFieldType::Text) => Err("must be a string".to_string()),
+            (false, CustomFieldType::Number) => Err("must be a number".to_string()),
+            (false, CustomFieldType::Boolean) => Err("must be true or false".to_string()),
+            (false, CustomFieldType::Date) => Err("must be a date (YYYY-MM-DD)".to_string()),
+        }
+    }
+}
+
+impl FromStr for CustomFieldType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "TEXT" => Ok(CustomFieldType::Text),
+            "NUMBER" => Ok(CustomFieldType::Number),
+            "BOOLEAN" => Ok(CustomFieldType::Boolean),
+            "DATE" => Ok(CustomFieldType::Date),
+            other => Err(format!("Unknown custom field type: {}", other)),
+        }
+    }
+}
+
+crate::models::marketplace::impl_varchar_enum!(CustomFieldType);
+
+/// A profile attribute a workplace tracks for its staff, e.g. bleep number
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct CustomFieldDefinition {
+    pub id: i32,
+    pub workplace_id: i32,
+    /// Lowercase letters, digits and underscores; names the CSV export column
+    pub key: String,
+    pub label: String,
+    pub field_type: CustomFieldType,
+    pub created_at: NaiveDateTime,
+}
+
+/// Input for defining a field
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CreateCustomFieldInput {
+    pub key: String,
+    pub label: String,
+    pub field_type: CustomFieldType,
+}
+
+/// Input for renaming a field (its key and type are fixed once values exist)
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UpdateCustomFieldInput {
+    pub label: String,
+}
+
+/// A user's value for one field, with the field's definition
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct CustomFieldValue {
+    pub definition_id: i32,
+    pub workplace_id: i32,
+    pub key: String,
+    pub label: String,
+    pub field_type: CustomFieldType,
+    #[schema(value_type = Object)]
+    pub value: Value,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_custom_field_type_check() {
+        assert!(CustomFieldType::Text.check(&json!("Bleep 4421")).is_ok());
+        assert!(CustomFieldType::Text.check(&json!("x".repeat(501))).is_err());
+        assert!(CustomFieldType::Number.check(&json!(4421)).is_ok());
+        assert!(CustomFieldType::Number.check(&json!("4421")).is_err());
+        assert!(CustomFieldType::Boolean.check(&json!(true)).is_ok());
+        assert!(CustomFieldType::Date.check(&json!("2026-02-28")).is_ok());
+        assert!(CustomFieldType::Date.check(&json!("2026-02-30")).is_err());
+        assert!(CustomFieldType::Date.check(&json!(20260228)).is_err());
+    }
+}
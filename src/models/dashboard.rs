@@ -0,0 +1,142 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::collections::BTreeMap;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::{
+    announcement::Announcement,
+    diary::{DiaryEntry, DiaryEntryType},
+    marketplace::{ShiftRequestStatus, ShiftRequestType},
+    shift::Shift,
+};
+
+/// Caller's marketplace requests that still need something to happen
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct MarketplaceCounts {
+    /// Own requests still OPEN
+    pub open: i64,
+    /// Own requests (or claims) waiting for an admin
+    pub awaiting_approval: i64,
+    /// Swap proposals addressed to the caller
+    pub incoming_proposals: i64,
+}
+
+/// Leave days recorded in the diary for the current year, against the year's entitlement
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct LeaveSummary {
+    pub year: i32,
+    pub annual_leave_days: i64,
+    pub study_leave_days: i64,
+    pub professional_leave_days: i64,
+    /// Entitlement for the year from the job plans in force on each day, pro rata, rounded to 0.1 day
+    pub annual_leave_allowance: f64,
+    pub study_leave_allowance: f64,
+    pub professional_leave_allowance: f64,
+    /// Leave days from today onwards (already booked, not yet taken)
+    pub upcoming_days: i64,
+}
+
+/// Approvals the caller can action; None when they lack the permission
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct PendingApprovals {
+    /// Marketplace requests in PENDING_APPROVAL (can_edit_rota or an active delegation)
+    pub marketplace: Option<i64>,
+    /// Rota months submitted for publish sign-off (can_approve_rota)
+    pub rota_publish: Option<i64>,
+}
+
+/// Everything the home screen needs in one response
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Dashboard {
+    /// Caller's next 5 shifts from today
+    pub next_shifts: Vec<Shift>,
+    pub marketplace: MarketplaceCounts,
+    /// Unread live announcements addressed to the caller, pinned first
+    pub announcements: Vec<Announcement>,
+    /// Role-wide diary notes for the caller's roles over the next 14 days
+    pub diary_notes: Vec<DiaryEntry>,
+    pub unread_notifications: i64,
+    pub leave: LeaveSummary,
+    pub approvals: PendingApprovals,
+}
+
+/// A working shift in the month view, with only what a calendar cell draws
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct CalendarShift {
+    #[serde(skip)]
+    pub date: NaiveDate,
+    pub uuid: Uuid,
+    pub role: i32,
+    pub label: String,
+    /// HH:MM
+    pub start: Option<String>,
+    pub end: Option<String>,
+    pub font_color: String,
+    pub bk_color: String,
+    pub published: bool,
+    pub crosses_midnight: bool,
+}
+
+/// Time off on a day: a time-off shift on the rota, or annual/study/professional leave recorded in the diary
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct CalendarTimeOff {
+    #[serde(skip)]
+    pub date: NaiveDate,
+    pub role: i32,
+    /// The shift's label, or AL / SL / PL for diary leave
+    pub label: String,
+    /// Time-off category of a rota entry; None for diary leave
+    pub time_off: Option<i32>,
+}
+
+/// An unresolved marketplace request involving the caller, on the day of the caller's shift in it
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct CalendarSwap {
+    #[serde(skip)]
+    pub date: NaiveDate,
+    pub id: i32,
+    #[serde(rename = "type")]
+    #[sqlx(rename = "type")]
+    pub request_type: ShiftRequestType,
+    pub status: ShiftRequestStatus,
+    pub shift_id: Uuid,
+    pub target_shift_id: Option<Uuid>,
+    /// Raised by someone else, with the caller as target or candidate
+    pub incoming: bool,
+}
+
+/// A diary note about the caller, or a role-wide note or announcement they can see
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct CalendarDiaryItem {
+    #[serde(skip)]
+    pub date: NaiveDate,
+    pub id: i32,
+    pub role: i32,
+    pub entry: Option<String>,
+    pub entry_type: DiaryEntryType,
+    /// The note is about the caller rather than the whole role
+    pub personal: bool,
+}
+
+/// Everything on one day; empty lists are left out
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct CalendarDay {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub shifts: Vec<CalendarShift>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub time_off: Vec<CalendarTimeOff>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub swaps: Vec<CalendarSwap>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub diary: Vec<CalendarDiaryItem>,
+}
+
+/// The caller's month for a calendar view, keyed by date (YYYY-MM-DD); days with nothing on them are left out
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PersonalCalendar {
+    pub year: i32,
+    pub month: i32,
+    pub days: BTreeMap<NaiveDate, CalendarDay>,
+}
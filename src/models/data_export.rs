@@ -0,0 +1,69 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::user::User;
+
+/// A requested copy of the caller's personal data
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct DataExport {
+    /// Download token: GET /api/users/me/export/{token}
+    pub token: Uuid,
+    pub status: String,  // PENDING | READY | FAILED
+    pub error: Option<String>,
+    #[serde(serialize_with = "serialize_naive_as_utc")]
+    pub requested_at: NaiveDateTime,
+    #[serde(serialize_with = "serialize_optional_naive_as_utc")]
+    pub completed_at: Option<NaiveDateTime>,
+    #[serde(serialize_with = "serialize_naive_as_utc")]
+    pub expires_at: NaiveDateTime,
+}
+
+/// Everything held about one user, as downloaded from a READY export
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DataExportBundle {
+    pub generated_at: DateTime<Utc>,
+    /// Profile with contact details decrypted; the PIN hash is left out
+    pub profile: User,
+    /// Role assignments and their permissions
+    #[schema(value_type = Vec<Object>)]
+    pub roles: Value,
+    /// Shifts assigned to the user
+    #[schema(value_type = Vec<Object>)]
+    pub shifts: Value,
+    /// Diary entries about the user, and role-wide ones they wrote (not those about colleagues), including
+    /// deleted ones not yet purged
+    #[schema(value_type = Vec<Object>)]
+    pub diary: Value,
+    /// Swap and give-away requests the user raised, was targeted by or accepted
+    #[schema(value_type = Vec<Object>)]
+    pub marketplace_requests: Value,
+    /// Give-away offers the user was notified about
+    #[schema(value_type = Vec<Object>)]
+    pub marketplace_offers: Value,
+    /// Shift audit entries made by or about the user (ids only; see GET /api/audit)
+    #[schema(value_type = Vec<Object>)]
+    pub audit_references: Value,
+}
+
+fn serialize_optional_naive_as_utc<S>(dt: &Option<NaiveDateTime>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    match dt {
+        Some(dt) => serialize_naive_as_utc(dt, serializer),
+        None => serializer.serialize_none(),
+    }
+}
+
+fn serialize_naive_as_utc<S>(dt: &NaiveDateTime, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    use chrono::SecondsFormat;
+    let utc_dt = DateTime::<Utc>::from_naive_utc_and_offset(*dt, Utc);
+    utc_dt.to_rfc3339_opts(SecondsFormat::Millis, true).serialize(serializer)
+}
@@ -3,6 +3,42 @@ use utoipa::ToSchema;
 
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use std::fmt;
+use std::str::FromStr;
+
+/// Stored in "Diary".entry_type. Notes follow can_access_diary in the entry's role; announcements are also
+/// shown to every member of the role.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DiaryEntryType {
+    #[default]
+    Note,
+    Announcement,
+}
+
+impl DiaryEntryType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DiaryEntryType::Note => "NOTE",
+            DiaryEntryType::Announcement => "ANNOUNCEMENT",
+        }
+    }
+}
+
+impl FromStr for DiaryEntryType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "NOTE" => Ok(DiaryEntryType::Note),
+            "ANNOUNCEMENT" => Ok(DiaryEntryType::Announcement),
+            other => Err(format!("Unknown diary entry type: {}", other)),
+        }
+    }
+}
+
+crate::models::marketplace::impl_varchar_enum!(DiaryEntryType);
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct DiaryEntry {
     pub id: i32,
@@ -17,6 +53,7 @@ pub struct DiaryEntry {
     pub user_profile_id: Option<i32>,
     pub created_by: i32,
     pub deleted: bool,
+    pub entry_type: DiaryEntryType,
     #[sqlx(default)]
     pub short_name: Option<String>,  // From LEFT JOIN with Users table
 }
@@ -2,6 +2,8 @@ use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
+use crate::models::DiaryEntryType;
+
 
 /// Input for creating a diary entry
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -13,9 +15,15 @@ pub struct CreateDiaryInput {
     pub sl: bool,
     pub pl: bool,
     pub user_profile_id: Option<i32>,
+    /// ANNOUNCEMENT entries are role-wide (no user_profile_id or leave flags) and visible to all role members
+    #[serde(default)]
+    pub entry_type: DiaryEntryType,
     pub created_by: Option<i32>, // Will be set to authenticated user
     #[serde(rename = "confirmedUserId")]
     pub confirmed_user_id: Option<i32>, // For generic accounts - PIN-verified user ID
+    /// Record leave past the user's remaining allowance or for a user without diary access (can_edit_rota only)
+    #[serde(default)]
+    pub override_leave_checks: bool,
 }
 
 /// Response for diary mutations
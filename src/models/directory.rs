@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// A workplace in the staff directory, with its roles and their members
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DirectoryWorkplace {
+    pub workplace_id: i32,
+    pub hospital: Option<String>,
+    pub ward: Option<String>,
+    pub roles: Vec<DirectoryRole>,
+}
+
+/// A role and the staff assigned to it
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DirectoryRole {
+    pub role_id: i32,
+    pub role_name: String,
+    pub members: Vec<DirectoryMember>,
+}
+
+/// One member of staff as listed under a role
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DirectoryMember {
+    pub user_profile_id: i32,
+    pub full_name: String,
+    pub short_name: String,
+    pub color: Option<String>,
+    /// Only present for callers with can_view_staff_details on this role (and for the member themselves)
+    pub contact: Option<DirectoryContact>,
+}
+
+/// Contact details shown in the directory
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DirectoryContact {
+    pub primary_email: Option<String>,
+    pub tel: Vec<String>,
+}
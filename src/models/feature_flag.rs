@@ -0,0 +1,50 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+
+/// Soft-launch switch. A user gets the feature when it is on for everyone, for one of their workplaces,
+/// for them by ID, or when they fall inside the rollout percentage.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct FeatureFlag {
+    pub key: String,
+    pub description: Option<String>,
+    pub enabled: bool,               // On for everyone
+    pub workplace_ids: Vec<i32>,     // On for users with a role in these workplaces
+    pub user_profile_ids: Vec<i32>,  // On for these users
+    pub rollout_percent: i16,        // On for this share of users (stable per user and flag)
+    pub updated_by: Option<i32>,
+    pub updated_at: NaiveDateTime,
+}
+
+/// Input for creating a feature flag
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CreateFeatureFlagInput {
+    pub key: String,  // lowercase letters, digits and underscores
+    pub description: Option<String>,
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub workplace_ids: Vec<i32>,
+    #[serde(default)]
+    pub user_profile_ids: Vec<i32>,
+    #[serde(default)]
+    pub rollout_percent: i16,
+}
+
+/// Input for updating a feature flag (omitted fields keep their current value)
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UpdateFeatureFlagInput {
+    pub description: Option<String>,
+    pub enabled: Option<bool>,
+    pub workplace_ids: Option<Vec<i32>>,
+    pub user_profile_ids: Option<Vec<i32>>,
+    pub rollout_percent: Option<i16>,
+}
+
+/// Response for feature flag deletion
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FeatureFlagMutationResponse {
+    pub success: bool,
+    pub message: Option<String>,
+}
@@ -21,10 +21,34 @@ pub struct JobPlan {
     pub until: Option<NaiveDate>,
     pub comment: Option<String>,
 }
+/// Kind of problem found between a user's consecutive job plans for a role
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum JobPlanIssueKind {
+    Overlap,
+    Gap,
+}
+
+/// Overlap or gap between two job plans of the same user and role
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct JobPlanIssue {
+    pub kind: JobPlanIssueKind,
+    pub user_profile_id: i32,
+    pub role_id: i32,
+    pub first_plan_id: i32,
+    pub second_plan_id: i32,
+    /// Affected period (inclusive): the overlapping days, or the uncovered days for a gap
+    pub from: NaiveDate,
+    pub until: Option<NaiveDate>,
+}
+
+/// Usual job plan shape for a role (or, without role_id, any role of the workplace)
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct JobPlanTemplate {
+    pub id: i32,
     #[serde(rename = "workplace")]
     pub workplace: i32,
+    pub role_id: Option<i32>,
     pub label: String,
     pub al_per_year: Option<f32>,
     pub sl_per_year: Option<f32>,
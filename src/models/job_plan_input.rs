@@ -43,3 +43,42 @@ pub struct JobPlanMutationResponse {
     pub success: bool,
     pub message: Option<String>,
 }
+
+/// Input for creating a job plan template
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CreateJobPlanTemplateInput {
+    pub role_id: i32,
+    pub label: String,
+    pub dcc_pa: Option<f32>,
+    pub dcc_hour: Option<f32>,
+    pub spa_pa: Option<f32>,
+    pub spa_hour: Option<f32>,
+    pub al_per_year: Option<f32>,
+    pub sl_per_year: Option<f32>,
+    pub pl_per_year: Option<f32>,
+}
+
+/// Input for updating a job plan template (omitted fields are unchanged)
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UpdateJobPlanTemplateInput {
+    pub label: Option<String>,
+    pub dcc_pa: Option<f32>,
+    pub dcc_hour: Option<f32>,
+    pub spa_pa: Option<f32>,
+    pub spa_hour: Option<f32>,
+    pub al_per_year: Option<f32>,
+    pub sl_per_year: Option<f32>,
+    pub pl_per_year: Option<f32>,
+}
+
+/// Input for instantiating a job plan from a template
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CreateJobPlanFromTemplateInput {
+    pub template_id: i32,
+    pub user_profile_id: i32,
+    /// Required for a workplace-wide template; must match the template's role otherwise
+    pub role_id: Option<i32>,
+    pub from: NaiveDate,
+    pub until: Option<NaiveDate>,
+    pub comment: Option<String>,
+}
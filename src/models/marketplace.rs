@@ -1,9 +1,185 @@
-use chrono::{NaiveDate, NaiveDateTime};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
 use utoipa::ToSchema;
 
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use std::fmt;
+use std::str::FromStr;
 use uuid::Uuid;
+
+/// Kind of marketplace request (stored in "ShiftRequests".type)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ShiftRequestType {
+    Swap,
+    #[serde(alias = "GIVEAWAY")]
+    GiveAway,
+    Pickup,
+}
+
+impl ShiftRequestType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ShiftRequestType::Swap => "SWAP",
+            ShiftRequestType::GiveAway => "GIVE_AWAY",
+            ShiftRequestType::Pickup => "PICKUP",
+        }
+    }
+}
+
+impl FromStr for ShiftRequestType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "SWAP" => Ok(ShiftRequestType::Swap),
+            // Older rows were written as GIVEAWAY
+            "GIVE_AWAY" | "GIVEAWAY" => Ok(ShiftRequestType::GiveAway),
+            "PICKUP" => Ok(ShiftRequestType::Pickup),
+            other => Err(format!("Unknown shift request type: {}", other)),
+        }
+    }
+}
+
+/// Lifecycle status of a marketplace request (stored in "ShiftRequests".status)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ShiftRequestStatus {
+    Open,
+    Proposed,
+    PeerAccepted,
+    PeerRejected,
+    PendingApproval,
+    Approved,
+    Rejected,
+    Cancelled,
+}
+
+impl ShiftRequestStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ShiftRequestStatus::Open => "OPEN",
+            ShiftRequestStatus::Proposed => "PROPOSED",
+            ShiftRequestStatus::PeerAccepted => "PEER_ACCEPTED",
+            ShiftRequestStatus::PeerRejected => "PEER_REJECTED",
+            ShiftRequestStatus::PendingApproval => "PENDING_APPROVAL",
+            ShiftRequestStatus::Approved => "APPROVED",
+            ShiftRequestStatus::Rejected => "REJECTED",
+            ShiftRequestStatus::Cancelled => "CANCELLED",
+        }
+    }
+
+    /// Resolved requests can no longer change state
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            ShiftRequestStatus::Approved | ShiftRequestStatus::Rejected | ShiftRequestStatus::Cancelled
+        )
+    }
+
+    /// Marketplace state machine:
+    /// - OPEN → PENDING_APPROVAL | APPROVED (claimed, approval depends on role auto-approve)
+    /// - PROPOSED / PEER_ACCEPTED → PENDING_APPROVAL | APPROVED | REJECTED (target user responds)
+    /// - PENDING_APPROVAL → APPROVED | REJECTED (admin decision)
+    /// - PENDING_APPROVAL → OPEN (candidate withdraws a claim on an open request)
+    /// - any non-terminal status → CANCELLED (requester withdraws)
+    pub fn can_transition_to(&self, next: ShiftRequestStatus) -> bool {
+        use ShiftRequestStatus::*;
+
+        match (*self, next) {
+            (from, Cancelled) => !from.is_terminal(),
+            (Open, PendingApproval | Approved) => true,
+            (Proposed | PeerAccepted, PendingApproval | Approved | Rejected) => true,
+            (PendingApproval, Approved | Rejected | Open) => true,
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for ShiftRequestStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "OPEN" => Ok(ShiftRequestStatus::Open),
+            "PROPOSED" => Ok(ShiftRequestStatus::Proposed),
+            "PEER_ACCEPTED" => Ok(ShiftRequestStatus::PeerAccepted),
+            "PEER_REJECTED" => Ok(ShiftRequestStatus::PeerRejected),
+            "PENDING_APPROVAL" => Ok(ShiftRequestStatus::PendingApproval),
+            "APPROVED" => Ok(ShiftRequestStatus::Approved),
+            "REJECTED" => Ok(ShiftRequestStatus::Rejected),
+            "CANCELLED" => Ok(ShiftRequestStatus::Cancelled),
+            other => Err(format!("Unknown shift request status: {}", other)),
+        }
+    }
+}
+
+/// These columns are plain varchar/text, so encode/decode through &str rather than
+/// deriving sqlx::Type (which would require a Postgres enum type)
+macro_rules! impl_varchar_enum {
+    ($ty:ty) => {
+        impl fmt::Display for $ty {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl sqlx::Type<sqlx::Postgres> for $ty {
+            fn type_info() -> sqlx::postgres::PgTypeInfo {
+                <&str as sqlx::Type<sqlx::Postgres>>::type_info()
+            }
+
+            fn compatible(ty: &sqlx::postgres::PgTypeInfo) -> bool {
+                <&str as sqlx::Type<sqlx::Postgres>>::compatible(ty)
+            }
+        }
+
+        impl<'q> sqlx::Encode<'q, sqlx::Postgres> for $ty {
+            fn encode_by_ref(
+                &self,
+                buf: &mut sqlx::postgres::PgArgumentBuffer,
+            ) -> Result<sqlx::encode::IsNull, sqlx::error::BoxDynError> {
+                <&str as sqlx::Encode<sqlx::Postgres>>::encode(self.as_str(), buf)
+            }
+        }
+
+        impl<'r> sqlx::Decode<'r, sqlx::Postgres> for $ty {
+            fn decode(value: sqlx::postgres::PgValueRef<'r>) -> Result<Self, sqlx::error::BoxDynError> {
+                let s = <&str as sqlx::Decode<sqlx::Postgres>>::decode(value)?;
+                Ok(s.parse()?)
+            }
+        }
+    };
+}
+
+pub(crate) use impl_varchar_enum;
+
+impl_varchar_enum!(ShiftRequestType);
+impl_varchar_enum!(ShiftRequestStatus);
+
+/// Sort order for marketplace list endpoints (`sort=` query parameter)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MarketplaceSort {
+    /// Newest (or most recently bumped) requests first
+    CreatedAt,
+    /// Earliest shift first
+    ShiftDate,
+    /// Upcoming shifts soonest first, past shifts last, then oldest request first
+    Urgency,
+}
+
+impl MarketplaceSort {
+    /// ORDER BY clause over MARKETPLACE_BASE_QUERY aliases (sr = request, s = shift)
+    pub fn order_by(&self) -> &'static str {
+        match self {
+            MarketplaceSort::CreatedAt => "COALESCE(sr.bumped_at, sr.created_at) DESC, sr.id DESC",
+            MarketplaceSort::ShiftDate => "s.date ASC, s.start ASC NULLS LAST, sr.id ASC",
+            MarketplaceSort::Urgency => "(s.date < CURRENT_DATE) ASC, s.date ASC, sr.created_at ASC, sr.id ASC",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct ShiftRequest {
     pub id: i32,
@@ -11,16 +187,30 @@ pub struct ShiftRequest {
     pub requester_id: i32,
     #[serde(rename = "type")]
     #[sqlx(rename = "type")]
-    pub request_type: String,
-    pub status: String,
+    pub request_type: ShiftRequestType,
+    pub status: ShiftRequestStatus,
     pub target_user_id: Option<i32>,
     pub target_shift_id: Option<Uuid>,
     pub candidate_id: Option<i32>,
     pub resolved_by: Option<i32>,
     pub resolved_at: Option<NaiveDateTime>,
+    /// Approver that resolved_by stood in for under an approval delegation
+    pub resolved_as_delegate_of: Option<i32>,
+    /// Admin who raised the request on the requester's behalf (None when self-created)
+    pub created_by_admin_id: Option<i32>,
     pub notes: Option<String>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    /// Last time the request was bumped back to the top of the open list
+    pub bumped_at: Option<NaiveDateTime>,
+    /// Approving it takes a party past the role's max_swaps_per_month, so it waits for an admin override
+    pub swap_cap_exceeded: bool,
+    /// Admin who approved it past the cap
+    pub swap_cap_override_by: Option<i32>,
+    /// Set when the request covers every shift of this series the requester holds, not just shift_id
+    pub series_id: Option<Uuid>,
+    /// "MarketplaceReasons" code given when the request was rejected or cancelled
+    pub reason_code: Option<String>,
 }
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct ShiftRequestWithDetails {
@@ -43,9 +233,39 @@ pub struct ShiftRequestWithDetails {
     pub target_shift_end: Option<String>,
     pub candidate_name: Option<String>,
     pub candidate_short_name: Option<String>,
+    pub created_by_admin_name: Option<String>,
     pub role_auto_approve: bool,
 }
 
+/// Approval rights lent by a can_edit_rota user to a colleague for a date range
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct ApprovalDelegation {
+    pub id: i32,
+    pub delegator_id: i32,
+    pub delegator_name: Option<String>, // From JOIN with Users
+    pub delegate_id: i32,
+    pub delegate_name: Option<String>,  // From JOIN with Users
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub reason: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+/// A date a locum has advertised as available to work for a role
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct LocumAvailability {
+    pub id: i32,
+    pub user_profile_id: i32,
+    pub user_short_name: Option<String>, // From JOIN with Users
+    pub role_id: i32,
+    pub date: NaiveDate,
+    pub notes: Option<String>,
+    pub created_at: NaiveDateTime,
+    /// Shift the locum was assigned from this posting (None while still advertised)
+    pub assigned_shift_id: Option<Uuid>,
+    pub assigned_at: Option<NaiveDateTime>,
+}
+
 /// Swappable shift (simplified shift info for marketplace)
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct SwappableShift {
@@ -69,3 +289,181 @@ pub struct UserWithSwappableShifts {
     pub user_name: String,
     pub shifts: Vec<SwappableShift>,
 }
+
+/// Outcome of one marketplace rule in a swap eligibility check
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SwapCheck {
+    /// Rule code, e.g. SHIFT_OWNER or NO_DOUBLE_BOOKING
+    pub rule: String,
+    pub passed: bool,
+    /// Why the rule failed (None when it passed)
+    pub reason: Option<String>,
+}
+
+/// Someone told about a GIVE_AWAY when it was posted or bumped (role member, free that day, not opted out)
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct ShiftOfferRecipient {
+    pub user_profile_id: i32,
+    pub short_name: String,
+    pub full_name: String,
+    /// When they were last told (a bump notifies again)
+    pub notified_at: NaiveDateTime,
+}
+
+/// Outcome of bumping an OPEN request
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BumpRequestResponse {
+    pub request: ShiftRequestWithDetails,
+    /// Staff notified again
+    pub notified: i32,
+    /// Earliest time the request can be bumped again
+    pub next_bump_at: NaiveDateTime,
+}
+
+/// Outcome of force-cancelling a stuck request (the shift may be gone, so no request details)
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ForceCancelResponse {
+    pub request_id: i32,
+    pub previous_status: ShiftRequestStatus,
+    /// Inconsistencies found between the request and its shifts, e.g. "Shift ... no longer exists"
+    pub issues: Vec<String>,
+    /// Parties told about the cancellation
+    pub notified: Vec<i32>,
+}
+
+/// A request opened from a notification deep link, with what the caller may do with it
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ShiftRequestByToken {
+    pub request: ShiftRequestWithDetails,
+    /// The caller may approve or reject it now (it is PENDING_APPROVAL and they are an approver or delegate)
+    pub can_decide: bool,
+    /// Approver the caller would be deciding for under an active delegation
+    pub delegate_of: Option<i32>,
+    /// Approving now would take a party past the role's monthly swap cap, so it needs override_swap_cap
+    pub requires_swap_cap_override: bool,
+    /// When the link stops working
+    pub link_expires_at: DateTime<Utc>,
+}
+
+/// Result of validating a proposed swap without creating a request
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SwapEligibility {
+    /// True when every check passed
+    pub eligible: bool,
+    /// The role auto-approves swaps once the other user accepts (no admin approval step)
+    pub auto_approve: bool,
+    pub checks: Vec<SwapCheck>,
+}
+
+/// A shift request status change, as returned by GET /api/marketplace/poll
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct MarketplaceEvent {
+    /// "ShiftRequestTransitions" id; pass the last one seen as ?since= on the next poll
+    pub cursor: i64,
+    pub shift_request_id: i32,
+    pub request_type: ShiftRequestType,
+    pub role_id: i32,
+    pub from_status: Option<ShiftRequestStatus>, // None when the request was created
+    pub to_status: ShiftRequestStatus,
+    pub changed_by: Option<i32>,
+    pub changed_at: NaiveDateTime,
+}
+
+/// Events after the poll's cursor, oldest first
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MarketplacePollResponse {
+    pub events: Vec<MarketplaceEvent>,
+    /// Cursor to send as ?since= next time (unchanged when the wait ended without events)
+    pub cursor: i64,
+}
+
+/// How long requests wait for an admin decision (PENDING_APPROVAL) over a date range
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MarketplaceSlaReport {
+    pub role_id: Option<i32>,
+    /// First and last day (inclusive) of the decisions counted
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub target_hours: i32,
+    /// Requests that left PENDING_APPROVAL in the range (approved, rejected, withdrawn or cancelled)
+    pub decided: i64,
+    pub approved: i64,
+    pub rejected: i64,
+    pub decided_over_target: i64,
+    pub wait_p50_seconds: Option<f64>,
+    pub wait_p90_seconds: Option<f64>,
+    pub wait_p95_seconds: Option<f64>,
+    pub wait_max_seconds: Option<f64>,
+    /// Requests waiting right now
+    pub pending: i64,
+    pub pending_over_target: i64,
+    pub oldest_pending_seconds: Option<f64>,
+    /// Approved requests per member and month, for shifts dated in the range
+    pub swap_usage: Vec<SwapUsage>,
+    /// Requests rejected or cancelled in the range, by reason code (None when no code was given)
+    pub failure_reasons: Vec<FailureReasonCount>,
+}
+
+/// Rejected and cancelled requests with one reason code
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, FromRow)]
+pub struct FailureReasonCount {
+    pub reason_code: Option<String>,
+    pub label: Option<String>,
+    pub rejected: i64,
+    pub cancelled: i64,
+}
+
+/// One member's approved marketplace requests (as requester or candidate) in a role for one month
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema, FromRow)]
+pub struct SwapUsage {
+    pub role_id: i32,
+    /// First day of the month
+    pub month: NaiveDate,
+    pub user_profile_id: i32,
+    pub short_name: String,
+    pub approved: i64,
+    /// Approved past the cap by an admin override
+    pub overrides: i64,
+    /// The role's max_swaps_per_month (None when uncapped)
+    pub cap: Option<i16>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_transitions() {
+        use ShiftRequestStatus::*;
+
+        assert!(Open.can_transition_to(PendingApproval));
+        assert!(Open.can_transition_to(Approved));
+        assert!(!Open.can_transition_to(Rejected));
+        assert!(Proposed.can_transition_to(Rejected));
+        assert!(PendingApproval.can_transition_to(Approved));
+        assert!(PendingApproval.can_transition_to(Open));
+        assert!(!Approved.can_transition_to(Open));
+        assert!(PeerRejected.can_transition_to(Cancelled));
+        assert!(!Approved.can_transition_to(Cancelled));
+        assert!(!Cancelled.can_transition_to(Cancelled));
+    }
+
+    #[test]
+    fn test_type_accepts_legacy_giveaway() {
+        assert_eq!("GIVEAWAY".parse::<ShiftRequestType>(), Ok(ShiftRequestType::GiveAway));
+        assert_eq!(
+            serde_json::from_str::<ShiftRequestType>("\"GIVE_AWAY\"").unwrap(),
+            ShiftRequestType::GiveAway
+        );
+        assert!("BOGUS".parse::<ShiftRequestStatus>().is_err());
+    }
+
+    #[test]
+    fn test_sort_parses_snake_case() {
+        assert_eq!(
+            serde_json::from_str::<MarketplaceSort>("\"shift_date\"").unwrap(),
+            MarketplaceSort::ShiftDate
+        );
+        assert!(MarketplaceSort::Urgency.order_by().starts_with("(s.date < CURRENT_DATE)"));
+    }
+}
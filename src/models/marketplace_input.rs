@@ -1,19 +1,38 @@
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use utoipa::ToSchema;
 
+use super::marketplace::ShiftRequestType;
+
 
 /// Input for creating a shift swap request
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct CreateShiftRequestInput {
     pub shift_id: Uuid,
     #[serde(rename = "type")]
-    pub request_type: String, // "SWAP" or "GIVE_AWAY"
+    pub request_type: ShiftRequestType, // SWAP or GIVE_AWAY
     pub target_user_id: Option<i32>,
     pub target_shift_id: Option<Uuid>,
     pub notes: Option<String>,
     #[serde(rename = "confirmedRequesterId")]
     pub confirmed_requester_id: Option<i32>, // For generic accounts - PIN-verified user ID
+    /// Admin path (can_edit_rota): raise the request as this staff member, e.g. when they are off sick
+    pub on_behalf_of: Option<i32>,
+    /// Offer every shift of shift_id's series the requester holds ("swap my week of nights")
+    #[serde(default)]
+    pub whole_series: bool,
+}
+
+/// Input for checking whether two shifts could be swapped
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ValidateSwapInput {
+    /// Shift the requester would give up
+    pub shift_id: Uuid,
+    /// Colleague's shift the requester would take
+    pub target_shift_id: Uuid,
+    #[serde(rename = "confirmedRequesterId")]
+    pub confirmed_requester_id: Option<i32>, // For generic accounts - PIN-verified user ID
 }
 
 /// Input for accepting/claiming an open request
@@ -24,12 +43,39 @@ pub struct AcceptRequestInput {
     pub confirmed_candidate_id: Option<i32>, // For generic accounts - PIN-verified user ID
 }
 
+/// Input for bumping an OPEN request back to the top of the open list
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BumpRequestInput {
+    #[serde(rename = "confirmedRequesterId")]
+    pub confirmed_requester_id: Option<i32>, // For generic accounts - PIN-verified user ID
+    /// Kept with the bump for audit, e.g. "still need cover, happy to swap back"
+    pub note: Option<String>,
+}
+
+/// Input for an admin force-cancelling a request that cannot proceed
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ForceCancelRequestInput {
+    /// Required; shown to every party and kept in the request's notes
+    pub reason: String,
+    /// Active "MarketplaceReasons" code for cancellations
+    pub reason_code: Option<String>,
+}
+
+/// Input for a candidate withdrawing from a request they accepted
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct WithdrawRequestInput {
+    #[serde(rename = "confirmedCandidateId")]
+    pub confirmed_candidate_id: Option<i32>, // For generic accounts - PIN-verified user ID
+}
+
 /// Input for responding to a proposed swap (approve or reject by target user)
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct RespondToProposalInput {
     pub accept: bool, // true = accept, false = reject
     #[serde(rename = "confirmedResponderId")]
     pub confirmed_responder_id: Option<i32>, // For generic accounts - PIN-verified user ID
+    /// Active "MarketplaceReasons" code for rejections; only used when rejecting
+    pub reason_code: Option<String>,
 }
 
 /// Input for admin approval decision
@@ -37,6 +83,11 @@ pub struct RespondToProposalInput {
 pub struct AdminDecisionInput {
     pub approve: bool, // true = approve, false = reject
     pub notes: Option<String>,
+    /// Must be true to approve a request flagged swap_cap_exceeded
+    #[serde(default)]
+    pub override_swap_cap: bool,
+    /// Active "MarketplaceReasons" code for rejections; only used when rejecting
+    pub reason_code: Option<String>,
 }
 
 /// Response for marketplace mutations
@@ -45,3 +96,27 @@ pub struct MarketplaceMutationResponse {
     pub success: bool,
     pub message: Option<String>,
 }
+
+/// Input for delegating the caller's approval rights (super admins may name the delegator)
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CreateDelegationInput {
+    pub delegator_id: Option<i32>,
+    pub delegate_id: i32,
+    pub start_date: NaiveDate,
+    pub end_date: NaiveDate,
+    pub reason: Option<String>,
+}
+
+/// Input for a locum advertising the dates they can work for a role
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CreateAvailabilityInput {
+    pub role_id: i32,
+    pub dates: Vec<NaiveDate>,
+    pub notes: Option<String>,
+}
+
+/// Input for assigning an advertised locum to an unfilled shift
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AssignLocumInput {
+    pub shift_id: Uuid,
+}
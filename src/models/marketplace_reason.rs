@@ -0,0 +1,79 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use std::fmt;
+use std::str::FromStr;
+use utoipa::ToSchema;
+
+/// Outcomes a reason can be given for (stored in "MarketplaceReasons".applies_to)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ReasonOutcome {
+    Reject,
+    Cancel,
+    #[default]
+    Any,
+}
+
+impl ReasonOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReasonOutcome::Reject => "REJECT",
+            ReasonOutcome::Cancel => "CANCEL",
+            ReasonOutcome::Any => "ANY",
+        }
+    }
+
+    /// Whether a reason for `self` may be given when a request ends in `outcome`
+    pub fn covers(&self, outcome: ReasonOutcome) -> bool {
+        *self == ReasonOutcome::Any || *self == outcome
+    }
+}
+
+impl FromStr for ReasonOutcome {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "REJECT" => Ok(ReasonOutcome::Reject),
+            "CANCEL" => Ok(ReasonOutcome::Cancel),
+            "ANY" => Ok(ReasonOutcome::Any),
+            other => Err(format!("Unknown reason outcome: {}", other)),
+        }
+    }
+}
+
+crate::models::marketplace::impl_varchar_enum!(ReasonOutcome);
+
+/// Why a marketplace request was rejected or cancelled, e.g. SHORT_NOTICE
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct MarketplaceReason {
+    pub code: String,
+    pub label: String,
+    pub applies_to: ReasonOutcome,
+    /// Inactive reasons stay on past requests but cannot be chosen
+    pub active: bool,
+    pub sort_order: i32,
+    pub updated_by: Option<i32>,
+    pub updated_at: NaiveDateTime,
+}
+
+/// Input for creating a reason
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CreateMarketplaceReasonInput {
+    pub code: String,  // uppercase letters, digits and underscores
+    pub label: String,
+    #[serde(default)]
+    pub applies_to: ReasonOutcome,
+    #[serde(default)]
+    pub sort_order: i32,
+}
+
+/// Input for updating a reason (omitted fields keep their current value)
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UpdateMarketplaceReasonInput {
+    pub label: Option<String>,
+    pub applies_to: Option<ReasonOutcome>,
+    pub active: Option<bool>,
+    pub sort_order: Option<i32>,
+}
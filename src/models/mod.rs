@@ -1,39 +1,103 @@
+pub mod absence;
+pub mod announcement;
+pub mod announcement_input;
+pub mod attachment;
+pub mod attendance;
 pub mod audit;
 pub mod comment;
+pub mod cover;
+pub mod custom_field;
+pub mod dashboard;
+pub mod data_export;
+pub mod feature_flag;
 pub mod diary;
+pub mod directory;
 pub mod diary_input;
 pub mod job_plan;
 pub mod job_plan_input;
 pub mod marketplace;
 pub mod marketplace_input;
+pub mod marketplace_reason;
+pub mod notification;
+pub mod pattern;
+pub mod pattern_input;
+pub mod pay;
 pub mod role;
+pub mod retention;
 pub mod role_input;
+pub mod rota;
+pub mod saved_view;
+pub mod search;
 pub mod shift;
 pub mod shift_input;
+pub mod shift_stats;
 pub mod template_input;
 pub mod time_off;
 pub mod user;
 pub mod user_input;
 pub mod user_role_input;
 
-pub use audit::AuditEntry;
+pub use absence::{AbsenceReport, AbsenceShiftImpact, ReplacementCandidate, ReportAbsenceInput};
+pub use announcement::Announcement;
+pub use announcement_input::{AnnouncementMutationResponse, CreateAnnouncementInput, UpdateAnnouncementInput};
+pub use attachment::{Attachment, AttachmentMutationResponse};
+pub use attendance::{
+    AttendanceDiscrepancy, AttendanceException, AttendanceLocationInput, AttendanceReport, ShiftAttendance,
+};
+pub use audit::{
+    AuditEntry, BackfillAuditInput, BackfillKind, BackfillReport, BackfilledChange, DataAccessEntry, LoginAuditEntry,
+    RoleMembershipEnd, RoleMembershipPeriod, RolePermissions, ShiftChangeKind, UserRoleAuditEntry, UserShiftChange,
+};
 pub use comment::COD;
-pub use diary::DiaryEntry;
+pub use cover::{CoverShift, SetNeedsCoverInput, VolunteerForCoverInput};
+pub use custom_field::{
+    CreateCustomFieldInput, CustomFieldDefinition, CustomFieldType, CustomFieldValue, UpdateCustomFieldInput,
+};
+pub use data_export::{DataExport, DataExportBundle};
+pub use dashboard::{
+    CalendarDay, CalendarDiaryItem, CalendarShift, CalendarSwap, CalendarTimeOff, Dashboard, LeaveSummary,
+    MarketplaceCounts, PendingApprovals, PersonalCalendar,
+};
+pub use diary::{DiaryEntry, DiaryEntryType};
+pub use feature_flag::{CreateFeatureFlagInput, FeatureFlag, FeatureFlagMutationResponse, UpdateFeatureFlagInput};
+pub use directory::{DirectoryContact, DirectoryMember, DirectoryRole, DirectoryWorkplace};
 pub use diary_input::{CreateDiaryInput, DiaryMutationResponse};
-pub use job_plan::JobPlan;
-pub use job_plan_input::{CreateJobPlanInput, JobPlanMutationResponse, UpdateJobPlanInput};
-pub use marketplace::{ShiftRequest, ShiftRequestWithDetails, SwappableShift, UserWithSwappableShifts};
-pub use marketplace_input::{AcceptRequestInput, AdminDecisionInput, CreateShiftRequestInput, MarketplaceMutationResponse, RespondToProposalInput};
-pub use role::{Role, Workplace};
-pub use role_input::{CreateRoleInput, CreateWorkplaceInput, DependencyCount, RoleMutationResponse, UpdateRoleInput, UpdateWorkplaceInput, WorkplaceMutationResponse};
-pub use shift::{Shift, ShiftTemplate};
-pub use shift_input::{CreateShiftInput, ShiftMutationResponse, UpdateShiftInput};
-pub use template_input::{CreateTemplateInput, TemplateMutationResponse, UpdateTemplateInput};
+pub use job_plan::{JobPlan, JobPlanIssue, JobPlanIssueKind, JobPlanTemplate};
+pub use job_plan_input::{CreateJobPlanFromTemplateInput, CreateJobPlanInput, CreateJobPlanTemplateInput, JobPlanMutationResponse, UpdateJobPlanInput, UpdateJobPlanTemplateInput};
+pub use marketplace::{ApprovalDelegation, BumpRequestResponse, ForceCancelResponse, LocumAvailability, FailureReasonCount, MarketplaceEvent, MarketplacePollResponse, MarketplaceSlaReport, MarketplaceSort, ShiftRequest, ShiftRequestStatus, ShiftRequestType, ShiftOfferRecipient, ShiftRequestByToken, ShiftRequestWithDetails, SwapCheck, SwapEligibility, SwappableShift, SwapUsage, UserWithSwappableShifts};
+pub use marketplace_reason::{CreateMarketplaceReasonInput, MarketplaceReason, ReasonOutcome, UpdateMarketplaceReasonInput};
+pub use marketplace_input::{AcceptRequestInput, AdminDecisionInput, AssignLocumInput, BumpRequestInput, CreateAvailabilityInput, CreateDelegationInput, CreateShiftRequestInput, ForceCancelRequestInput, MarketplaceMutationResponse, RespondToProposalInput, ValidateSwapInput, WithdrawRequestInput};
+pub use notification::{Notification, NotificationPreference, ShiftFieldChange, UpdateNotificationPreferencesInput};
+pub use pattern::{RotaPattern, RotaPatternEntry};
+pub use pattern_input::{ApplyPatternInput, ApplyPatternResponse, AssignmentSuggestion, CreatePatternInput, PatternEntryInput, PatternMutationResponse, UpdatePatternInput};
+pub use pay::{RoleCostReport, RolePayRules, ShiftCost, UpdatePayRulesInput};
+pub use retention::{RetentionReport, RetentionRuleResult};
+pub use role::{AssignmentDrift, AssignmentDriftFix, AssignmentIssue, DriftShift, PinPolicy, Role, RoleStats, Workplace, WorkplaceSettings};
+pub use role_input::{CreateDisplayTokenInput, CreateRoleInput, CreateWorkplaceInput, DependencyCount, FixAssignmentDriftInput, FixAssignmentDriftResponse, RoleMutationResponse, RotaApprovalDecisionInput, SubmitRotaApprovalInput, UpdateRoleInput, UpdateWorkplaceInput, UpdateWorkplaceSettingsInput, WorkplaceMutationResponse};
+pub use rota::{DisplayRota, DisplayShift, RoleRota, DisplayTokenResponse, MovedAssignment, RotaDiff, RotaLock, RotaPublishApproval, SnapshotShift};
+pub use saved_view::{CopySavedViewInput, CreateSavedViewInput, SavedView, SavedViewMutationResponse, UpdateSavedViewInput};
+pub use search::{DiarySearchHit, SearchResults, SearchType, ShiftSearchHit, UserSearchHit};
+pub use shift::{
+    DuplicateShift, DuplicateShiftPolicy, Shift, ShiftSeriesResponse, ShiftTemplate, TemplateMonthUsage, TemplateUsage,
+};
+pub use shift_input::{CreateShiftInput, CreateShiftSeriesInput, ShiftMutationResponse, UpdateShiftInput};
+pub use shift_stats::{RoleMonthStats, RoleMonthStatsReport, StatsFreshness, StatsViewRefresh, UserMonthStats, UserMonthStatsReport};
+pub use template_input::{
+    AutoFillAssignment, AutoFillInput, AutoFillResponse, AutoFillUnfilled, CreateTemplateInput, TemplateMutationResponse,
+    UpdateTemplateInput,
+};
 pub use time_off::TimeOffCategory;
-pub use user::{StaffFilterOption, User, UserRole};
+pub use user::{
+    OrphanedClerkUser, PrunedRollbackFailures, SortDirection, StaffFilterOption, UnlinkedProfile, UnlinkedProfileAction, User, UserExportRow, UserRole, UserSort,
+};
 pub use user_input::{
     ChangeOwnPinInput, ChangePasswordInput, ChangeProfilePinRequest, CheckEmailRequest, CheckEmailResponse,
-    CreateLoginInput, CreateLoginResponse, CreateUserProfileRequest, PinResponse, SearchUsersRequest, SuccessResponse,
+    ConfirmEmailVerificationRequest, CreateLoginInput, CreateLoginResponse, CreateUserProfileRequest, ImportRowError,
+    ImportUsersResponse, ImportedUser, PinResponse,
+    RequestEmailVerificationRequest, RequestEmailVerificationResponse, SearchUsersRequest, SuccessResponse,
     UpdateOwnProfileInput, UpdateUserProfileInput, VerifyIdentityRequest, VerifyIdentityResponse,
 };
-pub use user_role_input::{CreateUserRoleInput, UpdateUserRoleInput, UserRoleMutationResponse};
+pub use user_role_input::{
+    BulkUpdateUserRolesInput, BulkUpdateUserRolesResponse, CreateUserRoleInput, PermissionChange, PermissionFlagsPatch,
+    UpdateUserRoleInput, UserRoleMutationResponse, UserRolePermissionChanges,
+};
@@ -0,0 +1,61 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+
+/// In-app notification for a user
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct Notification {
+    pub id: i64,
+    pub user_profile_id: i32,
+    pub kind: String,
+    pub message: String,
+    #[schema(value_type = Object)]
+    pub payload: serde_json::Value,
+    #[serde(serialize_with = "serialize_naive_as_utc")]
+    pub created_at: NaiveDateTime,
+    #[serde(serialize_with = "serialize_option_naive_as_utc")]
+    pub read_at: Option<NaiveDateTime>,
+}
+
+/// Whether one notification kind is delivered to the caller
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct NotificationPreference {
+    /// e.g. SHIFT_CHANGED; see GET /api/notifications/preferences for every kind
+    pub kind: String,
+    pub enabled: bool,
+}
+
+/// Kinds to switch on or off; kinds left out keep their current setting
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct UpdateNotificationPreferencesInput {
+    pub preferences: Vec<NotificationPreference>,
+}
+
+/// One field of a shift before and after an admin's change, in a SHIFT_* notification's payload
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct ShiftFieldChange {
+    /// date, start, end, label, role or user_profile_id
+    pub field: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+fn serialize_naive_as_utc<S>(dt: &NaiveDateTime, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    use chrono::SecondsFormat;
+    let utc_dt = DateTime::<Utc>::from_naive_utc_and_offset(*dt, Utc);
+    utc_dt.to_rfc3339_opts(SecondsFormat::Millis, true).serialize(serializer)
+}
+
+fn serialize_option_naive_as_utc<S>(dt: &Option<NaiveDateTime>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    match dt {
+        Some(dt) => serialize_naive_as_utc(dt, serializer),
+        None => serializer.serialize_none(),
+    }
+}
@@ -0,0 +1,34 @@
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+
+/// A template placed on a weekday within a rota pattern
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct RotaPatternEntry {
+    /// ISO weekday: 1 = Monday ... 7 = Sunday
+    pub weekday: i16,
+    pub template_id: i32,
+    #[sqlx(default)]
+    pub template_label: Option<String>, // From JOIN with ShiftTemplates
+}
+
+/// A reusable week of shift templates for a role (e.g. Mon-Fri Day, nightly Night)
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RotaPattern {
+    pub id: i32,
+    pub role_id: i32,
+    pub name: String,
+    #[serde(serialize_with = "serialize_naive_as_utc")]
+    pub created_at: NaiveDateTime,
+    pub entries: Vec<RotaPatternEntry>,
+}
+
+fn serialize_naive_as_utc<S>(dt: &NaiveDateTime, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    use chrono::SecondsFormat;
+    let utc_dt = DateTime::<Utc>::from_naive_utc_and_offset(*dt, Utc);
+    utc_dt.to_rfc3339_opts(SecondsFormat::Millis, true).serialize(serializer)
+}
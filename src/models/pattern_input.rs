@@ -0,0 +1,80 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::shift::{DuplicateShift, DuplicateShiftPolicy, Shift};
+
+/// A weekday/template pair in a pattern input
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PatternEntryInput {
+    /// ISO weekday: 1 = Monday ... 7 = Sunday
+    pub weekday: i16,
+    pub template_id: i32,
+}
+
+/// Input for creating a rota pattern
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CreatePatternInput {
+    pub role_id: i32,
+    pub name: String,
+    pub entries: Vec<PatternEntryInput>,
+}
+
+/// Input for updating a rota pattern; `entries` replaces the whole week when given
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UpdatePatternInput {
+    pub name: Option<String>,
+    pub entries: Option<Vec<PatternEntryInput>>,
+}
+
+/// Input for rolling a pattern across a month
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApplyPatternInput {
+    pub year: i32,
+    pub month: u32,
+    /// Publish the generated shifts immediately (default false)
+    #[serde(default)]
+    pub published: bool,
+    /// Overrides the workplace's duplicate_shifts setting for this request
+    pub on_duplicate: Option<DuplicateShiftPolicy>,
+    /// Preview only: return the shifts that would be created and the duplicates found, writing nothing
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Result of applying a pattern; with the SKIP policy, days that already have the template's shift are skipped
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ApplyPatternResponse {
+    pub success: bool,
+    pub dry_run: bool,
+    /// Shifts created (or that would be, with dry_run)
+    pub created: usize,
+    pub skipped: usize,
+    /// Existing shifts that were skipped as duplicates; with dry_run and REJECT, the conflicts that would fail the apply
+    pub duplicates: Vec<DuplicateShift>,
+    /// Series formed by a template falling on consecutive days (e.g. a week of nights)
+    pub series: Vec<Uuid>,
+    /// Created shifts whose template has preferred staff; POST /api/templates/auto-fill assigns them
+    pub suggestions: Vec<AssignmentSuggestion>,
+    /// The shifts that would be created (dry_run only; their uuids are not kept)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub preview: Vec<Shift>,
+}
+
+/// A shift created from a template with preferred staff, and who to offer it to
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AssignmentSuggestion {
+    pub shift_uuid: Uuid,
+    pub date: NaiveDate,
+    pub template_id: i32,
+    /// Most preferred first
+    pub preferred_user_ids: Vec<i32>,
+}
+
+/// Response for pattern mutations
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PatternMutationResponse {
+    pub success: bool,
+    pub message: Option<String>,
+}
@@ -0,0 +1,83 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Pay enhancements for a role, as a percentage on top of a shift's money_per_hour. Where several apply to the
+/// same hour (a Saturday night), the highest one is paid; they do not stack.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct RolePayRules {
+    pub role_id: i32,
+    pub night_percent: f32,
+    pub weekend_percent: f32,
+    pub bank_holiday_percent: f32,
+    pub night_start: String,  // HH:MM:SS; night runs across midnight when night_end is earlier
+    pub night_end: String,    // HH:MM:SS
+    pub bank_holidays: Vec<NaiveDate>,
+}
+
+impl RolePayRules {
+    pub const MAX_PERCENT: f32 = 500.0;
+    pub const DEFAULT_NIGHT_START: &'static str = "20:00:00";
+    pub const DEFAULT_NIGHT_END: &'static str = "07:00:00";
+
+    pub fn defaults(role_id: i32) -> Self {
+        Self {
+            role_id,
+            night_percent: 0.0,
+            weekend_percent: 0.0,
+            bank_holiday_percent: 0.0,
+            night_start: Self::DEFAULT_NIGHT_START.to_string(),
+            night_end: Self::DEFAULT_NIGHT_END.to_string(),
+            bank_holidays: Vec::new(),
+        }
+    }
+}
+
+/// Input for updating a role's pay rules (omitted fields keep their current value)
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UpdatePayRulesInput {
+    pub night_percent: Option<f32>,  // 0-500
+    pub weekend_percent: Option<f32>,
+    pub bank_holiday_percent: Option<f32>,
+    pub night_start: Option<String>,  // HH:MM or HH:MM:SS
+    pub night_end: Option<String>,
+    /// Replaces the whole list
+    pub bank_holidays: Option<Vec<NaiveDate>>,
+}
+
+/// A shift's pay under the role's rules
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ShiftCost {
+    pub shift_uuid: Uuid,
+    pub date: NaiveDate,
+    pub label: String,
+    pub start: Option<String>,
+    pub end: Option<String>,
+    pub user_profile_id: Option<i32>,
+    pub is_locum: bool,
+    pub money_per_hour: Option<f32>,
+    pub hours: f64,
+    /// Hours paid at an enhanced rate
+    pub enhanced_hours: f64,
+    pub base: f64,
+    pub enhancement: f64,
+    pub total: f64,
+}
+
+/// Forecast pay for a role's month; shifts without times or money_per_hour are counted but not priced
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RoleCostReport {
+    pub role_id: i32,
+    pub year: i32,
+    pub month: i32,
+    pub locum_only: bool,
+    pub priced_shifts: i64,
+    pub unpriced_shifts: i64,
+    pub base_total: f64,
+    pub enhancement_total: f64,
+    pub total: f64,
+    pub locum_total: f64,
+    pub shifts: Vec<ShiftCost>,
+}
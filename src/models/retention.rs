@@ -0,0 +1,26 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Outcome of one retention rule
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RetentionRuleResult {
+    /// e.g. "deleted_diary_entries"
+    pub rule: String,
+    /// "purge" (rows deleted) or "anonymise" (actor ids cleared)
+    pub action: String,
+    pub retention_days: u32,
+    /// Rows older than this date are affected
+    pub cutoff: NaiveDate,
+    /// Rows affected, or that would be affected on a dry run
+    pub rows: i64,
+}
+
+/// What a retention run did (or, for a dry run, would do)
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RetentionReport {
+    pub dry_run: bool,
+    pub generated_at: NaiveDateTime,
+    /// Enabled rules only; a policy set to 0 days is skipped
+    pub rules: Vec<RetentionRuleResult>,
+}
@@ -1,6 +1,10 @@
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::shift::DuplicateShiftPolicy;
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct Workplace {
@@ -11,6 +15,104 @@ pub struct Workplace {
     pub code: Option<String>,
 }
 
+/// Per-workplace settings; defaults apply when the workplace has no settings row
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct WorkplaceSettings {
+    pub workplace_id: i32,
+    pub default_shift_start: Option<String>,  // HH:MM:SS, used when a shift is created without times
+    pub default_shift_end: Option<String>,    // HH:MM:SS
+    pub marketplace_enabled: bool,
+    pub pin_length: i16,                      // Exact number of digits required for user PINs
+    pub pin_require_complex: bool,            // Reject repeated digits (11111) and runs (12345, 54321)
+    pub pin_expiry_days: i16,                 // PINs must be changed after this many days (0 = never)
+    pub cover_window_days: i16,               // Unassigned published shifts this many days ahead show on the cover board
+    pub duplicate_shifts: String,             // REJECT | SKIP | ALLOW, when a new shift matches an existing one
+    pub geofence_latitude: Option<f64>,       // Centre of the check-in geofence
+    pub geofence_longitude: Option<f64>,
+    pub geofence_radius_m: i32,               // Check-ins must be within this many metres of the centre (0 = off)
+}
+
+impl WorkplaceSettings {
+    pub const DEFAULT_PIN_LENGTH: i16 = 5;
+    pub const MIN_PIN_LENGTH: i16 = 4;
+    pub const MAX_PIN_LENGTH: i16 = 8;
+    pub const MAX_PIN_EXPIRY_DAYS: i16 = 365;
+    pub const DEFAULT_COVER_WINDOW_DAYS: i16 = 7;
+    pub const MAX_COVER_WINDOW_DAYS: i16 = 90;
+    pub const MAX_GEOFENCE_RADIUS_M: i32 = 10_000;
+
+    pub fn defaults(workplace_id: i32) -> Self {
+        Self {
+            workplace_id,
+            default_shift_start: None,
+            default_shift_end: None,
+            marketplace_enabled: true,
+            pin_length: Self::DEFAULT_PIN_LENGTH,
+            pin_require_complex: false,
+            pin_expiry_days: 0,
+            cover_window_days: Self::DEFAULT_COVER_WINDOW_DAYS,
+            duplicate_shifts: DuplicateShiftPolicy::Allow.as_str().to_string(),
+            geofence_latitude: None,
+            geofence_longitude: None,
+            geofence_radius_m: 0,
+        }
+    }
+
+    pub fn duplicate_shift_policy(&self) -> DuplicateShiftPolicy {
+        self.duplicate_shifts.parse().unwrap_or(DuplicateShiftPolicy::Allow)
+    }
+}
+
+/// PIN rules for a user: the strictest settings across the workplaces they have roles in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct PinPolicy {
+    pub length: i16,
+    pub require_complex: bool,
+    pub expiry_days: i16,  // 0 = PINs never expire
+}
+
+impl Default for PinPolicy {
+    /// Applies to users without any role yet
+    fn default() -> Self {
+        Self {
+            length: WorkplaceSettings::DEFAULT_PIN_LENGTH,
+            require_complex: false,
+            expiry_days: 0,
+        }
+    }
+}
+
+impl PinPolicy {
+    /// Check a new PIN: exact length, digits only, and not trivially guessable when complexity is required
+    pub fn validate(&self, pin: &str) -> Result<(), String> {
+        if pin.len() != self.length as usize || !pin.chars().all(|c| c.is_ascii_digit()) {
+            return Err(format!("PIN must be exactly {} digits", self.length));
+        }
+        if self.require_complex && is_trivial_pin(pin) {
+            return Err("PIN must not be a repeated digit or a run like 12345".to_string());
+        }
+        Ok(())
+    }
+
+    /// Whether a PIN set at `changed_at` has outlived the expiry period (PINs of unknown age count as expired)
+    pub fn is_expired(&self, changed_at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> bool {
+        if self.expiry_days <= 0 {
+            return false;
+        }
+        match changed_at {
+            Some(changed_at) => now - changed_at >= chrono::Duration::days(i64::from(self.expiry_days)),
+            None => true,
+        }
+    }
+}
+
+/// All one digit, or each digit one more (or one less) than the previous
+fn is_trivial_pin(pin: &str) -> bool {
+    let digits: Vec<i8> = pin.bytes().map(|b| (b - b'0') as i8).collect();
+    let steps: Vec<i8> = digits.windows(2).map(|w| w[1] - w[0]).collect();
+    steps.iter().all(|&s| s == 0) || steps.iter().all(|&s| s == 1) || steps.iter().all(|&s| s == -1)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Role {
     pub id: i32,
@@ -18,6 +120,114 @@ pub struct Role {
     pub role_name: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub marketplace_auto_approve: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub publish_requires_approval: Option<bool>,  // Months must be approved (can_approve_rota) before publishing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_swaps_per_month: Option<i16>,  // Approved marketplace requests per member per month before an admin override is needed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub edit_reason_required: Option<bool>,  // Editing or deleting a published shift needs a reason for the audit trail
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archived: Option<bool>,  // Hidden from GET /api/roles unless includeArchived=true
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_staff: Option<i64>,  // Users who can work shifts in the role (generic logins excluded)
     #[serde(rename = "Workplaces")]
     pub workplaces: Option<Workplace>,
 }
+
+/// Rota figures for one role and month; time-off entries are counted separately from shifts
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct RoleStats {
+    pub role_id: i32,
+    pub year: i32,
+    pub month: i32,
+    pub shift_count: i64,
+    /// Shifts with someone assigned
+    pub filled: i64,
+    pub unfilled: i64,
+    pub locum_shifts: i64,
+    /// Locum shifts as a fraction of all shifts (0 when there are none)
+    pub locum_proportion: f64,
+    pub total_pa: f64,
+    /// Staff assigned to at least one shift
+    pub distinct_staff: i64,
+    pub time_off_entries: i64,
+}
+
+/// Why a shift's assignee does not pass the role check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AssignmentIssue {
+    /// The user has no "UserRoles" row for the role
+    NoUserRole,
+    /// The user holds the role without can_work_shifts
+    CannotWorkShifts,
+}
+
+/// Fix offered for shifts assigned to a user without the role
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum AssignmentDriftFix {
+    /// Leave the shifts unfilled (can_edit_rota)
+    Unassign,
+    /// Give the user the role with can_work_shifts (can_edit_staff; not for generic logins)
+    Regrant,
+}
+
+/// A shift whose assignee does not hold the role
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DriftShift {
+    pub uuid: Uuid,
+    pub date: NaiveDate,
+    pub label: String,
+    pub published: bool,
+}
+
+/// One user's shifts in a role they no longer hold
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AssignmentDrift {
+    pub role_id: i32,
+    pub role_name: String,
+    pub user_profile_id: i32,
+    pub short_name: String,
+    pub issue: AssignmentIssue,
+    pub shifts: Vec<DriftShift>,
+    pub actions: Vec<AssignmentDriftFix>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pin_policy_validate() {
+        let basic = PinPolicy::default();
+        assert!(basic.validate("12345").is_ok());
+        assert!(basic.validate("1234").is_err());
+        assert!(basic.validate("12a45").is_err());
+
+        let strict = PinPolicy { length: 6, require_complex: true, expiry_days: 0 };
+        assert!(strict.validate("000000").is_err());
+        assert!(strict.validate("345678").is_err());
+        assert!(strict.validate("987654").is_err());
+        assert!(strict.validate("135790").is_ok());
+    }
+
+    #[test]
+    fn test_duplicate_shifts_default_to_allow() {
+        assert_eq!(WorkplaceSettings::defaults(1).duplicate_shift_policy(), DuplicateShiftPolicy::Allow);
+        let settings = WorkplaceSettings { duplicate_shifts: "SKIP".to_string(), ..WorkplaceSettings::defaults(1) };
+        assert_eq!(settings.duplicate_shift_policy(), DuplicateShiftPolicy::Skip);
+        let unknown = WorkplaceSettings { duplicate_shifts: "?".to_string(), ..WorkplaceSettings::defaults(1) };
+        assert_eq!(unknown.duplicate_shift_policy(), DuplicateShiftPolicy::Allow);
+    }
+
+    #[test]
+    fn test_pin_policy_expiry() {
+        let now = Utc::now();
+        let policy = PinPolicy { expiry_days: 90, ..PinPolicy::default() };
+        assert!(!policy.is_expired(Some(now - chrono::Duration::days(89)), now));
+        assert!(policy.is_expired(Some(now - chrono::Duration::days(90)), now));
+        assert!(policy.is_expired(None, now));
+        assert!(!PinPolicy::default().is_expired(None, now));
+    }
+}
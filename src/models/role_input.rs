@@ -1,7 +1,11 @@
 use serde::{Deserialize, Serialize};
 
+use chrono::NaiveDate;
 use utoipa::ToSchema;
 
+use super::role::AssignmentDriftFix;
+use super::shift::DuplicateShiftPolicy;
+
 /// Input for creating a role
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct CreateRoleInput {
@@ -9,6 +13,12 @@ pub struct CreateRoleInput {
     pub role_name: String,
     #[serde(default)]
     pub marketplace_auto_approve: Option<bool>,
+    #[serde(default)]
+    pub publish_requires_approval: Option<bool>,
+    #[serde(default)]
+    pub max_swaps_per_month: Option<i16>,  // None or 0 means no cap
+    #[serde(default)]
+    pub edit_reason_required: Option<bool>,
 }
 
 /// Input for updating a role
@@ -17,6 +27,32 @@ pub struct UpdateRoleInput {
     pub workplace_id: Option<i32>,
     pub role_name: Option<String>,
     pub marketplace_auto_approve: Option<bool>,
+    pub publish_requires_approval: Option<bool>,
+    pub max_swaps_per_month: Option<i16>,  // 0 removes the cap
+    pub edit_reason_required: Option<bool>,
+    pub archived: Option<bool>,
+}
+
+/// Input for issuing a rota display token for a role
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CreateDisplayTokenInput {
+    pub valid_days: Option<i64>,  // Default 90, max 365
+}
+
+/// Input for submitting a month of a role's rota for publish approval
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SubmitRotaApprovalInput {
+    pub role_id: i32,
+    pub year: i32,
+    pub month: i32,
+    pub comment: Option<String>,
+}
+
+/// Approver's decision on a publish approval request
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RotaApprovalDecisionInput {
+    pub approve: bool,
+    pub comment: Option<String>,  // Required when rejecting
 }
 
 /// Response for role mutations
@@ -44,6 +80,22 @@ pub struct UpdateWorkplaceInput {
     pub code: Option<String>,
 }
 
+/// Input for updating workplace settings (omitted fields keep their current value)
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UpdateWorkplaceSettingsInput {
+    pub default_shift_start: Option<String>,  // HH:MM or HH:MM:SS, empty string clears
+    pub default_shift_end: Option<String>,    // HH:MM or HH:MM:SS, empty string clears
+    pub marketplace_enabled: Option<bool>,
+    pub pin_length: Option<i16>,
+    pub pin_require_complex: Option<bool>,
+    pub pin_expiry_days: Option<i16>,  // 0 turns expiry off
+    pub cover_window_days: Option<i16>,  // 0 lists flagged shifts only
+    pub duplicate_shifts: Option<DuplicateShiftPolicy>,
+    pub geofence_latitude: Option<f64>,  // -90 to 90
+    pub geofence_longitude: Option<f64>,  // -180 to 180
+    pub geofence_radius_m: Option<i32>,  // 0 turns the geofence off
+}
+
 /// Response for workplace mutations
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct WorkplaceMutationResponse {
@@ -65,3 +117,23 @@ pub struct DependencyCount {
     pub cod_entries: i32,
     pub unique_staff: i32,
 }
+
+/// Input for fixing shifts assigned to a user without the role
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FixAssignmentDriftInput {
+    pub user_profile_id: i32,
+    pub action: AssignmentDriftFix,
+    /// UNASSIGN only: first shift date to clear (default today; earlier shifts are history)
+    pub from: Option<NaiveDate>,
+}
+
+/// Result of an assignment drift fix
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct FixAssignmentDriftResponse {
+    pub success: bool,
+    pub action: AssignmentDriftFix,
+    /// Shifts left unfilled (UNASSIGN)
+    pub unassigned: u64,
+    /// The granted or updated "UserRoles" row (REGRANT)
+    pub user_role_id: Option<i32>,
+}
@@ -0,0 +1,131 @@
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// A published shift as captured in a daily rota snapshot
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SnapshotShift {
+    pub uuid: Uuid,
+    pub date: NaiveDate,
+    pub label: String,
+    pub start: Option<String>,  // HH:MM:SS format
+    pub end: Option<String>,    // HH:MM:SS format
+    pub user_profile_id: Option<i32>,
+}
+
+/// A shift present in both snapshots whose assignment, date or times changed
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct MovedAssignment {
+    pub uuid: Uuid,
+    pub before: SnapshotShift,
+    pub after: SnapshotShift,
+}
+
+/// Differences between two rota snapshots for a role
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RotaDiff {
+    pub role_id: i32,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    #[serde(serialize_with = "serialize_naive_as_utc")]
+    pub from_taken_at: NaiveDateTime,
+    #[serde(serialize_with = "serialize_naive_as_utc")]
+    pub to_taken_at: NaiveDateTime,
+    pub added: Vec<SnapshotShift>,
+    pub removed: Vec<SnapshotShift>,
+    pub moved: Vec<MovedAssignment>,
+}
+
+/// A published shift as shown on a wall display (no contact details, no time off)
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct DisplayShift {
+    pub date: NaiveDate,
+    pub label: String,
+    pub start: Option<String>,  // HH:MM format
+    pub end: Option<String>,    // HH:MM format
+    pub font_color: String,
+    pub bk_color: String,
+    pub staff_name: Option<String>,
+    pub staff_short_name: Option<String>,
+    pub duration_minutes: Option<i32>,
+    pub crosses_midnight: bool,
+}
+
+/// Read-only rota for a role, served to display-token holders
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DisplayRota {
+    pub role_id: i32,
+    pub role_name: String,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub shifts: Vec<DisplayShift>,
+}
+
+/// One role's shifts in a multi-role rota response
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RoleRota {
+    pub role_id: i32,
+    pub role_name: String,
+    pub shifts: Vec<super::Shift>,
+}
+
+/// Newly issued display token for a role
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct DisplayTokenResponse {
+    pub role_id: i32,
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// A month of a role's rota submitted for publish sign-off
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct RotaPublishApproval {
+    pub id: i32,
+    pub role_id: i32,
+    pub year: i32,
+    pub month: i32,
+    pub status: String,  // PENDING | APPROVED | REJECTED
+    pub submitted_by: i32,
+    pub submitted_by_name: Option<String>,
+    #[serde(serialize_with = "serialize_naive_as_utc")]
+    pub submitted_at: NaiveDateTime,
+    pub submit_comment: Option<String>,
+    pub decided_by: Option<i32>,
+    pub decided_by_name: Option<String>,
+    #[serde(serialize_with = "serialize_option_naive_as_utc")]
+    pub decided_at: Option<NaiveDateTime>,
+    pub decision_comment: Option<String>,
+}
+
+/// A role's month frozen after payroll: its shifts cannot be created, edited, deleted or swapped
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct RotaLock {
+    pub role_id: i32,
+    pub year: i32,
+    pub month: i32,
+    pub locked_by: i32,
+    pub locked_by_name: Option<String>,
+    #[serde(serialize_with = "serialize_naive_as_utc")]
+    pub locked_at: NaiveDateTime,
+}
+
+fn serialize_naive_as_utc<S>(dt: &NaiveDateTime, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    use chrono::SecondsFormat;
+    let utc_dt = DateTime::<Utc>::from_naive_utc_and_offset(*dt, Utc);
+    utc_dt.to_rfc3339_opts(SecondsFormat::Millis, true).serialize(serializer)
+}
+
+fn serialize_option_naive_as_utc<S>(dt: &Option<NaiveDateTime>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    match dt {
+        Some(dt) => serialize_naive_as_utc(dt, serializer),
+        None => serializer.serialize_none(),
+    }
+}
@@ -0,0 +1,54 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+/// Named rota filter set kept server-side so it follows the user across devices
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct SavedView {
+    pub id: i32,
+    pub user_profile_id: i32,
+    pub owner_short_name: Option<String>, // From JOIN with Users
+    pub name: String,
+    pub view_type: String,          // DAY | WEEK | MONTH | LIST
+    pub role_id: Option<i32>,       // None = all of the user's roles
+    pub staff_ids: Vec<i32>,        // Empty = everyone in the role
+    /// Colleagues open the view with GET /api/users/me/saved-views/shared/{token}; None while not shared
+    pub share_token: Option<Uuid>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+/// Input for saving a view
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CreateSavedViewInput {
+    pub name: String,
+    pub view_type: String,  // DAY | WEEK | MONTH | LIST
+    pub role_id: Option<i32>,
+    #[serde(default)]
+    pub staff_ids: Vec<i32>,
+}
+
+/// Input for updating a saved view (omitted fields keep their current value; role_id 0 clears the role)
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UpdateSavedViewInput {
+    pub name: Option<String>,
+    pub view_type: Option<String>,
+    pub role_id: Option<i32>,
+    pub staff_ids: Option<Vec<i32>>,
+}
+
+/// Input for copying a colleague's shared view into your own
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CopySavedViewInput {
+    /// Name for the copy (defaults to the shared view's name)
+    pub name: Option<String>,
+}
+
+/// Response for saved view deletion
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SavedViewMutationResponse {
+    pub success: bool,
+    pub message: Option<String>,
+}
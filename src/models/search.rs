@@ -0,0 +1,60 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::role::Workplace;
+
+/// Result bucket a search can be narrowed to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchType {
+    Users,
+    Shifts,
+    Diary,
+    Workplaces,
+}
+
+/// A member of staff sharing a role with the caller
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct UserSearchHit {
+    pub user_profile_id: i32,
+    pub full_name: String,
+    pub short_name: String,
+    pub color: Option<String>,
+    /// Only present with can_view_staff_details in a shared role (and for the caller themselves)
+    pub primary_email: Option<String>,
+}
+
+/// A shift whose label matched; unpublished shifts only for rota editors
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct ShiftSearchHit {
+    pub uuid: Uuid,
+    pub role_id: i32,
+    pub role_name: String,
+    pub date: NaiveDate,
+    pub label: String,
+    pub user_profile_id: Option<i32>,
+    pub user_short_name: Option<String>,
+}
+
+/// A diary entry from a role the caller has can_access_diary in
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct DiarySearchHit {
+    pub id: i32,
+    pub role_id: i32,
+    pub role_name: String,
+    pub date: NaiveDate,
+    pub entry: Option<String>,
+    pub user_short_name: Option<String>,
+}
+
+/// Global search results, one bucket per type (empty when the type was not searched)
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct SearchResults {
+    pub users: Vec<UserSearchHit>,
+    pub shifts: Vec<ShiftSearchHit>,
+    pub diary: Vec<DiarySearchHit>,
+    pub workplaces: Vec<Workplace>,
+}
@@ -1,9 +1,54 @@
-use chrono::{NaiveDate, NaiveDateTime};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
 use utoipa::ToSchema;
 
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use std::str::FromStr;
 use uuid::Uuid;
+
+/// What to do when a new shift matches an existing one on role, date, label, start, end and assignee
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum DuplicateShiftPolicy {
+    /// Fail with 409 and the matching shifts; nothing is created
+    Reject,
+    /// Leave the existing shift in place and report it
+    Skip,
+    /// Create the shift anyway
+    Allow,
+}
+
+impl DuplicateShiftPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DuplicateShiftPolicy::Reject => "REJECT",
+            DuplicateShiftPolicy::Skip => "SKIP",
+            DuplicateShiftPolicy::Allow => "ALLOW",
+        }
+    }
+}
+
+impl FromStr for DuplicateShiftPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "REJECT" => Ok(DuplicateShiftPolicy::Reject),
+            "SKIP" => Ok(DuplicateShiftPolicy::Skip),
+            "ALLOW" => Ok(DuplicateShiftPolicy::Allow),
+            other => Err(format!("Unknown duplicate shift policy: {}", other)),
+        }
+    }
+}
+
+/// An existing shift that a new one duplicated
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct DuplicateShift {
+    pub date: NaiveDate,
+    pub label: String,
+    pub existing_uuid: Uuid,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct Shift {
     pub uuid: Uuid,
@@ -29,7 +74,61 @@ pub struct Shift {
     pub time_off: Option<i32>,
     pub user_profile_id: Option<i32>,
     pub created_by: i32,
+    /// Length in minutes (None for shifts without both times, e.g. time off)
+    pub duration_minutes: Option<i32>,
+    /// End is at or before start, so the shift finishes the next day
+    pub crosses_midnight: bool,
+    /// Shared by the shifts of one multi-day block (e.g. a week of nights); None for a standalone shift
+    pub series_id: Option<Uuid>,
+    /// Latest unresolved marketplace request offering or targeting this shift; only with `include=marketplace`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[sqlx(default)]
+    pub marketplace_request_id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[sqlx(default)]
+    pub marketplace_request_type: Option<crate::models::ShiftRequestType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[sqlx(default)]
+    pub marketplace_request_status: Option<crate::models::ShiftRequestStatus>,
 }
+
+/// Shift.marketplace_* keys, kept alongside a `?fields=` projection when `include=marketplace`
+pub const MARKETPLACE_FIELDS: &[&str] = &["marketplace_request_id", "marketplace_request_type", "marketplace_request_status"];
+
+/// Select list for the Shift.marketplace_* columns, from MARKETPLACE_LATERAL_SQL
+pub const MARKETPLACE_COLUMNS_SQL: &str = r#"mr.id AS marketplace_request_id,
+    mr."type" AS marketplace_request_type,
+    mr.status AS marketplace_request_status"#;
+
+/// Joined after FROM "Shifts": the latest unresolved request where the shift is offered or is the swap target
+pub const MARKETPLACE_LATERAL_SQL: &str = r#" LEFT JOIN LATERAL (
+        SELECT sr.id, sr."type", sr.status
+        FROM "ShiftRequests" sr
+        WHERE (sr.shift_id = "Shifts".uuid OR sr.target_shift_id = "Shifts".uuid)
+          AND sr.status NOT IN ('APPROVED', 'REJECTED', 'CANCELLED')
+        ORDER BY sr.created_at DESC, sr.id DESC
+        LIMIT 1
+    ) mr ON TRUE"#;
+
+/// SQL for Shift.duration_minutes: an end at or before the start is taken as the next day
+pub const DURATION_MINUTES_SQL: &str = r#"CASE WHEN start IS NULL OR "end" IS NULL THEN NULL
+    ELSE (EXTRACT(EPOCH FROM ("end" - start)) / 60)::int4 + CASE WHEN "end" <= start THEN 1440 ELSE 0 END
+    END AS duration_minutes"#;
+
+/// SQL for Shift.crosses_midnight, matching DURATION_MINUTES_SQL
+pub const CROSSES_MIDNIGHT_SQL: &str = r#"COALESCE("end" <= start, false) AS crosses_midnight"#;
+
+/// Shift.duration_minutes for times already in hand, as DURATION_MINUTES_SQL computes it
+pub fn duration_minutes(start: NaiveTime, end: NaiveTime) -> i32 {
+    let minutes = (end - start).num_minutes() as i32;
+    if crosses_midnight(start, end) { minutes + 24 * 60 } else { minutes }
+}
+
+/// Shift.crosses_midnight for times already in hand, as CROSSES_MIDNIGHT_SQL computes it
+pub fn crosses_midnight(start: NaiveTime, end: NaiveTime) -> bool {
+    end <= start
+}
+
 fn serialize_naive_as_utc<S>(dt: &NaiveDateTime, serializer: S) -> Result<S::Ok, S::Error>
 where
     S: serde::Serializer,
@@ -39,6 +138,18 @@ where
     utc_dt.to_rfc3339_opts(SecondsFormat::Millis, true).serialize(serializer)
 }
 
+/// Shifts created together as one series
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ShiftSeriesResponse {
+    pub dry_run: bool,
+    pub series_id: Uuid,
+    /// Shifts created, or with dry_run those that would be (their uuids and series_id are not kept)
+    pub shifts: Vec<Shift>,
+    /// Existing shifts that days of the series duplicated (SKIP), so those days were not created; with dry_run
+    /// and REJECT, the conflicts that would fail the request
+    pub duplicates: Vec<DuplicateShift>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct ShiftTemplate {
     pub id: i32,
@@ -54,4 +165,59 @@ pub struct ShiftTemplate {
     pub money_per_hour: Option<f32>,
     pub is_spa: bool,
     pub is_dcc: bool,
+    /// Staff to suggest for shifts from this template, most preferred first
+    pub preferred_user_ids: Vec<i32>,
+}
+
+/// Shifts matching a template in one calendar month
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct TemplateMonthUsage {
+    pub year: i32,
+    pub month: i32,
+    pub shifts: i64,
+    pub published_shifts: i64,
+}
+
+/// How often a template is used. Shifts do not record their template, so a shift counts when
+/// it has the template's role, label, start and end.
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TemplateUsage {
+    pub template_id: i32,
+    pub total_shifts: i64,
+    pub last_used: Option<NaiveDate>,
+    /// Newest month first
+    pub months: Vec<TemplateMonthUsage>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(hhmm: &str) -> NaiveTime {
+        NaiveTime::parse_from_str(hhmm, "%H:%M").unwrap()
+    }
+
+    #[test]
+    fn test_duration_minutes() {
+        assert_eq!(duration_minutes(time("08:00"), time("17:30")), 570);
+        assert_eq!(duration_minutes(time("20:00"), time("08:00")), 720);
+        assert_eq!(duration_minutes(time("00:00"), time("00:00")), 1440);
+        assert_eq!(duration_minutes(time("23:45"), time("00:15")), 30);
+    }
+
+    #[test]
+    fn test_crosses_midnight() {
+        assert!(!crosses_midnight(time("08:00"), time("17:30")));
+        assert!(crosses_midnight(time("20:00"), time("08:00")));
+        assert!(crosses_midnight(time("09:00"), time("09:00")));
+        assert!(!crosses_midnight(time("00:00"), time("23:59")));
+    }
+
+    #[test]
+    fn test_computed_column_sql_aliases() {
+        // Shift decodes these by name, so the expressions must keep their aliases
+        assert!(DURATION_MINUTES_SQL.ends_with("AS duration_minutes"));
+        assert!(CROSSES_MIDNIGHT_SQL.ends_with("AS crosses_midnight"));
+        assert!(DURATION_MINUTES_SQL.contains(r#""end" <= start"#) && CROSSES_MIDNIGHT_SQL.contains(r#""end" <= start"#));
+    }
 }
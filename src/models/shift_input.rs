@@ -4,6 +4,8 @@ use utoipa::ToSchema;
 
 use uuid::Uuid;
 
+use super::shift::DuplicateShiftPolicy;
+
 /// Input DTO for creating a new shift
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct CreateShiftInput {
@@ -24,6 +26,20 @@ pub struct CreateShiftInput {
     pub time_off: Option<i32>,
     pub user_profile_id: Option<i32>,
     pub created_by: Option<i32>, // Optional - will default to authenticated user
+    /// Overrides the workplace's duplicate_shifts setting for this request
+    pub on_duplicate: Option<DuplicateShiftPolicy>,
+}
+
+/// Input DTO for creating the same shift on consecutive days, starting on `date`
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct CreateShiftSeriesInput {
+    #[serde(flatten)]
+    pub shift: CreateShiftInput,
+    /// Number of consecutive days, 2 to 31
+    pub days: u32,
+    /// Preview only: return the shifts that would be created and the duplicates found, writing nothing
+    #[serde(default)]
+    pub dry_run: bool,
 }
 
 /// Input DTO for updating an existing shift
@@ -45,6 +61,8 @@ pub struct UpdateShiftInput {
     #[serde(rename = "time_off_category")]  // Frontend sends time_off_category
     pub time_off: Option<i32>,
     pub user_profile_id: Option<i32>,
+    /// Why the shift changed, kept in its audit history (required for published shifts in roles with edit_reason_required)
+    pub reason: Option<String>,
 }
 
 /// Response after successful mutation
@@ -0,0 +1,71 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+
+/// How current the precomputed figures are (from "ShiftStatsRefreshes")
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct StatsFreshness {
+    /// None until the view has been refreshed once
+    pub refreshed_at: Option<DateTime<Utc>>,
+    pub age_seconds: Option<i64>,
+    /// Older than two refresh intervals (or never refreshed): the refresh job is probably not running
+    pub stale: bool,
+}
+
+/// PA totals for one user, role and month (from "ShiftStatsUserMonth"); time-off entries are counted separately
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct UserMonthStats {
+    pub user_profile_id: i32,
+    pub short_name: Option<String>,
+    pub role_id: i32,
+    /// First day of the month
+    pub month: NaiveDate,
+    pub shifts: i64,
+    pub locum_shifts: i64,
+    pub total_pa: f64,
+    pub dcc_pa: f64,
+    pub spa_pa: f64,
+    pub time_off_entries: i64,
+}
+
+/// Fill rate for one role and month (from "ShiftStatsRoleMonth")
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct RoleMonthStats {
+    pub role_id: i32,
+    pub role_name: Option<String>,
+    /// First day of the month
+    pub month: NaiveDate,
+    pub shift_count: i64,
+    pub filled: i64,
+    pub unfilled: i64,
+    /// Filled shifts as a fraction of all shifts (0 when there are none)
+    pub fill_rate: f64,
+    pub locum_shifts: i64,
+    pub total_pa: f64,
+    pub distinct_staff: i64,
+    pub time_off_entries: i64,
+}
+
+/// GET /api/shift-stats/users
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UserMonthStatsReport {
+    #[serde(flatten)]
+    pub freshness: StatsFreshness,
+    pub rows: Vec<UserMonthStats>,
+}
+
+/// GET /api/shift-stats/roles
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RoleMonthStatsReport {
+    #[serde(flatten)]
+    pub freshness: StatsFreshness,
+    pub rows: Vec<RoleMonthStats>,
+}
+
+/// One view refreshed by POST /api/shift-stats/refresh
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct StatsViewRefresh {
+    pub view: String,
+    pub duration_ms: i64,
+}
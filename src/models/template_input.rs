@@ -1,6 +1,7 @@
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
-
 use utoipa::ToSchema;
+use uuid::Uuid;
 
 /// Input for creating a shift template
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -15,6 +16,9 @@ pub struct CreateTemplateInput {
     pub bk_color: String,
     pub is_spa: bool,
     pub is_dcc: bool,
+    /// Staff to suggest for the template's shifts, most preferred first; each must work shifts in the role
+    #[serde(default)]
+    pub preferred_user_ids: Vec<i32>,
 }
 
 /// Input for updating a shift template
@@ -30,6 +34,46 @@ pub struct UpdateTemplateInput {
     pub bk_color: Option<String>,
     pub is_spa: Option<bool>,
     pub is_dcc: Option<bool>,
+    /// Replaces the whole list when given
+    pub preferred_user_ids: Option<Vec<i32>>,
+}
+
+/// Input for assigning templates' preferred staff to a month's unassigned shifts
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AutoFillInput {
+    pub role_id: i32,
+    pub year: i32,
+    /// 1-12
+    pub month: i32,
+    /// Report what would be assigned without changing anything (default false)
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// A shift given to one of its template's preferred staff
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct AutoFillAssignment {
+    pub shift_uuid: Uuid,
+    pub date: NaiveDate,
+    pub label: String,
+    pub user_profile_id: i32,
+}
+
+/// A shift none of the preferred staff could take: each already works that day, is on leave or is out of PAs
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ToSchema)]
+pub struct AutoFillUnfilled {
+    pub shift_uuid: Uuid,
+    pub date: NaiveDate,
+    pub label: String,
+}
+
+/// Result of an auto-fill; only unpublished, unassigned shifts matching a template with preferred staff are considered
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct AutoFillResponse {
+    pub success: bool,
+    pub dry_run: bool,
+    pub assigned: Vec<AutoFillAssignment>,
+    pub unfilled: Vec<AutoFillUnfilled>,
 }
 
 /// Response for template mutations
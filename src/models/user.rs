@@ -1,19 +1,25 @@
-use chrono::{DateTime, NaiveDateTime, Utc};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use sqlx::FromRow;
+use std::collections::BTreeMap;
 use utoipa::ToSchema;
 
+use super::custom_field::CustomFieldValue;
 use super::role::Role;
+use crate::db::encrypted::EncryptedString;
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct User {
     pub user_profile_id: i32,
     pub auth_id: String,
     pub full_name: String,
     pub short_name: String,
     pub primary_email: Option<String>,
-    pub secondary_emails: Option<Vec<String>>,
-    pub tel: Option<Vec<String>>,
+    #[schema(value_type = Option<Vec<String>>)]
+    pub secondary_emails: Option<Vec<EncryptedString>>, // Encrypted at rest when PII_ENCRYPTION_KEY is set
+    #[schema(value_type = Option<Vec<String>>)]
+    pub tel: Option<Vec<EncryptedString>>,
     pub gmc: Option<i32>,
     pub auth_pin: Option<String>,
     pub is_super_admin: bool,
@@ -22,6 +28,10 @@ pub struct User {
     pub created_at: NaiveDateTime,
     pub color: Option<String>,
     pub is_generic_login: bool,
+    /// Values for the custom fields of the user's workplaces; only filled by single-user reads and updates
+    #[sqlx(skip)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub custom_fields: Vec<CustomFieldValue>,
 }
 
 fn serialize_naive_as_utc<S>(dt: &NaiveDateTime, serializer: S) -> Result<S::Ok, S::Error>
@@ -45,12 +55,115 @@ pub struct UserRole {
     pub can_edit_templates: bool,
     pub can_edit_staff: bool,
     pub can_view_staff_details: bool,
+    pub can_approve_rota: bool,  // Sign off months for roles with publish_requires_approval
+    pub can_set_pay: bool,  // Set or change the hourly rate of locum shifts
+    /// Last day of access once revoked (DELETE /api/user-roles/{id}); None while the assignment is open-ended
+    pub effective_until: Option<NaiveDate>,
     #[serde(serialize_with = "serialize_naive_as_utc")]
     pub created_at: NaiveDateTime,
     #[serde(rename = "Roles")]
     pub roles: Option<Role>,
 }
 
+/// Clean-up action available for an unlinked profile
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum UnlinkedProfileAction {
+    /// POST /api/users/unlinked/{id}/invite - needs a primary email
+    Invite,
+    /// POST /api/users/create-login
+    CreateLogin,
+    /// POST /api/users/unlinked/{id}/deactivate
+    Deactivate,
+}
+
+/// Profile still on a temp_ auth_id, i.e. never linked to a Clerk account
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct UnlinkedProfile {
+    pub user_profile_id: i32,
+    pub full_name: String,
+    pub short_name: String,
+    pub primary_email: Option<String>,
+    #[serde(serialize_with = "serialize_naive_as_utc")]
+    pub created_at: NaiveDateTime,
+    pub role_count: i64,
+    /// Latest shift worked or leave taken, up to today
+    pub last_activity: Option<NaiveDate>,
+    pub invited_at: Option<NaiveDateTime>,
+    pub deactivated_at: Option<NaiveDateTime>,
+    #[sqlx(skip)]
+    pub actions: Vec<UnlinkedProfileAction>,
+}
+
+/// Clerk account that no profile's auth_id points at
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct OrphanedClerkUser {
+    pub clerk_user_id: String,
+    pub email: Option<String>,
+    /// user_profile_id the account was created for by create-login
+    pub external_id: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    /// Unlinked profile with the same primary email; it links itself on the account's first sign-in
+    pub matching_profile_id: Option<i32>,
+    /// When create-login failed to link the account and could not delete it either
+    pub rollback_failed_at: Option<NaiveDateTime>,
+}
+
+/// Outcome of clearing recorded create-login rollback failures
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PrunedRollbackFailures {
+    /// Failures removed because their Clerk account no longer exists
+    pub removed: u64,
+}
+
+/// One row of the HR export: a user with one active role assignment (or none, for super admins' unscoped
+/// exports), that role's job plan in force today and the last shift worked in it
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct UserExportRow {
+    pub user_profile_id: i32,
+    pub full_name: String,
+    pub short_name: String,
+    pub gmc: Option<i32>,
+    pub primary_email: Option<String>,
+    #[schema(value_type = Option<Vec<String>>)]
+    pub secondary_emails: Option<Vec<EncryptedString>>,
+    #[schema(value_type = Option<Vec<String>>)]
+    pub tel: Option<Vec<EncryptedString>>,
+    /// Emails and phone numbers were blanked: the caller cannot view staff details in any of the user's roles
+    #[sqlx(skip)]
+    pub contact_details_redacted: bool,
+    pub is_generic_login: bool,
+    #[serde(serialize_with = "serialize_naive_as_utc")]
+    pub created_at: NaiveDateTime,
+    pub deactivated_at: Option<NaiveDateTime>,
+    pub role_id: Option<i32>,
+    pub role_name: Option<String>,
+    pub hospital: Option<String>,
+    pub ward: Option<String>,
+    pub can_edit_rota: Option<bool>,
+    pub can_access_diary: Option<bool>,
+    pub can_work_shifts: Option<bool>,
+    pub can_edit_templates: Option<bool>,
+    pub can_edit_staff: Option<bool>,
+    pub can_view_staff_details: Option<bool>,
+    pub can_approve_rota: Option<bool>,
+    pub can_set_pay: Option<bool>,
+    pub job_plan_from: Option<NaiveDate>,
+    pub job_plan_until: Option<NaiveDate>,
+    pub job_plan_dcc_pa: Option<f32>,
+    pub job_plan_spa_pa: Option<f32>,
+    pub job_plan_al_per_year: Option<f32>,
+    /// Latest working shift in the role, up to today
+    pub last_shift_date: Option<NaiveDate>,
+    #[serde(skip)]
+    pub workplace_id: Option<i32>,
+    /// The user's values for the fields of this row's workplace (every workplace on rows without a role),
+    /// keyed by field key; CSV exports flatten them into custom_fields.<key> columns
+    #[sqlx(skip)]
+    #[schema(value_type = Object)]
+    pub custom_fields: BTreeMap<String, Value>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct StaffFilterOption {
     pub user_profile_id: i32,
@@ -58,3 +171,70 @@ pub struct StaffFilterOption {
     pub full_name: String,
     pub color: Option<String>,
 }
+
+/// Sort column for user lists (`sort=` on GET /api/users and staff-list, `sort` in the search body)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum UserSort {
+    /// Full name, A to Z by default
+    Name,
+    /// GMC number, lowest first by default; users without one last
+    Gmc,
+    /// Latest shift up to today, most recent first by default; users who never worked one last
+    LastShift,
+    /// Profile creation, newest first by default
+    CreatedAt,
+}
+
+/// Sort direction (`dir=`); each UserSort has its own default
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SortDirection {
+    Asc,
+    Desc,
+}
+
+impl UserSort {
+    pub fn default_direction(&self) -> SortDirection {
+        match self {
+            UserSort::Name | UserSort::Gmc => SortDirection::Asc,
+            UserSort::LastShift | UserSort::CreatedAt => SortDirection::Desc,
+        }
+    }
+
+    /// ORDER BY clause over the Users alias `u`, with user_profile_id as the tie-breaker
+    pub fn order_by(&self, dir: Option<SortDirection>) -> String {
+        let dir = match dir.unwrap_or(self.default_direction()) {
+            SortDirection::Asc => "ASC",
+            SortDirection::Desc => "DESC",
+        };
+        let column = match self {
+            UserSort::Name => format!("u.full_name {}", dir),
+            UserSort::Gmc => format!("u.gmc {} NULLS LAST", dir),
+            UserSort::LastShift => format!(
+                r#"(SELECT MAX(s.date) FROM "Shifts" s WHERE s.user_profile_id = u.user_profile_id AND s.date <= CURRENT_DATE) {} NULLS LAST"#,
+                dir
+            ),
+            UserSort::CreatedAt => format!("u.created_at {}", dir),
+        };
+        format!("{}, u.user_profile_id ASC", column)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_sort_whitelist_and_directions() {
+        assert_eq!(serde_json::from_str::<UserSort>("\"last_shift\"").unwrap(), UserSort::LastShift);
+        assert!(serde_json::from_str::<UserSort>("\"full_name; DROP TABLE\"").is_err());
+
+        assert_eq!(UserSort::Name.order_by(None), "u.full_name ASC, u.user_profile_id ASC");
+        assert_eq!(
+            UserSort::CreatedAt.order_by(Some(SortDirection::Asc)),
+            "u.created_at ASC, u.user_profile_id ASC"
+        );
+        assert!(UserSort::LastShift.order_by(None).ends_with("DESC NULLS LAST, u.user_profile_id ASC"));
+    }
+}
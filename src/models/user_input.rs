@@ -1,13 +1,20 @@
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use uuid::Uuid;
 
 use utoipa::ToSchema;
 
+use super::user::{SortDirection, UserSort};
+
 /// Input for updating own profile (self-service)
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct UpdateOwnProfileInput {
     pub short_name: String,
     pub tel: Option<Vec<String>>,
     pub color: Option<String>,
+    /// Stop (true) or resume (false) give-away offer notifications; unchanged when omitted
+    pub marketplace_offers_opt_out: Option<bool>,
 }
 
 /// Input for changing own PIN (self-service)
@@ -30,6 +37,9 @@ pub struct UpdateUserProfileInput {
     pub comment: Option<String>,
     pub auth_pin: Option<String>,
     pub color: Option<String>,
+    /// Custom field values keyed by field definition id; null clears a value
+    #[schema(value_type = Option<Object>)]
+    pub custom_fields: Option<BTreeMap<i32, Value>>,
 }
 
 /// Response for PIN operations
@@ -50,6 +60,8 @@ pub struct SearchUsersRequest {
     pub query: String,
     #[serde(rename = "roleId")]
     pub role_id: Option<i32>,
+    pub sort: Option<UserSort>,  // Default name
+    pub dir: Option<SortDirection>,
 }
 
 /// Request for creating a user profile without Clerk account
@@ -94,6 +106,28 @@ pub struct VerifyIdentityResponse {
     pub token: Option<String>,
 }
 
+/// Request for emailing a verification code to a user (alternative to PIN on generic terminals)
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RequestEmailVerificationRequest {
+    pub user_profile_id: i32,
+}
+
+/// Response after emailing a verification code
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RequestEmailVerificationResponse {
+    pub success: bool,
+    pub challenge_id: Uuid,
+    pub sent_to: String, // Masked email address
+    pub expires_in_seconds: u64,
+}
+
+/// Request for confirming an emailed verification code (returns the same token as PIN verification)
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ConfirmEmailVerificationRequest {
+    pub challenge_id: Uuid,
+    pub code: String,
+}
+
 /// Request for changing profile PIN with verification token (Step 2)
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct ChangeProfilePinRequest {
@@ -108,6 +142,33 @@ pub struct SuccessResponse {
     pub success: bool,
 }
 
+/// A validation problem with one CSV row (row 1 is the first data row after the header)
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ImportRowError {
+    pub row: usize,
+    pub field: Option<String>,
+    pub message: String,
+}
+
+/// A profile created (or, in a dry run, that would be created) from a CSV row
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ImportedUser {
+    pub row: usize,
+    pub user_profile_id: Option<i32>, // None in dry runs
+    pub full_name: String,
+    pub role_ids: Vec<i32>,
+}
+
+/// Result of a CSV user import; nothing is written unless every row is valid
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ImportUsersResponse {
+    pub success: bool,
+    pub dry_run: bool,
+    pub total_rows: usize,
+    pub users: Vec<ImportedUser>,
+    pub errors: Vec<ImportRowError>,
+}
+
 /// Input for creating a Clerk login for a user profile
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct CreateLoginInput {
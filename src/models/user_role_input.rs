@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 use utoipa::ToSchema;
 
@@ -13,6 +14,10 @@ pub struct CreateUserRoleInput {
     pub can_edit_templates: bool,
     pub can_edit_staff: bool,
     pub can_view_staff_details: bool,
+    #[serde(default)]
+    pub can_approve_rota: bool,
+    #[serde(default)]
+    pub can_set_pay: bool,
 }
 
 /// Input for updating a user role assignment
@@ -25,6 +30,73 @@ pub struct UpdateUserRoleInput {
     pub can_edit_templates: Option<bool>,
     pub can_edit_staff: Option<bool>,
     pub can_view_staff_details: Option<bool>,
+    pub can_approve_rota: Option<bool>,
+    pub can_set_pay: Option<bool>,
+    /// Cancel a scheduled revocation, so the assignment no longer ends after its effective_until day
+    #[serde(default)]
+    pub cancel_revocation: bool,
+}
+
+/// Permission flags to set; omitted flags are left as they are
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct PermissionFlagsPatch {
+    pub can_edit_rota: Option<bool>,
+    pub can_access_diary: Option<bool>,
+    pub can_work_shifts: Option<bool>,
+    pub can_edit_templates: Option<bool>,
+    pub can_edit_staff: Option<bool>,
+    pub can_view_staff_details: Option<bool>,
+    pub can_approve_rota: Option<bool>,
+    pub can_set_pay: Option<bool>,
+}
+
+/// Input for PATCH /api/user-roles/bulk
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BulkUpdateUserRolesInput {
+    /// Role whose assignments are updated
+    pub role_id: i32,
+    /// Only these profiles; omit for everyone assigned to the role
+    pub user_profile_ids: Option<Vec<i32>>,
+    pub set: PermissionFlagsPatch,
+    /// Report what would change without writing
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// One permission flag that changed on an assignment
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub struct PermissionChange {
+    pub permission: String,
+    pub from: bool,
+    pub to: bool,
+}
+
+/// An assignment the bulk update changed (or, for a dry run, would change)
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UserRolePermissionChanges {
+    pub user_role_id: i32,
+    pub user_profile_id: i32,
+    pub short_name: Option<String>,
+    pub changes: Vec<PermissionChange>,
+}
+
+/// Summary of PATCH /api/user-roles/bulk
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct BulkUpdateUserRolesResponse {
+    pub dry_run: bool,
+    /// Assignments matching the filter
+    pub matched: usize,
+    /// Assignments changed, each with one "UserRoleAudit" row
+    pub changed: usize,
+    /// Assignments that already had every requested flag
+    pub unchanged: usize,
+    /// Groups this update's audit rows; None for a dry run or when nothing changed
+    pub batch_id: Option<Uuid>,
+    pub assignments: Vec<UserRolePermissionChanges>,
+    /// Requested profiles that are not assigned to the role
+    pub not_in_role: Vec<i32>,
+    /// Generic accounts left without can_work_shifts (their other flags are still applied)
+    pub generic_accounts: Vec<i32>,
 }
 
 /// Response for user role mutations
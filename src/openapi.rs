@@ -1,7 +1,9 @@
 use utoipa::OpenApi;
-use utoipa::openapi::security::{ApiKey, ApiKeyValue, SecurityScheme};
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, SecurityRequirement, SecurityScheme};
 use utoipa::Modify;
 
+use crate::middleware::require_auth::is_public_route;
+
 #[derive(OpenApi)]
 #[openapi(
     info(
@@ -19,18 +21,42 @@ use utoipa::Modify;
     paths(
         // Health
         crate::handlers::health::health_check,
+        crate::handlers::version::version_handler,
 
         // Auth
         crate::handlers::auth_handler::get_me,
         crate::handlers::auth_handler::verify_pin,
+        crate::handlers::mfa_handler::get_mfa_status,
+        crate::handlers::mfa_handler::enroll_mfa,
+        crate::handlers::mfa_handler::confirm_mfa,
+        crate::handlers::mfa_handler::disable_mfa,
+        crate::handlers::passkeys_handler::list_passkeys,
+        crate::handlers::passkeys_handler::passkey_registration_options,
+        crate::handlers::passkeys_handler::register_passkey,
+        crate::handlers::passkeys_handler::passkey_verification_options,
+        crate::handlers::passkeys_handler::verify_passkey,
+        crate::handlers::passkeys_handler::delete_passkey,
 
         // Users
         crate::handlers::users_handler::get_users,
         crate::handlers::users_handler::get_user,
         crate::handlers::users_handler::get_substantive_users,
         crate::handlers::users_handler::get_staff_list,
+        crate::handlers::directory_handler::get_directory,
         crate::handlers::users_handler::update_own_profile,
         crate::handlers::users_handler::change_own_pin,
+        crate::handlers::data_export_handler::request_data_export,
+        crate::handlers::audit_handler::get_my_logins,
+        crate::handlers::data_export_handler::get_data_exports,
+        crate::handlers::data_export_handler::download_data_export,
+        crate::handlers::saved_views_handler::get_saved_views,
+        crate::handlers::saved_views_handler::create_saved_view,
+        crate::handlers::saved_views_handler::update_saved_view,
+        crate::handlers::saved_views_handler::delete_saved_view,
+        crate::handlers::saved_views_handler::share_saved_view,
+        crate::handlers::saved_views_handler::unshare_saved_view,
+        crate::handlers::saved_views_handler::get_shared_view,
+        crate::handlers::saved_views_handler::copy_shared_view,
         crate::handlers::users_handler::update_user_profile,
         crate::handlers::users_handler::reset_user_pin,
         // New Phase B endpoints
@@ -38,7 +64,16 @@ use utoipa::Modify;
         crate::handlers::users_handler::create_user_profile,
         crate::handlers::users_handler::check_email_usage,
         crate::handlers::users_handler::verify_profile_identity,
+        crate::handlers::user_import_handler::import_users,
+        crate::handlers::user_export_handler::export_users,
+        crate::handlers::users_handler::request_email_verification,
+        crate::handlers::users_handler::confirm_email_verification,
         crate::handlers::users_handler::change_profile_pin,
+        crate::handlers::unlinked_users_handler::get_unlinked_profiles,
+        crate::handlers::unlinked_users_handler::get_clerk_orphans,
+        crate::handlers::unlinked_users_handler::prune_rollback_failures,
+        crate::handlers::unlinked_users_handler::invite_unlinked_profile,
+        crate::handlers::unlinked_users_handler::deactivate_unlinked_profile,
 
         // References
         crate::handlers::references_handler::get_time_off_categories,
@@ -48,50 +83,105 @@ use utoipa::Modify;
 
         // Audit
         crate::handlers::audit_handler::get_audit,
+        crate::handlers::audit_handler::get_access_log,
+        crate::handlers::audit_handler::get_login_audit,
+        crate::handlers::audit_handler::get_user_role_audit,
+        crate::handlers::audit_handler::get_user_shift_changes,
+        crate::handlers::audit_handler::get_user_role_history,
+        crate::handlers::audit_handler::get_retention_report,
+        crate::handlers::audit_handler::backfill_audit,
 
         // Shifts
         crate::handlers::shifts_handler::get_shifts_for_month,
         crate::handlers::shifts_handler::get_shifts_for_date,
         crate::handlers::shifts_handler::get_shifts_for_range,
         crate::handlers::shifts_handler::create_shift,
+        crate::handlers::shifts_handler::create_shift_series,
         crate::handlers::shifts_handler::update_shift,
         crate::handlers::shifts_handler::delete_shift,
+        crate::handlers::attachments_handler::get_shift_attachments,
+        crate::handlers::attachments_handler::upload_shift_attachment,
+        crate::handlers::attachments_handler::delete_shift_attachment,
+        crate::handlers::payroll_locks_handler::get_locks,
+        crate::handlers::payroll_locks_handler::lock_period,
+        crate::handlers::payroll_locks_handler::unlock_period,
+
+        // Rota
+        crate::handlers::rota_handler::get_rota,
+        crate::handlers::rota_handler::get_rota_diff,
+        crate::handlers::rota_handler::get_display_rota,
+        crate::handlers::rota_approval_handler::get_rota_approvals,
+        crate::handlers::rota_approval_handler::submit_rota_approval,
+        crate::handlers::rota_approval_handler::decide_rota_approval,
 
         // Templates
         crate::handlers::templates_handler::get_templates,
         crate::handlers::templates_handler::create_template,
         crate::handlers::templates_handler::update_template,
         crate::handlers::templates_handler::delete_template,
+        crate::handlers::templates_handler::get_template_usage,
+        crate::handlers::templates_handler::auto_fill,
+        crate::handlers::patterns_handler::get_patterns,
+        crate::handlers::patterns_handler::create_pattern,
+        crate::handlers::patterns_handler::update_pattern,
+        crate::handlers::patterns_handler::delete_pattern,
+        crate::handlers::patterns_handler::apply_pattern,
 
         // Diary
         crate::handlers::diary_handler::get_diary,
         crate::handlers::diary_handler::create_diary_entry,
         crate::handlers::diary_handler::delete_diary_entry,
+        crate::handlers::attachments_handler::get_diary_attachments,
+        crate::handlers::attachments_handler::upload_diary_attachment,
+        crate::handlers::attachments_handler::delete_diary_attachment,
 
         // Job Plans
         crate::handlers::job_plans_handler::get_job_plans,
+        crate::handlers::job_plans_handler::get_job_plan_issues,
         crate::handlers::job_plans_handler::create_job_plan,
         crate::handlers::job_plans_handler::update_job_plan,
         crate::handlers::job_plans_handler::delete_job_plan,
         crate::handlers::job_plans_handler::terminate_job_plan,
+        crate::handlers::job_plans_handler::get_job_plan_templates,
+        crate::handlers::job_plans_handler::create_job_plan_template,
+        crate::handlers::job_plans_handler::update_job_plan_template,
+        crate::handlers::job_plans_handler::delete_job_plan_template,
+        crate::handlers::job_plans_handler::create_job_plan_from_template,
 
         // User Roles
         crate::handlers::user_roles_handler::get_user_roles,
         crate::handlers::user_roles_handler::create_user_role,
         crate::handlers::user_roles_handler::update_user_role,
+        crate::handlers::user_roles_handler::bulk_update_user_roles,
         crate::handlers::user_roles_handler::delete_user_role,
 
         // Roles
         crate::handlers::roles_handler::get_roles,
         crate::handlers::roles_handler::create_role,
         crate::handlers::roles_handler::update_role,
+        crate::handlers::roles_handler::create_display_token,
         crate::handlers::roles_handler::delete_role,
+        crate::handlers::roles_handler::get_role_stats,
+        crate::handlers::assignment_drift_handler::get_assignment_drift,
+        crate::handlers::assignment_drift_handler::fix_assignment_drift,
+        crate::handlers::pay_rules_handler::get_pay_rules,
+        crate::handlers::pay_rules_handler::update_pay_rules,
+        crate::handlers::pay_rules_handler::get_role_costs,
+        crate::handlers::attendance_handler::check_in,
+        crate::handlers::attendance_handler::check_out,
+        crate::handlers::attendance_handler::get_attendance_report,
 
         // Workplaces
         crate::handlers::workplaces_handler::get_workplaces,
         crate::handlers::workplaces_handler::create_workplace,
         crate::handlers::workplaces_handler::update_workplace,
         crate::handlers::workplaces_handler::delete_workplace,
+        crate::handlers::workplaces_handler::get_workplace_settings,
+        crate::handlers::workplaces_handler::update_workplace_settings,
+        crate::handlers::custom_fields_handler::get_custom_fields,
+        crate::handlers::custom_fields_handler::create_custom_field,
+        crate::handlers::custom_fields_handler::update_custom_field,
+        crate::handlers::custom_fields_handler::delete_custom_field,
 
         // Marketplace
         crate::handlers::marketplace_handler::get_open_requests,
@@ -99,43 +189,191 @@ use utoipa::Modify;
         crate::handlers::marketplace_handler::get_incoming_requests,
         crate::handlers::marketplace_handler::get_approval_requests,
         crate::handlers::marketplace_handler::get_dashboard,
+        crate::handlers::marketplace_sla_handler::get_sla_report,
+        crate::handlers::marketplace_poll_handler::poll_marketplace,
+        crate::handlers::shift_stats_handler::get_user_month_stats,
+        crate::handlers::shift_stats_handler::get_role_month_stats,
+        crate::handlers::shift_stats_handler::refresh_stats,
+        crate::handlers::marketplace_reasons_handler::get_reasons,
+        crate::handlers::marketplace_reasons_handler::create_reason,
+        crate::handlers::marketplace_reasons_handler::update_reason,
         crate::handlers::marketplace_handler::get_swappable_shifts,
+        crate::handlers::marketplace_handler::validate_swap,
         crate::handlers::marketplace_handler::create_shift_request,
+        crate::handlers::marketplace_handler::get_offer_recipients,
         crate::handlers::marketplace_handler::accept_shift_request,
+        crate::handlers::marketplace_handler::bump_shift_request,
+        crate::handlers::marketplace_handler::withdraw_shift_request,
         crate::handlers::marketplace_handler::respond_to_proposal,
         crate::handlers::marketplace_handler::admin_decision,
         crate::handlers::marketplace_handler::cancel_shift_request,
+        crate::handlers::marketplace_handler::get_shift_request_by_token,
+        crate::handlers::marketplace_handler::force_cancel_shift_request,
+        crate::handlers::cover_board_handler::get_cover_board,
+        crate::handlers::cover_board_handler::set_needs_cover,
+        crate::handlers::cover_board_handler::volunteer_for_cover,
+        crate::handlers::absences_handler::report_absence,
+        crate::handlers::search_handler::search,
+        crate::handlers::features_handler::get_features,
+        crate::handlers::features_handler::get_feature_flags,
+        crate::handlers::features_handler::create_feature_flag,
+        crate::handlers::features_handler::update_feature_flag,
+        crate::handlers::features_handler::delete_feature_flag,
+        crate::handlers::locum_availability_handler::get_availability,
+        crate::handlers::locum_availability_handler::create_availability,
+        crate::handlers::locum_availability_handler::delete_availability,
+        crate::handlers::locum_availability_handler::assign_locum,
+        crate::handlers::delegations_handler::get_delegations,
+        crate::handlers::delegations_handler::create_delegation,
+        crate::handlers::delegations_handler::delete_delegation,
+        crate::handlers::dashboard_handler::get_dashboard,
+        crate::handlers::dashboard_handler::get_my_calendar,
+
+        // Notifications
+        crate::handlers::notifications_handler::get_my_notifications,
+        crate::handlers::notifications_handler::mark_notification_read,
+        crate::handlers::notifications_handler::get_notification_preferences,
+        crate::handlers::notifications_handler::update_notification_preferences,
+        crate::handlers::announcements_handler::get_announcements,
+        crate::handlers::announcements_handler::create_announcement,
+        crate::handlers::announcements_handler::update_announcement,
+        crate::handlers::announcements_handler::delete_announcement,
+        crate::handlers::announcements_handler::mark_announcement_read,
     ),
     components(
         schemas(
+            crate::build_info::BuildInfo,
             // Core models
             crate::models::User,
+            crate::models::DataExport,
+            crate::models::DataExportBundle,
+            crate::models::SavedView,
+            crate::models::CreateSavedViewInput,
+            crate::models::UpdateSavedViewInput,
+            crate::models::CopySavedViewInput,
+            crate::models::SavedViewMutationResponse,
             crate::models::UserRole,
             crate::models::Role,
+            crate::models::RoleStats,
+            crate::models::AssignmentDrift,
+            crate::models::AssignmentIssue,
+            crate::models::AssignmentDriftFix,
+            crate::models::DriftShift,
+            crate::models::FixAssignmentDriftInput,
+            crate::models::FixAssignmentDriftResponse,
+            crate::models::RolePayRules,
+            crate::models::UpdatePayRulesInput,
+            crate::models::ShiftCost,
+            crate::models::RoleCostReport,
+            crate::models::ShiftAttendance,
+            crate::models::AttendanceLocationInput,
+            crate::models::AttendanceException,
+            crate::models::AttendanceDiscrepancy,
+            crate::models::AttendanceReport,
             crate::models::Workplace,
+            crate::models::WorkplaceSettings,
+            crate::models::CustomFieldType,
+            crate::models::CustomFieldDefinition,
+            crate::models::CustomFieldValue,
+            crate::models::CreateCustomFieldInput,
+            crate::models::UpdateCustomFieldInput,
             crate::models::Shift,
             crate::models::ShiftTemplate,
+            crate::models::TemplateUsage,
+            crate::models::AutoFillInput,
+            crate::models::AutoFillResponse,
+            crate::models::AutoFillAssignment,
+            crate::models::AutoFillUnfilled,
+            crate::models::TemplateMonthUsage,
             crate::models::DiaryEntry,
             crate::models::JobPlan,
+            crate::models::JobPlanIssue,
+            crate::models::JobPlanIssueKind,
+            crate::models::JobPlanTemplate,
             crate::models::ShiftRequest,
+            crate::models::ShiftRequestStatus,
+            crate::models::MarketplaceSort,
+            crate::models::ShiftRequestType,
             crate::models::ShiftRequestWithDetails,
+            crate::models::ShiftOfferRecipient,
+            crate::models::BumpRequestInput,
+            crate::models::BumpRequestResponse,
+            crate::models::ForceCancelRequestInput,
+            crate::models::ForceCancelResponse,
+            crate::models::ShiftRequestByToken,
             crate::models::TimeOffCategory,
             crate::models::AuditEntry,
+            crate::models::DataAccessEntry,
+            crate::models::LoginAuditEntry,
+            crate::models::UserRoleAuditEntry,
+            crate::models::UserShiftChange,
+            crate::models::RoleMembershipPeriod,
+            crate::models::RoleMembershipEnd,
+            crate::models::RolePermissions,
+            crate::models::ShiftChangeKind,
+            crate::models::RetentionReport,
+            crate::models::BackfillAuditInput,
+            crate::models::BackfillKind,
+            crate::models::BackfilledChange,
+            crate::models::BackfillReport,
+            crate::models::RetentionRuleResult,
             crate::models::COD,
+            crate::models::Notification,
+            crate::models::NotificationPreference,
+            crate::models::UpdateNotificationPreferencesInput,
+            crate::models::ShiftFieldChange,
+            crate::models::Announcement,
+            crate::models::CreateAnnouncementInput,
+            crate::models::UpdateAnnouncementInput,
+            crate::models::AnnouncementMutationResponse,
             crate::models::StaffFilterOption,
+            crate::models::DirectoryWorkplace,
+            crate::models::DirectoryRole,
+            crate::models::DirectoryMember,
+            crate::models::DirectoryContact,
+            crate::models::SnapshotShift,
+            crate::models::MovedAssignment,
+            crate::models::RotaDiff,
+            crate::models::RotaPublishApproval,
+            crate::models::RotaLock,
+            crate::models::SubmitRotaApprovalInput,
+            crate::models::RotaApprovalDecisionInput,
+            crate::models::DisplayShift,
+            crate::models::DisplayRota,
+            crate::models::RoleRota,
+            crate::models::DisplayTokenResponse,
+            crate::models::CreateDisplayTokenInput,
 
             // Input models
             crate::models::CreateShiftInput,
+            crate::models::CreateShiftSeriesInput,
+            crate::models::ShiftSeriesResponse,
             crate::models::UpdateShiftInput,
             crate::models::ShiftMutationResponse,
             crate::models::CreateDiaryInput,
             crate::models::DiaryMutationResponse,
+            crate::models::Attachment,
+            crate::models::AttachmentMutationResponse,
             crate::models::CreateJobPlanInput,
+            crate::models::CreateJobPlanTemplateInput,
+            crate::models::UpdateJobPlanTemplateInput,
+            crate::models::CreateJobPlanFromTemplateInput,
             crate::models::UpdateJobPlanInput,
             crate::models::JobPlanMutationResponse,
             crate::models::CreateTemplateInput,
             crate::models::UpdateTemplateInput,
             crate::models::TemplateMutationResponse,
+            crate::models::RotaPattern,
+            crate::models::RotaPatternEntry,
+            crate::models::PatternEntryInput,
+            crate::models::CreatePatternInput,
+            crate::models::UpdatePatternInput,
+            crate::models::ApplyPatternInput,
+            crate::models::ApplyPatternResponse,
+            crate::models::AssignmentSuggestion,
+            crate::models::DuplicateShift,
+            crate::models::DuplicateShiftPolicy,
+            crate::models::PatternMutationResponse,
             crate::models::UpdateOwnProfileInput,
             crate::models::ChangeOwnPinInput,
             crate::models::UpdateUserProfileInput,
@@ -147,33 +385,117 @@ use utoipa::Modify;
             crate::models::CheckEmailResponse,
             crate::models::VerifyIdentityRequest,
             crate::models::VerifyIdentityResponse,
+            crate::models::RequestEmailVerificationRequest,
+            crate::models::RequestEmailVerificationResponse,
+            crate::models::ConfirmEmailVerificationRequest,
+            crate::models::ImportUsersResponse,
+            crate::models::UserExportRow,
+            crate::models::ImportedUser,
+            crate::models::ImportRowError,
             crate::models::ChangeProfilePinRequest,
             crate::models::SuccessResponse,
+            crate::error::FieldError,
+            crate::error::InvalidFieldsBody,
             crate::models::CreateUserRoleInput,
             crate::models::UpdateUserRoleInput,
             crate::models::UserRoleMutationResponse,
+            crate::models::PermissionFlagsPatch,
+            crate::models::BulkUpdateUserRolesInput,
+            crate::models::PermissionChange,
+            crate::models::UserRolePermissionChanges,
+            crate::models::BulkUpdateUserRolesResponse,
             crate::models::CreateRoleInput,
             crate::models::UpdateRoleInput,
             crate::models::RoleMutationResponse,
             crate::models::CreateWorkplaceInput,
             crate::models::UpdateWorkplaceInput,
+            crate::models::UpdateWorkplaceSettingsInput,
             crate::models::WorkplaceMutationResponse,
             crate::models::CreateShiftRequestInput,
+            crate::models::ValidateSwapInput,
+            crate::models::SwapEligibility,
+            crate::models::MarketplaceEvent,
+            crate::models::MarketplacePollResponse,
+            crate::models::MarketplaceSlaReport,
+            crate::models::StatsFreshness,
+            crate::models::UserMonthStats,
+            crate::models::UserMonthStatsReport,
+            crate::models::RoleMonthStats,
+            crate::models::RoleMonthStatsReport,
+            crate::models::StatsViewRefresh,
+            crate::models::SwapUsage,
+            crate::models::FailureReasonCount,
+            crate::models::MarketplaceReason,
+            crate::models::CreateMarketplaceReasonInput,
+            crate::models::UpdateMarketplaceReasonInput,
+            crate::models::ReasonOutcome,
+            crate::models::CoverShift,
+            crate::models::SetNeedsCoverInput,
+            crate::models::VolunteerForCoverInput,
+            crate::models::ReportAbsenceInput,
+            crate::models::AbsenceReport,
+            crate::models::AbsenceShiftImpact,
+            crate::models::ReplacementCandidate,
+            crate::models::UserSort,
+            crate::models::UnlinkedProfile,
+            crate::models::UnlinkedProfileAction,
+            crate::models::OrphanedClerkUser,
+            crate::models::PrunedRollbackFailures,
+            crate::models::SortDirection,
+            crate::models::SearchType,
+            crate::models::SearchResults,
+            crate::models::UserSearchHit,
+            crate::models::ShiftSearchHit,
+            crate::models::DiarySearchHit,
+            crate::models::FeatureFlag,
+            crate::models::CreateFeatureFlagInput,
+            crate::models::UpdateFeatureFlagInput,
+            crate::models::FeatureFlagMutationResponse,
+            crate::models::SwapCheck,
             crate::models::AcceptRequestInput,
+            crate::models::WithdrawRequestInput,
             crate::models::RespondToProposalInput,
             crate::models::AdminDecisionInput,
             crate::models::MarketplaceMutationResponse,
+            crate::models::LocumAvailability,
+            crate::models::CreateAvailabilityInput,
+            crate::models::AssignLocumInput,
+            crate::models::ApprovalDelegation,
+            crate::models::CreateDelegationInput,
+            crate::models::Dashboard,
+            crate::models::MarketplaceCounts,
+            crate::models::LeaveSummary,
+            crate::models::PendingApprovals,
+            crate::models::PersonalCalendar,
+            crate::models::CalendarDay,
+            crate::models::CalendarShift,
+            crate::models::CalendarTimeOff,
+            crate::models::CalendarSwap,
+            crate::models::CalendarDiaryItem,
 
             // Auth types
             crate::handlers::auth_handler::VerifyPinRequest,
             crate::handlers::auth_handler::VerifyPinResponse,
+            crate::handlers::auth_handler::UserResponse,
+            crate::models::PinPolicy,
+            crate::handlers::mfa_handler::MfaStatus,
+            crate::handlers::mfa_handler::MfaEnrollment,
+            crate::handlers::mfa_handler::ConfirmMfaInput,
+            crate::handlers::passkeys_handler::Passkey,
+            crate::handlers::passkeys_handler::PasskeyRegistrationOptions,
+            crate::handlers::passkeys_handler::PasskeyVerificationOptions,
+            crate::handlers::passkeys_handler::RegisterPasskeyInput,
+            crate::handlers::passkeys_handler::VerifyPasskeyInput,
+            crate::handlers::passkeys_handler::PasskeyStepUp,
         )
     ),
     tags(
         (name = "health", description = "Health check"),
         (name = "auth", description = "Authentication endpoints"),
+        (name = "dashboard", description = "Home screen summary"),
         (name = "users", description = "User management"),
         (name = "shifts", description = "Shift management"),
+        (name = "rota", description = "Published rota snapshots"),
         (name = "templates", description = "Shift template management"),
         (name = "diary", description = "Diary entry management"),
         (name = "job-plans", description = "Job plan management"),
@@ -181,14 +503,30 @@ use utoipa::Modify;
         (name = "roles", description = "Role management"),
         (name = "workplaces", description = "Workplace management"),
         (name = "marketplace", description = "Shift swap marketplace"),
+        (name = "cover-board", description = "Shifts needing cover and volunteer claims"),
+        (name = "absences", description = "Sickness reporting"),
+        (name = "features", description = "Feature flags for soft launches"),
+        (name = "search", description = "Global search"),
+        (name = "notifications", description = "In-app notifications"),
+        (name = "announcements", description = "Targeted announcements with read tracking"),
         (name = "references", description = "Reference data"),
         (name = "comments", description = "Comments and COD"),
         (name = "audit", description = "Audit trail"),
+        (name = "shift-stats", description = "Precomputed shift statistics"),
     ),
     modifiers(&SecurityAddon)
 )]
 pub struct ApiDoc;
 
+/// The spec as pretty JSON with every object's keys sorted, so unchanged routes give identical bytes
+pub fn render_spec() -> Result<String, serde_json::Error> {
+    // serde_json::Value keeps object keys in a BTreeMap
+    let spec = serde_json::to_value(ApiDoc::openapi())?;
+    let mut json = serde_json::to_string_pretty(&spec)?;
+    json.push('\n');
+    Ok(json)
+}
+
 struct SecurityAddon;
 
 impl Modify for SecurityAddon {
@@ -199,5 +537,44 @@ impl Modify for SecurityAddon {
                 SecurityScheme::ApiKey(ApiKey::Cookie(ApiKeyValue::new("__session"))),
             )
         }
+
+        // The router requires a session everywhere but PUBLIC_ROUTES; say so on operations that don't already
+        for (path, item) in openapi.paths.paths.iter_mut() {
+            let operations = [
+                ("GET", &mut item.get),
+                ("PUT", &mut item.put),
+                ("POST", &mut item.post),
+                ("DELETE", &mut item.delete),
+                ("PATCH", &mut item.patch),
+            ];
+            for (method, operation) in operations {
+                let Some(operation) = operation else { continue };
+                if operation.security.is_none() && !is_public_route(method, path) {
+                    operation.security = Some(vec![SecurityRequirement::new("cookie_auth", Vec::<String>::new())]);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_operation_security_matches_router() {
+        let spec = ApiDoc::openapi();
+        for (path, item) in &spec.paths.paths {
+            for (method, operation) in [("GET", &item.get), ("POST", &item.post), ("PUT", &item.put), ("DELETE", &item.delete)] {
+                let Some(operation) = operation else { continue };
+                assert_eq!(
+                    operation.security.is_some(),
+                    !is_public_route(method, path),
+                    "{} {} security does not match require_auth::PUBLIC_ROUTES",
+                    method,
+                    path
+                );
+            }
+        }
     }
 }
@@ -0,0 +1,177 @@
+use once_cell::sync::{Lazy, OnceCell};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fmt;
+use std::io;
+use tracing::field::Field;
+use tracing_subscriber::{
+    field::MakeExt,
+    fmt::{format::Writer, FormatFields, MakeWriter},
+};
+
+/// Replacement for redacted values
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Keys whose values never reach the logs. Matching ignores case, `_` and `-`, so `primaryEmail`
+/// and `primary_email` are the same key. LOG_REDACT_KEYS adds to this list.
+const DEFAULT_DENY_KEYS: &[&str] = &[
+    "email",
+    "primary_email",
+    "secondary_emails",
+    "email_address",
+    "tel",
+    "phone",
+    "pin",
+    "auth_pin",
+    "new_pin",
+    "current_pin",
+    "pin_token",
+    "password",
+    "new_password",
+    "current_password",
+    "token",
+    "secret",
+];
+
+static DENY_KEYS: OnceCell<HashSet<String>> = OnceCell::new();
+
+/// Used until init runs (and by tests)
+static DEFAULT_KEYS: Lazy<HashSet<String>> = Lazy::new(|| parse_deny_keys(None));
+
+fn normalise(key: &str) -> String {
+    key.chars().filter(|c| *c != '_' && *c != '-').flat_map(char::to_lowercase).collect()
+}
+
+/// Built-in deny-list plus any comma-separated extra keys
+pub fn parse_deny_keys(extra: Option<&str>) -> HashSet<String> {
+    DEFAULT_DENY_KEYS
+        .iter()
+        .copied()
+        .chain(extra.unwrap_or_default().split(','))
+        .map(str::trim)
+        .filter(|k| !k.is_empty())
+        .map(normalise)
+        .collect()
+}
+
+/// Set the deny-list once at startup, before the tracing subscriber is built
+pub fn init(keys: HashSet<String>) {
+    if DENY_KEYS.set(keys).is_err() {
+        tracing::warn!("Log redaction keys already initialised");
+    }
+}
+
+fn deny_keys() -> &'static HashSet<String> {
+    DENY_KEYS.get().unwrap_or(&DEFAULT_KEYS)
+}
+
+pub fn is_denied(key: &str) -> bool {
+    deny_keys().contains(&normalise(key))
+}
+
+/// Replace the value of every deny-listed key, at any depth
+pub fn redact_json(value: &mut Value) {
+    match value {
+        Value::Object(fields) => {
+            for (key, field) in fields.iter_mut() {
+                if is_denied(key) {
+                    *field = Value::String(REDACTED.to_string());
+                } else {
+                    redact_json(field);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_json),
+        _ => {}
+    }
+}
+
+/// Loggable form of a request or response body: redacted JSON, or just its size when it is not JSON
+pub fn redact_body(body: &str) -> String {
+    match serde_json::from_str::<Value>(body) {
+        Ok(mut value) => {
+            redact_json(&mut value);
+            value.to_string()
+        }
+        Err(_) => format!("[{} bytes, not JSON]", body.len()),
+    }
+}
+
+/// Field formatter for the text log format: deny-listed fields are written as [REDACTED]
+pub fn text_fields() -> impl for<'w> FormatFields<'w> + Send + Sync + 'static {
+    tracing_subscriber::fmt::format::debug_fn(|writer: &mut Writer<'_>, field: &Field, value: &dyn fmt::Debug| {
+        match field.name() {
+            "message" => write!(writer, "{:?}", value),
+            name if is_denied(name) => write!(writer, "{}={}", name, REDACTED),
+            name => write!(writer, "{}={:?}", name, value),
+        }
+    })
+    .delimited(" ")
+}
+
+/// Writer for the JSON log format. The fmt layer writes each event as one JSON line, so the
+/// line is parsed, redacted (event fields and span fields alike) and written on.
+pub struct RedactingMakeWriter<M>(pub M);
+
+impl<'a, M: MakeWriter<'a>> MakeWriter<'a> for RedactingMakeWriter<M> {
+    type Writer = RedactingWriter<M::Writer>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        RedactingWriter(self.0.make_writer())
+    }
+}
+
+pub struct RedactingWriter<W>(W);
+
+impl<W: io::Write> io::Write for RedactingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match serde_json::from_slice::<Value>(buf) {
+            Ok(mut line) => {
+                redact_json(&mut line);
+                let mut out = line.to_string();
+                out.push('\n');
+                self.0.write_all(out.as_bytes())?;
+            }
+            Err(_) => self.0.write_all(buf)?,
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_redact_json_nested_keys() {
+        let mut value = json!({
+            "role_id": 3,
+            "primaryEmail": "a@example.org",
+            "users": [{ "short_name": "AB", "tel": ["07700 900000"], "auth_pin": "1234" }],
+        });
+        redact_json(&mut value);
+        assert_eq!(
+            value,
+            json!({
+                "role_id": 3,
+                "primaryEmail": REDACTED,
+                "users": [{ "short_name": "AB", "tel": REDACTED, "auth_pin": REDACTED }],
+            })
+        );
+        assert_eq!(redact_body("not json"), "[8 bytes, not JSON]");
+    }
+
+    #[test]
+    fn test_parse_deny_keys_extends_defaults() {
+        let keys = parse_deny_keys(Some(" gmc, NHS-Number ,"));
+        assert!(keys.contains("gmc"));
+        assert!(keys.contains("nhsnumber"));
+        assert!(keys.contains("email"));
+        assert!(!keys.contains(""));
+    }
+}
@@ -0,0 +1,360 @@
+use axum::{
+    extract::FromRequestParts,
+    http::{header, request::Parts, HeaderMap, HeaderValue},
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::borrow::Cow;
+
+use crate::AppError;
+
+mod pdf;
+
+/// Media types report endpoints can answer with, picked from the Accept header
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReportFormat {
+    #[default]
+    Json,
+    Csv,
+    Pdf,
+}
+
+impl ReportFormat {
+    pub fn content_type(self) -> &'static str {
+        match self {
+            ReportFormat::Json => "application/json",
+            ReportFormat::Csv => "text/csv; charset=utf-8",
+            ReportFormat::Pdf => "application/pdf",
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            ReportFormat::Json => "json",
+            ReportFormat::Csv => "csv",
+            ReportFormat::Pdf => "pdf",
+        }
+    }
+
+    fn for_media_range(range: &str) -> Option<Self> {
+        match range {
+            "application/json" | "application/*" | "*/*" => Some(ReportFormat::Json),
+            "text/csv" | "text/*" => Some(ReportFormat::Csv),
+            "application/pdf" => Some(ReportFormat::Pdf),
+            _ => None,
+        }
+    }
+
+    /// `?format=json|csv|pdf`, for download links that cannot set an Accept header
+    pub fn from_query(query: &str) -> Result<Option<Self>, AppError> {
+        let Some(value) = query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == "format")
+            .map(|(_, value)| value.to_ascii_lowercase())
+        else {
+            return Ok(None);
+        };
+
+        match value.as_str() {
+            "json" => Ok(Some(ReportFormat::Json)),
+            "csv" => Ok(Some(ReportFormat::Csv)),
+            "pdf" => Ok(Some(ReportFormat::Pdf)),
+            _ => Err(AppError::BadRequest("format must be json, csv or pdf".to_string())),
+        }
+    }
+
+    /// Highest-q supported media range wins, earlier ranges break ties. No Accept header means JSON;
+    /// a header naming nothing we can produce is a 406.
+    pub fn negotiate(headers: &HeaderMap) -> Result<Self, AppError> {
+        let accept: Vec<&str> = headers
+            .get_all(header::ACCEPT)
+            .iter()
+            .filter_map(|v| v.to_str().ok())
+            .collect();
+        if accept.iter().all(|v| v.trim().is_empty()) {
+            return Ok(ReportFormat::Json);
+        }
+
+        let mut best: Option<(f32, Self)> = None;
+        for range in accept.iter().flat_map(|v| v.split(',')) {
+            let mut parts = range.split(';');
+            let media = parts.next().unwrap_or_default().trim().to_ascii_lowercase();
+            let q = parts
+                .filter_map(|p| p.trim().strip_prefix("q="))
+                .find_map(|q| q.trim().parse::<f32>().ok())
+                .unwrap_or(1.0);
+            let Some(format) = Self::for_media_range(&media) else {
+                continue;
+            };
+            if q > 0.0 && best.is_none_or(|(best_q, _)| q > best_q) {
+                best = Some((q, format));
+            }
+        }
+
+        best.map(|(_, format)| format).ok_or_else(|| {
+            AppError::NotAcceptable(
+                "Reports are available as application/json, text/csv or application/pdf".to_string(),
+            )
+        })
+    }
+}
+
+impl<S: Send + Sync> FromRequestParts<S> for ReportFormat {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        match Self::from_query(parts.uri.query().unwrap_or_default())? {
+            Some(format) => Ok(format),
+            None => Self::negotiate(&parts.headers),
+        }
+    }
+}
+
+/// Report body rendered in the negotiated format. JSON is the body as-is; CSV and PDF get a table built
+/// by `table`, downloaded as "<title>.csv" / "<title>.pdf".
+pub struct Report<T> {
+    format: ReportFormat,
+    title: String,
+    body: T,
+}
+
+impl<T: Serialize> Report<T> {
+    pub fn new(format: ReportFormat, title: impl Into<String>, body: T) -> Self {
+        Self { format, title: title.into(), body }
+    }
+
+    fn render(self) -> Result<Response, AppError> {
+        if self.format == ReportFormat::Json {
+            return Ok(axum::Json(self.body).into_response());
+        }
+
+        let value = serde_json::to_value(&self.body)
+            .map_err(|e| AppError::Internal(format!("Failed to serialise report: {}", e)))?;
+        let (columns, rows) = table(value);
+        let bytes = match self.format {
+            ReportFormat::Csv => to_csv(&columns, &rows)?,
+            _ => pdf::render(&self.title, &columns, &rows),
+        };
+
+        let disposition = format!("attachment; filename=\"{}.{}\"", slug(&self.title), self.format.extension());
+        Ok((
+            [
+                (header::CONTENT_TYPE, HeaderValue::from_static(self.format.content_type())),
+                (
+                    header::CONTENT_DISPOSITION,
+                    HeaderValue::from_str(&disposition).unwrap_or(HeaderValue::from_static("attachment")),
+                ),
+            ],
+            bytes,
+        )
+            .into_response())
+    }
+}
+
+impl<T: Serialize> IntoResponse for Report<T> {
+    fn into_response(self) -> Response {
+        self.render().unwrap_or_else(IntoResponse::into_response)
+    }
+}
+
+/// Columns and rows for a report body. A list gives one row per item; an object with one list of objects
+/// gives a row per item with the object's other fields repeated; any other object (or one whose list is empty)
+/// is a single row.
+/// Nested objects become dotted columns ("rules.table"), lists of scalars are joined with "; ".
+fn table(value: Value) -> (Vec<String>, Vec<Vec<String>>) {
+    let records: Vec<Map<String, Value>> = match value {
+        Value::Array(items) => items.into_iter().map(into_object).collect(),
+        Value::Object(mut object) => {
+            let lists: Vec<String> = object
+                .iter()
+                .filter(|(_, v)| matches!(v, Value::Array(items) if !items.is_empty() && items.iter().all(Value::is_object)))
+                .map(|(k, _)| k.clone())
+                .collect();
+            match lists.as_slice() {
+                [key] => {
+                    let Some(Value::Array(items)) = object.remove(key) else {
+                        unreachable!("key was found above");
+                    };
+                    items
+                        .into_iter()
+                        .map(|item| {
+                            let mut row = object.clone();
+                            row.extend(into_object(item).into_iter().map(|(k, v)| (format!("{}.{}", key, k), v)));
+                            row
+                        })
+                        .collect()
+                }
+                _ => vec![object],
+            }
+        }
+        other => vec![into_object(other)],
+    };
+
+    let mut columns: Vec<String> = Vec::new();
+    let flat: Vec<Vec<(String, String)>> = records
+        .into_iter()
+        .map(|record| {
+            let mut cells = Vec::new();
+            flatten("", Value::Object(record), &mut cells);
+            cells
+        })
+        .collect();
+    for (column, _) in flat.iter().flatten() {
+        if !columns.contains(column) {
+            columns.push(column.clone());
+        }
+    }
+    let rows = flat
+        .into_iter()
+        .map(|cells| {
+            columns
+                .iter()
+                .map(|c| cells.iter().find(|(k, _)| k == c).map(|(_, v)| v.clone()).unwrap_or_default())
+                .collect()
+        })
+        .collect();
+    (columns, rows)
+}
+
+fn into_object(value: Value) -> Map<String, Value> {
+    match value {
+        Value::Object(object) => object,
+        other => Map::from_iter([("value".to_string(), other)]),
+    }
+}
+
+fn flatten(prefix: &str, value: Value, cells: &mut Vec<(String, String)>) {
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                let column = if prefix.is_empty() { key } else { format!("{}.{}", prefix, key) };
+                flatten(&column, value, cells);
+            }
+        }
+        Value::Array(items) if items.iter().all(|v| !v.is_object() && !v.is_array()) => {
+            cells.push((prefix.to_string(), items.iter().map(cell).collect::<Vec<_>>().join("; ")));
+        }
+        other => cells.push((prefix.to_string(), cell(&other))),
+    }
+}
+
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Spreadsheets run a cell starting with one of these as a formula
+const FORMULA_PREFIXES: &[char] = &['=', '+', '-', '@', '\t', '\r'];
+
+/// Quote a cell that a spreadsheet would treat as a formula (user-entered labels, notes and names end up in
+/// reports) with a leading apostrophe. Numbers such as -1.5 are left alone.
+fn csv_safe(cell: &str) -> Cow<'_, str> {
+    if cell.starts_with(FORMULA_PREFIXES) && cell.parse::<f64>().is_err() {
+        Cow::Owned(format!("'{}", cell))
+    } else {
+        Cow::Borrowed(cell)
+    }
+}
+
+fn to_csv(columns: &[String], rows: &[Vec<String>]) -> Result<Vec<u8>, AppError> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    let safe_record = |record: &[String]| record.iter().map(|c| csv_safe(c).into_owned()).collect::<Vec<_>>();
+    writer
+        .write_record(safe_record(columns))
+        .and_then(|_| rows.iter().try_for_each(|row| writer.write_record(safe_record(row))))
+        .map_err(|e| AppError::Internal(format!("Failed to write CSV: {}", e)))?;
+    writer
+        .into_inner()
+        .map_err(|e| AppError::Internal(format!("Failed to write CSV: {}", e)))
+}
+
+/// "Marketplace SLA 2025-03" -> "marketplace-sla-2025-03"
+fn slug(title: &str) -> String {
+    let mut slug = String::new();
+    for c in title.chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_end_matches('-');
+    if slug.is_empty() { "report".to_string() } else { slug.to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn accept(value: &str) -> Result<ReportFormat, AppError> {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT, value.parse().unwrap());
+        ReportFormat::negotiate(&headers)
+    }
+
+    #[test]
+    fn test_negotiate() {
+        assert_eq!(ReportFormat::negotiate(&HeaderMap::new()).unwrap(), ReportFormat::Json);
+        assert_eq!(accept("text/csv").unwrap(), ReportFormat::Csv);
+        assert_eq!(accept("application/pdf, application/json;q=0.5").unwrap(), ReportFormat::Pdf);
+        assert_eq!(accept("text/csv;q=0.4, application/pdf;q=0.9").unwrap(), ReportFormat::Pdf);
+        assert_eq!(accept("text/html, */*;q=0.1").unwrap(), ReportFormat::Json);
+        assert_eq!(accept("TEXT/CSV; charset=utf-8").unwrap(), ReportFormat::Csv);
+        assert!(matches!(accept("text/html"), Err(AppError::NotAcceptable(_))));
+        assert!(matches!(accept("text/csv;q=0"), Err(AppError::NotAcceptable(_))));
+    }
+
+    #[test]
+    fn test_from_query() {
+        assert_eq!(ReportFormat::from_query("").unwrap(), None);
+        assert_eq!(ReportFormat::from_query("roleId=3&format=CSV").unwrap(), Some(ReportFormat::Csv));
+        assert_eq!(ReportFormat::from_query("format=pdf").unwrap(), Some(ReportFormat::Pdf));
+        assert!(matches!(ReportFormat::from_query("format=xlsx"), Err(AppError::BadRequest(_))));
+    }
+
+    #[test]
+    fn test_table_shapes() {
+        let (columns, rows) = table(json!([{"a": 1, "b": {"c": "x"}}, {"a": 2, "d": [1, 2]}]));
+        assert_eq!(columns, vec!["a", "b.c", "d"]);
+        assert_eq!(rows, vec![vec!["1", "x", ""], vec!["2", "", "1; 2"]]);
+
+        let (columns, rows) = table(json!({"dry_run": true, "rules": [{"rows": 3}, {"rows": 4}]}));
+        assert_eq!(columns, vec!["dry_run", "rules.rows"]);
+        assert_eq!(rows, vec![vec!["true", "3"], vec!["true", "4"]]);
+
+        let (columns, rows) = table(json!({"decided": 5, "p50": null}));
+        assert_eq!(columns, vec!["decided", "p50"]);
+        assert_eq!(rows, vec![vec!["5", ""]]);
+
+        let (columns, rows) = table(json!({"decided": 5, "usage": []}));
+        assert_eq!(columns, vec!["decided", "usage"]);
+        assert_eq!(rows, vec![vec!["5", ""]]);
+        assert_eq!(slug("Marketplace SLA 2025-03-01 to 2025-03-31"), "marketplace-sla-2025-03-01-to-2025-03-31");
+    }
+
+    #[test]
+    fn test_csv_neutralises_formulas() {
+        for (cell, expected) in [
+            ("=HYPERLINK(\"http://x\")", "'=HYPERLINK(\"http://x\")"),
+            ("+44 20", "'+44 20"),
+            ("-cmd", "'-cmd"),
+            ("@SUM(A1)", "'@SUM(A1)"),
+            ("\tx", "'\tx"),
+            ("\rx", "'\rx"),
+            ("-1.5", "-1.5"),
+            ("Early", "Early"),
+            ("", ""),
+        ] {
+            assert_eq!(csv_safe(cell), expected);
+        }
+
+        let csv = to_csv(&["label".to_string()], &[vec!["=1+1".to_string()], vec!["-2".to_string()]]).unwrap();
+        assert_eq!(String::from_utf8(csv).unwrap(), "label\n'=1+1\n-2\n");
+    }
+}
@@ -0,0 +1,172 @@
+//! Minimal PDF writer for report tables: A4 landscape, built-in Courier so columns line up, no dependencies
+
+use std::fmt::Write;
+
+const PAGE_WIDTH: f32 = 842.0;
+const PAGE_HEIGHT: f32 = 595.0;
+const MARGIN: f32 = 36.0;
+const FONT_SIZE: f32 = 8.0;
+const LEADING: f32 = 10.0;
+/// Courier glyphs are 0.6 em wide
+const MAX_CHARS: usize = ((PAGE_WIDTH - 2.0 * MARGIN) / (FONT_SIZE * 0.6)) as usize;
+const LINES_PER_PAGE: usize = ((PAGE_HEIGHT - 2.0 * MARGIN) / LEADING) as usize;
+const COLUMN_GAP: usize = 2;
+const MIN_COLUMN: usize = 4;
+
+/// A single-row report reads better as field/value pairs than as one very wide row
+pub fn render(title: &str, columns: &[String], rows: &[Vec<String>]) -> Vec<u8> {
+    let (header, body): (Vec<String>, Vec<Vec<String>>) = if rows.len() == 1 {
+        (
+            vec!["field".to_string(), "value".to_string()],
+            columns.iter().cloned().zip(rows[0].iter().cloned()).map(|(k, v)| vec![k, v]).collect(),
+        )
+    } else {
+        (columns.to_vec(), rows.to_vec())
+    };
+
+    let widths = column_widths(&header, &body);
+    let header_line = format_row(&header, &widths);
+    let rule = "-".repeat(header_line.chars().count());
+
+    // Title and header open every page
+    let per_page = LINES_PER_PAGE.saturating_sub(4).max(1);
+    let mut pages: Vec<Vec<String>> = body
+        .chunks(per_page)
+        .map(|chunk| {
+            let mut lines = vec![title.to_string(), String::new(), header_line.clone(), rule.clone()];
+            lines.extend(chunk.iter().map(|row| format_row(row, &widths)));
+            lines
+        })
+        .collect();
+    if pages.is_empty() {
+        pages.push(vec![title.to_string(), String::new(), header_line, rule, "(no rows)".to_string()]);
+    }
+
+    write_document(&pages)
+}
+
+/// Natural widths, then the widest columns give way until the row fits the page
+fn column_widths(header: &[String], rows: &[Vec<String>]) -> Vec<usize> {
+    let mut widths: Vec<usize> = header.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let budget = MAX_CHARS.saturating_sub(COLUMN_GAP * widths.len().saturating_sub(1));
+    while widths.iter().sum::<usize>() > budget {
+        let Some(widest) = widths.iter_mut().filter(|w| **w > MIN_COLUMN).max() else {
+            break;
+        };
+        *widest -= 1;
+    }
+    widths
+}
+
+fn format_row(cells: &[String], widths: &[usize]) -> String {
+    let mut line = String::new();
+    for (i, (cell, width)) in cells.iter().zip(widths).enumerate() {
+        if i > 0 {
+            line.push_str(&" ".repeat(COLUMN_GAP));
+        }
+        let chars: Vec<char> = cell.chars().collect();
+        if chars.len() > *width {
+            line.extend(&chars[..width.saturating_sub(1)]);
+            line.push('~');
+        } else {
+            line.extend(&chars);
+            line.push_str(&" ".repeat(width - chars.len()));
+        }
+    }
+    line.trim_end().to_string()
+}
+
+/// PDF string literal; Latin-1 characters are octal escapes (WinAnsiEncoding), anything else becomes '?'
+fn pdf_string(text: &str) -> String {
+    let mut out = String::from("(");
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            ' '..='~' => out.push(c),
+            '\u{a0}'..='\u{ff}' => {
+                let _ = write!(out, "\\{:03o}", c as u32);
+            }
+            _ => out.push('?'),
+        }
+    }
+    out.push(')');
+    out
+}
+
+fn write_document(pages: &[Vec<String>]) -> Vec<u8> {
+    // 1 catalog, 2 page tree, 3 font, then a page and its content stream per page
+    let page_ids: Vec<usize> = (0..pages.len()).map(|i| 4 + 2 * i).collect();
+    let mut objects: Vec<String> = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            page_ids.iter().map(|id| format!("{} 0 R", id)).collect::<Vec<_>>().join(" "),
+            pages.len()
+        ),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Courier /Encoding /WinAnsiEncoding >>".to_string(),
+    ];
+    for (lines, page_id) in pages.iter().zip(&page_ids) {
+        let mut content = format!(
+            "BT /F1 {} Tf {} TL {} {} Td\n",
+            FONT_SIZE,
+            LEADING,
+            MARGIN,
+            PAGE_HEIGHT - MARGIN - FONT_SIZE
+        );
+        for line in lines {
+            let _ = writeln!(content, "{} Tj T*", pdf_string(line));
+        }
+        content.push_str("ET");
+
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+            PAGE_WIDTH,
+            PAGE_HEIGHT,
+            page_id + 1
+        ));
+        objects.push(format!("<< /Length {} >>\nstream\n{}\nendstream", content.len(), content));
+    }
+
+    let mut out = String::from("%PDF-1.4\n");
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(out.len());
+        let _ = write!(out, "{} 0 obj\n{}\nendobj\n", i + 1, object);
+    }
+    let xref = out.len();
+    let _ = write!(out, "xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        let _ = writeln!(out, "{:010} 00000 n ", offset);
+    }
+    let _ = write!(out, "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n", objects.len() + 1, xref);
+    out.into_bytes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_pages_and_escaping() {
+        assert_eq!(pdf_string("a (b) \\ é €"), "(a \\(b\\) \\\\ \\351 ?)");
+
+        let columns = vec!["id".to_string(), "name".to_string()];
+        let rows: Vec<Vec<String>> = (0..120).map(|i| vec![i.to_string(), format!("row {}", i)]).collect();
+        let pdf = String::from_utf8(render("Issues", &columns, &rows)).unwrap();
+        assert!(pdf.starts_with("%PDF-1.4\n") && pdf.ends_with("%%EOF\n"));
+        let pages = rows.len().div_ceil(LINES_PER_PAGE - 4);
+        assert!(pdf.contains(&format!("/Count {} ", pages)));
+
+        // The xref offset points at the xref table
+        let startxref: usize = pdf.rsplit("startxref\n").next().unwrap().lines().next().unwrap().parse().unwrap();
+        assert!(pdf[startxref..].starts_with("xref\n"));
+    }
+}
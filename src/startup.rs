@@ -1,12 +1,13 @@
 use axum::{
-    extract::Request,
+    extract::{DefaultBodyLimit, Request},
     http::{header, HeaderValue, Method, StatusCode},
     middleware::{self, Next},
     response::{IntoResponse, Response, Html},
-    routing::{delete, get, post, put},
+    routing::{delete, get, patch, post, put},
     Json, Router,
 };
 use subtle::ConstantTimeEq;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use tower_http::cors::CorsLayer;
 use tower_http::trace::{DefaultMakeSpan, DefaultOnRequest, DefaultOnResponse, TraceLayer};
@@ -15,17 +16,70 @@ use tracing::Level;
 use utoipa::OpenApi;
 
 use crate::{
+    config::{ListenAddress, TlsFiles},
     handlers,
-    middleware::{metrics_middleware, request_id_middleware},
+    middleware::{
+        access_log_middleware, conditional_get_middleware, metrics_middleware, request_id_middleware,
+        require_auth_middleware,
+    },
     openapi::ApiDoc,
 };
 
+/// Serve the router on a TCP address (HTTPS when TLS files are given) or a Unix socket until the process exits
+pub async fn serve(app: Router, listen: &ListenAddress, tls: Option<&TlsFiles>) -> std::io::Result<()> {
+    match (listen, tls) {
+        (ListenAddress::Tcp(addr), None) => {
+            let listener = tokio::net::TcpListener::bind(addr).await?;
+            tracing::info!("🚀 Server listening on http://{}", listener.local_addr()?);
+
+            // Connect info gives the auth extractor the peer address when no proxy sets X-Forwarded-For
+            axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await
+        }
+        (ListenAddress::Tcp(addr), Some(tls)) => {
+            // Only ring is compiled in; installing fails harmlessly if a provider is already set
+            let _ = rustls::crypto::ring::default_provider().install_default();
+            let config = axum_server::tls_rustls::RustlsConfig::from_pem_file(&tls.cert_path, &tls.key_path).await?;
+            tracing::info!("🔒 Server listening on https://{}", addr);
+
+            axum_server::bind_rustls(*addr, config)
+                .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+                .await
+        }
+        #[cfg(unix)]
+        (ListenAddress::Unix(path), _) => {
+            use std::os::unix::fs::FileTypeExt;
+
+            // A socket left by a previous run would make bind fail; anything else at the path is left alone
+            if std::fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_socket()) {
+                std::fs::remove_file(path)?;
+            }
+            let listener = tokio::net::UnixListener::bind(path)?;
+            tracing::info!("🚀 Server listening on unix:{}", path.display());
+
+            axum::serve(listener, app.into_make_service()).await
+        }
+        #[cfg(not(unix))]
+        (ListenAddress::Unix(_), _) => Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "UNIX_SOCKET_PATH is only supported on Unix",
+        )),
+    }
+}
+
 pub fn build_router(state: Arc<crate::AppState>) -> Router {
     // CORS configuration
     let cors = CorsLayer::new()
         .allow_origin("http://localhost:3000".parse::<HeaderValue>().unwrap())
-        .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
-        .allow_headers([header::CONTENT_TYPE, header::AUTHORIZATION, header::ACCEPT])
+        .allow_methods([Method::GET, Method::HEAD, Method::POST, Method::PUT, Method::PATCH, Method::DELETE])
+        .allow_headers([
+            header::CONTENT_TYPE,
+            header::AUTHORIZATION,
+            header::ACCEPT,
+            header::IF_NONE_MATCH,
+            header::HeaderName::from_static(handlers::mfa_handler::MFA_HEADER),
+            header::HeaderName::from_static(handlers::mfa_handler::PASSKEY_HEADER),
+        ])
+        .expose_headers([header::HeaderName::from_static(handlers::audit_handler::TRUNCATED_HEADER)])
         .allow_credentials(true);
 
     // Create middleware closure for debug key protection
@@ -34,7 +88,7 @@ pub fn build_router(state: Arc<crate::AppState>) -> Router {
         let debug_key = debug_key.clone();
         async move {
             let path = req.uri().path();
-            if path == "/metrics" || path == "/debug" {
+            if path == "/metrics" || path == "/debug" || path.starts_with("/api/debug/") {
                 // Check for X-Debug-Key header
                 if let Some(provided_key) = req.headers().get("X-Debug-Key").and_then(|v| v.to_str().ok()) {
                     // Constant-time comparison
@@ -52,7 +106,16 @@ pub fn build_router(state: Arc<crate::AppState>) -> Router {
     // Auth routes
     let auth_routes = Router::new()
         .route("/me", get(handlers::auth_handler::get_me))
-        .route("/verify-pin", post(handlers::auth_handler::verify_pin));
+        .route("/verify-pin", post(handlers::auth_handler::verify_pin))
+        .route("/mfa", get(handlers::mfa_handler::get_mfa_status).delete(handlers::mfa_handler::disable_mfa))
+        .route("/mfa/enroll", post(handlers::mfa_handler::enroll_mfa))
+        .route("/mfa/confirm", post(handlers::mfa_handler::confirm_mfa))
+        .route("/passkeys", get(handlers::passkeys_handler::list_passkeys))
+        .route("/passkeys/register/options", post(handlers::passkeys_handler::passkey_registration_options))
+        .route("/passkeys/register", post(handlers::passkeys_handler::register_passkey))
+        .route("/passkeys/verify/options", post(handlers::passkeys_handler::passkey_verification_options))
+        .route("/passkeys/verify", post(handlers::passkeys_handler::verify_passkey))
+        .route("/passkeys/{id}", delete(handlers::passkeys_handler::delete_passkey));
 
     // Reference routes
     let reference_routes = Router::new().route(
@@ -66,7 +129,15 @@ pub fn build_router(state: Arc<crate::AppState>) -> Router {
         .route("/", post(handlers::roles_handler::create_role))
         .route("/{id}", put(handlers::roles_handler::update_role))
         .route("/{id}", delete(handlers::roles_handler::delete_role))
+        .route("/{id}/display-token", post(handlers::roles_handler::create_display_token))
         .route("/{id}/dependencies", get(handlers::roles_handler::get_role_dependencies))
+        .route("/assignment-drift", get(handlers::assignment_drift_handler::get_assignment_drift))
+        .route("/{id}/assignment-drift/fix", post(handlers::assignment_drift_handler::fix_assignment_drift))
+        .route("/{id}/stats", get(handlers::roles_handler::get_role_stats))
+        .route("/{id}/pay-rules", get(handlers::pay_rules_handler::get_pay_rules))
+        .route("/{id}/pay-rules", put(handlers::pay_rules_handler::update_pay_rules))
+        .route("/{id}/costs", get(handlers::pay_rules_handler::get_role_costs))
+        .route("/{id}/attendance", get(handlers::attendance_handler::get_attendance_report))
         .route("/{id}/nuke", delete(handlers::roles_handler::nuke_role));
 
     // Workplace routes
@@ -75,13 +146,20 @@ pub fn build_router(state: Arc<crate::AppState>) -> Router {
         .route("/", post(handlers::workplaces_handler::create_workplace))
         .route("/{id}", put(handlers::workplaces_handler::update_workplace))
         .route("/{id}", delete(handlers::workplaces_handler::delete_workplace))
+        .route("/{id}/settings", get(handlers::workplaces_handler::get_workplace_settings))
+        .route("/{id}/settings", put(handlers::workplaces_handler::update_workplace_settings))
         .route("/{id}/dependencies", get(handlers::workplaces_handler::get_workplace_dependencies))
+        .route("/{id}/custom-fields", get(handlers::custom_fields_handler::get_custom_fields))
+        .route("/{id}/custom-fields", post(handlers::custom_fields_handler::create_custom_field))
+        .route("/{id}/custom-fields/{field_id}", put(handlers::custom_fields_handler::update_custom_field))
+        .route("/{id}/custom-fields/{field_id}", delete(handlers::custom_fields_handler::delete_custom_field))
         .route("/{id}/nuke", delete(handlers::workplaces_handler::nuke_workplace));
 
     // User Role routes
     let user_role_routes = Router::new()
         .route("/", get(handlers::user_roles_handler::get_user_roles))
         .route("/", post(handlers::user_roles_handler::create_user_role))
+        .route("/bulk", patch(handlers::user_roles_handler::bulk_update_user_roles))
         .route("/{id}", put(handlers::user_roles_handler::update_user_role))
         .route("/{id}", delete(handlers::user_roles_handler::delete_user_role));
 
@@ -91,57 +169,142 @@ pub fn build_router(state: Arc<crate::AppState>) -> Router {
         .route("/me", put(handlers::users_handler::update_own_profile))
         .route("/me/pin", post(handlers::users_handler::change_own_pin))
         .route("/me/password", post(handlers::users_handler::change_own_password))
+        .route("/me/logins", get(handlers::audit_handler::get_my_logins))
+        .route("/me/calendar", get(handlers::dashboard_handler::get_my_calendar))
+        .route("/me/export", get(handlers::data_export_handler::get_data_exports))
+        .route("/me/export", post(handlers::data_export_handler::request_data_export))
+        .route("/me/export/{token}", get(handlers::data_export_handler::download_data_export))
+        .route("/me/saved-views", get(handlers::saved_views_handler::get_saved_views))
+        .route("/me/saved-views", post(handlers::saved_views_handler::create_saved_view))
+        .route("/me/saved-views/shared/{token}", get(handlers::saved_views_handler::get_shared_view))
+        .route("/me/saved-views/shared/{token}/copy", post(handlers::saved_views_handler::copy_shared_view))
+        .route("/me/saved-views/{id}", put(handlers::saved_views_handler::update_saved_view))
+        .route("/me/saved-views/{id}", delete(handlers::saved_views_handler::delete_saved_view))
+        .route("/me/saved-views/{id}/share", post(handlers::saved_views_handler::share_saved_view))
+        .route("/me/saved-views/{id}/share", delete(handlers::saved_views_handler::unshare_saved_view))
         .route("/substantive", get(handlers::users_handler::get_substantive_users))
         .route("/locum", post(handlers::users_handler::get_locum_users))
         .route("/staff-list", get(handlers::users_handler::get_staff_list))
         // New Phase B endpoints - must come before /{id} to prevent route shadowing
         .route("/search", post(handlers::users_handler::search_users))
         .route("/profiles", post(handlers::users_handler::create_user_profile))
+        .route("/import", post(handlers::user_import_handler::import_users))
+        .route("/export", get(handlers::user_export_handler::export_users))
         .route("/check-email", post(handlers::users_handler::check_email_usage))
         .route("/verify-identity", post(handlers::users_handler::verify_profile_identity))
+        .route("/verify-identity/email", post(handlers::users_handler::request_email_verification))
+        .route("/verify-identity/email/confirm", post(handlers::users_handler::confirm_email_verification))
         .route("/change-profile-pin", post(handlers::users_handler::change_profile_pin))
         .route("/create-login", post(handlers::users_handler::create_login))
+        .route("/unlinked", get(handlers::unlinked_users_handler::get_unlinked_profiles))
+        .route("/clerk-orphans", get(handlers::unlinked_users_handler::get_clerk_orphans))
+        .route("/clerk-orphans/prune", post(handlers::unlinked_users_handler::prune_rollback_failures))
+        .route("/unlinked/{id}/invite", post(handlers::unlinked_users_handler::invite_unlinked_profile))
+        .route("/unlinked/{id}/deactivate", post(handlers::unlinked_users_handler::deactivate_unlinked_profile))
         // Existing routes
         .route("/profiles/{id}", put(handlers::users_handler::update_user_profile))
         .route("/{id}/reset-pin", post(handlers::users_handler::reset_user_pin))
-        .route("/{id}", get(handlers::users_handler::get_user));
+        .route("/{id}/shift-changes", get(handlers::audit_handler::get_user_shift_changes))
+        .route("/{id}/role-history", get(handlers::audit_handler::get_user_role_history))
+        .route("/{id}", get(handlers::users_handler::get_user))
+        .route_layer(middleware::from_fn_with_state(state.clone(), access_log_middleware))
+        .route_layer(middleware::from_fn(conditional_get_middleware));
 
     // Shift routes
     let shift_routes = Router::new()
         .route("/", get(handlers::shifts_handler::get_shifts_for_month))
         .route("/", post(handlers::shifts_handler::create_shift))
+        .route("/series", post(handlers::shifts_handler::create_shift_series))
         .route("/by-date", get(handlers::shifts_handler::get_shifts_for_date))
         .route("/range", get(handlers::shifts_handler::get_shifts_for_range))
+        .route("/locks", get(handlers::payroll_locks_handler::get_locks))
+        .route("/lock", post(handlers::payroll_locks_handler::lock_period))
+        .route("/unlock", post(handlers::payroll_locks_handler::unlock_period))
         .route("/{uuid}", put(handlers::shifts_handler::update_shift))
-        .route("/{uuid}", delete(handlers::shifts_handler::delete_shift));
+        .route("/{uuid}", delete(handlers::shifts_handler::delete_shift))
+        .route("/{uuid}/check-in", post(handlers::attendance_handler::check_in))
+        .route("/{uuid}/check-out", post(handlers::attendance_handler::check_out))
+        .route(
+            "/{uuid}/attachments",
+            get(handlers::attachments_handler::get_shift_attachments)
+                .post(handlers::attachments_handler::upload_shift_attachment)
+                .layer(DefaultBodyLimit::max(handlers::attachments_handler::MAX_ATTACHMENT_BYTES)),
+        )
+        .route("/{uuid}/attachments/{attachment_id}", delete(handlers::attachments_handler::delete_shift_attachment))
+        .route_layer(middleware::from_fn(conditional_get_middleware));
+
+    // Rota routes
+    let rota_routes = Router::new()
+        .route("/", get(handlers::rota_handler::get_rota))
+        .route("/diff", get(handlers::rota_handler::get_rota_diff))
+        .route("/approvals", get(handlers::rota_approval_handler::get_rota_approvals))
+        .route("/approvals", post(handlers::rota_approval_handler::submit_rota_approval))
+        .route("/approvals/{id}/decision", post(handlers::rota_approval_handler::decide_rota_approval))
+        .route_layer(middleware::from_fn(conditional_get_middleware));
 
     // Template routes
     let template_routes = Router::new()
         .route("/", get(handlers::templates_handler::get_templates))
         .route("/", post(handlers::templates_handler::create_template))
+        .route("/auto-fill", post(handlers::templates_handler::auto_fill))
         .route("/{id}", put(handlers::templates_handler::update_template))
-        .route("/{id}", delete(handlers::templates_handler::delete_template));
+        .route("/{id}", delete(handlers::templates_handler::delete_template))
+        .route("/{id}/usage", get(handlers::templates_handler::get_template_usage));
+
+    // Rota pattern routes
+    let pattern_routes = Router::new()
+        .route("/", get(handlers::patterns_handler::get_patterns))
+        .route("/", post(handlers::patterns_handler::create_pattern))
+        .route("/{id}", put(handlers::patterns_handler::update_pattern))
+        .route("/{id}", delete(handlers::patterns_handler::delete_pattern))
+        .route("/{id}/apply", post(handlers::patterns_handler::apply_pattern));
 
     // Diary routes
     let diary_routes = Router::new()
         .route("/", get(handlers::diary_handler::get_diary))
         .route("/", post(handlers::diary_handler::create_diary_entry))
-        .route("/{id}", delete(handlers::diary_handler::delete_diary_entry));
+        .route("/{id}", delete(handlers::diary_handler::delete_diary_entry))
+        .route(
+            "/{id}/attachments",
+            get(handlers::attachments_handler::get_diary_attachments)
+                .post(handlers::attachments_handler::upload_diary_attachment)
+                .layer(DefaultBodyLimit::max(handlers::attachments_handler::MAX_ATTACHMENT_BYTES)),
+        )
+        .route("/{id}/attachments/{attachment_id}", delete(handlers::attachments_handler::delete_diary_attachment))
+        .route_layer(middleware::from_fn_with_state(state.clone(), access_log_middleware));
 
     // Comments routes
     let comments_routes = Router::new().route("/", get(handlers::comments_handler::get_comments));
 
     // Audit routes
-    let audit_routes = Router::new().route("/", get(handlers::audit_handler::get_audit));
+    let audit_routes = Router::new()
+        .route("/", get(handlers::audit_handler::get_audit))
+        .route("/access-log", get(handlers::audit_handler::get_access_log))
+        .route("/logins", get(handlers::audit_handler::get_login_audit))
+        .route("/user-roles", get(handlers::audit_handler::get_user_role_audit))
+        .route("/retention", get(handlers::audit_handler::get_retention_report))
+        .route("/backfill", post(handlers::audit_handler::backfill_audit));
 
     // Job Plans routes
     let job_plans_routes = Router::new()
         .route("/", get(handlers::job_plans_handler::get_job_plans))
+        .route("/issues", get(handlers::job_plans_handler::get_job_plan_issues))
+        .route("/templates", get(handlers::job_plans_handler::get_job_plan_templates))
+        .route("/templates", post(handlers::job_plans_handler::create_job_plan_template))
+        .route("/templates/{id}", put(handlers::job_plans_handler::update_job_plan_template))
+        .route("/templates/{id}", delete(handlers::job_plans_handler::delete_job_plan_template))
+        .route("/from-template", post(handlers::job_plans_handler::create_job_plan_from_template))
         .route("/", post(handlers::job_plans_handler::create_job_plan))
         .route("/{id}", put(handlers::job_plans_handler::update_job_plan))
         .route("/{id}", delete(handlers::job_plans_handler::delete_job_plan))
         .route("/{id}/terminate", post(handlers::job_plans_handler::terminate_job_plan));
 
+    // Shift statistics routes (precomputed views)
+    let shift_stats_routes = Router::new()
+        .route("/users", get(handlers::shift_stats_handler::get_user_month_stats))
+        .route("/roles", get(handlers::shift_stats_handler::get_role_month_stats))
+        .route("/refresh", post(handlers::shift_stats_handler::refresh_stats));
+
     // Marketplace routes
     let marketplace_routes = Router::new()
         .route("/open", get(handlers::marketplace_handler::get_open_requests))
@@ -149,18 +312,88 @@ pub fn build_router(state: Arc<crate::AppState>) -> Router {
         .route("/incoming", get(handlers::marketplace_handler::get_incoming_requests))
         .route("/approvals", get(handlers::marketplace_handler::get_approval_requests))
         .route("/dashboard", get(handlers::marketplace_handler::get_dashboard))
+        .route("/sla", get(handlers::marketplace_sla_handler::get_sla_report))
+        .route("/poll", get(handlers::marketplace_poll_handler::poll_marketplace))
+        .route("/reasons", get(handlers::marketplace_reasons_handler::get_reasons))
+        .route("/reasons", post(handlers::marketplace_reasons_handler::create_reason))
+        .route("/reasons/{code}", put(handlers::marketplace_reasons_handler::update_reason))
         .route("/swappable", get(handlers::marketplace_handler::get_swappable_shifts))
+        .route("/validate-swap", post(handlers::marketplace_handler::validate_swap))
         .route("/requests", post(handlers::marketplace_handler::create_shift_request))
+        .route("/requests/by-token/{token}", get(handlers::marketplace_handler::get_shift_request_by_token))
+        .route("/requests/{id}/offers", get(handlers::marketplace_handler::get_offer_recipients))
         .route("/requests/{id}/accept", post(handlers::marketplace_handler::accept_shift_request))
+        .route("/requests/{id}/bump", post(handlers::marketplace_handler::bump_shift_request))
+        .route("/requests/{id}/withdraw", post(handlers::marketplace_handler::withdraw_shift_request))
         .route("/requests/{id}/respond", post(handlers::marketplace_handler::respond_to_proposal))
         .route("/requests/{id}/admin-decision", post(handlers::marketplace_handler::admin_decision))
-        .route("/requests/{id}", delete(handlers::marketplace_handler::cancel_shift_request));
+        .route("/requests/{id}", delete(handlers::marketplace_handler::cancel_shift_request))
+        .route("/requests/{id}/force-cancel", post(handlers::marketplace_handler::force_cancel_shift_request))
+        .route("/availability", get(handlers::locum_availability_handler::get_availability))
+        .route("/availability", post(handlers::locum_availability_handler::create_availability))
+        .route("/availability/{id}", delete(handlers::locum_availability_handler::delete_availability))
+        .route("/availability/{id}/assign", post(handlers::locum_availability_handler::assign_locum))
+        .route("/delegations", get(handlers::delegations_handler::get_delegations))
+        .route("/delegations", post(handlers::delegations_handler::create_delegation))
+        .route("/delegations/{id}", delete(handlers::delegations_handler::delete_delegation));
+
+    // Wall display routes (display token in query, no session)
+    let display_routes = Router::new().route("/rota", get(handlers::rota_handler::get_display_rota));
+
+    // Feature flag routes
+    let feature_routes = Router::new()
+        .route("/", get(handlers::features_handler::get_features))
+        .route(
+            "/flags",
+            get(handlers::features_handler::get_feature_flags).post(handlers::features_handler::create_feature_flag),
+        )
+        .route(
+            "/flags/{key}",
+            put(handlers::features_handler::update_feature_flag).delete(handlers::features_handler::delete_feature_flag),
+        );
+
+    // Cover board routes
+    let cover_board_routes = Router::new()
+        .route("/", get(handlers::cover_board_handler::get_cover_board))
+        .route("/{uuid}", put(handlers::cover_board_handler::set_needs_cover))
+        .route("/{uuid}/volunteer", post(handlers::cover_board_handler::volunteer_for_cover));
+
+    // Notification routes
+    let notification_routes = Router::new()
+        .route("/", get(handlers::notifications_handler::get_my_notifications))
+        .route(
+            "/preferences",
+            get(handlers::notifications_handler::get_notification_preferences)
+                .put(handlers::notifications_handler::update_notification_preferences),
+        )
+        .route("/{id}/read", post(handlers::notifications_handler::mark_notification_read));
+
+    // Announcement routes
+    let announcement_routes = Router::new()
+        .route(
+            "/",
+            get(handlers::announcements_handler::get_announcements).post(handlers::announcements_handler::create_announcement),
+        )
+        .route(
+            "/{id}",
+            put(handlers::announcements_handler::update_announcement).delete(handlers::announcements_handler::delete_announcement),
+        )
+        .route("/{id}/read", post(handlers::announcements_handler::mark_announcement_read));
 
     Router::new()
         .route("/health", get(handlers::health_check))
+        .route("/api/version", get(handlers::version_handler))
         // Protected routes (require DEBUG_KEY header)
         .route("/metrics", get(handlers::metrics_handler))
         .route("/debug", get(handlers::debug_handler))
+        .route("/api/debug/schema", get(handlers::schema_handler))
+        .route("/api/debug/permission-matrix", get(handlers::permission_matrix_handler))
+        .route("/api/dashboard", get(handlers::dashboard_handler::get_dashboard))
+        .route(
+            "/api/directory",
+            get(handlers::directory_handler::get_directory)
+                .route_layer(middleware::from_fn_with_state(state.clone(), access_log_middleware)),
+        )
         .nest("/api/auth", auth_routes)
         .nest("/api/references", reference_routes)
         .nest("/api/roles", role_routes)
@@ -168,16 +401,28 @@ pub fn build_router(state: Arc<crate::AppState>) -> Router {
         .nest("/api/user-roles", user_role_routes)
         .nest("/api/users", user_routes)
         .nest("/api/shifts", shift_routes)
+        .nest("/api/rota", rota_routes)
+        .nest("/api/display", display_routes)
         .nest("/api/templates", template_routes)
+        .nest("/api/patterns", pattern_routes)
         .nest("/api/diary", diary_routes)
         .nest("/api/comments", comments_routes)
         .nest("/api/audit", audit_routes)
         .nest("/api/job-plans", job_plans_routes)
         .nest("/api/marketplace", marketplace_routes)
+        .nest("/api/cover-board", cover_board_routes)
+        .nest("/api/shift-stats", shift_stats_routes)
+        .route("/api/absences", post(handlers::absences_handler::report_absence))
+        .nest("/api/features", feature_routes)
+        .route("/api/search", get(handlers::search_handler::search))
+        .nest("/api/notifications", notification_routes)
+        .nest("/api/announcements", announcement_routes)
         .route("/api-docs/openapi.json", get(|| async { Json(ApiDoc::openapi()) }))
         .route("/swagger-ui", get(swagger_ui))
+        // Every route above needs a session unless listed in require_auth::PUBLIC_ROUTES
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_auth_middleware))
         .with_state(state)
-        // Apply secret auth middleware to /metrics and /debug routes
+        // Apply secret auth middleware to /metrics, /debug and /api/debug/* routes
         .layer(middleware::from_fn(debug_auth_middleware))
         // Add metrics collection middleware
         .layer(middleware::from_fn(metrics_middleware))